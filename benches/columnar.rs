@@ -0,0 +1,74 @@
+//! Compares [`execute_columnar`] against calling [`execute`] for every row.
+//!
+//! Run with `cargo bench --bench columnar`.
+
+use std::{collections::HashMap, hint::black_box, time::Instant};
+
+use slac::{
+    compile, execute, execute_columnar, optimize, stdlib::extend_environment, Result,
+    StaticEnvironment, Value,
+};
+
+const ROWS: usize = 1_000_000;
+const SCRIPT: &str = "price * amount * (1 + 19 / 100) > max(100, 50 * 2) and not (name = 'test')";
+
+#[allow(clippy::cast_precision_loss)]
+fn columns() -> HashMap<String, Vec<Value>> {
+    HashMap::from([
+        (
+            String::from("price"),
+            (0..ROWS).map(|i| Value::Number((i % 100) as f64)).collect(),
+        ),
+        (
+            String::from("amount"),
+            (0..ROWS).map(|i| Value::Number((i % 7) as f64)).collect(),
+        ),
+        (
+            String::from("name"),
+            (0..ROWS)
+                .map(|i| Value::String(format!("name_{}", i % 10)))
+                .collect(),
+        ),
+    ])
+}
+
+fn naive(env: &mut StaticEnvironment, columns: &HashMap<String, Vec<Value>>) -> Result<Vec<Value>> {
+    let mut ast = compile(SCRIPT)?;
+    optimize(env, &mut ast)?;
+
+    (0..ROWS)
+        .map(|row| {
+            for (name, values) in columns {
+                env.add_variable(name, values[row].clone());
+            }
+
+            execute(env, &ast)
+        })
+        .collect()
+}
+
+fn columnar(env: &StaticEnvironment, columns: &HashMap<String, Vec<Value>>) -> Result<Vec<Value>> {
+    let ast = compile(SCRIPT)?;
+
+    execute_columnar(env, &ast, columns)
+}
+
+fn main() -> Result<()> {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    let columns = columns();
+
+    let start = Instant::now();
+    let naive_result = black_box(naive(&mut env, &columns)?);
+    println!("naive loop:       {:?}", start.elapsed());
+
+    env.clear_variables();
+
+    let start = Instant::now();
+    let columnar_result = black_box(columnar(&env, &columns)?);
+    println!("execute_columnar: {:?}", start.elapsed());
+
+    assert_eq!(naive_result, columnar_result);
+
+    Ok(())
+}
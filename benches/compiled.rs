@@ -0,0 +1,75 @@
+//! Compares the execution of an `Expression` with its `CompiledProgram`, when
+//! filtering many rows by the same rule.
+//!
+//! Run with `cargo bench --bench compiled`.
+
+use std::{hint::black_box, time::Instant};
+
+use slac::{
+    compile, execute, execute_compiled, stdlib::extend_environment, CompiledProgram, Result,
+    StaticEnvironment, Value,
+};
+
+const ROWS: usize = 21;
+const ITERATIONS: usize = 100_000;
+const RULE: &str = "status = 'active' and (amount * 1.19 > 100 or contains(tags, 'vip')) \
+                    and not (missing_discount <> 0)";
+
+/// A deterministic row, every third row is active.
+fn row(index: usize) -> StaticEnvironment {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let status = if index % 3 == 0 { "active" } else { "closed" };
+    let tags = if index % 7 == 0 { vec!["vip"] } else { vec![] };
+
+    env.add_variable("status", Value::String(String::from(status)));
+    env.add_variable("amount", Value::Number((index * 10) as f64));
+    env.add_variable(
+        "tags",
+        Value::Array(
+            tags.into_iter()
+                .map(|t| Value::String(t.to_string()))
+                .collect(),
+        ),
+    );
+    env
+}
+
+fn main() -> Result<()> {
+    let rows: Vec<StaticEnvironment> = (0..ROWS).map(row).collect();
+    let ast = compile(RULE)?;
+    let program = CompiledProgram::from(&ast);
+
+    let start = Instant::now();
+    let mut tree_matches = 0;
+    for _ in 0..ITERATIONS {
+        for env in &rows {
+            if black_box(execute(env, &ast)?) == Value::Boolean(true) {
+                tree_matches += 1;
+            }
+        }
+    }
+    println!(
+        "tree:     {:?} per row",
+        start.elapsed() / (ROWS * ITERATIONS) as u32
+    );
+
+    let start = Instant::now();
+    let mut compiled_matches = 0;
+    for _ in 0..ITERATIONS {
+        for env in &rows {
+            if black_box(execute_compiled(env, &program)?) == Value::Boolean(true) {
+                compiled_matches += 1;
+            }
+        }
+    }
+    println!(
+        "compiled: {:?} per row",
+        start.elapsed() / (ROWS * ITERATIONS) as u32
+    );
+
+    assert_eq!(tree_matches, compiled_matches);
+
+    Ok(())
+}
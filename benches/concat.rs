@@ -0,0 +1,57 @@
+//! Compares the evaluation of a long `+` chain of string variables against
+//! looking up and adding the same variables pairwise.
+//!
+//! Run with `cargo bench --bench concat`.
+
+use std::{hint::black_box, time::Instant};
+
+use slac::{compile, environment::Environment, execute, Result, StaticEnvironment, Value};
+
+const FRAGMENTS: usize = 100;
+const FRAGMENT_SIZE: usize = 1024;
+const ITERATIONS: usize = 10_000;
+
+fn fragments() -> Vec<Value> {
+    ('a'..='z')
+        .cycle()
+        .take(FRAGMENTS)
+        .map(|letter| Value::String(letter.to_string().repeat(FRAGMENT_SIZE)))
+        .collect()
+}
+
+fn pairwise(env: &StaticEnvironment, names: &[String]) -> Result<Value> {
+    names
+        .iter()
+        .filter_map(|name| env.variable(name))
+        .try_fold(Value::String(String::new()), |sum, fragment| {
+            sum + fragment.as_ref().clone()
+        })
+}
+
+fn main() -> Result<()> {
+    let names: Vec<String> = (0..FRAGMENTS).map(|i| format!("f{i}")).collect();
+    let mut env = StaticEnvironment::default();
+
+    for (name, fragment) in names.iter().zip(fragments()) {
+        env.add_variable(name, fragment);
+    }
+
+    let script = names.join(" + ");
+    let ast = compile(&script)?;
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(&pairwise(&env, &names)?);
+    }
+    println!("pairwise add: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(&execute(&env, &ast)?);
+    }
+    println!("plus chain:   {:?}", start.elapsed());
+
+    assert_eq!(pairwise(&env, &names), execute(&env, &ast));
+
+    Ok(())
+}
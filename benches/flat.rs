@@ -0,0 +1,55 @@
+//! Compares the execution of boxed `Expression` trees with their
+//! `FlatExpression` representation, for a deep and a wide AST.
+//!
+//! Run with `cargo bench --bench flat`.
+
+use std::{hint::black_box, time::Instant};
+
+use slac::{
+    compile, execute, execute_flat, Expression, FlatExpression, Result, StaticEnvironment, Value,
+};
+
+const NODES: usize = 1_000;
+const ITERATIONS: u32 = 2_000;
+
+/// A left-leaning chain of subtractions, nested `NODES` levels deep.
+fn deep() -> String {
+    let terms: Vec<String> = (0..NODES).map(|i| i.to_string()).collect();
+    format!("price - {} > 0", terms.join(" - "))
+}
+
+/// A single array with `NODES` small boolean expressions as elements.
+fn wide() -> String {
+    let terms: Vec<String> = (0..NODES)
+        .map(|i| format!("(price > {i}) and (price * {i} <> 3)"))
+        .collect();
+    format!("[{}]", terms.join(", "))
+}
+
+fn run(env: &StaticEnvironment, name: &str, ast: &Expression) -> Result<()> {
+    let flat = FlatExpression::from(ast);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(&execute(env, ast)?);
+    }
+    println!("{name} boxed: {:?} per rule", start.elapsed() / ITERATIONS);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(&execute_flat(env, &flat)?);
+    }
+    println!("{name} flat:  {:?} per rule", start.elapsed() / ITERATIONS);
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let mut env = StaticEnvironment::default();
+    env.add_variable("price", Value::Number(500.0));
+
+    run(&env, "deep", &compile(&deep())?)?;
+    run(&env, "wide", &compile(&wide())?)?;
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+//! Measures `optimize` on a large generated rule with tens of thousands of
+//! nodes, wrapped in a `try` and nested constant arithmetic.
+//!
+//! Run with `cargo bench --bench optimize`.
+
+use std::{hint::black_box, time::Instant};
+
+use slac::{compile, optimize, stdlib::extend_environment, Result, StaticEnvironment};
+
+const TERMS: usize = 2_000;
+const ITERATIONS: u32 = 10;
+
+fn script() -> String {
+    let terms: Vec<String> = (0..TERMS)
+        .map(|i| {
+            format!(
+                "if_then(price > {i} + 1 + 2 + 3, max({i}, 1 + 2) * 2, try(1 - 'a', if_then(true, {i} * 2, 0)))"
+            )
+        })
+        .collect();
+
+    format!("try(if_then(true, {}, 0), 0) > 0", terms.join(" + "))
+}
+
+fn main() -> Result<()> {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let ast = compile(&script())?;
+
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        let mut ast = ast.clone();
+        optimize(&env, &mut ast)?;
+        black_box(&ast);
+    }
+
+    println!("optimize: {:?} per rule", start.elapsed() / ITERATIONS);
+
+    Ok(())
+}
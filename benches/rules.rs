@@ -0,0 +1,43 @@
+//! Compares simple rules comparing variables against literals with the same
+//! rules comparing two variables.
+//!
+//! Run with `cargo bench --bench rules`.
+
+use std::{hint::black_box, time::Instant};
+
+use slac::{compile, execute, Result, StaticEnvironment, Value};
+
+const ITERATIONS: usize = 1_000_000;
+const LITERAL_RULE: &str = "status = 'active' and amount > 100";
+const VARIABLE_RULE: &str = "status = active and amount > limit";
+
+fn run(env: &StaticEnvironment, script: &str) -> Result<Value> {
+    let ast = compile(script)?;
+    let mut result = Value::Boolean(false);
+
+    for _ in 0..ITERATIONS {
+        result = black_box(execute(env, &ast)?);
+    }
+
+    Ok(result)
+}
+
+fn main() -> Result<()> {
+    let mut env = StaticEnvironment::default();
+    env.add_variable("status", Value::String(String::from("active")));
+    env.add_variable("amount", Value::Number(250.0));
+    env.add_variable("active", Value::String(String::from("active")));
+    env.add_variable("limit", Value::Number(100.0));
+
+    let start = Instant::now();
+    let variable_result = run(&env, VARIABLE_RULE)?;
+    println!("variable rule: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let literal_result = run(&env, LITERAL_RULE)?;
+    println!("literal rule:  {:?}", start.elapsed());
+
+    assert_eq!(variable_result, literal_result);
+
+    Ok(())
+}
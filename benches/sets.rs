@@ -0,0 +1,55 @@
+//! Compares `contains` on a variable with 100k values backed by a set with
+//! the same variable as a plain array.
+//!
+//! Run with `cargo bench --bench sets`.
+
+use std::{hint::black_box, time::Instant};
+
+use slac::{compile, execute, stdlib::extend_environment, Result, StaticEnvironment, Value};
+
+const SET_SIZE: u32 = 100_000;
+const SET_ITERATIONS: u32 = 100_000;
+const ARRAY_ITERATIONS: u32 = 1_000;
+const SCRIPT: &str = "contains(ids, needle)";
+
+fn run(env: &mut StaticEnvironment, iterations: u32) -> Result<usize> {
+    let ast = compile(SCRIPT)?;
+    let mut found = 0;
+
+    for i in 0..iterations {
+        // alternate between hits and misses at the end of the array
+        let needle = f64::from(SET_SIZE - 1 + i % 2);
+        env.add_variable("needle", Value::Number(needle));
+
+        if black_box(execute(env, &ast)?) == Value::Boolean(true) {
+            found += 1;
+        }
+    }
+
+    Ok(found)
+}
+
+fn main() -> Result<()> {
+    let ids = (0..SET_SIZE).map(|id| Value::Number(f64::from(id)));
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_set_var("ids", ids.clone());
+
+    let start = Instant::now();
+    let set_found = run(&mut env, SET_ITERATIONS)?;
+    println!("set:   {:?} per lookup", start.elapsed() / SET_ITERATIONS);
+
+    env.add_variable("ids", Value::Array(ids.collect()));
+
+    let start = Instant::now();
+    let array_found = run(&mut env, ARRAY_ITERATIONS)?;
+    println!("array: {:?} per lookup", start.elapsed() / ARRAY_ITERATIONS);
+
+    assert_eq!(
+        set_found * ARRAY_ITERATIONS as usize,
+        array_found * SET_ITERATIONS as usize
+    );
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! Compares setting up a [`StaticEnvironment`] with the cached builtins against
+//! rebuilding all stdlib function lists for every environment.
+//!
+//! Run with `cargo bench --bench setup`.
+
+use std::{hint::black_box, time::Instant};
+
+use slac::{
+    stdlib::{common, extend_environment, math, string},
+    StaticEnvironment,
+};
+
+const ENVIRONMENTS: usize = 100_000;
+
+fn rebuilt() -> StaticEnvironment {
+    let mut env = StaticEnvironment::default();
+    env.add_functions(
+        [
+            common::functions(),
+            math::functions(),
+            string::functions(),
+            #[cfg(feature = "chrono")]
+            slac::stdlib::time::functions(),
+            #[cfg(feature = "regex")]
+            slac::stdlib::regex::functions(),
+        ]
+        .concat(),
+    );
+
+    env
+}
+
+fn cached() -> StaticEnvironment {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    env
+}
+
+fn main() {
+    let start = Instant::now();
+    for _ in 0..ENVIRONMENTS {
+        black_box(rebuilt());
+    }
+    println!("rebuilt functions: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    for _ in 0..ENVIRONMENTS {
+        black_box(cached());
+    }
+    println!("cached builtins:   {:?}", start.elapsed());
+}
@@ -0,0 +1,109 @@
+//! Registers domain specific native functions next to the standard library.
+//!
+//! Shows the difference between pure and impure functions for the optimizer
+//! and how wrong parameter counts surface in the validation and at runtime.
+//!
+//! Run with `cargo run --example custom_functions`.
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use slac::function::{Arity, Function};
+use slac::prelude::*;
+use slac::stdlib::{NativeError, NativeResult};
+
+const VAT_RATE: f64 = 0.19;
+
+/// Adds the VAT to a net amount, a pure function which always returns the same
+/// result for the same parameters.
+fn gross(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(net)] => Ok(Value::Number(net * (1.0 + VAT_RATE))),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Formats an article number from a category and an optional sequence number.
+fn article_number(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(category)] => Ok(Value::String(format!("{category}-0000"))),
+        [Value::String(category), Value::Number(sequence)] => {
+            Ok(Value::String(format!("{category}-{sequence:04}")))
+        }
+        [_] | [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+// TODO: a `NativeFunction` is a plain function pointer, so the state of an
+// impure function has to live in a static instead of the registering code.
+static NEXT_TICKET: AtomicUsize = AtomicUsize::new(1);
+
+/// Draws the next ticket number, an impure function which returns a different
+/// result on every call.
+#[allow(clippy::cast_precision_loss)]
+fn next_ticket(_params: &[Value]) -> NativeResult {
+    let ticket = NEXT_TICKET.fetch_add(1, Ordering::Relaxed);
+
+    Ok(Value::Number(ticket as f64))
+}
+
+fn prepare(env: &StaticEnvironment, source: &str) -> Result<Expression> {
+    let mut ast = compile(source)?;
+    check_variables_and_functions(env, &ast)?;
+    optimize(env, &mut ast)?;
+
+    Ok(ast)
+}
+
+fn main() -> ExitCode {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    env.add_functions([
+        Function::new(gross, Arity::required(1), "gross(net: Number): Number"),
+        Function::new(
+            article_number,
+            Arity::optional(1, 1),
+            "article_number(category: String, sequence: Number = 0): String",
+        ),
+        Function::impure(next_ticket, Arity::None, "next_ticket(): Number"),
+    ]);
+
+    // pure functions with literal parameters are folded into a literal, the
+    // impure `next_ticket` is kept and called on every execution
+    for source in [
+        "round(gross(100)) + 1",
+        "article_number('TOOL', 7)",
+        "next_ticket() + gross(100)",
+    ] {
+        match prepare(&env, source) {
+            Ok(ast) => {
+                println!("{source}");
+                println!("  optimized: {ast:?}");
+                for _ in 0..2 {
+                    match execute(&env, &ast) {
+                        Ok(value) => println!("  result: {value}"),
+                        Err(error) => println!("  error: {error}"),
+                    }
+                }
+            }
+            Err(error) => println!("{source}\n  invalid: {error}"),
+        }
+    }
+
+    // a wrong parameter count is found by the validation ...
+    for source in ["gross()", "gross(1, 2)", "article_number('A', 1, 2)"] {
+        if let Err(error) = prepare(&env, source) {
+            println!("{source}\n  invalid: {error}");
+        }
+    }
+
+    // ... and by the interpreter, if the validation was skipped
+    let ast = compile("gross(1, 2)").expect("valid syntax");
+    if let Err(error) = execute(&env, &ast) {
+        println!("gross(1, 2)\n  error: {error}");
+    }
+
+    ExitCode::SUCCESS
+}
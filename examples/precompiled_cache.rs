@@ -0,0 +1,141 @@
+//! Precompiles rules once and executes them from a cache of serialized ASTs.
+//!
+//! A backend compiles, validates and optimizes the rules and stores the JSON of
+//! the resulting [`Expression`]s keyed by the rule id. A client only loads the
+//! JSON and executes it, without ever running the compiler.
+//!
+//! Run with `cargo run --release --example precompiled_cache`.
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use slac::check_ast_integrity;
+use slac::prelude::*;
+
+const RULES: [(&str, &str); 4] = [
+    (
+        "discount",
+        "if_then(total > 100 and not contains(tags, 'sale'), total * 0.1, 0)",
+    ),
+    (
+        "shipping",
+        "if_then(country = 'DE', max(0, 4.9 - total div 50), 14.9)",
+    ),
+    (
+        "label",
+        "uppercase(country) + '-' + str(round(total * 100) / 100)",
+    ),
+    (
+        "priority",
+        "any(total > 500, contains(tags, 'express'), length(tags) > 3)",
+    ),
+];
+
+const ROUNDS: u32 = 1_000;
+
+/// Compiles, validates and optimizes every rule.
+fn compile_rules(env: &StaticEnvironment) -> Result<BTreeMap<&'static str, Expression>> {
+    let mut rules = BTreeMap::new();
+
+    for (id, source) in RULES {
+        let mut ast = compile(source)?;
+        check_variables_and_functions(env, &ast)?;
+        optimize(env, &mut ast)?;
+
+        rules.insert(id, ast);
+    }
+
+    Ok(rules)
+}
+
+/// The backend, returns the JSON of the rule cache.
+fn precompile(env: &StaticEnvironment) -> Result<String> {
+    let rules = compile_rules(env)?;
+
+    Ok(serde_json::to_string(&rules).expect("serializable expressions"))
+}
+
+// TODO: parsing the JSON takes most of the time saved by skipping the
+// compiler, a more compact serialization of the `Expression` would help.
+/// The client, loads the rule cache without compiling the rules. The cache is
+/// not trusted blindly, the structure of every AST is checked once.
+fn load(json: &str) -> BTreeMap<String, Expression> {
+    let rules: BTreeMap<String, Expression> = serde_json::from_str(json).expect("valid rule cache");
+
+    for ast in rules.values() {
+        check_ast_integrity(ast).expect("intact rule");
+    }
+
+    rules
+}
+
+fn measure(name: &str, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+
+    println!("{name:<28} {elapsed:>10.2?}");
+    elapsed
+}
+
+fn main() -> ExitCode {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("total", Value::Number(0.0));
+    env.add_variable("country", Value::String(String::new()));
+    env.add_variable("tags", Value::Array(vec![]));
+
+    let json = match precompile(&env) {
+        Ok(json) => json,
+        Err(error) => {
+            println!("Error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("rule cache: {} bytes of JSON", json.len());
+
+    let rules = load(&json);
+
+    env.add_variable("total", Value::Number(149.99));
+    env.add_variable("country", Value::String(String::from("de")));
+    env.add_variable(
+        "tags",
+        Value::Array(vec![Value::String(String::from("express"))]),
+    );
+
+    for (id, ast) in &rules {
+        match execute(&env, ast) {
+            Ok(value) => println!("{id:<10} {value}"),
+            Err(error) => println!("{id:<10} error: {error}"),
+        }
+    }
+
+    // the timings are per round and include the execution of all rules
+    println!();
+    let compiled = measure("compile + optimize + execute", || {
+        for ast in compile_rules(&env).expect("valid rules").values() {
+            let _ = execute(&env, ast);
+        }
+    });
+    let cached = measure("load + execute", || {
+        for ast in load(&json).values() {
+            let _ = execute(&env, ast);
+        }
+    });
+    let prepared = measure("execute", || {
+        for ast in rules.values() {
+            let _ = execute(&env, ast);
+        }
+    });
+
+    println!();
+    println!(
+        "loading the cache is {:.1}x faster than compiling, keeping the loaded rules {:.1}x",
+        compiled.as_secs_f64() / cached.as_secs_f64(),
+        compiled.as_secs_f64() / prepared.as_secs_f64(),
+    );
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,138 @@
+//! Evaluates a set of business rules against a batch of records.
+//!
+//! The rules and records are embedded as JSON, as they would be loaded from a
+//! database or a configuration file. Every rule is compiled, validated and
+//! optimized once at startup, afterwards the records are evaluated against the
+//! prepared rules.
+//!
+//! Run with `cargo run --example rule_engine`.
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+
+use slac::prelude::*;
+use slac::DefaultCatalog;
+
+const RULES: &str = r#"[
+    {"id": "free_shipping", "source": "total >= 50 and country = 'DE'"},
+    {"id": "bulk_discount", "source": "max(quantities) >= 5 and not contains(tags, 'sale')"},
+    {"id": "suspicious", "source": "total > 1000 or length(email) = 0"},
+    {"id": "average_item", "source": "total / length(quantities) > 20"},
+    {"id": "second_item", "source": "at(quantities, 1) > 0"},
+    {"id": "typo", "source": "totl > 10"}
+]"#;
+
+const RECORDS: &str = r#"[
+    {"total": 64.5, "country": "DE", "quantities": [2, 1], "tags": [], "email": "a@example.com"},
+    {"total": 12, "country": "AT", "quantities": [8, 5], "tags": ["sale"], "email": ""},
+    {"total": 1250, "country": "DE", "quantities": [0], "tags": [], "email": "b@example.com"},
+    {"total": "n/a", "country": "FR", "quantities": [1], "tags": [], "email": "c@example.com"}
+]"#;
+
+#[derive(Debug)]
+struct Rule {
+    id: String,
+    ast: Expression,
+}
+
+/// The outcome of a single rule, as it would be returned by an API.
+enum Outcome {
+    Passed,
+    Failed,
+    /// The rule could not be evaluated, the `code` is meant for programs and
+    /// the `message` for humans.
+    Error {
+        code: &'static str,
+        message: String,
+    },
+}
+
+// TODO: `Error` has no stable code like `NativeError::key`, so the mapping
+// below has to match on the variants and will miss new ones.
+fn error_code(error: &Error) -> &'static str {
+    match error {
+        Error::NativeFunctionError(_, native) => native.key(),
+        Error::UndefinedVariable(_) | Error::MissingVariable(_) => "undefined_variable",
+        Error::InvalidBinaryOperator(_) | Error::InvalidUnaryOperator(_) => "type_mismatch",
+        _ => "internal",
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Passed => write!(f, "passed"),
+            Outcome::Failed => write!(f, "failed"),
+            Outcome::Error { code, message } => write!(f, "error {code}: {message}"),
+        }
+    }
+}
+
+impl From<Result<Value>> for Outcome {
+    fn from(result: Result<Value>) -> Self {
+        match result {
+            Ok(Value::Boolean(true)) => Outcome::Passed,
+            Ok(_) => Outcome::Failed,
+            Err(error) => Outcome::Error {
+                code: error_code(&error),
+                message: error.render_with(&DefaultCatalog),
+            },
+        }
+    }
+}
+
+/// Compiles, validates and optimizes all rules. Invalid rules are reported and
+/// skipped, so a single broken rule does not stop the others.
+fn prepare_rules(env: &StaticEnvironment, json: &str) -> Vec<Rule> {
+    let definitions: Vec<BTreeMap<String, String>> =
+        serde_json::from_str(json).expect("valid rule definitions");
+
+    let mut rules = Vec::new();
+
+    for definition in definitions {
+        let id = definition["id"].clone();
+        let prepared = compile(&definition["source"]).and_then(|mut ast| {
+            check_variables_and_functions(env, &ast)?;
+            check_boolean_result(&ast)?;
+            optimize(env, &mut ast)?;
+            Ok(ast)
+        });
+
+        match prepared {
+            Ok(ast) => rules.push(Rule { id, ast }),
+            Err(error) => println!("rule {id} is invalid: {error}"),
+        }
+    }
+
+    rules
+}
+
+fn main() -> ExitCode {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let records: Vec<BTreeMap<String, Value>> =
+        serde_json::from_str(RECORDS).expect("valid records");
+
+    // the validation needs to know the variables, so the first record acts as
+    // the schema of all records
+    for (name, value) in &records[0] {
+        env.add_variable(name, value.empty());
+    }
+
+    let rules = prepare_rules(&env, RULES);
+
+    for (index, record) in records.iter().enumerate() {
+        env.clear_variables();
+        for (name, value) in record {
+            env.add_variable(name, value.clone());
+        }
+
+        println!("record {index}:");
+        for rule in &rules {
+            let outcome = Outcome::from(execute(&env, &rule.ast));
+            println!("  {:<15} {outcome}", rule.id);
+        }
+    }
+
+    ExitCode::SUCCESS
+}
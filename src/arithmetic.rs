@@ -0,0 +1,61 @@
+//! The arithmetic of [`Value::Number`](crate::Value::Number) operands.
+//!
+//! Numbers are stored as `f64`. With the `decimal` feature the operators are
+//! calculated with a [`Decimal`] of the shortest representation of each
+//! operand, so `0.1 + 0.2` is the `f64` closest to `0.3` instead of
+//! `0.30000000000000004`. Operands without an exact decimal representation,
+//! like `1e300`, `NaN` or the infinities, and results outside the range of a
+//! [`Decimal`] are calculated with `f64`.
+
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+
+/// Converts a number into the [`Decimal`] of its shortest representation,
+/// if the [`Decimal`] converts back into the same number.
+#[cfg(feature = "decimal")]
+fn to_decimal(number: f64) -> Option<Decimal> {
+    let decimal: Decimal = number.to_string().parse().ok()?;
+
+    (to_f64(decimal)? == number).then_some(decimal)
+}
+
+/// Converts a [`Decimal`] into the closest number, unlike
+/// [`to_f64`](rust_decimal::prelude::ToPrimitive::to_f64) the result is correctly rounded.
+#[cfg(feature = "decimal")]
+fn to_f64(decimal: Decimal) -> Option<f64> {
+    decimal.to_string().parse().ok()
+}
+
+/// Calculates an operator with [`Decimal`] operands, returns `None` if an
+/// operand has no exact [`Decimal`] or if `operator` fails on an overflow or a
+/// division by zero.
+#[cfg(feature = "decimal")]
+fn decimal(lhs: f64, rhs: f64, operator: fn(Decimal, Decimal) -> Option<Decimal>) -> Option<f64> {
+    to_f64(operator(to_decimal(lhs)?, to_decimal(rhs)?)?)
+}
+
+macro_rules! generate_arithmetic_operators {
+    ($($name:ident $operator:tt $checked:ident),*) => {$(
+
+        pub(crate) fn $name(lhs: f64, rhs: f64) -> f64 {
+            let float = lhs $operator rhs;
+
+            #[cfg(feature = "decimal")]
+            if let Some(result) = decimal(lhs, rhs, Decimal::$checked) {
+                // a zero keeps the sign of the f64 result, like `-1 * 0 = -0`
+                return if result == 0.0 { 0.0_f64.copysign(float) } else { result };
+            }
+
+            float
+        }
+
+    )*};
+}
+
+generate_arithmetic_operators!(
+    add + checked_add,
+    sub - checked_sub,
+    mul * checked_mul,
+    div / checked_div,
+    rem % checked_rem
+);
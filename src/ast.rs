@@ -1,9 +1,16 @@
+use std::collections::BTreeSet;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::operator::Operator;
+use crate::scanner::Span;
 use crate::value::Value;
 
+/// The version of the JSON schema of a serialized [`Expression`], written by
+/// `serialize_expression` with the `serde` feature.
+pub const SCHEMA_VERSION: u32 = 1;
+
 /// An `Expression` is a statement which can always be evaluated to a single [`Value`].
 /// A recursive `Expression` is the foundation of an [AST](https://en.wikipedia.org/wiki/Abstract_syntax_tree).
 #[cfg_attr(
@@ -12,6 +19,7 @@ use crate::value::Value;
     serde(tag = "type", rename_all = "camelCase")
 )]
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[must_use]
 pub enum Expression {
     /// An unary operation on a single `Expression` operand using an [`Operator`]
     Unary {
@@ -31,6 +39,13 @@ pub enum Expression {
         right: Box<Expression>,
         operator: Operator,
     },
+    /// A chained comparison like `a < b <= c` of multiple `Expression` operands.
+    /// Each of the `operators` compares the neighbouring `expressions`, which
+    /// are evaluated at most once and from left to right.
+    Chain {
+        expressions: Vec<Expression>,
+        operators: Vec<Operator>,
+    },
     /// A list of `Expression` values.
     Array { expressions: Vec<Expression> },
     /// A literal [`Value`].
@@ -42,4 +57,470 @@ pub enum Expression {
         name: String,
         params: Vec<Expression>,
     },
+    /// Access to a single element of an array or character of a string like
+    /// `values[1]`, evaluated like the `at` function.
+    Index {
+        target: Box<Expression>,
+        index: Box<Expression>,
+    },
+}
+
+/// The source [`Spans`](Span) of all nodes of a compiled [`Expression`], see
+/// [`compile_with_spans`](crate::compile_with_spans).
+///
+/// The spans only fit the unmodified [`Expression`] they were compiled with,
+/// they become meaningless after e.g. [`optimize`](crate::optimize).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NodeSpans(Vec<Span>);
+
+impl NodeSpans {
+    /// Wraps the spans of all nodes in post-order.
+    pub(crate) fn new(spans: Vec<Span>) -> Self {
+        Self(spans)
+    }
+}
+
+/// Finds the innermost [`Expression`] node which encloses a character offset,
+/// e.g. the cursor position in an editor.
+///
+/// # Example
+/// ```
+/// use slac::{compile_with_spans, node_at, Expression, Value};
+///
+/// let (ast, spans) = compile_with_spans("max(1, 2 + 3)").unwrap();
+///
+/// assert!(matches!(node_at(&ast, &spans, 0), Some(Expression::Call { .. })));
+/// assert_eq!(
+///     Some(&Expression::Literal { value: Value::Number(3.0) }),
+///     node_at(&ast, &spans, 11)
+/// );
+/// assert!(matches!(node_at(&ast, &spans, 9), Some(Expression::Binary { .. })));
+/// assert_eq!(None, node_at(&ast, &spans, 13));
+/// ```
+#[must_use]
+pub fn node_at<'a>(
+    expression: &'a Expression,
+    spans: &NodeSpans,
+    offset: usize,
+) -> Option<&'a Expression> {
+    let mut found = None;
+
+    // children are visited before their parent, so the first match is the innermost node
+    visit_post_order(expression, Some(spans), &mut |node, span| {
+        if found.is_none() && span.is_some_and(|span| span.contains(offset)) {
+            found = Some(node);
+        }
+    });
+
+    found
+}
+
+/// Collects the names of all variables an [`Expression`] reads, e.g. to build
+/// an input form before the execution. The names are deduplicated and sorted,
+/// but kept as written, so `Amount` and `amount` are listed separately.
+///
+/// # Example
+/// ```
+/// use std::collections::BTreeSet;
+/// use slac::{collect_variables, compile};
+///
+/// let ast = compile("max(price, limit) > price * quantity").unwrap();
+///
+/// assert_eq!(
+///     BTreeSet::from(["limit", "price", "quantity"].map(String::from)),
+///     collect_variables(&ast)
+/// );
+/// ```
+#[must_use]
+pub fn collect_variables(expression: &Expression) -> BTreeSet<String> {
+    let mut variables = BTreeSet::new();
+
+    visit_post_order(expression, None, &mut |node, _| {
+        if let Expression::Variable { name } = node {
+            variables.insert(name.clone());
+        }
+    });
+
+    variables
+}
+
+/// Collects the names of all functions an [`Expression`] calls together with
+/// their number of parameters. A function called with different numbers of
+/// parameters is listed once for every number. The calls are deduplicated and
+/// sorted, but the names are kept as written.
+///
+/// # Example
+/// ```
+/// use std::collections::BTreeSet;
+/// use slac::{collect_function_calls, compile};
+///
+/// let ast = compile("max(1, round(price)) > max(limit)").unwrap();
+///
+/// assert_eq!(
+///     BTreeSet::from([
+///         (String::from("max"), 1),
+///         (String::from("max"), 2),
+///         (String::from("round"), 1)
+///     ]),
+///     collect_function_calls(&ast)
+/// );
+/// ```
+#[must_use]
+pub fn collect_function_calls(expression: &Expression) -> BTreeSet<(String, usize)> {
+    let mut calls = BTreeSet::new();
+
+    visit_post_order(expression, None, &mut |node, _| {
+        if let Expression::Call { name, params } = node {
+            calls.insert((name.clone(), params.len()));
+        }
+    });
+
+    calls
+}
+
+/// Visits all nodes in post-order, the order of the recorded [`NodeSpans`],
+/// together with their [`Span`] if known.
+pub(crate) fn visit_post_order<'a>(
+    expression: &'a Expression,
+    spans: Option<&NodeSpans>,
+    visit: &mut impl FnMut(&'a Expression, Option<Span>),
+) {
+    let mut spans = spans.map(|spans| spans.0.iter());
+    post_order(expression, &mut spans, visit);
+}
+
+fn post_order<'a>(
+    expression: &'a Expression,
+    spans: &mut Option<std::slice::Iter<Span>>,
+    visit: &mut impl FnMut(&'a Expression, Option<Span>),
+) {
+    let children: Vec<&Expression> = match expression {
+        Expression::Unary { right, .. } => vec![right],
+        Expression::Binary { left, right, .. } => vec![left, right],
+        Expression::Index { target, index } => vec![target, index],
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            ..
+        } => vec![left, middle, right],
+        Expression::Chain { expressions, .. }
+        | Expression::Array { expressions }
+        | Expression::Call {
+            params: expressions,
+            ..
+        } => expressions.iter().collect(),
+        Expression::Literal { .. } | Expression::Variable { .. } => vec![],
+    };
+
+    for child in children {
+        post_order(child, spans, visit);
+    }
+
+    let span = spans.as_mut().and_then(Iterator::next).copied();
+    visit(expression, span);
+}
+
+/// A contiguous range of child node indices or operators of a [`Node`] inside
+/// a [`FlatExpression`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeRange {
+    pub start: u32,
+    pub len: u32,
+}
+
+impl NodeRange {
+    fn indices(self) -> std::ops::Range<usize> {
+        let start = self.start as usize;
+        start..start + self.len as usize
+    }
+}
+
+/// A single node of a [`FlatExpression`]. Mirrors [`Expression`], but refers
+/// to its operands by their index in [`FlatExpression::nodes`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Unary {
+        right: u32,
+        operator: Operator,
+    },
+    Binary {
+        left: u32,
+        right: u32,
+        operator: Operator,
+    },
+    Ternary {
+        left: u32,
+        middle: u32,
+        right: u32,
+        operator: Operator,
+    },
+    Chain {
+        expressions: NodeRange,
+        operators: NodeRange,
+    },
+    Array {
+        expressions: NodeRange,
+    },
+    Literal {
+        value: Value,
+    },
+    Variable {
+        name: String,
+    },
+    Call {
+        name: String,
+        params: NodeRange,
+    },
+    Index {
+        target: u32,
+        index: u32,
+    },
+}
+
+/// An [`Expression`] stored as a contiguous list of [`Nodes`](Node) instead of
+/// individually boxed nodes. The whole tree lives in three allocations, which
+/// keeps its nodes close together in memory no matter how the boxed tree was
+/// built. Whether that executes faster depends on the shape of the tree, the
+/// `flat` benchmark compares both representations.
+///
+/// The nodes are stored in post-order, so every operand precedes the node
+/// using it and the root is the last node. Execute it with
+/// [`execute_flat`](crate::execute_flat).
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute_flat, Expression, FlatExpression, StaticEnvironment, Value};
+///
+/// let ast = compile("max(1, 2) + 3").unwrap();
+/// let flat = FlatExpression::from(&ast);
+///
+/// assert_eq!(5, flat.nodes().len());
+/// assert_eq!(ast, Expression::from(&flat));
+///
+/// let env = StaticEnvironment::default();
+/// assert!(execute_flat(&env, &flat).is_err()); // `max` is not part of the environment
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(from = "Expression", into = "Expression")
+)]
+#[derive(Clone, Debug, PartialEq)]
+#[must_use]
+pub struct FlatExpression {
+    nodes: Vec<Node>,
+    children: Vec<u32>,
+    operators: Vec<Operator>,
+}
+
+impl FlatExpression {
+    /// All nodes in post-order.
+    #[must_use]
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// The index of the root node.
+    #[must_use]
+    pub fn root(&self) -> u32 {
+        // a FlatExpression is only built from an Expression and never empty
+        index(self.nodes.len() - 1)
+    }
+
+    /// Gets a node by its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[must_use]
+    pub fn node(&self, index: u32) -> &Node {
+        &self.nodes[index as usize]
+    }
+
+    /// The node indices of the operands of a [`Node::Chain`], [`Node::Array`]
+    /// or [`Node::Call`].
+    #[must_use]
+    pub fn children(&self, range: NodeRange) -> &[u32] {
+        &self.children[range.indices()]
+    }
+
+    /// The comparison operators of a [`Node::Chain`].
+    #[must_use]
+    pub fn operators(&self, range: NodeRange) -> &[Operator] {
+        &self.operators[range.indices()]
+    }
+
+    fn push(&mut self, expression: &Expression) -> u32 {
+        let node = match expression {
+            Expression::Unary { right, operator } => Node::Unary {
+                right: self.push(right),
+                operator: *operator,
+            },
+            Expression::Binary {
+                left,
+                right,
+                operator,
+            } => Node::Binary {
+                left: self.push(left),
+                right: self.push(right),
+                operator: *operator,
+            },
+            Expression::Ternary {
+                left,
+                middle,
+                right,
+                operator,
+            } => Node::Ternary {
+                left: self.push(left),
+                middle: self.push(middle),
+                right: self.push(right),
+                operator: *operator,
+            },
+            Expression::Chain {
+                expressions,
+                operators,
+            } => {
+                let expressions = self.push_all(expressions);
+                let start = index(self.operators.len());
+                self.operators.extend_from_slice(operators);
+
+                Node::Chain {
+                    expressions,
+                    operators: NodeRange {
+                        start,
+                        len: index(operators.len()),
+                    },
+                }
+            }
+            Expression::Array { expressions } => Node::Array {
+                expressions: self.push_all(expressions),
+            },
+            Expression::Literal { value } => Node::Literal {
+                value: value.clone(),
+            },
+            Expression::Variable { name } => Node::Variable { name: name.clone() },
+            Expression::Call { name, params } => Node::Call {
+                name: name.clone(),
+                params: self.push_all(params),
+            },
+            Expression::Index { target, index } => Node::Index {
+                target: self.push(target),
+                index: self.push(index),
+            },
+        };
+
+        self.nodes.push(node);
+        index(self.nodes.len() - 1)
+    }
+
+    /// Pushes all expressions and stores their indices next to each other.
+    fn push_all(&mut self, expressions: &[Expression]) -> NodeRange {
+        let indices: Vec<u32> = expressions
+            .iter()
+            .map(|expression| self.push(expression))
+            .collect();
+
+        let start = index(self.children.len());
+        self.children.extend_from_slice(&indices);
+
+        NodeRange {
+            start,
+            len: index(indices.len()),
+        }
+    }
+
+    fn expression(&self, index: u32) -> Expression {
+        let boxed = |index| Box::new(self.expression(index));
+        let all = |range| {
+            self.children(range)
+                .iter()
+                .map(|i| self.expression(*i))
+                .collect()
+        };
+
+        match self.node(index) {
+            Node::Unary { right, operator } => Expression::Unary {
+                right: boxed(*right),
+                operator: *operator,
+            },
+            Node::Binary {
+                left,
+                right,
+                operator,
+            } => Expression::Binary {
+                left: boxed(*left),
+                right: boxed(*right),
+                operator: *operator,
+            },
+            Node::Ternary {
+                left,
+                middle,
+                right,
+                operator,
+            } => Expression::Ternary {
+                left: boxed(*left),
+                middle: boxed(*middle),
+                right: boxed(*right),
+                operator: *operator,
+            },
+            Node::Chain {
+                expressions,
+                operators,
+            } => Expression::Chain {
+                expressions: all(*expressions),
+                operators: self.operators(*operators).to_vec(),
+            },
+            Node::Array { expressions } => Expression::Array {
+                expressions: all(*expressions),
+            },
+            Node::Literal { value } => Expression::Literal {
+                value: value.clone(),
+            },
+            Node::Variable { name } => Expression::Variable { name: name.clone() },
+            Node::Call { name, params } => Expression::Call {
+                name: name.clone(),
+                params: all(*params),
+            },
+            Node::Index { target, index } => Expression::Index {
+                target: boxed(*target),
+                index: boxed(*index),
+            },
+        }
+    }
+}
+
+/// Converts an index into the `u32` used by a [`FlatExpression`].
+fn index(index: usize) -> u32 {
+    u32::try_from(index).expect("a FlatExpression holds at most u32::MAX nodes")
+}
+
+impl From<&Expression> for FlatExpression {
+    fn from(expression: &Expression) -> Self {
+        let mut flat = FlatExpression {
+            nodes: Vec::new(),
+            children: Vec::new(),
+            operators: Vec::new(),
+        };
+        flat.push(expression);
+        flat
+    }
+}
+
+impl From<Expression> for FlatExpression {
+    fn from(expression: Expression) -> Self {
+        Self::from(&expression)
+    }
+}
+
+impl From<&FlatExpression> for Expression {
+    fn from(flat: &FlatExpression) -> Self {
+        flat.expression(flat.root())
+    }
+}
+
+impl From<FlatExpression> for Expression {
+    fn from(flat: FlatExpression) -> Self {
+        Self::from(&flat)
+    }
 }
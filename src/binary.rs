@@ -0,0 +1,415 @@
+//! A compact binary encoding of an [`Expression`], e.g. to cache a large number
+//! of precompiled ASTs.
+//!
+//! The first byte is the [`BINARY_FORMAT_VERSION`], followed by the nodes in
+//! pre-order. Every node starts with a tag byte, lengths are unsigned LEB128
+//! integers and numbers are stored as the 8 little endian bytes of their
+//! IEEE 754 representation.
+
+use crate::{
+    ast::Expression,
+    error::{Error, Result},
+    interpreter::DEFAULT_MAX_DEPTH,
+    operator::Operator,
+    value::Value,
+};
+
+/// The version of the format written by [`to_bytes`]. [`from_bytes`] rejects
+/// data of any other version with an [`Error::UnsupportedVersion`].
+pub const BINARY_FORMAT_VERSION: u8 = 1;
+
+const UNARY: u8 = 0;
+const BINARY: u8 = 1;
+const TERNARY: u8 = 2;
+const CHAIN: u8 = 3;
+const ARRAY: u8 = 4;
+const LITERAL: u8 = 5;
+const VARIABLE: u8 = 6;
+const CALL: u8 = 7;
+const INDEX: u8 = 8;
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const NUMBER: u8 = 3;
+const STRING: u8 = 4;
+const VALUE_ARRAY: u8 = 5;
+
+/// The fixed tag of an [`Operator`], independent of its position in [`Operator::ALL`].
+fn operator_tag(operator: Operator) -> u8 {
+    match operator {
+        Operator::Plus => 0,
+        Operator::Minus => 1,
+        Operator::Multiply => 2,
+        Operator::Divide => 3,
+        Operator::Greater => 4,
+        Operator::GreaterEqual => 5,
+        Operator::Less => 6,
+        Operator::LessEqual => 7,
+        Operator::Equal => 8,
+        Operator::NotEqual => 9,
+        Operator::And => 10,
+        Operator::Or => 11,
+        Operator::Xor => 12,
+        Operator::Not => 13,
+        Operator::Div => 14,
+        Operator::Mod => 15,
+        Operator::IsEmpty => 16,
+        Operator::IsNotEmpty => 17,
+        Operator::TernaryCondition => 18,
+        Operator::Implies => 19,
+        Operator::Iff => 20,
+        Operator::In => 21,
+        Operator::ApproxEqual => 22,
+    }
+}
+
+/// Encodes an [`Expression`] into the compact binary format.
+///
+/// # Example
+/// ```
+/// use slac::{compile, from_bytes, to_bytes};
+///
+/// let ast = compile("max(price, 10) > 20 and name <> ''").unwrap();
+/// let bytes = to_bytes(&ast);
+///
+/// assert_eq!(Ok(ast), from_bytes(&bytes));
+/// ```
+#[must_use]
+pub fn to_bytes(expression: &Expression) -> Vec<u8> {
+    let mut bytes = vec![BINARY_FORMAT_VERSION];
+    write_expression(&mut bytes, expression);
+    bytes
+}
+
+/// Decodes an [`Expression`] written by [`to_bytes`].
+///
+/// # Errors
+///
+/// Returns an [`Error::UnsupportedVersion`] if the data was written in another
+/// version of the format.
+/// Returns an [`Error::InvalidBinary`] if the data is truncated, contains
+/// unknown tags or trailing bytes.
+/// Returns an [`Error::RecursionLimitExceeded`] if the nodes are nested deeper
+/// than the [`DEFAULT_MAX_DEPTH`](crate::DEFAULT_MAX_DEPTH).
+pub fn from_bytes(bytes: &[u8]) -> Result<Expression> {
+    let mut reader = Reader { bytes, position: 0 };
+
+    match reader.byte()? {
+        BINARY_FORMAT_VERSION => {}
+        version => return Err(Error::UnsupportedVersion(version)),
+    }
+
+    let expression = reader.expression(0)?;
+
+    if reader.position < bytes.len() {
+        return Err(reader.invalid("trailing bytes"));
+    }
+
+    Ok(expression)
+}
+
+fn write_length(bytes: &mut Vec<u8>, mut length: usize) {
+    loop {
+        let byte = (length & 0x7f) as u8;
+        length >>= 7;
+
+        if length == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    write_length(bytes, string.len());
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+fn write_expressions(bytes: &mut Vec<u8>, expressions: &[Expression]) {
+    write_length(bytes, expressions.len());
+    for expression in expressions {
+        write_expression(bytes, expression);
+    }
+}
+
+fn write_value(bytes: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => bytes.push(NULL),
+        Value::Boolean(false) => bytes.push(FALSE),
+        Value::Boolean(true) => bytes.push(TRUE),
+        Value::Number(number) => {
+            bytes.push(NUMBER);
+            bytes.extend_from_slice(&number.to_le_bytes());
+        }
+        Value::String(string) => {
+            bytes.push(STRING);
+            write_string(bytes, string);
+        }
+        Value::Array(values) => {
+            bytes.push(VALUE_ARRAY);
+            write_length(bytes, values.len());
+            for value in values {
+                write_value(bytes, value);
+            }
+        }
+    }
+}
+
+fn write_expression(bytes: &mut Vec<u8>, expression: &Expression) {
+    match expression {
+        Expression::Unary { right, operator } => {
+            bytes.extend([UNARY, operator_tag(*operator)]);
+            write_expression(bytes, right);
+        }
+        Expression::Binary {
+            left,
+            right,
+            operator,
+        } => {
+            bytes.extend([BINARY, operator_tag(*operator)]);
+            write_expression(bytes, left);
+            write_expression(bytes, right);
+        }
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            operator,
+        } => {
+            bytes.extend([TERNARY, operator_tag(*operator)]);
+            write_expression(bytes, left);
+            write_expression(bytes, middle);
+            write_expression(bytes, right);
+        }
+        Expression::Chain {
+            expressions,
+            operators,
+        } => {
+            bytes.push(CHAIN);
+            write_length(bytes, operators.len());
+            bytes.extend(operators.iter().map(|operator| operator_tag(*operator)));
+            write_expressions(bytes, expressions);
+        }
+        Expression::Array { expressions } => {
+            bytes.push(ARRAY);
+            write_expressions(bytes, expressions);
+        }
+        Expression::Literal { value } => {
+            bytes.push(LITERAL);
+            write_value(bytes, value);
+        }
+        Expression::Variable { name } => {
+            bytes.push(VARIABLE);
+            write_string(bytes, name);
+        }
+        Expression::Call { name, params } => {
+            bytes.push(CALL);
+            write_string(bytes, name);
+            write_expressions(bytes, params);
+        }
+        Expression::Index { target, index } => {
+            bytes.push(INDEX);
+            write_expression(bytes, target);
+            write_expression(bytes, index);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn invalid(&self, reason: &str) -> Error {
+        Error::InvalidBinary(format!("{reason} at byte {}", self.position))
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or_else(|| self.invalid("unexpected end of data"))?;
+
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn take(&mut self, length: usize) -> Result<&[u8]> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| self.invalid("unexpected end of data"))?;
+        let bytes = &self.bytes[self.position..end];
+
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Reads a length, which can not exceed the remaining bytes since every
+    /// element takes at least one byte. An invalid length fails before allocating.
+    fn length(&mut self) -> Result<usize> {
+        let mut length: u64 = 0;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            length |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return usize::try_from(length)
+                    .ok()
+                    .filter(|length| *length <= self.bytes.len() - self.position)
+                    .ok_or_else(|| self.invalid("unexpected end of data"));
+            }
+        }
+
+        Err(self.invalid("length out of range"))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let length = self.length()?;
+        let bytes = self.take(length)?.to_vec();
+
+        String::from_utf8(bytes).map_err(|_| self.invalid("invalid UTF-8"))
+    }
+
+    fn operator(&mut self) -> Result<Operator> {
+        let tag = self.byte()?;
+
+        Operator::ALL
+            .iter()
+            .copied()
+            .find(|operator| operator_tag(*operator) == tag)
+            .ok_or_else(|| self.invalid(&format!("unknown operator {tag}")))
+    }
+
+    fn boxed(&mut self, depth: usize) -> Result<Box<Expression>> {
+        Ok(Box::new(self.expression(depth)?))
+    }
+
+    fn expressions(&mut self, depth: usize) -> Result<Vec<Expression>> {
+        let length = self.length()?;
+
+        (0..length).map(|_| self.expression(depth)).collect()
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value> {
+        if depth >= DEFAULT_MAX_DEPTH {
+            return Err(Error::RecursionLimitExceeded(DEFAULT_MAX_DEPTH));
+        }
+
+        match self.byte()? {
+            NULL => Ok(Value::Null),
+            FALSE => Ok(Value::Boolean(false)),
+            TRUE => Ok(Value::Boolean(true)),
+            NUMBER => {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(self.take(8)?);
+
+                Ok(Value::Number(f64::from_le_bytes(bytes)))
+            }
+            STRING => Ok(Value::String(self.string()?)),
+            VALUE_ARRAY => {
+                let length = self.length()?;
+
+                (0..length)
+                    .map(|_| self.value(depth + 1))
+                    .collect::<Result<_>>()
+                    .map(Value::Array)
+            }
+            tag => Err(self.invalid(&format!("unknown value {tag}"))),
+        }
+    }
+
+    fn expression(&mut self, depth: usize) -> Result<Expression> {
+        if depth >= DEFAULT_MAX_DEPTH {
+            return Err(Error::RecursionLimitExceeded(DEFAULT_MAX_DEPTH));
+        }
+
+        // every kind of node is read by its own function, which keeps the
+        // stack frame of the recursion small
+        match self.byte()? {
+            UNARY => self.unary(depth + 1),
+            BINARY => self.binary(depth + 1),
+            TERNARY => self.ternary(depth + 1),
+            CHAIN => self.chain(depth + 1),
+            ARRAY => self.array(depth + 1),
+            LITERAL => self.literal(depth + 1),
+            VARIABLE => self.variable(),
+            CALL => self.call(depth + 1),
+            INDEX => self.index(depth + 1),
+            tag => Err(self.invalid(&format!("unknown node {tag}"))),
+        }
+    }
+
+    fn unary(&mut self, depth: usize) -> Result<Expression> {
+        Ok(Expression::Unary {
+            operator: self.operator()?,
+            right: self.boxed(depth)?,
+        })
+    }
+
+    fn binary(&mut self, depth: usize) -> Result<Expression> {
+        Ok(Expression::Binary {
+            operator: self.operator()?,
+            left: self.boxed(depth)?,
+            right: self.boxed(depth)?,
+        })
+    }
+
+    fn ternary(&mut self, depth: usize) -> Result<Expression> {
+        Ok(Expression::Ternary {
+            operator: self.operator()?,
+            left: self.boxed(depth)?,
+            middle: self.boxed(depth)?,
+            right: self.boxed(depth)?,
+        })
+    }
+
+    fn chain(&mut self, depth: usize) -> Result<Expression> {
+        let length = self.length()?;
+        let operators = (0..length)
+            .map(|_| self.operator())
+            .collect::<Result<_>>()?;
+
+        Ok(Expression::Chain {
+            operators,
+            expressions: self.expressions(depth)?,
+        })
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Expression> {
+        Ok(Expression::Array {
+            expressions: self.expressions(depth)?,
+        })
+    }
+
+    fn literal(&mut self, depth: usize) -> Result<Expression> {
+        Ok(Expression::Literal {
+            value: self.value(depth)?,
+        })
+    }
+
+    fn variable(&mut self) -> Result<Expression> {
+        Ok(Expression::Variable {
+            name: self.string()?,
+        })
+    }
+
+    fn call(&mut self, depth: usize) -> Result<Expression> {
+        Ok(Expression::Call {
+            name: self.string()?,
+            params: self.expressions(depth)?,
+        })
+    }
+
+    fn index(&mut self, depth: usize) -> Result<Expression> {
+        Ok(Expression::Index {
+            target: self.boxed(depth)?,
+            index: self.boxed(depth)?,
+        })
+    }
+}
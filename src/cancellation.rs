@@ -0,0 +1,43 @@
+//! Cooperative cancellation of a running execution.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A shared flag to cancel an execution started by [`execute_cancellable`](crate::execute_cancellable)
+/// from another thread. Clones of the token share the same flag.
+///
+/// # Example
+/// ```
+/// use slac::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let handle = token.clone();
+///
+/// handle.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a new token which is not cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signals all holders of the token to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks if the token was cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,219 @@
+//! A machine-readable description of all functions and variables inside an
+//! [`Environment`](crate::environment::Environment).
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    function::{Arity, Function},
+    value::Value,
+};
+
+/// The description of a registered [`Function`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionEntry {
+    pub name: String,
+    /// The minimum number of parameters.
+    pub min_params: usize,
+    /// The maximum number of parameters, `None` if the function is variadic.
+    pub max_params: Option<usize>,
+    pub pure: bool,
+    pub declaration: String,
+    /// The module providing the function, e.g. `math` for the stdlib.
+    pub module: Option<String>,
+}
+
+/// The description of a registered variable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariableEntry {
+    pub name: String,
+    /// The [`Value`] kind of the current variable value.
+    pub value_type: String,
+}
+
+/// A list of all functions and variables, sorted by their name.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Catalog {
+    pub functions: Vec<FunctionEntry>,
+    pub variables: Vec<VariableEntry>,
+}
+
+/// A single entry of a [`Catalog`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CatalogEntry {
+    Function(FunctionEntry),
+    Variable(VariableEntry),
+}
+
+/// The differences between two versions of a [`Catalog`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogDiff {
+    pub added: Vec<CatalogEntry>,
+    pub removed: Vec<CatalogEntry>,
+    /// Entries with the same name but different content as `(old, new)`.
+    pub changed: Vec<(CatalogEntry, CatalogEntry)>,
+}
+
+impl From<&Function> for FunctionEntry {
+    fn from(function: &Function) -> Self {
+        let (min_params, max_params) = match function.arity {
+            Arity::Polyadic { required, optional } => (required, Some(required + optional)),
+            Arity::Variadic => (1, None),
+            Arity::None => (0, Some(0)),
+        };
+
+        Self {
+            name: function.name.clone(),
+            min_params,
+            max_params,
+            pure: function.pure,
+            declaration: function.declaration(),
+            module: function.module.map(str::to_string),
+        }
+    }
+}
+
+impl Catalog {
+    pub(crate) fn new<'a>(
+        functions: impl Iterator<Item = &'a Function>,
+        variables: impl Iterator<Item = (&'a str, &'a Value)>,
+    ) -> Self {
+        let mut functions: Vec<FunctionEntry> = functions.map(FunctionEntry::from).collect();
+        functions.sort_by(|left, right| left.name.cmp(&right.name));
+
+        let mut variables: Vec<VariableEntry> = variables
+            .map(|(name, value)| VariableEntry {
+                name: name.to_string(),
+                value_type: value.type_name().to_string(),
+            })
+            .collect();
+        variables.sort_by(|left, right| left.name.cmp(&right.name));
+
+        Self {
+            functions,
+            variables,
+        }
+    }
+
+    fn entries(&self) -> BTreeMap<(u8, &str), CatalogEntry> {
+        let functions = self.functions.iter().map(|function| {
+            let key = (0, function.name.as_str());
+            (key, CatalogEntry::Function(function.clone()))
+        });
+        let variables = self.variables.iter().map(|variable| {
+            let key = (1, variable.name.as_str());
+            (key, CatalogEntry::Variable(variable.clone()))
+        });
+
+        functions.chain(variables).collect()
+    }
+
+    /// Lists all entries which were added, removed or changed compared to an
+    /// `old` [`Catalog`].
+    ///
+    /// # Example
+    /// ```
+    /// use slac::{stdlib::extend_environment, StaticEnvironment, Value};
+    ///
+    /// let mut env = StaticEnvironment::default();
+    /// extend_environment(&mut env);
+    /// let old = env.catalog();
+    ///
+    /// env.remove_function("max");
+    /// env.add_variable("price", Value::Number(42.0));
+    /// let diff = env.catalog().diff(&old);
+    ///
+    /// assert_eq!(1, diff.added.len());
+    /// assert_eq!(1, diff.removed.len());
+    /// assert!(diff.changed.is_empty());
+    /// ```
+    #[must_use]
+    pub fn diff(&self, old: &Catalog) -> CatalogDiff {
+        let old = old.entries();
+        let new = self.entries();
+        let mut diff = CatalogDiff::default();
+
+        for (key, entry) in &new {
+            match old.get(key) {
+                None => diff.added.push(entry.clone()),
+                Some(old_entry) if old_entry != entry => {
+                    diff.changed.push((old_entry.clone(), entry.clone()));
+                }
+                Some(_) => (),
+            }
+        }
+
+        for (key, entry) in old {
+            if !new.contains_key(&key) {
+                diff.removed.push(entry);
+            }
+        }
+
+        diff
+    }
+}
+
+impl CatalogDiff {
+    /// Checks if both [`Catalogs`](Catalog) are identical.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CatalogEntry, FunctionEntry};
+    use crate::{
+        function::{Arity, Function},
+        stdlib::NativeResult,
+        StaticEnvironment, Value,
+    };
+
+    fn test_func(_params: &[Value]) -> NativeResult {
+        unreachable!()
+    }
+
+    #[test]
+    fn function_entry() {
+        let function = Function::impure(test_func, Arity::optional(1, 2), "test(a, b, c): Any");
+
+        assert_eq!(
+            FunctionEntry {
+                name: String::from("test"),
+                min_params: 1,
+                max_params: Some(3),
+                pure: false,
+                declaration: String::from("test(a, b, c): Any"),
+                module: None,
+            },
+            FunctionEntry::from(&function)
+        );
+    }
+
+    #[test]
+    fn catalog_diff() {
+        let mut env = StaticEnvironment::default();
+        env.add_function(Function::new(test_func, Arity::Variadic, "test(...)"));
+        env.add_variable("some_var", Value::Number(42.0));
+        let old = env.catalog();
+
+        assert!(env.catalog().diff(&old).is_empty());
+
+        env.add_function(Function::new(test_func, Arity::required(1), "test(a)"));
+        env.add_variable("some_var", Value::String(String::from("42")));
+        let diff = env.catalog().diff(&old);
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(2, diff.changed.len());
+        assert!(matches!(
+            diff.changed[0],
+            (CatalogEntry::Function(_), CatalogEntry::Function(_))
+        ));
+    }
+}
@@ -0,0 +1,57 @@
+//! Conversions between [`Value::Number`] and the [`chrono`] datetime types.
+//!
+//! A datetime is a number of days since the UNIX epoch, with the time of day
+//! as fraction. See [`stdlib::time`](crate::stdlib::time) for details.
+//!
+//! The conversions are only available with the `chrono` feature.
+
+use chrono::{DateTime, NaiveDateTime};
+
+use crate::{stdlib::NativeError, Value};
+
+pub(crate) const SECONDS_PER_DAY: f64 = 24. * 60. * 60.;
+pub(crate) const MILLISECONDS_PER_DAY: f64 = SECONDS_PER_DAY * 1000.;
+
+impl TryFrom<&Value> for NaiveDateTime {
+    type Error = NativeError;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(value) => {
+                let milliseconds = (value * MILLISECONDS_PER_DAY) as i64;
+
+                DateTime::from_timestamp_millis(milliseconds)
+                    .map(|dt| dt.naive_utc())
+                    .ok_or(NativeError::keyed("err.datetime.range", []))
+            }
+            _ => Err(NativeError::WrongParameterType),
+        }
+    }
+}
+
+impl From<NaiveDateTime> for Value {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(val: NaiveDateTime) -> Self {
+        let milliseconds = val.and_utc().timestamp_millis();
+
+        Value::Number(milliseconds as f64 / MILLISECONDS_PER_DAY)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDateTime;
+
+    use crate::Value;
+
+    #[test]
+    fn datetime_to_float() {
+        let timestamp =
+            NaiveDateTime::parse_from_str("2019-07-24 18:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let time_value = Value::from(timestamp);
+
+        assert_eq!(Value::Number(18101.75), time_value);
+        assert_eq!(NaiveDateTime::try_from(&time_value).unwrap(), timestamp);
+    }
+}
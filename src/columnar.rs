@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::Expression,
+    environment::Environment,
+    error::{Error, Result},
+    optimizer::optimize,
+    value::Value,
+    vm::{CompiledProgram, VirtualMachine},
+};
+
+/// Collects the names of all [`Variable`](Expression::Variable) expressions
+/// in order of their first appearance.
+fn variable_names<'a>(expression: &'a Expression, names: &mut Vec<&'a str>) {
+    match expression {
+        Expression::Unary { right, operator: _ } => variable_names(right, names),
+        Expression::Binary {
+            left,
+            right,
+            operator: _,
+        }
+        | Expression::Index {
+            target: left,
+            index: right,
+        } => {
+            variable_names(left, names);
+            variable_names(right, names);
+        }
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            operator: _,
+        } => {
+            variable_names(left, names);
+            variable_names(middle, names);
+            variable_names(right, names);
+        }
+        Expression::Array {
+            expressions: params,
+        }
+        | Expression::Chain {
+            expressions: params,
+            operators: _,
+        }
+        | Expression::Call { name: _, params } => {
+            for expression in params {
+                variable_names(expression, names);
+            }
+        }
+        Expression::Variable { name } => {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+        Expression::Literal { value: _ } => (),
+    }
+}
+
+/// Returns the shared row count of all columns.
+fn row_count(columns: &HashMap<String, Vec<Value>>) -> Result<usize> {
+    let mut names: Vec<&String> = columns.keys().collect();
+    names.sort();
+
+    let Some(expected) = names.first().map(|name| columns[*name].len()) else {
+        return Ok(0);
+    };
+
+    for name in names {
+        let found = columns[name].len();
+
+        if found != expected {
+            return Err(Error::ColumnLengthMismatch(name.clone(), found, expected));
+        }
+    }
+
+    Ok(expected)
+}
+
+/// Finds the column of a variable. A column with the exact name is preferred,
+/// otherwise the name is matched case-insensitively.
+fn find_column<'c>(name: &str, columns: &'c HashMap<String, Vec<Value>>) -> Result<&'c [Value]> {
+    if let Some(values) = columns.get(name) {
+        return Ok(values);
+    }
+
+    let key = name.to_lowercase();
+    let mut matches = columns
+        .iter()
+        .filter(|(column, _)| column.to_lowercase() == key);
+
+    match (matches.next(), matches.next()) {
+        (Some((_, values)), None) => Ok(values),
+        (Some(_), Some(_)) => Err(Error::AmbiguousColumn(name.to_string())),
+        (None, _) => Err(Error::MissingVariable(name.to_string())),
+    }
+}
+
+/// Resolves every variable of the [`Expression`] to its column once, at the
+/// position of its name in the [`CompiledProgram`], so rows are read by index.
+/// Variables denied by the [`Environment`] keep no column and fail on access.
+fn resolve_columns<'c>(
+    env: &impl Environment,
+    expression: &Expression,
+    program: &CompiledProgram,
+    columns: &'c HashMap<String, Vec<Value>>,
+) -> Result<Vec<Option<&'c [Value]>>> {
+    let mut names = vec![];
+    variable_names(expression, &mut names);
+
+    let mut slots = vec![None; program.names().len()];
+
+    for name in names {
+        let values = find_column(name, columns)?;
+
+        if let Some(slot) = program.names().iter().position(|n| n == name) {
+            if env.variable_allowed(name) {
+                slots[slot] = Some(values);
+            }
+        }
+    }
+
+    Ok(slots)
+}
+
+/// Prepares the [`Expression`] for repeated execution by hoisting all constant
+/// subexpressions via [`optimize`] and compiling it into a [`CompiledProgram`].
+fn prepare(env: &impl Environment, ast: &Expression) -> Result<(Expression, CompiledProgram)> {
+    let mut ast = ast.clone();
+    optimize(env, &mut ast)?;
+    let program = CompiledProgram::from(&ast);
+
+    Ok((ast, program))
+}
+
+/// Executes an [`Expression`] once for every row of a set of named columns and
+/// stops at the first row which fails.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use slac::{compile, execute_columnar, StaticEnvironment, Value};
+///
+/// let ast = compile("price * amount > 100").unwrap();
+/// let env = StaticEnvironment::default();
+/// let columns = HashMap::from([
+///     (String::from("price"), vec![Value::Number(10.0), Value::Number(20.0)]),
+///     (String::from("amount"), vec![Value::Number(5.0), Value::Number(6.0)]),
+/// ]);
+///
+/// let result = execute_columnar(&env, &ast, &columns);
+///
+/// assert_eq!(Ok(vec![Value::Boolean(false), Value::Boolean(true)]), result);
+/// ```
+///
+/// # Remarks
+///
+/// * Every variable used in the [`Expression`] must be provided as a column.
+///   Columns shadow variables with the same name inside the [`Environment`].
+/// * Variables are matched to columns case-insensitively, a column with the
+///   exact name of the variable is preferred.
+/// * All columns must contain the same number of rows.
+/// * The [`Expression`] is optimized once prior to execution.
+///
+/// # Errors
+///
+/// Returns [`Error::MissingVariable`], [`Error::AmbiguousColumn`] or
+/// [`Error::ColumnLengthMismatch`] if the columns do not fit the [`Expression`]
+/// or the first [`Error`] encountered while executing the rows.
+pub fn execute_columnar(
+    env: &impl Environment,
+    ast: &Expression,
+    columns: &HashMap<String, Vec<Value>>,
+) -> Result<Vec<Value>> {
+    let rows = row_count(columns)?;
+    let (ast, program) = prepare(env, ast)?;
+    let columns = resolve_columns(env, &ast, &program, columns)?;
+
+    (0..rows)
+        .map(|row| VirtualMachine::execute_row(env, &program, &columns, row))
+        .collect()
+}
+
+/// Executes an [`Expression`] once for every row of a set of named columns and
+/// collects a separate [`Result`] for every row.
+///
+/// See also: [`execute_columnar`]
+///
+/// # Errors
+///
+/// Returns [`Error::MissingVariable`], [`Error::AmbiguousColumn`] or
+/// [`Error::ColumnLengthMismatch`] if the columns do not fit the [`Expression`].
+pub fn execute_columnar_rows(
+    env: &impl Environment,
+    ast: &Expression,
+    columns: &HashMap<String, Vec<Value>>,
+) -> Result<Vec<Result<Value>>> {
+    let rows = row_count(columns)?;
+    let (ast, program) = prepare(env, ast)?;
+    let columns = resolve_columns(env, &ast, &program, columns)?;
+
+    Ok((0..rows)
+        .map(|row| VirtualMachine::execute_row(env, &program, &columns, row))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{execute_columnar, execute_columnar_rows};
+    use crate::{compile, Error, StaticEnvironment, Value};
+
+    #[test]
+    fn err_missing_column() {
+        let ast = compile("a + b").unwrap();
+        let columns = HashMap::from([(String::from("a"), vec![Value::Number(1.0)])]);
+
+        assert_eq!(
+            Err(Error::MissingVariable(String::from("b"))),
+            execute_columnar(&StaticEnvironment::default(), &ast, &columns)
+        );
+    }
+
+    #[test]
+    fn err_column_length() {
+        let ast = compile("a + b").unwrap();
+        let columns = HashMap::from([
+            (
+                String::from("a"),
+                vec![Value::Number(1.0), Value::Number(2.0)],
+            ),
+            (String::from("b"), vec![Value::Number(1.0)]),
+        ]);
+
+        assert_eq!(
+            Err(Error::ColumnLengthMismatch(String::from("b"), 1, 2)),
+            execute_columnar_rows(&StaticEnvironment::default(), &ast, &columns)
+        );
+    }
+
+    #[test]
+    fn case_insensitive_columns() {
+        let ast = compile("Some_Var + some_var").unwrap();
+        let columns = HashMap::from([(String::from("SOME_VAR"), vec![Value::Number(21.0)])]);
+
+        assert_eq!(
+            Ok(vec![Value::Number(42.0)]),
+            execute_columnar(&StaticEnvironment::default(), &ast, &columns)
+        );
+    }
+
+    #[test]
+    fn exact_case_column_preferred() {
+        let ast = compile("Some_Var + some_var").unwrap();
+        let columns = HashMap::from([
+            (String::from("Some_Var"), vec![Value::Number(1.0)]),
+            (String::from("some_var"), vec![Value::Number(2.0)]),
+        ]);
+
+        assert_eq!(
+            Ok(vec![Value::Number(3.0)]),
+            execute_columnar(&StaticEnvironment::default(), &ast, &columns)
+        );
+    }
+
+    #[test]
+    fn err_ambiguous_column() {
+        let ast = compile("some_var").unwrap();
+        let columns = HashMap::from([
+            (String::from("Some_Var"), vec![Value::Number(1.0)]),
+            (String::from("SOME_VAR"), vec![Value::Number(2.0)]),
+        ]);
+
+        assert_eq!(
+            Err(Error::AmbiguousColumn(String::from("some_var"))),
+            execute_columnar(&StaticEnvironment::default(), &ast, &columns)
+        );
+    }
+}
@@ -2,19 +2,124 @@ use crate::{error::Result, operator::Operator};
 use std::vec;
 
 use crate::{
-    ast::Expression,
+    ast::{Expression, NodeSpans},
     error::Error,
-    token::{Precedence, Token},
+    interpreter::DEFAULT_MAX_DEPTH,
+    scanner::{Scanner, Span},
+    token::{Precedence, Token, TokenKind},
 };
 
+const EMPTY_KEYWORD: &str = "empty";
+
+/// The kinds of [`Tokens`](Token) which can start an operand.
+const OPERAND_KINDS: [TokenKind; 7] = [
+    TokenKind::Literal,
+    TokenKind::Identifier,
+    TokenKind::LeftParen,
+    TokenKind::LeftBracket,
+    TokenKind::Not,
+    TokenKind::Minus,
+    TokenKind::If,
+];
+
+/// The kinds of [`Tokens`](Token) which can follow an operand.
+const OPERATOR_KINDS: [TokenKind; 22] = [
+    TokenKind::Plus,
+    TokenKind::Minus,
+    TokenKind::Star,
+    TokenKind::Slash,
+    TokenKind::Div,
+    TokenKind::Mod,
+    TokenKind::Equal,
+    TokenKind::NotEqual,
+    TokenKind::ApproxEqual,
+    TokenKind::Greater,
+    TokenKind::GreaterEqual,
+    TokenKind::Less,
+    TokenKind::LessEqual,
+    TokenKind::And,
+    TokenKind::Or,
+    TokenKind::Xor,
+    TokenKind::Implies,
+    TokenKind::Iff,
+    TokenKind::Is,
+    TokenKind::In,
+    TokenKind::LeftParen,
+    TokenKind::LeftBracket,
+];
+
+/// Lists the kinds of [`Tokens`](Token) which would have been valid where the
+/// compilation of `source` failed, e.g. to offer completions in an editor.
+/// Returns an empty list if `source` compiles. Invalid characters end the
+/// input, so `1 + $` expects the same tokens as `1 +`.
+///
+/// # Examples
+/// ```
+/// use slac::{expected_tokens_at_error, TokenKind};
+///
+/// let expected = expected_tokens_at_error("max(1, ");
+///
+/// assert!(expected.contains(&TokenKind::Literal));
+/// assert!(expected.contains(&TokenKind::RightParen));
+/// assert!(!expected.contains(&TokenKind::Comma));
+/// assert!(expected_tokens_at_error("max(1)").is_empty());
+/// ```
+#[must_use]
+pub fn expected_tokens_at_error(source: &str) -> Vec<TokenKind> {
+    let (tokens, scan_error) = Scanner::scan_partial(source, false);
+    let mut compiler = Compiler::new(tokens.into_iter().map(|(token, _)| token).collect());
+
+    match compiler.compile() {
+        Ok(_) if scan_error.is_none() => vec![],
+        _ => compiler.expected_tokens(),
+    }
+}
+
+/// Checks if a comparison [`Operator`] requires its left side to be smaller.
+pub(crate) fn is_ascending(operator: Operator) -> bool {
+    matches!(operator, Operator::Less | Operator::LessEqual)
+}
+
+/// The position of the [`Compiler`] in the grammar, used to report the expected
+/// tokens when compiling fails.
+#[derive(Clone, Copy)]
+enum Expect {
+    /// The start of an operand.
+    Operand,
+    /// The start of a list element or the end of the list.
+    Element,
+    /// An operator after a complete operand.
+    Operator,
+    /// The keyword `empty` after `is`, `not` is only allowed once.
+    Empty { allow_not: bool },
+}
+
+/// A closing token the [`Compiler`] is waiting for.
+struct Closer {
+    token: Token,
+    is_list: bool,
+    /// The index of the opening bracket or keyword.
+    opened: usize,
+}
+
 /// A compiler to transform a list of [`Tokens`](Token) into a single nested [`Expression`] tree.
 ///
 /// # Remarks
 ///
 /// Uses a Pratt-Parser to build the AST based on the tokens `Precedence`.
+/// Operands nested deeper than the [`DEFAULT_MAX_DEPTH`](crate::DEFAULT_MAX_DEPTH)
+/// fail with an [`Error::RecursionLimitExceeded`].
 pub struct Compiler {
     tokens: Vec<Token>,
     current: usize,
+    /// The nesting depth of the operand currently compiled.
+    depth: usize,
+    expect: Expect,
+    closers: Vec<Closer>,
+    /// The token ranges of all compiled nodes in post-order, if recorded.
+    nodes: Option<Vec<(usize, usize)>>,
+    /// The [`Span`] of every token, if the tokens were scanned from a source.
+    spans: Option<Vec<Span>>,
 }
 
 impl Compiler {
@@ -24,10 +129,132 @@ impl Compiler {
     ///
     /// Returns an [`Error`] when encountering an invalid combination of [`Tokens`](Token).
     pub fn compile_ast(tokens: Vec<Token>) -> Result<Expression> {
-        let mut compiler = Compiler { tokens, current: 0 };
+        let mut compiler = Compiler::new(tokens);
         compiler.compile()
     }
 
+    /// Compiles a structured [`Expression`] tree from a list of [`Tokens`](Token)
+    /// and records the [`Span`] of every node, see [`compile_with_spans`](crate::compile_with_spans).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] when encountering an invalid combination of [`Tokens`](Token).
+    pub fn compile_ast_with_spans(tokens: Vec<(Token, Span)>) -> Result<(Expression, NodeSpans)> {
+        let mut compiler = Compiler::with_spans(tokens);
+        compiler.nodes = Some(vec![]);
+
+        let expression = compiler.compile()?;
+        let token_spans = compiler.spans.unwrap_or_default();
+        let spans = compiler
+            .nodes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(first, last)| Span {
+                end: token_spans[last - 1].end,
+                ..token_spans[first]
+            })
+            .collect();
+
+        Ok((expression, NodeSpans::new(spans)))
+    }
+
+    /// Compiles a structured [`Expression`] tree from a list of [`Tokens`](Token)
+    /// and their [`Spans`](Span), without recording the spans of the nodes.
+    /// Errors point to the position of the offending token.
+    pub(crate) fn compile_ast_located(tokens: Vec<(Token, Span)>) -> Result<Expression> {
+        Compiler::with_spans(tokens).compile()
+    }
+
+    fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            current: 0,
+            depth: 0,
+            expect: Expect::Operand,
+            closers: vec![],
+            nodes: None,
+            spans: None,
+        }
+    }
+
+    fn with_spans(tokens: Vec<(Token, Span)>) -> Self {
+        let (tokens, spans): (Vec<Token>, Vec<Span>) = tokens.into_iter().unzip();
+
+        Self {
+            spans: Some(spans),
+            ..Compiler::new(tokens)
+        }
+    }
+
+    /// The [`Span`] of the token at `index`, if the spans are known.
+    fn span_at(&self, index: usize) -> Option<Span> {
+        self.spans
+            .as_ref()
+            .and_then(|spans| spans.get(index).copied())
+    }
+
+    /// The [`Span`] from the token `first` up to and including the token `last`.
+    fn span_between(&self, first: usize, last: usize) -> Option<Span> {
+        Some(Span {
+            end: self.span_at(last)?.end,
+            ..self.span_at(first)?
+        })
+    }
+
+    /// Waits for the closing `token` of the previous token.
+    fn open(&mut self, token: Token, is_list: bool) {
+        self.closers.push(Closer {
+            token,
+            is_list,
+            opened: self.current - 1,
+        });
+    }
+
+    /// The kinds of [`Tokens`](Token) which are valid at the current position.
+    fn expected_tokens(&self) -> Vec<TokenKind> {
+        let closer = self.closers.last();
+        let mut expected = vec![];
+
+        match self.expect {
+            Expect::Operand => expected.extend(OPERAND_KINDS),
+            Expect::Element => {
+                expected.extend(OPERAND_KINDS);
+                expected.extend(closer.map(|c| TokenKind::from(&c.token)));
+            }
+            Expect::Operator => {
+                expected.extend(OPERATOR_KINDS);
+
+                if let Some(closer) = closer {
+                    expected.push(TokenKind::from(&closer.token));
+
+                    if closer.is_list {
+                        // the comma between list elements is optional
+                        expected.push(TokenKind::Comma);
+                        expected.extend(OPERAND_KINDS);
+                    }
+                }
+            }
+            Expect::Empty { allow_not } => {
+                expected.push(TokenKind::Identifier);
+
+                if allow_not {
+                    expected.push(TokenKind::Not);
+                }
+            }
+        }
+
+        expected.sort_unstable();
+        expected.dedup();
+        expected
+    }
+
+    /// Records the token range of a compiled node, starting at the token `first`.
+    fn record_node(&mut self, first: usize) {
+        if let Some(nodes) = &mut self.nodes {
+            nodes.push((first, self.current));
+        }
+    }
+
     fn compile(&mut self) -> Result<Expression> {
         let expression = self.expression()?;
 
@@ -38,16 +265,31 @@ impl Compiler {
     }
 
     fn expression(&mut self) -> Result<Expression> {
-        if self.current < self.tokens.len() {
-            self.parse_precedence(Precedence::Or)
-        } else {
-            Err(Error::Eof)
-        }
+        self.parse_precedence(Precedence::Iff)
     }
 
     fn parse_precedence(&mut self, precedence: Precedence) -> Result<Expression> {
+        if self.depth >= DEFAULT_MAX_DEPTH {
+            return Err(Error::RecursionLimitExceeded(DEFAULT_MAX_DEPTH));
+        }
+
+        let first = self.current;
+        self.depth += 1;
+        self.expect = Expect::Operand;
+
+        if self.current >= self.tokens.len() {
+            return Err(self.unclosed_error());
+        }
+
         self.advance();
+
+        let is_grouping = self.previous() == Ok(&Token::LeftParen);
         let mut expression = self.do_prefix()?;
+        self.expect = Expect::Operator;
+
+        if !is_grouping {
+            self.record_node(first); // a grouping is represented by its inner node
+        }
 
         while self
             .current()
@@ -55,8 +297,11 @@ impl Compiler {
         {
             self.advance();
             expression = self.do_infix(expression)?;
+            self.expect = Expect::Operator;
+            self.record_node(first);
         }
 
+        self.depth -= 1;
         Ok(expression)
     }
 
@@ -70,7 +315,11 @@ impl Compiler {
             Token::LeftParen => self.grouping(),
             Token::LeftBracket => self.array(),
             Token::Not | Token::Minus => self.unary(),
-            _ => Err(Error::NoValidPrefixToken(previous.clone())),
+            Token::If => self.if_else(),
+            _ => Err(Error::NoValidPrefixToken {
+                token: previous.clone(),
+                span: self.span_at(self.current - 1),
+            }),
         }
     }
 
@@ -85,14 +334,20 @@ impl Compiler {
             | Token::Mod
             | Token::Equal
             | Token::NotEqual
+            | Token::ApproxEqual
             | Token::Greater
             | Token::GreaterEqual
             | Token::Less
             | Token::LessEqual
             | Token::And
             | Token::Or
-            | Token::Xor => self.binary(left),
+            | Token::Xor
+            | Token::Implies
+            | Token::Iff
+            | Token::In => self.binary(left),
             Token::LeftParen => self.call(left),
+            Token::LeftBracket => self.index(left),
+            Token::Is => self.is_empty(left),
             _ => Err(Error::NoValidInfixToken(previous.clone())),
         }
     }
@@ -100,21 +355,37 @@ impl Compiler {
     fn expression_list(&mut self, end_token: &Token) -> Result<Vec<Expression>> {
         let mut expressions: Vec<Expression> = vec![];
 
+        self.expect = Expect::Element;
+        self.open(end_token.clone(), true);
+
         while self.current().is_some_and(|t| t != end_token) {
+            if self.current() == Some(&Token::Comma) {
+                return Err(Error::EmptyArrayElement {
+                    position: expressions.len(),
+                    span: self.span_at(self.current),
+                });
+            }
+
             expressions.push(self.expression()?);
 
             if self.current() == Some(&Token::Comma) {
                 self.advance();
+                self.expect = Expect::Element;
             }
         }
 
         self.chomp(end_token)?;
+        self.closers.pop();
 
         Ok(expressions)
     }
 
     fn call(&mut self, left: Expression) -> Result<Expression> {
         if let Expression::Variable { name } = left {
+            if let Some(nodes) = &mut self.nodes {
+                nodes.pop(); // the name is part of the call node
+            }
+
             Ok(Expression::Call {
                 name,
                 params: self.expression_list(&Token::RightParen)?,
@@ -124,6 +395,21 @@ impl Compiler {
         }
     }
 
+    /// Compiles the postfix access `target[index]` of a single element.
+    fn index(&mut self, target: Expression) -> Result<Expression> {
+        self.open(Token::RightBracket, false);
+        self.check_operand(false)?;
+
+        let index = self.expression()?;
+        self.chomp(&Token::RightBracket)?;
+        self.closers.pop();
+
+        Ok(Expression::Index {
+            target: Box::new(target),
+            index: Box::new(index),
+        })
+    }
+
     fn array(&mut self) -> Result<Expression> {
         Ok(Expression::Array {
             expressions: self.expression_list(&Token::RightBracket)?,
@@ -132,7 +418,22 @@ impl Compiler {
 
     fn binary(&mut self, left: Expression) -> Result<Expression> {
         let operator = Operator::try_from(self.previous()?)?;
-        let right = self.parse_precedence(Precedence::from(self.previous()?).next())?;
+        let precedence = Precedence::from(self.previous()?);
+        self.check_operand(true)?;
+
+        // `a implies b implies c` is read as `a implies (b implies c)`
+        let right = if precedence == Precedence::Implies {
+            self.parse_precedence(precedence)?
+        } else {
+            self.parse_precedence(precedence.next())?
+        };
+
+        if precedence == Precedence::Comparison
+            && operator != Operator::In
+            && self.current_is_comparison()
+        {
+            return self.chain(left, right, operator);
+        }
 
         Ok(Expression::Binary {
             left: Box::new(left),
@@ -141,8 +442,48 @@ impl Compiler {
         })
     }
 
+    /// Checks if the current token continues a chain of comparisons, `in`
+    /// shares their precedence but is never chained.
+    fn current_is_comparison(&self) -> bool {
+        self.current()
+            .is_some_and(|t| t != &Token::In && Precedence::from(t) == Precedence::Comparison)
+    }
+
+    /// Compiles consecutive comparisons like `a < b <= c` into an
+    /// [`Expression::Chain`] instead of comparing the result of `a < b` with `c`.
+    /// All operators of a chain must point into the same direction.
+    fn chain(
+        &mut self,
+        left: Expression,
+        right: Expression,
+        operator: Operator,
+    ) -> Result<Expression> {
+        let mut expressions = vec![left, right];
+        let mut operators = vec![operator];
+
+        while self.current_is_comparison() {
+            self.advance();
+            let next = Operator::try_from(self.previous()?)?;
+
+            if is_ascending(next) != is_ascending(operator) {
+                return Err(Error::MixedComparisonChain(operator, next));
+            }
+
+            self.check_operand(true)?;
+
+            operators.push(next);
+            expressions.push(self.parse_precedence(Precedence::Comparison.next())?);
+        }
+
+        Ok(Expression::Chain {
+            expressions,
+            operators,
+        })
+    }
+
     fn unary(&mut self) -> Result<Expression> {
         let operator = Operator::try_from(self.previous()?)?;
+        self.check_operand(false)?;
         let right = self.parse_precedence(Precedence::Unary)?;
 
         Ok(Expression::Unary {
@@ -151,9 +492,120 @@ impl Compiler {
         })
     }
 
+    /// Compiles `if a then b else c` into an [`Operator::TernaryCondition`].
+    /// The `else` branch extends as far as possible, like the right side of an
+    /// operator with the lowest precedence.
+    fn if_else(&mut self) -> Result<Expression> {
+        self.check_operand(false)?;
+        let left = self.branch(&Token::Then)?;
+
+        self.check_operand(false)?;
+        let middle = self.branch(&Token::Else)?;
+
+        self.check_operand(false)?;
+        let right = self.expression()?;
+
+        Ok(Expression::Ternary {
+            left: Box::new(left),
+            middle: Box::new(middle),
+            right: Box::new(right),
+            operator: Operator::TernaryCondition,
+        })
+    }
+
+    /// Compiles the part of an `if` expression in front of the `keyword`.
+    fn branch(&mut self, keyword: &Token) -> Result<Expression> {
+        self.open(keyword.clone(), false);
+
+        let expression = self.expression()?;
+
+        if self.current() != Some(keyword) {
+            return Err(self.unclosed_error());
+        }
+
+        self.advance();
+        self.closers.pop();
+
+        Ok(expression)
+    }
+
+    /// Checks if the operator at the previous token is followed by the start of
+    /// an operand, to report a missing operand with its operator.
+    fn check_operand(&mut self, is_binary: bool) -> Result<()> {
+        self.expect = Expect::Operand;
+        let operator = self.previous()?.clone();
+        let span = self.span_at(self.current - 1);
+
+        match self.current() {
+            None if is_binary => Err(Error::TrailingOperator { operator, span }),
+            None => Err(Error::MissingOperand { operator, span }),
+            Some(token) if !OPERAND_KINDS.contains(&TokenKind::from(token)) => {
+                Err(Error::MissingOperand { operator, span })
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// The error for a missing closing token, like at the end of the tokens.
+    /// An open bracket is reported as unbalanced and an open `if` as a missing
+    /// keyword, both located at their opening token.
+    fn unclosed_error(&self) -> Error {
+        let Some(closer) = self.closers.last() else {
+            return Error::Eof;
+        };
+        let expected = closer.token.clone();
+        let span = self.span_at(closer.opened);
+
+        match expected {
+            Token::Then | Token::Else => Error::MissingKeyword { expected, span },
+            _ => Error::UnbalancedParen { expected, span },
+        }
+    }
+
+    /// Compiles the postfix keywords `is empty` and `is not empty`.
+    fn is_empty(&mut self, left: Expression) -> Result<Expression> {
+        self.expect = Expect::Empty { allow_not: true };
+
+        let operator = if self.current() == Some(&Token::Not) {
+            self.advance();
+            self.expect = Expect::Empty { allow_not: false };
+            Operator::IsNotEmpty
+        } else {
+            Operator::IsEmpty
+        };
+
+        let found_empty = matches!(
+            self.current(),
+            Some(Token::Identifier(name)) if name.eq_ignore_ascii_case(EMPTY_KEYWORD)
+        );
+
+        if !found_empty {
+            return Err(self.current().map_or(Error::Eof, |t| Error::InvalidToken {
+                token: t.clone(),
+                span: self.span_at(self.current),
+            }));
+        }
+
+        self.advance();
+
+        Ok(Expression::Unary {
+            right: Box::new(left),
+            operator,
+        })
+    }
+
     fn grouping(&mut self) -> Result<Expression> {
+        self.open(Token::RightParen, false);
+
+        if self.current() == Some(&Token::RightParen) {
+            return Err(Error::EmptyParentheses {
+                span: self.span_between(self.current - 1, self.current),
+            });
+        }
+
         let expression = self.expression()?;
         self.chomp(&Token::RightParen)?;
+        self.closers.pop();
 
         Ok(expression)
     }
@@ -175,20 +627,26 @@ impl Compiler {
     }
 
     fn chomp(&mut self, token: &Token) -> Result<()> {
-        if self.current() == Some(token) {
-            self.advance();
-            Ok(())
-        } else {
-            Err(self
-                .current()
-                .map_or(Error::Eof, |t| Error::InvalidToken(t.clone())))
+        match self.current() {
+            Some(current) if current == token => {
+                self.advance();
+                Ok(())
+            }
+            Some(Token::RightParen | Token::RightBracket) | None => Err(self.unclosed_error()),
+            Some(current) => Err(Error::InvalidToken {
+                token: current.clone(),
+                span: self.span_at(self.current),
+            }),
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{ast::Expression, error::Error, operator::Operator, token::Token, value::Value};
+    use crate::{
+        ast::Expression, error::Error, interpreter::DEFAULT_MAX_DEPTH, operator::Operator,
+        scanner::Span, token::Token, value::Value,
+    };
 
     use super::Compiler;
 
@@ -429,7 +887,10 @@ mod test {
             Token::LeftParen,
         ]);
 
-        let expected = Error::Eof;
+        let expected = Error::UnbalancedParen {
+            expected: Token::RightParen,
+            span: None,
+        };
 
         assert_eq!(ast, Err(expected));
     }
@@ -441,22 +902,150 @@ mod test {
             Token::Literal(Value::Boolean(false)),
         ]);
 
-        let expected = Error::Eof;
+        let expected = Error::UnbalancedParen {
+            expected: Token::RightBracket,
+            span: None,
+        };
         assert_eq!(ast, Err(expected));
     }
 
     #[test]
     fn err_open_group() {
+        let expected = || Error::UnbalancedParen {
+            expected: Token::RightParen,
+            span: None,
+        };
         let ast = Compiler::compile_ast(vec![Token::LeftParen]);
 
-        assert_eq!(ast, Err(Error::Eof));
+        assert_eq!(ast, Err(expected()));
 
         let ast = Compiler::compile_ast(vec![
             Token::Identifier(String::from("test")),
             Token::And,
             Token::LeftParen,
         ]);
-        assert_eq!(ast, Err(Error::Eof));
+        assert_eq!(ast, Err(expected()));
+    }
+
+    #[test]
+    fn is_not_empty() {
+        let tokens = vec![
+            Token::Identifier(String::from("some_var")),
+            Token::Is,
+            Token::Not,
+            Token::Identifier(String::from("Empty")),
+            Token::And,
+            Token::Literal(Value::Boolean(true)),
+        ];
+        let expected = Expression::Binary {
+            left: Box::new(Expression::Unary {
+                right: Box::new(Expression::Variable {
+                    name: String::from("some_var"),
+                }),
+                operator: Operator::IsNotEmpty,
+            }),
+            right: Box::new(Expression::Literal {
+                value: Value::Boolean(true),
+            }),
+            operator: Operator::And,
+        };
+
+        assert_eq!(Ok(expected), Compiler::compile_ast(tokens));
+    }
+
+    #[test]
+    fn err_is_without_empty() {
+        let tokens = vec![
+            Token::Identifier(String::from("some_var")),
+            Token::Is,
+            Token::Identifier(String::from("full")),
+        ];
+        assert_eq!(
+            Err(Error::InvalidToken {
+                token: Token::Identifier(String::from("full")),
+                span: None
+            }),
+            Compiler::compile_ast(tokens)
+        );
+
+        let tokens = vec![Token::Identifier(String::from("some_var")), Token::Is];
+        assert_eq!(Err(Error::Eof), Compiler::compile_ast(tokens));
+    }
+
+    #[test]
+    fn comparison_chain() {
+        let number = |value: f64| Expression::Literal {
+            value: Value::Number(value),
+        };
+        let ast = Compiler::compile_ast(vec![
+            Token::Literal(Value::Number(1.0)),
+            Token::Less,
+            Token::Literal(Value::Number(2.0)),
+            Token::LessEqual,
+            Token::Literal(Value::Number(3.0)),
+            Token::Less,
+            Token::Literal(Value::Number(4.0)),
+        ]);
+        let expected = Expression::Chain {
+            expressions: vec![number(1.0), number(2.0), number(3.0), number(4.0)],
+            operators: vec![Operator::Less, Operator::LessEqual, Operator::Less],
+        };
+
+        assert_eq!(Ok(expected), ast);
+    }
+
+    #[test]
+    fn membership_is_not_chained() {
+        let variable = |name: &str| {
+            Box::new(Expression::Variable {
+                name: String::from(name),
+            })
+        };
+        let binary = |left, right, operator| Expression::Binary {
+            left,
+            right,
+            operator,
+        };
+
+        assert_eq!(
+            Ok(binary(variable("a"), variable("b"), Operator::In)),
+            crate::compile("a in b")
+        );
+        assert_eq!(
+            Ok(binary(
+                Box::new(binary(variable("a"), variable("b"), Operator::Less)),
+                variable("c"),
+                Operator::In
+            )),
+            crate::compile("a < b in c")
+        );
+        assert_eq!(
+            Ok(binary(
+                Box::new(binary(variable("a"), variable("b"), Operator::In)),
+                variable("c"),
+                Operator::Equal
+            )),
+            crate::compile("a in b = c")
+        );
+    }
+
+    #[test]
+    fn err_mixed_comparison_chain() {
+        let ast = Compiler::compile_ast(vec![
+            Token::Literal(Value::Number(1.0)),
+            Token::Less,
+            Token::Literal(Value::Number(3.0)),
+            Token::GreaterEqual,
+            Token::Literal(Value::Number(2.0)),
+        ]);
+
+        assert_eq!(
+            Err(Error::MixedComparisonChain(
+                Operator::Less,
+                Operator::GreaterEqual
+            )),
+            ast
+        );
     }
 
     #[test]
@@ -464,7 +1053,406 @@ mod test {
         let ast =
             Compiler::compile_ast(vec![Token::LeftBracket, Token::Comma, Token::RightBracket]);
 
-        let expected = Error::NoValidPrefixToken(Token::Comma);
+        let expected = Error::EmptyArrayElement {
+            position: 0,
+            span: None,
+        };
         assert_eq!(ast, Err(expected));
     }
+
+    #[test]
+    fn err_missing_operand() {
+        let ast = Compiler::compile_ast(vec![Token::Literal(Value::Number(1.0)), Token::Plus]);
+        assert_eq!(
+            Err(Error::TrailingOperator {
+                operator: Token::Plus,
+                span: None
+            }),
+            ast
+        );
+
+        assert_eq!(
+            Err(Error::MissingOperand {
+                operator: Token::Not,
+                span: None
+            }),
+            Compiler::compile_ast(vec![Token::Not])
+        );
+        assert_eq!(
+            Err(Error::MissingOperand {
+                operator: Token::Minus,
+                span: None
+            }),
+            Compiler::compile_ast(vec![Token::Minus])
+        );
+    }
+
+    /// Common mistakes and the error reported for them.
+    #[test]
+    fn if_then_else() {
+        let variable = |name: &str| {
+            Box::new(Expression::Variable {
+                name: String::from(name),
+            })
+        };
+        let ternary = |left, middle, right| Expression::Ternary {
+            left,
+            middle,
+            right,
+            operator: Operator::TernaryCondition,
+        };
+
+        assert_eq!(
+            Ok(ternary(variable("a"), variable("b"), variable("c"))),
+            crate::compile("if a then b else c")
+        );
+        assert_eq!(
+            Ok(ternary(
+                variable("a"),
+                Box::new(ternary(variable("b"), variable("c"), variable("d"))),
+                Box::new(ternary(variable("e"), variable("f"), variable("g"))),
+            )),
+            crate::compile("if a then if b then c else d else if e then f else g")
+        );
+        assert_eq!(
+            crate::compile("if a then b else (c or d)"),
+            crate::compile("if a then b else c or d")
+        );
+        assert_eq!(
+            crate::compile("(if a then b else c) or d"),
+            Ok(Expression::Binary {
+                left: Box::new(ternary(variable("a"), variable("b"), variable("c"))),
+                right: variable("d"),
+                operator: Operator::Or,
+            })
+        );
+    }
+
+    #[test]
+    fn index() {
+        let variable = |name: &str| {
+            Box::new(Expression::Variable {
+                name: String::from(name),
+            })
+        };
+        let number = |value: f64| {
+            Box::new(Expression::Literal {
+                value: Value::Number(value),
+            })
+        };
+        let index = |target, index| Expression::Index { target, index };
+
+        assert_eq!(
+            Ok(index(variable("a"), number(1.0))),
+            crate::compile("a[1]")
+        );
+        assert_eq!(
+            Ok(index(
+                Box::new(index(variable("a"), number(1.0))),
+                variable("b")
+            )),
+            crate::compile("a[1][b]")
+        );
+        assert_eq!(
+            Ok(Expression::Binary {
+                left: number(1.0),
+                right: Box::new(index(variable("a"), number(2.0))),
+                operator: Operator::Plus,
+            }),
+            crate::compile("1 + a[2]")
+        );
+        assert_eq!(
+            Ok(Expression::Unary {
+                right: Box::new(index(variable("a"), number(0.0))),
+                operator: Operator::Minus,
+            }),
+            crate::compile("-a[0]")
+        );
+        assert_eq!(
+            Ok(index(
+                Box::new(Expression::Call {
+                    name: String::from("words"),
+                    params: vec![*variable("a")],
+                }),
+                number(0.0)
+            )),
+            crate::compile("words(a)[0]")
+        );
+        assert_eq!(crate::compile("a[1]"), crate::compile("(a)[1]"));
+    }
+
+    #[test]
+    fn err_recursion_limit() {
+        let nested = |depth: usize| {
+            let mut tokens = vec![Token::LeftParen; depth];
+            tokens.push(Token::Literal(Value::Number(1.0)));
+            tokens.extend(vec![Token::RightParen; depth]);
+            Compiler::compile_ast(tokens)
+        };
+        let exceeded = Err(Error::RecursionLimitExceeded(DEFAULT_MAX_DEPTH));
+
+        assert!(nested(DEFAULT_MAX_DEPTH - 1).is_ok());
+        assert_eq!(exceeded, nested(DEFAULT_MAX_DEPTH));
+        assert_eq!(exceeded, nested(100_000));
+        assert_eq!(exceeded, Compiler::compile_ast(vec![Token::Not; 100_000]));
+        assert_eq!(
+            exceeded,
+            Compiler::compile_ast(vec![Token::LeftBracket; 100_000])
+        );
+
+        // a chain of operators is compiled by a loop instead of recursion
+        let mut chain = vec![Token::Literal(Value::Number(1.0))];
+        for _ in 0..10_000 {
+            chain.extend([Token::Star, Token::Literal(Value::Number(1.0))]);
+        }
+        assert!(Compiler::compile_ast(chain).is_ok());
+    }
+
+    #[test]
+    fn err_malformed_inputs() {
+        let identifier = |name: &str| Token::Identifier(String::from(name));
+        let span = |start, end| {
+            Some(Span {
+                start,
+                end,
+                line: 1,
+                column: start + 1,
+            })
+        };
+        let cases = [
+            ("", Error::Eof),
+            ("()", Error::EmptyParentheses { span: span(0, 2) }),
+            ("1 + ()", Error::EmptyParentheses { span: span(4, 6) }),
+            (
+                "max(,1)",
+                Error::EmptyArrayElement {
+                    position: 0,
+                    span: span(4, 5),
+                },
+            ),
+            (
+                "[1,,2]",
+                Error::EmptyArrayElement {
+                    position: 1,
+                    span: span(3, 4),
+                },
+            ),
+            (
+                "[,]",
+                Error::EmptyArrayElement {
+                    position: 0,
+                    span: span(1, 2),
+                },
+            ),
+            (
+                "1 +",
+                Error::TrailingOperator {
+                    operator: Token::Plus,
+                    span: span(2, 3),
+                },
+            ),
+            (
+                "a and",
+                Error::TrailingOperator {
+                    operator: Token::And,
+                    span: span(2, 5),
+                },
+            ),
+            (
+                "1 < 2 <=",
+                Error::TrailingOperator {
+                    operator: Token::LessEqual,
+                    span: span(6, 8),
+                },
+            ),
+            (
+                "max(1 *",
+                Error::TrailingOperator {
+                    operator: Token::Star,
+                    span: span(6, 7),
+                },
+            ),
+            (
+                "1 + * 2",
+                Error::MissingOperand {
+                    operator: Token::Plus,
+                    span: span(2, 3),
+                },
+            ),
+            (
+                "(1 - )",
+                Error::MissingOperand {
+                    operator: Token::Minus,
+                    span: span(3, 4),
+                },
+            ),
+            (
+                "[1 =, 2]",
+                Error::MissingOperand {
+                    operator: Token::Equal,
+                    span: span(3, 4),
+                },
+            ),
+            (
+                "1 < 2 < )",
+                Error::MissingOperand {
+                    operator: Token::Less,
+                    span: span(6, 7),
+                },
+            ),
+            (
+                "not",
+                Error::MissingOperand {
+                    operator: Token::Not,
+                    span: span(0, 3),
+                },
+            ),
+            (
+                "-)",
+                Error::MissingOperand {
+                    operator: Token::Minus,
+                    span: span(0, 1),
+                },
+            ),
+            (
+                "(",
+                Error::UnbalancedParen {
+                    expected: Token::RightParen,
+                    span: span(0, 1),
+                },
+            ),
+            (
+                "(1 + 2",
+                Error::UnbalancedParen {
+                    expected: Token::RightParen,
+                    span: span(0, 1),
+                },
+            ),
+            (
+                "max(1, 2",
+                Error::UnbalancedParen {
+                    expected: Token::RightParen,
+                    span: span(3, 4),
+                },
+            ),
+            (
+                "[1, 2",
+                Error::UnbalancedParen {
+                    expected: Token::RightBracket,
+                    span: span(0, 1),
+                },
+            ),
+            (
+                "(1]",
+                Error::UnbalancedParen {
+                    expected: Token::RightParen,
+                    span: span(0, 1),
+                },
+            ),
+            (
+                "[(1])",
+                Error::UnbalancedParen {
+                    expected: Token::RightParen,
+                    span: span(1, 2),
+                },
+            ),
+            (
+                "if a then 1",
+                Error::MissingKeyword {
+                    expected: Token::Else,
+                    span: span(5, 9),
+                },
+            ),
+            (
+                "if a 1 else 2",
+                Error::MissingKeyword {
+                    expected: Token::Then,
+                    span: span(0, 2),
+                },
+            ),
+            (
+                "(if a)",
+                Error::MissingKeyword {
+                    expected: Token::Then,
+                    span: span(1, 3),
+                },
+            ),
+            (
+                "if a then 1 else",
+                Error::MissingOperand {
+                    operator: Token::Else,
+                    span: span(12, 16),
+                },
+            ),
+            (
+                "if then 1 else 2",
+                Error::MissingOperand {
+                    operator: Token::If,
+                    span: span(0, 2),
+                },
+            ),
+            (
+                "if (a then 1 else 2",
+                Error::InvalidToken {
+                    token: Token::Then,
+                    span: span(6, 10),
+                },
+            ),
+            (
+                "else 1",
+                Error::NoValidPrefixToken {
+                    token: Token::Else,
+                    span: span(0, 4),
+                },
+            ),
+            (
+                "a[]",
+                Error::MissingOperand {
+                    operator: Token::LeftBracket,
+                    span: span(1, 2),
+                },
+            ),
+            (
+                "a[1",
+                Error::UnbalancedParen {
+                    expected: Token::RightBracket,
+                    span: span(1, 2),
+                },
+            ),
+            (
+                "max(a[1)",
+                Error::UnbalancedParen {
+                    expected: Token::RightBracket,
+                    span: span(5, 6),
+                },
+            ),
+            (
+                ",",
+                Error::NoValidPrefixToken {
+                    token: Token::Comma,
+                    span: span(0, 1),
+                },
+            ),
+            ("1)", Error::MultipleExpressions(Token::RightParen)),
+            (
+                "(1 2)",
+                Error::InvalidToken {
+                    token: Token::Literal(Value::Number(2.0)),
+                    span: span(3, 4),
+                },
+            ),
+            (
+                "a is full",
+                Error::InvalidToken {
+                    token: identifier("full"),
+                    span: span(5, 9),
+                },
+            ),
+            ("1(2)", Error::CallNotOnVariable(Token::LeftParen)),
+        ];
+
+        for (source, expected) in cases {
+            assert_eq!(Err(expected), crate::compile(source), "{source}");
+        }
+    }
 }
@@ -0,0 +1,93 @@
+//! Capturing and replaying the execution of an [`Expression`] for debugging.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::Expression,
+    environment::StaticEnvironment,
+    error::{Error, Result},
+    execute,
+    value::Value,
+};
+
+/// A serializable copy of all variables inside a [`StaticEnvironment`].
+/// Functions are not part of the snapshot.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VariableSnapshot {
+    pub variables: BTreeMap<String, Value>,
+}
+
+/// Everything needed to replay the execution of an [`Expression`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DebugBundle {
+    /// The original source of the [`Expression`], if available.
+    pub source: Option<String>,
+    pub ast: Expression,
+    pub variables: VariableSnapshot,
+    /// The recorded result of the execution, if it succeeded.
+    pub result: Option<Value>,
+}
+
+impl DebugBundle {
+    /// Serializes the bundle into a JSON string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::InvalidDebugBundle`] if the bundle can not be serialized.
+    pub fn save_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::InvalidDebugBundle(e.to_string()))
+    }
+
+    /// Deserializes a bundle from a JSON string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::InvalidDebugBundle`] if the JSON is not a valid bundle.
+    pub fn load_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::InvalidDebugBundle(e.to_string()))
+    }
+}
+
+/// Replaces all variables of the [`StaticEnvironment`] with the variables of
+/// the [`DebugBundle`] and executes its [`Expression`].
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute, replay, DebugBundle, StaticEnvironment, Value};
+///
+/// let mut env = StaticEnvironment::default();
+/// env.add_variable("price", Value::Number(42.0));
+///
+/// let ast = compile("price > 40").unwrap();
+/// let bundle = DebugBundle {
+///     source: Some(String::from("price > 40")),
+///     result: execute(&env, &ast).ok(),
+///     variables: env.snapshot_variables(),
+///     ast,
+/// };
+///
+/// let json = bundle.save_json().unwrap();
+/// let bundle = DebugBundle::load_json(&json).unwrap();
+///
+/// assert_eq!(Ok(Value::Boolean(true)), replay(&bundle, &mut StaticEnvironment::default()));
+/// ```
+///
+/// # Errors
+///
+/// Returns the [`Error`] of the execution or an [`Error::ReplayMismatch`] if
+/// the result differs from the recorded result.
+pub fn replay(bundle: &DebugBundle, env: &mut StaticEnvironment) -> Result<Value> {
+    env.restore_variables(&bundle.variables);
+
+    let result = execute(env, &bundle.ast)?;
+
+    match &bundle.result {
+        Some(expected) if *expected != result => {
+            Err(Error::ReplayMismatch(expected.clone(), result))
+        }
+        _ => Ok(result),
+    }
+}
@@ -0,0 +1,277 @@
+//! A small grammar to declare variables and stub functions of a
+//! [`StaticEnvironment`](crate::StaticEnvironment) for test fixtures.
+
+use crate::{
+    compile,
+    environment::StaticEnvironment,
+    error::{Error, Result},
+    execute,
+    function::{Arity, Function},
+    Value,
+};
+
+/// A parsed declaration string.
+pub(crate) enum Declaration {
+    Variable(String, Value),
+    Function(Function),
+}
+
+/// Parses a single declaration:
+///
+/// * Variables: `name: Type = literal`, the literal may be omitted for concrete types.
+/// * Functions: `name(param: Type, optional: Type = literal): Type => body`.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidDeclaration`] with the reason the declaration was rejected.
+pub(crate) fn parse(declaration: &str) -> Result<Declaration> {
+    let invalid = |reason: String| Error::InvalidDeclaration(declaration.to_string(), reason);
+    let text = declaration.trim();
+
+    let name_len = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+    let (name, rest) = text.split_at(name_len);
+
+    if name.is_empty() {
+        return Err(invalid(String::from("missing name")));
+    }
+
+    let rest = rest.trim_start();
+
+    if let Some(rest) = rest.strip_prefix('(') {
+        parse_function(name, rest).map_err(invalid)
+    } else if let Some(rest) = rest.strip_prefix(':') {
+        parse_variable(rest)
+            .map(|value| Declaration::Variable(name.to_string(), value))
+            .map_err(invalid)
+    } else {
+        Err(invalid(String::from("expected ':' or '(' after the name")))
+    }
+}
+
+/// The reason a declaration was rejected, wrapped into [`Error::InvalidDeclaration`] by [`parse`].
+type Reason<T> = std::result::Result<T, String>;
+
+fn parse_variable(rest: &str) -> Reason<Value> {
+    match split_default(rest) {
+        (kind, None) => {
+            empty_value(check_type(kind)?).ok_or(format!("type {kind} requires a default value"))
+        }
+        (kind, Some(literal)) => parse_literal(literal, check_type(kind)?),
+    }
+}
+
+fn parse_function(name: &str, rest: &str) -> Reason<Declaration> {
+    let close = find_top_level(rest, ')').ok_or("missing closing parenthesis")?;
+    let (params, rest) = (&rest[..close], &rest[close + 1..]);
+
+    let (signature, body) = rest
+        .split_once("=>")
+        .ok_or("missing function body after '=>'")?;
+
+    let return_type = signature.trim();
+    if !return_type.is_empty() {
+        let return_type = return_type
+            .strip_prefix(':')
+            .ok_or("expected ':' before the return type")?;
+        check_type(return_type.trim())?;
+    }
+
+    let arity = parse_params(params)?;
+    let body = compile(body).map_err(|e| format!("invalid function body: {e}"))?;
+
+    let declaration = format!("{name}({}){}", params.trim(), signature.trim_end());
+
+    Ok(Declaration::Function(Function::stub(
+        body,
+        arity,
+        &declaration,
+    )))
+}
+
+fn parse_params(params: &str) -> Reason<Arity> {
+    if params.trim().is_empty() {
+        return Ok(Arity::None);
+    }
+
+    let params = split_top_level(params, ',');
+    let mut required = 0;
+    let mut optional = 0;
+
+    for param in &params {
+        let param = param.trim();
+
+        if param == "..." {
+            if params.len() > 1 {
+                return Err(String::from("'...' must be the only parameter"));
+            }
+            return Ok(Arity::Variadic);
+        }
+
+        let (param_name, rest) = param
+            .split_once(':')
+            .ok_or(format!("parameter \"{param}\" has no type"))?;
+
+        match split_default(rest) {
+            (kind, None) => {
+                check_type(kind)?;
+
+                if optional > 0 {
+                    return Err(format!(
+                        "required parameter \"{}\" after an optional parameter",
+                        param_name.trim()
+                    ));
+                }
+                required += 1;
+            }
+            (kind, Some(literal)) => {
+                // the default is only validated, it is part of the declaration text
+                let _default = parse_literal(literal, check_type(kind)?)?;
+                optional += 1;
+            }
+        }
+    }
+
+    Ok(Arity::optional(required, optional))
+}
+
+/// Evaluates a constant expression and checks it against the declared type.
+fn parse_literal(literal: &str, kind: Option<&str>) -> Reason<Value> {
+    let value = compile(literal)
+        .and_then(|ast| execute(&StaticEnvironment::default(), &ast))
+        .map_err(|e| format!("invalid literal \"{}\": {e}", literal.trim()))?;
+
+    match kind {
+        Some(kind) if kind != value.type_name() => Err(format!(
+            "expected a {kind} but got a {} literal",
+            value.type_name()
+        )),
+        _ => Ok(value),
+    }
+}
+
+/// Checks a type like `Number`, `Array<String>` or `[String|Array]`. Returns
+/// the [`Value`] kind of concrete types, `None` for `Any` and unions.
+fn check_type(kind: &str) -> Reason<Option<&'static str>> {
+    if let Some(union) = kind.strip_prefix('[').and_then(|k| k.strip_suffix(']')) {
+        for member in split_top_level(union, '|') {
+            check_type(member.trim())?;
+        }
+        return Ok(None);
+    }
+
+    if let Some(inner) = kind
+        .strip_prefix("Array<")
+        .and_then(|k| k.strip_suffix('>'))
+    {
+        check_type(inner.trim())?;
+        return Ok(Some("Array"));
+    }
+
+    match kind {
+        "Boolean" => Ok(Some("Boolean")),
+        "Number" => Ok(Some("Number")),
+        "String" => Ok(Some("String")),
+        "Array" => Ok(Some("Array")),
+        "Any" => Ok(None),
+        _ => Err(format!("unknown type \"{kind}\"")),
+    }
+}
+
+fn empty_value(kind: Option<&str>) -> Option<Value> {
+    match kind? {
+        "Boolean" => Some(Value::Boolean(false)),
+        "Number" => Some(Value::Number(0.0)),
+        "String" => Some(Value::String(String::new())),
+        "Array" => Some(Value::Array(vec![])),
+        _ => None,
+    }
+}
+
+/// Finds the first separator outside of string literals and brackets.
+fn find_top_level(text: &str, separator: char) -> Option<usize> {
+    let mut depth = 0_usize;
+    let mut quoted = false;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            _ if quoted => (),
+            _ if c == separator && depth == 0 => return Some(index),
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Splits `Type = literal` at the first `=`, the literal may contain further ones.
+fn split_default(text: &str) -> (&str, Option<&str>) {
+    match find_top_level(text, '=') {
+        Some(index) => (text[..index].trim(), Some(&text[index + 1..])),
+        None => (text.trim(), None),
+    }
+}
+
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut rest = text;
+
+    while let Some(index) = find_top_level(rest, separator) {
+        parts.push(&rest[..index]);
+        rest = &rest[index + separator.len_utf8()..];
+    }
+
+    parts.push(rest);
+    parts
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, Declaration};
+    use crate::{function::Arity, Value};
+
+    #[test]
+    fn round_trip() {
+        let declarations = [
+            ("lookup(key: String): String", Arity::required(1)),
+            (
+                "pad(text: String, width: Number = 10): String",
+                Arity::optional(1, 1),
+            ),
+            ("sum(...): Number", Arity::Variadic),
+            ("now()", Arity::None),
+        ];
+
+        for (declaration, arity) in declarations {
+            let Ok(Declaration::Function(function)) = parse(&format!("{declaration} => 1")) else {
+                panic!("{declaration} is not a function declaration");
+            };
+
+            assert_eq!(declaration, function.declaration());
+            assert_eq!(arity, function.arity);
+        }
+
+        let variables = [
+            ("amount: Number = 12.5", Value::Number(12.5)),
+            (
+                "name: String = 'a = b'",
+                Value::String(String::from("a = b")),
+            ),
+            ("flag: Boolean = 1 = 1", Value::Boolean(true)),
+            ("items: [String|Array] = []", Value::Array(vec![])),
+            ("empty: Number", Value::Number(0.0)),
+        ];
+
+        for (declaration, expected) in variables {
+            let Ok(Declaration::Variable(_, value)) = parse(declaration) else {
+                panic!("{declaration} is not a variable declaration");
+            };
+
+            assert_eq!(expected, value);
+        }
+    }
+}
@@ -1,12 +1,33 @@
 //! Dynamic variables and function calls can be provided by an [`Environment`].
 
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
 
+#[cfg(feature = "random")]
+use crate::stdlib::{
+    default_number,
+    math::{choice_of, random_in_range, split_mix64, SPLIT_MIX_GAMMA},
+};
 use crate::{
-    function::{Arity, Function},
+    cancellation::CancellationToken,
+    declaration::{self, Declaration},
+    error::{Error, Result},
+    function::{Arity, Function, FunctionSignature},
     stdlib::{NativeError, NativeResult},
     value::Value,
+    value_set::ValueSet,
+    Expression,
 };
+#[cfg(feature = "serde")]
+use crate::{catalog::Catalog, debug::VariableSnapshot};
+#[cfg(feature = "random")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "chrono")]
+use chrono::NaiveDateTime;
 
 /// An enum signaling if a matching function is provided by a [`Environment`].
 pub enum FunctionResult {
@@ -22,7 +43,7 @@ pub enum FunctionResult {
 /// It provides access to variables and native function calls.
 pub trait Environment {
     /// Get a variable [`Value`] from the Environment.
-    fn variable(&self, name: &str) -> Option<Rc<Value>>;
+    fn variable(&self, name: &str) -> Option<Arc<Value>>;
 
     /// Call a [`Function`] and may return a [`Value`].
     ///
@@ -31,82 +52,712 @@ pub trait Environment {
     /// Returns [`NativeError`] when encountering an error inside a [`NativeFunction`](crate::stdlib::NativeFunction)
     fn call(&self, name: &str, params: &[Value]) -> NativeResult;
 
+    /// Call a [`Function`] during a cancellable execution. Forwards to
+    /// [`Environment::call`] by default.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NativeError`] when encountering an error inside a [`NativeFunction`](crate::stdlib::NativeFunction)
+    fn call_cancellable(
+        &self,
+        name: &str,
+        params: &[Value],
+        _token: &CancellationToken,
+    ) -> NativeResult {
+        self.call(name, params)
+    }
+
     /// Checks if a variable with a matching name exists.
     fn variable_exists(&self, name: &str) -> bool;
 
     /// Checks if a function with a matching name and compatible arity exists.
     fn function_exists(&self, name: &str, arity: usize) -> FunctionResult;
+
+    /// Returns a deprecation note if the function with a matching name is deprecated.
+    fn function_deprecation(&self, _name: &str) -> Option<String> {
+        None
+    }
+
+    /// Checks if the function with a matching name receives parameters which
+    /// fail with an [`Error::UndefinedVariable`] as [`Value::Null`].
+    fn function_accepts_undefined(&self, _name: &str) -> bool {
+        false
+    }
+
+    /// Checks if the function with a matching name checks a range of its
+    /// parameters, see [`Function::checks_range`].
+    fn function_checks_range(&self, _name: &str) -> bool {
+        false
+    }
+
+    /// Get a [`ValueSet`] index of an array variable, used to answer `contains`
+    /// checks without searching the array.
+    fn variable_set(&self, _name: &str) -> Option<Arc<ValueSet>> {
+        None
+    }
+
+    /// Checks if an expression may read a variable. Reading a denied variable
+    /// fails with [`Error::VariableAccessDenied`] instead of being treated as undefined.
+    fn variable_allowed(&self, _name: &str) -> bool {
+        true
+    }
+}
+
+/// Wraps an [`Environment`] and records the names of all variables which were
+/// looked up, regardless of whether they exist.
+pub(crate) struct RecordingEnvironment<'a, E: Environment> {
+    environment: &'a E,
+    reads: RefCell<HashSet<String>>,
+}
+
+impl<'a, E: Environment> RecordingEnvironment<'a, E> {
+    pub(crate) fn new(environment: &'a E) -> Self {
+        Self {
+            environment,
+            reads: RefCell::default(),
+        }
+    }
+
+    pub(crate) fn into_reads(self) -> HashSet<String> {
+        self.reads.into_inner()
+    }
+}
+
+impl<E: Environment> Environment for RecordingEnvironment<'_, E> {
+    fn variable(&self, name: &str) -> Option<Arc<Value>> {
+        self.reads.borrow_mut().insert(name.to_string());
+        self.environment.variable(name)
+    }
+
+    fn call(&self, name: &str, params: &[Value]) -> NativeResult {
+        self.environment.call(name, params)
+    }
+
+    fn call_cancellable(
+        &self,
+        name: &str,
+        params: &[Value],
+        token: &CancellationToken,
+    ) -> NativeResult {
+        self.environment.call_cancellable(name, params, token)
+    }
+
+    fn variable_exists(&self, name: &str) -> bool {
+        self.environment.variable_exists(name)
+    }
+
+    fn function_exists(&self, name: &str, arity: usize) -> FunctionResult {
+        self.environment.function_exists(name, arity)
+    }
+
+    fn function_deprecation(&self, name: &str) -> Option<String> {
+        self.environment.function_deprecation(name)
+    }
+
+    fn function_accepts_undefined(&self, name: &str) -> bool {
+        self.environment.function_accepts_undefined(name)
+    }
+
+    fn function_checks_range(&self, name: &str) -> bool {
+        self.environment.function_checks_range(name)
+    }
+
+    fn variable_set(&self, name: &str) -> Option<Arc<ValueSet>> {
+        self.reads.borrow_mut().insert(name.to_string());
+        self.environment.variable_set(name)
+    }
+
+    fn variable_allowed(&self, name: &str) -> bool {
+        self.environment.variable_allowed(name)
+    }
+}
+
+/// Wraps an [`Environment`] and restricts the variables an expression may read
+/// to an allowlist. Function calls are passed through unchanged.
+///
+/// # Example
+/// ```
+/// use std::collections::HashSet;
+/// use slac::{compile, execute, Error, ScopedEnvironment, StaticEnvironment, Value};
+///
+/// let mut env = StaticEnvironment::default();
+/// env.add_variable("price", Value::Number(10.0));
+/// env.add_variable("internal_cost", Value::Number(7.0));
+///
+/// let allowlist = HashSet::from([String::from("price")]);
+/// let scoped = ScopedEnvironment::with_allowed_variables(&env, allowlist);
+///
+/// let ast = compile("price > 5").unwrap();
+/// assert_eq!(Ok(Value::Boolean(true)), execute(&scoped, &ast));
+///
+/// let ast = compile("price > internal_cost").unwrap();
+/// assert_eq!(
+///     Err(Error::VariableAccessDenied(String::from("internal_cost"))),
+///     execute(&scoped, &ast)
+/// );
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub struct ScopedEnvironment<'a, E: Environment> {
+    environment: &'a E,
+    allowed: HashSet<String>,
+}
+
+impl<'a, E: Environment> ScopedEnvironment<'a, E> {
+    /// Creates a [`ScopedEnvironment`] which only allows reading the variables
+    /// of the allowlist. Names are compared *case-insensitive*.
+    pub fn with_allowed_variables(environment: &'a E, allowlist: HashSet<String>) -> Self {
+        Self {
+            environment,
+            allowed: allowlist.iter().map(|name| get_env_key(name)).collect(),
+        }
+    }
+}
+
+impl<E: Environment> Environment for ScopedEnvironment<'_, E> {
+    fn variable(&self, name: &str) -> Option<Arc<Value>> {
+        if self.variable_allowed(name) {
+            self.environment.variable(name)
+        } else {
+            None
+        }
+    }
+
+    fn call(&self, name: &str, params: &[Value]) -> NativeResult {
+        self.environment.call(name, params)
+    }
+
+    fn call_cancellable(
+        &self,
+        name: &str,
+        params: &[Value],
+        token: &CancellationToken,
+    ) -> NativeResult {
+        self.environment.call_cancellable(name, params, token)
+    }
+
+    fn variable_exists(&self, name: &str) -> bool {
+        self.variable_allowed(name) && self.environment.variable_exists(name)
+    }
+
+    fn function_exists(&self, name: &str, arity: usize) -> FunctionResult {
+        self.environment.function_exists(name, arity)
+    }
+
+    fn function_deprecation(&self, name: &str) -> Option<String> {
+        self.environment.function_deprecation(name)
+    }
+
+    fn function_accepts_undefined(&self, name: &str) -> bool {
+        self.environment.function_accepts_undefined(name)
+    }
+
+    fn function_checks_range(&self, name: &str) -> bool {
+        self.environment.function_checks_range(name)
+    }
+
+    fn variable_set(&self, name: &str) -> Option<Arc<ValueSet>> {
+        if self.variable_allowed(name) {
+            self.environment.variable_set(name)
+        } else {
+            None
+        }
+    }
+
+    fn variable_allowed(&self, name: &str) -> bool {
+        self.allowed.contains(&get_env_key(name)) && self.environment.variable_allowed(name)
+    }
+}
+
+/// Layers a small map of variables over a shared [`Environment`], e.g. to
+/// execute the same [`Expression`] for many records without rebuilding or
+/// cloning the functions of the base environment.
+///
+/// Variables are looked up in the overlay first and then in the base, function
+/// calls are always passed to the base. A variable of the overlay hides a
+/// variable of the base with the same name, including its [`ValueSet`]. The
+/// base still decides which variables may be read.
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute, stdlib::extend_environment, OverlayEnvironment};
+/// use slac::{StaticEnvironment, Value};
+///
+/// let mut base = StaticEnvironment::default();
+/// extend_environment(&mut base);
+/// base.add_variable("limit", Value::Number(10.0));
+///
+/// let ast = compile("max(amount, 0) > limit").unwrap();
+///
+/// for (amount, expected) in [(12.0, true), (8.0, false)] {
+///     let env = OverlayEnvironment::new(&base).with_var("amount", Value::Number(amount));
+///     assert_eq!(Ok(Value::Boolean(expected)), execute(&env, &ast));
+/// }
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub struct OverlayEnvironment<'a, E: Environment> {
+    environment: &'a E,
+    variables: HashMap<String, Arc<Value>>,
+}
+
+impl<'a, E: Environment> OverlayEnvironment<'a, E> {
+    /// Creates an [`OverlayEnvironment`] without any variables of its own.
+    pub fn new(environment: &'a E) -> Self {
+        Self {
+            environment,
+            variables: HashMap::new(),
+        }
+    }
+
+    /// Adds or updates a single variable of the overlay.
+    #[must_use]
+    pub fn with_var(mut self, name: &str, value: Value) -> Self {
+        self.add_variable(name, value);
+        self
+    }
+
+    /// Adds or updates a single variable of the overlay.
+    pub fn add_variable(&mut self, name: &str, value: Value) {
+        self.variables.insert(get_env_key(name), Arc::new(value));
+    }
+
+    /// Clears all variables of the overlay, the base is unchanged.
+    pub fn clear_variables(&mut self) {
+        self.variables.clear();
+    }
+}
+
+impl<E: Environment> Environment for OverlayEnvironment<'_, E> {
+    fn variable(&self, name: &str) -> Option<Arc<Value>> {
+        match self.variables.get(&get_env_key(name)) {
+            Some(value) => Some(value.clone()),
+            None => self.environment.variable(name),
+        }
+    }
+
+    fn call(&self, name: &str, params: &[Value]) -> NativeResult {
+        self.environment.call(name, params)
+    }
+
+    fn call_cancellable(
+        &self,
+        name: &str,
+        params: &[Value],
+        token: &CancellationToken,
+    ) -> NativeResult {
+        self.environment.call_cancellable(name, params, token)
+    }
+
+    fn variable_exists(&self, name: &str) -> bool {
+        self.variables.contains_key(&get_env_key(name)) || self.environment.variable_exists(name)
+    }
+
+    fn function_exists(&self, name: &str, arity: usize) -> FunctionResult {
+        self.environment.function_exists(name, arity)
+    }
+
+    fn function_deprecation(&self, name: &str) -> Option<String> {
+        self.environment.function_deprecation(name)
+    }
+
+    fn function_accepts_undefined(&self, name: &str) -> bool {
+        self.environment.function_accepts_undefined(name)
+    }
+
+    fn function_checks_range(&self, name: &str) -> bool {
+        self.environment.function_checks_range(name)
+    }
+
+    fn variable_set(&self, name: &str) -> Option<Arc<ValueSet>> {
+        if self.variables.contains_key(&get_env_key(name)) {
+            None
+        } else {
+            self.environment.variable_set(name)
+        }
+    }
+
+    fn variable_allowed(&self, name: &str) -> bool {
+        self.environment.variable_allowed(name)
+    }
 }
 
 /// An [`Environment`] implementation in which all variables and functions are
 /// known ahead of execution. All variable and function names treated as *case-insensitive*.
+///
+/// Names which only differ in their case refer to the same variable or
+/// function, so adding `Amount` after `amount` replaces the earlier value. The
+/// execution and the validation with [`check_variables_and_functions`](crate::check_variables_and_functions)
+/// resolve `MAX(Amount)` and `max(amount)` to the same function and variable.
+///
+/// The environment is [`Send`] and [`Sync`], so a single environment can be
+/// shared by many threads executing expressions at the same time. Variables
+/// and functions are added through `&mut self` before it is shared.
 #[allow(clippy::module_name_repetitions)]
 #[derive(Default)]
 pub struct StaticEnvironment {
-    variables: HashMap<String, Rc<Value>>,
-    functions: HashMap<String, Rc<Function>>,
+    variables: HashMap<String, Arc<Value>>,
+    functions: HashMap<String, Arc<Function>>,
+    sets: HashMap<String, Arc<ValueSet>>,
+    boolean_strings: Option<(String, String)>,
+    #[cfg(feature = "random")]
+    random_state: Option<AtomicU64>,
+    #[cfg(feature = "chrono")]
+    clock: Option<NaiveDateTime>,
 }
 
 /// Transforms all variable and function names to lowercase for case-insensitive lookup.
-fn get_env_key(name: &str) -> String {
+pub(crate) fn get_env_key(name: &str) -> String {
     name.to_lowercase()
 }
 
 impl StaticEnvironment {
-    /// Adds or updates a single variable.
+    /// Creates an environment from declaration strings. Intended for test
+    /// fixtures and tooling, not for production use.
+    ///
+    /// * Variables: `name: Type = literal`. Without a literal, the variable
+    ///   holds the empty value of its type.
+    /// * Functions: `name(params): Type => body`, with params in the same format
+    ///   as the declaration of [`Function::new`]. The body is compiled once and
+    ///   evaluated on every call, ignoring the arguments.
+    ///
+    /// # Example
+    /// ```
+    /// use slac::{compile, execute, StaticEnvironment, Value};
+    ///
+    /// let env = StaticEnvironment::from_declarations(&[
+    ///     "amount: Number = 12.5",
+    ///     "name: String = 'x'",
+    ///     "lookup(key: String, fallback: String = ''): String => 'stub'",
+    /// ])
+    /// .unwrap();
+    ///
+    /// let ast = compile("amount > 10 and lookup(name) = 'stub'").unwrap();
+    /// assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidDeclaration`] on unknown types, invalid literals
+    /// or parameter lists and invalid function bodies.
+    pub fn from_declarations(declarations: &[&str]) -> Result<Self> {
+        let mut env = Self::default();
+
+        for declaration in declarations {
+            match declaration::parse(declaration)? {
+                Declaration::Variable(name, value) => env.add_variable(&name, value),
+                Declaration::Function(function) => {
+                    env.add_function(function);
+                }
+            }
+        }
+
+        Ok(env)
+    }
+
+    /// Adds or updates a single variable, replacing a variable whose name
+    /// only differs in its case.
     pub fn add_variable(&mut self, name: &str, value: Value) {
-        self.variables.insert(get_env_key(name), Rc::new(value));
+        let key = get_env_key(name);
+        self.sets.remove(&key);
+        self.variables.insert(key, Arc::new(value));
+    }
+
+    /// Adds or updates a variable holding a large collection of values.
+    ///
+    /// The variable is a sorted [`Value::Array`] without duplicates, but calls
+    /// of `contains(name, needle)` are answered by a [`ValueSet`] in constant
+    /// time instead of searching the array.
+    ///
+    /// # Example
+    /// ```
+    /// use slac::{compile, execute, stdlib::extend_environment, StaticEnvironment, Value};
+    ///
+    /// let mut env = StaticEnvironment::default();
+    /// extend_environment(&mut env);
+    /// env.add_set_var("blocked", (0..100_000).map(|id| Value::Number(f64::from(id))));
+    ///
+    /// let ast = compile("contains(blocked, 4711)").unwrap();
+    /// assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+    /// ```
+    pub fn add_set_var(&mut self, name: &str, values: impl IntoIterator<Item = Value>) {
+        let mut values: Vec<Value> = values.into_iter().collect();
+        values.sort();
+        values.dedup();
+
+        let set = ValueSet::from_iter(values.iter().cloned());
+        let key = get_env_key(name);
+
+        self.sets.insert(key.clone(), Arc::new(set));
+        self.variables.insert(key, Arc::new(Value::Array(values)));
     }
 
-    /// Removes a variable and return its [`Rc<Value>`] if it existed.
-    pub fn remove_variable(&mut self, name: &str) -> Option<Rc<Value>> {
-        self.variables.remove(&get_env_key(name))
+    /// Removes a variable and return its [`Arc<Value>`] if it existed.
+    pub fn remove_variable(&mut self, name: &str) -> Option<Arc<Value>> {
+        let key = get_env_key(name);
+        self.sets.remove(&key);
+        self.variables.remove(&key)
     }
 
     /// Clears all variables.
     pub fn clear_variables(&mut self) {
+        self.sets.clear();
         self.variables.clear();
     }
 
+    /// Copies all variables into a serializable [`VariableSnapshot`].
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub fn snapshot_variables(&self) -> VariableSnapshot {
+        VariableSnapshot {
+            variables: self
+                .variables
+                .iter()
+                .map(|(name, value)| (name.clone(), value.as_ref().clone()))
+                .collect(),
+        }
+    }
+
+    /// Replaces all variables with the variables of a [`VariableSnapshot`].
+    #[cfg(feature = "serde")]
+    pub fn restore_variables(&mut self, snapshot: &VariableSnapshot) {
+        self.clear_variables();
+
+        for (name, value) in &snapshot.variables {
+            self.add_variable(name, value.clone());
+        }
+    }
+
+    /// Sets the texts `str` converts `true` and `false` into during the
+    /// executions against this environment, e.g. `'Ja'` and `'Nein'`.
+    ///
+    /// # Remarks
+    ///
+    /// The [`Display`](std::fmt::Display) of a [`Value`] can not see the
+    /// environment and always renders `true` and `false`. Constant calls like
+    /// `str(true)` are folded by the optimizer with the texts of the
+    /// environment at the time of the optimization.
+    ///
+    /// # Example
+    /// ```
+    /// use slac::{compile, execute, stdlib::extend_environment, StaticEnvironment, Value};
+    ///
+    /// let mut env = StaticEnvironment::default();
+    /// extend_environment(&mut env);
+    /// env.set_boolean_strings("Ja", "Nein");
+    ///
+    /// let ast = compile("str(1 > 2)").unwrap();
+    /// assert_eq!(Ok(Value::String(String::from("Nein"))), execute(&env, &ast));
+    /// ```
+    pub fn set_boolean_strings(&mut self, true_text: &str, false_text: &str) {
+        self.boolean_strings = Some((true_text.to_string(), false_text.to_string()));
+    }
+
+    /// Converts a single boolean parameter with the texts of
+    /// [`Self::set_boolean_strings`], if the function [formats booleans](Function::formats_booleans).
+    fn call_with_boolean_strings(&self, function: &Function, params: &[Value]) -> Option<Value> {
+        let (true_text, false_text) = self.boolean_strings.as_ref()?;
+
+        match params {
+            [Value::Boolean(value)] if function.formats_booleans => {
+                let text = if *value { true_text } else { false_text };
+                Some(Value::String(text.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Replaces the operating system randomness of `random` and `choice` with
+    /// a deterministic [SplitMix64](https://prng.di.unimi.it/splitmix64.c)
+    /// generator starting at `seed`, e.g. to reproduce results in tests.
+    /// Every call advances the generator, so both functions stay impure.
+    ///
+    /// # Example
+    /// ```
+    /// use slac::{compile, execute, stdlib::extend_environment, StaticEnvironment};
+    ///
+    /// let seeded = |seed| {
+    ///     let mut env = StaticEnvironment::default();
+    ///     extend_environment(&mut env);
+    ///     env.set_random_seed(seed);
+    ///     env
+    /// };
+    /// let ast = compile("[random(), random(), choice(1, 2, 3)]").unwrap();
+    ///
+    /// assert_eq!(execute(&seeded(42), &ast), execute(&seeded(42), &ast));
+    /// assert_ne!(execute(&seeded(42), &ast), execute(&seeded(43), &ast));
+    /// ```
+    #[cfg(feature = "random")]
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.random_state = Some(AtomicU64::new(seed));
+    }
+
+    /// Calls `random` or `choice` with the generator of [`Self::set_random_seed`].
+    #[cfg(feature = "random")]
+    fn call_seeded(&self, function: &Function, params: &[Value]) -> Option<NativeResult> {
+        let state = self.random_state.as_ref()?;
+        let next = || {
+            let previous = state.fetch_add(SPLIT_MIX_GAMMA, Ordering::Relaxed);
+            split_mix64(previous.wrapping_add(SPLIT_MIX_GAMMA))
+        };
+
+        if function.name.eq_ignore_ascii_case("random") {
+            let range = default_number(params, 0, 1.0);
+            Some(range.map(|range| Value::Number(random_in_range(next(), range))))
+        } else if function.name.eq_ignore_ascii_case("choice") {
+            Some(choice_of(next(), params))
+        } else {
+            None
+        }
+    }
+
+    /// Fixes the local date and time returned by `now` and `today`, see
+    /// [`extend_environment_with_clock`](crate::stdlib::time::extend_environment_with_clock).
+    #[cfg(feature = "chrono")]
+    pub fn set_clock(&mut self, clock: NaiveDateTime) {
+        self.clock = Some(clock);
+    }
+
+    /// Calls `now` or `today` with the clock of [`Self::set_clock`].
+    #[cfg(feature = "chrono")]
+    fn call_with_clock(&self, function: &Function, params: &[Value]) -> Option<NativeResult> {
+        let clock = self.clock?;
+
+        if function.name.eq_ignore_ascii_case("now") {
+            Some(crate::stdlib::time::now_at(clock, params))
+        } else if function.name.eq_ignore_ascii_case("today") {
+            Some(crate::stdlib::time::today_at(clock, params))
+        } else {
+            None
+        }
+    }
+
     /// Adds or updates a [`NativeFunction`](crate::stdlib::NativeFunction).
-    pub fn add_function(&mut self, func: Function) {
+    ///
+    /// # Remarks
+    ///
+    /// Functions are identified by their case-insensitive name only. An existing
+    /// [`Function`] with the same name is replaced, regardless of its [`Arity`],
+    /// and returned.
+    pub fn add_function(&mut self, func: Function) -> Option<Arc<Function>> {
         self.functions
-            .insert(get_env_key(&func.name), Rc::new(func));
+            .insert(get_env_key(&func.name), Arc::new(func))
     }
 
-    /// Calls `add_function` for a `Vec<Function>`.
-    pub fn add_functions(&mut self, functions: Vec<Function>) {
+    /// Calls `add_function` for multiple functions in order. If multiple
+    /// functions share the same name, the last one is kept.
+    pub fn add_functions(&mut self, functions: impl IntoIterator<Item = Function>) {
         for func in functions {
             self.add_function(func);
         }
     }
 
+    /// Adds a deprecated alias for an already registered [`Function`].
+    /// Calls to the alias are executed by the aliased [`Function`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingFunction`] if no [`Function`] with a matching name exists.
+    pub fn add_alias(&mut self, alias: &str, name: &str) -> Result<()> {
+        let function = self
+            .functions
+            .get(&get_env_key(name))
+            .ok_or(Error::MissingFunction(name.to_string()))?;
+
+        let alias = Function {
+            name: alias.to_string(),
+            ..function.as_ref().clone()
+        }
+        .alias_for(&function.name);
+
+        self.add_function(alias);
+        Ok(())
+    }
+
     /// Removes a [`NativeFunction`](crate::stdlib::NativeFunction) and return
     /// its [`Function`] if it existed.
-    pub fn remove_function(&mut self, name: &str) -> Option<Rc<Function>> {
+    pub fn remove_function(&mut self, name: &str) -> Option<Arc<Function>> {
         self.functions.remove(&get_env_key(name))
     }
 
-    /// Output all currently registered [`Function`] structs as [`Rc`].
+    fn get_function(&self, name: &str) -> std::result::Result<&Function, NativeError> {
+        self.functions
+            .get(&get_env_key(name))
+            .map(AsRef::as_ref)
+            .ok_or(NativeError::FunctionNotFound(name.to_string()))
+    }
+
+    /// Evaluates the body of a [`Function::stub`].
+    fn call_stub(&self, body: &Expression) -> NativeResult {
+        crate::execute(self, body).map_err(|error| match error {
+            Error::NativeFunctionError(_, error) => error,
+            error => NativeError::from(error.to_string()),
+        })
+    }
+
+    /// Output all currently registered [`Function`] structs as [`Arc`].
     #[must_use]
-    pub fn list_functions(&self) -> Vec<Rc<Function>> {
+    pub fn list_functions(&self) -> Vec<Arc<Function>> {
         self.functions.values().cloned().collect()
     }
+
+    /// Collects the [`FunctionSignature`] of all registered functions.
+    /// Two environments with equal signature sets provide the same functions.
+    #[must_use]
+    pub fn signature_set(&self) -> BTreeSet<FunctionSignature> {
+        self.functions.values().map(|f| f.signature()).collect()
+    }
+
+    /// Describes all registered functions and variables in a serializable [`Catalog`].
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub fn catalog(&self) -> Catalog {
+        Catalog::new(
+            self.functions.values().map(AsRef::as_ref),
+            self.variables
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_ref())),
+        )
+    }
 }
 
 impl Environment for StaticEnvironment {
-    fn variable(&self, name: &str) -> Option<Rc<Value>> {
+    fn variable(&self, name: &str) -> Option<Arc<Value>> {
         self.variables.get(&get_env_key(name)).cloned()
     }
 
     fn call(&self, name: &str, params: &[Value]) -> NativeResult {
-        let function = self
-            .functions
-            .get(&get_env_key(name))
-            .ok_or(NativeError::FunctionNotFound(name.to_string()))?;
+        self.call_cancellable(name, params, &CancellationToken::default())
+    }
+
+    fn call_cancellable(
+        &self,
+        name: &str,
+        params: &[Value],
+        token: &CancellationToken,
+    ) -> NativeResult {
+        let function = self.get_function(name)?;
 
-        let call = function.func;
-        call(params)
+        if let Some(body) = &function.stub {
+            return self.call_stub(body);
+        }
+
+        if let Some(value) = self.call_with_boolean_strings(function, params) {
+            return Ok(value);
+        }
+
+        #[cfg(feature = "random")]
+        if let Some(result) = self.call_seeded(function, params) {
+            return result;
+        }
+
+        #[cfg(feature = "chrono")]
+        if let Some(result) = self.call_with_clock(function, params) {
+            return result;
+        }
+
+        match function.cancellable {
+            Some(call) => call(params, token),
+            None => (function.func)(params),
+        }
     }
 
     fn variable_exists(&self, name: &str) -> bool {
@@ -128,16 +779,37 @@ impl Environment for StaticEnvironment {
                         }
                     }
                 }
-                Arity::Variadic if param_count > 0 => FunctionResult::Exists {
+                Arity::None if param_count > 0 => FunctionResult::WrongArity { min: 0, max: 0 },
+                Arity::Variadic | Arity::None => FunctionResult::Exists {
                     pure: function.pure,
                 },
-                Arity::Variadic => FunctionResult::WrongArity { min: 1, max: 99 }, // variadic without parameters
-                Arity::None => FunctionResult::WrongArity { min: 0, max: 0 },
             }
         } else {
             FunctionResult::NotFound
         }
     }
+
+    fn function_deprecation(&self, name: &str) -> Option<String> {
+        self.functions
+            .get(&get_env_key(name))
+            .and_then(|function| function.deprecation())
+    }
+
+    fn function_accepts_undefined(&self, name: &str) -> bool {
+        self.functions
+            .get(&get_env_key(name))
+            .is_some_and(|function| function.accepts_undefined)
+    }
+
+    fn function_checks_range(&self, name: &str) -> bool {
+        self.functions
+            .get(&get_env_key(name))
+            .is_some_and(|function| function.checks_range)
+    }
+
+    fn variable_set(&self, name: &str) -> Option<Arc<ValueSet>> {
+        self.sets.get(&get_env_key(name)).cloned()
+    }
 }
 
 #[cfg(test)]
@@ -165,6 +837,69 @@ mod test {
         assert_eq!(Ok(Value::Boolean(false)), execute(&env, &ast));
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn static_variable_snapshot() {
+        let mut env = StaticEnvironment::default();
+        env.add_variable("Some_Var", Value::Number(42.0));
+
+        let snapshot = env.snapshot_variables();
+        env.add_variable("other_var", Value::Boolean(true));
+        env.restore_variables(&snapshot);
+
+        assert_eq!(snapshot, env.snapshot_variables());
+        assert!(env.variable_exists("some_var"));
+        assert!(!env.variable_exists("other_var"));
+    }
+
+    #[test]
+    fn overlay_variables() {
+        let mut base = StaticEnvironment::default();
+        crate::stdlib::extend_environment(&mut base);
+        base.add_variable("limit", Value::Number(10.0));
+        base.add_set_var("codes", [Value::String(String::from("a"))]);
+
+        let ast = compile("max(amount, 1) > limit and contains(codes, 'a')").unwrap();
+
+        // two overlays over the same base at the same time
+        let small = OverlayEnvironment::new(&base).with_var("amount", Value::Number(8.0));
+        let large = OverlayEnvironment::new(&base).with_var("Amount", Value::Number(12.0));
+        assert_eq!(Ok(Value::Boolean(false)), execute(&small, &ast));
+        assert_eq!(Ok(Value::Boolean(true)), execute(&large, &ast));
+        assert!(!base.variable_exists("amount"));
+
+        // the overlay hides the variable and the set of the base
+        let mut env = OverlayEnvironment::new(&base)
+            .with_var("amount", Value::Number(12.0))
+            .with_var("codes", Value::Array(vec![]));
+        assert!(env.variable_set("codes").is_none());
+        assert_eq!(Ok(Value::Boolean(false)), execute(&env, &ast));
+
+        env.clear_variables();
+        env.add_variable("amount", Value::Number(12.0));
+        assert!(env.variable_set("codes").is_some());
+        assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+    }
+
+    #[test]
+    fn static_names_case_insensitive() {
+        let mut env = StaticEnvironment::default();
+        crate::stdlib::extend_environment(&mut env);
+
+        env.add_variable("Amount", Value::Number(1.0));
+        env.add_variable("AMOUNT", Value::Number(2.0));
+        env.add_variable("amount", Value::Number(3.0));
+
+        let ast = compile("MAX(Amount, 0) = max(amount, 0) and Max(AMOUNT) = 3").unwrap();
+        assert!(crate::check_variables_and_functions(&env, &ast).is_ok());
+        assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+        assert_eq!(
+            Some(Value::Number(3.0)),
+            env.remove_variable("aMoUnT").as_deref().cloned()
+        );
+        assert!(!env.variable_exists("Amount"));
+    }
+
     #[test]
     fn static_functions() {
         fn test_func(_params: &[Value]) -> NativeResult {
@@ -181,4 +916,106 @@ mod test {
 
         assert_eq!(removed.name, registered.first().unwrap().name);
     }
+
+    #[test]
+    fn static_function_alias() {
+        let mut env = StaticEnvironment::default();
+        crate::stdlib::extend_environment(&mut env);
+
+        env.add_alias("maximum", "max").unwrap();
+
+        let ast = compile("maximum(10, 20) = max(10, 20)").unwrap();
+        assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+        assert_eq!(
+            Some(String::from("use \"max\" instead")),
+            env.function_deprecation("MAXIMUM")
+        );
+        assert_eq!(None, env.function_deprecation("max"));
+
+        assert_eq!(
+            Err(Error::MissingFunction(String::from("not_found"))),
+            env.add_alias("alias", "not_found")
+        );
+    }
+
+    #[test]
+    fn static_function_replace() {
+        fn test_func(_params: &[Value]) -> NativeResult {
+            unreachable!()
+        }
+        let mut env = StaticEnvironment::default();
+
+        assert!(env
+            .add_function(Function::new(test_func, Arity::None, "test()"))
+            .is_none());
+
+        let replaced = env
+            .add_function(Function::new(test_func, Arity::required(1), "TEST(a)"))
+            .unwrap();
+
+        assert_eq!(Arity::None, replaced.arity);
+        assert!(matches!(
+            env.function_exists("test", 0),
+            FunctionResult::WrongArity { min: 1, max: 1 }
+        ));
+    }
+
+    #[test]
+    fn static_functions_idempotent() {
+        let mut once = StaticEnvironment::default();
+        crate::stdlib::extend_environment(&mut once);
+
+        for _ in 0..10 {
+            let mut env = StaticEnvironment::default();
+            crate::stdlib::extend_environment(&mut env);
+            crate::stdlib::extend_environment(&mut env);
+
+            assert_eq!(once.list_functions().len(), env.list_functions().len());
+            assert_eq!(once.signature_set(), env.signature_set());
+        }
+    }
+
+    #[test]
+    fn static_functions_last_wins() {
+        fn test_func(_params: &[Value]) -> NativeResult {
+            unreachable!()
+        }
+        let mut env = StaticEnvironment::default();
+
+        env.add_functions(vec![
+            Function::new(test_func, Arity::None, "test()"),
+            Function::new(test_func, Arity::required(2), "Test(a, b)"),
+        ]);
+
+        assert_eq!(1, env.list_functions().len());
+        assert!(matches!(
+            env.function_exists("test", 2),
+            FunctionResult::Exists { pure: true }
+        ));
+    }
+
+    #[test]
+    fn static_signature_set() {
+        let mut env = StaticEnvironment::default();
+        crate::stdlib::extend_environment(&mut env);
+
+        let signatures = env.signature_set();
+        assert_eq!(crate::stdlib::builtins().len(), signatures.len());
+        assert_eq!(signatures, env.signature_set());
+
+        let mut other = StaticEnvironment::default();
+        crate::stdlib::extend_environment(&mut other);
+        assert_eq!(signatures, other.signature_set());
+
+        let max = other.remove_function("max").unwrap();
+        other.add_function(Function::impure(max.func, max.arity, &max.declaration()));
+
+        let other_signatures = other.signature_set();
+        let difference: Vec<_> = signatures.difference(&other_signatures).collect();
+        assert_eq!(vec![&max.signature()], difference);
+        assert_eq!(
+            "Function { name: \"max\", arity: Variadic, pure: true, declaration: \"max(...): Any\", .. }",
+            format!("{max:?}")
+        );
+    }
 }
@@ -2,9 +2,13 @@ use std::result;
 
 use thiserror::Error;
 
+use crate::message::{render, MessageCatalog};
 use crate::operator::Operator;
+use crate::scanner::Span;
 use crate::stdlib::NativeError;
 use crate::token::Token;
+use crate::value::ConversionError;
+use crate::value::Value;
 
 /// The error type for failures while scanning, compiling or validation slac
 /// expressions.
@@ -13,49 +17,210 @@ pub enum Error {
     #[error("unexpected end of file")]
     Eof,
     // scanner errors
-    #[error("\"{0}\" is not a valid character")]
-    InvalidCharacter(char),
+    #[error("error at {span}: \"{character}\" is not a valid character")]
+    InvalidCharacter { character: char, span: Span },
     #[error("\"{0}\" is not a valid number")]
     InvalidNumber(String),
-    #[error("unterminated string literal")]
-    UnterminatedStringLiteral,
+    #[error("\"{0}\" can not be represented as a number without losing precision")]
+    ImpreciseNumberLiteral(String),
+    #[error("error at {span}: unterminated string literal")]
+    UnterminatedStringLiteral { span: Span },
     #[error("encountered multiple expressions at Token \"{0:?}\"")]
     // compiler errors
     MultipleExpressions(Token),
-    #[error("\"{0:?}\" is not a valid prefix Token")]
-    NoValidPrefixToken(Token),
+    #[error("{}\"{token:?}\" is not a valid prefix Token", located(.span))]
+    NoValidPrefixToken { token: Token, span: Option<Span> },
     #[error("\"{0:?}\" is not a valid infix Token")]
     NoValidInfixToken(Token),
     #[error("\"{0:?}\" is not a valid call target")]
     CallNotOnVariable(Token),
     #[error("previous Token not found")]
     PreviousTokenNotFound,
-    #[error("invalid Token \"{0:?}\"")]
-    InvalidToken(Token),
+    #[error("{}invalid Token \"{token:?}\"", located(.span))]
+    InvalidToken { token: Token, span: Option<Span> },
     #[error("\"{0:?}\" is not a valid Operator")]
     TokenNotAnOperator(Token),
+    #[error("\"{0}\" is not a valid Operator")]
+    UnknownOperator(String),
+    #[error("can not chain comparison operators \"{0}\" and \"{1}\" with different directions")]
+    MixedComparisonChain(Operator, Operator),
+    #[error("{}empty parentheses, a grouping must contain an expression", located(.span))]
+    EmptyParentheses { span: Option<Span> },
+    #[error(
+        "{}missing list element at position {position}, remove the extra comma",
+        located(.span)
+    )]
+    EmptyArrayElement { position: usize, span: Option<Span> },
+    #[error(
+        "{}the expression ends with the operator \"{operator:?}\", its right operand is missing",
+        located(.span)
+    )]
+    TrailingOperator { operator: Token, span: Option<Span> },
+    #[error("{}missing operand after the operator \"{operator:?}\"", located(.span))]
+    MissingOperand { operator: Token, span: Option<Span> },
+    #[error("{}unbalanced brackets, expected a closing \"{expected:?}\"", located(.span))]
+    UnbalancedParen { expected: Token, span: Option<Span> },
+    #[error("{}missing the keyword \"{expected:?}\" of the if expression", located(.span))]
+    MissingKeyword { expected: Token, span: Option<Span> },
     #[error("missing variable \"{0}\"")]
     // validation errors
     MissingVariable(String),
     #[error("missing function \"{0}\"")]
     MissingFunction(String),
-    #[error("expected {1} to {2} parameters but got {3} for function \"{0}\"")]
-    ParamCountMismatch(String, usize, usize, usize), // name, expected, found
+    #[error(
+        "function \"{name}\" expects {expected_min} to {expected_max} parameters but got {found}"
+    )]
+    ParamCountMismatch {
+        name: String,
+        expected_min: usize,
+        expected_max: usize,
+        found: usize,
+    },
+    #[error("invalid declaration \"{0}\": {1}")]
+    InvalidDeclaration(String, String), // declaration, reason
+    #[error("column \"{0}\" has {1} rows but expected {2}")]
+    ColumnLengthMismatch(String, usize, usize), // name, found, expected
+    #[error("variable \"{0}\" matches multiple columns which only differ in case")]
+    AmbiguousColumn(String),
     #[error("invalid unary operator \"{0:?}\"")]
     InvalidUnaryOperator(Operator),
     #[error("invalid binary operator \"{0:?}\"")]
     InvalidBinaryOperator(Operator),
     #[error("invalid ternary operator \"{0:?}\"")]
     InvalidTernaryOperator(Operator),
+    #[error("invalid {kind} node: {reason}")]
+    InvalidNode { kind: &'static str, reason: String },
     #[error("top level expression does not return a boolean value")]
     LiteralNotBoolean,
     // runtime errors
+    #[error("can not apply operator \"{0:?}\" to {1} and {2}")]
+    IncomparableTypes(Operator, &'static str, &'static str),
+    #[error("execution was cancelled")]
+    Cancelled,
+    #[error("the expression exceeds the maximum nesting depth of {0}")]
+    RecursionLimitExceeded(usize),
     #[error("undefined variable \"{0}\"")]
     UndefinedVariable(String),
+    #[error("access to variable \"{0}\" is denied")]
+    VariableAccessDenied(String),
+    #[error("call of function \"{name}\" is denied: {reason}")]
+    FunctionCallDenied { name: String, reason: String },
     #[error("native function \"{0}\" encountered an error: \"{1}\"")]
     NativeFunctionError(String, NativeError),
+    #[error("operator \"{0:?}\" resulted in a non-finite number")]
+    NonFiniteNumber(Operator),
+    #[error("function \"{0}\" returned a non-finite number")]
+    NonFiniteResult(String),
+    #[error("expected a {expected} result but got a {found}")]
+    ResultTypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    // debugging errors
+    #[error("invalid debug bundle: {0}")]
+    InvalidDebugBundle(String),
+    #[error("replay returned \"{1}\" but \"{0}\" was recorded")]
+    ReplayMismatch(Value, Value), // expected, found
+    #[error(
+        "unsupported schema version {0}, the latest version is {}",
+        crate::ast::SCHEMA_VERSION
+    )]
+    UnsupportedSchemaVersion(u32),
+    #[error("invalid serialized expression: {0}")]
+    InvalidSerializedExpression(String),
+    // binary format errors
+    #[error("unsupported binary format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("invalid binary expression: {0}")]
+    InvalidBinary(String),
+}
+
+impl From<ConversionError> for Error {
+    fn from(error: ConversionError) -> Self {
+        Error::ResultTypeMismatch {
+            expected: error.expected,
+            found: error.found,
+        }
+    }
+}
+
+/// Prefixes the message of a compiler error with its position, if the
+/// [`Tokens`](Token) were compiled together with their [`Spans`](Span).
+fn located(span: &Option<Span>) -> String {
+    span.map(|span| format!("error at {span}: "))
+        .unwrap_or_default()
+}
+
+impl Error {
+    /// The position of the error in the source, e.g. to underline the
+    /// offending part of a script. Returns `None` if the position is unknown.
+    ///
+    /// An unbalanced bracket or a missing keyword is located at its opening
+    /// bracket or keyword. The errors of invalid number literals and the
+    /// remaining compiler errors, like [`Error::MultipleExpressions`] or
+    /// [`Error::MixedComparisonChain`], carry no position.
+    ///
+    /// # Examples
+    /// ```
+    /// use slac::{compile, Span};
+    ///
+    /// let error = compile("1 +\n  $").unwrap_err();
+    ///
+    /// assert_eq!("error at line 2, column 3: \"$\" is not a valid character", error.to_string());
+    /// assert_eq!(Some(Span { start: 6, end: 7, line: 2, column: 3 }), error.span());
+    /// ```
+    #[must_use]
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::InvalidCharacter { span, .. } | Error::UnterminatedStringLiteral { span } => {
+                Some(*span)
+            }
+            Error::NoValidPrefixToken { span, .. }
+            | Error::InvalidToken { span, .. }
+            | Error::EmptyParentheses { span }
+            | Error::EmptyArrayElement { span, .. }
+            | Error::TrailingOperator { span, .. }
+            | Error::MissingOperand { span, .. }
+            | Error::UnbalancedParen { span, .. }
+            | Error::MissingKeyword { span, .. } => *span,
+            _ => None,
+        }
+    }
+
+    /// Checks if the error ends the execution, even inside of `try`.
+    pub(crate) fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            Error::Cancelled
+                | Error::RecursionLimitExceeded(_)
+                | Error::VariableAccessDenied(_)
+                | Error::FunctionCallDenied { .. }
+        )
+    }
+
+    /// Renders the error with the templates of a [`MessageCatalog`].
+    ///
+    /// # Remarks
+    ///
+    /// Only errors of native functions are identified by message keys, all
+    /// other errors are rendered with their English [`Display`](std::fmt::Display).
+    #[must_use]
+    pub fn render_with(&self, catalog: &dyn MessageCatalog) -> String {
+        match self {
+            Error::NativeFunctionError(name, error) => render(
+                catalog,
+                "err.native",
+                &[name.clone(), error.render_with(catalog)],
+            ),
+            error => error.to_string(),
+        }
+    }
 }
 
 /// A specialized [`Result`] type for [`Errors`](enum@Error) during the scanning, compiling or
 /// validation phase.
-pub type Result<T> = result::Result<T, Error>;
+///
+/// The error type defaults to [`Error`](enum@Error) but can be replaced, so a glob import
+/// of the alias does not shadow `std::result::Result<T, E>`. The alias is also
+/// exported as `SlacResult`.
+pub type Result<T, E = Error> = result::Result<T, E>;
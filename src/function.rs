@@ -1,9 +1,12 @@
 //! Wrapper structs for native [`Function`] definitions.
 
-use crate::stdlib::NativeFunction;
+use std::sync::Arc;
+
+use crate::stdlib::{CancellableFunction, NativeError, NativeFunction, NativeResult};
+use crate::{Expression, Value};
 
 /// The [Arity](https://en.wikipedia.org/wiki/Arity) of a [`NativeFunction`].
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Arity {
     Polyadic { required: usize, optional: usize },
     Variadic,
@@ -35,6 +38,52 @@ pub struct Function {
     pub arity: Arity,
     pub params: String,
     pub pure: bool,
+    /// The note of a deprecated `Function`, see [`Function::deprecation`].
+    pub(crate) deprecated: Option<&'static str>,
+    /// The name of the replacement of a deprecated alias, see [`Function::deprecation`].
+    pub(crate) alias_for: Option<String>,
+    pub module: Option<&'static str>,
+    pub cancellable: Option<CancellableFunction>,
+    /// The body of a [`Function::stub`].
+    pub stub: Option<Arc<Expression>>,
+    /// Receives undefined variables as [`Value::Null`], see [`Function::accepts_undefined`].
+    pub accepts_undefined: bool,
+    /// Converts a boolean with the texts of the environment, see [`Function::formats_booleans`].
+    pub(crate) formats_booleans: bool,
+    /// Orders its parameters like `between`, see [`Function::checks_range`].
+    pub(crate) checks_range: bool,
+}
+
+/// The comparable parts of a [`Function`], used to check if two environments
+/// provide the same functions.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FunctionSignature {
+    pub name: String,
+    pub arity: Arity,
+    pub pure: bool,
+}
+
+impl std::fmt::Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("pure", &self.pure)
+            .field("declaration", &self.declaration())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Placeholder for the [`NativeFunction`] of a [`Function::cancellable`], which
+/// is never called by the [`StaticEnvironment`](crate::StaticEnvironment).
+fn requires_cancellation(_params: &[Value]) -> NativeResult {
+    Err(NativeError::keyed("err.function.cancellable", []))
+}
+
+/// Placeholder for the [`NativeFunction`] of a [`Function::stub`], which
+/// is never called by the [`StaticEnvironment`](crate::StaticEnvironment).
+fn requires_environment(_params: &[Value]) -> NativeResult {
+    Err(NativeError::keyed("err.function.stub", []))
 }
 
 impl Function {
@@ -55,6 +104,14 @@ impl Function {
             arity,
             params,
             pure: true,
+            deprecated: None,
+            alias_for: None,
+            module: None,
+            cancellable: None,
+            stub: None,
+            accepts_undefined: false,
+            formats_booleans: false,
+            checks_range: false,
         }
     }
 
@@ -67,6 +124,127 @@ impl Function {
             ..Self::new(func, arity, declaration)
         }
     }
+
+    /// Creates a pure `Function` which receives the [`CancellationToken`](crate::CancellationToken)
+    /// of the current execution. Without a cancellable execution, the function
+    /// receives a token which is never cancelled.
+    ///
+    /// See also: [`Function::new`]
+    #[must_use]
+    pub fn cancellable(func: CancellableFunction, arity: Arity, declaration: &str) -> Self {
+        Self {
+            cancellable: Some(func),
+            ..Self::new(requires_cancellation, arity, declaration)
+        }
+    }
+
+    /// Creates a pure `Function` which ignores its parameters and evaluates
+    /// the body [`Expression`] against the [`StaticEnvironment`](crate::StaticEnvironment)
+    /// on every call. Intended for test fixtures, see
+    /// [`StaticEnvironment::from_declarations`](crate::StaticEnvironment::from_declarations).
+    ///
+    /// See also: [`Function::new`]
+    #[must_use]
+    pub fn stub(body: Expression, arity: Arity, declaration: &str) -> Self {
+        Self {
+            stub: Some(Arc::new(body)),
+            ..Self::new(requires_environment, arity, declaration)
+        }
+    }
+
+    /// Marks the `Function` as deprecated with an explanatory note.
+    #[must_use]
+    pub fn deprecated(self, note: &'static str) -> Self {
+        Self {
+            deprecated: Some(note),
+            ..self
+        }
+    }
+
+    /// Marks the `Function` as a deprecated alias of another function.
+    #[must_use]
+    pub fn alias_for(self, name: &str) -> Self {
+        Self {
+            alias_for: Some(name.to_string()),
+            ..self
+        }
+    }
+
+    /// Sets the name of the module which provides the `Function`.
+    #[must_use]
+    pub fn module(self, module: &'static str) -> Self {
+        Self {
+            module: Some(module),
+            ..self
+        }
+    }
+
+    /// Lets the `Function` receive a [`Value::Null`] for a parameter which
+    /// fails with an undefined variable, instead of failing the whole call.
+    /// Used by `coalesce` to replace missing variables with a default.
+    #[must_use]
+    pub fn accepts_undefined(self) -> Self {
+        Self {
+            accepts_undefined: true,
+            ..self
+        }
+    }
+
+    /// Lets the [`StaticEnvironment`](crate::StaticEnvironment) convert a single
+    /// [`Value::Boolean`] parameter into its [boolean strings](crate::StaticEnvironment::set_boolean_strings)
+    /// instead of calling the function. Used by `str`.
+    #[must_use]
+    pub fn formats_booleans(self) -> Self {
+        Self {
+            formats_booleans: true,
+            ..self
+        }
+    }
+
+    /// Marks the `Function` as checking if its first parameter lies within the
+    /// range of the second and third parameter. With
+    /// [`ExecutionOptions::strict_type_comparisons`](crate::ExecutionOptions::strict_type_comparisons),
+    /// bounds which are not comparable to the value are rejected before the
+    /// call. Used by `between`.
+    #[must_use]
+    pub fn checks_range(self) -> Self {
+        Self {
+            checks_range: true,
+            ..self
+        }
+    }
+
+    /// Returns the name of the `Function`, which is used to call it.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the [`FunctionSignature`] of the `Function`.
+    #[must_use]
+    pub fn signature(&self) -> FunctionSignature {
+        FunctionSignature {
+            name: self.name.clone(),
+            arity: self.arity,
+            pure: self.pure,
+        }
+    }
+
+    /// Returns the full declaration string of the `Function`.
+    #[must_use]
+    pub fn declaration(&self) -> String {
+        format!("{}{}", self.name, self.params)
+    }
+
+    /// Returns the deprecation note if the `Function` is deprecated or an alias.
+    #[must_use]
+    pub fn deprecation(&self) -> Option<String> {
+        match (self.deprecated, &self.alias_for) {
+            (Some(note), _) => Some(note.to_string()),
+            (None, Some(name)) => Some(format!("use \"{name}\" instead")),
+            (None, None) => None,
+        }
+    }
 }
 
 fn parse_declaration(declaration: &str) -> (String, String) {
@@ -97,5 +275,25 @@ mod test {
         let func = Function::new(test_func, Arity::None, "only_name");
         assert_eq!("only_name", func.name);
         assert_eq!("", func.params);
+        assert_eq!("only_name", func.declaration());
+    }
+
+    #[test]
+    fn deprecated_function() {
+        fn test_func(_params: &[Value]) -> NativeResult {
+            unreachable!()
+        }
+
+        let func = Function::new(test_func, Arity::None, "some_name()");
+        assert_eq!(None, func.deprecation());
+
+        let func = func.deprecated("do not use");
+        assert_eq!(Some(String::from("do not use")), func.deprecation());
+
+        let func = Function::new(test_func, Arity::None, "old_name()").alias_for("new_name");
+        assert_eq!(
+            Some(String::from("use \"new_name\" instead")),
+            func.deprecation()
+        );
     }
 }
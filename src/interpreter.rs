@@ -1,29 +1,292 @@
+//! The [`TreeWalkingInterpreter`] and the [`FlatInterpreter`], which evaluate
+//! an AST to a single [`Value`].
+//!
+//! # Undefined variables
+//!
+//! Reading a variable unknown to the [`Environment`] fails with an
+//! [`Error::UndefinedVariable`]. Operators and function calls pass this error
+//! on unchanged, so an operand *containing* an undefined variable, like
+//! `missing + 1`, `-missing` or `lowercase(missing)`, is undefined as a whole.
+//!
+//! An undefined operand is only accepted in the following places, everywhere
+//! else the [`Error::UndefinedVariable`] is returned:
+//!
+//! | Expression                 | Result                              |
+//! |----------------------------|-------------------------------------|
+//! | `defined = undefined`      | `true` if `defined` is empty        |
+//! | `undefined = defined`      | `true` if `defined` is empty        |
+//! | `undefined = undefined`    | `true`                              |
+//! | `defined <> undefined`     | `true` if `defined` is not empty    |
+//! | `undefined <> defined`     | `true` if `defined` is not empty    |
+//! | `undefined <> undefined`   | `false`                             |
+//! | `undefined is empty`       | `true`                              |
+//! | `undefined is not empty`   | `false`                             |
+//! | `undefined and right`      | `false`, `right` is not evaluated   |
+//! | `undefined or right`       | the result of `right`               |
+//! | `undefined implies right`  | `true`, `right` is not evaluated    |
+//! | `try(undefined, fallback)` | the result of `fallback`            |
+//!
+//! A [`Value`] is empty if it equals the [`Value::empty`] value of its type,
+//! i.e. `false`, `''`, `0` or `[]`. If the other operand of `=` or `<>` fails
+//! with a different [`Error`], that error is returned instead.
+//!
+//! # Null
+//!
+//! A variable may also hold an explicit [`Value::Null`], which is a regular
+//! value instead of an error. `null` is ordered below all other values and
+//! propagated by the arithmetic operators, so `null + 1` is `null`. The `=`
+//! and `<>` operators, `in` and `contains` treat `null` as equal to every
+//! empty value, see [`Value::equals`].
+
+use std::{cell::Cell, fmt, ops::Deref, sync::Arc};
+
 use crate::{
-    ast::Expression, environment::Environment, operator::Operator, value::Value, Error, Result,
+    ast::{Expression, FlatExpression, Node},
+    cancellation::CancellationToken,
+    environment::Environment,
+    operator::Operator,
+    stdlib::common::{at, AT, CONTAINS, TRY},
+    value::Value,
+    value_set::ValueSet,
+    Error, Result,
 };
 
+/// The default of [`ExecutionOptions::max_depth`], also the maximum nesting
+/// depth of the operands accepted by the [`Compiler`](crate::Compiler).
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// Options to change the behaviour of the interpreter during execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionOptions {
+    /// Returns an [`Error::IncomparableTypes`] instead of ordering values of
+    /// different kinds by their ordinal ranking. Applies to `<`, `<=`, `>`, `>=`
+    /// and the `between` function.
+    pub strict_type_comparisons: bool,
+    /// Consulted before every call of an [`Environment`] function, a rejected
+    /// call fails with an [`Error::FunctionCallDenied`].
+    pub call_guard: Option<CallGuard>,
+    /// The maximum nesting depth of the evaluated nodes, a deeper expression
+    /// fails with an [`Error::RecursionLimitExceeded`] instead of overflowing
+    /// the stack. Defaults to [`DEFAULT_MAX_DEPTH`].
+    pub max_depth: usize,
+    /// Returns an [`Error::NonFiniteNumber`] if an arithmetic operator results
+    /// in `NaN` or an infinite number, e.g. `1 / 0`, and an
+    /// [`Error::NonFiniteResult`] if a function returns such a number, e.g.
+    /// `ln(-1)`. Non-finite numbers from variables or literals, like the `inf`
+    /// constant, are only rejected once they are calculated with.
+    pub reject_non_finite_numbers: bool,
+}
+
+impl Default for ExecutionOptions {
+    fn default() -> Self {
+        Self {
+            strict_type_comparisons: false,
+            call_guard: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            reject_non_finite_numbers: false,
+        }
+    }
+}
+
+type GuardFn = dyn Fn(&str, &[Value]) -> std::result::Result<(), String> + Send + Sync;
+
+/// Decides if a function of the [`Environment`] may be called with the
+/// evaluated parameters, or returns the reason of the rejection.
+///
+/// # Remarks
+///
+/// The guard sees every call the interpreter makes, including calls inside
+/// the branches of `if_then` and the parameters of `try`. Calls which are
+/// skipped by short-circuiting are never passed to the guard. Functions
+/// evaluated ahead of time by [`optimize`](crate::optimize) are not guarded.
+///
+/// Function names are case-insensitive, so the guard always receives the
+/// lowercase name.
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute_with_options, CallGuard, Error, ExecutionOptions};
+/// use slac::{stdlib::extend_environment, StaticEnvironment};
+///
+/// let mut env = StaticEnvironment::default();
+/// extend_environment(&mut env);
+///
+/// let ast = compile("random() < 2").unwrap();
+/// let options = ExecutionOptions {
+///     call_guard: Some(CallGuard::new(|name, _| match name {
+///         "random" => Err(String::from("not allowed")),
+///         _ => Ok(()),
+///     })),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(
+///     Err(Error::FunctionCallDenied {
+///         name: String::from("random"),
+///         reason: String::from("not allowed")
+///     }),
+///     execute_with_options(&env, &ast, options)
+/// );
+/// ```
+#[derive(Clone)]
+pub struct CallGuard(Arc<GuardFn>);
+
+impl CallGuard {
+    /// Creates a guard from a callback returning the reason of a rejection.
+    pub fn new(
+        guard: impl Fn(&str, &[Value]) -> std::result::Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(guard))
+    }
+
+    /// Checks a call, returns an [`Error::FunctionCallDenied`] if rejected.
+    fn check(&self, name: &str, params: &[Value]) -> Result<()> {
+        (self.0)(&name.to_lowercase(), params).map_err(|reason| Error::FunctionCallDenied {
+            name: name.to_string(),
+            reason,
+        })
+    }
+}
+
+impl fmt::Debug for CallGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CallGuard")
+    }
+}
+
+/// Two guards are equal if they share the same callback.
+impl PartialEq for CallGuard {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CallGuard {}
+
+/// A [`Value`] which is borrowed from a literal or a variable if possible,
+/// instead of being cloned.
+pub(crate) enum Operand<'e> {
+    Borrowed(&'e Value),
+    Shared(Arc<Value>),
+    Owned(Value),
+}
+
+impl Operand<'_> {
+    fn into_owned(self) -> Value {
+        match self {
+            Operand::Borrowed(value) => value.clone(),
+            Operand::Shared(value) => Arc::try_unwrap(value).unwrap_or_else(|v| v.as_ref().clone()),
+            Operand::Owned(value) => value,
+        }
+    }
+}
+
+impl Deref for Operand<'_> {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Operand::Borrowed(value) => value,
+            Operand::Shared(value) => value,
+            Operand::Owned(value) => value,
+        }
+    }
+}
+
 /// A simple recursive tree walking interpreter.
 /// Given an [`Environment`] and an [`AST`](Expression) recursivly walks the tree
 /// and computes a single output [`Value`].
 ///
+/// The `CANCELLABLE` variant checks a [`CancellationToken`] on every visited
+/// node, the default variant does not contain any of these checks.
+///
 /// # Errors
 ///
 /// Returns an [`Error`] if the execution fails.
 #[allow(clippy::module_name_repetitions)]
-pub struct TreeWalkingInterpreter<'a> {
+pub struct TreeWalkingInterpreter<'a, const CANCELLABLE: bool = false> {
     environment: &'a dyn Environment,
+    options: ExecutionOptions,
+    cancellation: Option<&'a CancellationToken>,
+    depth: Cell<usize>,
 }
 
 impl<'a> TreeWalkingInterpreter<'a> {
     pub fn new(environment: &'a dyn Environment) -> Self {
-        Self { environment }
+        Self {
+            environment,
+            options: ExecutionOptions::default(),
+            cancellation: None,
+            depth: Cell::new(0),
+        }
     }
 
     pub fn interprete(env: &impl Environment, expression: &Expression) -> Result<Value> {
-        TreeWalkingInterpreter::new(env).expression(expression)
+        TreeWalkingInterpreter::<false>::new(env).expression(expression)
+    }
+
+    pub fn interprete_with_options(
+        env: &impl Environment,
+        expression: &Expression,
+        options: ExecutionOptions,
+    ) -> Result<Value> {
+        TreeWalkingInterpreter::<false> {
+            options,
+            ..TreeWalkingInterpreter::<false>::new(env)
+        }
+        .expression(expression)
+    }
+}
+
+impl<'a> TreeWalkingInterpreter<'a, true> {
+    pub fn interprete_cancellable(
+        env: &impl Environment,
+        expression: &Expression,
+        token: &CancellationToken,
+    ) -> Result<Value> {
+        TreeWalkingInterpreter::<true> {
+            environment: env,
+            options: ExecutionOptions::default(),
+            cancellation: Some(token),
+            depth: Cell::new(0),
+        }
+        .expression(expression)
+    }
+}
+
+impl<'a, const CANCELLABLE: bool> TreeWalkingInterpreter<'a, CANCELLABLE> {
+    fn check_cancelled(&self) -> Result<()> {
+        match self.cancellation {
+            Some(token) if token.is_cancelled() => Err(Error::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluates a node one level deeper than the current node, fails if the
+    /// maximum depth is reached.
+    fn nested(&self, evaluate: impl FnOnce() -> Result<Value>) -> Result<Value> {
+        let depth = self.depth.get();
+
+        if depth >= self.options.max_depth {
+            return Err(Error::RecursionLimitExceeded(self.options.max_depth));
+        }
+
+        self.depth.set(depth + 1);
+        let result = evaluate();
+        self.depth.set(depth);
+
+        result
     }
 
     fn expression(&self, expression: &Expression) -> Result<Value> {
+        if CANCELLABLE {
+            self.check_cancelled()?;
+        }
+
+        self.nested(|| self.evaluate(expression))
+    }
+
+    fn evaluate(&self, expression: &Expression) -> Result<Value> {
         match expression {
             Expression::Unary { right, operator } => self.unary(right, *operator),
             Expression::Binary {
@@ -37,48 +300,180 @@ impl<'a> TreeWalkingInterpreter<'a> {
                 right,
                 operator,
             } => self.ternary(left, middle, right, *operator),
+            Expression::Chain {
+                expressions,
+                operators,
+            } => self.chain(expressions, operators),
             Expression::Array { expressions } => self.array(expressions),
             Expression::Literal { value } => Ok(value.clone()),
             Expression::Variable { name } => self.variable(name),
             Expression::Call { name, params } => self.call(name, params),
+            Expression::Index { target, index } => {
+                element(self.expression(target)?, self.expression(index)?)
+            }
         }
     }
 
     fn unary(&self, right: &Expression, operator: Operator) -> Result<Value> {
-        let right = self.expression(right);
+        unary(operator, self.expression(right))
+    }
 
-        match (operator, right) {
-            (Operator::Minus, Ok(rhs)) => -rhs,
-            (Operator::Not, Ok(rhs)) => !rhs,
-            _ => Err(Error::InvalidUnaryOperator(operator)),
+    fn binary(&self, left: &Expression, right: &Expression, operator: Operator) -> Result<Value> {
+        match (operator, left, right) {
+            (
+                Operator::Plus,
+                Expression::Binary {
+                    operator: Operator::Plus,
+                    ..
+                },
+                _,
+            ) => return self.plus(left, right),
+            (
+                Operator::Equal
+                | Operator::NotEqual
+                | Operator::Greater
+                | Operator::GreaterEqual
+                | Operator::Less
+                | Operator::LessEqual,
+                Expression::Variable { name: _ } | Expression::Literal { value: _ },
+                Expression::Literal { value: _ },
+            )
+            | (
+                Operator::Equal
+                | Operator::NotEqual
+                | Operator::Greater
+                | Operator::GreaterEqual
+                | Operator::Less
+                | Operator::LessEqual,
+                Expression::Literal { value: _ },
+                Expression::Variable { name: _ },
+            ) => {
+                return self.compare_operands(operator, self.operand(left), || self.operand(right))
+            }
+            (Operator::In, _, Expression::Variable { name }) => {
+                if let Some(found) = self.contains_in_set(name, || self.operand(left))? {
+                    return Ok(Value::Boolean(found));
+                }
+            }
+            _ => (),
         }
+
+        self.binary_values(operator, self.expression(left), || self.expression(right))
     }
 
-    fn binary(&self, left: &Expression, right: &Expression, operator: Operator) -> Result<Value> {
-        let left = self.expression(left);
+    /// Collects the operands of a left-leaning chain of `+` operators like
+    /// `a + b + c`, see [`Self::plus_chain`].
+    fn plus(&self, left: &Expression, right: &Expression) -> Result<Value> {
+        let mut operands = vec![right];
+        let mut first = left;
+
+        while let Expression::Binary {
+            left,
+            right,
+            operator: Operator::Plus,
+        } = first
+        {
+            operands.push(right);
+            first = left;
+        }
+
+        let operands = operands.into_iter().rev();
+        self.plus_chain(
+            self.operand(first)?,
+            operands.map(|operand| self.operand(operand)),
+        )
+    }
+
+    /// Evaluates an [`Expression`] without cloning the [`Value`] of a literal
+    /// or a variable.
+    fn operand<'e>(&'e self, expression: &'e Expression) -> Result<Operand<'e>> {
+        match expression {
+            Expression::Literal { value } => Ok(Operand::Borrowed(value)),
+            Expression::Variable { name } => self.lookup(name),
+            expression => self.expression(expression).map(Operand::Owned),
+        }
+    }
+
+    fn chain(&self, expressions: &[Expression], operators: &[Operator]) -> Result<Value> {
+        let operands = expressions
+            .iter()
+            .map(|expression| self.operand(expression));
+        self.chain_operands(operands, operators)
+    }
 
+    fn ternary(
+        &self,
+        left: &Expression,
+        middle: &Expression,
+        right: &Expression,
+        operator: Operator,
+    ) -> Result<Value> {
+        ternary(
+            operator,
+            || self.expression(left),
+            || self.expression(middle),
+            || self.expression(right),
+        )
+    }
+
+    fn get_values(&self, expressions: &[Expression]) -> Result<Vec<Value>> {
+        expressions
+            .iter()
+            .map(|expression| self.expression(expression))
+            .collect::<Result<_>>()
+    }
+
+    fn array(&self, expressions: &[Expression]) -> Result<Value> {
+        Ok(Value::Array(self.get_values(expressions)?))
+    }
+
+    fn call(&self, name: &str, expressions: &[Expression]) -> Result<Value> {
+        if let [first, second] = expressions {
+            if name.eq_ignore_ascii_case(TRY) {
+                return try_fallback(self.expression(first), || self.expression(second));
+            }
+
+            if let (true, Expression::Variable { name: haystack }) =
+                (name.eq_ignore_ascii_case(CONTAINS), first)
+            {
+                if let Some(found) = self.contains_in_set(haystack, || self.operand(second))? {
+                    return Ok(Value::Boolean(found));
+                }
+            }
+        }
+
+        let params = expressions
+            .iter()
+            .map(|expression| self.argument(name, self.expression(expression)))
+            .collect::<Result<Vec<_>>>()?;
+
+        self.call_native(name, &params)
+    }
+}
+
+/// The evaluation of the operators and function calls on already evaluated
+/// operands, shared by the [`TreeWalkingInterpreter`], the [`FlatInterpreter`]
+/// and the [`VirtualMachine`](crate::vm::VirtualMachine). Operands which are
+/// not always evaluated are passed as closures.
+impl<'a, const CANCELLABLE: bool> TreeWalkingInterpreter<'a, CANCELLABLE> {
+    /// Applies a binary operator, following the rules for undefined variables.
+    /// The right operand is skipped by short-circuiting `and`, `or` and `implies`.
+    pub(crate) fn binary_values(
+        &self,
+        operator: Operator,
+        left: Result<Value>,
+        right: impl FnOnce() -> Result<Value>,
+    ) -> Result<Value> {
         match (operator, left) {
-            (Operator::And, Ok(left)) => self.boolean::<true>(&left, right),
+            (Operator::And, Ok(left)) => boolean::<true>(&left, right),
             (Operator::And, Err(Error::UndefinedVariable(_))) => Ok(Value::Boolean(false)), // short circuit to false
-            (Operator::Or, Ok(left)) => self.boolean::<false>(&left, right),
-            (Operator::Or, Err(Error::UndefinedVariable(_))) => self.expression(right), // evaluate right side
+            (Operator::Or, Ok(left)) => boolean::<false>(&left, right),
+            (Operator::Or, Err(Error::UndefinedVariable(_))) => right(), // evaluate right side
+            (Operator::Implies, Ok(left)) => implies(&left, right),
+            (Operator::Implies, Err(Error::UndefinedVariable(_))) => Ok(Value::Boolean(true)), // short circuit to true
             (_, Ok(left)) => {
-                let right = self.expression(right);
-
-                match (operator, right) {
-                    (Operator::Plus, Ok(right)) => left + right,
-                    (Operator::Minus, Ok(right)) => left - right,
-                    (Operator::Multiply, Ok(right)) => left * right,
-                    (Operator::Divide, Ok(right)) => left / right,
-                    (Operator::Div, Ok(right)) => left.div_int(right),
-                    (Operator::Mod, Ok(right)) => left % right,
-                    (Operator::Xor, Ok(right)) => left ^ right,
-                    (Operator::Greater, Ok(right)) => Ok(Value::Boolean(left > right)),
-                    (Operator::GreaterEqual, Ok(right)) => Ok(Value::Boolean(left >= right)),
-                    (Operator::Less, Ok(right)) => Ok(Value::Boolean(left < right)),
-                    (Operator::LessEqual, Ok(right)) => Ok(Value::Boolean(left <= right)),
-                    (Operator::Equal, Ok(right)) => Ok(Value::Boolean(left == right)),
-                    (Operator::NotEqual, Ok(right)) => Ok(Value::Boolean(left != right)),
+                let result = match (operator, right()) {
+                    (_, Ok(right)) => self.operate(operator, left, right),
                     (Operator::Equal, Err(Error::UndefinedVariable(_))) => {
                         // Check if the left expression is equal to empty
                         Ok(Value::Boolean(left.is_empty()))
@@ -88,12 +483,13 @@ impl<'a> TreeWalkingInterpreter<'a> {
                         Ok(Value::Boolean(!left.is_empty()))
                     }
                     (_, Err(right)) => Err(right),
-                    (operator, _) => Err(Error::InvalidBinaryOperator(operator)),
-                }
+                };
+
+                self.check_finite(operator, result)
             }
             (Operator::Equal, Err(Error::UndefinedVariable(_))) => {
                 // Check if the right expression is equal to empty
-                match self.expression(right) {
+                match right() {
                     Ok(right) => Ok(Value::Boolean(right.is_empty())),
                     // check `empty = empty -> true`
                     Err(Error::UndefinedVariable(_)) => Ok(Value::Boolean(true)),
@@ -102,7 +498,7 @@ impl<'a> TreeWalkingInterpreter<'a> {
             }
             (Operator::NotEqual, Err(Error::UndefinedVariable(_))) => {
                 // Check if the right expression is not equal to empty
-                match self.expression(right) {
+                match right() {
                     Ok(right) => Ok(Value::Boolean(!right.is_empty())),
                     // check `empty <> empty -> true`
                     Err(Error::UndefinedVariable(_)) => Ok(Value::Boolean(false)),
@@ -113,74 +509,551 @@ impl<'a> TreeWalkingInterpreter<'a> {
         }
     }
 
-    fn boolean<const FULL_EVAL: bool>(&self, left: &Value, right: &Expression) -> Result<Value> {
-        let left = left.as_bool();
+    /// Applies a binary operator to two values, without the short-circuiting
+    /// operators `and`, `or` and `implies`.
+    pub(crate) fn operate(&self, operator: Operator, left: Value, right: Value) -> Result<Value> {
+        match operator {
+            Operator::Plus => left + right,
+            Operator::Minus => left - right,
+            Operator::Multiply => left * right,
+            Operator::Divide => left / right,
+            Operator::Div => left.div_int(right),
+            Operator::In => left.contained_in(right),
+            Operator::ApproxEqual => left.approx_equal(right),
+            Operator::Mod => left % right,
+            Operator::Xor => left ^ right,
+            Operator::Iff => Ok(Value::Boolean(left.as_bool() == right.as_bool())),
+            Operator::Greater
+            | Operator::GreaterEqual
+            | Operator::Less
+            | Operator::LessEqual
+            | Operator::Equal
+            | Operator::NotEqual => self.compare(operator, &left, &right),
+            operator => Err(Error::InvalidBinaryOperator(operator)),
+        }
+    }
+
+    /// Evaluates a left-leaning chain of `+` operators like `a + b + c` in a
+    /// single pass. String variables are borrowed from the [`Environment`] and
+    /// joined with a single allocation, instead of growing the intermediate
+    /// result with every operand.
+    ///
+    /// All operands are evaluated and added from left to right, the first
+    /// failing operand or addition is returned as [`Error`]. The first operand
+    /// which is not a String, like `null` in `'a' + null + 'b'`, continues the
+    /// chain with pairwise additions.
+    pub(crate) fn plus_chain<'o>(
+        &self,
+        first: Operand<'o>,
+        mut operands: impl Iterator<Item = Result<Operand<'o>>>,
+    ) -> Result<Value> {
+        let sum = if let Value::String(_) = first.deref() {
+            let mut parts = vec![first];
+            let mut rest = None;
+
+            for operand in operands.by_ref() {
+                let part = operand?;
+
+                if let Value::String(_) = part.deref() {
+                    parts.push(part);
+                } else {
+                    rest = Some(part);
+                    break;
+                }
+            }
+
+            let strings: Vec<&str> = parts
+                .iter()
+                .filter_map(|part| match part.deref() {
+                    Value::String(part) => Some(part.as_str()),
+                    _ => None,
+                })
+                .collect();
+            let joined = Value::String(strings.concat());
 
-        if left == FULL_EVAL {
-            let right = self.expression(right)?;
-            Ok(Value::Boolean(right.as_bool()))
+            match rest {
+                Some(part) => self.check_finite(Operator::Plus, joined + part.into_owned())?,
+                None => return Ok(joined),
+            }
         } else {
-            Ok(Value::Boolean(left)) // short circuit
+            first.into_owned()
+        };
+
+        operands.try_fold(sum, |sum, operand| {
+            self.check_finite(Operator::Plus, sum + operand?.into_owned())
+        })
+    }
+
+    /// Rejects a non-finite result of an arithmetic operator, if enabled by
+    /// [`ExecutionOptions::reject_non_finite_numbers`].
+    fn check_finite(&self, operator: Operator, result: Result<Value>) -> Result<Value> {
+        match result {
+            Ok(Value::Number(number))
+                if self.options.reject_non_finite_numbers && !number.is_finite() =>
+            {
+                Err(Error::NonFiniteNumber(operator))
+            }
+            result => result,
         }
     }
 
-    fn ternary(
+    /// Resolves a variable from the [`Environment`] without cloning its
+    /// [`Value`].
+    pub(crate) fn lookup(&self, name: &str) -> Result<Operand<'_>> {
+        self.check_access(name)?;
+
+        self.environment
+            .variable(name)
+            .map(Operand::Shared)
+            .ok_or(Error::UndefinedVariable(name.to_string()))
+    }
+
+    /// Compares a variable against a literal by reference, without cloning
+    /// either side. Follows the same rules for undefined variables as
+    /// [`Self::binary_values`].
+    pub(crate) fn compare_operands<'o>(
         &self,
-        left: &Expression,
-        middle: &Expression,
-        right: &Expression,
         operator: Operator,
+        left: Result<Operand<'o>>,
+        right: impl FnOnce() -> Result<Operand<'o>>,
     ) -> Result<Value> {
-        match operator {
-            Operator::TernaryCondition => {
-                let left = self.expression(left)?;
+        let left = match (operator, left) {
+            (_, Ok(left)) => left,
+            (Operator::Equal, Err(Error::UndefinedVariable(_))) => {
+                return Ok(Value::Boolean(right()?.is_empty()))
+            }
+            (Operator::NotEqual, Err(Error::UndefinedVariable(_))) => {
+                return Ok(Value::Boolean(!right()?.is_empty()))
+            }
+            (_, Err(left)) => return Err(left),
+        };
 
-                // short circuit evaluation
-                if left.as_bool() {
-                    self.expression(middle)
-                } else {
-                    self.expression(right)
-                }
+        match (operator, right()) {
+            (_, Ok(right)) => self.compare(operator, &left, &right),
+            (Operator::Equal, Err(Error::UndefinedVariable(_))) => {
+                Ok(Value::Boolean(left.is_empty()))
+            }
+            (Operator::NotEqual, Err(Error::UndefinedVariable(_))) => {
+                Ok(Value::Boolean(!left.is_empty()))
             }
-            _ => Err(Error::InvalidTernaryOperator(operator)),
+            (_, Err(right)) => Err(right),
         }
     }
 
-    fn get_values(&self, expressions: &[Expression]) -> Result<Vec<Value>> {
-        expressions
-            .iter()
-            .map(|expression| self.expression(expression))
-            .collect::<Result<_>>()
+    pub(crate) fn compare(&self, operator: Operator, left: &Value, right: &Value) -> Result<Value> {
+        match operator {
+            Operator::Equal => Ok(Value::Boolean(left.equals(right))),
+            Operator::NotEqual => Ok(Value::Boolean(!left.equals(right))),
+            Operator::Greater => {
+                self.check_comparable(operator, left, right)?;
+                Ok(Value::Boolean(left > right))
+            }
+            Operator::GreaterEqual => {
+                self.check_comparable(operator, left, right)?;
+                Ok(Value::Boolean(left >= right))
+            }
+            Operator::Less => {
+                self.check_comparable(operator, left, right)?;
+                Ok(Value::Boolean(left < right))
+            }
+            Operator::LessEqual => {
+                self.check_comparable(operator, left, right)?;
+                Ok(Value::Boolean(left <= right))
+            }
+            _ => Err(Error::InvalidBinaryOperator(operator)),
+        }
     }
 
-    fn array(&self, expressions: &[Expression]) -> Result<Value> {
-        Ok(Value::Array(self.get_values(expressions)?))
+    /// Rejects the ordering of values with different kinds in strict mode.
+    fn check_comparable(&self, operator: Operator, left: &Value, right: &Value) -> Result<()> {
+        if self.options.strict_type_comparisons && !left.is_comparable(right) {
+            Err(Error::IncomparableTypes(
+                operator,
+                left.type_name(),
+                right.type_name(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Evaluates a chained comparison like `(a < b) and (b < c)`, but evaluates
+    /// every operand at most once. Stops at the first failing comparison.
+    pub(crate) fn chain_operands<'o>(
+        &self,
+        mut operands: impl Iterator<Item = Result<Operand<'o>>>,
+        operators: &[Operator],
+    ) -> Result<Value> {
+        let Some(first) = operands.next() else {
+            return Ok(Value::Boolean(true));
+        };
+        let mut left = first?;
+
+        for (operator, right) in operators.iter().zip(operands) {
+            let right = right?;
+
+            if !self.compare(*operator, &left, &right)?.as_bool() {
+                return Ok(Value::Boolean(false));
+            }
+
+            left = right;
+        }
+
+        Ok(Value::Boolean(true))
     }
 
-    fn variable(&self, name: &str) -> Result<Value> {
+    /// Denied variables are an error of their own, so the rules for undefined
+    /// variables never mask an access violation.
+    fn check_access(&self, name: &str) -> Result<()> {
+        if self.environment.variable_allowed(name) {
+            Ok(())
+        } else {
+            Err(Error::VariableAccessDenied(name.to_string()))
+        }
+    }
+
+    pub(crate) fn variable(&self, name: &str) -> Result<Value> {
+        self.check_access(name)?;
+
         self.environment
             .variable(name)
             .map(|v| (*v).clone())
             .ok_or(Error::UndefinedVariable(name.to_string()))
     }
 
-    fn call(&self, name: &str, expressions: &[Expression]) -> Result<Value> {
-        self.environment
-            .call(name, &self.get_values(expressions)?)
-            .map_err(|e| Error::NativeFunctionError(name.to_string(), e))
+    /// Answers `contains` and `in` with the [`ValueSet`](crate::ValueSet) of a
+    /// set-backed variable. Returns `None` if the haystack is not backed by a set.
+    fn contains_in_set<'o>(
+        &self,
+        haystack: &str,
+        needle: impl FnOnce() -> Result<Operand<'o>>,
+    ) -> Result<Option<bool>> {
+        match self.variable_set(haystack) {
+            Some(set) => Ok(Some(set.contains(&*needle()?))),
+            None => Ok(None),
+        }
+    }
+
+    /// The [`ValueSet`] of a variable. Sets are ignored with a [`CallGuard`],
+    /// so the guard sees the parameters of the regular `contains` call.
+    pub(crate) fn variable_set(&self, name: &str) -> Option<Arc<ValueSet>> {
+        if self.options.call_guard.is_some() {
+            None
+        } else {
+            self.environment.variable_set(name)
+        }
+    }
+
+    /// Evaluates a parameter of a function call. A parameter failing with
+    /// an [`Error::UndefinedVariable`] is passed as [`Value::Null`], if the
+    /// function [accepts undefined variables](Environment::function_accepts_undefined).
+    fn argument(&self, name: &str, value: Result<Value>) -> Result<Value> {
+        match value {
+            Err(Error::UndefinedVariable(_)) if self.accepts_undefined(name) => Ok(Value::Null),
+            value => value,
+        }
+    }
+
+    pub(crate) fn accepts_undefined(&self, name: &str) -> bool {
+        self.environment.function_accepts_undefined(name)
+    }
+
+    /// Calls a function of the [`Environment`] with evaluated parameters.
+    pub(crate) fn call_native(&self, name: &str, params: &[Value]) -> Result<Value> {
+        if let Some(guard) = &self.options.call_guard {
+            guard.check(name, params)?;
+        }
+
+        if let [value, lower, upper] = params {
+            if self.options.strict_type_comparisons && self.environment.function_checks_range(name)
+            {
+                self.check_comparable(Operator::GreaterEqual, value, lower)?;
+                self.check_comparable(Operator::LessEqual, value, upper)?;
+            }
+        }
+
+        let result = match self.cancellation {
+            Some(token) if CANCELLABLE => {
+                self.check_cancelled()?;
+                self.environment.call_cancellable(name, params, token)
+            }
+            _ => self.environment.call(name, params),
+        };
+
+        match result.map_err(|e| Error::NativeFunctionError(name.to_string(), e))? {
+            Value::Number(number)
+                if self.options.reject_non_finite_numbers && !number.is_finite() =>
+            {
+                Err(Error::NonFiniteResult(name.to_string()))
+            }
+            value => Ok(value),
+        }
+    }
+}
+
+/// Accesses the element at `index` of `target` like the `at` function,
+/// which is called directly instead of through the [`Environment`].
+fn element(target: Value, index: Value) -> Result<Value> {
+    at(&[target, index]).map_err(|e| Error::NativeFunctionError(AT.to_string(), e))
+}
+
+fn unary(operator: Operator, right: Result<Value>) -> Result<Value> {
+    match (operator, right) {
+        (Operator::Minus, Ok(rhs)) => -rhs,
+        (Operator::Not, Ok(rhs)) => !rhs,
+        (Operator::IsEmpty, Ok(rhs)) => Ok(Value::Boolean(rhs.is_empty())),
+        (Operator::IsNotEmpty, Ok(rhs)) => Ok(Value::Boolean(!rhs.is_empty())),
+        // undefined variables are treated as empty, like `undefined_var = ''`
+        (Operator::IsEmpty, Err(Error::UndefinedVariable(_))) => Ok(Value::Boolean(true)),
+        (Operator::IsNotEmpty, Err(Error::UndefinedVariable(_))) => Ok(Value::Boolean(false)),
+        // the operand error is kept, an undefined operand stays undefined
+        (_, Err(error)) => Err(error),
+        _ => Err(Error::InvalidUnaryOperator(operator)),
+    }
+}
+
+fn boolean<const FULL_EVAL: bool>(
+    left: &Value,
+    right: impl FnOnce() -> Result<Value>,
+) -> Result<Value> {
+    let left = left.as_bool();
+
+    if left == FULL_EVAL {
+        Ok(Value::Boolean(right()?.as_bool()))
+    } else {
+        Ok(Value::Boolean(left)) // short circuit
+    }
+}
+
+/// Evaluates `left implies right` as `not left or right`. The right side is
+/// only evaluated if the left side is `true`.
+fn implies(left: &Value, right: impl FnOnce() -> Result<Value>) -> Result<Value> {
+    if left.as_bool() {
+        Ok(Value::Boolean(right()?.as_bool()))
+    } else {
+        Ok(Value::Boolean(true)) // short circuit
+    }
+}
+
+fn ternary(
+    operator: Operator,
+    left: impl FnOnce() -> Result<Value>,
+    middle: impl FnOnce() -> Result<Value>,
+    right: impl FnOnce() -> Result<Value>,
+) -> Result<Value> {
+    match operator {
+        Operator::TernaryCondition => {
+            // short circuit evaluation
+            if left()?.as_bool() {
+                middle()
+            } else {
+                right()
+            }
+        }
+        _ => Err(Error::InvalidTernaryOperator(operator)),
+    }
+}
+
+/// Evaluates the fallback only if the value fails, cancellation and denied
+/// variable access or function calls are never caught.
+fn try_fallback(value: Result<Value>, fallback: impl FnOnce() -> Result<Value>) -> Result<Value> {
+    match value {
+        Err(error) if error.is_fatal() => Err(error),
+        Err(_) => fallback(),
+        value => value,
+    }
+}
+
+/// Executes a [`FlatExpression`] by walking its node indices. Follows the
+/// exact same rules as the [`TreeWalkingInterpreter`], which it uses for
+/// everything not depending on the shape of the tree.
+pub(crate) struct FlatInterpreter<'a, 't> {
+    interpreter: TreeWalkingInterpreter<'a>,
+    tree: &'t FlatExpression,
+}
+
+impl<'a, 't> FlatInterpreter<'a, 't> {
+    pub fn interprete(env: &'a impl Environment, tree: &'t FlatExpression) -> Result<Value> {
+        let interpreter = FlatInterpreter {
+            interpreter: TreeWalkingInterpreter::new(env),
+            tree,
+        };
+
+        interpreter.expression(tree.root())
+    }
+
+    fn expression(&self, index: u32) -> Result<Value> {
+        self.interpreter.nested(|| self.evaluate(index))
+    }
+
+    fn evaluate(&self, index: u32) -> Result<Value> {
+        match self.tree.node(index) {
+            Node::Unary { right, operator } => unary(*operator, self.expression(*right)),
+            Node::Binary {
+                left,
+                right,
+                operator,
+            } => self.binary(*left, *right, *operator),
+            Node::Ternary {
+                left,
+                middle,
+                right,
+                operator,
+            } => ternary(
+                *operator,
+                || self.expression(*left),
+                || self.expression(*middle),
+                || self.expression(*right),
+            ),
+            Node::Chain {
+                expressions,
+                operators,
+            } => {
+                let operands = self.tree.children(*expressions).iter();
+                self.interpreter.chain_operands(
+                    operands.map(|operand| self.operand(*operand)),
+                    self.tree.operators(*operators),
+                )
+            }
+            Node::Array { expressions } => self.array(self.tree.children(*expressions)),
+            Node::Literal { value } => Ok(value.clone()),
+            Node::Variable { name } => self.interpreter.variable(name),
+            Node::Call { name, params } => self.call(name, self.tree.children(*params)),
+            Node::Index { target, index } => {
+                element(self.expression(*target)?, self.expression(*index)?)
+            }
+        }
+    }
+
+    fn binary(&self, left: u32, right: u32, operator: Operator) -> Result<Value> {
+        match (operator, self.tree.node(left), self.tree.node(right)) {
+            (
+                Operator::Plus,
+                Node::Binary {
+                    operator: Operator::Plus,
+                    ..
+                },
+                _,
+            ) => return self.plus(left, right),
+            (
+                Operator::Equal
+                | Operator::NotEqual
+                | Operator::Greater
+                | Operator::GreaterEqual
+                | Operator::Less
+                | Operator::LessEqual,
+                Node::Variable { .. } | Node::Literal { .. },
+                Node::Literal { .. },
+            )
+            | (
+                Operator::Equal
+                | Operator::NotEqual
+                | Operator::Greater
+                | Operator::GreaterEqual
+                | Operator::Less
+                | Operator::LessEqual,
+                Node::Literal { .. },
+                Node::Variable { .. },
+            ) => {
+                return self
+                    .interpreter
+                    .compare_operands(operator, self.operand(left), || self.operand(right))
+            }
+            (Operator::In, _, Node::Variable { name }) => {
+                if let Some(found) = self
+                    .interpreter
+                    .contains_in_set(name, || self.operand(left))?
+                {
+                    return Ok(Value::Boolean(found));
+                }
+            }
+            _ => (),
+        }
+
+        self.interpreter
+            .binary_values(operator, self.expression(left), || self.expression(right))
+    }
+
+    /// See [`TreeWalkingInterpreter::plus`].
+    fn plus(&self, left: u32, right: u32) -> Result<Value> {
+        let mut operands = vec![right];
+        let mut first = left;
+
+        while let Node::Binary {
+            left,
+            right,
+            operator: Operator::Plus,
+        } = self.tree.node(first)
+        {
+            operands.push(*right);
+            first = *left;
+        }
+
+        let operands = operands.into_iter().rev();
+        self.interpreter.plus_chain(
+            self.operand(first)?,
+            operands.map(|operand| self.operand(operand)),
+        )
+    }
+
+    fn operand(&self, index: u32) -> Result<Operand<'_>> {
+        match self.tree.node(index) {
+            Node::Literal { value } => Ok(Operand::Borrowed(value)),
+            Node::Variable { name } => self.interpreter.lookup(name),
+            _ => self.expression(index).map(Operand::Owned),
+        }
+    }
+
+    fn array(&self, expressions: &[u32]) -> Result<Value> {
+        let values = expressions
+            .iter()
+            .map(|expression| self.expression(*expression))
+            .collect::<Result<_>>()?;
+
+        Ok(Value::Array(values))
+    }
+
+    fn call(&self, name: &str, expressions: &[u32]) -> Result<Value> {
+        if let [first, second] = expressions {
+            if name.eq_ignore_ascii_case(TRY) {
+                return try_fallback(self.expression(*first), || self.expression(*second));
+            }
+
+            if let (true, Node::Variable { name: haystack }) =
+                (name.eq_ignore_ascii_case(CONTAINS), self.tree.node(*first))
+            {
+                if let Some(found) = self
+                    .interpreter
+                    .contains_in_set(haystack, || self.operand(*second))?
+                {
+                    return Ok(Value::Boolean(found));
+                }
+            }
+        }
+
+        let params = expressions
+            .iter()
+            .map(|expression| {
+                self.interpreter
+                    .argument(name, self.expression(*expression))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.interpreter.call_native(name, &params)
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
-        ast::Expression,
+        ast::{Expression, FlatExpression},
         function::{Arity, Function},
-        interpreter::TreeWalkingInterpreter,
+        interpreter::{FlatInterpreter, TreeWalkingInterpreter},
         operator::Operator,
         stdlib::common::max,
         value::Value,
-        StaticEnvironment,
+        vm::{CompiledProgram, VirtualMachine},
+        Error, StaticEnvironment,
     };
 
     #[test]
@@ -388,4 +1261,174 @@ mod test {
         let expected = Value::Number(20.0);
         assert_eq!(expected, result);
     }
+
+    fn variable(name: &str) -> Expression {
+        Expression::Variable {
+            name: name.to_string(),
+        }
+    }
+
+    /// Operands failing with [`Error::UndefinedVariable`]. A bare variable takes
+    /// the shortcut for comparisons with literals, all others take the general path.
+    fn undefined_operands() -> Vec<Expression> {
+        let literal = Box::new(Expression::Literal {
+            value: Value::Number(1.0),
+        });
+
+        vec![
+            variable("missing"),
+            Expression::Binary {
+                left: Box::new(variable("missing")),
+                right: literal.clone(),
+                operator: Operator::Plus,
+            },
+            Expression::Binary {
+                left: literal.clone(),
+                right: Box::new(variable("missing")),
+                operator: Operator::Greater,
+            },
+            Expression::Unary {
+                right: Box::new(variable("missing")),
+                operator: Operator::Minus,
+            },
+            Expression::Unary {
+                right: Box::new(variable("missing")),
+                operator: Operator::Not,
+            },
+            Expression::Ternary {
+                left: Box::new(variable("missing")),
+                middle: literal.clone(),
+                right: literal,
+                operator: Operator::TernaryCondition,
+            },
+            Expression::Array {
+                expressions: vec![variable("missing")],
+            },
+            Expression::Call {
+                name: String::from("max"),
+                params: vec![variable("missing")],
+            },
+        ]
+    }
+
+    fn execute_both(env: &StaticEnvironment, ast: &Expression) -> crate::Result<Value> {
+        let result = TreeWalkingInterpreter::interprete(env, ast);
+        let flat = FlatInterpreter::interprete(env, &FlatExpression::from(ast));
+        assert_eq!(result, flat, "{ast:?}");
+        let compiled = VirtualMachine::execute(env, &CompiledProgram::from(ast));
+        assert_eq!(result, compiled, "{ast:?}");
+
+        result
+    }
+
+    #[test]
+    fn undefined_operands_propagate() {
+        let env = StaticEnvironment::default();
+
+        for operand in undefined_operands() {
+            assert_eq!(
+                Err(Error::UndefinedVariable(String::from("missing"))),
+                execute_both(&env, &operand),
+                "{operand:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn undefined_comparison_matrix() {
+        let values = [
+            Value::Boolean(false),
+            Value::Boolean(true),
+            Value::String(String::new()),
+            Value::String(String::from("text")),
+            Value::Number(0.0),
+            Value::Number(42.0),
+            Value::Array(vec![]),
+            Value::Array(vec![Value::Number(0.0)]),
+        ];
+        let compare = |left: &Expression, right: &Expression, operator| Expression::Binary {
+            left: Box::new(left.clone()),
+            right: Box::new(right.clone()),
+            operator,
+        };
+
+        for value in values {
+            let mut env = StaticEnvironment::default();
+            env.add_variable("defined", value.clone());
+
+            let literal = Expression::Literal {
+                value: value.clone(),
+            };
+
+            // a literal takes the shortcut with a bare variable, a variable never does
+            for defined in [literal, variable("defined")] {
+                for undefined in undefined_operands() {
+                    for (operator, expected) in [
+                        (Operator::Equal, value.is_empty()),
+                        (Operator::NotEqual, !value.is_empty()),
+                    ] {
+                        for ast in [
+                            compare(&defined, &undefined, operator),
+                            compare(&undefined, &defined, operator),
+                        ] {
+                            assert_eq!(
+                                Ok(Value::Boolean(expected)),
+                                execute_both(&env, &ast),
+                                "{ast:?}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        let env = StaticEnvironment::default();
+
+        for left in undefined_operands() {
+            for right in undefined_operands() {
+                assert_eq!(
+                    Ok(Value::Boolean(true)),
+                    execute_both(&env, &compare(&left, &right, Operator::Equal))
+                );
+                assert_eq!(
+                    Ok(Value::Boolean(false)),
+                    execute_both(&env, &compare(&left, &right, Operator::NotEqual))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn undefined_comparison_other_errors() {
+        let env = StaticEnvironment::default();
+        let invalid = Expression::Binary {
+            left: Box::new(Expression::Literal {
+                value: Value::Number(1.0),
+            }),
+            right: Box::new(Expression::Literal {
+                value: Value::String(String::from("a")),
+            }),
+            operator: Operator::Minus,
+        };
+
+        for operator in [Operator::Equal, Operator::NotEqual] {
+            for ast in [
+                Expression::Binary {
+                    left: Box::new(variable("missing")),
+                    right: Box::new(invalid.clone()),
+                    operator,
+                },
+                Expression::Binary {
+                    left: Box::new(invalid.clone()),
+                    right: Box::new(variable("missing")),
+                    operator,
+                },
+            ] {
+                assert_eq!(
+                    Err(Error::InvalidBinaryOperator(Operator::Minus)),
+                    execute_both(&env, &ast)
+                );
+            }
+        }
+    }
 }
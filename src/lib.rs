@@ -5,8 +5,7 @@
 //!
 //! # Example
 //! ```
-//! use slac::{check_variables_and_functions, compile, execute, StaticEnvironment, Value};
-//! use slac::stdlib::extend_environment;
+//! use slac::prelude::*;
 //!
 //! let ast = compile("max(10, 20) + 1").expect("compiles the ast");
 //! let mut env = StaticEnvironment::default();
@@ -18,53 +17,137 @@
 //! assert_eq!(Value::Number(21.0), result);
 //! ```
 //!
+//! The [`prelude`] re-exports the commonly used API, the functions of the
+//! standard library are found in [`stdlib`].
+//!
 //! # Serialization / Deserialization
 //!
 //! The [`Expression`] can be fully serialized into an (e.g.) JSON string for precompilation
 //! and cached execution using [serde](https://crates.io/crates/serde). See `test/serde_test.rs`
-//! for the resulting JSON.
+//! for the resulting JSON. `serialize_expression` adds a schema version, so a
+//! cached AST of an unsupported version is rejected instead of misread.
+//!
+//! With the `binary` feature, `to_bytes` and `from_bytes` store an [`Expression`]
+//! in a compact, versioned binary format instead.
+//!
+//! A `DebugBundle` captures an [`Expression`] together with the variables of an
+//! execution, so a misbehaving rule can be replayed later on.
+//!
+//! # Integration examples
+//!
+//! The `examples` directory of the repository contains complete applications
+//! of the API, run them with `cargo run --example <name>`.
+//!
+//! * `rule_engine`: validates rules loaded from JSON and evaluates them against
+//!   a batch of records, mapping the errors to an API response.
+//! * `custom_functions`: registers pure and impure native functions and shows
+//!   their handling by the optimizer and the validation.
+//! * `precompiled_cache`: serializes optimized ASTs, executes them without
+//!   recompiling and measures the difference.
+//! * `cli`: executes an expression passed on the command line.
+//! * `list_functions`: lists the functions of the standard library.
 
+mod arithmetic;
 mod ast;
+#[cfg(feature = "binary")]
+mod binary;
+mod cancellation;
+#[cfg(feature = "serde")]
+mod catalog;
+#[cfg(feature = "chrono")]
+mod chrono_interop;
+mod columnar;
 mod compiler;
+#[cfg(feature = "serde")]
+mod debug;
+mod declaration;
 pub mod environment;
 mod error;
 pub mod function;
 mod interpreter;
+mod message;
 mod operator;
 pub mod optimizer;
+pub mod prelude;
 mod scanner;
+#[cfg(feature = "serde")]
+mod schema;
+mod source;
 pub mod stdlib;
 mod token;
 mod validate;
 mod value;
+mod value_set;
+mod vm;
 
-use crate::environment::Environment;
+use ::std::collections::HashSet;
 
+use crate::environment::{Environment, RecordingEnvironment};
+
+#[doc(inline)]
+pub use crate::ast::{
+    collect_function_calls, collect_variables, node_at, Expression, FlatExpression, Node,
+    NodeRange, NodeSpans, SCHEMA_VERSION,
+};
+#[cfg(feature = "binary")]
 #[doc(inline)]
-pub use crate::ast::Expression;
+pub use crate::binary::{from_bytes, to_bytes, BINARY_FORMAT_VERSION};
 #[doc(inline)]
-pub use crate::compiler::Compiler;
+pub use crate::cancellation::CancellationToken;
+#[cfg(feature = "serde")]
 #[doc(inline)]
-pub use crate::environment::StaticEnvironment;
+pub use crate::catalog::{Catalog, CatalogDiff, CatalogEntry, FunctionEntry, VariableEntry};
 #[doc(inline)]
-pub use crate::error::{Error, Result};
+pub use crate::columnar::{execute_columnar, execute_columnar_rows};
+#[doc(inline)]
+pub use crate::compiler::{expected_tokens_at_error, Compiler};
+#[cfg(feature = "serde")]
+#[doc(inline)]
+pub use crate::debug::{replay, DebugBundle, VariableSnapshot};
+#[doc(inline)]
+pub use crate::environment::{OverlayEnvironment, ScopedEnvironment, StaticEnvironment};
+#[doc(inline)]
+pub use crate::error::{Error, Result, Result as SlacResult};
+#[doc(inline)]
+pub use crate::interpreter::{CallGuard, ExecutionOptions, DEFAULT_MAX_DEPTH};
+#[doc(inline)]
+pub use crate::message::{DefaultCatalog, MessageCatalog};
 #[doc(inline)]
 pub use crate::operator::Operator;
 #[doc(inline)]
-pub use crate::optimizer::optimize;
+pub use crate::optimizer::{optimize, optimize_with_context, OptimizerContext};
+#[doc(inline)]
+pub use crate::scanner::{Scanner, Span};
+#[cfg(feature = "serde")]
+#[doc(inline)]
+pub use crate::schema::{deserialize_expression, serialize_expression, SerializedExpression};
 #[doc(inline)]
-pub use crate::scanner::Scanner;
+pub use crate::token::{Token, TokenKind};
 #[doc(inline)]
-pub use crate::token::Token;
+pub use crate::validate::{
+    check_ast_integrity, check_boolean_result, check_variable_scope, check_variables_and_functions,
+    find_deprecated, lint_array_scalar_equality, Lint, MAX_CALL_PARAMS,
+};
 #[doc(inline)]
-pub use crate::validate::{check_boolean_result, check_variables_and_functions};
+pub use crate::value::{ConversionError, Value};
 #[doc(inline)]
-pub use crate::value::Value;
+pub use crate::value_set::ValueSet;
+#[doc(inline)]
+pub use crate::vm::CompiledProgram;
+
+/// The former name of the [`stdlib`] module, which was renamed to avoid the
+/// confusion with Rust's `std`.
+#[deprecated(since = "0.15.0", note = "use `slac::stdlib` instead")]
+pub mod std {
+    #[doc(no_inline)]
+    pub use crate::stdlib::*;
+}
 
 /// Compiles a string into an [`Expression`] tree.
 ///
 /// # Errors
-/// Returns an [`Error`] when encountering invalid Input.
+/// Returns an [`Error`] when encountering invalid Input, syntax errors report
+/// their position in the source with [`Error::span`].
 ///
 /// # Examples
 /// ```
@@ -89,12 +172,55 @@ pub use crate::value::Value;
 /// assert_eq!(ast, Ok(expected));
 /// ```
 pub fn compile(source: &str) -> Result<Expression> {
-    let tokens = Scanner::tokenize(source)?;
-    let ast = Compiler::compile_ast(tokens)?;
+    let tokens = Scanner::tokenize_with_spans(source)?;
+    let ast = Compiler::compile_ast_located(tokens)?;
+
+    Ok(ast)
+}
+
+/// Compiles a string into an [`Expression`] tree like [`compile`], but rejects
+/// integer literals which lose precision as a [`Value::Number`], see
+/// [`Scanner::tokenize_strict`].
+///
+/// # Errors
+/// Returns an [`Error::ImpreciseNumberLiteral`] for an integer literal above
+/// `2^53` which can not be represented exactly, or any [`Error`] of [`compile`].
+///
+/// # Examples
+/// ```
+/// use slac::{compile, compile_strict, Error};
+///
+/// assert_eq!(compile("id = 9007199254740992"), compile_strict("id = 9007199254740992"));
+/// assert_eq!(
+///     Err(Error::ImpreciseNumberLiteral(String::from("9007199254740993"))),
+///     compile_strict("id = 9007199254740993")
+/// );
+/// ```
+pub fn compile_strict(source: &str) -> Result<Expression> {
+    let tokens = Scanner::scan_with_spans(source, true)?;
+    let ast = Compiler::compile_ast_located(tokens)?;
 
     Ok(ast)
 }
 
+/// Compiles a string into an [`Expression`] tree and records the source [`Span`]
+/// of every node, e.g. to find the node under the cursor using [`node_at`].
+///
+/// # Errors
+/// Returns an [`Error`] when encountering invalid Input.
+///
+/// # Examples
+/// ```
+/// use slac::{compile, compile_with_spans};
+///
+/// let (ast, _spans) = compile_with_spans("10 + 20 >= 30").unwrap();
+/// assert_eq!(compile("10 + 20 >= 30"), Ok(ast));
+/// ```
+pub fn compile_with_spans(source: &str) -> Result<(Expression, NodeSpans)> {
+    let tokens = Scanner::tokenize_with_spans(source)?;
+    Compiler::compile_ast_with_spans(tokens)
+}
+
 /// Executes an [`Expression`] using an [`Environment`].
 ///
 /// # Example
@@ -122,6 +248,7 @@ pub fn compile(source: &str) -> Result<Expression> {
 /// * Invalid operations will be evaluated to an [`Error`].
 /// * Comparison of empty Values ([`Value::empty()`]) against [`Error::UndefinedVariable`] is a valid operation
 ///   * e.g: `undefined_var = ''` is valid
+///   * an operand containing an undefined variable is undefined as a whole, e.g. `undefined_var + 1 = ''`
 ///
 /// # Errors
 ///
@@ -129,3 +256,195 @@ pub fn compile(source: &str) -> Result<Expression> {
 pub fn execute(env: &impl Environment, ast: &Expression) -> Result<Value> {
     interpreter::TreeWalkingInterpreter::interprete(env, ast)
 }
+
+/// Executes a [`FlatExpression`] using an [`Environment`]. The result is always
+/// the same as [`execute`] on the [`Expression`] the [`FlatExpression`] was
+/// built from.
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute_flat, FlatExpression, StaticEnvironment, Value};
+///
+/// let mut env = StaticEnvironment::default();
+/// env.add_variable("price", Value::Number(20.0));
+///
+/// let ast = FlatExpression::from(compile("price * 2 > 30 and price < 100").unwrap());
+///
+/// assert_eq!(Ok(Value::Boolean(true)), execute_flat(&env, &ast));
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error`] when encountering an error at runtime.
+pub fn execute_flat(env: &impl Environment, ast: &FlatExpression) -> Result<Value> {
+    interpreter::FlatInterpreter::interprete(env, ast)
+}
+
+/// Executes a [`CompiledProgram`] using an [`Environment`]. The result is always
+/// the same as [`execute`] on the [`Expression`] the program was compiled from.
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute_compiled, CompiledProgram, StaticEnvironment, Value};
+///
+/// let mut env = StaticEnvironment::default();
+/// env.add_variable("price", Value::Number(20.0));
+///
+/// let program = CompiledProgram::from(compile("price * 2 > 30 and price < 100").unwrap());
+///
+/// assert_eq!(Ok(Value::Boolean(true)), execute_compiled(&env, &program));
+/// ```
+///
+/// # Remarks
+/// * Compile the program once and execute it for many variables, e.g. to
+///   filter a large number of rows by the same expression.
+/// * Uses the default [`ExecutionOptions`] and can not be cancelled.
+///
+/// # Errors
+///
+/// Returns [`Error`] when encountering an error at runtime.
+pub fn execute_compiled(env: &impl Environment, program: &CompiledProgram) -> Result<Value> {
+    vm::VirtualMachine::execute(env, program)
+}
+
+/// Executes an [`Expression`] using an [`Environment`] and converts the result
+/// into a Rust type. The conversion is strict, e.g. a [`Value::Number`] is never
+/// treated as a `bool`.
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute_as, Error, StaticEnvironment, Value};
+///
+/// let mut env = StaticEnvironment::default();
+/// env.add_variable("amount", Value::Number(250.0));
+///
+/// // instead of `check_boolean_result`, `execute` and matching the `Value`
+/// let ast = compile("amount > 100").unwrap();
+/// assert_eq!(Ok(true), execute_as::<bool>(&env, &ast));
+///
+/// let ast = compile("amount * 2").unwrap();
+/// assert_eq!(Ok(500.0), execute_as::<f64>(&env, &ast));
+/// assert_eq!(
+///     Err(Error::ResultTypeMismatch {
+///         expected: "Boolean",
+///         found: "Number"
+///     }),
+///     execute_as::<bool>(&env, &ast)
+/// );
+/// ```
+///
+/// # Remarks
+/// * Implemented for `bool`, `f64`, `String` and `Vec<Value>`.
+/// * Non-finite numbers are still a [`Value::Number`] and returned as `f64`.
+///
+/// # Errors
+///
+/// Returns [`Error::ResultTypeMismatch`] if the result has the wrong kind or
+/// any [`Error`] encountered at runtime.
+pub fn execute_as<T>(env: &impl Environment, ast: &Expression) -> Result<T>
+where
+    T: TryFrom<Value, Error = ConversionError>,
+{
+    let value = execute(env, ast)?;
+
+    Ok(T::try_from(value)?)
+}
+
+/// Executes an [`Expression`] using an [`Environment`] and [`ExecutionOptions`].
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute, execute_with_options, Error, ExecutionOptions, Operator};
+/// use slac::StaticEnvironment;
+///
+/// let env = StaticEnvironment::default();
+/// let ast = compile("5 > 'abc'").unwrap();
+/// let options = ExecutionOptions {
+///     strict_type_comparisons: true,
+///     ..Default::default()
+/// };
+///
+/// assert!(execute(&env, &ast).is_ok());
+/// assert_eq!(
+///     Err(Error::IncomparableTypes(Operator::Greater, "Number", "String")),
+///     execute_with_options(&env, &ast, options)
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error`] when encountering an error at runtime.
+pub fn execute_with_options(
+    env: &impl Environment,
+    ast: &Expression,
+    options: ExecutionOptions,
+) -> Result<Value> {
+    interpreter::TreeWalkingInterpreter::interprete_with_options(env, ast, options)
+}
+
+/// Executes an [`Expression`] using an [`Environment`] until it finishes or
+/// the [`CancellationToken`] is cancelled.
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute_cancellable, CancellationToken, Error, StaticEnvironment};
+///
+/// let env = StaticEnvironment::default();
+/// let ast = compile("1 + 2").unwrap();
+/// let token = CancellationToken::new();
+///
+/// assert!(execute_cancellable(&env, &ast, &token).is_ok());
+///
+/// token.cancel();
+/// assert_eq!(Err(Error::Cancelled), execute_cancellable(&env, &ast, &token));
+/// ```
+///
+/// # Remarks
+/// * The token is checked before each visited node and each native function call.
+/// * Functions created with [`Function::cancellable`](function::Function::cancellable)
+///   receive the token and may stop early.
+///
+/// # Errors
+///
+/// Returns [`Error::Cancelled`] if the token was cancelled or any other [`Error`]
+/// encountered at runtime.
+pub fn execute_cancellable(
+    env: &impl Environment,
+    ast: &Expression,
+    token: &CancellationToken,
+) -> Result<Value> {
+    interpreter::TreeWalkingInterpreter::interprete_cancellable(env, ast, token)
+}
+
+/// Executes an [`Expression`] using an [`Environment`] and collects the names
+/// of all variables which were read during the execution.
+///
+/// # Example
+/// ```
+/// use std::collections::HashSet;
+/// use slac::{compile, execute_with_reads, StaticEnvironment, Value};
+///
+/// let mut env = StaticEnvironment::default();
+/// env.add_variable("checked", Value::Boolean(false));
+///
+/// let ast = compile("checked and price > 100").unwrap();
+/// let (result, reads) = execute_with_reads(&env, &ast);
+///
+/// assert_eq!(Ok(Value::Boolean(false)), result);
+/// assert_eq!(HashSet::from([String::from("checked")]), reads);
+/// ```
+///
+/// # Remarks
+/// * Variables skipped by short-circuit evaluation are not part of the result.
+/// * Undefined variables are part of the result, as they may still affect it
+///   e.g. `undefined_var = ''`.
+/// * The names are collected as written in the [`Expression`].
+pub fn execute_with_reads(
+    env: &impl Environment,
+    ast: &Expression,
+) -> (Result<Value>, HashSet<String>) {
+    let recording = RecordingEnvironment::new(env);
+    let result = execute(&recording, ast);
+
+    (result, recording.into_reads())
+}
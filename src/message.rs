@@ -0,0 +1,241 @@
+//! Stable message keys for errors of native functions and their default English rendering.
+
+/// A source of message templates, e.g. to localize the errors of native functions.
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute, stdlib::extend_environment, MessageCatalog, StaticEnvironment};
+///
+/// struct German;
+///
+/// impl MessageCatalog for German {
+///     fn template(&self, key: &str) -> Option<&str> {
+///         match key {
+///             "err.native" => Some("Fehler in Funktion \"{0}\": {1}"),
+///             "err.bool.parse" => Some("'{0}' ist kein Wahrheitswert"),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let mut env = StaticEnvironment::default();
+/// extend_environment(&mut env);
+///
+/// let ast = compile("bool('ja')").unwrap();
+/// let error = execute(&env, &ast).unwrap_err();
+///
+/// assert_eq!("Fehler in Funktion \"bool\": 'ja' ist kein Wahrheitswert", error.render_with(&German));
+/// ```
+pub trait MessageCatalog {
+    /// Returns the template of a message key, with `{0}`, `{1}`, ... as the
+    /// placeholders of its arguments. Unknown keys return `None` and fall
+    /// back to the [`DefaultCatalog`].
+    fn template(&self, key: &str) -> Option<&str>;
+}
+
+/// The default English messages, used by the [`Display`](std::fmt::Display)
+/// implementations of all errors.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultCatalog;
+
+impl DefaultCatalog {
+    /// All message keys with their English template.
+    pub const MESSAGES: &'static [(&'static str, &'static str)] = &[
+        (
+            "err.native",
+            "native function \"{0}\" encountered an error: \"{1}\"",
+        ),
+        ("err.custom", "{0}"),
+        ("err.function.not_found", "function \"{0}\" not found"),
+        (
+            "err.function.cancellable",
+            "function must be called with a CancellationToken",
+        ),
+        (
+            "err.function.stub",
+            "stub function must be called by a StaticEnvironment",
+        ),
+        ("err.param.count", "not enough parameters: \"{0}\" expected"),
+        ("err.param.type", "wrong parameter type"),
+        ("err.index.bounds", "index \"{0}\" is out of bounds"),
+        ("err.index.negative", "index must not be negative"),
+        (
+            "err.output.too_large",
+            "output length {1} exceeds the limit of {0}",
+        ),
+        ("err.bool.parse", "'{0}' can not be converted to a Boolean"),
+        // the raw parser error is the last argument
+        ("err.number.parse", "{1}"),
+        (
+            "err.number.safe_integer",
+            "{0} is outside of the safe integer range",
+        ),
+        ("err.string.chr_range", "number is out of ASCII range"),
+        ("err.string.ord_range", "character is out of ASCII range"),
+        ("err.string.ord_length", "string is too long"),
+        (
+            "err.string.normalize_form",
+            "unknown normalization form '{0}', expected 'NFC' or 'NFD'",
+        ),
+        ("err.string.base64", "'{0}' is not valid Base64"),
+        (
+            "err.string.url_encoding",
+            "'{0}' is not a valid URL encoding",
+        ),
+        ("err.string.utf8", "the decoded text is not valid UTF-8"),
+        ("err.regex.invalid", "{1}"),
+        ("err.datetime.parse", "{2}"),
+        ("err.datetime.parse_any", "'{0}' does not match any of: {1}"),
+        ("err.datetime.range", "datetime out of range"),
+        ("err.datetime.local", "invalid datetime value"),
+        (
+            "err.datetime.unit",
+            "unknown unit '{0}', expected one of: {1}",
+        ),
+        (
+            "err.datetime.weekday_mode",
+            "unknown weekday mode '{0}', expected one of: {1}",
+        ),
+        (
+            "err.datetime.duration_format",
+            "unknown duration specifier '%{0}', expected one of: {1}",
+        ),
+        (
+            "err.datetime.duration_parse",
+            "'{0}' is not a valid duration, expected H:MM:SS",
+        ),
+        ("err.datetime.encode_date", "invalid date parameters"),
+        ("err.datetime.encode_time", "invalid time parameters"),
+        (
+            "err.datetime.month_overflow",
+            "inc_month increment overflow",
+        ),
+        (
+            "err.datetime.month_underflow",
+            "inc_month decrement underflow",
+        ),
+        (
+            "err.math.not_number",
+            "element at index {0} is not a Number",
+        ),
+        ("err.math.window", "window {0} must be between 1 and {1}"),
+        (
+            "err.math.empty",
+            "can not compute the {0} of an empty array",
+        ),
+        (
+            "err.math.quantile_empty",
+            "can not compute the quantile of an empty array",
+        ),
+        (
+            "err.math.quantile_range",
+            "quantile {0} must be between 0 and 1",
+        ),
+        (
+            "err.math.percentile_range",
+            "percentile {0} must be between 0 and 100",
+        ),
+        ("err.math.interpolation", "unknown interpolation '{0}'"),
+        (
+            "err.math.clamp_range",
+            "minimum {0} must not be greater than maximum {1}",
+        ),
+        ("err.math.random", "{0}"),
+        (
+            "err.money.parse",
+            "'{1}' is not part of a valid amount in '{0}'",
+        ),
+    ];
+}
+
+impl MessageCatalog for DefaultCatalog {
+    fn template(&self, key: &str) -> Option<&str> {
+        Self::MESSAGES
+            .iter()
+            .find(|(message_key, _)| *message_key == key)
+            .map(|(_, template)| *template)
+    }
+}
+
+/// Renders a message key with the template of the catalog, the [`DefaultCatalog`]
+/// or the key itself, replacing all placeholders with the arguments.
+pub(crate) fn render(catalog: &dyn MessageCatalog, key: &str, args: &[String]) -> String {
+    let template = catalog
+        .template(key)
+        .or_else(|| DefaultCatalog.template(key))
+        .unwrap_or(key);
+
+    // a single pass, so placeholders inside of arguments are not replaced
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let (before, placeholder) = rest.split_at(start);
+        message.push_str(before);
+
+        let arg = placeholder.find('}').and_then(|end| {
+            let index = placeholder[1..end].parse::<usize>().ok()?;
+            Some((args.get(index)?, end))
+        });
+
+        match arg {
+            Some((arg, end)) => {
+                message.push_str(arg);
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                message.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+
+    message.push_str(rest);
+    message
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{render, DefaultCatalog, MessageCatalog};
+
+    struct Shouting;
+
+    impl MessageCatalog for Shouting {
+        fn template(&self, key: &str) -> Option<&str> {
+            (key == "err.param.type").then_some("WRONG TYPE")
+        }
+    }
+
+    #[test]
+    fn unique_keys() {
+        let keys: HashSet<&str> = DefaultCatalog::MESSAGES
+            .iter()
+            .map(|(key, _)| *key)
+            .collect();
+        assert_eq!(DefaultCatalog::MESSAGES.len(), keys.len());
+    }
+
+    #[test]
+    fn render_fallback() {
+        assert_eq!("WRONG TYPE", render(&Shouting, "err.param.type", &[]));
+        assert_eq!(
+            "index \"4\" is out of bounds",
+            render(&Shouting, "err.index.bounds", &[String::from("4")])
+        );
+        assert_eq!("err.unknown", render(&Shouting, "err.unknown", &[]));
+        assert_eq!(
+            "'{0}' can not be converted to a Boolean",
+            render(&Shouting, "err.bool.parse", &[String::from("{0}")])
+        );
+        assert_eq!(
+            "window 0 must be between 1 and 3",
+            render(
+                &DefaultCatalog,
+                "err.math.window",
+                &[String::from("0"), String::from("3")]
+            )
+        );
+    }
+}
@@ -1,20 +1,157 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use std::{fmt::Display, str::FromStr};
+
 use crate::{error::Error, token::Token};
 
 /// A binary or arithemtic operator.
+///
+/// The postfix keywords `is empty` and `is not empty` are represented by the
+/// unary [`Operator::IsEmpty`] and [`Operator::IsNotEmpty`], so the interpreter
+/// can treat undefined variables as empty.
+///
+/// # Display and Serialization
+///
+/// An `Operator` is displayed, parsed and serialized by its SLAC syntax
+/// (e.g. `+`, `<>`, `and`, `is not empty`). These names are stable and can be
+/// used by external tools to build an [`Expression`](crate::Expression).
+/// The [`Operator::TernaryCondition`] has no symbol of its own and uses `if`.
+///
+/// The camelCase variant names of previous versions (e.g. `plus`) are still
+/// accepted when deserializing, but are deprecated.
 #[derive(Debug, PartialEq, PartialOrd, Eq, Clone, Copy)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
-#[rustfmt::skip]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Operator {
-    Plus, Minus, Multiply, Divide,
-    Greater, GreaterEqual,
-    Less, LessEqual,
-    Equal, NotEqual,
-    And, Or, Xor, Not, 
-    Div, Mod,
+    #[cfg_attr(feature = "serde", serde(rename = "+", alias = "plus"))]
+    Plus,
+    #[cfg_attr(feature = "serde", serde(rename = "-", alias = "minus"))]
+    Minus,
+    #[cfg_attr(feature = "serde", serde(rename = "*", alias = "multiply"))]
+    Multiply,
+    #[cfg_attr(feature = "serde", serde(rename = "/", alias = "divide"))]
+    Divide,
+    #[cfg_attr(feature = "serde", serde(rename = ">", alias = "greater"))]
+    Greater,
+    #[cfg_attr(feature = "serde", serde(rename = ">=", alias = "greaterEqual"))]
+    GreaterEqual,
+    #[cfg_attr(feature = "serde", serde(rename = "<", alias = "less"))]
+    Less,
+    #[cfg_attr(feature = "serde", serde(rename = "<=", alias = "lessEqual"))]
+    LessEqual,
+    #[cfg_attr(feature = "serde", serde(rename = "=", alias = "equal"))]
+    Equal,
+    #[cfg_attr(feature = "serde", serde(rename = "<>", alias = "notEqual"))]
+    NotEqual,
+    #[cfg_attr(feature = "serde", serde(rename = "and"))]
+    And,
+    #[cfg_attr(feature = "serde", serde(rename = "or"))]
+    Or,
+    #[cfg_attr(feature = "serde", serde(rename = "xor"))]
+    Xor,
+    #[cfg_attr(feature = "serde", serde(rename = "not"))]
+    Not,
+    #[cfg_attr(feature = "serde", serde(rename = "div"))]
+    Div,
+    #[cfg_attr(feature = "serde", serde(rename = "mod"))]
+    Mod,
+    #[cfg_attr(feature = "serde", serde(rename = "is empty", alias = "isEmpty"))]
+    IsEmpty,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "is not empty", alias = "isNotEmpty")
+    )]
+    IsNotEmpty,
+    #[cfg_attr(feature = "serde", serde(rename = "if", alias = "ternaryCondition"))]
     TernaryCondition,
+    #[cfg_attr(feature = "serde", serde(rename = "implies"))]
+    Implies,
+    #[cfg_attr(feature = "serde", serde(rename = "iff"))]
+    Iff,
+    #[cfg_attr(feature = "serde", serde(rename = "in"))]
+    In,
+    #[cfg_attr(feature = "serde", serde(rename = "~="))]
+    ApproxEqual,
+}
+
+impl Operator {
+    /// All operators, e.g. for iteration by external tools.
+    pub const ALL: &'static [Operator] = &[
+        Operator::Plus,
+        Operator::Minus,
+        Operator::Multiply,
+        Operator::Divide,
+        Operator::Greater,
+        Operator::GreaterEqual,
+        Operator::Less,
+        Operator::LessEqual,
+        Operator::Equal,
+        Operator::NotEqual,
+        Operator::And,
+        Operator::Or,
+        Operator::Xor,
+        Operator::Not,
+        Operator::Div,
+        Operator::Mod,
+        Operator::IsEmpty,
+        Operator::IsNotEmpty,
+        Operator::TernaryCondition,
+        Operator::Implies,
+        Operator::Iff,
+        Operator::In,
+        Operator::ApproxEqual,
+    ];
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Operator::Plus => "+",
+            Operator::Minus => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Equal => "=",
+            Operator::NotEqual => "<>",
+            Operator::And => "and",
+            Operator::Or => "or",
+            Operator::Xor => "xor",
+            Operator::Not => "not",
+            Operator::Div => "div",
+            Operator::Mod => "mod",
+            Operator::IsEmpty => "is empty",
+            Operator::IsNotEmpty => "is not empty",
+            Operator::TernaryCondition => "if",
+            Operator::Implies => "implies",
+            Operator::Iff => "iff",
+            Operator::In => "in",
+            Operator::ApproxEqual => "~=",
+        };
+
+        write!(f, "{symbol}")
+    }
+}
+
+/// Parses an [`Operator`] from its case-insensitive SLAC syntax.
+///
+/// # Errors
+///
+/// Returns [`Error::UnknownOperator`] if the string is not a valid operator.
+impl FromStr for Operator {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowercase = s.to_lowercase();
+
+        Operator::ALL
+            .iter()
+            .find(|operator| operator.to_string() == lowercase)
+            .copied()
+            .ok_or(Error::UnknownOperator(s.to_string()))
+    }
 }
 
 /// Convert a [`Token`] into an [`Operator`].
@@ -37,9 +174,13 @@ impl TryFrom<&Token> for Operator {
             Token::LessEqual => Ok(Operator::LessEqual),
             Token::Equal => Ok(Operator::Equal),
             Token::NotEqual => Ok(Operator::NotEqual),
+            Token::ApproxEqual => Ok(Operator::ApproxEqual),
             Token::And => Ok(Operator::And),
             Token::Or => Ok(Operator::Or),
             Token::Xor => Ok(Operator::Xor),
+            Token::Implies => Ok(Operator::Implies),
+            Token::Iff => Ok(Operator::Iff),
+            Token::In => Ok(Operator::In),
             Token::Not => Ok(Operator::Not),
             Token::Div => Ok(Operator::Div),
             Token::Mod => Ok(Operator::Mod),
@@ -47,3 +188,63 @@ impl TryFrom<&Token> for Operator {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::Operator;
+    use crate::Error;
+
+    #[test]
+    fn display_from_str() {
+        for operator in Operator::ALL {
+            assert_eq!(Ok(*operator), Operator::from_str(&operator.to_string()));
+        }
+
+        assert_eq!(Ok(Operator::IsNotEmpty), Operator::from_str("IS NOT EMPTY"));
+        assert_eq!(Ok(Operator::NotEqual), "<>".parse());
+        assert_eq!(
+            Err(Error::UnknownOperator(String::from("plus"))),
+            Operator::from_str("plus")
+        );
+    }
+
+    /// The position of an operator in [`Operator::ALL`]. The match is
+    /// exhaustive, so a new variant fails to compile until it is listed here
+    /// and at the same position in [`Operator::ALL`].
+    fn position(operator: Operator) -> usize {
+        match operator {
+            Operator::Plus => 0,
+            Operator::Minus => 1,
+            Operator::Multiply => 2,
+            Operator::Divide => 3,
+            Operator::Greater => 4,
+            Operator::GreaterEqual => 5,
+            Operator::Less => 6,
+            Operator::LessEqual => 7,
+            Operator::Equal => 8,
+            Operator::NotEqual => 9,
+            Operator::And => 10,
+            Operator::Or => 11,
+            Operator::Xor => 12,
+            Operator::Not => 13,
+            Operator::Div => 14,
+            Operator::Mod => 15,
+            Operator::IsEmpty => 16,
+            Operator::IsNotEmpty => 17,
+            Operator::TernaryCondition => 18,
+            Operator::Implies => 19,
+            Operator::Iff => 20,
+            Operator::In => 21,
+            Operator::ApproxEqual => 22,
+        }
+    }
+
+    #[test]
+    fn all_operators() {
+        for (index, operator) in Operator::ALL.iter().enumerate() {
+            assert_eq!(index, position(*operator), "{operator:?}");
+        }
+    }
+}
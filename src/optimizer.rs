@@ -1,19 +1,335 @@
 //! Transformation routines to optimize an [`Expression`] AST.
 
+use std::collections::{HashMap, VecDeque};
+use std::mem::discriminant;
+
 use crate::environment::{Environment, FunctionResult};
-use crate::{execute, Expression, Operator, Result};
+use crate::{execute, Expression, Operator, Result, Value};
+
+use crate::stdlib::common::{TERNARY_IF_THEN, TRY};
+
+/// Statically determines the empty [`Value`] an [`Expression`] would produce
+/// on successful evaluation, without evaluating it.
+///
+/// Returns `None` if the resulting [`Value`] type can only be known at runtime
+/// (e.g. variables, function calls or the overloaded `+` operator). A
+/// [`Value::Null`] operand propagates through arithmetic, so the type of an
+/// arithmetic operation is only known if the types of its operands are.
+fn empty_value(expression: &Expression) -> Option<Value> {
+    match expression {
+        Expression::Literal { value } => Some(value.empty()),
+        Expression::Array { expressions: _ } => Some(Value::Array(vec![])),
+        Expression::Unary { right, operator } => match operator {
+            Operator::Not | Operator::IsEmpty | Operator::IsNotEmpty => Some(Value::Boolean(false)),
+            Operator::Minus => match empty_value(right)? {
+                Value::Null => Some(Value::Null),
+                _ => Some(Value::Number(0.0)),
+            },
+            _ => None,
+        },
+        Expression::Binary {
+            left,
+            right,
+            operator,
+        } => match operator {
+            Operator::Greater
+            | Operator::GreaterEqual
+            | Operator::Less
+            | Operator::LessEqual
+            | Operator::Equal
+            | Operator::NotEqual
+            | Operator::And
+            | Operator::Implies
+            | Operator::Iff => Some(Value::Boolean(false)),
+            Operator::In if matches!(empty_value(right), Some(Value::Array(_))) => {
+                Some(Value::Boolean(false))
+            }
+            Operator::Xor | Operator::ApproxEqual | Operator::In => {
+                propagate_null(left, right, Value::Boolean(false))
+            }
+            Operator::Minus
+            | Operator::Multiply
+            | Operator::Divide
+            | Operator::Div
+            | Operator::Mod => propagate_null(left, right, Value::Number(0.0)),
+            _ => None, // `+` is overloaded, `or` may return an untouched right side
+        },
+        Expression::Chain {
+            expressions: _,
+            operators: _,
+        } => Some(Value::Boolean(false)),
+        Expression::Ternary {
+            left: _,
+            middle,
+            right,
+            operator: _,
+        } => match (empty_value(middle), empty_value(right)) {
+            (Some(middle), Some(right)) if discriminant(&middle) == discriminant(&right) => {
+                Some(middle)
+            }
+            _ => None,
+        },
+        Expression::Variable { name: _ }
+        | Expression::Call { name: _, params: _ }
+        | Expression::Index {
+            target: _,
+            index: _,
+        } => None,
+    }
+}
+
+/// The empty `value` of an operation, or [`Value::Null`] if one of the operands
+/// is `null`. Returns `None` if the type of an operand is unknown.
+fn propagate_null(left: &Expression, right: &Expression, value: Value) -> Option<Value> {
+    match (empty_value(left)?, empty_value(right)?) {
+        (Value::Null, _) | (_, Value::Null) => Some(Value::Null),
+        _ => Some(value),
+    }
+}
+
+/// The maximum number of passes of [`optimize`]. Every pass folds the tree
+/// bottom-up and reaches the fixpoint on its own, so a second pass only
+/// confirms it. The limit guards against rewrites which would undo each other.
+pub const MAX_PASSES: usize = 8;
+
+/// The default number of results an [`OptimizerContext`] memoizes.
+pub const DEFAULT_MEMO_CAPACITY: usize = 1024;
+
+/// The name of a function and the literals of its parameters.
+type MemoKey = (String, Vec<String>);
+
+/// The memoized results of pure function calls with constant parameters.
+/// A capacity of `0` disables the memoization.
+#[derive(Debug, Default)]
+struct Memo {
+    results: HashMap<MemoKey, Value>,
+    /// The keys in the order of insertion, the oldest result is evicted first.
+    order: VecDeque<MemoKey>,
+    capacity: usize,
+}
+
+impl Memo {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    /// Builds the key of a call, if the memoization is enabled.
+    ///
+    /// The parameters are keyed by their literal instead of the [`Value`]
+    /// itself, as `1`, `true` and `'1'` are equal but must not share a result.
+    fn key(&self, name: &str, params: &[Expression]) -> Option<MemoKey> {
+        if self.capacity == 0 {
+            return None;
+        }
+
+        let literals = params
+            .iter()
+            .map(|param| match param {
+                Expression::Literal { value } => value.to_literal(),
+                _ => unreachable!("only calls with constant parameters are memoized"),
+            })
+            .collect();
+
+        Some((name.to_lowercase(), literals))
+    }
+
+    fn insert(&mut self, key: MemoKey, value: Value) {
+        if self.results.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.results.remove(&oldest);
+            }
+        }
+
+        self.order.push_back(key.clone());
+        self.results.insert(key, value);
+    }
+}
+
+/// Shares the results of pure function calls between the optimization of
+/// many expressions, see [`optimize_with_context`].
+///
+/// # Remarks
+///
+/// The context holds up to a fixed number of results, when it is full the
+/// oldest result is evicted. The results are only valid for the functions of
+/// the [`Environment`] the context was created with.
+///
+/// # Example
+/// ```
+/// use slac::optimizer::{optimize_with_context, OptimizerContext};
+/// use slac::{compile, stdlib::extend_environment, StaticEnvironment};
+///
+/// let mut env = StaticEnvironment::default();
+/// extend_environment(&mut env);
+///
+/// let mut context = OptimizerContext::new(&env);
+///
+/// for source in ["price > max(10, 20)", "amount < max(10, 20)"] {
+///     let mut ast = compile(source).unwrap();
+///     optimize_with_context(&mut context, &mut ast).unwrap();
+/// }
+///
+/// assert_eq!(1, context.len()); // `max` was called only once
+/// ```
+#[derive(Debug)]
+pub struct OptimizerContext<'a, E: Environment> {
+    env: &'a E,
+    memo: Memo,
+}
+
+impl<'a, E: Environment> OptimizerContext<'a, E> {
+    /// Creates a context which memoizes up to [`DEFAULT_MEMO_CAPACITY`] results.
+    pub fn new(env: &'a E) -> Self {
+        Self::with_capacity(env, DEFAULT_MEMO_CAPACITY)
+    }
+
+    /// Creates a context which memoizes up to `capacity` results, a capacity
+    /// of `0` disables the memoization.
+    pub fn with_capacity(env: &'a E, capacity: usize) -> Self {
+        Self {
+            env,
+            memo: Memo::with_capacity(capacity),
+        }
+    }
+
+    /// The number of memoized results.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.memo.results.len()
+    }
+
+    /// Checks if no result is memoized.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.memo.results.is_empty()
+    }
+
+    /// Removes all memoized results, e.g. after the functions of the
+    /// [`Environment`] changed.
+    pub fn clear(&mut self) {
+        self.memo.results.clear();
+        self.memo.order.clear();
+    }
+}
+
+/// Moves an [`Expression`] out of the tree, leaving a cheap placeholder behind.
+fn take(expression: &mut Expression) -> Expression {
+    std::mem::replace(
+        expression,
+        Expression::Literal {
+            value: Value::Boolean(false),
+        },
+    )
+}
+
+fn is_literal(expression: &Expression) -> bool {
+    matches!(expression, Expression::Literal { value: _ })
+}
+
+/// Checks if a constant left side decides a boolean operator on its own.
+fn short_circuits(left: &Expression, operator: Operator) -> bool {
+    let Expression::Literal { value } = left else {
+        return false;
+    };
+
+    match operator {
+        Operator::And | Operator::Implies => !value.as_bool(),
+        Operator::Or => value.as_bool(),
+        _ => false,
+    }
+}
+
+/// Checks if a constant left side makes a boolean operator return the boolean
+/// coercion of its right side, which is the right side itself if it always
+/// evaluates into a [`Value::Boolean`].
+fn passes_right(left: &Expression, operator: Operator, right: &Expression) -> bool {
+    let Expression::Literal { value } = left else {
+        return false;
+    };
+
+    let passes = match operator {
+        Operator::And => value.as_bool(),
+        Operator::Or => !value.as_bool(),
+        _ => false,
+    };
+
+    passes && is_boolean(right)
+}
+
+/// Moves the parameters of a ternary function call into an [`Expression::Ternary`].
+/// Returns `None` and leaves the parameters untouched if the call can not be transformed.
+///
+/// The missing third parameter of a two parameter call is the empty [`Value`]
+/// of the type of the second parameter, the call is only transformed if the
+/// type is known statically. A condition which is not always a Boolean is
+/// checked by `if_then(condition, true)`, which fails for a non-boolean
+/// condition like the original call.
+fn take_ternary(params: &mut Vec<Expression>) -> Option<Expression> {
+    let two_params = params.len() == 2;
+    let right = match params.as_slice() {
+        [_, _, _] => params.pop()?,
+        // the boolean check of a condition
+        [left, Expression::Literal {
+            value: Value::Boolean(true),
+        }] if !is_boolean(left) => return None,
+        [_, middle] => Expression::Literal {
+            value: empty_value(middle)?,
+        },
+        _ => return None,
+    };
+    let middle = params.pop()?;
+    let mut left = params.pop()?;
 
-use crate::stdlib::common::TERNARY_IF_THEN;
+    if two_params && !is_boolean(&left) {
+        left = Expression::Call {
+            name: String::from(TERNARY_IF_THEN),
+            params: vec![
+                left,
+                Expression::Literal {
+                    value: Value::Boolean(true),
+                },
+            ],
+        };
+    }
+
+    Some(Expression::Ternary {
+        left: Box::new(left),
+        middle: Box::new(middle),
+        right: Box::new(right),
+        operator: Operator::TernaryCondition,
+    })
+}
 
 /// Recursivly transforms ternary function calls into [`Expression::Ternary`].
 /// Three parameter [`crate::stdlib::common::if_then`] calls are transformed
 /// into a [`Operator::TernaryCondition`];
 ///
+/// Two parameter [`crate::stdlib::common::if_then`] calls are also transformed,
+/// if the type of the second parameter is known statically. The missing third
+/// parameter is synthesized as the empty [`Value`] of that type. Otherwise the
+/// call is kept and evaluated eagerly, e.g. the result of a function call may
+/// be `null`.
+///
+/// `found_const` is only set if a call was actually transformed, so a second
+/// call on the same tree leaves it untouched.
+///
 /// # Remarks
 ///
 /// While the [`crate::stdlib::common::if_then`] is eagerly evaluated, the
 /// [`Expression::Ternary`] supports short-circuit evaluation in the `TreeWalkingInterpreter`.
 pub fn transform_ternary(expression: &mut Expression, found_const: &mut bool) {
+    if let Expression::Call { name, params } = expression {
+        if name == TERNARY_IF_THEN {
+            if let Some(ternary) = take_ternary(params) {
+                *found_const = true;
+                *expression = ternary;
+            }
+        }
+    }
+
     match expression {
         Expression::Unary { right, operator: _ } => {
             transform_ternary(right, found_const);
@@ -22,6 +338,10 @@ pub fn transform_ternary(expression: &mut Expression, found_const: &mut bool) {
             left,
             right,
             operator: _,
+        }
+        | Expression::Index {
+            target: left,
+            index: right,
         } => {
             transform_ternary(left, found_const);
             transform_ternary(right, found_const);
@@ -36,169 +356,764 @@ pub fn transform_ternary(expression: &mut Expression, found_const: &mut bool) {
             transform_ternary(middle, found_const);
             transform_ternary(right, found_const);
         }
-        Expression::Array { expressions } => {
+        Expression::Array { expressions }
+        | Expression::Chain {
+            expressions,
+            operators: _,
+        }
+        | Expression::Call {
+            name: _,
+            params: expressions,
+        } => {
             for expr in expressions {
                 transform_ternary(expr, found_const);
             }
         }
-        Expression::Call { name, params } if (name == TERNARY_IF_THEN) => {
-            if let [left, middle, right] = params.as_slice() {
-                *found_const = true;
-                *expression = Expression::Ternary {
-                    left: Box::new(left.clone()),
-                    middle: Box::new(middle.clone()),
-                    right: Box::new(right.clone()),
-                    operator: Operator::TernaryCondition,
+        _ => (),
+    }
+}
+
+fn expressions_are_const(expressions: &[Expression]) -> bool {
+    expressions.iter().all(is_literal)
+}
+
+/// Evaluates [`Expression::Unary`], [`Expression::Binary`], [`Expression::Index`], [`Expression::Chain`] and [`Expression::Array`] into a single
+/// [`Expression::Literal`] if all arguments are also an [`Expression::Literal`].
+///
+/// Evaluates [`Operator::TernaryCondition`] [`Expression::Ternary`] into either
+/// the second or third argument, if the first argument is a [`Expression::Literal`].
+///
+/// Evaluates [`Expression::Call`] into a single [`Expression::Literal`] if all parameters
+/// are [`Expression::Literal`] and the function is a pure function.
+///
+/// Evaluates `and`, `or` and `implies` into a single [`Expression::Literal`] if
+/// a constant left side decides them on their own, e.g. `false and price > 10`.
+/// The right side is never evaluated at runtime, so it is dropped unevaluated.
+/// A constant left side which does not decide `and` or `or` is removed if the
+/// right side always evaluates into a boolean, e.g. `true and price > 10`.
+///
+/// Evaluates `try` calls into the first argument if it is a [`Expression::Literal`]
+/// or into the fallback if the first argument always fails.
+///
+/// # Remarks
+///
+/// The tree is folded bottom-up and in place, so a single call folds all
+/// nested constants. `found_const` is only set if the tree was rewritten.
+///
+/// # Errors
+///
+/// Will return [`crate::Error`] if constant evaluation is not possible.
+pub fn fold_constants(
+    env: &impl Environment,
+    expression: &mut Expression,
+    found_const: &mut bool,
+) -> Result<()> {
+    if fold(env, expression, false, &mut Memo::default())? {
+        *found_const = true;
+    }
+
+    Ok(())
+}
+
+/// Replaces an [`Expression`] with the [`Value`] it evaluates to.
+fn evaluate(env: &impl Environment, expression: &mut Expression) -> Result<bool> {
+    *expression = Expression::Literal {
+        value: execute(env, expression)?,
+    };
+
+    Ok(true)
+}
+
+/// Folds the children of an [`Expression`] before the expression itself.
+/// Transforms ternary function calls on the way, if `ternary` is set.
+/// Returns `true` if the tree was rewritten.
+fn fold(
+    env: &impl Environment,
+    expression: &mut Expression,
+    ternary: bool,
+    memo: &mut Memo,
+) -> Result<bool> {
+    match expression {
+        Expression::Literal { value: _ } | Expression::Variable { name: _ } => Ok(false),
+        Expression::Unary { right, operator: _ } => {
+            let changed = fold(env, right, ternary, memo)?;
+
+            if is_literal(right) {
+                evaluate(env, expression)
+            } else {
+                Ok(changed)
+            }
+        }
+        Expression::Binary {
+            left,
+            right,
+            operator,
+        } => {
+            let changed = fold(env, left, ternary, memo)?;
+
+            // the right side is never evaluated and may never succeed
+            if short_circuits(left, *operator) {
+                return evaluate(env, expression);
+            }
+
+            let changed = if matches!(operator, Operator::And | Operator::Or | Operator::Implies) {
+                fold_lazy(env, right, ternary, memo) || changed
+            } else {
+                fold(env, right, ternary, memo)? || changed
+            };
+
+            if is_literal(left) && is_literal(right) {
+                evaluate(env, expression)
+            } else if passes_right(left, *operator, right) {
+                // other right sides are kept, the operator is their cheapest coercion
+                *expression = take(right);
+
+                Ok(true)
+            } else {
+                Ok(changed)
+            }
+        }
+        Expression::Index { target, index } => {
+            let changed = fold(env, target, ternary, memo)?;
+            let changed = fold(env, index, ternary, memo)? || changed;
+
+            if is_literal(target) && is_literal(index) {
+                evaluate(env, expression)
+            } else {
+                Ok(changed)
+            }
+        }
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            operator,
+        } => {
+            let changed = fold(env, left, ternary, memo)?;
+
+            match (left.as_ref(), operator) {
+                // only the taken branch is folded, the other one may never succeed
+                (Expression::Literal { value }, Operator::TernaryCondition) => {
+                    *expression = if value.as_bool() {
+                        take(middle)
+                    } else {
+                        take(right)
+                    };
+                    fold(env, expression, ternary, memo)?;
+
+                    Ok(true)
                 }
+                _ => {
+                    let changed = fold(env, middle, ternary, memo)? || changed;
+                    let changed = fold(env, right, ternary, memo)? || changed;
+
+                    Ok(changed)
+                }
+            }
+        }
+        Expression::Array { expressions }
+        | Expression::Chain {
+            expressions,
+            operators: _,
+        } => {
+            let changed = fold_all(env, expressions, ternary, memo)?;
+
+            if expressions_are_const(expressions) {
+                evaluate(env, expression)
             } else {
-                for expr in params {
-                    transform_ternary(expr, found_const);
+                Ok(changed)
+            }
+        }
+        Expression::Call { name, params }
+            if name.eq_ignore_ascii_case(TRY) && params.len() == 2 =>
+        {
+            match fold(env, &mut params[0], ternary, memo) {
+                Err(_) => {
+                    *expression = take(&mut params[1]);
+                    fold(env, expression, ternary, memo)?;
+
+                    Ok(true)
+                }
+                Ok(_) if is_literal(&params[0]) => {
+                    *expression = take(&mut params[0]);
+
+                    Ok(true)
                 }
+                Ok(changed) => Ok(fold(env, &mut params[1], ternary, memo)? || changed),
+            }
+        }
+        Expression::Call { name, params } if ternary && name == TERNARY_IF_THEN => {
+            // the branches are not folded before the transformation, a constant
+            // condition would discard one of them
+            if let Some(transformed) = take_ternary(params) {
+                *expression = transformed;
+                fold(env, expression, ternary, memo)?;
+
+                return Ok(true);
+            }
+
+            let changed = fold_all(env, params, ternary, memo)?;
+
+            // the folded parameters may reveal the type of the second parameter
+            if let Some(transformed) = take_ternary(params) {
+                *expression = transformed;
+                fold(env, expression, ternary, memo)?;
+
+                Ok(true)
+            } else {
+                fold_call(env, expression, changed, memo)
             }
         }
         Expression::Call { name: _, params } => {
-            for expr in params {
-                transform_ternary(expr, found_const);
+            let changed = fold_all(env, params, ternary, memo)?;
+
+            fold_call(env, expression, changed, memo)
+        }
+    }
+}
+
+/// Folds an [`Expression`] which is only evaluated depending on another
+/// operand, like the right side of `and`. The expression is left unfolded if
+/// folding fails, its error may never occur during execution.
+fn fold_lazy(
+    env: &impl Environment,
+    expression: &mut Expression,
+    ternary: bool,
+    memo: &mut Memo,
+) -> bool {
+    let mut folded = expression.clone();
+
+    match fold(env, &mut folded, ternary, memo) {
+        Ok(changed) => {
+            *expression = folded;
+            changed
+        }
+        Err(_) => false,
+    }
+}
+
+fn fold_all(
+    env: &impl Environment,
+    expressions: &mut [Expression],
+    ternary: bool,
+    memo: &mut Memo,
+) -> Result<bool> {
+    let mut changed = false;
+
+    for expression in expressions {
+        changed = fold(env, expression, ternary, memo)? || changed;
+    }
+
+    Ok(changed)
+}
+
+/// Inlines a call of a pure function with constant parameters, the result is
+/// taken from and added to the [`Memo`].
+fn fold_call(
+    env: &impl Environment,
+    expression: &mut Expression,
+    changed: bool,
+    memo: &mut Memo,
+) -> Result<bool> {
+    let Expression::Call { name, params } = expression else {
+        return Ok(changed);
+    };
+
+    if !expressions_are_const(params) {
+        return Ok(changed);
+    }
+
+    // only inline pure functions
+    if !matches!(
+        env.function_exists(name, params.len()),
+        FunctionResult::Exists { pure: true }
+    ) {
+        return Ok(changed);
+    }
+
+    let Some(key) = memo.key(name, params) else {
+        return evaluate(env, expression);
+    };
+
+    let value = match memo.results.get(&key) {
+        Some(value) => value.clone(),
+        None => {
+            let value = execute(env, expression)?;
+            memo.insert(key, value.clone());
+            value
+        }
+    };
+
+    *expression = Expression::Literal { value };
+
+    Ok(true)
+}
+
+/// Checks if an [`Expression`] never fails because of an undefined variable,
+/// e.g. since it compares it to an empty value.
+fn absorbs_undefined(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal { value: _ } => true,
+        Expression::Variable { name: _ } => false,
+        Expression::Unary {
+            right: _,
+            operator: Operator::IsEmpty | Operator::IsNotEmpty,
+        } => true,
+        Expression::Unary { right, operator: _ } => absorbs_undefined(right),
+        Expression::Binary {
+            left: _,
+            right: _,
+            operator: Operator::Equal | Operator::NotEqual,
+        } => true,
+        // an undefined left side is absorbed, the right side is evaluated on its own
+        Expression::Binary {
+            left: _,
+            right,
+            operator: Operator::And | Operator::Or | Operator::Implies,
+        } => absorbs_undefined(right),
+        Expression::Binary {
+            left,
+            right,
+            operator: _,
+        }
+        | Expression::Index {
+            target: left,
+            index: right,
+        } => absorbs_undefined(left) && absorbs_undefined(right),
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            operator: _,
+        } => absorbs_undefined(left) && absorbs_undefined(middle) && absorbs_undefined(right),
+        Expression::Array { expressions }
+        | Expression::Chain {
+            expressions,
+            operators: _,
+        }
+        | Expression::Call {
+            name: _,
+            params: expressions,
+        } => expressions.iter().all(absorbs_undefined),
+    }
+}
+
+/// The side of an [`Expression::Binary`] which remains after simplifying it.
+enum Side {
+    Left,
+    Right,
+}
+
+/// Returns the side a binary operation can be replaced with, if the other side
+/// is the identity element of the operator and removing it can not change the
+/// result of a successful or failing evaluation.
+fn identity_side(left: &Expression, operator: Operator, right: &Expression) -> Option<Side> {
+    let number = |expression: &Expression, identity: f64| matches!(expression, Expression::Literal { value: Value::Number(number) } if *number == identity);
+    let boolean = |expression: &Expression, identity: bool| matches!(expression, Expression::Literal { value: Value::Boolean(boolean) } if *boolean == identity);
+
+    match operator {
+        Operator::Plus | Operator::Multiply => {
+            let identity = if operator == Operator::Plus { 0.0 } else { 1.0 };
+
+            if number(right, identity) && is_numeric(left) {
+                Some(Side::Left)
+            } else if number(left, identity) && is_numeric(right) {
+                Some(Side::Right)
+            } else {
+                None
+            }
+        }
+        Operator::Minus if number(right, 0.0) && is_numeric(left) => Some(Side::Left),
+        Operator::Divide if number(right, 1.0) && is_numeric(left) => Some(Side::Left),
+        // an undefined variable on the left side is absorbed by `and` and `or`
+        Operator::And | Operator::Or => {
+            let identity = operator == Operator::And;
+
+            if boolean(right, identity) && is_boolean(left) && absorbs_undefined(left) {
+                Some(Side::Left)
+            } else if boolean(left, identity) && is_boolean(right) {
+                Some(Side::Right)
+            } else {
+                None
+            }
+        }
+        Operator::Xor | Operator::Iff => {
+            let identity = operator == Operator::Iff;
+
+            if boolean(right, identity) && is_boolean(left) {
+                Some(Side::Left)
+            } else if boolean(left, identity) && is_boolean(right) {
+                Some(Side::Right)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Simplifies the children of an [`Expression`] before the expression itself.
+/// Returns `true` if the tree was rewritten.
+fn simplify(expression: &mut Expression) -> bool {
+    let mut changed = match expression {
+        Expression::Literal { value: _ } | Expression::Variable { name: _ } => false,
+        Expression::Unary { right, operator: _ } => simplify(right),
+        Expression::Binary {
+            left,
+            right,
+            operator: _,
+        }
+        | Expression::Index {
+            target: left,
+            index: right,
+        } => simplify(left) | simplify(right),
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            operator: _,
+        } => simplify(left) | simplify(middle) | simplify(right),
+        Expression::Array { expressions }
+        | Expression::Chain {
+            expressions,
+            operators: _,
+        }
+        | Expression::Call {
+            name: _,
+            params: expressions,
+        } => {
+            let mut changed = false;
+
+            for expression in expressions {
+                changed = simplify(expression) || changed;
             }
+
+            changed
+        }
+    };
+
+    while let Expression::Binary {
+        left,
+        right,
+        operator,
+    } = expression
+    {
+        *expression = match identity_side(left, *operator, right) {
+            Some(Side::Left) => take(left),
+            Some(Side::Right) => take(right),
+            None => break,
+        };
+        changed = true;
+    }
+
+    changed
+}
+
+/// Rewrites binary operations with the identity element of their operator
+/// into the bare operand, e.g. `amount * 1` into `amount`.
+///
+/// - `x + 0`, `0 + x`, `x - 0`, `x * 1`, `1 * x` and `x / 1` are rewritten
+///   into `x`, if `x` always evaluates into a number.
+/// - `true and x`, `false or x`, `x xor false`, `false xor x`, `x iff true`
+///   and `true iff x` are rewritten into `x`, if `x` always evaluates into a
+///   boolean.
+/// - `x and true` and `x or false` are rewritten into `x`, if `x` additionally
+///   never fails because of an undefined variable.
+///
+/// # Remarks
+///
+/// A rewrite never changes the result of an evaluation, failing evaluations
+/// included. The only exception is the sign of a zero: `x + 0` evaluates
+/// into `0` for a negative zero, the rewritten `x` into `-0`, which is equal.
+///
+/// Operands of an unknown type are kept, since `'a' * 1` fails while `'a'`
+/// does not. `x and true` results in `false` if `x` fails because of an
+/// undefined variable, while `x` on its own fails. Absorbing operations like
+/// `x * 0`, `x and false` or `x or true` are never rewritten, `x` may fail
+/// or evaluate into `NaN` or `null`.
+pub fn simplify_identities(expression: &mut Expression) {
+    simplify(expression);
+}
+
+/// Transforms an [`Expression`] tree by applying [`transform_ternary`] and
+/// [`fold_constants`] in a single bottom-up pass followed by
+/// [`simplify_identities`], repeated until no further optimization is possible
+/// or [`MAX_PASSES`] is reached.
+///
+/// # Idempotence
+///
+/// Optimizing an already optimized tree with the same [`Environment`] is a
+/// no-op, so cached ASTs can safely be optimized again. Debug builds assert
+/// this by running an additional pass which must not rewrite anything.
+///
+/// # Errors
+///
+/// Will return [`crate::Error`] if constant evaluation is not possible.
+pub fn optimize(env: &impl Environment, expression: &mut Expression) -> Result<()> {
+    optimize_with_memo(env, expression, &mut Memo::default())
+}
+
+/// Transforms an [`Expression`] tree like [`optimize`], but shares the results
+/// of pure function calls with constant parameters between all expressions
+/// optimized with the same [`OptimizerContext`]. A pure function is only
+/// called once for the same parameters, e.g. across hundreds of rules.
+///
+/// # Errors
+///
+/// Will return [`crate::Error`] if constant evaluation is not possible.
+pub fn optimize_with_context<E: Environment>(
+    context: &mut OptimizerContext<'_, E>,
+    expression: &mut Expression,
+) -> Result<()> {
+    optimize_with_memo(context.env, expression, &mut context.memo)
+}
+
+fn optimize_with_memo(
+    env: &impl Environment,
+    expression: &mut Expression,
+    memo: &mut Memo,
+) -> Result<()> {
+    for _ in 0..MAX_PASSES {
+        let folded = fold(env, expression, true, memo)?;
+
+        if !(simplify(expression) || folded) {
+            break;
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    {
+        let rewritten = fold(env, expression, true, memo)? || simplify(expression);
+        debug_assert!(!rewritten, "optimize did not reach a fixpoint");
+    }
+
+    Ok(())
+}
+
+/// Checks if an [`Expression`] always evaluates into a [`Value::Boolean`] or
+/// fails, so a double negation of it can be removed.
+fn is_boolean(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal {
+            value: Value::Boolean(_),
+        }
+        | Expression::Unary {
+            operator: Operator::Not | Operator::IsEmpty | Operator::IsNotEmpty,
+            ..
+        }
+        | Expression::Chain { .. } => true,
+        Expression::Binary {
+            right, operator, ..
+        } => match operator {
+            Operator::Equal
+            | Operator::NotEqual
+            | Operator::Greater
+            | Operator::GreaterEqual
+            | Operator::Less
+            | Operator::LessEqual
+            | Operator::And
+            | Operator::Implies
+            | Operator::Iff => true,
+            // an undefined left side returns the right side untouched
+            Operator::Or => is_boolean(right),
+            // `xor`, `in` and `~=` may result in `Value::Null`
+            _ => false,
+        },
+        Expression::Ternary { middle, right, .. } => is_boolean(middle) && is_boolean(right),
+        _ => false,
+    }
+}
+
+/// Checks if an [`Expression`] always evaluates into a [`Value::Number`], a
+/// [`Value::Null`] or fails, so the identity element of an arithmetic operator
+/// can be removed from it.
+fn is_numeric(expression: &Expression) -> bool {
+    match expression {
+        Expression::Unary {
+            operator: Operator::Minus,
+            ..
         }
-        _ => (),
+        | Expression::Binary {
+            operator:
+                Operator::Minus | Operator::Multiply | Operator::Divide | Operator::Div | Operator::Mod,
+            ..
+        } => true,
+        _ => matches!(
+            empty_value(expression),
+            Some(Value::Number(_) | Value::Null)
+        ),
     }
 }
 
-fn expressions_are_const(expressions: &[Expression]) -> bool {
-    expressions
+/// The sort key of a commutative operand, variables are ordered before literals.
+fn operand_key(expression: &Expression) -> Option<(bool, String)> {
+    match expression {
+        Expression::Variable { name } => Some((false, name.clone())),
+        Expression::Literal { value } => Some((true, value.to_literal())),
+        _ => None,
+    }
+}
+
+/// Checks if the operands of a commutative [`Operator`] can be swapped without
+/// changing the result, the error of a failing evaluation included.
+fn may_swap(operator: Operator, left: &Expression, right: &Expression) -> bool {
+    let variables = [left, right]
         .iter()
-        .all(|e| matches!(e, Expression::Literal { value: _ }))
+        .filter(|operand| matches!(operand, Expression::Variable { .. }))
+        .count();
+    let numbers = || {
+        [left, right].iter().all(|operand| match operand {
+            Expression::Literal { value } => matches!(value, Value::Number(_)),
+            _ => true,
+        })
+    };
+
+    match operator {
+        // undefined variables are compared as empty on both sides
+        Operator::Equal | Operator::NotEqual => true,
+        // two undefined variables would report the other name, strings and
+        // arrays are concatenated in order
+        Operator::Plus => variables < 2 && numbers(),
+        Operator::Multiply | Operator::Xor | Operator::Iff => variables < 2,
+        // an undefined left side short-circuits
+        Operator::And | Operator::Or => variables == 0,
+        _ => false,
+    }
 }
 
-/// Evaluates [`Expression::Unary`], [`Expression::Binary`] [`Expression::Array`] into a single
-/// [`Expression::Literal`] if all arguments are also an [`Expression::Literal`].
+/// Rewrites a single node until none of the rules of [`normalize`] applies.
+fn normalize_node(expression: &mut Expression) {
+    loop {
+        match expression {
+            Expression::Unary {
+                right,
+                operator: Operator::Not,
+            } => match right.as_mut() {
+                Expression::Binary {
+                    operator: operator @ (Operator::Equal | Operator::NotEqual),
+                    ..
+                } => {
+                    *operator = if *operator == Operator::Equal {
+                        Operator::NotEqual
+                    } else {
+                        Operator::Equal
+                    };
+                    *expression = take(right);
+                }
+                Expression::Unary {
+                    right: inner,
+                    operator: Operator::Not,
+                } if is_boolean(inner) => {
+                    *expression = take(inner);
+                }
+                _ => return,
+            },
+            Expression::Binary {
+                left,
+                right,
+                operator,
+            } => {
+                if let (Some(left_key), Some(right_key)) = (operand_key(left), operand_key(right)) {
+                    if right_key < left_key && may_swap(*operator, left, right) {
+                        std::mem::swap(left, right);
+                    }
+                }
+
+                return;
+            }
+            _ => return,
+        }
+    }
+}
+
+/// Rewrites an [`Expression`] tree into a canonical form, so equivalent rules
+/// written in a different way result in the same AST, e.g. to deduplicate
+/// stored rules. The rewritten tree always evaluates into the same result.
 ///
-/// Evaluates [`Operator::TernaryCondition`] [`Expression::Ternary`] into either
-/// the second or third argument, if the first argument is a [`Expression::Literal`].
+/// - `not (a = b)` and `not (a <> b)` are rewritten into `a <> b` and `a = b`.
+/// - `not not a` is rewritten into `a`, if `a` always evaluates into a boolean.
+/// - The operands of `=`, `<>`, `+`, `*`, `and`, `or`, `xor` and `iff` are
+///   ordered if both are a variable or a literal, variables before literals.
 ///
-/// Evaluates [`Expression::Call`] into a single [`Expression::Literal`] if all parameters
-/// are [`Expression::Literal`] and the function is a pure function.
+/// # Remarks
 ///
-/// # Errors
+/// Operands are only ordered if this can not change the result. Two variables
+/// are only ordered for `=` and `<>`, an undefined variable would otherwise
+/// report a different name. `and` and `or` only order literals, since an
+/// undefined variable on the left side short-circuits them. `+` only orders
+/// number literals, as strings and arrays are concatenated in order.
 ///
-/// Will return [`crate::Error`] if constant evaluation is not possible.
-pub fn fold_constants(
-    env: &impl Environment,
-    expression: &mut Expression,
-    found_const: &mut bool,
-) -> Result<()> {
+/// The function is independent of [`optimize`] and idempotent, normalizing an
+/// already normalized tree is a no-op.
+///
+/// # Example
+/// ```
+/// use slac::{compile, optimizer::normalize};
+///
+/// let mut ast = compile("not ('active' = status)").unwrap();
+/// normalize(&mut ast);
+///
+/// assert_eq!(compile("status <> 'active'").unwrap(), ast);
+/// ```
+pub fn normalize(expression: &mut Expression) {
     match expression {
-        Expression::Unary { right, operator: _ } => match right.as_ref() {
-            Expression::Literal { value: _ } => {
-                *found_const = true;
-                *expression = Expression::Literal {
-                    value: execute(env, expression)?,
-                }
-            }
-            _ => fold_constants(env, right, found_const)?,
-        },
+        Expression::Unary { right, operator: _ } => normalize(right),
         Expression::Binary {
             left,
             right,
             operator: _,
+        }
+        | Expression::Index {
+            target: left,
+            index: right,
         } => {
-            if let (Expression::Literal { value: _ }, Expression::Literal { value: _ }) =
-                (left.as_ref(), right.as_ref())
-            {
-                *found_const = true;
-                *expression = Expression::Literal {
-                    value: execute(env, expression)?,
-                };
-            } else {
-                fold_constants(env, left, found_const)?;
-                fold_constants(env, right, found_const)?;
-            }
+            normalize(left);
+            normalize(right);
         }
         Expression::Ternary {
             left,
             middle,
             right,
-            operator,
+            operator: _,
         } => {
-            if let (Expression::Literal { value: left }, Operator::TernaryCondition) =
-                (left.as_ref(), operator)
-            {
-                *found_const = true;
-                if left.as_bool() {
-                    *expression = *middle.clone();
-                } else {
-                    *expression = *right.clone();
-                }
-            } else {
-                fold_constants(env, left, found_const)?;
-                fold_constants(env, middle, found_const)?;
-                fold_constants(env, right, found_const)?;
-            }
-        }
-        Expression::Array { expressions } if expressions_are_const(expressions) => {
-            *found_const = true;
-            *expression = Expression::Literal {
-                value: execute(env, expression)?,
-            };
-        }
-        Expression::Array { expressions } => {
-            for expr in expressions {
-                fold_constants(env, expr, found_const)?;
-            }
+            normalize(left);
+            normalize(middle);
+            normalize(right);
         }
-
-        Expression::Call { name, params } if expressions_are_const(params) => {
-            match env.function_exists(name, params.len()) {
-                // only inline pure functions
-                FunctionResult::Exists { pure } if pure => {
-                    *found_const = true;
-                    *expression = Expression::Literal {
-                        value: execute(env, expression)?,
-                    };
-                }
-                _ => (),
-            }
+        Expression::Array { expressions }
+        | Expression::Chain {
+            expressions,
+            operators: _,
         }
-        Expression::Call { name: _, params } => {
-            for expr in params {
-                fold_constants(env, expr, found_const)?;
+        | Expression::Call {
+            name: _,
+            params: expressions,
+        } => {
+            for expression in expressions {
+                normalize(expression);
             }
         }
-        _ => (),
-    };
-
-    Ok(())
-}
-
-/// Transforms an [`Expression`] tree by applying [`transform_ternary`] and
-/// [`fold_constants`] in a loop until no further optimization is possible.
-///
-/// # Errors
-///
-/// Will return [`crate::Error`] if constant evaluation is not possible.
-pub fn optimize(env: &impl Environment, expression: &mut Expression) -> Result<()> {
-    let mut found_const = false;
-
-    loop {
-        transform_ternary(expression, &mut found_const);
-        fold_constants(env, expression, &mut found_const)?;
-
-        if found_const {
-            found_const = false; // repeat until no further folding is possible
-        } else {
-            return Ok(());
-        }
+        Expression::Literal { value: _ } | Expression::Variable { name: _ } => (),
     }
+
+    normalize_node(expression);
 }
 
 #[cfg(test)]
 mod test {
 
-    use super::{optimize, transform_ternary};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{
+        fold, fold_constants, normalize, optimize, optimize_with_context, simplify_identities,
+        transform_ternary, Memo, OptimizerContext,
+    };
+    use crate::compile;
+    use crate::function::{Arity, Function};
     use crate::stdlib::common::TERNARY_IF_THEN;
     use crate::stdlib::extend_environment;
-    use crate::{Expression, Operator, StaticEnvironment, Value};
+    use crate::stdlib::NativeResult;
+    use crate::{execute, Expression, Operator, StaticEnvironment, Value};
 
     #[test]
     fn ternary_flat() {
@@ -276,6 +1191,111 @@ mod test {
         assert_eq!(ternary, expr);
     }
 
+    #[test]
+    fn ternary_two_params() {
+        let mut expr = Expression::Call {
+            name: String::from(TERNARY_IF_THEN),
+            params: vec![
+                Expression::Variable {
+                    name: String::from("condition"),
+                },
+                Expression::Literal {
+                    value: Value::String(String::from("Hello")),
+                },
+            ],
+        };
+
+        // the condition is not always a Boolean and is checked by `if_then`
+        let ternary = Expression::Ternary {
+            left: Box::new(Expression::Call {
+                name: String::from(TERNARY_IF_THEN),
+                params: vec![
+                    Expression::Variable {
+                        name: String::from("condition"),
+                    },
+                    Expression::Literal {
+                        value: Value::Boolean(true),
+                    },
+                ],
+            }),
+            middle: Box::new(Expression::Literal {
+                value: Value::String(String::from("Hello")),
+            }),
+            right: Box::new(Expression::Literal {
+                value: Value::String(String::new()),
+            }),
+            operator: Operator::TernaryCondition,
+        };
+
+        transform_ternary(&mut expr, &mut false);
+
+        assert_eq!(ternary, expr);
+    }
+
+    #[test]
+    fn ternary_two_params_unknown_type() {
+        let condition = Expression::Variable {
+            name: String::from("condition"),
+        };
+        let value = Expression::Variable {
+            name: String::from("value"),
+        };
+        let call = |params| Expression::Call {
+            name: String::from(TERNARY_IF_THEN),
+            params,
+        };
+
+        // the empty value of `value` is only known at runtime
+        let expected = call(vec![condition.clone(), value]);
+        let mut expr = expected.clone();
+        let mut found_const = false;
+
+        transform_ternary(&mut expr, &mut found_const);
+
+        assert_eq!(expected, expr);
+        assert!(!found_const);
+
+        // the boolean check of a condition is not transformed
+        let expected = call(vec![
+            condition,
+            Expression::Literal {
+                value: Value::Boolean(true),
+            },
+        ]);
+        let mut expr = expected.clone();
+
+        transform_ternary(&mut expr, &mut found_const);
+
+        assert_eq!(expected, expr);
+        assert!(!found_const);
+    }
+
+    #[test]
+    fn ternary_two_params_function_call() {
+        let call = Expression::Call {
+            name: String::from("expensive"),
+            params: vec![],
+        };
+        // `-expensive()` is `null` if `expensive()` returns `null`
+        let expected = Expression::Call {
+            name: String::from(TERNARY_IF_THEN),
+            params: vec![
+                Expression::Variable {
+                    name: String::from("condition"),
+                },
+                Expression::Unary {
+                    right: Box::new(call),
+                    operator: Operator::Minus,
+                },
+            ],
+        };
+        let mut expr = expected.clone();
+
+        transform_ternary(&mut expr, &mut false);
+
+        assert_eq!(expected, expr);
+    }
+
     #[test]
     fn fold_const_flat_binary() {
         let mut expr = Expression::Binary {
@@ -382,21 +1402,8 @@ mod test {
             }],
         };
 
-        let value = Expression::Array {
-            expressions: vec![Expression::Unary {
-                right: Box::new(Expression::Call {
-                    name: String::from(TERNARY_IF_THEN),
-                    params: vec![
-                        Expression::Literal {
-                            value: Value::Boolean(true),
-                        },
-                        Expression::Literal {
-                            value: Value::Number(3.0),
-                        },
-                    ],
-                }),
-                operator: Operator::Minus,
-            }],
+        let value = Expression::Literal {
+            value: Value::Array(vec![Value::Number(-3.0)]),
         };
         optimize(&StaticEnvironment::default(), &mut expr).unwrap();
 
@@ -450,4 +1457,324 @@ mod test {
 
         assert_eq!(value, expr);
     }
+
+    #[test]
+    fn fold_try() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        let mut expr = compile("try('a' - 1, 10 + 20)").unwrap();
+        optimize(&env, &mut expr).unwrap();
+        assert_eq!(compile("30").unwrap(), expr);
+
+        let mut expr = compile("try(1 + 2, 'a' - 1)").unwrap();
+        optimize(&env, &mut expr).unwrap();
+        assert_eq!(compile("3").unwrap(), expr);
+
+        let mut expr = compile("try(float(value), 1 + 1)").unwrap();
+        optimize(&env, &mut expr).unwrap();
+        assert_eq!(compile("try(float(value), 2)").unwrap(), expr);
+
+        let mut expr = compile("try(value, 'a' - 1)").unwrap();
+        assert!(optimize(&env, &mut expr).is_err());
+    }
+
+    #[test]
+    fn fold_boolean_operators() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        let cases = [
+            ("false and expensive > 10", "false"),
+            ("true or random(1) > 0", "true"),
+            ("false implies 1 - 'a'", "true"),
+            ("true and expensive > 10", "expensive > 10"),
+            ("false or not expensive", "not expensive"),
+            (
+                "1 > 0 and (expensive = 1 or expensive = 2)",
+                "expensive = 1 or expensive = 2",
+            ),
+            ("true and expensive", "true and expensive"),
+            (
+                "false or contains(expensive, 1)",
+                "false or contains(expensive, 1)",
+            ),
+            (
+                "true and (expensive xor true)",
+                "true and (expensive xor true)",
+            ),
+            ("expensive and true", "expensive and true"),
+        ];
+
+        let environment = |value: Option<Value>| {
+            let mut env = StaticEnvironment::default();
+            extend_environment(&mut env);
+            if let Some(value) = value {
+                env.add_variable("expensive", value);
+            }
+            env
+        };
+        let environments = [
+            environment(None),
+            environment(Some(Value::Number(20.0))),
+            environment(Some(Value::Null)),
+        ];
+
+        for (source, expected) in cases {
+            let original = compile(source).unwrap();
+            let mut expr = original.clone();
+            let mut found_const = false;
+
+            fold_constants(&env, &mut expr, &mut found_const).unwrap();
+            assert_eq!(compile(expected).unwrap(), expr, "{source}");
+            assert_eq!(source != expected, found_const, "{source}");
+
+            // `expensive` stays undefined in the first environment
+            if !source.contains("random") {
+                for env in &environments {
+                    assert_eq!(execute(env, &original), execute(env, &expr), "{source}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_pass() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        let mut expr = compile(
+            "price > 1 + 2 + 3 + 4 and if_then(value, if_then(true, max(1, 2) * 3)) = try(1 + 'a', 6)",
+        )
+        .unwrap();
+
+        assert!(fold(&env, &mut expr, true, &mut Memo::default()).unwrap());
+        assert!(!fold(&env, &mut expr, true, &mut Memo::default()).unwrap());
+
+        let mut expected =
+            compile("price > 10 and if_then(if_then(value, true), 6, 0) = 6").unwrap();
+        transform_ternary(&mut expected, &mut false);
+        assert_eq!(expected, expr);
+    }
+
+    #[test]
+    fn change_flags() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        let mut expr = compile("if_then(price, if_then(value, 1, 2), 3) + max(price, 1)").unwrap();
+
+        let mut found_const = false;
+        transform_ternary(&mut expr, &mut found_const);
+        assert!(found_const);
+
+        let mut found_const = false;
+        transform_ternary(&mut expr, &mut found_const);
+        assert!(!found_const);
+
+        let mut found_const = false;
+        fold_constants(&env, &mut expr, &mut found_const).unwrap();
+        assert!(!found_const);
+
+        let mut expr = compile("price + 1 + 2").unwrap();
+        let mut found_const = false;
+        fold_constants(&env, &mut expr, &mut found_const).unwrap();
+        assert!(!found_const); // `(price + 1) + 2` has no constant subtree
+    }
+
+    static HOLIDAY_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn holiday_table(params: &[Value]) -> NativeResult {
+        HOLIDAY_CALLS.fetch_add(1, Ordering::SeqCst);
+
+        Ok(Value::Array(params.to_vec()))
+    }
+
+    static SQUARE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn square(params: &[Value]) -> NativeResult {
+        SQUARE_CALLS.fetch_add(1, Ordering::SeqCst);
+
+        match params {
+            [Value::Number(value)] => Ok(Value::Number(value * value)),
+            _ => Ok(Value::Boolean(false)),
+        }
+    }
+
+    #[test]
+    fn context_memoizes_pure_calls() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+        env.add_function(Function::new(
+            holiday_table,
+            Arity::required(1),
+            "holiday_table(year: Number): Array",
+        ));
+
+        let mut context = OptimizerContext::new(&env);
+
+        for i in 0..10 {
+            let source = format!("contains(Holiday_Table(2024), day) or day = {i}");
+            let mut expr = compile(&source).unwrap();
+            optimize_with_context(&mut context, &mut expr).unwrap();
+
+            let mut expected = compile(&format!("contains([2024], day) or day = {i}")).unwrap();
+            optimize(&env, &mut expected).unwrap();
+            assert_eq!(expected, expr);
+        }
+
+        assert_eq!(1, HOLIDAY_CALLS.load(Ordering::SeqCst));
+        assert_eq!(1, context.len());
+
+        // equal but not identical parameters have their own result
+        let mut expr = compile("holiday_table(true) = holiday_table('1')").unwrap();
+        optimize_with_context(&mut context, &mut expr).unwrap();
+        assert_eq!(3, HOLIDAY_CALLS.load(Ordering::SeqCst));
+        assert_eq!(3, context.len());
+
+        // the plain `optimize` bypasses the memoization
+        let mut expr = compile("holiday_table(2024)").unwrap();
+        optimize(&env, &mut expr).unwrap();
+        assert_eq!(4, HOLIDAY_CALLS.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn context_capacity() {
+        let mut env = StaticEnvironment::default();
+        env.add_function(Function::new(
+            square,
+            Arity::required(1),
+            "square(value: Number): Number",
+        ));
+
+        let mut context = OptimizerContext::with_capacity(&env, 2);
+        let mut optimize_source = |source: &str| {
+            let mut expr = compile(source).unwrap();
+            optimize_with_context(&mut context, &mut expr).unwrap();
+            expr
+        };
+
+        assert_eq!(
+            compile("5").unwrap(),
+            optimize_source("square(1) + square(2)")
+        );
+        assert_eq!(2, SQUARE_CALLS.load(Ordering::SeqCst));
+
+        // the result of `square(1)` is evicted for `square(3)`
+        assert_eq!(compile("9").unwrap(), optimize_source("square(3)"));
+        assert_eq!(3, SQUARE_CALLS.load(Ordering::SeqCst));
+
+        assert_eq!(
+            compile("13").unwrap(),
+            optimize_source("square(2) + square(3)")
+        );
+        assert_eq!(3, SQUARE_CALLS.load(Ordering::SeqCst));
+
+        assert_eq!(compile("1").unwrap(), optimize_source("square(1)"));
+        assert_eq!(4, SQUARE_CALLS.load(Ordering::SeqCst));
+        assert_eq!(2, context.len());
+
+        context.clear();
+        assert!(context.is_empty());
+
+        // a capacity of zero disables the memoization
+        let mut context = OptimizerContext::with_capacity(&env, 0);
+        for _ in 0..3 {
+            let mut expr = compile("square(1)").unwrap();
+            optimize_with_context(&mut context, &mut expr).unwrap();
+        }
+        assert_eq!(7, SQUARE_CALLS.load(Ordering::SeqCst));
+        assert!(context.is_empty());
+    }
+
+    #[test]
+    fn normalize_rules() {
+        let normalized = |source: &str| {
+            let mut expr = compile(source).unwrap();
+            normalize(&mut expr);
+            expr
+        };
+
+        let cases = [
+            ("not ('active' = status)", "status <> 'active'"),
+            ("not (a <> b)", "a = b"),
+            ("not not (b = a)", "a = b"),
+            ("not not not (price > 1)", "not (price > 1)"),
+            ("not not flag", "not not flag"),
+            ("not not (a xor b)", "not not (a xor b)"),
+            ("1 + price", "price + 1"),
+            ("'a' + name", "'a' + name"),
+            ("2 * 3", "2 * 3"),
+            ("3 * 2", "2 * 3"),
+            ("b * a", "b * a"),
+            ("true and flag", "true and flag"),
+            ("true or false", "false or true"),
+            (
+                "max(2 * price, not not (1 < price))",
+                "max(price * 2, 1 < price)",
+            ),
+        ];
+
+        for (source, expected) in cases {
+            assert_eq!(compile(expected).unwrap(), normalized(source), "{source}");
+        }
+
+        let expr = normalized("not (b = 'x') and not not (1 * c > 2)");
+        assert_eq!(compile("b <> 'x' and c * 1 > 2").unwrap(), expr);
+
+        let mut again = expr.clone();
+        normalize(&mut again);
+        assert_eq!(expr, again);
+    }
+
+    #[test]
+    fn simplify_identities_rules() {
+        let simplified = |source: &str| {
+            let mut expr = compile(source).unwrap();
+            simplify_identities(&mut expr);
+            expr
+        };
+
+        let cases = [
+            ("(amount - 1) * 1", "amount - 1"),
+            ("0 + -amount", "-amount"),
+            ("(amount / 2 - 0) / 1 + 0", "amount / 2"),
+            ("max(1 * (amount * 2 * 1), 0)", "max(amount * 2, 0)"),
+            ("(a > 1) xor false", "a > 1"),
+            ("true iff (a > 1)", "a > 1"),
+            ("true and not (a = 1 and true)", "not (a = 1)"),
+            (
+                "(a <> b or false) and (false or a is empty)",
+                "a <> b and a is empty",
+            ),
+            // the type of the operand is unknown
+            ("amount * 1", "amount * 1"),
+            ("total + 0", "total + 0"),
+            ("'a' + 0", "'a' + 0"),
+            ("max(a, b) * 1", "max(a, b) * 1"),
+            ("(a + 1) + 0", "(a + 1) + 0"),
+            // absorbing elements may change errors, `NaN` and `null` results
+            ("(amount - 1) * 0", "(amount - 1) * 0"),
+            ("(a > 1) and false", "(a > 1) and false"),
+            ("(a > 1) or true", "(a > 1) or true"),
+            // `flag` must not be coerced into a boolean
+            ("true and flag", "true and flag"),
+            ("flag xor false", "flag xor false"),
+            // an undefined `a` is absorbed by `and` and `or`
+            ("(a > 1) and true", "(a > 1) and true"),
+            ("(a = 1 or a > 1) or false", "(a = 1 or a > 1) or false"),
+        ];
+
+        for (source, expected) in cases {
+            assert_eq!(compile(expected).unwrap(), simplified(source), "{source}");
+        }
+
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        // the folded constant exposes the identity
+        let mut expr = compile("(price - 0) * (3 - 2)").unwrap();
+        optimize(&env, &mut expr).unwrap();
+        assert_eq!(compile("price - 0").unwrap(), expr);
+    }
 }
@@ -0,0 +1,56 @@
+//! Re-exports the commonly used parts of the API, so a single glob import is
+//! enough for most applications.
+//!
+//! ```
+//! use slac::prelude::*;
+//!
+//! let mut env = StaticEnvironment::default();
+//! extend_environment(&mut env);
+//!
+//! let mut ast = compile("max(10, 20) + 1").unwrap();
+//! check_variables_and_functions(&env, &ast).unwrap();
+//! optimize(&env, &mut ast).unwrap();
+//!
+//! assert_eq!(Ok(Value::Number(21.0)), execute(&env, &ast));
+//! ```
+//!
+//! [`Result`] takes an optional second parameter, so the glob import does not
+//! break other uses of `Result<T, E>`. [`SlacResult`] is the same alias for
+//! code which prefers an unambiguous name.
+//!
+//! ```
+//! use slac::prelude::*;
+//!
+//! fn parse_limit(text: &str) -> Result<f64, std::num::ParseFloatError> {
+//!     text.parse()
+//! }
+//!
+//! fn check(source: &str) -> SlacResult<Expression> {
+//!     let ast = compile(source)?;
+//!     check_boolean_result(&ast)?;
+//!     Ok(ast)
+//! }
+//!
+//! assert_eq!(Ok(10.0), parse_limit("10"));
+//! assert!(check("price > 10").is_ok());
+//! ```
+//!
+//! The results of the compilation, validation, optimization and execution must
+//! be used, an ignored validation error is reported by the compiler.
+//!
+//! ```compile_fail
+//! #![deny(unused_must_use)]
+//! use slac::prelude::*;
+//!
+//! let env = StaticEnvironment::default();
+//! let ast = compile("price > 10").unwrap();
+//!
+//! check_variables_and_functions(&env, &ast); // the error is silently dropped
+//! ```
+
+#[doc(no_inline)]
+pub use crate::{
+    check_boolean_result, check_variables_and_functions, compile, environment::Environment,
+    execute, execute_as, optimize, stdlib::extend_environment, Error, Expression, Result,
+    SlacResult, StaticEnvironment, Value,
+};
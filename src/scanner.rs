@@ -1,8 +1,34 @@
+use std::fmt::Display;
 use std::vec;
 
 use crate::error::{Error, Result};
 use crate::token::Token;
-use crate::value::Value;
+use crate::value::{Value, NULL_LITERAL};
+
+/// The position of a [`Token`] or [`Expression`](crate::Expression) in the
+/// source, as a half-open range of *character* offsets. The 1-based `line` and
+/// `column` locate the `start` of the span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// Checks if the character at `offset` lies within the `Span`.
+    #[must_use]
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
 
 /// A lexer to split a string into a list of [`Tokens`](Token).
 pub struct Scanner<'a> {
@@ -10,6 +36,7 @@ pub struct Scanner<'a> {
     start: usize,
     current: usize,
     end: usize,
+    strict: bool,
 }
 
 impl<'a> Scanner<'a> {
@@ -27,33 +54,133 @@ impl<'a> Scanner<'a> {
     /// # Errors
     /// Returns an [`Error`] when encountering invalid input.
     pub fn tokenize(source: &'a str) -> Result<Vec<Token>> {
+        Scanner::scan(source, false)
+    }
+
+    /// Converts an input string into a list of [`Tokens`](Token) and rejects
+    /// integer literals which can not be represented as a [`Value::Number`]
+    /// without losing precision.
+    ///
+    /// # Examples
+    /// ```
+    /// use slac::{Error, Scanner};
+    ///
+    /// assert!(Scanner::tokenize_strict("9007199254740992").is_ok());
+    /// assert_eq!(
+    ///     Err(Error::ImpreciseNumberLiteral(String::from("9007199254740993"))),
+    ///     Scanner::tokenize_strict("9007199254740993")
+    /// );
+    /// ```
+    /// # Errors
+    /// Returns an [`Error`] when encountering invalid input or an imprecise number literal.
+    pub fn tokenize_strict(source: &'a str) -> Result<Vec<Token>> {
+        Scanner::scan(source, true)
+    }
+
+    /// Converts an input string into a list of [`Tokens`](Token) together with
+    /// their position in the source.
+    ///
+    /// # Examples
+    /// ```
+    /// use slac::{Scanner, Span, Token, Value};
+    ///
+    /// let tokens = Scanner::tokenize_with_spans("40 +\n  2").unwrap();
+    ///
+    /// assert_eq!(
+    ///     tokens,
+    ///     vec![
+    ///         (Token::Literal(Value::Number(40.0)), Span { start: 0, end: 2, line: 1, column: 1 }),
+    ///         (Token::Plus, Span { start: 3, end: 4, line: 1, column: 4 }),
+    ///         (Token::Literal(Value::Number(2.0)), Span { start: 7, end: 8, line: 2, column: 3 }),
+    ///     ]
+    /// );
+    /// ```
+    /// # Errors
+    /// Returns an [`Error`] when encountering invalid input.
+    pub fn tokenize_with_spans(source: &'a str) -> Result<Vec<(Token, Span)>> {
+        Scanner::scan_with_spans(source, false)
+    }
+
+    /// Finds the [`Token`] at a character offset, e.g. the cursor position in
+    /// an editor. A cursor directly behind a token also points to it, unless
+    /// another token starts there. Invalid input is scanned up to the first error.
+    ///
+    /// # Examples
+    /// ```
+    /// use slac::{Scanner, Span, Token};
+    ///
+    /// let name = Token::Identifier(String::from("max"));
+    /// let span = Span { start: 0, end: 3, line: 1, column: 1 };
+    ///
+    /// assert_eq!(Some((name.clone(), span)), Scanner::token_at("max(1)", 1));
+    /// assert_eq!(Some((name, span)), Scanner::token_at("max $", 3));
+    /// assert_eq!(None, Scanner::token_at("max $", 4));
+    /// ```
+    #[must_use]
+    pub fn token_at(source: &'a str, offset: usize) -> Option<(Token, Span)> {
+        let (tokens, _) = Scanner::scan_partial(source, false);
+
+        let containing = tokens.iter().position(|(_, span)| span.contains(offset));
+        let preceding = || tokens.iter().rposition(|(_, span)| span.end == offset);
+
+        containing
+            .or_else(preceding)
+            .map(|index| tokens[index].clone())
+    }
+
+    fn scan(source: &'a str, strict: bool) -> Result<Vec<Token>> {
+        let tokens = Scanner::scan_with_spans(source, strict)?;
+
+        Ok(tokens.into_iter().map(|(token, _)| token).collect())
+    }
+
+    pub(crate) fn scan_with_spans(source: &'a str, strict: bool) -> Result<Vec<(Token, Span)>> {
+        match Scanner::scan_partial(source, strict) {
+            (_, Some(error)) => Err(error),
+            (tokens, None) if tokens.is_empty() => Err(Error::Eof),
+            (tokens, None) => Ok(tokens),
+        }
+    }
+
+    /// Scans as many tokens as possible and stops at the first [`Error`].
+    pub(crate) fn scan_partial(
+        source: &'a str,
+        strict: bool,
+    ) -> (Vec<(Token, Span)>, Option<Error>) {
         let mut scanner = Scanner {
             source,
             start: 0,
             current: 0,
             end: source.chars().count(),
+            strict,
         };
 
-        let mut tokens: Vec<Token> = vec![];
+        let mut tokens: Vec<(Token, Span)> = vec![];
 
         scanner.skip_whitespace();
 
         while !scanner.is_at_end() {
-            tokens.push(scanner.next_token()?);
+            let start = scanner.current;
+
+            match scanner.next_token() {
+                Ok(token) => tokens.push((token, scanner.span(start, scanner.current))),
+                Err(error) => return (tokens, Some(error)),
+            }
+
             scanner.skip_whitespace();
         }
 
-        if tokens.is_empty() {
-            Err(Error::Eof)
-        } else {
-            Ok(tokens)
-        }
+        (tokens, None)
     }
 
     fn next_token(&mut self) -> Result<Token> {
         self.start = self.current;
         let next = self.next_char().ok_or(Error::Eof)?;
 
+        if next == 'r' && self.peek() == Some('\'') {
+            return self.raw_string();
+        }
+
         if Scanner::is_identifier_start(next) {
             return Ok(self.identifier());
         }
@@ -65,6 +192,7 @@ impl<'a> Scanner<'a> {
         match next {
             '\'' => self.string(),
             '.' => self.number(), // interprete .1 as 0.1
+            '$' if self.peek().is_some_and(|c| c.is_ascii_hexdigit()) => self.radix_number(16),
             '(' => Ok(Token::LeftParen),
             ')' => Ok(Token::RightParen),
             '[' => Ok(Token::LeftBracket),
@@ -77,7 +205,30 @@ impl<'a> Scanner<'a> {
             '=' => Ok(Token::Equal),
             '>' => Ok(self.greater()),
             '<' => Ok(self.lesser()),
-            _ => Err(Error::InvalidCharacter(next)),
+            '~' if self.peek() == Some('=') => Ok(self.encounter_double(Token::ApproxEqual)),
+            _ => Err(Error::InvalidCharacter {
+                character: next,
+                span: self.span(self.start, self.current),
+            }),
+        }
+    }
+
+    /// The [`Span`] between two character offsets, with the line and column
+    /// of the `start` offset.
+    fn span(&self, start: usize, end: usize) -> Span {
+        let (line, line_start) = self
+            .source
+            .chars()
+            .take(start)
+            .enumerate()
+            .filter(|(_, c)| *c == '\n')
+            .fold((1, 0), |(line, _), (index, _)| (line + 1, index + 1));
+
+        Span {
+            start,
+            end,
+            line,
+            column: start - line_start + 1,
         }
     }
 
@@ -89,9 +240,10 @@ impl<'a> Scanner<'a> {
         self.current += 1;
     }
 
+    /// Advances over digits and the `_` separators between them.
     fn advance_numeric(&mut self) {
         while let Some(c) = self.peek() {
-            if c.is_numeric() {
+            if c.is_numeric() || c == '_' {
                 self.advance();
             } else {
                 break;
@@ -112,19 +264,24 @@ impl<'a> Scanner<'a> {
         self.source.chars().nth(self.current + offset)
     }
 
+    /// Skips any interleaving of whitespace and comments between two tokens.
     fn skip_whitespace(&mut self) {
         loop {
             while let Some(' ' | '\r' | '\t' | '\n') = self.peek() {
                 self.advance();
             }
 
+            // a comment may be followed by more whitespace or another comment
             if !self.skip_comments() {
-                // repeat check for whitespace if comments where found
                 break;
             };
         }
     }
 
+    /// Skips a single line (`// ...`) or block (`{ ... }`) comment.
+    ///
+    /// Block comments nest and end at their matching `}` or EOF. A `//` inside
+    /// a block comment has no meaning, so `{ // } 1` is the literal `1`.
     fn skip_comments(&mut self) -> bool {
         match (self.peek_ahead(0), self.peek_ahead(1)) {
             (Some('/'), Some('/')) => {
@@ -176,23 +333,118 @@ impl<'a> Scanner<'a> {
         match ident.to_lowercase().as_str() {
             "true" => Token::Literal(Value::Boolean(true)),
             "false" => Token::Literal(Value::Boolean(false)),
+            NULL_LITERAL => Token::Literal(Value::Null),
             "and" => Token::And,
             "or" => Token::Or,
             "xor" => Token::Xor,
+            "implies" => Token::Implies,
+            "iff" => Token::Iff,
             "not" => Token::Not,
             "div" => Token::Div,
             "mod" => Token::Mod,
+            "is" => Token::Is,
+            "in" => Token::In,
+            "if" => Token::If,
+            "then" => Token::Then,
+            "else" => Token::Else,
             _ => Token::Identifier(ident),
         }
     }
 
-    fn extract_number(content: &str) -> Result<f64> {
-        content
+    fn extract_number(digits: &str, content: &str) -> Result<f64> {
+        digits
             .parse::<f64>()
-            .map_err(|o| Error::InvalidNumber(o.to_string()))
+            .map_err(|_| Error::InvalidNumber(content.to_string()))
+    }
+
+    /// Checks if an integer literal changes its value during the round-trip
+    /// through an f64. The `digits` of the literal may have an exponent.
+    fn is_imprecise(digits: &str, number: f64) -> bool {
+        if !number.is_finite() {
+            return true;
+        }
+
+        let (mantissa, exponent) = digits.split_once(['e', 'E']).unwrap_or((digits, "0"));
+        let (integral, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        // the exponent moves the decimal point within the digits of the mantissa
+        let digits = format!("{integral}{fraction}");
+        let point = isize::try_from(integral.len())
+            .ok()
+            .zip(exponent.parse::<isize>().ok())
+            .and_then(|(integral, exponent)| integral.checked_add(exponent))
+            .map_or(0, |point| usize::try_from(point).unwrap_or(0));
+
+        if digits
+            .get(point..)
+            .is_some_and(|f| f.chars().any(|c| c != '0'))
+        {
+            return false; // only integer literals are checked
+        }
+
+        let integral = match digits
+            .get(..point)
+            .unwrap_or(&digits)
+            .trim_start_matches('0')
+        {
+            "" => String::from("0"),
+            integral => integral.to_string() + &"0".repeat(point.saturating_sub(digits.len())),
+        };
+
+        format!("{number:.0}") != integral
+    }
+
+    /// Scans the digits of an integer literal with a prefix like `$`, `0x` or
+    /// `0b`, which has already been advanced.
+    fn radix_number(&mut self, radix: u32) -> Result<Token> {
+        let prefix_len = self.current - self.start;
+
+        // consume all alphanumerics, so `0b102` is reported as a whole
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '_' {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        let content = self.get_content(0);
+        let digits: String = content
+            .chars()
+            .skip(prefix_len)
+            .filter(|c| *c != '_')
+            .collect();
+        let number = u64::from_str_radix(&digits, radix)
+            .map_err(|_| Error::InvalidNumber(content.clone()))?;
+
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        if self.strict && (number as f64) as u128 != u128::from(number) {
+            return Err(Error::ImpreciseNumberLiteral(content));
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        Ok(Token::Literal(Value::Number(number as f64)))
     }
 
     fn number(&mut self) -> Result<Token> {
+        if self.get_content(0) == "0" {
+            match self.peek() {
+                Some('x' | 'X') => {
+                    self.advance();
+                    return self.radix_number(16);
+                }
+                Some('b' | 'B') => {
+                    self.advance();
+                    return self.radix_number(2);
+                }
+                _ => {}
+            }
+        }
+
         self.advance_numeric(); // advance integral
 
         if self.peek() == Some('.') {
@@ -205,8 +457,27 @@ impl<'a> Scanner<'a> {
             }
         }
 
+        if let Some('e' | 'E') = self.peek() {
+            self.advance(); // advance exponent marker
+
+            if let Some('+' | '-') = self.peek() {
+                self.advance();
+            }
+
+            if !self.peek().is_some_and(char::is_numeric) {
+                return Err(Error::InvalidNumber(self.get_content(0)));
+            }
+
+            self.advance_numeric(); // advance exponent
+        }
+
         let content = self.get_content(0);
-        let number = Scanner::extract_number(content.as_str())?;
+        let digits = content.replace('_', "");
+        let number = Scanner::extract_number(&digits, &content)?;
+
+        if self.strict && Scanner::is_imprecise(&digits, number) {
+            return Err(Error::ImpreciseNumberLiteral(content));
+        }
 
         Ok(Token::Literal(Value::Number(number)))
     }
@@ -220,7 +491,9 @@ impl<'a> Scanner<'a> {
             }
 
             if self.is_at_end() {
-                return Err(Error::UnterminatedStringLiteral);
+                return Err(Error::UnterminatedStringLiteral {
+                    span: self.span(self.start, self.current),
+                });
             };
 
             self.advance(); // consume closing single quote
@@ -242,6 +515,16 @@ impl<'a> Scanner<'a> {
         Ok(Token::Literal(Value::String(content)))
     }
 
+    /// Scans a raw string literal like `r'\d+'`. Everything except the closing
+    /// single quote is kept verbatim, a doubled single quote is still an escaped
+    /// single quote.
+    fn raw_string(&mut self) -> Result<Token> {
+        self.advance(); // consume the opening single quote
+        self.start += 1; // skip the prefix
+
+        self.string()
+    }
+
     fn encounter_double(&mut self, token: Token) -> Token {
         self.advance();
         token
@@ -267,7 +550,7 @@ impl<'a> Scanner<'a> {
 mod tests {
     use std::f64::consts::PI;
 
-    use super::{Scanner, Token};
+    use super::{Scanner, Span, Token};
     use crate::{
         error::{Error, Result},
         value::Value,
@@ -340,6 +623,20 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn approx_equal() -> Result<()> {
+        let tokens = Scanner::tokenize("a ~= 1")?;
+        let expected = vec![
+            Token::Identifier(String::from("a")),
+            Token::ApproxEqual,
+            Token::Literal(Value::Number(1.0)),
+        ];
+
+        assert_eq!(expected, tokens);
+        assert!(Scanner::tokenize("a ~ 1").is_err());
+        Ok(())
+    }
+
     #[test]
     fn unterminated_less() -> Result<()> {
         let tokens = Scanner::tokenize("<")?;
@@ -368,6 +665,121 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn number_exponent() -> Result<()> {
+        test_number("1e3", 1000.0)?;
+        test_number("1E3", 1000.0)?;
+        test_number("2.5e+2", 250.0)?;
+        test_number("25e-1", 2.5)?;
+        test_number(".5e1", 5.0)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn number_underscores() -> Result<()> {
+        test_number("1_000", 1000.0)?;
+        test_number("1_000.000_5", 1000.0005)?;
+        test_number("1_000.5e2", 100_050.0)?;
+        test_number("1e1_0", 1e10)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn number_radix() -> Result<()> {
+        test_number("$FF", 255.0)?;
+        test_number("$ff", 255.0)?;
+        test_number("0xFF", 255.0)?;
+        test_number("0X1_0000", 65536.0)?;
+        test_number("0b1010", 10.0)?;
+        test_number("0B1111_0000", 240.0)?;
+
+        assert_eq!(
+            vec![
+                Token::Literal(Value::Number(3.0)),
+                Token::Plus,
+                Token::Literal(Value::Number(255.0)),
+            ],
+            Scanner::tokenize("0b11+$FF")?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn err_number_radix() {
+        for input in [
+            "0x",
+            "0b",
+            "0b102",
+            "0xFG",
+            "$FFG",
+            "0x_",
+            "$1_0000_0000_0000_0000",
+        ] {
+            assert_eq!(
+                Err(Error::InvalidNumber(String::from(input))),
+                Scanner::tokenize(input)
+            );
+        }
+
+        assert!(Scanner::tokenize_strict("$20_0000_0000_0000").is_ok());
+        assert_eq!(
+            Err(Error::ImpreciseNumberLiteral(String::from(
+                "$20_0000_0000_0001"
+            ))),
+            Scanner::tokenize_strict("$20_0000_0000_0001")
+        );
+    }
+
+    #[test]
+    fn err_number_exponent() {
+        for input in ["1e", "1e+", "1.5E-"] {
+            assert_eq!(
+                Err(Error::InvalidNumber(String::from(input))),
+                Scanner::tokenize(input)
+            );
+        }
+    }
+
+    #[test]
+    fn imprecise_number() -> Result<()> {
+        for input in [
+            "9007199254740991",
+            "9007199254740992",
+            "009007199254740992.00",
+            "0",
+            ".5",
+            "9_007_199_254_740_992",
+            "9007199254740992e0",
+            "90071992547409.92e2",
+            "1.5e1",
+            "0e-5",
+            "1e-3",
+        ] {
+            assert_eq!(Scanner::tokenize(input)?, Scanner::tokenize_strict(input)?);
+        }
+
+        for input in [
+            "9007199254740993",
+            "9007199254740993.0",
+            "18446744073709551617",
+            "9_007_199_254_740_993",
+            "9007199254740993e0",
+            "900719925474099.3e1",
+            "1e400",
+        ] {
+            assert!(Scanner::tokenize(input).is_ok());
+            assert_eq!(
+                Err(Error::ImpreciseNumberLiteral(String::from(input))),
+                Scanner::tokenize_strict(input)
+            );
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn err_empty_input() {
         let tokens = Scanner::tokenize("");
@@ -379,15 +791,31 @@ mod tests {
     #[test]
     fn err_unknown_token_1() {
         let tokens = Scanner::tokenize("$");
-        let expected = Err(Error::InvalidCharacter('$'));
+        let expected = Err(Error::InvalidCharacter {
+            character: '$',
+            span: Span {
+                start: 0,
+                end: 1,
+                line: 1,
+                column: 1,
+            },
+        });
 
         assert_eq!(expected, tokens);
     }
 
     #[test]
     fn err_unknown_token_2() {
-        let tokens = Scanner::tokenize("$hello");
-        let expected = Err(Error::InvalidCharacter('$'));
+        let tokens = Scanner::tokenize("1 +\n\t$hello");
+        let expected = Err(Error::InvalidCharacter {
+            character: '$',
+            span: Span {
+                start: 5,
+                end: 6,
+                line: 2,
+                column: 2,
+            },
+        });
 
         assert_eq!(expected, tokens);
     }
@@ -395,7 +823,14 @@ mod tests {
     #[test]
     fn err_unterminated_string() {
         let tokens = Scanner::tokenize("'hello' + 'world");
-        let expected = Err(Error::UnterminatedStringLiteral);
+        let expected = Err(Error::UnterminatedStringLiteral {
+            span: Span {
+                start: 10,
+                end: 16,
+                line: 1,
+                column: 11,
+            },
+        });
 
         assert_eq!(expected, tokens);
     }
@@ -449,6 +884,81 @@ mod tests {
         assert_eq!(expected, Scanner::tokenize("{Test}1+3"));
     }
 
+    #[test]
+    fn interleaved_comments() {
+        let expected = Ok(vec![
+            Token::Identifier(String::from("f")),
+            Token::LeftParen,
+            Token::Minus,
+            Token::Literal(Value::Number(1.0)),
+            Token::Comma,
+            Token::LeftBracket,
+            Token::RightBracket,
+            Token::RightParen,
+        ]);
+
+        assert_eq!(
+            expected,
+            Scanner::tokenize("f{a}(-//b\n{c} {d}1,//e\n//f\n\t[{g}]{h}{i}) {j}//k")
+        );
+
+        let one = Ok(vec![Token::Literal(Value::Number(1.0))]);
+        assert_eq!(one, Scanner::tokenize("{ // } 1"));
+        assert_eq!(one, Scanner::tokenize("{ // { } } 1"));
+        assert_eq!(
+            Err(Error::InvalidCharacter {
+                character: '}',
+                span: Span {
+                    start: 8,
+                    end: 9,
+                    line: 2,
+                    column: 2,
+                },
+            }),
+            Scanner::tokenize("{ // }\n } 1")
+        );
+    }
+
+    #[test]
+    fn raw_string() {
+        let string = |value: &str| Token::Literal(Value::String(String::from(value)));
+
+        assert_eq!(Ok(vec![string("")]), Scanner::tokenize("r''"));
+        assert_eq!(Ok(vec![string("it's")]), Scanner::tokenize("r'it''s'"));
+        assert_eq!(Ok(vec![string(r"\d+\.")]), Scanner::tokenize(r"r'\d+\.'"));
+        assert_eq!(
+            Ok(vec![string("a"), Token::Plus, string("b")]),
+            Scanner::tokenize("r'a'+r'b'")
+        );
+        assert_eq!(
+            Err(Error::UnterminatedStringLiteral {
+                span: Span {
+                    start: 1,
+                    end: 5,
+                    line: 1,
+                    column: 2,
+                },
+            }),
+            Scanner::tokenize(r"r'\d+")
+        );
+        assert_eq!(
+            Ok(vec![Token::Identifier(String::from("r")), string("a")]),
+            Scanner::tokenize("r 'a'")
+        );
+        assert_eq!(
+            Ok(vec![Token::Identifier(String::from("R")), string("a")]),
+            Scanner::tokenize("R'a'")
+        );
+        assert_eq!(
+            Ok(vec![Token::Identifier(String::from("rr")), string("a")]),
+            Scanner::tokenize("rr'a'")
+        );
+        assert_eq!(
+            Ok(vec![Token::Identifier(String::from("r"))]),
+            Scanner::tokenize("r")
+        );
+    }
+
     #[test]
     fn quote_char_in_string() {
         let expected = Ok(vec![Token::Literal(Value::String(String::from(
@@ -459,7 +969,14 @@ mod tests {
         let expected = Ok(vec![Token::Literal(Value::String(String::from("'")))]);
         assert_eq!(expected, Scanner::tokenize("''''"));
 
-        let expected = Err(Error::UnterminatedStringLiteral);
+        let expected = Err(Error::UnterminatedStringLiteral {
+            span: Span {
+                start: 0,
+                end: 3,
+                line: 1,
+                column: 1,
+            },
+        });
         assert_eq!(expected, Scanner::tokenize("'''"));
 
         let expected = Ok(vec![
@@ -0,0 +1,146 @@
+//! A versioned JSON envelope for a serialized [`Expression`].
+//!
+//! The plain serde representation of an [`Expression`] contains no version, a
+//! cached AST silently breaks once a variant changes. [`serialize_expression`]
+//! wraps the AST together with the [`SCHEMA_VERSION`]:
+//!
+//! ```json
+//! { "version": 1, "ast": { "type": "variable", "name": "a" } }
+//! ```
+//!
+//! # Versions
+//!
+//! * `0`: the unversioned JSON of the [`Expression`] itself, as written by
+//!   `serde_json::to_string(&ast)`. It is upgraded while reading.
+//! * `1`: the envelope with the `version` and the `ast` in the format of `0`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::{Expression, SCHEMA_VERSION},
+    error::{Error, Result},
+};
+
+/// The keys of an [`Expression`] object which contain a single operand.
+const OPERAND_KEYS: [&str; 5] = ["left", "middle", "right", "target", "index"];
+/// The keys of an [`Expression`] object which contain a list of operands.
+const OPERAND_LIST_KEYS: [&str; 2] = ["expressions", "params"];
+
+/// The envelope of a serialized [`Expression`] with its schema version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializedExpression {
+    pub version: u32,
+    pub ast: Expression,
+}
+
+impl From<Expression> for SerializedExpression {
+    fn from(ast: Expression) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            ast,
+        }
+    }
+}
+
+/// Serializes an [`Expression`] into a JSON [`SerializedExpression`] of the
+/// current [`SCHEMA_VERSION`].
+///
+/// # Example
+/// ```
+/// use slac::{compile, deserialize_expression, serialize_expression};
+///
+/// let ast = compile("a").unwrap();
+/// let json = serialize_expression(&ast).unwrap();
+///
+/// assert_eq!(r#"{"version":1,"ast":{"type":"variable","name":"a"}}"#, json);
+/// assert_eq!(Ok(ast), deserialize_expression(&json));
+/// ```
+///
+/// # Errors
+///
+/// Returns an [`Error::InvalidSerializedExpression`] if the [`Expression`] can
+/// not be serialized.
+pub fn serialize_expression(expression: &Expression) -> Result<String> {
+    let envelope = SerializedExpression::from(expression.clone());
+
+    serde_json::to_string(&envelope).map_err(|e| Error::InvalidSerializedExpression(e.to_string()))
+}
+
+/// Deserializes an [`Expression`] from a JSON [`SerializedExpression`].
+/// The unversioned JSON of an [`Expression`] is read as version `0` and upgraded.
+///
+/// Only the types of the nodes are checked, run [`check_ast_integrity`](crate::check_ast_integrity)
+/// on JSON from an untrusted source.
+///
+/// # Errors
+///
+/// Returns an [`Error::UnsupportedSchemaVersion`] if the version is unknown.
+/// Returns an [`Error::InvalidSerializedExpression`] if the JSON is not a valid
+/// [`Expression`], the message names the path of the invalid node, e.g.
+/// ``unknown variant `plus` ... at `ast.left` ``.
+pub fn deserialize_expression(json: &str) -> Result<Expression> {
+    // the fast path for the current version
+    if let Ok(envelope) = serde_json::from_str::<SerializedExpression>(json) {
+        if envelope.version == SCHEMA_VERSION {
+            return Ok(envelope.ast);
+        }
+    }
+
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| Error::InvalidSerializedExpression(e.to_string()))?;
+
+    match value.get("version") {
+        None => read_ast(&value, "ast"),
+        Some(version) => {
+            let version = version
+                .as_u64()
+                .ok_or_else(|| invalid("version", "expected an unsigned integer"))?;
+
+            match version {
+                1 => value
+                    .get("ast")
+                    .ok_or_else(|| invalid("ast", "missing field `ast`"))
+                    .and_then(|ast| read_ast(ast, "ast")),
+                _ => Err(Error::UnsupportedSchemaVersion(
+                    u32::try_from(version).unwrap_or(u32::MAX),
+                )),
+            }
+        }
+    }
+}
+
+fn invalid(path: &str, message: &str) -> Error {
+    Error::InvalidSerializedExpression(format!("{message} at `{path}`"))
+}
+
+fn read_ast(value: &serde_json::Value, path: &str) -> Result<Expression> {
+    Expression::deserialize(value).map_err(|_| locate(value, path))
+}
+
+/// Finds the innermost invalid node below an invalid [`Expression`], serde
+/// only reports the error without its position in the tree.
+fn locate(value: &serde_json::Value, path: &str) -> Error {
+    let operands = OPERAND_KEYS
+        .iter()
+        .filter_map(|key| Some((format!("{path}.{key}"), value.get(key)?)));
+    let operand_lists = OPERAND_LIST_KEYS.iter().flat_map(|key| {
+        value
+            .get(key)
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(move |(index, operand)| (format!("{path}.{key}[{index}]"), operand))
+    });
+
+    for (path, operand) in operands.chain(operand_lists) {
+        if Expression::deserialize(operand).is_err() {
+            return locate(operand, &path);
+        }
+    }
+
+    match Expression::deserialize(value) {
+        Err(e) => invalid(path, &e.to_string()),
+        Ok(_) => invalid(path, "invalid expression"),
+    }
+}
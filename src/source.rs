@@ -0,0 +1,165 @@
+//! Renders an [`Expression`] back into SLAC source text.
+
+use crate::{ast::Expression, operator::Operator, token::Precedence, value::Value};
+
+impl Expression {
+    /// Renders the expression as SLAC source text, e.g. to log a deserialized
+    /// AST in a readable form. Compiling the text results in the same
+    /// [`Expression`], parentheses are only added where the precedence of the
+    /// operators requires them.
+    ///
+    /// # Remarks
+    ///
+    /// Some expressions are not created by the compiler and only read back as
+    /// an equivalent expression: negative numbers are compiled as a negation
+    /// and array literals as an [`Expression::Array`]. Names are rendered as
+    /// they are, a variable named like a keyword can not be compiled again.
+    ///
+    /// # Example
+    /// ```
+    /// use slac::compile;
+    ///
+    /// let ast = compile("(1+2)*3 > max( a ,b) AND NOT (c IS EMPTY)").unwrap();
+    ///
+    /// assert_eq!("(1 + 2) * 3 > max(a, b) and not (c is empty)", ast.to_source());
+    /// assert_eq!(Ok(ast.clone()), compile(&ast.to_source()));
+    /// ```
+    #[must_use]
+    pub fn to_source(&self) -> String {
+        operand(self, Precedence::None)
+    }
+}
+
+/// The precedence of a binary [`Operator`] in the compiler.
+fn binary_precedence(operator: Operator) -> Precedence {
+    match operator {
+        Operator::Plus | Operator::Minus => Precedence::Term,
+        Operator::Multiply | Operator::Divide | Operator::Div | Operator::Mod => Precedence::Factor,
+        Operator::Equal | Operator::NotEqual | Operator::ApproxEqual => Precedence::Equality,
+        Operator::Greater
+        | Operator::GreaterEqual
+        | Operator::Less
+        | Operator::LessEqual
+        | Operator::In => Precedence::Comparison,
+        Operator::And => Precedence::And,
+        Operator::Or => Precedence::Or,
+        Operator::Xor => Precedence::Xor,
+        Operator::Implies => Precedence::Implies,
+        Operator::Iff => Precedence::Iff,
+        Operator::Not | Operator::IsEmpty | Operator::IsNotEmpty | Operator::TernaryCondition => {
+            Precedence::None
+        }
+    }
+}
+
+/// The precedence of the operator binding the expression together, the
+/// `else` branch of an `if` extends as far as possible and binds the least.
+fn precedence(expression: &Expression) -> Precedence {
+    match expression {
+        Expression::Unary {
+            operator: Operator::IsEmpty | Operator::IsNotEmpty,
+            ..
+        } => Precedence::Equality,
+        Expression::Unary { .. } => Precedence::Unary,
+        Expression::Binary { operator, .. } => binary_precedence(*operator),
+        Expression::Chain { .. } => Precedence::Comparison,
+        Expression::Ternary { .. } => Precedence::None,
+        Expression::Literal {
+            value: Value::Number(number),
+        } if number.is_sign_negative() => Precedence::Unary,
+        Expression::Array { .. }
+        | Expression::Literal { .. }
+        | Expression::Variable { .. }
+        | Expression::Call { .. }
+        | Expression::Index { .. } => Precedence::Primary,
+    }
+}
+
+/// Renders an operand, wrapped in parentheses if it binds less than `minimum`.
+fn operand(expression: &Expression, minimum: Precedence) -> String {
+    let source = render(expression);
+
+    if precedence(expression) < minimum {
+        format!("({source})")
+    } else {
+        source
+    }
+}
+
+fn list(expressions: &[Expression]) -> String {
+    expressions
+        .iter()
+        .map(|expression| operand(expression, Precedence::None))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn render(expression: &Expression) -> String {
+    match expression {
+        Expression::Unary { right, operator } => match operator {
+            Operator::Minus => format!("-{}", operand(right, Precedence::Unary)),
+            Operator::IsEmpty | Operator::IsNotEmpty => {
+                format!("{} {operator}", operand(right, Precedence::Equality))
+            }
+            _ => format!("{operator} {}", operand(right, Precedence::Unary)),
+        },
+        Expression::Binary {
+            left,
+            right,
+            operator,
+        } => {
+            let precedence = binary_precedence(*operator);
+            let (left_minimum, right_minimum) = match operator {
+                // `a implies b implies c` is read as `a implies (b implies c)`
+                Operator::Implies => (precedence.next(), precedence),
+                // `(a < b) < c` would be read as the chain `a < b < c`
+                Operator::Greater
+                | Operator::GreaterEqual
+                | Operator::Less
+                | Operator::LessEqual => (Precedence::Term, Precedence::Term),
+                _ => (precedence, precedence.next()),
+            };
+
+            format!(
+                "{} {operator} {}",
+                operand(left, left_minimum),
+                operand(right, right_minimum)
+            )
+        }
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            ..
+        } => format!(
+            "if {} then {} else {}",
+            left.to_source(),
+            middle.to_source(),
+            right.to_source()
+        ),
+        Expression::Chain {
+            expressions,
+            operators,
+        } => {
+            let mut operands = expressions
+                .iter()
+                .map(|expression| operand(expression, Precedence::Term));
+            let mut source = operands.next().unwrap_or_default();
+
+            for (operator, operand) in operators.iter().zip(operands) {
+                source += &format!(" {operator} {operand}");
+            }
+
+            source
+        }
+        Expression::Array { expressions } => format!("[{}]", list(expressions)),
+        Expression::Literal { value } => value.to_literal(),
+        Expression::Variable { name } => name.clone(),
+        Expression::Call { name, params } => format!("{name}({})", list(params)),
+        Expression::Index { target, index } => format!(
+            "{}[{}]",
+            operand(target, Precedence::Call),
+            index.to_source()
+        ),
+    }
+}
@@ -4,9 +4,10 @@
 use std::collections::HashSet;
 
 use super::{
-    default_string,
+    check_output_len, default_string,
     error::{NativeError, NativeResult},
-    f64_from_usize, get_index, get_string_index, smart_vec, usize_from_f64, STRING_OFFSET,
+    f64_from_usize, get_index, get_string_index, is_safe_integer_f64, smart_vec,
+    string_index_out_of_bounds, usize_from_f64, STRING_OFFSET,
 };
 
 use crate::{
@@ -15,6 +16,9 @@ use crate::{
 };
 
 pub(crate) const TERNARY_IF_THEN: &str = "if_then";
+pub(crate) const CONTAINS: &str = "contains";
+pub(crate) const TRY: &str = "try";
+pub(crate) const AT: &str = "at";
 
 /// Returns all common Functions.
 #[rustfmt::skip]
@@ -23,26 +27,36 @@ pub fn functions() -> Vec<Function> {
         Function::new(all, Arity::Variadic, "all(...): Boolean"),
         Function::new(any, Arity::Variadic, "any(...): Boolean"),
         Function::new(at, Arity::required(2), "at(values: [String|Array], index: Number): Any"),
-        Function::new(between, Arity::required(3), "between(value: Any, lower: Any, upper: Any): Boolean"),
+        Function::new(between, Arity::required(3), "between(value: Any, lower: Any, upper: Any): Boolean").checks_range(),
         Function::new(bool, Arity::required(1), "bool(value: Any): Boolean"),
+        Function::new(bool_to_string, Arity::optional(1, 2), "bool_to_string(value: Boolean, true_text: String = 'true', false_text: String = 'false'): String"),
+        Function::new(coalesce, Arity::Variadic, "coalesce(...): Any").accepts_undefined(),
         Function::new(contains, Arity::required(2), "contains(haystack: [String|Array], needle: [String|Any]): Boolean"),
         Function::new(compare, Arity::required(2), "compare(left: Any, right: Any): Number"),
         Function::new(copy, Arity::required(3), "copy(source: [String|Array], start: Number, count: Number): [String|Array]"),
         Function::new(count, Arity::required(2), "count(haystack: [String|Array], needle: Any"),
+        Function::new(count_true, Arity::Variadic, "count_true(...): Number"),
         Function::new(empty, Arity::required(1), "empty(value: Any): Boolean"),
+        Function::new(failed_indices, Arity::Variadic, "failed_indices(...): Array"),
         Function::new(find, Arity::required(2), "find(haystack: [String|Array], needle: [String|Any]): Number"),
+        Function::new(first, Arity::required(1), "first(values: [String|Array]): Any"),
         Function::new(float, Arity::required(1), "float(value: Any): Number"),
         Function::new(if_then, Arity::optional(2, 1), &format!("{TERNARY_IF_THEN}(condition: Boolean, first: Any, second: Any): Any")),
         Function::new(insert, Arity::required(3), "insert(target: [String|Array], source: [String|Any], index: Number): Any"),
         Function::new(int, Arity::required(1), "int(value: Any): Number"),
+        Function::new(last, Arity::required(1), "last(values: [String|Array]): Any"),
         Function::new(length, Arity::required(1), "length(value: [String|Array]): Number"),
         Function::new(max, Arity::Variadic, "max(...): Any"),
         Function::new(min, Arity::Variadic, "min(...): Any"),
         Function::new(replace, Arity::optional(2, 1), "replace(value: [String|Array], from: [String|Any], to: [String|Any]): [String|Array]"),
         Function::new(replace, Arity::required(2), "remove(value: [String|Array], from: [String|Any]): [String|Array]"), // replace with only 2 parameters acts as remove
         Function::new(reverse, Arity::required(1), "reverse(value: [Array|String]): [Array|String]"),
+        Function::new(skip, Arity::required(2), "skip(values: [String|Array], count: Number): [String|Array]"),
         Function::new(sort, Arity::required(1), "sort(values: Array): Array"),
-        Function::new(str, Arity::required(1), "str(value: Any): String"),
+        Function::new(str, Arity::required(1), "str(value: Any): String").formats_booleans(),
+        Function::new(take, Arity::required(2), "take(values: [String|Array], count: Number): [String|Array]"),
+        Function::new(truthy, Arity::required(1), "truthy(value: Any): Boolean"),
+        Function::new(try_, Arity::required(2), &format!("{TRY}(value: Any, fallback: Any): Any")),
         Function::new(unique, Arity::required(1), "unique(values: Array): Array"),
     ]
 }
@@ -79,6 +93,7 @@ pub fn any(params: &[Value]) -> NativeResult {
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::IndexNegative`] or [`NativeError::IndexOutOfBounds`] if the index is outside of the supplied value.
 pub fn at(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(values), Value::Number(index)] => {
@@ -86,7 +101,7 @@ pub fn at(params: &[Value]) -> NativeResult {
 
             match values.chars().nth(index) {
                 Some(char) => Ok(Value::String(char.to_string())),
-                None => Err(NativeError::IndexOutOfBounds(index)),
+                None => Err(string_index_out_of_bounds(index)),
             }
         }
         [Value::Array(values), Value::Number(index)] => {
@@ -109,7 +124,9 @@ pub fn at(params: &[Value]) -> NativeResult {
 ///
 /// # Remarks
 ///
-/// The range includes the lower and upper bounds.
+/// The range includes the lower and upper bounds. Values of different kinds
+/// are ordered by the total order of [`Value`], unless the interpreter runs with
+/// [`ExecutionOptions::strict_type_comparisons`](crate::ExecutionOptions::strict_type_comparisons).
 ///
 /// # Errors
 ///
@@ -121,28 +138,101 @@ pub fn between(params: &[Value]) -> NativeResult {
     }
 }
 
-/// Converts any [`Value`] to a [`Value::Boolean`].
+/// Converts a [`Value::Boolean`], [`Value::Number`] or [`Value::String`] to a [`Value::Boolean`].
 ///
 /// * Declaration: `bool(value: Any): Boolean`
 ///
 /// # Remarks
 ///
-/// Conversion depends on the supplied [`Value`] parameter, see [`Value::as_bool`].
+/// Strings are parsed case-insensitive: `'true'` and `'1'` are converted to `true`,
+/// `'false'` and `'0'` are converted to `false`. Any other string is an error.
+/// Numbers are `true` if they are not zero.
+///
+/// This differs from [`truthy`], which is `true` for any non-empty string,
+/// including `'false'` and `'0'`.
 ///
 /// # Errors
 ///
+/// Will return [`NativeError::Keyed`] if the String can not be converted to a Boolean.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn bool(params: &[Value]) -> NativeResult {
     match params {
-        [value] => Ok(Value::Boolean(value.as_bool())),
+        [Value::Boolean(v)] => Ok(Value::Boolean(*v)),
+        [Value::Number(v)] => Ok(Value::Boolean(*v != 0.0)),
+        [Value::String(v)] => match v.to_lowercase().as_str() {
+            "true" | "1" => Ok(Value::Boolean(true)),
+            "false" | "0" => Ok(Value::Boolean(false)),
+            _ => Err(NativeError::keyed("err.bool.parse", [v])),
+        },
+        [_] => Err(NativeError::WrongParameterType),
         _ => Err(NativeError::WrongParameterCount(1)),
     }
 }
 
+/// Converts a [`Value::Boolean`] into one of two texts, e.g. `'Yes'` or `'No'`.
+///
+/// * Declaration: `bool_to_string(value: Boolean, true_text: String = 'true', false_text: String = 'false'): String`
+///
+/// # Remarks
+///
+/// The texts are always the supplied parameters or their defaults, unlike
+/// [`str`] they are not affected by the boolean strings of the environment
+/// (see [`StaticEnvironment::set_boolean_strings`](crate::StaticEnvironment::set_boolean_strings)).
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn bool_to_string(params: &[Value]) -> NativeResult {
+    let (value, true_text, false_text) = match params {
+        [Value::Boolean(value)] => (*value, "true", "false"),
+        [Value::Boolean(value), Value::String(true_text)] => (*value, true_text.as_str(), "false"),
+        [Value::Boolean(value), Value::String(true_text), Value::String(false_text)] => {
+            (*value, true_text.as_str(), false_text.as_str())
+        }
+        [_] | [_, _] | [_, _, _] => return Err(NativeError::WrongParameterType),
+        _ => return Err(NativeError::WrongParameterCount(1)),
+    };
+
+    let text = if value { true_text } else { false_text };
+
+    Ok(Value::String(text.to_string()))
+}
+
+/// Returns the first parameter which is not empty, or the last parameter if
+/// all of them are empty. Empty values are the same as for `is empty`, so a
+/// `0`, `''` or `false` parameter is skipped too.
+///
+/// * Declaration: `coalesce(...): Any`
+///
+/// # Remarks
+///
+/// The function accepts undefined variables, which are passed as
+/// [`Value::Null`]. `coalesce(missing_var, 0)` returns `0` instead of failing
+/// with an [`UndefinedVariable`](crate::Error::UndefinedVariable) error.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if no parameters are supplied.
+pub fn coalesce(params: &[Value]) -> NativeResult {
+    params
+        .iter()
+        .find(|value| !value.is_empty())
+        .or(params.last())
+        .cloned()
+        .ok_or(NativeError::WrongParameterCount(1))
+}
+
 /// Checks if needle is contained inside the first haystack.
 ///
 /// * Declaration: `contains(haystack: [String|Array], needle: [String|Any]): Boolean`
 ///
+/// # Remarks
+///
+/// Strings are compared by their exact codepoints without Unicode normalization,
+/// use the `normalize` function to normalize them beforehand.
+///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
@@ -150,7 +240,7 @@ pub fn bool(params: &[Value]) -> NativeResult {
 pub fn contains(params: &[Value]) -> NativeResult {
     let found = match params {
         [Value::String(haystack), Value::String(needle)] => haystack.contains(needle), // search in String
-        [Value::Array(haystack), needle] => haystack.iter().any(|v| v == needle), // search in Array
+        [Value::Array(haystack), needle] => haystack.iter().any(|v| v.equals(needle)), // search in Array
         [_, _] => return Err(NativeError::WrongParameterType),
         _ => return Err(NativeError::WrongParameterCount(2)),
     };
@@ -162,6 +252,11 @@ pub fn contains(params: &[Value]) -> NativeResult {
 ///
 /// * Declaration: `compare(left: Any, right: Any): Number`
 ///
+/// # Remarks
+///
+/// Always uses the total order of [`Value`], regardless of
+/// [`ExecutionOptions::strict_type_comparisons`](crate::ExecutionOptions::strict_type_comparisons).
+///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
@@ -180,23 +275,34 @@ pub fn compare(params: &[Value]) -> NativeResult {
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::IndexNegative`] or [`NativeError::IndexOutOfBounds`] if the start is before the first element.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
 pub fn copy(params: &[Value]) -> NativeResult {
     match params {
-        [Value::String(source), Value::Number(start), Value::Number(count)] => Ok(Value::String(
-            source
-                .chars()
-                .skip(get_string_index(*start)?)
-                .take(usize_from_f64(*count))
-                .collect(),
-        )),
-        [Value::Array(source), Value::Number(start), Value::Number(count)] => Ok(Value::Array(
-            source
-                .iter()
-                .skip(get_index(*start)?)
-                .take(usize_from_f64(*count))
-                .cloned()
-                .collect(),
-        )),
+        [Value::String(source), Value::Number(start), Value::Number(count)] => {
+            let start = get_string_index(*start)?;
+            let byte_index = |index: usize| {
+                source
+                    .char_indices()
+                    .nth(index)
+                    .map_or(source.len(), |(byte, _)| byte)
+            };
+
+            let first = byte_index(start);
+            let last = byte_index(start.saturating_add(usize_from_f64(*count)));
+            check_output_len(last - first)?;
+
+            Ok(Value::String(source[first..last].to_string()))
+        }
+        [Value::Array(source), Value::Number(start), Value::Number(count)] => {
+            let start = get_index(*start)?;
+            let count = usize_from_f64(*count);
+            check_output_len(source.len().saturating_sub(start).min(count))?;
+
+            Ok(Value::Array(
+                source.iter().skip(start).take(count).cloned().collect(),
+            ))
+        }
         [_, _, _] => Err(NativeError::WrongParameterType),
         _ => Err(NativeError::WrongParameterCount(3)),
     }
@@ -211,7 +317,7 @@ pub fn copy(params: &[Value]) -> NativeResult {
 fn count(params: &[Value]) -> NativeResult {
     match params {
         [Value::Array(haystack), needle] => {
-            let count = haystack.iter().filter(|v| *v == needle).count();
+            let count = haystack.iter().filter(|v| v.equals(needle)).count();
             Ok(Value::Number(f64_from_usize(count)))
         }
         [Value::String(haystack), Value::String(needle)] => {
@@ -223,6 +329,34 @@ fn count(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Counts the members of a [`Value::Array`] which are [`Value::Boolean(true)`].
+/// Can be called with a single [`Value::Array`] parameter or as varadic function.
+///
+/// * Declaration: `count_true(...): Number`
+///
+/// # Remarks
+///
+/// Like all functions, `count_true` evaluates every member before it is called,
+/// there is no short circuit evaluation. A member failing with an error fails
+/// the whole call.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterType`] if a member is not a [`Value::Boolean`].
+pub fn count_true(params: &[Value]) -> NativeResult {
+    let mut count = 0;
+
+    for value in smart_vec(params) {
+        match value {
+            Value::Boolean(true) => count += 1,
+            Value::Boolean(false) => (),
+            _ => return Err(NativeError::WrongParameterType),
+        }
+    }
+
+    Ok(Value::Number(f64_from_usize(count)))
+}
+
 /// Checks if the supplied [`Value`] is empty.
 ///
 /// * Declaration: `empty(value: Any): Boolean`
@@ -245,11 +379,37 @@ pub fn empty(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Returns the zero-based indices of the members of a [`Value::Array`] which
+/// are not [`Value::Boolean(true)`], e.g. the failed checks of a rule.
+/// Can be called with a single [`Value::Array`] parameter or as varadic function.
+///
+/// * Declaration: `failed_indices(...): Array`
+///
+/// # Remarks
+///
+/// Like [`count_true`], every member is evaluated before the call. Members
+/// which are not a [`Value::Boolean`] count as failed.
+#[allow(clippy::missing_errors_doc)]
+pub fn failed_indices(params: &[Value]) -> NativeResult {
+    let indices = smart_vec(params)
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| !matches!(value, Value::Boolean(true)))
+        .map(|(index, _)| Value::Number(f64_from_usize(index)))
+        .collect();
+
+    Ok(Value::Array(indices))
+}
+
 /// Finds the index of a [`Value`] inside an [`Value::Array`] or the position of a substring inside
 /// a [`Value::String`].
 ///
 /// * Declaration: `find(haystack: [String|Array], needle: [String|Any]): Number`
 ///
+/// # Remarks
+///
+/// Like [`contains`], the search does not apply any Unicode normalization.
+///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
@@ -263,7 +423,7 @@ pub fn find(params: &[Value]) -> NativeResult {
             })),
         [Value::Array(haystack), needle] => Ok(haystack
             .iter()
-            .position(|v| v == needle)
+            .position(|v| v.equals(needle))
             .map_or(Value::Number(-1.0), |index| {
                 Value::Number(f64_from_usize(index))
             })),
@@ -272,20 +432,48 @@ pub fn find(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Returns the first element of a [`Value::Array`] or the first character of a
+/// [`Value::String`].
+///
+/// * Declaration: `first(values: [String|Array]): Any`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::IndexOutOfBounds`] with the index of the first element if the value is empty.
+pub fn first(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(values)] => values
+            .chars()
+            .next()
+            .map(|char| Value::String(char.to_string()))
+            .ok_or_else(|| string_index_out_of_bounds(0)),
+        [Value::Array(values)] => values
+            .first()
+            .cloned()
+            .ok_or(NativeError::IndexOutOfBounds(0)),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
 /// Converts a [`Value::Boolean`] or a [`Value::String`] to a [`Value::Number`].
 ///
 /// * Declaration: `float(value: Any): Number`
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the Value can not be converted to a Number.
+/// Will return [`NativeError::Keyed`] if the Value can not be converted to a Number.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn float(params: &[Value]) -> NativeResult {
     match params {
         [Value::Boolean(v)] => Ok(Value::Number(f64::from(i8::from(*v)))),
         [Value::String(v)] => {
-            let float = v.parse::<f64>().map_err(|e| e.to_string())?;
+            let float = v
+                .parse::<f64>()
+                .map_err(|e| NativeError::keyed("err.number.parse", [v, &e]))?;
             Ok(Value::Number(float))
         }
         [Value::Number(v)] => Ok(Value::Number(*v)),
@@ -302,6 +490,7 @@ pub fn float(params: &[Value]) -> NativeResult {
 /// # Remarks
 ///
 /// *All parameters are evaluated* prior the the functions execution. There is *no short circuit* evaluation.
+/// A `null` condition counts as `False`.
 ///
 /// # Errors
 ///
@@ -309,12 +498,9 @@ pub fn float(params: &[Value]) -> NativeResult {
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn if_then(params: &[Value]) -> NativeResult {
     match params {
-        [Value::Boolean(condition), first, ..] => {
-            if *condition {
-                Ok(first.clone())
-            } else {
-                Ok(params.get(2).cloned().unwrap_or_else(|| first.empty()))
-            }
+        [Value::Boolean(true), first, ..] => Ok(first.clone()),
+        [Value::Boolean(false) | Value::Null, first, ..] => {
+            Ok(params.get(2).cloned().unwrap_or_else(|| first.empty()))
         }
         [_, _] => Err(NativeError::WrongParameterType),
         _ => Err(NativeError::WrongParameterCount(2)),
@@ -330,15 +516,18 @@ pub fn if_then(params: &[Value]) -> NativeResult {
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 /// Will return [`NativeError::IndexOutOfBounds`] if the index parameter does not fit inside the supplied value length.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
 pub fn insert(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(target), Value::String(source), Value::Number(index)] => {
             let index = get_string_index(*index)?;
 
             if index > target.chars().count() {
-                return Err(NativeError::IndexOutOfBounds(index));
+                return Err(string_index_out_of_bounds(index));
             }
 
+            check_output_len(target.len() + source.len())?;
+
             let before: String = target.chars().take(index).collect();
             let after: String = target.chars().skip(index).collect();
 
@@ -350,6 +539,8 @@ pub fn insert(params: &[Value]) -> NativeResult {
                 return Err(NativeError::IndexOutOfBounds(index));
             }
 
+            check_output_len(values.len() + 1)?;
+
             let mut values = values.clone();
             values.insert(index, element.clone());
 
@@ -366,16 +557,46 @@ pub fn insert(params: &[Value]) -> NativeResult {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the Value can not be converted to a Number.
+/// Will return [`NativeError::Keyed`] if the Value can not be converted to a Number
+/// or if the result exceeds the range of [`super::MAX_SAFE_INTEGER`].
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn int(params: &[Value]) -> NativeResult {
     match float(params)? {
-        Value::Number(value) => Ok(Value::Number(value.trunc())),
+        Value::Number(value) if is_safe_integer_f64(value.trunc()) => {
+            Ok(Value::Number(value.trunc()))
+        }
+        Value::Number(value) => Err(NativeError::keyed("err.number.safe_integer", [&value])),
         _ => Err(NativeError::WrongParameterType),
     }
 }
 
+/// Returns the last element of a [`Value::Array`] or the last character of a
+/// [`Value::String`].
+///
+/// * Declaration: `last(values: [String|Array]): Any`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::IndexOutOfBounds`] with the index of the first element if the value is empty.
+pub fn last(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(values)] => values
+            .chars()
+            .next_back()
+            .map(|char| Value::String(char.to_string()))
+            .ok_or_else(|| string_index_out_of_bounds(0)),
+        [Value::Array(values)] => values
+            .last()
+            .cloned()
+            .ok_or(NativeError::IndexOutOfBounds(0)),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
 /// Returns the length of the supplied [`Value::String`] or [`Value::Array`].
 /// For other [`Value`] types return 0.
 ///
@@ -395,6 +616,10 @@ pub fn length(params: &[Value]) -> NativeResult {
 ///
 /// * Declaration: `max(...): Any`
 ///
+/// # Remarks
+///
+/// Parameters of different kinds are ordered like in [`compare`].
+///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
@@ -410,6 +635,10 @@ pub fn max(params: &[Value]) -> NativeResult {
 ///
 /// * Declaration: `min(...): Any`
 ///
+/// # Remarks
+///
+/// Parameters of different kinds are ordered like in [`compare`].
+///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
@@ -434,20 +663,28 @@ pub fn min(params: &[Value]) -> NativeResult {
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
 pub fn replace(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(value), Value::String(from), ..] => {
             let to = default_string(params, 2, "")?;
+            let matches = value.matches(from.as_str()).count();
+            check_output_len(
+                (value.len() - matches * from.len())
+                    .saturating_add(matches.saturating_mul(to.len())),
+            )?;
+
             Ok(Value::String(value.replace(from, to)))
         }
         [Value::Array(values), from, ..] => {
             let to = params.get(2).cloned();
+            check_output_len(values.len())?;
 
             Ok(Value::Array(
                 values
                     .iter()
                     .filter_map(|value| {
-                        if value == from {
+                        if value.equals(from) {
                             to.clone()
                         } else {
                             Some(value.clone())
@@ -478,8 +715,36 @@ pub fn reverse(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Skips the first `count` elements of a [`Value::Array`] or characters of a
+/// [`Value::String`] and returns the rest. A `count` larger than the length
+/// results in an empty value.
+///
+/// * Declaration: `skip(values: [String|Array], count: Number): [String|Array]`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::IndexNegative`] if the count is negative.
+pub fn skip(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(values), Value::Number(count)] => Ok(Value::String(
+            values.chars().skip(get_index(*count)?).collect(),
+        )),
+        [Value::Array(values), Value::Number(count)] => Ok(Value::Array(
+            values.iter().skip(get_index(*count)?).cloned().collect(),
+        )),
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
 /// Returns a sorted copy of the provided [`Value::Array`].
 ///
+/// # Remarks
+///
+/// Elements of different kinds are ordered like in [`compare`].
+///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
@@ -501,16 +766,88 @@ pub fn sort(params: &[Value]) -> NativeResult {
 ///
 /// * Declaration: `str(value: Any): String`
 ///
+/// # Remarks
+///
+/// Numbers are formatted like [`format_number_auto`](super::math::format_number_auto).
+/// Booleans are converted into `'true'` and `'false'`, unless the environment
+/// sets other texts with [`StaticEnvironment::set_boolean_strings`](crate::StaticEnvironment::set_boolean_strings).
+/// `null` is converted into an empty string.
+///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 pub fn str(params: &[Value]) -> NativeResult {
     match params {
+        [Value::Null] => Ok(Value::String(String::new())),
         [value] => Ok(Value::String(value.to_string())),
         _ => Err(NativeError::WrongParameterCount(1)),
     }
 }
 
+/// Returns the first `count` elements of a [`Value::Array`] or characters of a
+/// [`Value::String`]. A `count` larger than the length returns the whole value.
+///
+/// * Declaration: `take(values: [String|Array], count: Number): [String|Array]`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::IndexNegative`] if the count is negative.
+pub fn take(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(values), Value::Number(count)] => Ok(Value::String(
+            values.chars().take(get_index(*count)?).collect(),
+        )),
+        [Value::Array(values), Value::Number(count)] => Ok(Value::Array(
+            values.iter().take(get_index(*count)?).cloned().collect(),
+        )),
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Converts any [`Value`] to a [`Value::Boolean`] by checking if it is not empty.
+///
+/// * Declaration: `truthy(value: Any): Boolean`
+///
+/// # Remarks
+///
+/// Conversion depends on the supplied [`Value`] parameter, see [`Value::as_bool`].
+/// Unlike [`bool`], every non-empty string is `true`, including `'false'` and `'0'`.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+pub fn truthy(params: &[Value]) -> NativeResult {
+    match params {
+        [value] => Ok(Value::Boolean(value.as_bool())),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Returns the value, or the fallback if the evaluation of the value failed.
+///
+/// * Declaration: `try(value: Any, fallback: Any): Any`
+///
+/// # Remarks
+///
+/// The `TreeWalkingInterpreter` evaluates `try` lazily. Any [`Error`](crate::Error)
+/// of the first parameter is discarded and only then the fallback is evaluated.
+/// Errors inside the fallback are not caught, neither are cancellations,
+/// [`VariableAccessDenied`](crate::Error::VariableAccessDenied) and
+/// [`FunctionCallDenied`](crate::Error::FunctionCallDenied) errors.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+pub fn try_(params: &[Value]) -> NativeResult {
+    match params {
+        [value, _] => Ok(value.clone()),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
 /// Returns all unique members of a [`Value::Array`] in order.
 ///
 /// * Declaration: `unique(values: Array): Array`
@@ -563,6 +900,37 @@ mod test {
         assert_eq!(Value::Boolean(false), all(&values).unwrap());
     }
 
+    #[test]
+    fn std_count_true() {
+        let values = vec![
+            Value::Boolean(true),
+            Value::Boolean(false),
+            Value::Boolean(true),
+        ];
+        assert_eq!(Ok(Value::Number(2.0)), count_true(&values));
+        assert_eq!(Ok(Value::Number(2.0)), count_true(&[Value::Array(values)]));
+        assert_eq!(Ok(Value::Number(0.0)), count_true(&[]));
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            count_true(&[Value::Boolean(true), Value::Number(1.0)])
+        );
+    }
+
+    #[test]
+    fn std_failed_indices() {
+        let values = vec![
+            Value::Boolean(true),
+            Value::Boolean(false),
+            Value::Number(1.0),
+            Value::Boolean(true),
+        ];
+        let expected = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]);
+
+        assert_eq!(Ok(expected.clone()), failed_indices(&values));
+        assert_eq!(Ok(expected), failed_indices(&[Value::Array(values)]));
+        assert_eq!(Ok(Value::Array(vec![])), failed_indices(&[]));
+    }
+
     #[test]
     fn std_any() {
         let values = vec![Value::Boolean(true), Value::Boolean(true)];
@@ -632,6 +1000,36 @@ mod test {
         );
     }
 
+    #[test]
+    fn std_bool_to_string() {
+        let text = |text: &str| Ok(Value::String(text.to_string()));
+        let yes = Value::String(String::from("Yes"));
+        let no = Value::String(String::from("No"));
+
+        assert_eq!(text("true"), bool_to_string(&[Value::Boolean(true)]));
+        assert_eq!(text("false"), bool_to_string(&[Value::Boolean(false)]));
+        assert_eq!(
+            text("Yes"),
+            bool_to_string(&[Value::Boolean(true), yes.clone()])
+        );
+        assert_eq!(
+            text("false"),
+            bool_to_string(&[Value::Boolean(false), yes.clone()])
+        );
+        assert_eq!(
+            text("No"),
+            bool_to_string(&[Value::Boolean(false), yes.clone(), no.clone()])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            bool_to_string(&[Value::Number(1.0), yes, no])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(1)),
+            bool_to_string(&[])
+        );
+    }
+
     #[test]
     fn std_bool() {
         assert_eq!(
@@ -645,31 +1043,63 @@ mod test {
         );
 
         assert_eq!(
-            Value::Boolean(false),
-            bool(&vec![Value::String(String::from(""))]).unwrap()
+            Value::Boolean(true),
+            bool(&[Value::String(String::from("True"))]).unwrap()
         );
 
+        assert!(bool(&[Value::String(String::new())]).is_err());
+        assert!(bool(&[Value::String(String::from("other"))]).is_err());
+
         assert_eq!(
             Value::Boolean(true),
-            bool(&vec![Value::String(String::from("other"))]).unwrap()
+            bool(&vec![Value::Boolean(true)]).unwrap()
+        );
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            bool(&[Value::Array(vec![])])
+        );
+
+        assert!(bool(&vec![]).is_err());
+    }
+
+    #[test]
+    fn std_truthy() {
+        assert_eq!(
+            Value::Boolean(false),
+            truthy(&[Value::String(String::new())]).unwrap()
         );
 
         assert_eq!(
             Value::Boolean(true),
-            bool(&vec![Value::Boolean(true)]).unwrap()
+            truthy(&[Value::String(String::from("other"))]).unwrap()
         );
 
         assert_eq!(
             Value::Boolean(false),
-            bool(&vec![Value::Array(vec![])]).unwrap()
+            truthy(&[Value::Array(vec![])]).unwrap()
         );
 
         assert_eq!(
             Value::Boolean(true),
-            bool(&vec![Value::Array(vec![Value::Boolean(true)])]).unwrap()
+            truthy(&[Value::Array(vec![Value::Boolean(true)])]).unwrap()
         );
 
-        assert!(bool(&vec![]).is_err());
+        assert!(truthy(&[]).is_err());
+    }
+
+    #[test]
+    fn std_coalesce() {
+        let values = vec![Value::Null, Value::Number(0.0), Value::Number(2.0)];
+        assert_eq!(Value::Number(2.0), coalesce(&values).unwrap());
+
+        let values = vec![Value::Null, Value::String(String::new())];
+        assert_eq!(Value::String(String::new()), coalesce(&values).unwrap());
+
+        assert_eq!(
+            NativeError::WrongParameterCount(1),
+            coalesce(&[]).unwrap_err()
+        );
     }
 
     #[test]
@@ -891,6 +1321,15 @@ mod test {
         assert_eq!(Ok(Value::Number(0.0)), int(&vec![Value::Boolean(false)]));
 
         assert!(int(&vec![]).is_err());
+
+        assert_eq!(
+            Ok(Value::Number(9007199254740991.0)),
+            int(&[Value::String(String::from("9007199254740991"))])
+        );
+        assert!(int(&[Value::String(String::from("9007199254740992"))]).is_err());
+        assert!(int(&[Value::String(String::from("9007199254740993"))]).is_err());
+        assert!(int(&[Value::String(String::from("-9007199254740993"))]).is_err());
+        assert!(int(&[Value::Number(9007199254740992.0)]).is_err());
     }
 
     #[test]
@@ -1059,6 +1498,71 @@ mod test {
         );
     }
 
+    #[test]
+    fn std_first_last() {
+        let values = [Value::Array(vec![Value::Number(1.0), Value::Number(2.0)])];
+        assert_eq!(Ok(Value::Number(1.0)), first(&values));
+        assert_eq!(Ok(Value::Number(2.0)), last(&values));
+
+        let text = [Value::String(String::from("äbc"))];
+        assert_eq!(Ok(Value::String(String::from("ä"))), first(&text));
+        assert_eq!(Ok(Value::String(String::from("c"))), last(&text));
+
+        let empty = [Value::Array(vec![])];
+        assert_eq!(Err(NativeError::IndexOutOfBounds(0)), first(&empty));
+        assert_eq!(Err(NativeError::IndexOutOfBounds(0)), last(&empty));
+
+        let empty = [Value::String(String::new())];
+        assert_eq!(Err(string_index_out_of_bounds(0)), first(&empty));
+        assert_eq!(Err(string_index_out_of_bounds(0)), last(&empty));
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            first(&[Value::Number(1.0)])
+        );
+    }
+
+    #[test]
+    fn std_take_skip() {
+        let values = Value::Array(vec![
+            Value::Number(1.0),
+            Value::Number(2.0),
+            Value::Number(3.0),
+        ]);
+        let text = Value::String(String::from("äbc"));
+
+        let call = |function: fn(&[Value]) -> NativeResult, value: &Value, count: f64| {
+            function(&[value.clone(), Value::Number(count)])
+        };
+
+        assert_eq!(
+            Ok(Value::Array(vec![Value::Number(1.0), Value::Number(2.0)])),
+            call(take, &values, 2.0)
+        );
+        assert_eq!(
+            Ok(Value::Array(vec![Value::Number(3.0)])),
+            call(skip, &values, 2.0)
+        );
+        assert_eq!(Ok(values.clone()), call(take, &values, 10.0));
+        assert_eq!(Ok(Value::Array(vec![])), call(skip, &values, 10.0));
+        assert_eq!(Ok(values.clone()), call(skip, &values, 0.0));
+
+        assert_eq!(
+            Ok(Value::String(String::from("äb"))),
+            call(take, &text, 2.0)
+        );
+        assert_eq!(Ok(Value::String(String::from("c"))), call(skip, &text, 2.0));
+        assert_eq!(Ok(text.clone()), call(take, &text, 10.0));
+        assert_eq!(Ok(Value::String(String::new())), call(skip, &text, 10.0));
+
+        assert_eq!(Err(NativeError::IndexNegative), call(take, &values, -1.0));
+        assert_eq!(Err(NativeError::IndexNegative), call(skip, &text, -1.0));
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            call(take, &Value::Number(1.0), 1.0)
+        );
+    }
+
     #[test]
     fn std_at() {
         assert_eq!(
@@ -1183,3 +1687,122 @@ mod test {
         );
     }
 }
+
+#[cfg(test)]
+mod test_string_indices {
+    use super::{at, copy, find, insert};
+    use crate::{stdlib::NativeError, Value};
+
+    fn text(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+
+    fn index(value: f64) -> Value {
+        Value::Number(value)
+    }
+
+    #[test]
+    fn negative_index() {
+        assert_eq!(
+            Err(NativeError::IndexNegative),
+            at(&[text("abc"), index(-1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::IndexNegative),
+            copy(&[text("abc"), index(-1.0), index(1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::IndexNegative),
+            insert(&[text("abc"), text("x"), index(-1.0)])
+        );
+    }
+
+    #[cfg(not(feature = "zero_based_strings"))]
+    #[test]
+    fn one_based_boundaries() {
+        assert_eq!(
+            Err(NativeError::IndexOutOfBounds(0)),
+            at(&[text("abc"), index(0.0)])
+        );
+        assert_eq!(Ok(text("a")), at(&[text("abc"), index(1.0)]));
+        assert_eq!(Ok(text("c")), at(&[text("abc"), index(3.0)]));
+        assert_eq!(
+            Err(NativeError::IndexOutOfBounds(4)),
+            at(&[text("abc"), index(4.0)])
+        );
+
+        assert_eq!(
+            Err(NativeError::IndexOutOfBounds(0)),
+            copy(&[text("abc"), index(0.0), index(1.0)])
+        );
+        assert_eq!(Ok(text("a")), copy(&[text("abc"), index(1.0), index(1.0)]));
+        assert_eq!(Ok(text("c")), copy(&[text("abc"), index(3.0), index(1.0)]));
+        assert_eq!(Ok(text("")), copy(&[text("abc"), index(4.0), index(1.0)]));
+
+        assert_eq!(
+            Err(NativeError::IndexOutOfBounds(0)),
+            insert(&[text("abc"), text("x"), index(0.0)])
+        );
+        assert_eq!(
+            Ok(text("xabc")),
+            insert(&[text("abc"), text("x"), index(1.0)])
+        );
+        assert_eq!(
+            Ok(text("abxc")),
+            insert(&[text("abc"), text("x"), index(3.0)])
+        );
+        assert_eq!(
+            Ok(text("abcx")),
+            insert(&[text("abc"), text("x"), index(4.0)])
+        );
+        assert_eq!(
+            Err(NativeError::IndexOutOfBounds(5)),
+            insert(&[text("abc"), text("x"), index(5.0)])
+        );
+
+        assert_eq!(Ok(index(1.0)), find(&[text("abc"), text("a")]));
+        assert_eq!(Ok(index(3.0)), find(&[text("abc"), text("c")]));
+        assert_eq!(Ok(index(0.0)), find(&[text("abc"), text("d")]));
+    }
+
+    #[cfg(feature = "zero_based_strings")]
+    #[test]
+    fn zero_based_boundaries() {
+        assert_eq!(Ok(text("a")), at(&[text("abc"), index(0.0)]));
+        assert_eq!(Ok(text("b")), at(&[text("abc"), index(1.0)]));
+        assert_eq!(
+            Err(NativeError::IndexOutOfBounds(3)),
+            at(&[text("abc"), index(3.0)])
+        );
+        assert_eq!(
+            Err(NativeError::IndexOutOfBounds(4)),
+            at(&[text("abc"), index(4.0)])
+        );
+
+        assert_eq!(Ok(text("a")), copy(&[text("abc"), index(0.0), index(1.0)]));
+        assert_eq!(Ok(text("b")), copy(&[text("abc"), index(1.0), index(1.0)]));
+        assert_eq!(Ok(text("")), copy(&[text("abc"), index(3.0), index(1.0)]));
+        assert_eq!(Ok(text("")), copy(&[text("abc"), index(4.0), index(1.0)]));
+
+        assert_eq!(
+            Ok(text("xabc")),
+            insert(&[text("abc"), text("x"), index(0.0)])
+        );
+        assert_eq!(
+            Ok(text("axbc")),
+            insert(&[text("abc"), text("x"), index(1.0)])
+        );
+        assert_eq!(
+            Ok(text("abcx")),
+            insert(&[text("abc"), text("x"), index(3.0)])
+        );
+        assert_eq!(
+            Err(NativeError::IndexOutOfBounds(4)),
+            insert(&[text("abc"), text("x"), index(4.0)])
+        );
+
+        assert_eq!(Ok(index(0.0)), find(&[text("abc"), text("a")]));
+        assert_eq!(Ok(index(2.0)), find(&[text("abc"), text("c")]));
+        assert_eq!(Ok(index(-1.0)), find(&[text("abc"), text("d")]));
+    }
+}
@@ -1,26 +1,85 @@
+use std::fmt::Display;
+
 use thiserror::Error;
 
-use crate::Value;
+use crate::{
+    message::{render, DefaultCatalog, MessageCatalog},
+    Value,
+};
 
 /// Error types created by [`super::NativeFunction`] calls.
 /// `NativeError::CustomError` can be used for general purpose errors.
+///
+/// Every error is identified by a stable message key, which can be rendered
+/// in another language with [`NativeError::render_with`].
 #[allow(clippy::module_name_repetitions)]
 #[derive(Error, Debug, PartialEq)]
 pub enum NativeError {
-    #[error("function \"{0}\" not found")]
     FunctionNotFound(String),
-    #[error("not enough parameters: \"{0}\" expected")]
     WrongParameterCount(usize),
-    #[error("wrong parameter type")]
     WrongParameterType,
-    #[error("index \"{0}\" is out of bounds")]
     IndexOutOfBounds(usize),
-    #[error("index must not be negative")]
     IndexNegative,
-    #[error("{0}")]
+    /// The output of a function would exceed the [`max_output_len`](super::max_output_len).
+    OutputTooLarge {
+        limit: usize,
+        requested: usize,
+    },
+    /// An error with a message key of the [`DefaultCatalog`] and its arguments.
+    Keyed(&'static str, Vec<String>),
     CustomError(String),
 }
 
+impl NativeError {
+    /// Creates a [`NativeError::Keyed`] from a message key and its arguments.
+    pub(crate) fn keyed<const N: usize>(key: &'static str, args: [&dyn Display; N]) -> Self {
+        Self::Keyed(key, args.iter().map(ToString::to_string).collect())
+    }
+
+    /// Returns the stable message key of the error.
+    #[must_use]
+    pub fn key(&self) -> &'static str {
+        match self {
+            NativeError::FunctionNotFound(_) => "err.function.not_found",
+            NativeError::WrongParameterCount(_) => "err.param.count",
+            NativeError::WrongParameterType => "err.param.type",
+            NativeError::IndexOutOfBounds(_) => "err.index.bounds",
+            NativeError::IndexNegative => "err.index.negative",
+            NativeError::OutputTooLarge { .. } => "err.output.too_large",
+            NativeError::Keyed(key, _) => key,
+            NativeError::CustomError(_) => "err.custom",
+        }
+    }
+
+    /// Returns the arguments of the message.
+    #[must_use]
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            NativeError::FunctionNotFound(name) => vec![name.clone()],
+            NativeError::WrongParameterCount(count) => vec![count.to_string()],
+            NativeError::IndexOutOfBounds(index) => vec![index.to_string()],
+            NativeError::OutputTooLarge { limit, requested } => {
+                vec![limit.to_string(), requested.to_string()]
+            }
+            NativeError::WrongParameterType | NativeError::IndexNegative => vec![],
+            NativeError::Keyed(_, args) => args.clone(),
+            NativeError::CustomError(message) => vec![message.clone()],
+        }
+    }
+
+    /// Renders the message of the error with the templates of a [`MessageCatalog`].
+    #[must_use]
+    pub fn render_with(&self, catalog: &dyn MessageCatalog) -> String {
+        render(catalog, self.key(), &self.args())
+    }
+}
+
+impl std::fmt::Display for NativeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render_with(&DefaultCatalog))
+    }
+}
+
 impl From<&str> for NativeError {
     fn from(value: &str) -> Self {
         Self::CustomError(value.to_string())
@@ -0,0 +1,323 @@
+//! Optional module to calculate checksums and hashes of [`Value::String`].
+//!
+//! # Hash
+//!
+//! This module has no additional dependencies and can be included using the `hash` feature.
+//! All texts are hashed as their UTF-8 bytes.
+
+use std::fmt::Write;
+
+use crate::{
+    function::{Arity, Function},
+    Value,
+};
+
+use super::{NativeError, NativeResult};
+
+/// Returns all hash functions as a fixed size array.
+#[rustfmt::skip]
+pub fn functions() -> Vec<Function> {
+    vec![
+        Function::new(crc32, Arity::required(1), "crc32(text: String): Number"),
+        Function::new(sha256, Arity::required(1), "sha256(text: String): String"),
+    ]
+}
+
+/// The lookup table of the reflected CRC-32 polynomial `0xEDB88320`.
+#[allow(clippy::cast_possible_truncation)]
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[index] = crc;
+        index += 1;
+    }
+
+    table
+};
+
+#[allow(clippy::cast_possible_truncation)]
+fn crc32_checksum(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(u32::MAX, |crc, byte| {
+        CRC32_TABLE[usize::from((crc as u8) ^ byte)] ^ (crc >> 8)
+    })
+}
+
+/// The first 32 bits of the fractional parts of the cube roots of the first 64 primes.
+const SHA256_K: [u32; 64] = [
+    0x428a_2f98,
+    0x7137_4491,
+    0xb5c0_fbcf,
+    0xe9b5_dba5,
+    0x3956_c25b,
+    0x59f1_11f1,
+    0x923f_82a4,
+    0xab1c_5ed5,
+    0xd807_aa98,
+    0x1283_5b01,
+    0x2431_85be,
+    0x550c_7dc3,
+    0x72be_5d74,
+    0x80de_b1fe,
+    0x9bdc_06a7,
+    0xc19b_f174,
+    0xe49b_69c1,
+    0xefbe_4786,
+    0x0fc1_9dc6,
+    0x240c_a1cc,
+    0x2de9_2c6f,
+    0x4a74_84aa,
+    0x5cb0_a9dc,
+    0x76f9_88da,
+    0x983e_5152,
+    0xa831_c66d,
+    0xb003_27c8,
+    0xbf59_7fc7,
+    0xc6e0_0bf3,
+    0xd5a7_9147,
+    0x06ca_6351,
+    0x1429_2967,
+    0x27b7_0a85,
+    0x2e1b_2138,
+    0x4d2c_6dfc,
+    0x5338_0d13,
+    0x650a_7354,
+    0x766a_0abb,
+    0x81c2_c92e,
+    0x9272_2c85,
+    0xa2bf_e8a1,
+    0xa81a_664b,
+    0xc24b_8b70,
+    0xc76c_51a3,
+    0xd192_e819,
+    0xd699_0624,
+    0xf40e_3585,
+    0x106a_a070,
+    0x19a4_c116,
+    0x1e37_6c08,
+    0x2748_774c,
+    0x34b0_bcb5,
+    0x391c_0cb3,
+    0x4ed8_aa4a,
+    0x5b9c_ca4f,
+    0x682e_6ff3,
+    0x748f_82ee,
+    0x78a5_636f,
+    0x84c8_7814,
+    0x8cc7_0208,
+    0x90be_fffa,
+    0xa450_6ceb,
+    0xbef9_a3f7,
+    0xc671_78f2,
+];
+
+/// The first 32 bits of the fractional parts of the square roots of the first 8 primes.
+const SHA256_H: [u32; 8] = [
+    0x6a09_e667,
+    0xbb67_ae85,
+    0x3c6e_f372,
+    0xa54f_f53a,
+    0x510e_527f,
+    0x9b05_688c,
+    0x1f83_d9ab,
+    0x5be0_cd19,
+];
+
+/// The variable names follow the notation of FIPS 180-4.
+#[allow(clippy::many_single_char_names)]
+fn sha256_digest(bytes: &[u8]) -> [u8; 32] {
+    let mut message = bytes.to_vec();
+    let bit_len = (bytes.len() as u64).wrapping_mul(8);
+
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&bit_len.to_be_bytes());
+
+    let mut hash = SHA256_H;
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+
+        for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = hash;
+
+        for (k, w) in SHA256_K.iter().zip(w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(*k)
+                .wrapping_add(w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(majority);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (value, add) in hash.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0; 32];
+    for (chunk, value) in digest.chunks_exact_mut(4).zip(hash) {
+        chunk.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+/// Calculates the CRC-32 (IEEE 802.3) checksum of a [`Value::String`].
+///
+/// * Declaration: `crc32(text: String): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn crc32(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(text)] => Ok(Value::Number(f64::from(crc32_checksum(text.as_bytes())))),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Calculates the SHA-256 hash of a [`Value::String`] as lowercase hexadecimal digits.
+///
+/// * Declaration: `sha256(text: String): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn sha256(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(text)] => {
+            let hex = sha256_digest(text.as_bytes()).iter().fold(
+                String::with_capacity(64),
+                |mut hex, byte| {
+                    let _ = write!(hex, "{byte:02x}");
+                    hex
+                },
+            );
+
+            Ok(Value::String(hex))
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Value;
+
+    #[test]
+    fn hash_crc32() {
+        for (text, expected) in [
+            ("", 0.0),
+            ("abc", 891_568_578.0),
+            ("123456789", 3_421_780_262.0),
+            (
+                "The quick brown fox jumps over the lazy dog",
+                1_095_738_169.0,
+            ),
+        ] {
+            assert_eq!(
+                Ok(Value::Number(expected)),
+                crc32(&[Value::String(String::from(text))]),
+                "{text}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            crc32(&[Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), crc32(&[]));
+    }
+
+    #[test]
+    fn hash_sha256() {
+        for (text, expected) in [
+            (
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                "abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ] {
+            assert_eq!(
+                Ok(Value::String(String::from(expected))),
+                sha256(&[Value::String(String::from(text))]),
+                "{text}"
+            );
+        }
+
+        assert_eq!(Err(NativeError::WrongParameterType), sha256(&[Value::Null]));
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(1)),
+            sha256(&[
+                Value::String(String::from("a")),
+                Value::String(String::from("b"))
+            ])
+        );
+    }
+
+    #[test]
+    fn hash_utf8_bytes() {
+        // 'ä' is hashed as the two UTF-8 bytes 0xC3 0xA4
+        assert_eq!(
+            Ok(Value::String(String::from(
+                "33e6d73fee82904c8d7afb78de1154d1e8dc2a0edb08120e63df5b9385c2d9cc"
+            ))),
+            sha256(&[Value::String(String::from("ä"))])
+        );
+        assert_eq!(
+            Ok(Value::Number(1_890_972_035.0)),
+            crc32(&[Value::String(String::from("ä"))])
+        );
+    }
+}
@@ -1,14 +1,16 @@
 //! Functions to perform calculations with [`Value::Number`] variables.
 
+#[cfg(feature = "random")]
 use getrandom::{getrandom, Error};
 
 use super::{
-    default_number,
+    approx_equal_f64, check_output_len, default_number, default_string,
     error::{NativeError, NativeResult},
-    smart_vec, usize_from_f64,
+    f64_from_usize, is_safe_integer_f64, smart_vec, usize_from_f64, DEFAULT_EPSILON,
 };
 
 use crate::{
+    arithmetic,
     function::{Arity, Function},
     Value,
 };
@@ -18,24 +20,74 @@ use crate::{
 pub fn functions() -> Vec<Function> {
     vec![
         Function::new(abs, Arity::required(1), "abs(value: Number): Number"),
+        Function::new(arc_cos, Arity::required(1), "arc_cos(value: Number): Number"),
+        Function::new(arc_sin, Arity::required(1), "arc_sin(value: Number): Number"),
         Function::new(arc_tan, Arity::required(1), "arc_tan(value: Number): Number"),
+        Function::new(ceil, Arity::required(1), "ceil(value: Number): Number"),
+        Function::new(clamp, Arity::required(3), "clamp(value: Number, min: Number, max: Number): Number"),
         Function::new(cos, Arity::required(1), "cos(value: Number): Number"),
+        Function::new(cos_deg, Arity::required(1), "cos_deg(degrees: Number): Number"),
+        Function::new(degrees, Arity::required(1), "degrees(radians: Number): Number"),
         Function::new(exp, Arity::required(1), "exp(value: Number): Number"),
+        Function::new(floor, Arity::required(1), "floor(value: Number): Number"),
         Function::new(frac, Arity::required(1), "frac(value: Number): Number"),
         Function::new(ln, Arity::required(1), "ln(value: Number): Number"),
+        Function::new(log, Arity::required(2), "log(value: Number, base: Number): Number"),
+        Function::new(log2, Arity::required(1), "log2(value: Number): Number"),
+        Function::new(log10, Arity::required(1), "log10(value: Number): Number"),
+        Function::new(radians, Arity::required(1), "radians(degrees: Number): Number"),
         Function::new(round, Arity::required(1), "round(value: Number): Number"),
+        Function::new(round_to, Arity::required(2), "round_to(value: Number, decimals: Number): Number"),
+        Function::new(sign, Arity::required(1), "sign(value: Number): Number"),
         Function::new(sin, Arity::required(1), "sin(value: Number): Number"),
+        Function::new(sin_deg, Arity::required(1), "sin_deg(degrees: Number): Number"),
         Function::new(sqrt, Arity::required(1), "sqrt(value: Number): Number"),
+        Function::new(tan, Arity::required(1), "tan(value: Number): Number"),
+        Function::new(tan_deg, Arity::required(1), "tan_deg(degrees: Number): Number"),
         Function::new(trunc, Arity::required(1), "trunc(value: Number): Number"),
         Function::new(int_to_hex, Arity::required(1), "int_to_hex(value: Number): String"),
+        Function::new(hex_to_int, Arity::required(1), "hex_to_int(value: String): Number"),
+        Function::new(format_number_auto, Arity::required(1), "format_number_auto(value: Number): String"),
+        Function::new(format_float, Arity::optional(2, 1), "format_float(value: Number, decimals: Number, thousands_sep: String = ''): String"),
         Function::new(even, Arity::required(1), "even(value: Number): Boolean"),
         Function::new(odd, Arity::required(1), "odd(value: Number): Boolean"),
+        Function::new(is_safe_integer, Arity::required(1), "is_safe_integer(value: Number): Boolean"),
+        Function::new(is_nan, Arity::required(1), "is_nan(value: Number): Boolean"),
+        Function::new(is_infinite, Arity::required(1), "is_infinite(value: Number): Boolean"),
+        Function::new(approx_equal, Arity::optional(2, 1), "approx_equal(left: Number, right: Number, epsilon: Number = 1e-9): Boolean"),
         Function::new(pow, Arity::optional(1, 1), "pow(value: Number, exponent: Number = 2): Number"),
+        Function::new(cumulative_sum, Arity::required(1), "cumulative_sum(values: Array): Array"),
+        Function::new(moving_average, Arity::required(2), "moving_average(values: Array, window: Number): Array"),
+        Function::new(diff, Arity::required(1), "diff(values: Array): Array"),
+        Function::new(percentile, Arity::optional(2, 1), "percentile(values: Array, p: Number, interpolation: String = 'linear'): Number"),
+        Function::new(quantile, Arity::optional(2, 1), "quantile(values: Array, q: Number, interpolation: String = 'linear'): Number"),
+        Function::new(sum, Arity::Variadic, "sum(...): Number"),
+        Function::new(product, Arity::Variadic, "product(...): Number"),
+        Function::new(avg, Arity::Variadic, "avg(...): Number"),
+        Function::new(median, Arity::Variadic, "median(...): Number"),
+        #[cfg(feature = "random")]
         Function::impure(random, Arity::optional(0, 1), "random(range: Number = 1): Number"),
+        #[cfg(feature = "random")]
         Function::impure(choice, Arity::Variadic, "choice(...): Any"),
+        Function::new(random_seeded, Arity::optional(1, 1), "random_seeded(seed: Number, range: Number = 1): Number"),
+        Function::new(choice_seeded, Arity::Variadic, "choice_seeded(seed: Number, ...): Any"),
     ]
 }
 
+/// Returns all math constants: `pi`, `e`, `tau`, `inf` and `nan`.
+pub fn constants() -> Vec<(String, Value)> {
+    [
+        ("pi", std::f64::consts::PI),
+        ("e", std::f64::consts::E),
+        ("tau", std::f64::consts::TAU),
+        ("inf", f64::INFINITY),
+        ("nan", f64::NAN),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), Value::Number(value)))
+    .collect()
+}
+
 macro_rules! generate_std_math_functions {
     ($($func_name:ident $std_func:ident),*) => {$(
 
@@ -59,17 +111,149 @@ macro_rules! generate_std_math_functions {
 // Generate common parameter-less f64 functions.
 generate_std_math_functions!(
     abs abs,
+    arc_cos acos,
+    arc_sin asin,
     arc_tan atan,
+    ceil ceil,
     cos cos,
+    degrees to_degrees,
     exp exp,
+    floor floor,
     frac fract,
     ln ln,
+    log2 log2,
+    log10 log10,
+    radians to_radians,
     round round,
     sin sin,
     sqrt sqrt,
+    tan tan,
     trunc trunc
 );
 
+/// Applies a trigonometric function to an angle in degrees. The angle is reduced
+/// to the range of 0 to 360 degrees first and results within a small epsilon of
+/// a multiple of 0.5 are snapped to it, so `sin_deg(180)` is exactly 0.
+fn degree_function(params: &[Value], function: fn(f64) -> f64) -> NativeResult {
+    match params {
+        [Value::Number(degrees)] => {
+            let result = function(degrees.rem_euclid(360.0).to_radians());
+            let snapped = (result * 2.0).round() / 2.0;
+
+            if (result - snapped).abs() < 1e-12 {
+                Ok(Value::Number(snapped + 0.0)) // avoid -0
+            } else {
+                Ok(Value::Number(result))
+            }
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Calculates the sine of an angle in degrees.
+///
+/// * Declaration: `sin_deg(degrees: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn sin_deg(params: &[Value]) -> NativeResult {
+    degree_function(params, f64::sin)
+}
+
+/// Calculates the cosine of an angle in degrees.
+///
+/// * Declaration: `cos_deg(degrees: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn cos_deg(params: &[Value]) -> NativeResult {
+    degree_function(params, f64::cos)
+}
+
+/// Calculates the tangent of an angle in degrees. The tangent of 90 and 270
+/// degrees is undefined and returns `NaN`.
+///
+/// * Declaration: `tan_deg(degrees: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn tan_deg(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(degrees)] if degrees.rem_euclid(180.0) == 90.0 => {
+            Ok(Value::Number(f64::NAN))
+        }
+        _ => degree_function(params, f64::tan),
+    }
+}
+
+/// Returns the sign of a [`Value::Number`] as -1, 0 or 1. `NaN` is returned as is.
+///
+/// * Declaration: `sign(value: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn sign(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(value)] if *value == 0.0 => Ok(Value::Number(0.0)),
+        [Value::Number(value)] if value.is_nan() => Ok(Value::Number(*value)),
+        [Value::Number(value)] => Ok(Value::Number(value.signum())),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Restricts a [`Value::Number`] to the range between `min` and `max`.
+///
+/// * Declaration: `clamp(value: Number, min: Number, max: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if `min` is greater than `max` or either bound is `NaN`.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn clamp(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(value), Value::Number(min), Value::Number(max)] if min <= max => {
+            Ok(Value::Number(value.clamp(*min, *max)))
+        }
+        [Value::Number(_), Value::Number(min), Value::Number(max)] => {
+            Err(NativeError::keyed("err.math.clamp_range", [min, max]))
+        }
+        [_, _, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(3)),
+    }
+}
+
+/// Calculates the logarithm of a [`Value::Number`] to an arbitrary base.
+///
+/// * Declaration: `log(value: Number, base: Number): Number`
+///
+/// # Remarks
+///
+/// A value which is not positive results in `NaN` or `-inf`, which are rejected
+/// by [`ExecutionOptions::reject_non_finite_numbers`](crate::ExecutionOptions::reject_non_finite_numbers).
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn log(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(value), Value::Number(base)] => Ok(Value::Number(value.log(*base))),
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
 /// Converts a [`Value::Number`] to an uppercase hex [`Value::String`].
 ///
 /// * Declaration: `int_to_hex(value: Number): String`
@@ -87,6 +271,222 @@ pub fn int_to_hex(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Converts a hex [`Value::String`] to a [`Value::Number`], the inverse of
+/// [`int_to_hex`].
+///
+/// * Declaration: `hex_to_int(value: String): Number`
+///
+/// # Remarks
+///
+/// The digits are case insensitive and may have a `$` or `0x` prefix like a
+/// hex literal. Sixteen digits are read as a two's complement, so the negative
+/// numbers of `int_to_hex` read back as well.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the Value is not a valid hex number.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+#[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
+pub fn hex_to_int(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(v)] => {
+            let digits = v
+                .strip_prefix('$')
+                .or_else(|| v.strip_prefix("0x"))
+                .or_else(|| v.strip_prefix("0X"))
+                .unwrap_or(v);
+            let value = u64::from_str_radix(digits, 16)
+                .map_err(|e| NativeError::keyed("err.number.parse", [v, &e]))?;
+
+            Ok(Value::Number(value as i64 as f64))
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Formats a [`Value::Number`] into a [`Value::String`], like `str` and the
+/// [`Display`](std::fmt::Display) of a [`Value`].
+///
+/// * Declaration: `format_number_auto(value: Number): String`
+///
+/// # Remarks
+///
+/// The notation depends on the magnitude of the number:
+///
+/// | Number                          | Notation                          | Example              |
+/// |---------------------------------|-----------------------------------|----------------------|
+/// | `1e-6 <= abs(value) < 1e15`     | plain decimal                     | `0.000001`, `-12.5`  |
+/// | all other finite numbers        | scientific                        | `1e15`, `-1.5e-7`    |
+/// | `0` and `-0`                    | plain decimal                     | `0`                  |
+/// | infinity and `NaN`              | the names of the constants        | `inf`, `-inf`, `nan` |
+///
+/// Both notations use the fewest significant digits which read back to the
+/// exact same number, up to 17 digits. The plain decimal notation can always
+/// be read back by the compiler.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn format_number_auto(params: &[Value]) -> NativeResult {
+    match params {
+        [value @ Value::Number(_)] => Ok(Value::String(value.to_string())),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Rounds the shortest decimal representation of a finite number half away
+/// from zero, so `1.005` is rounded like the written literal and not like its
+/// binary approximation `1.00499...`.
+///
+/// Returns the digits of the absolute value and the count of integer digits,
+/// which is at least one.
+fn round_half_away(value: f64, decimals: usize) -> (Vec<u8>, usize) {
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: isize = exponent.parse().unwrap_or(0);
+
+    let mut digits: Vec<u8> = mantissa
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|digit| digit - b'0')
+        .collect();
+
+    // the first digit has the place value 10^exponent
+    let leading = usize::try_from(-exponent).unwrap_or(0);
+    digits.splice(0..0, std::iter::repeat(0).take(leading));
+
+    let mut point = usize::try_from(exponent + 1).unwrap_or(0).max(1);
+    if digits.len() < point {
+        digits.resize(point, 0);
+    }
+
+    let keep = point.saturating_add(decimals);
+    if digits.len() > keep {
+        let round_up = digits[keep] >= 5;
+        digits.truncate(keep);
+
+        if round_up {
+            match digits.iter().rposition(|digit| *digit < 9) {
+                Some(index) => {
+                    digits[index] += 1;
+                    digits[index + 1..].fill(0);
+                }
+                None => {
+                    digits.fill(0);
+                    digits.insert(0, 1);
+                    point += 1;
+                }
+            }
+        }
+    }
+
+    (digits, point)
+}
+
+fn digits_to_string(digits: &[u8]) -> String {
+    digits
+        .iter()
+        .map(|digit| char::from(b'0' + digit))
+        .collect()
+}
+
+/// Rounds a [`Value::Number`] to a number of decimals, half away from zero.
+///
+/// * Declaration: `round_to(value: Number, decimals: Number): Number`
+///
+/// # Remarks
+///
+/// The number is rounded like its shortest decimal representation, e.g.
+/// `round_to(1.005, 2)` is `1.01`. Infinity and `NaN` are returned unchanged.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type
+/// or the decimals are negative.
+pub fn round_to(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(value), Value::Number(decimals)] if *decimals >= 0.0 => {
+            if !value.is_finite() {
+                return Ok(Value::Number(*value));
+            }
+
+            let (digits, point) = round_half_away(*value, usize_from_f64(*decimals));
+            let sign = if value.is_sign_negative() { "-" } else { "" };
+            let text = format!(
+                "{sign}{}.{}",
+                digits_to_string(&digits[..point]),
+                digits_to_string(&digits[point..])
+            );
+
+            text.parse()
+                .map(Value::Number)
+                .map_err(|_| NativeError::WrongParameterType)
+        }
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Formats a [`Value::Number`] with a fixed number of decimals into a
+/// [`Value::String`], optionally with grouped thousands.
+///
+/// * Declaration: `format_float(value: Number, decimals: Number, thousands_sep: String = ''): String`
+///
+/// # Remarks
+///
+/// The number is rounded like [`round_to`]. Zero decimals omit the decimal
+/// point and a number rounded to zero has no sign.
+///
+/// # Errors
+///
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type,
+/// the value is not finite or the decimals are negative.
+pub fn format_float(params: &[Value]) -> NativeResult {
+    let thousands_sep = default_string(params, 2, "")?;
+
+    let (value, decimals) = match params {
+        [Value::Number(value), Value::Number(decimals), ..]
+            if value.is_finite() && *decimals >= 0.0 =>
+        {
+            (*value, usize_from_f64(*decimals))
+        }
+        [_, _, ..] => return Err(NativeError::WrongParameterType),
+        _ => return Err(NativeError::WrongParameterCount(2)),
+    };
+
+    let (mut digits, point) = round_half_away(value, decimals);
+
+    check_output_len(decimals.saturating_add(point + point / 3 * thousands_sep.len() + 2))?;
+    digits.resize(point + decimals, 0);
+
+    let mut result = String::new();
+
+    if value.is_sign_negative() && digits.iter().any(|digit| *digit != 0) {
+        result.push('-');
+    }
+
+    for (index, digit) in digits_to_string(&digits[..point]).chars().enumerate() {
+        if index > 0 && (point - index) % 3 == 0 {
+            result.push_str(thousands_sep);
+        }
+        result.push(digit);
+    }
+
+    if decimals > 0 {
+        result.push('.');
+        result.push_str(&digits_to_string(&digits[point..]));
+    }
+
+    Ok(Value::String(result))
+}
+
 /// Checks if a [`Value::Number`] is even and returns a [`Value::Boolean`].
 ///
 /// * Declaration: `even(value: Number): Boolean`
@@ -119,6 +519,77 @@ pub fn odd(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Checks if a [`Value::Number`] is an integer which can be represented without
+/// losing precision (see [`super::MAX_SAFE_INTEGER`]).
+///
+/// * Declaration: `is_safe_integer(value: Number): Boolean`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn is_safe_integer(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(value)] => Ok(Value::Boolean(is_safe_integer_f64(*value))),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Checks if a [`Value::Number`] is `NaN`, e.g. the result of `0 / 0`.
+///
+/// * Declaration: `is_nan(value: Number): Boolean`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn is_nan(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(value)] => Ok(Value::Boolean(value.is_nan())),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Checks if a [`Value::Number`] is positive or negative infinity.
+///
+/// * Declaration: `is_infinite(value: Number): Boolean`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn is_infinite(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(value)] => Ok(Value::Boolean(value.is_infinite())),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Checks if two [`Value::Number`] are equal within a relative tolerance, to
+/// compare the results of floating point calculations like `0.1 + 0.2`.
+/// Infinities are only equal if they are identical and `NaN` is never equal.
+///
+/// * Declaration: `approx_equal(left: Number, right: Number, epsilon: Number = 1e-9): Boolean`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn approx_equal(params: &[Value]) -> NativeResult {
+    let epsilon = default_number(params, 2, DEFAULT_EPSILON)?;
+
+    match params {
+        [Value::Number(left), Value::Number(right), ..] => {
+            Ok(Value::Boolean(approx_equal_f64(*left, *right, epsilon)))
+        }
+        [_, _, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
 /// Raises a [`Value::Number`] to the power of an exponent.
 ///
 /// * Declaration: `pow(value: Number, exponent: Number = 2): Number`
@@ -137,9 +608,323 @@ pub fn pow(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Extracts the numbers of a [`Value::Array`] or names the index of the first
+/// element which is not a [`Value::Number`].
+fn numbers(values: &[Value]) -> Result<Vec<f64>, NativeError> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| match value {
+            Value::Number(number) => Ok(*number),
+            _ => Err(NativeError::keyed("err.math.not_number", [&index])),
+        })
+        .collect()
+}
+
+/// Returns the running totals of a [`Value::Array`] of numbers.
+///
+/// * Declaration: `cumulative_sum(values: Array): Array`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if an element of the array is not a [`Value::Number`].
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn cumulative_sum(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Array(values)] => {
+            let mut sum = 0.0;
+
+            Ok(Value::Array(
+                numbers(values)?
+                    .into_iter()
+                    .map(|number| {
+                        sum += number;
+                        Value::Number(sum)
+                    })
+                    .collect(),
+            ))
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Returns the averages of all windows with a fixed size over a [`Value::Array`]
+/// of numbers. The result contains `length - window + 1` elements.
+///
+/// * Declaration: `moving_average(values: Array, window: Number): Array`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if an element of the array is not a [`Value::Number`]
+/// or if the window is smaller than 1 or larger than the array.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn moving_average(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Array(values), Value::Number(window)] => {
+            let numbers = numbers(values)?;
+
+            if *window < 1.0 || usize_from_f64(*window) > numbers.len() {
+                return Err(NativeError::keyed(
+                    "err.math.window",
+                    [window, &numbers.len()],
+                ));
+            }
+
+            let window = usize_from_f64(*window);
+            let divisor = f64_from_usize(window);
+            let mut sum: f64 = numbers[..window].iter().sum();
+            let mut averages = Vec::with_capacity(numbers.len() - window + 1);
+            averages.push(Value::Number(sum / divisor));
+
+            // slide the window by adding the next and removing the oldest number
+            for (next, oldest) in numbers[window..].iter().zip(&numbers) {
+                sum += next - oldest;
+                averages.push(Value::Number(sum / divisor));
+            }
+
+            Ok(Value::Array(averages))
+        }
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Returns the differences between successive elements of a [`Value::Array`]
+/// of numbers. The result contains `length - 1` elements.
+///
+/// * Declaration: `diff(values: Array): Array`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if an element of the array is not a [`Value::Number`].
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn diff(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Array(values)] => Ok(Value::Array(
+            numbers(values)?
+                .windows(2)
+                .map(|pair| Value::Number(arithmetic::sub(pair[1], pair[0])))
+                .collect(),
+        )),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Extracts the numbers of an aggregation, either from a single [`Value::Array`]
+/// or from all parameters.
+fn aggregated(params: &[Value]) -> Result<Vec<f64>, NativeError> {
+    smart_vec(params)
+        .iter()
+        .map(|value| match value {
+            Value::Number(number) => Ok(*number),
+            _ => Err(NativeError::WrongParameterType),
+        })
+        .collect()
+}
+
+/// Returns the sum of a [`Value::Array`] of numbers or of all parameters.
+///
+/// * Declaration: `sum(...): Number`
+///
+/// # Remarks
+///
+/// The sum of an empty array is `0`, the neutral element of the addition.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterType`] if a value is not a [`Value::Number`].
+pub fn sum(params: &[Value]) -> NativeResult {
+    Ok(Value::Number(
+        aggregated(params)?.into_iter().fold(0.0, arithmetic::add),
+    ))
+}
+
+/// Returns the product of a [`Value::Array`] of numbers or of all parameters.
+///
+/// * Declaration: `product(...): Number`
+///
+/// # Remarks
+///
+/// The product of an empty array is `1`, the neutral element of the multiplication.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterType`] if a value is not a [`Value::Number`].
+pub fn product(params: &[Value]) -> NativeResult {
+    Ok(Value::Number(
+        aggregated(params)?.into_iter().fold(1.0, arithmetic::mul),
+    ))
+}
+
+/// Returns the arithmetic mean of a [`Value::Array`] of numbers or of all parameters.
+///
+/// * Declaration: `avg(...): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if there are no values, the average is undefined.
+/// Will return [`NativeError::WrongParameterType`] if a value is not a [`Value::Number`].
+pub fn avg(params: &[Value]) -> NativeResult {
+    let numbers = aggregated(params)?;
+
+    if numbers.is_empty() {
+        return Err(NativeError::keyed("err.math.empty", [&"average"]));
+    }
+
+    let sum = numbers.iter().copied().fold(0.0, arithmetic::add);
+    Ok(Value::Number(arithmetic::div(
+        sum,
+        f64_from_usize(numbers.len()),
+    )))
+}
+
+/// Returns the median of a [`Value::Array`] of numbers or of all parameters.
+/// The values do not need to be sorted beforehand.
+///
+/// * Declaration: `median(...): Number`
+///
+/// # Remarks
+///
+/// The median of an even number of values is the average of the two middle values.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if there are no values, the median is undefined.
+/// Will return [`NativeError::WrongParameterType`] if a value is not a [`Value::Number`].
+pub fn median(params: &[Value]) -> NativeResult {
+    let mut numbers = aggregated(params)?;
+
+    if numbers.is_empty() {
+        return Err(NativeError::keyed("err.math.empty", [&"median"]));
+    }
+
+    numbers.sort_by(f64::total_cmp);
+
+    let middle = numbers.len() / 2;
+    let median = if numbers.len() % 2 == 0 {
+        (numbers[middle - 1] + numbers[middle]) / 2.0
+    } else {
+        numbers[middle]
+    };
+
+    Ok(Value::Number(median))
+}
+
+/// Computes the `q`-th quantile of unsorted numbers, following the inclusive
+/// convention of NumPy: the rank of the quantile is `q * (length - 1)`.
+fn compute_quantile(values: &[Value], q: f64, interpolation: &str) -> NativeResult {
+    let mut numbers = numbers(values)?;
+
+    if numbers.is_empty() {
+        return Err(NativeError::keyed("err.math.quantile_empty", []));
+    }
+
+    numbers.sort_by(f64::total_cmp);
+
+    let rank = q * f64_from_usize(numbers.len() - 1);
+    let lower = numbers[usize_from_f64(rank)];
+    let higher = numbers[usize_from_f64(rank.ceil())];
+    let fraction = rank.fract();
+
+    let result = match interpolation {
+        "linear" => lower + (higher - lower) * fraction,
+        "lower" => lower,
+        "higher" => higher,
+        "midpoint" => (lower + higher) / 2.0,
+        // ties are resolved to the even rank
+        "nearest" if fraction == 0.5 && usize_from_f64(rank) % 2 == 0 => lower,
+        "nearest" if fraction >= 0.5 => higher,
+        "nearest" => lower,
+        _ => {
+            return Err(NativeError::keyed(
+                "err.math.interpolation",
+                [&interpolation],
+            ))
+        }
+    };
+
+    Ok(Value::Number(result))
+}
+
+/// Returns the `p`-th percentile of a [`Value::Array`] of numbers.
+///
+/// * Declaration: `percentile(values: Array, p: Number, interpolation: String = 'linear'): Number`
+///
+/// # Remarks
+///
+/// See [`quantile`], with `p` ranging from 0 to 100.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the array is empty, an element is not a [`Value::Number`],
+/// `p` is outside of 0 to 100 or the interpolation is unknown.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn percentile(params: &[Value]) -> NativeResult {
+    let interpolation = default_string(params, 2, "linear")?;
+
+    match params {
+        [Value::Array(values), Value::Number(p), ..] if (0.0..=100.0).contains(p) => {
+            compute_quantile(values, p / 100.0, interpolation)
+        }
+        [Value::Array(_), Value::Number(p), ..] => {
+            Err(NativeError::keyed("err.math.percentile_range", [p]))
+        }
+        [_, _, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Returns the `q`-th quantile of a [`Value::Array`] of numbers.
+///
+/// * Declaration: `quantile(values: Array, q: Number, interpolation: String = 'linear'): Number`
+///
+/// # Remarks
+///
+/// The array is sorted internally and does not need to be sorted beforehand.
+/// The quantile follows the inclusive convention (type 7 in R, the default
+/// of NumPy), its rank inside the sorted array is `q * (length - 1)`.
+/// If the rank falls between two elements `lower` and `higher` the result
+/// depends on the interpolation:
+///
+/// * `'linear'`: `lower + (higher - lower) * fraction`
+/// * `'lower'`: `lower`
+/// * `'higher'`: `higher`
+/// * `'midpoint'`: `(lower + higher) / 2`
+/// * `'nearest'`: the element with the closer rank, ties resolve to the even rank
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the array is empty, an element is not a [`Value::Number`],
+/// `q` is outside of 0 to 1 or the interpolation is unknown.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn quantile(params: &[Value]) -> NativeResult {
+    let interpolation = default_string(params, 2, "linear")?;
+
+    match params {
+        [Value::Array(values), Value::Number(q), ..] if (0.0..=1.0).contains(q) => {
+            compute_quantile(values, *q, interpolation)
+        }
+        [Value::Array(_), Value::Number(q), ..] => {
+            Err(NativeError::keyed("err.math.quantile_range", [q]))
+        }
+        [_, _, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+#[cfg(feature = "random")]
 const USIZE_BYTE_SIZE: usize = (usize::BITS / 8) as usize;
+#[cfg(feature = "random")]
 const U64_BYTE_SIZE: usize = (u64::BITS / 8) as usize;
 
+#[cfg(feature = "random")]
 #[allow(clippy::cast_precision_loss)]
 fn get_random_float(max: f64) -> Result<f64, Error> {
     if max == 0.0 {
@@ -155,55 +940,144 @@ fn get_random_float(max: f64) -> Result<f64, Error> {
     Ok((random * max) / u64::MAX as f64)
 }
 
-fn get_random_int(max: usize) -> Result<usize, Error> {
-    if max == 0 {
-        return Ok(0); // shortcut for empty range
+#[cfg(feature = "random")]
+fn get_random_int(max: usize) -> Result<usize, Error> {
+    if max == 0 {
+        return Ok(0); // shortcut for empty range
+    }
+
+    // get random bytes from the OS
+    let mut buffer = [0u8; USIZE_BYTE_SIZE];
+    getrandom(&mut buffer)?;
+
+    // constrain the values to an integer range via modulo
+    let random = usize::from_le_bytes(buffer);
+    Ok(random % max)
+}
+
+/// Generates a random [`Value::Number`] provided by the os system source via [`mod@getrandom`].
+///
+/// * Declaration: `random(range: Number = 1): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+#[cfg(feature = "random")]
+pub fn random(params: &[Value]) -> NativeResult {
+    let range = default_number(params, 0, 1.0)?;
+    let result =
+        get_random_float(range).map_err(|e| NativeError::keyed("err.math.random", [&e]))?;
+
+    Ok(Value::Number(result))
+}
+
+/// Returns a random choice of one of the provided parameters.
+/// If a [`Value::Array`] is provided as sole parameter, returns one random [`Value`]
+///
+/// * Declaration: `choice(...): Any`
+///
+/// # Remarks
+///
+/// Uses [`mod@getrandom`] as RNG source.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterType`] no parameters are provided.
+#[cfg(feature = "random")]
+pub fn choice(params: &[Value]) -> NativeResult {
+    let choices = smart_vec(params);
+    let index: usize =
+        get_random_int(choices.len()).map_err(|e| NativeError::keyed("err.math.random", [&e]))?;
+
+    choices
+        .get(index)
+        .cloned()
+        .ok_or(NativeError::WrongParameterType)
+}
+
+/// The increment of the [SplitMix64](https://prng.di.unimi.it/splitmix64.c) state.
+pub(crate) const SPLIT_MIX_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// The output function of SplitMix64 for an already incremented `state`.
+pub(crate) fn split_mix64(state: u64) -> u64 {
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Scales the upper 53 bits of a random number to a float between 0 and `range`,
+/// the `range` itself is excluded.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn random_in_range(bits: u64, range: f64) -> f64 {
+    (bits >> 11) as f64 / (1_u64 << 53) as f64 * range
+}
+
+/// Picks one of the choices with a random number, see [`choice`].
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn choice_of(bits: u64, params: &[Value]) -> NativeResult {
+    let choices = smart_vec(params);
+
+    if choices.is_empty() {
+        return Err(NativeError::WrongParameterType);
     }
 
-    // get random bytes from the OS
-    let mut buffer = [0u8; USIZE_BYTE_SIZE];
-    getrandom(&mut buffer)?;
+    // the modulo of an u64 keeps the result independent of the platform
+    let index = (bits % choices.len() as u64) as usize;
+    Ok(choices[index].clone())
+}
 
-    // constrain the values to an integer range via modulo
-    let random = usize::from_le_bytes(buffer);
-    Ok(random % max)
+/// The seed of a [`Value::Number`] is its bit pattern, so every number is a
+/// distinct seed on every platform.
+fn seeded(seed: f64) -> u64 {
+    split_mix64(seed.to_bits().wrapping_add(SPLIT_MIX_GAMMA))
 }
 
-/// Generates a random [`Value::Number`] provided by the os system source via [`mod@getrandom`].
+/// Generates a deterministic pseudo random [`Value::Number`] between 0 and `range`
+/// from a seed. The same seed returns the same number on every platform.
 ///
-/// * Declaration: `random(range: Number = 1): Number`
+/// * Declaration: `random_seeded(seed: Number, range: Number = 1): Number`
+///
+/// # Remarks
+///
+/// The number is the first output of a [SplitMix64](https://prng.di.unimi.it/splitmix64.c)
+/// generator started at the bit pattern of the seed. The function is pure, so
+/// the optimizer replaces a call with constant parameters by its result.
 ///
 /// # Errors
 ///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
-pub fn random(params: &[Value]) -> NativeResult {
-    let range = default_number(params, 0, 1.0)?;
-    let result = get_random_float(range).map_err(|e| NativeError::CustomError(e.to_string()))?;
+pub fn random_seeded(params: &[Value]) -> NativeResult {
+    let range = default_number(params, 1, 1.0)?;
 
-    Ok(Value::Number(result))
+    match params {
+        [Value::Number(seed), ..] => Ok(Value::Number(random_in_range(seeded(*seed), range))),
+        [_, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
 }
 
-/// Returns a random choice of one of the provided parameters.
-/// If a [`Value::Array`] is provided as sole parameter, returns one random [`Value`]
+/// Returns a deterministic pseudo random choice of the parameters following the seed.
+/// If a [`Value::Array`] is provided after the seed, returns one of its elements.
 ///
-/// * Declaration: `choice(...): Any`
+/// * Declaration: `choice_seeded(seed: Number, ...): Any`
 ///
 /// # Remarks
 ///
-/// Uses [`mod@getrandom`] as RNG source.
+/// Uses the same generator as [`random_seeded`] and is pure as well.
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::WrongParameterType`] no parameters are provided.
-pub fn choice(params: &[Value]) -> NativeResult {
-    let choices = smart_vec(params);
-    let index: usize =
-        get_random_int(choices.len()).map_err(|e| NativeError::CustomError(e.to_string()))?;
-
-    choices
-        .get(index)
-        .cloned()
-        .ok_or(NativeError::WrongParameterType)
+/// Will return [`NativeError::WrongParameterCount`] if no seed is provided.
+/// Will return [`NativeError::WrongParameterType`] if the seed is not a [`Value::Number`]
+/// or there is nothing to choose from.
+pub fn choice_seeded(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Number(seed), choices @ ..] => choice_of(seeded(*seed), choices),
+        [_, ..] => Err(NativeError::WrongParameterType),
+        [] => Err(NativeError::WrongParameterCount(1)),
+    }
 }
 
 #[cfg(test)]
@@ -237,6 +1111,115 @@ mod test {
         );
     }
 
+    #[test]
+    fn math_hex_to_int() {
+        assert_eq!(
+            Ok(Value::Number(12345.0)),
+            hex_to_int(&vec![Value::String(String::from("3039"))])
+        );
+        assert_eq!(
+            Ok(Value::Number(3735928559.0)),
+            hex_to_int(&vec![Value::String(String::from("$deadBEEF"))])
+        );
+        assert_eq!(
+            Ok(Value::Number(255.0)),
+            hex_to_int(&vec![Value::String(String::from("0xFF"))])
+        );
+        assert_eq!(
+            Ok(Value::Number(-1.0)),
+            hex_to_int(&vec![Value::String(String::from("FFFFFFFFFFFFFFFF"))])
+        );
+
+        for input in ["", "0x", "FG", "-1", "1_000"] {
+            assert!(hex_to_int(&vec![Value::String(String::from(input))]).is_err());
+        }
+
+        for value in [0.0, 255.0, -255.0, 3735928559.0] {
+            assert_eq!(
+                Ok(Value::Number(value)),
+                int_to_hex(&vec![Value::Number(value)]).and_then(|hex| hex_to_int(&[hex]))
+            );
+        }
+    }
+
+    #[test]
+    fn math_round_to() {
+        let round =
+            |value: f64, decimals: f64| round_to(&[Value::Number(value), Value::Number(decimals)]);
+
+        assert_eq!(Ok(Value::Number(1234.57)), round(1234.5678, 2.0));
+        assert_eq!(Ok(Value::Number(-1234.57)), round(-1234.5678, 2.0));
+        assert_eq!(Ok(Value::Number(1.01)), round(1.005, 2.0));
+        assert_eq!(Ok(Value::Number(2.68)), round(2.675, 2.0));
+        assert_eq!(Ok(Value::Number(3.0)), round(2.5, 0.0));
+        assert_eq!(Ok(Value::Number(-3.0)), round(-2.5, 0.0));
+        assert_eq!(Ok(Value::Number(10.0)), round(9.995, 2.0));
+        assert_eq!(Ok(Value::Number(0.1)), round(0.05, 1.0));
+        assert_eq!(Ok(Value::Number(0.0)), round(0.004, 2.0));
+        assert_eq!(Ok(Value::Number(1e21)), round(1e21, 2.0));
+        assert_eq!(Ok(Value::Number(0.1 + 0.2)), round(0.1 + 0.2, 400.0));
+        assert_eq!(Ok(Value::Number(f64::INFINITY)), round(f64::INFINITY, 2.0));
+
+        assert_eq!(Err(NativeError::WrongParameterType), round(1.5, -1.0));
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            round_to(&[Value::String(String::from("1.5")), Value::Number(1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(2)),
+            round_to(&[Value::Number(1.5)])
+        );
+    }
+
+    #[test]
+    fn math_format_float() {
+        let format = |value: f64, decimals: f64, separator: &str| {
+            format_float(&[
+                Value::Number(value),
+                Value::Number(decimals),
+                Value::String(String::from(separator)),
+            ])
+        };
+        let text = |value: &str| Ok(Value::String(String::from(value)));
+
+        assert_eq!(
+            text("1234.57"),
+            format_float(&[Value::Number(1234.5678), Value::Number(2.0)])
+        );
+        assert_eq!(text("1,234.57"), format(1234.5678, 2.0, ","));
+        assert_eq!(text("-1,234.57"), format(-1234.5678, 2.0, ","));
+        assert_eq!(text("1 234 568"), format(1234567.5, 0.0, " "));
+        assert_eq!(text("123,456"), format(123456.0, 0.0, ","));
+        assert_eq!(text("1,000.00"), format(999.999, 2.0, ","));
+        assert_eq!(text("0.0"), format(-0.04, 1.0, ""));
+        assert_eq!(text("-0.1"), format(-0.05, 1.0, ""));
+        assert_eq!(text("1.01"), format(1.005, 2.0, ""));
+        assert_eq!(text("0.500"), format(0.5, 3.0, ""));
+        assert_eq!(text("1000000000000000000000.0"), format(1e21, 1.0, ""));
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            format(f64::NAN, 2.0, "")
+        );
+        assert_eq!(Err(NativeError::WrongParameterType), format(1.0, -2.0, ""));
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            format_float(&[Value::Boolean(true), Value::Number(2.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            format_float(&[Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(2)),
+            format_float(&[Value::Number(1.0)])
+        );
+        assert!(matches!(
+            format(1.0, 1e12, ""),
+            Err(NativeError::OutputTooLarge { .. })
+        ));
+    }
+
     #[test]
     fn math_even() {
         assert_eq!(Ok(Value::Boolean(true)), even(&vec![Value::Number(10.0)]));
@@ -265,6 +1248,296 @@ mod test {
         }
     }
 
+    #[test]
+    fn math_is_safe_integer() {
+        let max = 2.0_f64.powi(53);
+
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            is_safe_integer(&[Value::Number(42.0)])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            is_safe_integer(&[Value::Number(-(max - 1.0))])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            is_safe_integer(&[Value::Number(max - 1.0)])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(false)),
+            is_safe_integer(&[Value::Number(max)])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(false)),
+            is_safe_integer(&[Value::Number(max + 2.0)])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(false)),
+            is_safe_integer(&[Value::Number(1.5)])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(false)),
+            is_safe_integer(&[Value::Number(f64::NAN)])
+        );
+
+        assert!(is_safe_integer(&[Value::String(String::from("1"))]).is_err());
+    }
+
+    #[test]
+    fn math_non_finite() {
+        for (value, nan, infinite) in [
+            (1.0, false, false),
+            (f64::NAN, true, false),
+            (f64::INFINITY, false, true),
+            (f64::NEG_INFINITY, false, true),
+        ] {
+            assert_eq!(Ok(Value::Boolean(nan)), is_nan(&[Value::Number(value)]));
+            assert_eq!(
+                Ok(Value::Boolean(infinite)),
+                is_infinite(&[Value::Number(value)])
+            );
+        }
+
+        assert!(is_nan(&[Value::String(String::from("NaN"))]).is_err());
+        assert!(is_infinite(&[Value::String(String::from("inf"))]).is_err());
+    }
+
+    fn numbers(values: &[f64]) -> Value {
+        Value::Array(values.iter().copied().map(Value::Number).collect())
+    }
+
+    #[test]
+    fn math_cumulative_sum() {
+        assert_eq!(
+            Ok(numbers(&[1.0, 3.0, 6.0, 2.0])),
+            cumulative_sum(&[numbers(&[1.0, 2.0, 3.0, -4.0])])
+        );
+        assert_eq!(Ok(numbers(&[])), cumulative_sum(&[numbers(&[])]));
+        assert_eq!(
+            Err(NativeError::Keyed(
+                "err.math.not_number",
+                vec![String::from("1")]
+            )),
+            cumulative_sum(&[Value::Array(vec![
+                Value::Number(1.0),
+                Value::String(String::from("2"))
+            ])])
+        );
+    }
+
+    #[test]
+    fn math_moving_average() {
+        let values = numbers(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        assert_eq!(
+            Ok(numbers(&[2.0, 3.0, 4.0])),
+            moving_average(&[values.clone(), Value::Number(3.0)])
+        );
+        assert_eq!(
+            Ok(values.clone()),
+            moving_average(&[values.clone(), Value::Number(1.0)])
+        );
+        assert_eq!(
+            Ok(numbers(&[3.0])),
+            moving_average(&[values.clone(), Value::Number(5.0)])
+        );
+        assert!(moving_average(&[values.clone(), Value::Number(0.0)]).is_err());
+        assert!(moving_average(&[values.clone(), Value::Number(6.0)]).is_err());
+        assert!(moving_average(&[numbers(&[]), Value::Number(1.0)]).is_err());
+        assert!(moving_average(&[values, Value::String(String::from("2"))]).is_err());
+    }
+
+    #[test]
+    fn math_diff() {
+        assert_eq!(
+            Ok(numbers(&[1.0, -3.0, 0.5])),
+            diff(&[numbers(&[1.0, 2.0, -1.0, -0.5])])
+        );
+        assert_eq!(Ok(numbers(&[])), diff(&[numbers(&[42.0])]));
+        assert_eq!(Ok(numbers(&[])), diff(&[numbers(&[])]));
+        assert!(diff(&[Value::Array(vec![Value::Boolean(true)])]).is_err());
+    }
+
+    #[test]
+    fn math_aggregations() {
+        let values = [numbers(&[4.0, 1.0, 3.0, 2.0])];
+
+        assert_eq!(Ok(Value::Number(10.0)), sum(&values));
+        assert_eq!(Ok(Value::Number(24.0)), product(&values));
+        assert_eq!(Ok(Value::Number(2.5)), avg(&values));
+        assert_eq!(Ok(Value::Number(2.5)), median(&values));
+        assert_eq!(
+            Ok(Value::Number(3.0)),
+            median(&[Value::Number(5.0), Value::Number(3.0), Value::Number(-1.0)])
+        );
+
+        assert_eq!(Ok(Value::Number(0.0)), sum(&[numbers(&[])]));
+        assert_eq!(Ok(Value::Number(1.0)), product(&[]));
+        assert_eq!(
+            Err(NativeError::Keyed(
+                "err.math.empty",
+                vec![String::from("average")]
+            )),
+            avg(&[numbers(&[])])
+        );
+        assert_eq!(
+            Err(NativeError::Keyed(
+                "err.math.empty",
+                vec![String::from("median")]
+            )),
+            median(&[])
+        );
+
+        let mixed = [Value::Number(1.0), Value::String(String::from("2"))];
+        assert_eq!(Err(NativeError::WrongParameterType), sum(&mixed));
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            median(&[Value::Array(mixed.to_vec())])
+        );
+    }
+
+    #[test]
+    fn math_quantile_known_answers() {
+        // expected values computed with the NumPy quantile methods
+        // [linear, lower, higher, midpoint, nearest]
+        let cases: [(&[f64], f64, [f64; 5]); 9] = [
+            (&[7.0], 0.5, [7.0, 7.0, 7.0, 7.0, 7.0]),
+            (&[7.0], 0.95, [7.0, 7.0, 7.0, 7.0, 7.0]),
+            (&[3.0, 1.0], 0.25, [1.5, 1.0, 3.0, 2.0, 1.0]),
+            (&[3.0, 1.0], 0.5, [2.0, 1.0, 3.0, 2.0, 1.0]),
+            (
+                &[15.0, 20.0, 35.0, 40.0, 50.0],
+                0.9,
+                [46.0, 40.0, 50.0, 45.0, 50.0],
+            ),
+            (
+                &[9.0, 1.0, 8.0, 2.0, 7.0, 3.0, 6.0, 4.0, 5.0, 10.0],
+                0.25,
+                [3.25, 3.0, 4.0, 3.5, 3.0],
+            ),
+            (
+                &[9.0, 1.0, 8.0, 2.0, 7.0, 3.0, 6.0, 4.0, 5.0, 10.0],
+                0.95,
+                [9.55, 9.0, 10.0, 9.5, 10.0],
+            ),
+            (
+                &[120.0, 80.0, 95.0, 310.0, 101.0, 99.0, 87.0],
+                0.25,
+                [91.0, 87.0, 95.0, 91.0, 95.0],
+            ),
+            (
+                &[120.0, 80.0, 95.0, 310.0, 101.0, 99.0, 87.0],
+                0.9,
+                [196.0, 120.0, 310.0, 215.0, 120.0],
+            ),
+        ];
+        let interpolations = ["linear", "lower", "higher", "midpoint", "nearest"];
+
+        for (values, q, expected) in cases {
+            for (interpolation, expected) in interpolations.iter().zip(expected) {
+                let params = [
+                    numbers(values),
+                    Value::Number(q),
+                    Value::String(interpolation.to_string()),
+                ];
+
+                let Ok(Value::Number(result)) = quantile(&params) else {
+                    panic!("quantile failed for {values:?}, {q}, {interpolation}");
+                };
+                assert!((result - expected).abs() < 1e-9, "{result} <> {expected}");
+
+                let params = [numbers(values), Value::Number(q * 100.0), params[2].clone()];
+                let Ok(Value::Number(result)) = percentile(&params) else {
+                    panic!("percentile failed for {values:?}, {q}, {interpolation}");
+                };
+                assert!((result - expected).abs() < 1e-9, "{result} <> {expected}");
+            }
+        }
+    }
+
+    #[test]
+    fn math_quantile_errors() {
+        let values = numbers(&[1.0, 2.0, 3.0]);
+
+        assert_eq!(
+            Ok(Value::Number(2.0)),
+            quantile(&[values.clone(), Value::Number(0.5)])
+        );
+        assert!(quantile(&[values.clone(), Value::Number(-0.1)]).is_err());
+        assert!(quantile(&[values.clone(), Value::Number(1.1)]).is_err());
+        assert!(percentile(&[values.clone(), Value::Number(100.1)]).is_err());
+        assert!(quantile(&[numbers(&[]), Value::Number(0.5)]).is_err());
+        assert!(quantile(&[values.clone(), Value::Number(0.5), Value::Boolean(true)]).is_err());
+        assert_eq!(
+            Err(NativeError::Keyed(
+                "err.math.interpolation",
+                vec![String::from("cubic")]
+            )),
+            quantile(&[
+                values,
+                Value::Number(0.5),
+                Value::String(String::from("cubic"))
+            ])
+        );
+        assert_eq!(
+            Err(NativeError::Keyed(
+                "err.math.not_number",
+                vec![String::from("2")]
+            )),
+            percentile(&[
+                Value::Array(vec![
+                    Value::Number(1.0),
+                    Value::Number(2.0),
+                    Value::Boolean(true)
+                ]),
+                Value::Number(50.0)
+            ])
+        );
+    }
+
+    #[test]
+    fn math_approx_equal() {
+        for (left, right, expected) in [
+            (0.1 + 0.2, 0.3, true),
+            (1e10, 1e10 + 1.0, true),
+            (0.0, 0.0, true),
+            (0.0, 1e-12, false),
+            (1.0, 2.0, false),
+            (f64::INFINITY, f64::INFINITY, true),
+            (f64::INFINITY, f64::NEG_INFINITY, false),
+            (f64::INFINITY, f64::MAX, false),
+            (f64::NAN, f64::NAN, false),
+        ] {
+            assert_eq!(
+                Ok(Value::Boolean(expected)),
+                approx_equal(&[Value::Number(left), Value::Number(right)]),
+                "{left} ~= {right}"
+            );
+        }
+
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            approx_equal(&[
+                Value::Number(100.0),
+                Value::Number(101.0),
+                Value::Number(0.01)
+            ])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            approx_equal(&[Value::Number(1.0), Value::String(String::from("1"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            approx_equal(&[Value::Null, Value::Number(1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(2)),
+            approx_equal(&[Value::Number(1.0)])
+        );
+    }
+
     #[test]
     fn math_pow() {
         assert_eq!(
@@ -304,6 +1577,231 @@ mod test {
         assert!(round(&vec![]).is_err());
     }
 
+    #[test]
+    fn math_floor_ceil() {
+        for (value, floored, ceiled) in [(1.5, 1.0, 2.0), (-1.5, -2.0, -1.0), (3.0, 3.0, 3.0)] {
+            assert_eq!(Ok(Value::Number(floored)), floor(&[Value::Number(value)]));
+            assert_eq!(Ok(Value::Number(ceiled)), ceil(&[Value::Number(value)]));
+        }
+
+        assert!(floor(&[Value::String(String::from("1.5"))]).is_err());
+        assert!(ceil(&[]).is_err());
+    }
+
+    #[test]
+    fn math_sign() {
+        for (value, expected) in [(-3.5, -1.0), (0.0, 0.0), (-0.0, 0.0), (42.0, 1.0)] {
+            assert_eq!(Ok(Value::Number(expected)), sign(&[Value::Number(value)]));
+        }
+
+        assert!(matches!(
+            sign(&[Value::Number(f64::NAN)]),
+            Ok(Value::Number(value)) if value.is_nan()
+        ));
+        assert_eq!(Err(NativeError::WrongParameterType), sign(&[Value::Null]));
+    }
+
+    #[test]
+    fn math_clamp() {
+        let clamped = |value: f64| {
+            clamp(&[
+                Value::Number(value),
+                Value::Number(0.0),
+                Value::Number(10.0),
+            ])
+        };
+
+        assert_eq!(Ok(Value::Number(0.0)), clamped(-5.0));
+        assert_eq!(Ok(Value::Number(5.0)), clamped(5.0));
+        assert_eq!(Ok(Value::Number(10.0)), clamped(15.0));
+        assert_eq!(
+            Ok(Value::Number(3.0)),
+            clamp(&[Value::Number(1.0), Value::Number(3.0), Value::Number(3.0)])
+        );
+
+        assert_eq!(
+            Err(NativeError::keyed("err.math.clamp_range", [&10.0, &0.0])),
+            clamp(&[Value::Number(5.0), Value::Number(10.0), Value::Number(0.0)])
+        );
+        assert!(clamp(&[
+            Value::Number(5.0),
+            Value::Number(f64::NAN),
+            Value::Number(1.0)
+        ])
+        .is_err());
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            clamp(&[Value::Number(5.0), Value::Null, Value::Number(1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(3)),
+            clamp(&[Value::Number(5.0), Value::Number(1.0)])
+        );
+    }
+
+    #[test]
+    fn math_logarithms() {
+        assert_eq!(Ok(Value::Number(10.0)), log2(&[Value::Number(1024.0)]));
+        assert_eq!(Ok(Value::Number(3.0)), log10(&[Value::Number(1000.0)]));
+        assert_eq!(
+            Ok(Value::Number(2.0)),
+            log(&[Value::Number(81.0), Value::Number(9.0)])
+        );
+        assert_eq!(
+            Ok(Value::Number(f64::NEG_INFINITY)),
+            log(&[Value::Number(0.0), Value::Number(10.0)])
+        );
+        assert!(matches!(
+            log(&[Value::Number(-1.0), Value::Number(10.0)]),
+            Ok(Value::Number(value)) if value.is_nan()
+        ));
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            log(&[Value::Number(8.0), Value::String(String::from("2"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(2)),
+            log(&[Value::Number(8.0)])
+        );
+    }
+
+    #[test]
+    fn math_degrees() {
+        let deg = |function: fn(&[Value]) -> NativeResult, degrees: f64| {
+            function(&[Value::Number(degrees)])
+        };
+
+        assert_eq!(Ok(Value::Number(180.0)), deg(degrees, std::f64::consts::PI));
+        assert_eq!(Ok(Value::Number(std::f64::consts::PI)), deg(radians, 180.0));
+
+        for (degrees, sin, cos) in [
+            (0.0, 0.0, 1.0),
+            (30.0, 0.5, 30_f64.to_radians().cos()),
+            (90.0, 1.0, 0.0),
+            (180.0, 0.0, -1.0),
+            (270.0, -1.0, 0.0),
+            (360.0, 0.0, 1.0),
+            (-90.0, -1.0, 0.0),
+            (720.0 + 60.0, 60_f64.to_radians().sin(), 0.5),
+        ] {
+            assert_eq!(
+                Ok(Value::Number(sin)),
+                deg(sin_deg, degrees),
+                "sin {degrees}"
+            );
+            assert_eq!(
+                Ok(Value::Number(cos)),
+                deg(cos_deg, degrees),
+                "cos {degrees}"
+            );
+        }
+
+        assert_eq!(Ok(Value::Number(1.0)), deg(tan_deg, 45.0));
+        assert_eq!(Ok(Value::Number(0.0)), deg(tan_deg, 180.0));
+        assert_eq!(Ok(Value::Number(-1.0)), deg(tan_deg, 135.0));
+        for degrees in [90.0, 270.0, -90.0] {
+            assert!(matches!(deg(tan_deg, degrees), Ok(Value::Number(value)) if value.is_nan()));
+        }
+        assert!(matches!(deg(sin_deg, f64::INFINITY), Ok(Value::Number(value)) if value.is_nan()));
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            sin_deg(&[Value::String(String::from("90"))])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), tan_deg(&[]));
+    }
+
+    #[test]
+    fn math_trigonometry() {
+        let close = |result: NativeResult, expected: f64| matches!(result, Ok(Value::Number(value)) if (value - expected).abs() < 1e-12);
+
+        assert!(close(
+            tan(&[Value::Number(std::f64::consts::FRAC_PI_4)]),
+            1.0
+        ));
+        assert!(close(
+            arc_sin(&[Value::Number(1.0)]),
+            std::f64::consts::FRAC_PI_2
+        ));
+        assert!(close(arc_cos(&[Value::Number(1.0)]), 0.0));
+        assert!(matches!(
+            arc_sin(&[Value::Number(2.0)]),
+            Ok(Value::Number(value)) if value.is_nan()
+        ));
+    }
+
+    #[test]
+    fn math_split_mix64() {
+        // the first outputs of the reference implementation seeded with 0
+        let outputs: Vec<u64> = (1..=3)
+            .map(|step: u64| split_mix64(step.wrapping_mul(SPLIT_MIX_GAMMA)))
+            .collect();
+
+        assert_eq!(
+            vec![
+                0xE220_A839_7B1D_CDAF,
+                0x6E78_9E6A_A1B9_65F4,
+                0x06C4_5D18_8009_454F
+            ],
+            outputs
+        );
+    }
+
+    #[test]
+    fn math_random_seeded() {
+        assert_eq!(
+            Ok(Value::Number(0.883_310_808_213_642_6)),
+            random_seeded(&[Value::Number(0.0)])
+        );
+
+        for seed in [-1.0, 0.5, 42.0, 1e300] {
+            let first = random_seeded(&[Value::Number(seed), Value::Number(10.0)]);
+
+            assert_eq!(
+                first,
+                random_seeded(&[Value::Number(seed), Value::Number(10.0)])
+            );
+            assert!(matches!(first, Ok(Value::Number(value)) if (0.0..10.0).contains(&value)));
+        }
+        assert_ne!(
+            random_seeded(&[Value::Number(1.0)]),
+            random_seeded(&[Value::Number(2.0)])
+        );
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            random_seeded(&[Value::String(String::from("1"))])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), random_seeded(&[]));
+    }
+
+    #[test]
+    fn math_choice_seeded() {
+        let choices = [Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)];
+        let spread = [
+            Value::Number(7.0),
+            choices[0].clone(),
+            choices[1].clone(),
+            choices[2].clone(),
+        ];
+        let array = [Value::Number(7.0), Value::Array(choices.to_vec())];
+
+        assert_eq!(choice_seeded(&spread), choice_seeded(&array));
+        assert!(choices.contains(&choice_seeded(&spread).unwrap()));
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            choice_seeded(&[Value::Number(7.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            choice_seeded(&[Value::Null, Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), choice_seeded(&[]));
+    }
+
+    #[cfg(feature = "random")]
     #[test]
     fn math_random() {
         for _ in 0..1000 {
@@ -321,6 +1819,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "random")]
     #[test]
     fn math_choice() {
         let input = &vec![
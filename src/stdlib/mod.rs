@@ -1,7 +1,9 @@
 //! The SLAC standard library features various functions which can be included into a [`StaticEnvironment`].
 
+use std::sync::OnceLock;
+
 use crate::function::Function;
-use crate::{StaticEnvironment, Value};
+use crate::{CancellationToken, StaticEnvironment, Value};
 
 #[doc(inline)]
 pub use self::error::NativeError;
@@ -10,7 +12,10 @@ pub use self::error::NativeResult;
 
 pub mod common;
 pub mod error;
+#[cfg(feature = "hash")]
+pub mod hash;
 pub mod math;
+pub mod money;
 #[cfg(feature = "regex")]
 pub mod regex;
 pub mod string;
@@ -23,28 +28,110 @@ pub const STRING_OFFSET: f64 = 0.0;
 #[cfg(not(feature = "zero_based_strings"))]
 pub const STRING_OFFSET: f64 = 1.0;
 
+/// The largest integer (2^53 - 1) which can be represented by a [`Value::Number`]
+/// without losing precision.
+pub const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// The default relative tolerance of `approx_equal` and the `~=` operator.
+pub const DEFAULT_EPSILON: f64 = 1e-9;
+
+/// The default of [`max_output_len`], 16 MiB.
+pub const DEFAULT_MAX_OUTPUT_LEN: usize = 16 * 1024 * 1024;
+
+static MAX_OUTPUT_LEN: OnceLock<usize> = OnceLock::new();
+
+/// Sets the maximum length of strings (in bytes) and arrays (in elements)
+/// produced by the builtin functions.
+///
+/// # Remarks
+///
+/// The limit is crate-wide and can only be set once, before it is read by
+/// the first builtin function.
+///
+/// # Errors
+///
+/// Returns the rejected limit if the limit was already set or read.
+pub fn set_max_output_len(limit: usize) -> Result<(), usize> {
+    MAX_OUTPUT_LEN.set(limit)
+}
+
+/// Returns the maximum length of strings (in bytes) and arrays (in elements)
+/// produced by the builtin functions, [`DEFAULT_MAX_OUTPUT_LEN`] if not set.
+#[must_use]
+pub fn max_output_len() -> usize {
+    *MAX_OUTPUT_LEN.get_or_init(|| DEFAULT_MAX_OUTPUT_LEN)
+}
+
+/// Checks the estimated length of an output before allocating it.
+pub(crate) fn check_output_len(requested: usize) -> Result<(), NativeError> {
+    let limit = max_output_len();
+
+    if requested > limit {
+        Err(NativeError::OutputTooLarge { limit, requested })
+    } else {
+        Ok(())
+    }
+}
+
 /// A function pointer used to execute native Rust functions.
 /// All parameters to the function are inside a single Vec<[`Value`]>.
 pub type NativeFunction = fn(&[Value]) -> NativeResult;
 
-/// A vector of all builtin [`Functions`](Function) for use with [`extend_environment`].
+/// A function pointer used to execute native Rust functions, which may observe
+/// the [`CancellationToken`] of the current execution to stop early.
+pub type CancellableFunction = fn(&[Value], &CancellationToken) -> NativeResult;
+
+/// A slice of all builtin [`Functions`](Function) for use with [`extend_environment`].
+/// Each [`Function`] is tagged with the name of its module.
+///
+/// The slice is created once on the first call and shared afterwards.
+#[must_use]
+pub fn builtins() -> &'static [Function] {
+    static BUILTINS: OnceLock<Vec<Function>> = OnceLock::new();
+
+    BUILTINS.get_or_init(|| {
+        [
+            in_module("common", common::functions()),
+            in_module("math", math::functions()),
+            in_module("money", money::functions()),
+            in_module("string", string::functions()),
+            #[cfg(feature = "chrono")]
+            in_module("time", time::functions()),
+            #[cfg(feature = "regex")]
+            in_module("regex", regex::functions()),
+            #[cfg(feature = "hash")]
+            in_module("hash", hash::functions()),
+        ]
+        .concat()
+    })
+}
+
+/// Returns all builtin constants, e.g. `pi` or `inf`, for use with [`extend_environment`].
 #[must_use]
-pub fn builtins() -> Vec<Function> {
-    [
-        common::functions(),
-        math::functions(),
-        string::functions(),
-        #[cfg(feature = "chrono")]
-        time::functions(),
-        #[cfg(feature = "regex")]
-        regex::functions(),
-    ]
-    .concat()
-}
-
-/// Extends a [`StaticEnvironment`] with all standard library functions.
+pub fn constants() -> Vec<(String, Value)> {
+    math::constants()
+}
+
+fn in_module(module: &'static str, functions: Vec<Function>) -> Vec<Function> {
+    functions
+        .into_iter()
+        .map(|function| function.module(module))
+        .collect()
+}
+
+/// Extends a [`StaticEnvironment`] with all standard library functions and [`constants`].
+///
+/// # Remarks
+///
+/// Existing functions and variables with the same name as a builtin are replaced.
+/// Variables added afterwards replace the constants.
+/// Calling `extend_environment` multiple times has the same result as calling it once.
 pub fn extend_environment(env: &mut StaticEnvironment) {
-    env.add_functions(builtins());
+    env.add_functions(builtins().iter().cloned());
+
+    for (name, value) in constants() {
+        env.add_variable(&name, value);
+    }
 }
 
 pub(crate) fn default_string<'a>(
@@ -80,9 +167,21 @@ pub(crate) fn get_index(index: f64) -> Result<usize, NativeError> {
     }
 }
 
+/// Converts a user-facing string index into a zero-based index, depending on
+/// the `zero_based_strings` feature.
 #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
 pub(crate) fn get_string_index(index: f64) -> Result<usize, NativeError> {
-    get_index(index).map(|index| index - STRING_OFFSET as usize)
+    let index = get_index(index)?;
+
+    index
+        .checked_sub(STRING_OFFSET as usize)
+        .ok_or(NativeError::IndexOutOfBounds(index))
+}
+
+/// Reports a zero-based string index as the user-facing index.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn string_index_out_of_bounds(index: usize) -> NativeError {
+    NativeError::IndexOutOfBounds(index + STRING_OFFSET as usize)
 }
 
 #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
@@ -95,6 +194,24 @@ pub(crate) fn f64_from_usize(value: usize) -> f64 {
     value as f64
 }
 
+/// Checks if a number is an integer inside the range of [`MAX_SAFE_INTEGER`].
+pub(crate) fn is_safe_integer_f64(value: f64) -> bool {
+    value.trunc() == value && value.abs() <= MAX_SAFE_INTEGER
+}
+
+/// Checks if two numbers differ by at most `epsilon` relative to the larger
+/// magnitude. Infinities are only equal to themselves and `NaN` is never equal.
+pub(crate) fn approx_equal_f64(left: f64, right: f64, epsilon: f64) -> bool {
+    #[allow(clippy::float_cmp)]
+    if left == right {
+        return true;
+    }
+
+    left.is_finite()
+        && right.is_finite()
+        && (left - right).abs() <= epsilon * left.abs().max(right.abs())
+}
+
 /// Returns the first parameter if it's an [`Value::Array`] or return all
 /// parameters as varadic function.
 pub(crate) fn smart_vec(params: &[Value]) -> &[Value] {
@@ -0,0 +1,349 @@
+//! Functions to parse and format currency amounts like `1.234,56 EUR` or `$1,234.56`.
+
+use crate::{
+    function::{Arity, Function},
+    Value,
+};
+
+use super::{
+    check_output_len, default_string,
+    error::{NativeError, NativeResult},
+};
+
+/// Returns all money functions.
+#[rustfmt::skip]
+pub fn functions() -> Vec<Function> {
+    vec![
+        Function::new(format_money, Arity::optional(2, 2), "format_money(amount: Number, currency: String, decimal_sep: String = '.', thousands_sep: String = ','): String"),
+        Function::new(parse_money, Arity::required(1), "parse_money(text: String): Array"),
+    ]
+}
+
+/// Currency symbols with their ISO 4217 code.
+const CURRENCY_SYMBOLS: [(&str, &str); 5] = [
+    ("$", "USD"),
+    ("€", "EUR"),
+    ("£", "GBP"),
+    ("¥", "JPY"),
+    ("Fr.", "CHF"),
+];
+
+/// Regular, non-breaking and thin spaces.
+fn is_money_space(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '\u{00A0}' | '\u{2009}' | '\u{202F}')
+}
+
+fn is_thousands_separator(c: char) -> bool {
+    matches!(c, '\'' | '’') || is_money_space(c)
+}
+
+fn parse_error(text: &str, portion: &str) -> NativeError {
+    NativeError::keyed("err.money.parse", [&text, &portion])
+}
+
+/// Resolves a currency symbol or a three letter code into an uppercase code.
+fn currency_code(text: &str, currency: &str) -> Result<String, NativeError> {
+    if let Some((_, code)) = CURRENCY_SYMBOLS
+        .iter()
+        .find(|(symbol, _)| *symbol == currency)
+    {
+        Ok((*code).to_string())
+    } else if currency.len() == 3 && currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(currency.to_ascii_uppercase())
+    } else if currency.is_empty() {
+        Ok(String::new())
+    } else {
+        Err(parse_error(text, currency))
+    }
+}
+
+/// Parses the digits and separators of an amount. The last `.` or `,` is the
+/// decimal separator, unless it occurs multiple times.
+fn parse_amount(text: &str, amount: &str) -> Result<f64, NativeError> {
+    if let Some(invalid) = amount
+        .split(|c: char| c.is_ascii_digit() || matches!(c, '.' | ',') || is_thousands_separator(c))
+        .find(|portion| !portion.is_empty())
+    {
+        return Err(parse_error(text, invalid));
+    }
+
+    let decimal = amount
+        .rfind(['.', ','])
+        .filter(|index| amount.matches(&amount[*index..=*index]).count() == 1);
+
+    let (integer, fraction) = match decimal {
+        Some(index) => (&amount[..index], &amount[index + 1..]),
+        None => (amount, ""),
+    };
+
+    if let Some(separator) = fraction.matches(|c: char| !c.is_ascii_digit()).next() {
+        return Err(parse_error(text, separator));
+    }
+
+    // thousands are grouped by a single kind of separator
+    let mut grouping = integer.matches(['.', ',']);
+    if let Some(first) = grouping.next() {
+        if let Some(other) = grouping.find(|separator| *separator != first) {
+            return Err(parse_error(text, other));
+        }
+    }
+
+    let digits: String = integer.chars().filter(char::is_ascii_digit).collect();
+
+    format!("{digits}.{fraction}")
+        .parse::<f64>()
+        .map_err(|_| parse_error(text, amount))
+}
+
+/// Parses a currency amount into a [`Value::Array`] of the amount and the currency code.
+///
+/// * Declaration: `parse_money(text: String): Array`
+///
+/// # Remarks
+///
+/// * The last `.` or `,` is the decimal separator, unless it occurs multiple times
+///   (`1.234,56` and `1,234.56` are both `1234.56`, but `1,234,567` is `1234567`).
+///   A single separator is always decimal, `1,234` is `1.234`.
+/// * Apostrophes (`1'234.50`) and spaces, including thin spaces, group thousands.
+/// * The currency may precede or follow the amount, as a three letter code or
+///   one of the symbols `$`, `€`, `£`, `¥` or `Fr.`. Codes are returned uppercase,
+///   symbols as their code and a missing currency as an empty string.
+/// * Negative amounts start with a `-` or are enclosed in parentheses, e.g. `(12.50 EUR)`.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] naming the portion of the text which is not part of an amount.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn parse_money(params: &[Value]) -> NativeResult {
+    let text = match params {
+        [Value::String(text)] => text,
+        [_] => return Err(NativeError::WrongParameterType),
+        _ => return Err(NativeError::WrongParameterCount(1)),
+    };
+
+    let trimmed = text.trim_matches(is_money_space);
+    let (negative, trimmed) = match trimmed.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(inner) => (true, inner.trim_matches(is_money_space)),
+        None => (false, trimmed),
+    };
+
+    let (Some(start), Some(end)) = (
+        trimmed.find(|c: char| c.is_ascii_digit()),
+        trimmed.rfind(|c: char| c.is_ascii_digit()),
+    ) else {
+        return Err(parse_error(text, trimmed));
+    };
+
+    let prefix = trimmed[..start].trim_matches(is_money_space);
+    let suffix = trimmed[end + 1..].trim_matches(is_money_space);
+
+    // the sign may precede or follow a currency symbol, like `-$12` or `$-12`
+    let (minus, prefix) = match (prefix.strip_prefix('-'), prefix.strip_suffix('-')) {
+        (Some(prefix), _) | (None, Some(prefix)) => (true, prefix.trim_matches(is_money_space)),
+        (None, None) => (false, prefix),
+    };
+
+    if minus && negative {
+        return Err(parse_error(text, "-"));
+    }
+
+    let currency = match (prefix, suffix) {
+        (currency, "") | ("", currency) => currency_code(text, currency)?,
+        (_, suffix) => return Err(parse_error(text, suffix)),
+    };
+
+    let amount = parse_amount(text, &trimmed[start..=end])?;
+    let amount = if negative || minus { -amount } else { amount };
+
+    Ok(Value::Array(vec![
+        Value::Number(amount),
+        Value::String(currency),
+    ]))
+}
+
+/// Formats an amount with two decimals, grouped thousands and a trailing currency code.
+///
+/// * Declaration: `format_money(amount: Number, currency: String, decimal_sep: String = '.', thousands_sep: String = ','): String`
+///
+/// # Remarks
+///
+/// The result can be parsed with [`parse_money`], if the separators are
+/// supported by it and differ from each other.
+///
+/// # Errors
+///
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type
+/// or the amount is not finite.
+pub fn format_money(params: &[Value]) -> NativeResult {
+    let decimal_sep = default_string(params, 2, ".")?;
+    let thousands_sep = default_string(params, 3, ",")?;
+
+    let (amount, currency) = match params {
+        [Value::Number(amount), Value::String(currency), ..] if amount.is_finite() => {
+            (amount, currency)
+        }
+        [_, _, ..] => return Err(NativeError::WrongParameterType),
+        _ => return Err(NativeError::WrongParameterCount(2)),
+    };
+
+    let rounded = format!("{:.2}", amount.abs());
+    let (integer, fraction) = rounded.split_at(rounded.len() - 3);
+    let groups = (integer.len() + 2) / 3;
+
+    check_output_len(
+        rounded.len() + groups * thousands_sep.len() + decimal_sep.len() + currency.len() + 2,
+    )?;
+
+    let mut result = String::new();
+
+    if amount.is_sign_negative() && rounded.bytes().any(|b| matches!(b, b'1'..=b'9')) {
+        result.push('-');
+    }
+
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            result.push_str(thousands_sep);
+        }
+        result.push(digit);
+    }
+
+    result.push_str(decimal_sep);
+    result.push_str(&fraction[1..]);
+
+    if !currency.is_empty() {
+        result.push(' ');
+        result.push_str(currency);
+    }
+
+    Ok(Value::String(result))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_money, parse_money};
+    use crate::{stdlib::NativeError, Value};
+
+    fn parse(text: &str) -> Result<(f64, String), NativeError> {
+        match parse_money(&[Value::String(text.to_string())])? {
+            Value::Array(values) => match values.as_slice() {
+                [Value::Number(amount), Value::String(currency)] => Ok((*amount, currency.clone())),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    fn format(amount: f64, currency: &str, decimal_sep: &str, thousands_sep: &str) -> String {
+        let params = [
+            Value::Number(amount),
+            Value::String(currency.to_string()),
+            Value::String(decimal_sep.to_string()),
+            Value::String(thousands_sep.to_string()),
+        ];
+
+        match format_money(&params) {
+            Ok(Value::String(text)) => text,
+            result => panic!("{result:?}"),
+        }
+    }
+
+    #[test]
+    fn money_parse() {
+        let cases = [
+            // EUR
+            ("1.234,56 EUR", 1234.56, "EUR"),
+            ("1.234,56 €", 1234.56, "EUR"),
+            ("€ 12,50", 12.5, "EUR"),
+            ("1\u{202F}234,56\u{00A0}eur", 1234.56, "EUR"),
+            // US
+            ("$1,234.56", 1234.56, "USD"),
+            ("1,234,567 USD", 1_234_567.0, "USD"),
+            ("-$12.00", -12.0, "USD"),
+            ("$-12.00", -12.0, "USD"),
+            ("($1,234.56)", -1234.56, "USD"),
+            // Swiss
+            ("CHF 1'234.50", 1234.5, "CHF"),
+            ("1’234.50 Fr.", 1234.5, "CHF"),
+            ("(CHF 12.05)", -12.05, "CHF"),
+            // without currency
+            ("1 234 567,8", 1_234_567.8, ""),
+            ("42", 42.0, ""),
+            ("1,234", 1.234, ""),
+        ];
+
+        for (text, amount, currency) in cases {
+            assert_eq!(Ok((amount, currency.to_string())), parse(text), "{text}");
+        }
+    }
+
+    #[test]
+    fn money_parse_errors() {
+        let cases = [
+            ("12a4 EUR", "a"),
+            ("12.50 EURO", "EURO"),
+            ("$12.50 USD", "USD"),
+            ("EUR", "EUR"),
+            ("", ""),
+            ("-(12.50)", ")"),
+            ("(-12.50)", "-"),
+            ("1.234.5,6,7", ","),
+        ];
+
+        for (text, portion) in cases {
+            assert_eq!(
+                Err(NativeError::Keyed(
+                    "err.money.parse",
+                    vec![text.to_string(), portion.to_string()]
+                )),
+                parse(text),
+                "{text}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            parse_money(&[Value::Number(12.0)])
+        );
+    }
+
+    #[test]
+    fn money_format() {
+        let cases = [
+            (1234.56, "EUR", ",", ".", "1.234,56 EUR"),
+            (1234.56, "USD", ".", ",", "1,234.56 USD"),
+            (1234.5, "CHF", ".", "'", "1'234.50 CHF"),
+            (-1_234_567.891, "USD", ".", ",", "-1,234,567.89 USD"),
+            (0.5, "", ".", ",", "0.50"),
+            (-0.001, "EUR", ",", ".", "0,00 EUR"),
+            (999.999, "EUR", ",", "\u{202F}", "1\u{202F}000,00 EUR"),
+        ];
+
+        for (amount, currency, decimal_sep, thousands_sep, expected) in cases {
+            assert_eq!(
+                expected,
+                format(amount, currency, decimal_sep, thousands_sep),
+                "{amount}"
+            );
+        }
+
+        assert_eq!(
+            Ok(Value::String(String::from("1,234.00 USD"))),
+            format_money(&[Value::Number(1234.0), Value::String(String::from("USD"))])
+        );
+    }
+
+    #[test]
+    fn money_round_trip() {
+        let conventions = [(",", "."), (".", ","), (".", "'"), (",", " "), (".", "")];
+
+        for amount in [0.0, 0.5, 12.05, 999.99, 1234.56, -1234.56, 1_234_567.89] {
+            for (decimal_sep, thousands_sep) in conventions {
+                let text = format(amount, "EUR", decimal_sep, thousands_sep);
+                assert_eq!(Ok((amount, String::from("EUR"))), parse(&text), "{text}");
+            }
+        }
+    }
+}
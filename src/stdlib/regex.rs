@@ -11,7 +11,9 @@ use crate::{
     Value,
 };
 
-use super::{default_number, default_string, usize_from_f64, NativeError, NativeResult};
+use super::{
+    check_output_len, default_number, default_string, usize_from_f64, NativeError, NativeResult,
+};
 
 /// Returns all regex functions as a fixed size array.
 #[rustfmt::skip]
@@ -20,6 +22,8 @@ pub fn functions() -> Vec<Function> {
         Function::new(is_match, Arity::required(2), "re_is_match(haystack: String, pattern: String): Boolean"),
         Function::new(find, Arity::required(2), "re_find(haystack: String, pattern: String): Array<String>"),
         Function::new(capture, Arity::required(2), "re_capture(haystack: String, pattern: String): Array<String>"),
+        Function::new(capture_named, Arity::required(2), "re_capture_named(haystack: String, pattern: String): Array<Array<String>>"),
+        Function::new(split, Arity::optional(2, 1), "re_split(text: String, pattern: String, limit: Number = 0): Array<String>"),
         Function::new(replace, Arity::optional(2, 2), "re_replace(haystack: String, pattern: String, replacement: String = '', limit = 0): String"),
     ]
 }
@@ -30,13 +34,14 @@ pub fn functions() -> Vec<Function> {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the regex produces an error.
+/// Will return [`NativeError::Keyed`] if the regex produces an error.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn is_match(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(haystack), Value::String(pattern)] => {
-            let re = Regex::new(pattern).map_err(|e| NativeError::from(e.to_string()))?;
+            let re = Regex::new(pattern)
+                .map_err(|e| NativeError::keyed("err.regex.invalid", [pattern, &e]))?;
 
             Ok(Value::Boolean(re.is_match(haystack)))
         }
@@ -52,13 +57,14 @@ pub fn is_match(params: &[Value]) -> NativeResult {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the regex produces an error.
+/// Will return [`NativeError::Keyed`] if the regex produces an error.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn find(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(haystack), Value::String(pattern)] => {
-            let re = Regex::new(pattern).map_err(|e| NativeError::from(e.to_string()))?;
+            let re = Regex::new(pattern)
+                .map_err(|e| NativeError::keyed("err.regex.invalid", [pattern, &e]))?;
 
             let groups: Vec<Value> = re
                 .find_iter(haystack)
@@ -90,13 +96,14 @@ fn get_capture_groups(captures: Captures) -> Vec<Value> {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the regex produces an error.
+/// Will return [`NativeError::Keyed`] if the regex produces an error.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn capture(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(haystack), Value::String(pattern)] => {
-            let re = Regex::new(pattern).map_err(|e| NativeError::from(e.to_string()))?;
+            let re = Regex::new(pattern)
+                .map_err(|e| NativeError::keyed("err.regex.invalid", [pattern, &e]))?;
 
             let groups: Vec<Value> = re.captures(haystack).map_or_else(
                 || vec![Value::String(String::new()); re.captures_len()],
@@ -110,13 +117,103 @@ pub fn capture(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Returns the matches of the named regex capture groups as a [`Value::Array`]
+/// of `[name, value]` pairs, in the order of the groups inside the pattern.
+/// Unnamed groups are skipped, named groups which did not participate in the
+/// match are returned as an empty string.
+///
+/// * Declaration: `re_capture_named(haystack: String, pattern: String): Array<Array<String>>`
+///
+/// # Remarks
+///
+/// If the pattern does not match the haystack an empty [`Value::Array`] is returned.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the regex produces an error.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn capture_named(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(haystack), Value::String(pattern)] => {
+            let re = Regex::new(pattern)
+                .map_err(|e| NativeError::keyed("err.regex.invalid", [pattern, &e]))?;
+
+            let Some(captures) = re.captures(haystack) else {
+                return Ok(Value::Array(vec![]));
+            };
+
+            let groups: Vec<Value> = re
+                .capture_names()
+                .flatten()
+                .map(|name| {
+                    let value = captures.name(name).map_or("", |m| m.as_str());
+
+                    Value::Array(vec![
+                        Value::String(name.to_string()),
+                        Value::String(value.to_string()),
+                    ])
+                })
+                .collect();
+
+            Ok(Value::Array(groups))
+        }
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Splits a [`Value::String`] at every match of a regex pattern into a [`Value::Array`].
+/// A `limit` greater than zero caps the number of splits, the remaining text is
+/// returned as the last element.
+///
+/// * Declaration: `re_split(text: String, pattern: String, limit: Number = 0): Array<String>`
+///
+/// # Remarks
+///
+/// Empty matches split the text like [`Regex::split`], e.g. `re_split('abc', '')`
+/// returns `['', 'a', 'b', 'c', '']`.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the regex produces an error.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn split(params: &[Value]) -> NativeResult {
+    let limit = usize_from_f64(default_number(params, 2, 0.0)?);
+
+    match params {
+        [Value::String(text), Value::String(pattern), ..] => {
+            let re = Regex::new(pattern)
+                .map_err(|e| NativeError::keyed("err.regex.invalid", [pattern, &e]))?;
+
+            let parts: Vec<&str> = if limit == 0 {
+                re.split(text).collect()
+            } else {
+                re.splitn(text, limit.saturating_add(1)).collect()
+            };
+            check_output_len(parts.len())?;
+
+            Ok(Value::Array(
+                parts
+                    .into_iter()
+                    .map(|part| Value::String(part.to_string()))
+                    .collect(),
+            ))
+        }
+        [_, _, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
 /// Replaces all matches of a regex pattern inside a [`Value::String`] with a replacement [`Value::String`].
 ///
 /// * Declaration: `re_replace(haystack: String, pattern: String, replacement: String = '', limit = 0): String`
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the regex produces an error.
+/// Will return [`NativeError::Keyed`] if the regex produces an error.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn replace(params: &[Value]) -> NativeResult {
@@ -125,7 +222,8 @@ pub fn replace(params: &[Value]) -> NativeResult {
 
     match params {
         [Value::String(haystack), Value::String(needle), ..] => {
-            let re = Regex::new(needle).map_err(|e| NativeError::from(e.to_string()))?;
+            let re = Regex::new(needle)
+                .map_err(|e| NativeError::keyed("err.regex.invalid", [needle, &e]))?;
 
             Ok(Value::String(
                 re.replacen(haystack, limit, replacement).to_string(),
@@ -194,6 +292,119 @@ mod test {
         );
     }
 
+    #[test]
+    fn re_capture_named() {
+        let pair = |name: &str, value: &str| {
+            Value::Array(vec![
+                Value::String(String::from(name)),
+                Value::String(String::from(value)),
+            ])
+        };
+
+        assert_eq!(
+            Ok(Value::Array(vec![
+                pair("year", "2023"),
+                pair("month", "09"),
+                pair("day", "30")
+            ])),
+            capture_named(&[
+                Value::String(String::from("Date: 2023-09-30")),
+                Value::String(String::from(
+                    r"(?P<year>\d{4})-(?<month>\d{2})-(?<day>\d{2})"
+                ))
+            ])
+        );
+
+        // unnamed groups are skipped, unmatched named groups are empty
+        assert_eq!(
+            Ok(Value::Array(vec![pair("year", "2023"), pair("day", "")])),
+            capture_named(&[
+                Value::String(String::from("2023-09")),
+                Value::String(String::from(r"(?<year>\d{4})-(\d{2})(-(?<day>\d{2}))?"))
+            ])
+        );
+
+        assert_eq!(
+            Ok(Value::Array(vec![])),
+            capture_named(&[
+                Value::String(String::from("no date")),
+                Value::String(String::from(r"(?<year>\d{4})"))
+            ])
+        );
+
+        assert_eq!(
+            Ok(Value::Array(vec![])),
+            capture_named(&[
+                Value::String(String::from("2023")),
+                Value::String(String::from(r"(\d{4})"))
+            ])
+        );
+
+        assert!(matches!(
+            capture_named(&[
+                Value::String(String::from("2023")),
+                Value::String(String::from("(?<year>"))
+            ]),
+            Err(NativeError::Keyed("err.regex.invalid", _))
+        ));
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            capture_named(&[Value::String(String::from("2023")), Value::Null])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(2)),
+            capture_named(&[Value::String(String::from("2023"))])
+        );
+    }
+
+    #[test]
+    fn re_split() {
+        let strings = |parts: &[&str]| {
+            Value::Array(
+                parts
+                    .iter()
+                    .map(|part| Value::String(part.to_string()))
+                    .collect(),
+            )
+        };
+        let text = Value::String(String::from("a1b22c"));
+        let digits = Value::String(String::from(r"\d+"));
+
+        assert_eq!(
+            Ok(strings(&["a", "b", "c"])),
+            split(&[text.clone(), digits.clone()])
+        );
+        assert_eq!(
+            Ok(strings(&["a", "b22c"])),
+            split(&[text.clone(), digits.clone(), Value::Number(1.0)])
+        );
+        assert_eq!(
+            Ok(strings(&["a", "b", "c"])),
+            split(&[text.clone(), digits.clone(), Value::Number(5.0)])
+        );
+        assert_eq!(
+            Ok(strings(&["", "a", "b", "c", ""])),
+            split(&[
+                Value::String(String::from("abc")),
+                Value::String(String::new())
+            ])
+        );
+        assert_eq!(
+            Ok(strings(&[""])),
+            split(&[Value::String(String::new()), digits.clone()])
+        );
+
+        assert!(matches!(
+            split(&[text.clone(), Value::String(String::from("("))]),
+            Err(NativeError::Keyed("err.regex.invalid", _))
+        ));
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            split(&[text.clone(), digits, Value::Boolean(true)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(2)), split(&[text]));
+    }
+
     #[test]
     fn re_replace() {
         assert_eq!(
@@ -1,11 +1,20 @@
 //! Functions to manipulate [`Value::String`] variables.
 
+use std::fmt::Write;
+
+#[cfg(feature = "unicode")]
+use unicode_normalization::UnicodeNormalization;
+
 use crate::{
     function::{Arity, Function},
     Value,
 };
 
-use super::error::{NativeError, NativeResult};
+use super::{
+    check_output_len, default_string,
+    error::{NativeError, NativeResult},
+    f64_from_usize, usize_from_f64,
+};
 
 /// Returns all string functions as a fixed size array.
 #[rustfmt::skip]
@@ -15,12 +24,36 @@ pub fn functions() -> Vec<Function> {
         Function::new(ord, Arity::required(1), "ord(char: String): Number"),
         Function::new(lowercase, Arity::required(1), "lowercase(text: String): String"),
         Function::new(uppercase, Arity::required(1), "uppercase(text: String): String"),
+        Function::new(format, Arity::Variadic, "format(template: String, ...): String"),
+        #[cfg(not(feature = "unicode"))]
         Function::new(same_text, Arity::required(2), "same_text(left: String, right: String): Boolean"),
+        #[cfg(feature = "unicode")]
+        Function::new(same_text, Arity::optional(2, 1), "same_text(left: String, right: String, normalize: Boolean = false): Boolean"),
+        #[cfg(feature = "unicode")]
+        Function::new(normalize, Arity::optional(1, 1), "normalize(text: String, form: String = 'NFC'): String"),
+        #[cfg(feature = "unicode")]
+        Function::new(fold_text, Arity::required(1), "fold_text(text: String): String"),
+        #[cfg(feature = "unicode")]
+        Function::new(same_text_normalized, Arity::required(2), "same_text_normalized(left: String, right: String): Boolean"),
         Function::new(split, Arity::required(2), "split(line: String, separator: String): Array<String>"),
+        Function::new(join, Arity::optional(1, 1), "join(values: Array, separator: String = ','): String"),
         Function::new(split_csv, Arity::optional(1, 1), "split_csv(line: String, separator: String = ';'): Array<String>"),
+        Function::new(tokenize, Arity::optional(1, 2), "tokenize(text: String, delimiters: String = ' \\t\\n', quote: String = '\"'): Array<String>"),
+        Function::new(words, Arity::required(1), "words(text: String): Array<String>"),
         Function::new(trim, Arity::required(1), "trim(text: String): String"),
         Function::new(trim_left, Arity::required(1), "trim_left(text: String): String"),
         Function::new(trim_right, Arity::required(1), "trim_right(text: String): String"),
+        Function::new(pad_left, Arity::optional(2, 1), "pad_left(text: String, width: Number, pad: String = ' '): String"),
+        Function::new(pad_right, Arity::optional(2, 1), "pad_right(text: String, width: Number, pad: String = ' '): String"),
+        Function::new(center, Arity::optional(2, 1), "center(text: String, width: Number, pad: String = ' '): String"),
+        Function::new(levenshtein, Arity::required(2), "levenshtein(left: String, right: String): Number"),
+        Function::new(similarity, Arity::required(2), "similarity(left: String, right: String): Number"),
+        Function::new(base64_encode, Arity::required(1), "base64_encode(text: String): String"),
+        Function::new(base64_decode, Arity::required(1), "base64_decode(text: String): String"),
+        Function::new(url_encode, Arity::required(1), "url_encode(text: String): String"),
+        Function::new(url_decode, Arity::required(1), "url_decode(text: String): String"),
+        Function::new(starts_with, Arity::required(2), "starts_with(text: [String|Array], prefix: [String|Any]): Boolean"),
+        Function::new(ends_with, Arity::required(2), "ends_with(text: [String|Array], suffix: [String|Any]): Boolean"),
     ]
 }
 
@@ -30,7 +63,7 @@ pub fn functions() -> Vec<Function> {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the supplied number is outside of ASCII character range.
+/// Will return [`NativeError::Keyed`] if the supplied number is outside of ASCII character range.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
@@ -39,7 +72,7 @@ pub fn chr(params: &[Value]) -> NativeResult {
         [Value::Number(ordinal)] if (0.0..127.0).contains(ordinal) => Ok(Value::String(
             char::from_u32(*ordinal as u32).unwrap_or('\0').to_string(),
         )),
-        [Value::Number(_)] => Err(NativeError::from("number is out of ASCII range")),
+        [Value::Number(_)] => Err(NativeError::keyed("err.string.chr_range", [])),
         [_] => Err(NativeError::WrongParameterType),
         _ => Err(NativeError::WrongParameterCount(1)),
     }
@@ -51,7 +84,7 @@ pub fn chr(params: &[Value]) -> NativeResult {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the supplied number is outside of ASCII character range.
+/// Will return [`NativeError::Keyed`] if the supplied number is outside of ASCII character range.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn ord(params: &[Value]) -> NativeResult {
@@ -62,10 +95,10 @@ pub fn ord(params: &[Value]) -> NativeResult {
                     char.chars().next().unwrap_or('\0') as u8,
                 )))
             } else {
-                Err(NativeError::from("character is out of ASCII range"))
+                Err(NativeError::keyed("err.string.ord_range", []))
             }
         }
-        [Value::String(_)] => Err(NativeError::from("string is too long")),
+        [Value::String(_)] => Err(NativeError::keyed("err.string.ord_length", [])),
         [_] => Err(NativeError::WrongParameterType),
         _ => Err(NativeError::WrongParameterCount(1)),
     }
@@ -105,11 +138,16 @@ pub fn uppercase(params: &[Value]) -> NativeResult {
 
 /// Compares two [`Value::String`] by text content.
 ///
-/// * Declaration: `same_text(left: String, right: String): Boolean`
+/// * Declaration: `same_text(left: String, right: String, normalize: Boolean = false): Boolean`
 ///
 /// # Remarks
 ///
-/// Comparison is made by comparing the lowercase values.
+/// Comparison is made by comparing the lowercase values. If `normalize` is `true`,
+/// both values are also converted to the Unicode normalization form NFC, so a
+/// composed `é` equals an `e` followed by a combining accent.
+/// The `normalize` parameter requires the `unicode` feature.
+///
+/// Unlike `same_text`, the `=` operator always compares the exact codepoints.
 ///
 /// # Errors
 ///
@@ -120,6 +158,125 @@ pub fn same_text(params: &[Value]) -> NativeResult {
         [Value::String(left), Value::String(right)] => {
             Ok(Value::Boolean(left.to_lowercase() == right.to_lowercase()))
         }
+        #[cfg(feature = "unicode")]
+        [Value::String(left), Value::String(right), Value::Boolean(normalize)] => {
+            if *normalize {
+                let left = left.to_lowercase().nfc().collect::<String>();
+                let right = right.to_lowercase().nfc().collect::<String>();
+
+                Ok(Value::Boolean(left == right))
+            } else {
+                Ok(Value::Boolean(left.to_lowercase() == right.to_lowercase()))
+            }
+        }
+        #[cfg(feature = "unicode")]
+        [_, _, _] => Err(NativeError::WrongParameterType),
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Converts a [`Value::String`] to the Unicode normalization form `NFC` (composed)
+/// or `NFD` (decomposed). The form is *case-insensitive*.
+///
+/// * Declaration: `normalize(text: String, form: String = 'NFC'): String`
+///
+/// # Remarks
+///
+/// Requires the `unicode` feature, which covers all scripts of the Unicode standard.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the form is neither `NFC` nor `NFD`.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+#[cfg(feature = "unicode")]
+pub fn normalize(params: &[Value]) -> NativeResult {
+    let form = super::default_string(params, 1, "NFC")?;
+
+    match params {
+        [Value::String(text), ..] => {
+            if form.eq_ignore_ascii_case("NFC") {
+                Ok(Value::String(text.nfc().collect()))
+            } else if form.eq_ignore_ascii_case("NFD") {
+                Ok(Value::String(text.nfd().collect()))
+            } else {
+                Err(NativeError::keyed("err.string.normalize_form", [&form]))
+            }
+        }
+        [_, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// The case and accent insensitive form of a text: lowercase, decomposed with
+/// `NFKD` and without combining marks. The German `ß` is folded to `ss`.
+#[cfg(feature = "unicode")]
+fn folded(text: &str) -> String {
+    text.to_lowercase()
+        .nfkd()
+        .filter(|char| !unicode_normalization::char::is_combining_mark(*char))
+        .fold(String::with_capacity(text.len()), |mut folded, char| {
+            match char {
+                'ß' => folded.push_str("ss"),
+                char => folded.push(char),
+            }
+            folded
+        })
+}
+
+/// Converts a [`Value::String`] into a case and accent insensitive form, to
+/// match text regardless of the locale it was written in.
+///
+/// * Declaration: `fold_text(text: String): String`
+///
+/// # Remarks
+///
+/// The text is converted to lowercase and decomposed with the Unicode
+/// normalization form `NFKD`, all combining marks are removed afterwards.
+/// This turns `'Café'` into `'cafe'` and compatibility characters like the
+/// ligature `ﬁ` into `fi`. The German `ß` is folded to `ss`.
+/// Requires the `unicode` feature.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+#[cfg(feature = "unicode")]
+pub fn fold_text(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(text)] => {
+            let text = folded(text);
+            check_output_len(text.len())?;
+
+            Ok(Value::String(text))
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Compares two [`Value::String`] ignoring case and accents, like comparing
+/// the results of `fold_text`. `'Straße'` equals `'STRASSE'` and `'Café'`
+/// equals `'cafe'`.
+///
+/// * Declaration: `same_text_normalized(left: String, right: String): Boolean`
+///
+/// # Remarks
+///
+/// Requires the `unicode` feature.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+#[cfg(feature = "unicode")]
+pub fn same_text_normalized(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(left), Value::String(right)] => {
+            Ok(Value::Boolean(folded(left) == folded(right)))
+        }
         [_, _] => Err(NativeError::WrongParameterType),
         _ => Err(NativeError::WrongParameterCount(2)),
     }
@@ -133,9 +290,12 @@ pub fn same_text(params: &[Value]) -> NativeResult {
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
 pub fn split(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(line), Value::String(separator)] => {
+            check_output_len(line.split(separator.as_str()).count())?;
+
             let values = line
                 .split(separator)
                 .map(String::from)
@@ -149,6 +309,42 @@ pub fn split(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Joins the members of a [`Value::Array`] into a single [`Value::String`],
+/// placing the separator between each member. The separator defaults to `','`.
+/// Members are converted like the `str` function, [`Value::Null`] becomes an
+/// empty string. Booleans are always written as `true` and `false`.
+///
+/// * Declaration: `join(values: Array, separator: String = ','): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn join(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::Array(values), ..] => {
+            let separator = default_string(params, 1, ",")?;
+            let members: Vec<String> = values
+                .iter()
+                .map(|value| match value {
+                    Value::Null => String::new(),
+                    value => value.to_string(),
+                })
+                .collect();
+
+            check_output_len(
+                members.iter().map(String::len).sum::<usize>()
+                    + separator.len() * members.len().saturating_sub(1),
+            )?;
+
+            Ok(Value::String(members.join(separator)))
+        }
+        [_, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
 fn char_from_value(value: &Value) -> Option<char> {
     match value {
         Value::String(string) if string.len() == 1 => string.chars().next(),
@@ -156,27 +352,71 @@ fn char_from_value(value: &Value) -> Option<char> {
     }
 }
 
-fn parse_csv(line: &str, separator: char) -> Vec<String> {
+/// Counts the fields [`parse_fields`] returns without `collapse`, i.e. the
+/// delimiters outside of a quoted span plus one. A doubled quote toggles the
+/// quoted span twice, so it needs no special handling.
+fn count_fields(line: &str, is_delimiter: impl Fn(char) -> bool, quote: Option<char>) -> usize {
+    let mut in_quotes = false;
+
+    line.chars().fold(1, |count, c| {
+        if Some(c) == quote {
+            in_quotes = !in_quotes;
+            count
+        } else if is_delimiter(c) && !in_quotes {
+            count + 1
+        } else {
+            count
+        }
+    })
+}
+
+/// Splits a line into fields at every delimiter outside of a quoted span.
+/// A doubled quote inside a quoted span is an escaped quote, an unterminated
+/// quote extends to the end of the line.
+///
+/// If `collapse` is set, runs of delimiters separate only once and empty fields
+/// are dropped, unless they are quoted (e.g. `""`).
+fn parse_fields(
+    line: &str,
+    is_delimiter: impl Fn(char) -> bool,
+    quote: Option<char>,
+    collapse: bool,
+) -> Vec<String> {
     let mut result = Vec::new();
     let mut field = String::new();
+    let mut quoted = false; // the field contains a quoted span
     let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
 
-    for c in line.chars() {
-        if c == separator && !in_quotes {
-            result.push(field.clone());
-            field.clear();
-        } else if c == '"' {
-            in_quotes = !in_quotes;
+    while let Some(c) = chars.next() {
+        if Some(c) == quote {
+            if in_quotes && chars.peek() == Some(&c) {
+                field.push(c); // doubled quote
+                chars.next();
+            } else {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+        } else if is_delimiter(c) && !in_quotes {
+            if !collapse || quoted || !field.is_empty() {
+                result.push(std::mem::take(&mut field));
+            }
+            quoted = false;
         } else {
             field.push(c);
         }
     }
 
-    result.push(field);
+    if !collapse || quoted || !field.is_empty() {
+        result.push(field);
+    }
+
     result
 }
 
 /// Splits a csv [`Value::String`] into a [`Value::Array`].
+/// Separators inside double quotes are part of the field, a doubled double
+/// quote is an escaped double quote.
 ///
 /// * Declaration: `split_csv(line: String, separator: String = ';'): Array<String>`
 ///
@@ -184,12 +424,15 @@ fn parse_csv(line: &str, separator: char) -> Vec<String> {
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
 pub fn split_csv(params: &[Value]) -> NativeResult {
     let separator = params.get(1).and_then(char_from_value).unwrap_or(';');
 
     match params {
         [Value::String(line), ..] => {
-            let values = parse_csv(line, separator)
+            check_output_len(count_fields(line, |c| c == separator, Some('"')))?;
+
+            let values = parse_fields(line, |c| c == separator, Some('"'), false)
                 .into_iter()
                 .map(Value::String)
                 .collect();
@@ -200,6 +443,171 @@ pub fn split_csv(params: &[Value]) -> NativeResult {
     }
 }
 
+/// Splits a [`Value::String`] into a [`Value::Array`] of tokens. Any character
+/// of `delimiters` separates two tokens, runs of delimiters separate only once
+/// and never produce empty tokens.
+///
+/// Delimiters inside a span enclosed by the `quote` character are part of the
+/// token, a doubled quote inside the span is an escaped quote. An empty `quote`
+/// disables quoting.
+///
+/// * Declaration: `tokenize(text: String, delimiters: String = ' \t\n', quote: String = '"'): Array<String>`
+///
+/// # Remarks
+///
+/// An unterminated quote extends the last token to the end of the text.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type
+/// or the quote is longer than one character.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn tokenize(params: &[Value]) -> NativeResult {
+    let delimiters = default_string(params, 1, " \t\n")?;
+    let mut quote = default_string(params, 2, "\"")?.chars();
+
+    let quote = match (quote.next(), quote.next()) {
+        (quote, None) => quote,
+        _ => return Err(NativeError::WrongParameterType),
+    };
+
+    match params {
+        [Value::String(text), ..] => {
+            // runs of delimiters are counted as well, which overestimates the tokens
+            check_output_len(count_fields(text, |c| delimiters.contains(c), quote))?;
+
+            let values = parse_fields(text, |c| delimiters.contains(c), quote, true)
+                .into_iter()
+                .map(Value::String)
+                .collect();
+            Ok(Value::Array(values))
+        }
+        [_, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Splits a [`Value::String`] into a [`Value::Array`] of its words, separated by
+/// runs of Unicode whitespace.
+///
+/// * Declaration: `words(text: String): Array<String>`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn words(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(text)] => {
+            check_output_len(text.split_whitespace().count())?;
+
+            let values = text
+                .split_whitespace()
+                .map(String::from)
+                .map(Value::String)
+                .collect();
+
+            Ok(Value::Array(values))
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// A part of a `format` template.
+enum Piece<'a> {
+    Text(&'a str),
+    Argument(usize),
+}
+
+/// Splits a `format` template into texts and the placeholders of the `count`
+/// arguments, other braces are kept as text.
+fn pieces(template: &str, count: usize) -> Vec<Piece<'_>> {
+    let mut pieces = vec![];
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        pieces.push(Piece::Text(&rest[..open]));
+        let after = &rest[open + 1..];
+
+        if let Some(escaped) = after.strip_prefix('{') {
+            pieces.push(Piece::Text("{"));
+            rest = escaped;
+            continue;
+        }
+
+        let digits = after
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after.len());
+
+        match after[..digits].parse::<usize>() {
+            Ok(index) if index < count && after[digits..].starts_with('}') => {
+                pieces.push(Piece::Argument(index));
+                rest = &after[digits + 1..];
+            }
+            _ => {
+                pieces.push(Piece::Text("{"));
+                rest = after;
+            }
+        }
+    }
+
+    pieces.push(Piece::Text(rest));
+    pieces
+}
+
+/// Substitutes the placeholders `{0}`, `{1}`, ... of a template with the
+/// following parameters, e.g. `format('Order {0} total {1}', 7, 12.5)`.
+///
+/// * Declaration: `format(template: String, ...): String`
+///
+/// # Remarks
+///
+/// The parameters are converted like [`str`](super::common::str), `null` becomes
+/// an empty string. A placeholder may be repeated, a placeholder without a
+/// matching parameter is kept untouched. `{{` is an escaped `{`, a single `}`
+/// needs no escaping.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the template is not a [`Value::String`].
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn format(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(template), arguments @ ..] => {
+            let arguments: Vec<String> = arguments
+                .iter()
+                .map(|value| match value {
+                    Value::Null => String::new(),
+                    value => value.to_string(),
+                })
+                .collect();
+
+            let pieces = pieces(template, arguments.len());
+            let piece = |piece: &Piece<'_>| match piece {
+                Piece::Text(text) => text.len(),
+                Piece::Argument(index) => arguments[*index].len(),
+            };
+            check_output_len(pieces.iter().map(piece).sum())?;
+
+            let mut output = String::new();
+            for piece in pieces {
+                match piece {
+                    Piece::Text(text) => output.push_str(text),
+                    Piece::Argument(index) => output.push_str(&arguments[index]),
+                }
+            }
+
+            Ok(Value::String(output))
+        }
+        [_, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
 /// Trims the whitespace of a [`Value::String`] on both sides.
 ///
 /// * Declaration: `trim(text: String): String`
@@ -248,77 +656,452 @@ pub fn trim_right(params: &[Value]) -> NativeResult {
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::Value;
-
-    #[test]
-    fn string_ord() {
-        assert_eq!(
-            Ok(Value::Number(97.0)),
-            ord(&vec![Value::String(String::from("a"))])
-        );
+/// Reads the parameters of the padding functions and returns the text, the
+/// number of missing characters and the padding character.
+fn padding(params: &[Value]) -> Result<(&str, usize, char), NativeError> {
+    match params {
+        [Value::String(text), Value::Number(width), ..] => {
+            let mut pad = default_string(params, 2, " ")?.chars();
+            let (Some(pad), None) = (pad.next(), pad.next()) else {
+                return Err(NativeError::WrongParameterType);
+            };
+            let missing = usize_from_f64(*width).saturating_sub(text.chars().count());
 
-        assert_eq!(
-            Ok(Value::Number(13.0)),
-            ord(&vec![Value::String(String::from("\r"))])
-        );
-        assert_eq!(
-            Ok(Value::Number(10.0)),
-            ord(&vec![Value::String(String::from("\n"))])
-        );
+            check_output_len(
+                text.len()
+                    .saturating_add(missing.saturating_mul(pad.len_utf8())),
+            )?;
 
-        assert!(ord(&vec![Value::String(String::from("Hello World"))]).is_err());
-        assert!(ord(&vec![Value::String(String::from("🙄"))]).is_err());
+            Ok((text, missing, pad))
+        }
+        [_, _, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
     }
+}
 
-    #[test]
-    fn string_chr() {
-        assert_eq!(
-            Ok(Value::String(String::from("a"))),
-            chr(&vec![Value::Number(97.0)])
-        );
+/// Pads a [`Value::String`] on the left side up to a width in characters.
+/// The text is returned unchanged if it is already as long as the width.
+///
+/// * Declaration: `pad_left(text: String, width: Number, pad: String = ' '): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type
+/// or the padding is not a single character.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn pad_left(params: &[Value]) -> NativeResult {
+    let (text, missing, pad) = padding(params)?;
+    let padding = pad.to_string().repeat(missing);
 
-        assert_eq!(
-            Ok(Value::String(String::from("\0"))),
-            chr(&vec![Value::Number(0.0)])
-        );
+    Ok(Value::String(padding + text))
+}
 
-        assert!(chr(&vec![Value::Number(256.0)]).is_err());
-    }
+/// Pads a [`Value::String`] on the right side up to a width in characters.
+/// The text is returned unchanged if it is already as long as the width.
+///
+/// * Declaration: `pad_right(text: String, width: Number, pad: String = ' '): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type
+/// or the padding is not a single character.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn pad_right(params: &[Value]) -> NativeResult {
+    let (text, missing, pad) = padding(params)?;
+    let padding = pad.to_string().repeat(missing);
 
-    #[test]
-    fn string_lowercase() {
-        assert_eq!(
-            Ok(Value::String(String::from("hello world"))),
-            lowercase(&vec![Value::String(String::from("Hello World"))])
-        );
+    Ok(Value::String(text.to_string() + &padding))
+}
 
-        assert!(lowercase(&vec![]).is_err());
-        assert!(lowercase(&vec![Value::Boolean(true)]).is_err());
-    }
+/// Pads a [`Value::String`] on both sides up to a width in characters.
+/// If the padding can not be split evenly, the right side gets one more character.
+///
+/// * Declaration: `center(text: String, width: Number, pad: String = ' '): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type
+/// or the padding is not a single character.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn center(params: &[Value]) -> NativeResult {
+    let (text, missing, pad) = padding(params)?;
+    let left = pad.to_string().repeat(missing / 2);
+    let right = pad.to_string().repeat(missing - missing / 2);
 
-    #[test]
-    fn string_uppercase() {
-        assert_eq!(
-            Ok(Value::String(String::from("HELLO WORLD"))),
-            uppercase(&vec![Value::String(String::from("Hello World"))])
-        );
+    Ok(Value::String(left + text + &right))
+}
 
-        assert!(uppercase(&vec![]).is_err());
-        assert!(uppercase(&vec![Value::Boolean(true)]).is_err());
+/// The Levenshtein distance between two texts in characters, only a single
+/// row of the shorter text is kept in memory.
+fn edit_distance(left: &str, right: &str) -> usize {
+    let (long, short) = if left.chars().count() >= right.chars().count() {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    let short: Vec<char> = short.chars().collect();
+    let mut row: Vec<usize> = (0..=short.len()).collect();
+
+    for (index, long_char) in long.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = index + 1;
+
+        for (column, short_char) in short.iter().enumerate() {
+            let substitution = diagonal + usize::from(long_char != *short_char);
+            diagonal = row[column + 1];
+            row[column + 1] = substitution.min(diagonal + 1).min(row[column] + 1);
+        }
     }
 
-    #[test]
-    fn string_split_csv() {
-        assert_eq!(
-            Ok(Value::Array(vec![
-                Value::String(String::from("Hello; World")),
-                Value::String(String::from("1234")),
-                Value::String(String::from("")),
-                Value::String(String::from("End"))
-            ])),
+    row[short.len()]
+}
+
+/// Returns the Levenshtein distance between two [`Value::String`], the minimum
+/// number of inserted, removed or replaced characters to turn one into the other.
+///
+/// * Declaration: `levenshtein(left: String, right: String): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn levenshtein(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(left), Value::String(right)] => {
+            Ok(Value::Number(f64_from_usize(edit_distance(left, right))))
+        }
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Returns the similarity of two [`Value::String`] between `0` (nothing in common)
+/// and `1` (equal), based on the Levenshtein distance relative to the length of
+/// the longer text. Two empty strings have a similarity of `1`.
+///
+/// * Declaration: `similarity(left: String, right: String): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn similarity(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(left), Value::String(right)] => {
+            let length = left.chars().count().max(right.chars().count());
+
+            if length == 0 {
+                return Ok(Value::Number(1.0));
+            }
+
+            let distance = f64_from_usize(edit_distance(left, right));
+
+            Ok(Value::Number(1.0 - distance / f64_from_usize(length)))
+        }
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Converts decoded bytes back into text, rejecting invalid UTF-8.
+fn utf8_text(bytes: Vec<u8>) -> NativeResult {
+    String::from_utf8(bytes)
+        .map(Value::String)
+        .map_err(|_| NativeError::keyed("err.string.utf8", []))
+}
+
+/// Encodes the UTF-8 bytes of a [`Value::String`] as Base64 with the standard
+/// alphabet and `=` padding (RFC 4648).
+///
+/// * Declaration: `base64_encode(text: String): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn base64_encode(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(text)] => {
+            let length = (text.len() + 2) / 3 * 4;
+            check_output_len(length)?;
+
+            let mut encoded = String::with_capacity(length);
+
+            for chunk in text.as_bytes().chunks(3) {
+                let bytes = [
+                    chunk[0],
+                    *chunk.get(1).unwrap_or(&0),
+                    *chunk.get(2).unwrap_or(&0),
+                ];
+                let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+                for index in 0..4 {
+                    if index <= chunk.len() {
+                        let sextet = (bits >> (18 - 6 * index)) & 0x3F;
+                        encoded.push(char::from(BASE64_ALPHABET[sextet as usize]));
+                    } else {
+                        encoded.push('=');
+                    }
+                }
+            }
+
+            Ok(Value::String(encoded))
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Decodes a Base64 [`Value::String`] with the standard alphabet and `=` padding
+/// (RFC 4648) into text.
+///
+/// * Declaration: `base64_decode(text: String): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the text is not padded Base64 or the
+/// decoded bytes are not valid UTF-8.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+#[allow(clippy::cast_possible_truncation)]
+pub fn base64_decode(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(text)] => {
+            let invalid = || NativeError::keyed("err.string.base64", [text]);
+
+            if text.len() % 4 != 0 {
+                return Err(invalid());
+            }
+
+            let data = text.trim_end_matches('=');
+            if text.len() - data.len() > 2 {
+                return Err(invalid());
+            }
+
+            let mut bytes = Vec::with_capacity(data.len() / 4 * 3 + 2);
+            let mut bits: u32 = 0;
+            let mut count = 0;
+
+            for char in data.bytes() {
+                let sextet = BASE64_ALPHABET
+                    .iter()
+                    .position(|c| *c == char)
+                    .ok_or_else(invalid)?;
+
+                bits = (bits << 6) | sextet as u32;
+                count += 6;
+
+                if count >= 8 {
+                    count -= 8;
+                    bytes.push((bits >> count) as u8);
+                }
+            }
+
+            utf8_text(bytes)
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Percent-encodes the UTF-8 bytes of a [`Value::String`] for the use inside an
+/// URL. All characters except `A-Z`, `a-z`, `0-9`, `-`, `.`, `_` and `~` are
+/// encoded, a space becomes `%20`.
+///
+/// * Declaration: `url_encode(text: String): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+/// Will return [`NativeError::OutputTooLarge`] if the result would exceed the [`max_output_len`](super::max_output_len).
+pub fn url_encode(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(text)] => {
+            let reserved = text
+                .bytes()
+                .filter(|byte| !(byte.is_ascii_alphanumeric() || b"-._~".contains(byte)))
+                .count();
+            check_output_len(text.len() + reserved * 2)?;
+
+            let mut encoded = String::with_capacity(text.len() + reserved * 2);
+
+            for byte in text.bytes() {
+                if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                    encoded.push(char::from(byte));
+                } else {
+                    let _ = write!(encoded, "%{byte:02X}");
+                }
+            }
+
+            Ok(Value::String(encoded))
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Decodes the percent-encoded sequences of a [`Value::String`]. A `+` is kept
+/// as it is and not decoded as a space.
+///
+/// * Declaration: `url_decode(text: String): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if a `%` is not followed by two hexadecimal
+/// digits or the decoded bytes are not valid UTF-8.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn url_decode(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(text)] => {
+            let mut bytes = Vec::with_capacity(text.len());
+            let mut input = text.bytes();
+
+            while let Some(byte) = input.next() {
+                if byte == b'%' {
+                    let digits = [input.next(), input.next()];
+                    let decoded = match digits {
+                        [Some(high), Some(low)] => std::str::from_utf8(&[high, low])
+                            .ok()
+                            .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+                        _ => None,
+                    };
+
+                    bytes
+                        .push(decoded.ok_or_else(|| {
+                            NativeError::keyed("err.string.url_encoding", [text])
+                        })?);
+                } else {
+                    bytes.push(byte);
+                }
+            }
+
+            utf8_text(bytes)
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Checks if a [`Value::String`] starts with the prefix. For a [`Value::Array`]
+/// the first element is compared with the prefix.
+///
+/// * Declaration: `starts_with(text: [String|Array], prefix: [String|Any]): Boolean`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn starts_with(params: &[Value]) -> NativeResult {
+    let found = match params {
+        [Value::String(text), Value::String(prefix)] => text.starts_with(prefix.as_str()),
+        [Value::Array(values), prefix] => values.first() == Some(prefix),
+        [_, _] => return Err(NativeError::WrongParameterType),
+        _ => return Err(NativeError::WrongParameterCount(2)),
+    };
+
+    Ok(Value::Boolean(found))
+}
+
+/// Checks if a [`Value::String`] ends with the suffix. For a [`Value::Array`]
+/// the last element is compared with the suffix.
+///
+/// * Declaration: `ends_with(text: [String|Array], suffix: [String|Any]): Boolean`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn ends_with(params: &[Value]) -> NativeResult {
+    let found = match params {
+        [Value::String(text), Value::String(suffix)] => text.ends_with(suffix.as_str()),
+        [Value::Array(values), suffix] => values.last() == Some(suffix),
+        [_, _] => return Err(NativeError::WrongParameterType),
+        _ => return Err(NativeError::WrongParameterCount(2)),
+    };
+
+    Ok(Value::Boolean(found))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Value;
+
+    #[test]
+    fn string_ord() {
+        assert_eq!(
+            Ok(Value::Number(97.0)),
+            ord(&vec![Value::String(String::from("a"))])
+        );
+
+        assert_eq!(
+            Ok(Value::Number(13.0)),
+            ord(&vec![Value::String(String::from("\r"))])
+        );
+        assert_eq!(
+            Ok(Value::Number(10.0)),
+            ord(&vec![Value::String(String::from("\n"))])
+        );
+
+        assert!(ord(&vec![Value::String(String::from("Hello World"))]).is_err());
+        assert!(ord(&vec![Value::String(String::from("🙄"))]).is_err());
+    }
+
+    #[test]
+    fn string_chr() {
+        assert_eq!(
+            Ok(Value::String(String::from("a"))),
+            chr(&vec![Value::Number(97.0)])
+        );
+
+        assert_eq!(
+            Ok(Value::String(String::from("\0"))),
+            chr(&vec![Value::Number(0.0)])
+        );
+
+        assert!(chr(&vec![Value::Number(256.0)]).is_err());
+    }
+
+    #[test]
+    fn string_lowercase() {
+        assert_eq!(
+            Ok(Value::String(String::from("hello world"))),
+            lowercase(&vec![Value::String(String::from("Hello World"))])
+        );
+
+        assert!(lowercase(&vec![]).is_err());
+        assert!(lowercase(&vec![Value::Boolean(true)]).is_err());
+    }
+
+    #[test]
+    fn string_uppercase() {
+        assert_eq!(
+            Ok(Value::String(String::from("HELLO WORLD"))),
+            uppercase(&vec![Value::String(String::from("Hello World"))])
+        );
+
+        assert!(uppercase(&vec![]).is_err());
+        assert!(uppercase(&vec![Value::Boolean(true)]).is_err());
+    }
+
+    #[test]
+    fn string_split_csv() {
+        assert_eq!(
+            Ok(Value::Array(vec![
+                Value::String(String::from("Hello; World")),
+                Value::String(String::from("1234")),
+                Value::String(String::from("")),
+                Value::String(String::from("End"))
+            ])),
             split_csv(&vec![Value::String(String::from(
                 "\"Hello; World\";1234;;End"
             ))])
@@ -330,6 +1113,158 @@ mod test {
         );
     }
 
+    fn strings(values: &[&str]) -> NativeResult {
+        Ok(Value::Array(
+            values
+                .iter()
+                .map(|value| Value::String(value.to_string()))
+                .collect(),
+        ))
+    }
+
+    fn text(value: &str) -> Value {
+        Value::String(String::from(value))
+    }
+
+    #[test]
+    fn string_split_csv_doubled_quotes() {
+        assert_eq!(
+            strings(&["say \"hi\"", "x"]),
+            split_csv(&[text("\"say \"\"hi\"\"\";x")])
+        );
+        assert_eq!(strings(&["", ""]), split_csv(&[text("\"\";")]));
+    }
+
+    #[test]
+    fn string_tokenize() {
+        assert_eq!(
+            strings(&["a", "b", "c"]),
+            tokenize(&[text("  a \t b\n\nc  ")])
+        );
+        assert_eq!(
+            strings(&["first", "quoted phrase", "last"]),
+            tokenize(&[text("first \"quoted phrase\" last")])
+        );
+        assert_eq!(
+            strings(&["a \"b\"", "c"]),
+            tokenize(&[text("\"a \"\"b\"\"\" c")])
+        );
+        assert_eq!(strings(&["", "x"]), tokenize(&[text("\"\" x")]));
+        assert_eq!(strings(&["ab"]), tokenize(&[text("a\"\"b")]));
+    }
+
+    #[test]
+    fn string_tokenize_delimiters() {
+        assert_eq!(
+            strings(&["a", "b", "c d"]),
+            tokenize(&[text(",a;;b,'c d',"), text(",;"), text("'")])
+        );
+        assert_eq!(strings(&[]), tokenize(&[text(" ,;, "), text(" ,;")]));
+        assert_eq!(strings(&[]), tokenize(&[text("")]));
+
+        // an empty quote disables quoting
+        assert_eq!(
+            strings(&["\"a", "b\""]),
+            tokenize(&[text("\"a b\""), text(" "), text("")])
+        );
+    }
+
+    #[test]
+    fn string_tokenize_unterminated_quote() {
+        assert_eq!(strings(&["a", "b  c "]), tokenize(&[text("a \"b  c ")]));
+    }
+
+    #[test]
+    fn string_tokenize_errors() {
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            tokenize(&[text("a"), text(" "), text("''")])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            tokenize(&[text("a"), Value::Number(1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            tokenize(&[Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), tokenize(&[]));
+    }
+
+    #[test]
+    fn string_format() {
+        let number = Value::Number;
+
+        assert_eq!(
+            Ok(text("Order 7 total 12.5")),
+            format(&[text("Order {0} total {1}"), number(7.0), number(12.5)])
+        );
+        assert_eq!(
+            Ok(text("a-a-b")),
+            format(&[text("{0}-{0}-{1}"), text("a"), text("b")])
+        );
+        assert_eq!(
+            Ok(text("1e21 0.30000000000000004 true ")),
+            format(&[
+                text("{0} {1} {2} {3}"),
+                number(1e21),
+                number(0.1 + 0.2),
+                Value::Boolean(true),
+                Value::Null,
+            ])
+        );
+        assert_eq!(
+            Ok(text("no placeholders")),
+            format(&[text("no placeholders")])
+        );
+    }
+
+    #[test]
+    fn string_format_braces() {
+        assert_eq!(Ok(text("{0} a")), format(&[text("{{0} {0}"), text("a")]));
+        assert_eq!(Ok(text("{a}")), format(&[text("{{{0}}"), text("a")]));
+        assert_eq!(
+            Ok(text("a {1} {x} {} {-1} {0 {")),
+            format(&[text("{0} {1} {x} {} {-1} {0 {"), text("a")])
+        );
+        assert_eq!(
+            Ok(text("{99999999999999999999}")),
+            format(&[text("{99999999999999999999}"), text("a")])
+        );
+        assert_eq!(Ok(text("{0}")), format(&[text("{0}")]));
+        assert_eq!(Ok(text("ü{0}")), format(&[text("ü{0}")]));
+    }
+
+    #[test]
+    fn string_format_errors() {
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            format(&[Value::Number(1.0), text("a")])
+        );
+        assert_eq!(Err(NativeError::WrongParameterType), format(&[Value::Null]));
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), format(&[]));
+    }
+
+    #[test]
+    fn string_words() {
+        assert_eq!(
+            strings(&["one", "two", "three"]),
+            words(&[text("  one two\t\tthree\n")])
+        );
+        assert_eq!(
+            strings(&["non", "breaking", "space"]),
+            words(&[text("non\u{a0}breaking\u{2003}space")])
+        );
+        assert_eq!(strings(&[]), words(&[text(" \t\n\u{a0}")]));
+        assert_eq!(strings(&[]), words(&[text("")]));
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            words(&[Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), words(&[]));
+    }
+
     #[test]
     fn string_split() {
         assert_eq!(
@@ -355,6 +1290,39 @@ mod test {
         );
     }
 
+    #[test]
+    fn string_join() {
+        let values = Value::Array(vec![
+            Value::String(String::from("a")),
+            Value::Number(1.5),
+            Value::Boolean(true),
+            Value::Null,
+        ]);
+        let params = [values, Value::String(String::from(" - "))];
+
+        assert_eq!(
+            Ok(Value::String(String::from("a,1.5,true,"))),
+            join(&params[..1])
+        );
+        assert_eq!(
+            Ok(Value::String(String::from("a - 1.5 - true - "))),
+            join(&params)
+        );
+        assert_eq!(
+            Ok(Value::String(String::new())),
+            join(&[Value::Array(vec![])])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            join(&[Value::String(String::from("a"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            join(&[Value::Array(vec![]), Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), join(&[]));
+    }
+
     #[test]
     fn string_same_text() {
         assert_eq!(
@@ -374,6 +1342,97 @@ mod test {
         );
     }
 
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn string_fold_text() {
+        for (text, expected) in [
+            ("Straße", "strasse"),
+            ("STRASSE", "strasse"),
+            ("Café", "cafe"),
+            ("Cafe\u{301}", "cafe"),
+            ("Crème Brûlée à la française", "creme brulee a la francaise"),
+            ("Ça, déjà, Noël, Œuvre", "ca, deja, noel, œuvre"),
+            ("ﬁnal ①", "final 1"),
+            ("plain ASCII 123!", "plain ascii 123!"),
+            ("", ""),
+        ] {
+            assert_eq!(
+                Ok(Value::String(String::from(expected))),
+                fold_text(&[Value::String(String::from(text))]),
+                "{text}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            fold_text(&[Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), fold_text(&[]));
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn string_same_text_normalized() {
+        for (left, right, expected) in [
+            ("Straße", "STRASSE", true),
+            ("Café", "CAFE", true),
+            ("élève", "Eleve", true),
+            ("Garçon", "garcon", true),
+            ("same", "same", true),
+            ("Straße", "Strase", false),
+            ("cafe", "cafes", false),
+        ] {
+            assert_eq!(
+                Ok(Value::Boolean(expected)),
+                same_text_normalized(&[
+                    Value::String(String::from(left)),
+                    Value::String(String::from(right))
+                ]),
+                "{left} = {right}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            same_text_normalized(&[Value::String(String::from("a")), Value::Null])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(2)),
+            same_text_normalized(&[Value::String(String::from("a"))])
+        );
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn string_normalize() {
+        let composed = Value::String(String::from("Caf\u{e9}"));
+        let decomposed = Value::String(String::from("Cafe\u{301}"));
+
+        assert_eq!(
+            Ok(composed.clone()),
+            normalize(&[decomposed.clone(), Value::String(String::from("NFC"))])
+        );
+        assert_eq!(
+            Ok(decomposed.clone()),
+            normalize(&[composed.clone(), Value::String(String::from("nfd"))])
+        );
+        assert_eq!(
+            Ok(composed.clone()),
+            normalize(std::slice::from_ref(&composed))
+        );
+        assert_eq!(
+            Err(NativeError::Keyed(
+                "err.string.normalize_form",
+                vec![String::from("NFKC")]
+            )),
+            normalize(&[composed, Value::String(String::from("NFKC"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            normalize(&[Value::Number(1.0)])
+        );
+    }
+
     #[test]
     fn string_trim() {
         assert_eq!(
@@ -394,4 +1453,283 @@ mod test {
             trim_right(&vec![Value::String(String::from("  Hello World       "))])
         );
     }
+
+    #[test]
+    fn string_pad() {
+        let text = Value::String(String::from("42"));
+        let zero = Value::String(String::from("0"));
+
+        assert_eq!(
+            Ok(Value::String(String::from("00042"))),
+            pad_left(&[text.clone(), Value::Number(5.0), zero.clone()])
+        );
+        assert_eq!(
+            Ok(Value::String(String::from("42   "))),
+            pad_right(&[text.clone(), Value::Number(5.0)])
+        );
+        assert_eq!(
+            Ok(Value::String(String::from(" 42  "))),
+            center(&[text.clone(), Value::Number(5.0)])
+        );
+        assert_eq!(
+            Ok(Value::String(String::from("42"))),
+            pad_left(&[text.clone(), Value::Number(1.0), zero.clone()])
+        );
+        assert_eq!(
+            Ok(Value::String(String::from("42"))),
+            center(&[text.clone(), Value::Number(-3.0)])
+        );
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            pad_left(&[
+                text.clone(),
+                Value::Number(5.0),
+                Value::String(String::from("ab"))
+            ])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            pad_right(&[
+                text.clone(),
+                Value::Number(5.0),
+                Value::String(String::new())
+            ])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            center(&[text.clone(), Value::Number(5.0), Value::Number(0.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            pad_left(&[Value::Number(42.0), Value::Number(5.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(2)), pad_right(&[text]));
+    }
+
+    #[test]
+    fn string_levenshtein() {
+        for (left, right, expected) in [
+            ("kitten", "sitting", 3.0),
+            ("sitting", "kitten", 3.0),
+            ("flaw", "lawn", 2.0),
+            ("", "abc", 3.0),
+            ("abc", "", 3.0),
+            ("", "", 0.0),
+            ("same", "same", 0.0),
+            ("Straße", "Strasse", 2.0),
+            ("日本語", "日本", 1.0),
+        ] {
+            assert_eq!(
+                Ok(Value::Number(expected)),
+                levenshtein(&[
+                    Value::String(String::from(left)),
+                    Value::String(String::from(right))
+                ]),
+                "{left} {right}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            levenshtein(&[Value::String(String::from("a")), Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(2)), levenshtein(&[]));
+    }
+
+    #[test]
+    fn string_similarity() {
+        for (left, right, expected) in [
+            ("kitten", "sitting", 1.0 - 3.0 / 7.0),
+            ("", "", 1.0),
+            ("", "abc", 0.0),
+            ("abc", "xyz", 0.0),
+            ("abcd", "abcx", 0.75),
+            ("äöü", "äöü", 1.0),
+        ] {
+            assert_eq!(
+                Ok(Value::Number(expected)),
+                similarity(&[
+                    Value::String(String::from(left)),
+                    Value::String(String::from(right))
+                ]),
+                "{left} {right}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            similarity(&[Value::Null, Value::String(String::from("a"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(2)),
+            similarity(&[Value::String(String::from("a"))])
+        );
+    }
+
+    #[test]
+    fn string_base64() {
+        for (text, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foobar", "Zm9vYmFy"),
+            ("Grüße ✓", "R3LDvMOfZSDinJM="),
+        ] {
+            let text = Value::String(String::from(text));
+            let encoded = Value::String(String::from(encoded));
+
+            assert_eq!(
+                Ok(encoded.clone()),
+                base64_encode(std::slice::from_ref(&text))
+            );
+            assert_eq!(Ok(text), base64_decode(&[encoded]));
+        }
+
+        for invalid in ["Zg=", "Zm9v!A==", "Z===", "=Zg=", "Zm=v"] {
+            assert_eq!(
+                Err(NativeError::Keyed(
+                    "err.string.base64",
+                    vec![String::from(invalid)]
+                )),
+                base64_decode(&[Value::String(String::from(invalid))]),
+                "{invalid}"
+            );
+        }
+
+        // 0xFF is not valid UTF-8
+        assert_eq!(
+            Err(NativeError::Keyed("err.string.utf8", vec![])),
+            base64_decode(&[Value::String(String::from("/w=="))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            base64_encode(&[Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), base64_decode(&[]));
+    }
+
+    #[test]
+    fn string_url_encoding() {
+        for (text, encoded) in [
+            ("", ""),
+            ("AZaz09-._~", "AZaz09-._~"),
+            ("a b&c=d/e?", "a%20b%26c%3Dd%2Fe%3F"),
+            ("Grüße", "Gr%C3%BC%C3%9Fe"),
+            ("100%", "100%25"),
+        ] {
+            let text = Value::String(String::from(text));
+            let encoded = Value::String(String::from(encoded));
+
+            assert_eq!(Ok(encoded.clone()), url_encode(std::slice::from_ref(&text)));
+            assert_eq!(Ok(text), url_decode(&[encoded]));
+        }
+
+        assert_eq!(
+            Ok(Value::String(String::from("a+b é"))),
+            url_decode(&[Value::String(String::from("a+b%20%c3%a9"))])
+        );
+
+        for invalid in ["%", "%4", "%G0", "a%2"] {
+            assert_eq!(
+                Err(NativeError::Keyed(
+                    "err.string.url_encoding",
+                    vec![String::from(invalid)]
+                )),
+                url_decode(&[Value::String(String::from(invalid))]),
+                "{invalid}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::Keyed("err.string.utf8", vec![])),
+            url_decode(&[Value::String(String::from("%C3"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            url_encode(&[Value::Null])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), url_decode(&[]));
+    }
+
+    #[test]
+    fn string_starts_ends_with() {
+        let iban = Value::String(String::from("DE89370400440532013000"));
+        let values = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]);
+        let empty = Value::Array(vec![]);
+
+        for (expected, function, params) in [
+            (
+                true,
+                starts_with as fn(&[Value]) -> NativeResult,
+                [iban.clone(), Value::String(String::from("DE"))],
+            ),
+            (
+                false,
+                starts_with,
+                [iban.clone(), Value::String(String::from("AT"))],
+            ),
+            (
+                true,
+                ends_with,
+                [iban.clone(), Value::String(String::from("3000"))],
+            ),
+            (
+                true,
+                ends_with,
+                [iban.clone(), Value::String(String::new())],
+            ),
+            (true, starts_with, [values.clone(), Value::Number(1.0)]),
+            (false, starts_with, [values.clone(), Value::Number(2.0)]),
+            (true, ends_with, [values.clone(), Value::Number(2.0)]),
+            (false, ends_with, [values.clone(), Value::Number(1.0)]),
+            (false, starts_with, [empty.clone(), Value::Null]),
+            (false, ends_with, [empty.clone(), Value::Number(1.0)]),
+        ] {
+            assert_eq!(Ok(Value::Boolean(expected)), function(&params));
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            starts_with(&[iban.clone(), Value::Number(1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            ends_with(&[Value::Number(1.0), Value::String(String::from("1"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(2)),
+            starts_with(&[iban])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(2)), ends_with(&[]));
+    }
+
+    #[test]
+    fn string_pad_unicode() {
+        assert_eq!(
+            Ok(Value::String(String::from("··äöü"))),
+            pad_left(&[
+                Value::String(String::from("äöü")),
+                Value::Number(5.0),
+                Value::String(String::from("·"))
+            ])
+        );
+        assert_eq!(
+            Ok(Value::String(String::from("日本★★"))),
+            pad_right(&[
+                Value::String(String::from("日本")),
+                Value::Number(4.0),
+                Value::String(String::from("★"))
+            ])
+        );
+        assert_eq!(
+            Ok(Value::String(String::from("€€é€€€"))),
+            center(&[
+                Value::String(String::from("é")),
+                Value::Number(6.0),
+                Value::String(String::from("€"))
+            ])
+        );
+    }
 }
@@ -6,7 +6,8 @@
 //!
 //! All functions operate on a local timezone. (eg: 12:00 Noon = 0.5)
 //! The RFC-functions do automatic conversion from the provided offset into the
-//! systems local timezone.
+//! systems local timezone. Their `_utc` and `_tz` variants as well as `convert_tz`
+//! use explicit offsets instead and return the same results on every host.
 //!
 //! The integral part of the [`Value::Number`] float is the number of **days**,
 //! which have passed since `midnight, January 1, 1970` (aka. the UNIX timestamp).
@@ -27,21 +28,25 @@
 //! Likewise the calculation of a timespan is a simple fraction of a day.
 //!
 //! ```slac
-//! encode_time(12, 30, 00) + (1 / 24) <= now // valid from 13:30:00
+//! encode_time(12, 30, 00) + (1 / 24) <= time(now()) // valid from 13:30:00
 //! ```
 //!
+//! The current moment is read from the local clock by `now` and `today`.
+//! Tests and replays fix it with [`extend_environment_with_clock`].
+//!
 //! # Chrono
 //!
 //! This module uses the [`chrono`] crate and can be included using
 //! the `chrono` feature.
 use chrono::{
-    DateTime, Datelike, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
-    Timelike,
+    DateTime, Datelike, Days, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime,
+    NaiveTime, TimeZone, Timelike, Weekday,
 };
 
 use crate::{
+    chrono_interop::{MILLISECONDS_PER_DAY, SECONDS_PER_DAY},
     function::{Arity, Function},
-    Value,
+    StaticEnvironment, Value,
 };
 
 use super::{
@@ -58,16 +63,34 @@ pub fn functions() -> Vec<Function> {
         Function::new(date_to_string, Arity::required(2), "date_to_string(fmt: String, datetime: Number): String"),
         Function::new(date_to_string, Arity::required(2), "time_to_string(fmt: String, datetime: Number): String"),
         Function::new(string_to_date, Arity::optional(1, 1), "string_to_date(date: String, format: String = '%Y-%m-%d'): Number"),
+        Function::new(string_to_week_date, Arity::required(1), "string_to_week_date(date: String): Number"),
+        Function::new(string_to_ordinal_date, Arity::required(1), "string_to_ordinal_date(date: String): Number"),
+        Function::new(parse_date_any, Arity::required(1), "parse_date_any(text: String): Number"),
         Function::new(string_to_time, Arity::optional(1, 1), "string_to_time(time: String, format: String = '%H:%M:%S'): Number"),
         Function::new(string_to_datetime, Arity::optional(1, 1), "string_to_datetime(datetime: String, format: String = '%Y-%m-%d %H:%M:%S'): Number"),
+        Function::new(string_to_datetime, Arity::optional(1, 1), "string_to_date_time(datetime: String, format: String = '%Y-%m-%d %H:%M:%S'): Number").alias_for("string_to_datetime"),
         Function::new(date_from_rfc2822, Arity::required(1), "date_from_rfc2822(datetime: String): Number"),
         Function::new(date_from_rfc3339, Arity::required(1), "date_from_rfc3339(datetime: String): Number"),
         Function::new(date_to_rfc2822, Arity::required(1), "date_to_rfc2822(datetime: Number): String"),
         Function::new(date_to_rfc3339, Arity::required(1), "date_to_rfc3339(datetime: Number): String"),
-        Function::new(day_of_week, Arity::required(1), "day_of_week(datetime: Number): Number"),
+        Function::new(date_from_rfc2822_utc, Arity::required(1), "date_from_rfc2822_utc(datetime: String): Number"),
+        Function::new(date_from_rfc3339_utc, Arity::required(1), "date_from_rfc3339_utc(datetime: String): Number"),
+        Function::new(date_to_rfc2822_tz, Arity::required(2), "date_to_rfc2822_tz(datetime: Number, offset_minutes: Number): String"),
+        Function::new(date_to_rfc3339_tz, Arity::required(2), "date_to_rfc3339_tz(datetime: Number, offset_minutes: Number): String"),
+        Function::new(convert_tz, Arity::required(3), "convert_tz(datetime: Number, from_offset_minutes: Number, to_offset_minutes: Number): Number"),
+        Function::new(day_of_week, Arity::optional(1, 1), "day_of_week(datetime: Number, mode: String = 'zero'): Number"),
         Function::new(encode_date, Arity::required(3), "encode_date(year: Number, month: Number, day: Number): Number"),
         Function::new(encode_time, Arity::optional(3, 1), "encode_time(hour: Number, minute: Number, second: Number, millisecond: Number = 0): Number"),
         Function::new(inc_month, Arity::optional(1, 1), "inc_month(datetime: Number, increment: Number = 1): Number"),
+        Function::new(add_days, Arity::required(2), "add_days(datetime: Number, days: Number): Number"),
+        Function::new(add_years, Arity::required(2), "add_years(datetime: Number, years: Number): Number"),
+        Function::new(days_between, Arity::required(2), "days_between(a: Number, b: Number): Number"),
+        Function::new(start_of_month, Arity::required(1), "start_of_month(datetime: Number): Number"),
+        Function::new(end_of_month, Arity::required(1), "end_of_month(datetime: Number): Number"),
+        Function::new(week_of_year, Arity::required(1), "week_of_year(datetime: Number): Number"),
+        Function::new(week_of_year, Arity::required(1), "iso_week(datetime: Number): Number"),
+        Function::new(iso_week_year, Arity::required(1), "iso_week_year(datetime: Number): Number"),
+        Function::new(quarter, Arity::required(1), "quarter(datetime: Number): Number"),
         Function::new(is_leap_year, Arity::required(1), "is_leap_year(datetime: Number): Number"),
         Function::new(year, Arity::required(1), "year(datetime: Number): Number"),
         Function::new(month, Arity::required(1), "month(datetime: Number): Number"),
@@ -76,36 +99,85 @@ pub fn functions() -> Vec<Function> {
         Function::new(minute, Arity::required(1), "minute(datetime: Number): Number"),
         Function::new(second, Arity::required(1), "second(datetime: Number): Number"),
         Function::new(millisecond, Arity::required(1), "millisecond(datetime: Number): Number"),
+        Function::new(from_unix, Arity::required(1), "from_unix(seconds: Number): Number"),
+        Function::new(to_unix, Arity::required(1), "to_unix(datetime: Number): Number"),
+        Function::new(from_unix_ms, Arity::required(1), "from_unix_ms(milliseconds: Number): Number"),
+        Function::new(to_unix_ms, Arity::required(1), "to_unix_ms(datetime: Number): Number"),
+        Function::new(trunc_to, Arity::required(2), "trunc_to(datetime: Number, unit: String): Number"),
+        Function::new(round_to_unit, Arity::required(2), "round_to_unit(datetime: Number, unit: String): Number"),
+        Function::new(same_moment, Arity::optional(2, 1), "same_moment(a: Number, b: Number, tolerance_seconds: Number = 1): Boolean"),
+        Function::new(format_duration, Arity::optional(1, 1), "format_duration(days: Number, fmt: String = '%H:%M:%S'): String"),
+        Function::new(parse_duration, Arity::required(1), "parse_duration(text: String): Number"),
+        Function::impure(now, Arity::None, "now(): Number"),
+        Function::impure(today, Arity::None, "today(): Number"),
     ]
 }
 
-const MILLISECONDS_PER_DAY: f64 = 24. * 60. * 60. * 1000.;
-
-impl TryFrom<&Value> for NaiveDateTime {
-    type Error = NativeError;
-
-    #[allow(clippy::cast_possible_truncation)]
-    fn try_from(value: &Value) -> Result<Self, Self::Error> {
-        match value {
-            Value::Number(value) => {
-                let milliseconds = (value * MILLISECONDS_PER_DAY) as i64;
+/// Extends a [`StaticEnvironment`] like [`super::extend_environment`], but
+/// `now` and `today` return a fixed `clock` instead of the local time. Unit
+/// tests and replayed audits evaluate time dependent expressions the same way
+/// on every run.
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use slac::{compile, execute, stdlib::time::extend_environment_with_clock, StaticEnvironment, Value};
+///
+/// let clock = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap().and_hms_opt(18, 0, 0).unwrap();
+/// let mut env = StaticEnvironment::default();
+/// extend_environment_with_clock(&mut env, clock);
+///
+/// let ast = compile("today() = encode_date(2024, 2, 29) and hour(now()) = 18").unwrap();
+/// assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+/// ```
+pub fn extend_environment_with_clock(env: &mut StaticEnvironment, clock: NaiveDateTime) {
+    super::extend_environment(env);
+    env.set_clock(clock);
+}
 
-                DateTime::from_timestamp_millis(milliseconds)
-                    .map(|dt| dt.naive_utc())
-                    .ok_or(NativeError::from("datetime out of range"))
-            }
-            _ => Err(NativeError::WrongParameterType),
-        }
+/// Returns the `clock` as datetime [`Value`], see [`now`].
+pub(crate) fn now_at(clock: NaiveDateTime, params: &[Value]) -> NativeResult {
+    match params {
+        [] => Ok(Value::from(clock)),
+        _ => Err(NativeError::WrongParameterCount(0)),
     }
 }
 
-impl From<NaiveDateTime> for Value {
-    #[allow(clippy::cast_precision_loss)]
-    fn from(val: NaiveDateTime) -> Self {
-        let milliseconds = val.and_utc().timestamp_millis();
+/// Returns the date of the `clock` as [`Value`], see [`today`].
+pub(crate) fn today_at(clock: NaiveDateTime, params: &[Value]) -> NativeResult {
+    now_at(clock.date().and_time(NaiveTime::MIN), params)
+}
 
-        Value::Number(milliseconds as f64 / MILLISECONDS_PER_DAY)
-    }
+/// Returns the current local date and time.
+///
+/// * Declaration: `now(): Number`
+///
+/// # Remarks
+///
+/// The function is impure and never evaluated by the optimizer. See
+/// [`extend_environment_with_clock`] to use a fixed clock.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+pub fn now(params: &[Value]) -> NativeResult {
+    now_at(Local::now().naive_local(), params)
+}
+
+/// Returns the current local date without the time of day.
+///
+/// * Declaration: `today(): Number`
+///
+/// # Remarks
+///
+/// The function is impure and never evaluated by the optimizer. See
+/// [`extend_environment_with_clock`] to use a fixed clock.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+pub fn today(params: &[Value]) -> NativeResult {
+    today_at(Local::now().naive_local(), params)
 }
 
 /// Formats a datetime [`Value`] with the specified format string.
@@ -136,7 +208,7 @@ pub fn date_to_string(params: &[Value]) -> NativeResult {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the String can not be parsed.
+/// Will return [`NativeError::Keyed`] if the String can not be parsed.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn string_to_date(params: &[Value]) -> NativeResult {
@@ -145,7 +217,7 @@ pub fn string_to_date(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(s), ..] => {
             let datetime = NaiveDate::parse_from_str(s, fmt)
-                .map_err(|e| NativeError::from(e.to_string()))?
+                .map_err(|e| NativeError::keyed("err.datetime.parse", [s, &fmt, &e]))?
                 .and_time(NaiveTime::default());
 
             Ok(Value::from(datetime))
@@ -155,6 +227,103 @@ pub fn string_to_date(params: &[Value]) -> NativeResult {
     }
 }
 
+/// The formats tried by [`parse_date_any`] in order, as `(name, format)`.
+const LENIENT_DATE_FORMATS: [(&str, &str); 4] = [
+    ("ISO 8601 extended", "%Y-%m-%d"),
+    ("ISO 8601 basic", "%Y%m%d"),
+    ("ISO 8601 week date", "%G-W%V-%u"),
+    ("ISO 8601 ordinal date", "%Y-%j"),
+];
+
+fn date_value(date: NaiveDate) -> Value {
+    Value::from(date.and_time(NaiveTime::default()))
+}
+
+fn parse_date(params: &[Value], fmt: &str) -> NativeResult {
+    match params {
+        [Value::String(s)] => NaiveDate::parse_from_str(s, fmt)
+            .map(date_value)
+            .map_err(|e| NativeError::keyed("err.datetime.parse", [s, &fmt, &e])),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Parses an [ISO 8601 week date](https://en.wikipedia.org/wiki/ISO_week_date)
+/// string (e.g: `2023-W34-7`) and returns a [`Value::Number`].
+///
+/// * Declaration: `string_to_week_date(date: String): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the String can not be parsed.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn string_to_week_date(params: &[Value]) -> NativeResult {
+    parse_date(params, "%G-W%V-%u")
+}
+
+/// Parses an [ISO 8601 ordinal date](https://en.wikipedia.org/wiki/ISO_8601#Ordinal_dates)
+/// string (e.g: `2023-239`) and returns a [`Value::Number`].
+///
+/// * Declaration: `string_to_ordinal_date(date: String): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the String can not be parsed.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn string_to_ordinal_date(params: &[Value]) -> NativeResult {
+    parse_date(params, "%Y-%j")
+}
+
+/// Parses a date string in one of several unambiguous formats and returns a [`Value::Number`].
+///
+/// * Declaration: `parse_date_any(text: String): Number`
+///
+/// # Remarks
+///
+/// The formats are tried in the following order and the first match is returned:
+/// 1. ISO 8601 extended: `2023-08-27`
+/// 2. ISO 8601 basic: `20230827`
+/// 3. ISO 8601 week date: `2023-W34-7`
+/// 4. ISO 8601 ordinal date: `2023-239`
+/// 5. RFC 3339 timestamp: `2023-08-27T10:30:00+02:00`, only the date in its own offset is used
+///
+/// Locale dependent formats like `8/27/23` are not supported, since the order
+/// of day and month can not be determined from the text alone (`8/7/23` is
+/// either August 7th or July 8th). Use `string_to_date` with an explicit format.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] listing all attempted formats if the String can not be parsed.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn parse_date_any(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(s)] => LENIENT_DATE_FORMATS
+            .iter()
+            .find_map(|(_, fmt)| NaiveDate::parse_from_str(s, fmt).ok())
+            .or_else(|| {
+                DateTime::parse_from_rfc3339(s)
+                    .ok()
+                    .map(|datetime| datetime.date_naive())
+            })
+            .map(date_value)
+            .ok_or_else(|| {
+                let formats: Vec<String> = LENIENT_DATE_FORMATS
+                    .iter()
+                    .map(|(name, fmt)| format!("{name} ({fmt})"))
+                    .chain([String::from("RFC 3339")])
+                    .collect();
+
+                NativeError::keyed("err.datetime.parse_any", [s, &formats.join(", ")])
+            }),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
 /// Parses a time string with the specified format string and returns a [`Value::Number`].
 /// See [`chrono::format::strftime`] for info on the syntax.
 ///
@@ -162,7 +331,7 @@ pub fn string_to_date(params: &[Value]) -> NativeResult {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the String can not be parsed.
+/// Will return [`NativeError::Keyed`] if the String can not be parsed.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 #[allow(clippy::module_name_repetitions, clippy::cast_possible_truncation)]
@@ -171,8 +340,8 @@ pub fn string_to_time(params: &[Value]) -> NativeResult {
 
     match params {
         [Value::String(s), ..] => {
-            let time =
-                NaiveTime::parse_from_str(s, fmt).map_err(|e| NativeError::from(e.to_string()))?;
+            let time = NaiveTime::parse_from_str(s, fmt)
+                .map_err(|e| NativeError::keyed("err.datetime.parse", [s, &fmt, &e]))?;
             let datetime = NaiveDate::default().and_time(time);
 
             Ok(Value::from(datetime))
@@ -197,7 +366,7 @@ pub fn string_to_datetime(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(s), ..] => {
             let datetime = NaiveDateTime::parse_from_str(s, fmt)
-                .map_err(|e| NativeError::from(e.to_string()))?;
+                .map_err(|e| NativeError::keyed("err.datetime.parse", [s, &fmt, &e]))?;
 
             Ok(Value::from(datetime))
         }
@@ -211,7 +380,7 @@ fn naive_to_fixed(datetime: NaiveDateTime) -> Result<DateTime<FixedOffset>, Nati
         .from_local_datetime(&datetime)
         .single()
         .map(|datetime| datetime.fixed_offset())
-        .ok_or(NativeError::from("invalid datetime value"))
+        .ok_or(NativeError::keyed("err.datetime.local", []))
 }
 
 fn fixed_to_naive(datetime: DateTime<FixedOffset>) -> NaiveDateTime {
@@ -225,14 +394,14 @@ fn fixed_to_naive(datetime: DateTime<FixedOffset>) -> NaiveDateTime {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the String can not be parsed.
+/// Will return [`NativeError::Keyed`] if the String can not be parsed.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn date_from_rfc2822(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(value)] => {
             let datetime = DateTime::parse_from_rfc2822(value)
-                .map_err(|e| NativeError::from(e.to_string()))?;
+                .map_err(|e| NativeError::keyed("err.datetime.parse", [value, &"RFC 2822", &e]))?;
 
             Ok(Value::from(fixed_to_naive(datetime)))
         }
@@ -268,14 +437,14 @@ pub fn date_to_rfc2822(params: &[Value]) -> NativeResult {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if the String can not be parsed.
+/// Will return [`NativeError::Keyed`] if the String can not be parsed.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn date_from_rfc3339(params: &[Value]) -> NativeResult {
     match params {
         [Value::String(value)] => {
             let datetime = DateTime::parse_from_rfc3339(value)
-                .map_err(|e| NativeError::from(e.to_string()))?;
+                .map_err(|e| NativeError::keyed("err.datetime.parse", [value, &"RFC 3339", &e]))?;
 
             Ok(Value::from(fixed_to_naive(datetime)))
         }
@@ -304,20 +473,209 @@ pub fn date_to_rfc3339(params: &[Value]) -> NativeResult {
     }
 }
 
+/// The largest offset from UTC in use, `+14:00` in Kiribati.
+const MAX_OFFSET_MINUTES: f64 = 14.0 * 60.0;
+
+/// Converts an offset in whole minutes east of UTC into a [`FixedOffset`].
+fn offset_from_minutes(minutes: f64) -> Result<FixedOffset, NativeError> {
+    if !(-MAX_OFFSET_MINUTES..=MAX_OFFSET_MINUTES).contains(&minutes) {
+        return Err(NativeError::CustomError(format!(
+            "offset of {minutes} minutes is outside of ±14 hours"
+        )));
+    }
+
+    FixedOffset::east_opt(minutes.trunc() as i32 * 60)
+        .ok_or(NativeError::keyed("err.datetime.range", []))
+}
+
+/// Parses a [RFC 2822](https://www.rfc-editor.org/rfc/rfc2822) string
+/// (e.g: `Fri, 21 Nov 1997 09:55:06 -0600`) and returns the moment in UTC
+/// as a [`Value::Number`].
+///
+/// * Declaration: `date_from_rfc2822_utc(datetime: String): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the String can not be parsed.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn date_from_rfc2822_utc(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(value)] => {
+            let datetime = DateTime::parse_from_rfc2822(value)
+                .map_err(|e| NativeError::keyed("err.datetime.parse", [value, &"RFC 2822", &e]))?;
+
+            Ok(Value::from(datetime.naive_utc()))
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Parses a [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339) [`Value::String`]
+/// (e.g: `1997-11-21T09:55:06.00-06:00`) and returns the moment in UTC
+/// as a [`Value::Number`].
+///
+/// * Declaration: `date_from_rfc3339_utc(datetime: String): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the String can not be parsed.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn date_from_rfc3339_utc(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(value)] => {
+            let datetime = DateTime::parse_from_rfc3339(value)
+                .map_err(|e| NativeError::keyed("err.datetime.parse", [value, &"RFC 3339", &e]))?;
+
+            Ok(Value::from(datetime.naive_utc()))
+        }
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Interprets a datetime [`Value::Number`] as UTC and converts it into a
+/// [RFC 2822](https://www.rfc-editor.org/rfc/rfc2822) [`Value::String`]
+/// in the supplied offset (e.g: `Fri, 21 Nov 1997 03:55:06 -0600`).
+///
+/// * Declaration: `date_to_rfc2822_tz(datetime: Number, offset_minutes: Number): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::CustomError`] if the offset is outside of ±14 hours.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn date_to_rfc2822_tz(params: &[Value]) -> NativeResult {
+    match params {
+        [value, Value::Number(offset)] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+            let offset = offset_from_minutes(*offset)?;
+
+            Ok(Value::String(
+                offset.from_utc_datetime(&datetime).to_rfc2822(),
+            ))
+        }
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Interprets a datetime [`Value::Number`] as UTC and converts it into a
+/// [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339) [`Value::String`]
+/// in the supplied offset (e.g: `1997-11-21T03:55:06-06:00`).
+///
+/// * Declaration: `date_to_rfc3339_tz(datetime: Number, offset_minutes: Number): String`
+///
+/// # Errors
+///
+/// Will return [`NativeError::CustomError`] if the offset is outside of ±14 hours.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn date_to_rfc3339_tz(params: &[Value]) -> NativeResult {
+    match params {
+        [value, Value::Number(offset)] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+            let offset = offset_from_minutes(*offset)?;
+
+            Ok(Value::String(
+                offset.from_utc_datetime(&datetime).to_rfc3339(),
+            ))
+        }
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Converts a datetime [`Value::Number`] from one offset into another, both
+/// given in minutes east of UTC.
+///
+/// * Declaration: `convert_tz(datetime: Number, from_offset_minutes: Number, to_offset_minutes: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::CustomError`] if an offset is outside of ±14 hours.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn convert_tz(params: &[Value]) -> NativeResult {
+    match params {
+        [value, Value::Number(from), Value::Number(to)] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+            let from = offset_from_minutes(*from)?;
+            let to = offset_from_minutes(*to)?;
+
+            from.from_local_datetime(&datetime)
+                .single()
+                .map(|datetime| datetime.with_timezone(&to).naive_local())
+                .map(Value::from)
+                .ok_or(NativeError::keyed("err.datetime.range", []))
+        }
+        [_, _, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(3)),
+    }
+}
+
+/// The numbering schemes of the days of the week.
+#[derive(Clone, Copy)]
+enum WeekdayMode {
+    /// `0` = Monday to `6` = Sunday.
+    Zero,
+    /// ISO 8601: `1` = Monday to `7` = Sunday.
+    Iso,
+    /// Delphi `DayOfWeek`: `1` = Sunday to `7` = Saturday.
+    Delphi,
+}
+
+const WEEKDAY_MODES: &str = "zero, iso, delphi";
+
+impl TryFrom<&str> for WeekdayMode {
+    type Error = NativeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "zero" => Ok(Self::Zero),
+            "iso" => Ok(Self::Iso),
+            "delphi" => Ok(Self::Delphi),
+            _ => Err(NativeError::keyed(
+                "err.datetime.weekday_mode",
+                [&value, &WEEKDAY_MODES],
+            )),
+        }
+    }
+}
+
+impl WeekdayMode {
+    fn number(self, weekday: Weekday) -> u32 {
+        match self {
+            Self::Zero => weekday.num_days_from_monday(),
+            Self::Iso => weekday.number_from_monday(),
+            Self::Delphi => weekday.number_from_sunday(),
+        }
+    }
+}
+
 /// Returns the day of the week for a specified date.
 ///
-/// * Declaration: `day_of_week(datetime: Number): Number`
+/// * Declaration: `day_of_week(datetime: Number, mode: String = 'zero'): Number`
+///
+/// The `mode` selects the numbering: `'zero'` counts from `0` = Monday to
+/// `6` = Sunday, `'iso'` from `1` = Monday to `7` = Sunday and `'delphi'`
+/// from `1` = Sunday to `7` = Saturday.
 ///
 /// # Errors
 ///
+/// Will return [`NativeError::Keyed`] if the mode is unknown.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 pub fn day_of_week(params: &[Value]) -> NativeResult {
+    let mode = WeekdayMode::try_from(default_string(params, 1, "zero")?)?;
+
     match params {
-        [value] => {
+        [value, ..] => {
             let datetime = NaiveDateTime::try_from(value)?;
 
-            Ok(Value::Number(f64::from(datetime.weekday() as u8)))
+            Ok(Value::Number(f64::from(mode.number(datetime.weekday()))))
         }
         _ => Err(NativeError::WrongParameterCount(1)),
     }
@@ -329,7 +687,7 @@ pub fn day_of_week(params: &[Value]) -> NativeResult {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if an under/overflow occures.
+/// Will return [`NativeError::Keyed`] if an under/overflow occures.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
@@ -339,7 +697,7 @@ pub fn encode_date(params: &[Value]) -> NativeResult {
             NaiveDate::from_ymd_opt(*year as i32, *month as u32, *day as u32)
                 .map(|date| date.and_time(NaiveTime::default()))
                 .map(Value::from)
-                .ok_or(NativeError::from("invalid date parameters"))
+                .ok_or(NativeError::keyed("err.datetime.encode_date", []))
         }
         [_, _, _] => Err(NativeError::WrongParameterType),
         _ => Err(NativeError::WrongParameterCount(3)),
@@ -352,7 +710,7 @@ pub fn encode_date(params: &[Value]) -> NativeResult {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if an under/overflow occures.
+/// Will return [`NativeError::Keyed`] if an under/overflow occures.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 #[allow(
@@ -367,7 +725,7 @@ pub fn encode_time(params: &[Value]) -> NativeResult {
         [Value::Number(hour), Value::Number(min), Value::Number(sec), ..] => NaiveDate::default()
             .and_hms_milli_opt(*hour as u32, *min as u32, *sec as u32, milli as u32)
             .map(Value::from)
-            .ok_or(NativeError::from("invalid time parameters")),
+            .ok_or(NativeError::keyed("err.datetime.encode_time", [])),
         [_, _, _, ..] => Err(NativeError::WrongParameterType),
         _ => Err(NativeError::WrongParameterCount(3)),
     }
@@ -383,7 +741,7 @@ pub fn encode_time(params: &[Value]) -> NativeResult {
 ///
 /// # Errors
 ///
-/// Will return [`NativeError::CustomError`] if an under/overflow occures.
+/// Will return [`NativeError::Keyed`] if an under/overflow occures.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
 #[allow(clippy::cast_possible_truncation)]
@@ -392,21 +750,8 @@ pub fn inc_month(params: &[Value]) -> NativeResult {
 
     match params {
         [value, ..] => {
-            let datetime = NaiveDateTime::try_from(value).and_then(|datetime| {
-                let delta = Months::new((increment as i32).unsigned_abs());
-
-                if increment > 0.0 {
-                    datetime
-                        .checked_add_months(delta)
-                        .ok_or(NativeError::from("inc_month increment overflow"))
-                } else if increment < 0.0 {
-                    datetime
-                        .checked_sub_months(delta)
-                        .ok_or(NativeError::from("inc_month decrement underflow"))
-                } else {
-                    Ok(datetime)
-                }
-            })?;
+            let datetime = NaiveDateTime::try_from(value)
+                .and_then(|datetime| add_months(datetime, increment as i32))?;
 
             Ok(Value::from(datetime))
         }
@@ -414,178 +759,729 @@ pub fn inc_month(params: &[Value]) -> NativeResult {
     }
 }
 
-/// Returns a [`Value::Boolean`] if the supplied datetime [`Value::Number`] is a leap year.
+/// Adds a positive or negative number of months, the day is clamped to the
+/// last day of the resulting month.
+fn add_months(datetime: NaiveDateTime, months: i32) -> Result<NaiveDateTime, NativeError> {
+    let delta = Months::new(months.unsigned_abs());
+
+    match months {
+        1.. => datetime
+            .checked_add_months(delta)
+            .ok_or(NativeError::keyed("err.datetime.month_overflow", [])),
+        ..=-1 => datetime
+            .checked_sub_months(delta)
+            .ok_or(NativeError::keyed("err.datetime.month_underflow", [])),
+        0 => Ok(datetime),
+    }
+}
+
+/// Adds a whole number of days to the supplied datetime [`Value::Number`].
 ///
-/// * Declaration: `is_leap_year(datetime: Number): Number`
+/// * Declaration: `add_days(datetime: Number, days: Number): Number`
+///
+/// # Remarks
+///
+/// The fraction of `days` is ignored, the days can be negative.
 ///
 /// # Errors
 ///
+/// Will return [`NativeError::Keyed`] if the result is out of range.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
-pub fn is_leap_year(params: &[Value]) -> NativeResult {
+#[allow(clippy::cast_possible_truncation)]
+pub fn add_days(params: &[Value]) -> NativeResult {
     match params {
-        [value] => {
-            let is_leap_year = NaiveDateTime::try_from(value)
-                .map(|datetime| datetime.year())
-                .map(|year| year % 4 == 0 && (year % 100 != 0 || year % 400 == 0))?;
-
-            Ok(Value::Boolean(is_leap_year))
+        [value, Value::Number(days)] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+            let delta = Days::new((*days as i64).unsigned_abs());
+            let result = if *days < 0.0 {
+                datetime.checked_sub_days(delta)
+            } else {
+                datetime.checked_add_days(delta)
+            };
+
+            result
+                .map(Value::from)
+                .ok_or(NativeError::keyed("err.datetime.range", []))
         }
-        _ => Err(NativeError::WrongParameterCount(1)),
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
     }
 }
 
-/// Returns the year portion of a supplied Datetime as a [`Value::Number`].
+/// Adds a whole number of years to the supplied datetime [`Value::Number`].
 ///
-/// * Declaration: `year(datetime: Number): Number`
+/// * Declaration: `add_years(datetime: Number, years: Number): Number`
+///
+/// # Remarks
+///
+/// February 29 becomes February 28 in a year which is not a leap year.
+/// The years can be negative.
 ///
 /// # Errors
 ///
+/// Will return [`NativeError::Keyed`] if an under/overflow occures.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
-pub fn year(params: &[Value]) -> NativeResult {
+#[allow(clippy::cast_possible_truncation)]
+pub fn add_years(params: &[Value]) -> NativeResult {
     match params {
-        [value] => {
+        [value, Value::Number(years)] => {
             let datetime = NaiveDateTime::try_from(value)?;
+            let months = (*years as i32).saturating_mul(12);
 
-            Ok(Value::Number(f64::from(datetime.year())))
+            Ok(Value::from(add_months(datetime, months)?))
         }
-        _ => Err(NativeError::WrongParameterCount(1)),
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
     }
 }
 
-/// Returns the month portion of a supplied Datetime as a [`Value::Number`].
+/// Returns the signed number of whole days from `a` to `b`.
 ///
-/// * Declaration: `month(datetime: Number): Number`
+/// * Declaration: `days_between(a: Number, b: Number): Number`
+///
+/// # Remarks
+///
+/// Only complete days of 24 hours are counted, partial days are truncated
+/// towards zero. The result is negative if `b` is before `a`.
 ///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
-pub fn month(params: &[Value]) -> NativeResult {
+#[allow(clippy::cast_precision_loss)]
+pub fn days_between(params: &[Value]) -> NativeResult {
     match params {
-        [value] => {
-            let datetime = NaiveDateTime::try_from(value)?;
+        [a, b] => {
+            let a = NaiveDateTime::try_from(a)?;
+            let b = NaiveDateTime::try_from(b)?;
 
-            Ok(Value::Number(f64::from(datetime.month())))
+            Ok(Value::Number((b - a).num_days() as f64))
         }
-        _ => Err(NativeError::WrongParameterCount(1)),
+        _ => Err(NativeError::WrongParameterCount(2)),
     }
 }
 
-/// Returns the day portion of a supplied Datetime as a [`Value::Number`].
+/// Returns the first day of the month of the supplied datetime [`Value::Number`] at midnight.
 ///
-/// * Declaration: `day(datetime: Number): Number`
+/// * Declaration: `start_of_month(datetime: Number): Number`
 ///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
-pub fn day(params: &[Value]) -> NativeResult {
+pub fn start_of_month(params: &[Value]) -> NativeResult {
     match params {
         [value] => {
-            let datetime = NaiveDateTime::try_from(value)?;
+            let date = NaiveDateTime::try_from(value)?.date();
 
-            Ok(Value::Number(f64::from(datetime.day())))
+            Ok(Value::from(
+                (date - Days::new(u64::from(date.day0()))).and_time(NaiveTime::MIN),
+            ))
         }
         _ => Err(NativeError::WrongParameterCount(1)),
     }
 }
 
-/// Returns the hour portion of a supplied Datetime as a [`Value::Number`].
+/// Returns the last day of the month of the supplied datetime [`Value::Number`] at midnight.
 ///
-/// * Declaration: `hour(datetime: Number): Number`
+/// * Declaration: `end_of_month(datetime: Number): Number`
+///
+/// # Remarks
+///
+/// The result is a date without time of day like [`start_of_month`], the
+/// first day of the following month is `end_of_month(datetime) + 1`.
 ///
 /// # Errors
 ///
+/// Will return [`NativeError::Keyed`] if the following month is out of range.
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
-pub fn hour(params: &[Value]) -> NativeResult {
+pub fn end_of_month(params: &[Value]) -> NativeResult {
     match params {
         [value] => {
-            let datetime = NaiveDateTime::try_from(value)?;
-
-            Ok(Value::Number(f64::from(datetime.hour())))
+            let date = NaiveDateTime::try_from(value)?.date();
+            let first = date - Days::new(u64::from(date.day0()));
+
+            first
+                .checked_add_months(Months::new(1))
+                .and_then(|next| next.pred_opt())
+                .map(|last| Value::from(last.and_time(NaiveTime::MIN)))
+                .ok_or(NativeError::keyed("err.datetime.range", []))
         }
         _ => Err(NativeError::WrongParameterCount(1)),
     }
 }
 
-/// Returns the minute portion of a supplied Datetime as a [`Value::Number`].
+/// Returns the ISO 8601 week number of the supplied datetime [`Value::Number`].
+/// Weeks start on Monday and the first week contains the first Thursday of the year.
 ///
-/// * Declaration: `minute(datetime: Number): Number`
+/// * Declaration: `week_of_year(datetime: Number): Number`
 ///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
-pub fn minute(params: &[Value]) -> NativeResult {
+pub fn week_of_year(params: &[Value]) -> NativeResult {
     match params {
         [value] => {
             let datetime = NaiveDateTime::try_from(value)?;
 
-            Ok(Value::Number(f64::from(datetime.minute())))
+            Ok(Value::Number(f64::from(datetime.iso_week().week())))
         }
         _ => Err(NativeError::WrongParameterCount(1)),
     }
 }
 
-/// Returns the second portion of a supplied Datetime as a [`Value::Number`].
+/// Returns the year of the ISO 8601 week containing the supplied datetime
+/// [`Value::Number`]. Around new year it can differ from the calendar year,
+/// e.g. `2024-12-30` belongs to week 1 of 2025.
 ///
-/// * Declaration: `second(datetime: Number): Number`
+/// * Declaration: `iso_week_year(datetime: Number): Number`
 ///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
-pub fn second(params: &[Value]) -> NativeResult {
+pub fn iso_week_year(params: &[Value]) -> NativeResult {
     match params {
         [value] => {
             let datetime = NaiveDateTime::try_from(value)?;
 
-            Ok(Value::Number(f64::from(datetime.second())))
+            Ok(Value::Number(f64::from(datetime.iso_week().year())))
         }
         _ => Err(NativeError::WrongParameterCount(1)),
     }
 }
 
-/// Returns the millisecond portion of a supplied Datetime as a [`Value::Number`].
+/// Returns the quarter (`1` to `4`) of the supplied datetime [`Value::Number`].
 ///
-/// * Declaration: `millisecond(datetime: Number): Number`
+/// * Declaration: `quarter(datetime: Number): Number`
 ///
 /// # Errors
 ///
 /// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
 /// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
-pub fn millisecond(params: &[Value]) -> NativeResult {
+pub fn quarter(params: &[Value]) -> NativeResult {
     match params {
         [value] => {
             let datetime = NaiveDateTime::try_from(value)?;
 
-            Ok(Value::Number(f64::from(datetime.nanosecond() / 1_000_000)))
+            Ok(Value::Number(f64::from(datetime.month0() / 3 + 1)))
         }
         _ => Err(NativeError::WrongParameterCount(1)),
     }
 }
 
-#[cfg(test)]
-mod test {
-    use chrono::NaiveDateTime;
-
-    use super::*;
-    use crate::Value;
-
-    #[test]
-    fn time_datetime_to_float() {
-        let timestamp =
-            NaiveDateTime::parse_from_str("2019-07-24 18:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
-        let time_value = Value::from(timestamp);
-
-        assert_eq!(Value::Number(18101.75), time_value);
-        assert_eq!(NaiveDateTime::try_from(&time_value).unwrap(), timestamp);
-    }
-
-    #[test]
+/// Returns a [`Value::Boolean`] if the supplied datetime [`Value::Number`] is a leap year.
+///
+/// * Declaration: `is_leap_year(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn is_leap_year(params: &[Value]) -> NativeResult {
+    match params {
+        [value] => {
+            let is_leap_year = NaiveDateTime::try_from(value)
+                .map(|datetime| datetime.year())
+                .map(|year| year % 4 == 0 && (year % 100 != 0 || year % 400 == 0))?;
+
+            Ok(Value::Boolean(is_leap_year))
+        }
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Returns the year portion of a supplied Datetime as a [`Value::Number`].
+///
+/// * Declaration: `year(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn year(params: &[Value]) -> NativeResult {
+    match params {
+        [value] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+
+            Ok(Value::Number(f64::from(datetime.year())))
+        }
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Returns the month portion of a supplied Datetime as a [`Value::Number`].
+///
+/// * Declaration: `month(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn month(params: &[Value]) -> NativeResult {
+    match params {
+        [value] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+
+            Ok(Value::Number(f64::from(datetime.month())))
+        }
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Returns the day portion of a supplied Datetime as a [`Value::Number`].
+///
+/// * Declaration: `day(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn day(params: &[Value]) -> NativeResult {
+    match params {
+        [value] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+
+            Ok(Value::Number(f64::from(datetime.day())))
+        }
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Returns the hour portion of a supplied Datetime as a [`Value::Number`].
+///
+/// * Declaration: `hour(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn hour(params: &[Value]) -> NativeResult {
+    match params {
+        [value] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+
+            Ok(Value::Number(f64::from(datetime.hour())))
+        }
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Returns the minute portion of a supplied Datetime as a [`Value::Number`].
+///
+/// * Declaration: `minute(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn minute(params: &[Value]) -> NativeResult {
+    match params {
+        [value] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+
+            Ok(Value::Number(f64::from(datetime.minute())))
+        }
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Returns the second portion of a supplied Datetime as a [`Value::Number`].
+///
+/// * Declaration: `second(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn second(params: &[Value]) -> NativeResult {
+    match params {
+        [value] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+
+            Ok(Value::Number(f64::from(datetime.second())))
+        }
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Returns the millisecond portion of a supplied Datetime as a [`Value::Number`].
+///
+/// * Declaration: `millisecond(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn millisecond(params: &[Value]) -> NativeResult {
+    match params {
+        [value] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+
+            Ok(Value::Number(f64::from(datetime.nanosecond() / 1_000_000)))
+        }
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+fn scale(params: &[Value], factor: f64) -> NativeResult {
+    match params {
+        [Value::Number(value)] => Ok(Value::Number(value * factor)),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+/// Converts a UNIX timestamp in seconds into a datetime [`Value::Number`].
+/// Both are based on UTC, no timezone adjustment takes place.
+///
+/// * Declaration: `from_unix(seconds: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn from_unix(params: &[Value]) -> NativeResult {
+    scale(params, 1. / SECONDS_PER_DAY)
+}
+
+/// Converts a datetime [`Value::Number`] into a UNIX timestamp in seconds.
+/// Both are based on UTC, no timezone adjustment takes place.
+///
+/// * Declaration: `to_unix(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn to_unix(params: &[Value]) -> NativeResult {
+    scale(params, SECONDS_PER_DAY)
+}
+
+/// Converts a UNIX timestamp in milliseconds, as used by e.g. JavaScript, into
+/// a datetime [`Value::Number`]. Both are based on UTC.
+///
+/// * Declaration: `from_unix_ms(milliseconds: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn from_unix_ms(params: &[Value]) -> NativeResult {
+    scale(params, 1. / MILLISECONDS_PER_DAY)
+}
+
+/// Converts a datetime [`Value::Number`] into a UNIX timestamp in milliseconds.
+/// Both are based on UTC.
+///
+/// * Declaration: `to_unix_ms(datetime: Number): Number`
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn to_unix_ms(params: &[Value]) -> NativeResult {
+    scale(params, MILLISECONDS_PER_DAY)
+}
+
+/// The calendar units a datetime can be truncated or rounded to.
+#[derive(Clone, Copy)]
+enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+const TIME_UNITS: &str = "second, minute, hour, day, month, year";
+
+impl TryFrom<&str> for TimeUnit {
+    type Error = NativeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "second" => Ok(Self::Second),
+            "minute" => Ok(Self::Minute),
+            "hour" => Ok(Self::Hour),
+            "day" => Ok(Self::Day),
+            "month" => Ok(Self::Month),
+            "year" => Ok(Self::Year),
+            _ => Err(NativeError::keyed(
+                "err.datetime.unit",
+                [&value, &TIME_UNITS],
+            )),
+        }
+    }
+}
+
+impl TimeUnit {
+    /// The start of the unit containing the datetime.
+    fn trunc(self, datetime: NaiveDateTime) -> NaiveDateTime {
+        let date = datetime.date();
+
+        let (date, time) = match self {
+            Self::Second => (
+                date,
+                (datetime.hour(), datetime.minute(), datetime.second()),
+            ),
+            Self::Minute => (date, (datetime.hour(), datetime.minute(), 0)),
+            Self::Hour => (date, (datetime.hour(), 0, 0)),
+            Self::Day => (date, (0, 0, 0)),
+            Self::Month => (date.with_day(1).unwrap_or(date), (0, 0, 0)),
+            Self::Year => (date.with_ordinal(1).unwrap_or(date), (0, 0, 0)),
+        };
+
+        date.and_hms_opt(time.0, time.1, time.2).unwrap_or(datetime)
+    }
+
+    /// The start of the following unit.
+    fn next(self, start: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Self::Second => start.checked_add_signed(Duration::seconds(1)),
+            Self::Minute => start.checked_add_signed(Duration::minutes(1)),
+            Self::Hour => start.checked_add_signed(Duration::hours(1)),
+            Self::Day => start.checked_add_signed(Duration::days(1)),
+            Self::Month => start.checked_add_months(Months::new(1)),
+            Self::Year => start.checked_add_months(Months::new(12)),
+        }
+    }
+}
+
+fn datetime_and_unit(params: &[Value]) -> Result<(NaiveDateTime, TimeUnit), NativeError> {
+    match params {
+        [value, Value::String(unit)] => {
+            let datetime = NaiveDateTime::try_from(value)?;
+
+            Ok((datetime, TimeUnit::try_from(unit.as_str())?))
+        }
+        [_, _] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+/// Truncates a datetime [`Value::Number`] to the start of a `'second'`,
+/// `'minute'`, `'hour'`, `'day'`, `'month'` or `'year'`.
+///
+/// * Declaration: `trunc_to(datetime: Number, unit: String): Number`
+///
+/// # Remarks
+///
+/// The units are calendar based, e.g. a month starts on the first day
+/// regardless of its length. Truncating to whole seconds removes the
+/// millisecond noise of different sources before comparing datetimes.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the unit is unknown.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn trunc_to(params: &[Value]) -> NativeResult {
+    let (datetime, unit) = datetime_and_unit(params)?;
+
+    Ok(Value::from(unit.trunc(datetime)))
+}
+
+/// Rounds a datetime [`Value::Number`] to the nearest start of a `'second'`,
+/// `'minute'`, `'hour'`, `'day'`, `'month'` or `'year'`.
+///
+/// * Declaration: `round_to_unit(datetime: Number, unit: String): Number`
+///
+/// # Remarks
+///
+/// A datetime exactly in the middle of a unit is rounded up. Months and years
+/// are rounded by their actual length, e.g. noon of February 15 in a common
+/// year is already closer to March.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the unit is unknown or the result is out of range.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn round_to_unit(params: &[Value]) -> NativeResult {
+    let (datetime, unit) = datetime_and_unit(params)?;
+
+    let start = unit.trunc(datetime);
+    let end = unit
+        .next(start)
+        .ok_or(NativeError::keyed("err.datetime.range", []))?;
+
+    if datetime - start < end - datetime {
+        Ok(Value::from(start))
+    } else {
+        Ok(Value::from(end))
+    }
+}
+
+/// Checks if two datetime [`Value::Number`] parameters are at most
+/// `tolerance_seconds` apart.
+///
+/// * Declaration: `same_moment(a: Number, b: Number, tolerance_seconds: Number = 1): Boolean`
+///
+/// # Remarks
+///
+/// Unlike truncating both datetimes, the tolerance also matches two datetimes
+/// on different sides of a second boundary, e.g. `12:00:00.900` and `12:00:01.100`.
+///
+/// # Errors
+///
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn same_moment(params: &[Value]) -> NativeResult {
+    let tolerance = default_number(params, 2, 1.0)?;
+
+    match params {
+        [Value::Number(a), Value::Number(b), ..] => {
+            Ok(Value::Boolean((a - b).abs() * SECONDS_PER_DAY <= tolerance))
+        }
+        [_, _, ..] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(2)),
+    }
+}
+
+const DURATION_SPECIFIERS: &str = "%D, %H, %M, %S, %f, %%";
+
+/// Formats a timespan [`Value::Number`] in days (e.g. `1.5104166`) with the
+/// specifiers `%D` (days), `%H` (hours), `%M` (minutes), `%S` (seconds),
+/// `%f` (milliseconds) and `%%` into a [`Value::String`] (e.g. `36:15:00`).
+///
+/// * Declaration: `format_duration(days: Number, fmt: String = '%H:%M:%S'): String`
+///
+/// # Remarks
+///
+/// Unlike `time_to_string` the hours are not limited to a single day: `%H`
+/// counts the total hours, unless the format also contains `%D`. The duration
+/// is rounded to whole milliseconds and a negative duration starts with `-`.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the format contains an unknown specifier
+/// or the duration is not finite.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn format_duration(params: &[Value]) -> NativeResult {
+    let fmt = default_string(params, 1, "%H:%M:%S")?;
+
+    let days = match params {
+        [Value::Number(days), ..] => *days,
+        [_, ..] => return Err(NativeError::WrongParameterType),
+        _ => return Err(NativeError::WrongParameterCount(1)),
+    };
+    let total = (days.abs() * MILLISECONDS_PER_DAY).round();
+
+    if !total.is_finite() || total > u64::MAX as f64 {
+        return Err(NativeError::keyed("err.datetime.range", []));
+    }
+
+    let total = total as u64;
+    let hours = total / 3_600_000;
+    let hours = if fmt.contains("%D") {
+        hours % 24
+    } else {
+        hours
+    };
+
+    let mut result = String::from(if days < 0.0 && total > 0 { "-" } else { "" });
+    let mut chars = fmt.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '%' {
+            result.push(char);
+            continue;
+        }
+
+        match chars.next() {
+            Some('D') => result += &(total / 86_400_000).to_string(),
+            Some('H') => result += &format!("{hours:02}"),
+            Some('M') => result += &format!("{:02}", total / 60_000 % 60),
+            Some('S') => result += &format!("{:02}", total / 1_000 % 60),
+            Some('f') => result += &format!("{:03}", total % 1_000),
+            Some('%') => result.push('%'),
+            specifier => {
+                return Err(NativeError::keyed(
+                    "err.datetime.duration_format",
+                    [
+                        &specifier.map(String::from).unwrap_or_default(),
+                        &DURATION_SPECIFIERS,
+                    ],
+                ))
+            }
+        }
+    }
+
+    Ok(Value::String(result))
+}
+
+/// Parses `[-]H:MM:SS[.fff]` into the total seconds, the hours are not limited.
+fn duration_seconds(text: &str) -> Option<f64> {
+    let is_digits = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    let is_two_digits = |text: &str| text.len() == 2 && is_digits(text);
+
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(text) => (-1.0, text),
+        None => (1.0, text),
+    };
+    let mut parts = text.split(':');
+    let (hours, minutes, seconds) = (parts.next()?, parts.next()?, parts.next()?);
+    let (whole_seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+
+    let valid = parts.next().is_none()
+        && is_digits(hours)
+        && is_two_digits(minutes)
+        && is_two_digits(whole_seconds)
+        && is_digits(fraction);
+    if !valid {
+        return None;
+    }
+
+    let hours: f64 = hours.parse().ok()?;
+    let minutes: f64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.parse().ok()?;
+
+    if minutes < 60.0 && seconds < 60.0 {
+        Some(sign * (hours * 3600.0 + minutes * 60.0 + seconds))
+    } else {
+        None
+    }
+}
+
+/// Parses a timespan [`Value::String`] like `36:15:00` or `-0:30:00.250`
+/// into a [`Value::Number`] in days (e.g. `1.5104166`).
+///
+/// * Declaration: `parse_duration(text: String): Number`
+///
+/// # Remarks
+///
+/// The hours are not limited to a single day, minutes and seconds need two
+/// digits and must be below 60. Seconds can have a fractional part.
+///
+/// # Errors
+///
+/// Will return [`NativeError::Keyed`] if the String is not a valid duration.
+/// Will return [`NativeError::WrongParameterCount`] if there is a mismatch in the supplied parameters.
+/// Will return [`NativeError::WrongParameterType`] if the the supplied parameters have the wrong type.
+pub fn parse_duration(params: &[Value]) -> NativeResult {
+    match params {
+        [Value::String(text)] => duration_seconds(text)
+            .map(|seconds| Value::Number(seconds / SECONDS_PER_DAY))
+            .ok_or(NativeError::keyed("err.datetime.duration_parse", [text])),
+        [_] => Err(NativeError::WrongParameterType),
+        _ => Err(NativeError::WrongParameterCount(1)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Value;
+
+    #[test]
     fn time_date_to_string() {
         let date = date_to_string(&vec![
             Value::String(String::from("%Y-%m-%d %H:%M:%S")),
@@ -611,6 +1507,66 @@ mod test {
         assert_eq!(Value::Number(18101.0), date);
     }
 
+    #[test]
+    fn time_string_to_week_date() {
+        let date = |text: &str| string_to_week_date(&[Value::String(String::from(text))]);
+
+        assert_eq!(
+            Ok(Value::Number(19596.0)), // 2023-08-27
+            date("2023-W34-7")
+        );
+        assert_eq!(
+            Ok(Value::Number(16803.0)), // 2016-01-03
+            date("2015-W53-7")
+        );
+        assert_eq!(
+            Ok(Value::Number(16797.0)), // 2015-12-28
+            date("2015-W53-1")
+        );
+        assert!(date("2023-W53-1").is_err());
+        assert!(date("2023-W35-8").is_err());
+        assert!(date("2023-08-27").is_err());
+    }
+
+    #[test]
+    fn time_string_to_ordinal_date() {
+        let date = |text: &str| string_to_ordinal_date(&[Value::String(String::from(text))]);
+
+        assert_eq!(Ok(Value::Number(19596.0)), date("2023-239"));
+        assert_eq!(Ok(Value::Number(19722.0)), date("2023-365"));
+        assert_eq!(Ok(Value::Number(20088.0)), date("2024-366"));
+        assert!(date("2023-366").is_err());
+        assert!(date("2023-000").is_err());
+    }
+
+    #[test]
+    fn time_parse_date_any() {
+        let date = |text: &str| parse_date_any(&[Value::String(String::from(text))]);
+
+        for text in [
+            "2023-08-27",
+            "20230827",
+            "2023-W34-7",
+            "2023-239",
+            "2023-08-27T10:30:00+02:00",
+            "2023-08-27T23:30:00-05:00",
+        ] {
+            assert_eq!(Ok(Value::Number(19596.0)), date(text), "{text}");
+        }
+
+        for text in ["8/27/23", "27.08.2023", "2023-08-27 10:30", "garbage", ""] {
+            assert!(
+                matches!(date(text), Err(NativeError::Keyed("err.datetime.parse_any", args)) if args[1].contains("ISO 8601 week date (%G-W%V-%u)")),
+                "{text}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            parse_date_any(&[Value::Number(19596.0)])
+        );
+    }
+
     #[test]
     fn time_string_to_time() {
         let date = string_to_time(&vec![Value::String(String::from("12:00:00"))]).unwrap();
@@ -632,6 +1588,72 @@ mod test {
             Ok(Value::Number(2.0)),
             day_of_week(&vec![Value::Number(18101.75)])
         );
+
+        // 2023-08-27 is a Sunday, 2023-08-28 a Monday
+        for (value, mode, expected) in [
+            ("2023-08-27 00:00:00.000", "zero", 6.0),
+            ("2023-08-28 00:00:00.000", "zero", 0.0),
+            ("2023-08-27 00:00:00.000", "iso", 7.0),
+            ("2023-08-28 00:00:00.000", "ISO", 1.0),
+            ("2023-08-27 00:00:00.000", "delphi", 1.0),
+            ("2023-09-02 00:00:00.000", "delphi", 7.0),
+        ] {
+            assert_eq!(
+                Ok(Value::Number(expected)),
+                day_of_week(&[datetime(value), Value::String(String::from(mode))]),
+                "{value} {mode}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::keyed(
+                "err.datetime.weekday_mode",
+                [&"sunday", &WEEKDAY_MODES]
+            )),
+            day_of_week(&[Value::Number(0.0), Value::String(String::from("sunday"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            day_of_week(&[Value::Number(0.0), Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), day_of_week(&[]));
+    }
+
+    #[test]
+    fn time_iso_week_and_quarter() {
+        for (value, week, week_year, quarter_of_year) in [
+            ("2024-12-29 00:00:00.000", 52.0, 2024.0, 4.0),
+            ("2024-12-30 00:00:00.000", 1.0, 2025.0, 4.0),
+            ("2025-01-01 00:00:00.000", 1.0, 2025.0, 1.0),
+            ("2021-01-03 23:59:59.999", 53.0, 2020.0, 1.0),
+            ("2021-01-04 00:00:00.000", 1.0, 2021.0, 1.0),
+            ("2023-03-31 00:00:00.000", 13.0, 2023.0, 1.0),
+            ("2023-04-01 00:00:00.000", 13.0, 2023.0, 2.0),
+            ("2023-07-01 00:00:00.000", 26.0, 2023.0, 3.0),
+            ("2023-10-01 00:00:00.000", 39.0, 2023.0, 4.0),
+        ] {
+            assert_eq!(
+                Ok(Value::Number(week)),
+                week_of_year(&[datetime(value)]),
+                "{value}"
+            );
+            assert_eq!(
+                Ok(Value::Number(week_year)),
+                iso_week_year(&[datetime(value)]),
+                "{value}"
+            );
+            assert_eq!(
+                Ok(Value::Number(quarter_of_year)),
+                quarter(&[datetime(value)]),
+                "{value}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            quarter(&[Value::String(String::from("2023-01-01"))])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), iso_week_year(&[]));
     }
 
     #[test]
@@ -701,6 +1723,114 @@ mod test {
         assert_eq!(Ok(dec_one), inc_month(&vec![date, Value::Number(-1.0)]));
     }
 
+    #[test]
+    fn time_add_days_and_years() {
+        let leap_day = datetime("2024-02-29 18:00:00.000");
+
+        assert_eq!(
+            Ok(datetime("2024-03-01 18:00:00.000")),
+            add_days(&[leap_day.clone(), Value::Number(1.0)])
+        );
+        assert_eq!(
+            Ok(datetime("2023-12-31 18:00:00.000")),
+            add_days(&[leap_day.clone(), Value::Number(-60.9)])
+        );
+        assert_eq!(
+            Ok(datetime("2025-02-28 18:00:00.000")),
+            add_years(&[leap_day.clone(), Value::Number(1.0)])
+        );
+        assert_eq!(
+            Ok(datetime("2028-02-29 18:00:00.000")),
+            add_years(&[leap_day.clone(), Value::Number(4.0)])
+        );
+        assert_eq!(
+            Ok(datetime("2023-02-28 18:00:00.000")),
+            add_years(&[leap_day.clone(), Value::Number(-1.0)])
+        );
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            add_days(&[leap_day.clone(), Value::String(String::from("1"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            add_years(&[Value::String(String::from("2024")), Value::Number(1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(2)),
+            add_years(&[leap_day])
+        );
+    }
+
+    #[test]
+    fn time_days_between() {
+        let start = datetime("2024-02-28 12:00:00.000");
+
+        for (end, expected) in [
+            ("2024-03-01 12:00:00.000", 2.0),
+            ("2024-03-01 11:59:59.999", 1.0),
+            ("2024-02-28 00:00:00.000", 0.0),
+            ("2024-02-27 00:00:00.000", -1.0),
+            ("2023-02-28 12:00:00.000", -365.0),
+        ] {
+            assert_eq!(
+                Ok(Value::Number(expected)),
+                days_between(&[start.clone(), datetime(end)]),
+                "{end}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            days_between(&[start, Value::Null])
+        );
+    }
+
+    #[test]
+    fn time_start_and_end_of_month() {
+        for (value, start, end) in [
+            ("2024-02-15 13:45:00.000", "2024-02-01", "2024-02-29"),
+            ("2023-02-28 23:59:59.999", "2023-02-01", "2023-02-28"),
+            ("2023-12-31 00:00:00.000", "2023-12-01", "2023-12-31"),
+            ("2024-04-01 00:00:00.000", "2024-04-01", "2024-04-30"),
+        ] {
+            let midnight = |date: &str| datetime(&format!("{date} 00:00:00.000"));
+
+            assert_eq!(Ok(midnight(start)), start_of_month(&[datetime(value)]));
+            assert_eq!(Ok(midnight(end)), end_of_month(&[datetime(value)]));
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            end_of_month(&[Value::Boolean(true)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(1)),
+            start_of_month(&[])
+        );
+    }
+
+    #[test]
+    fn time_week_of_year() {
+        for (value, expected) in [
+            ("2024-01-01 00:00:00.000", 1.0),
+            ("2023-01-01 00:00:00.000", 52.0),
+            ("2020-12-31 00:00:00.000", 53.0),
+            ("2024-12-30 00:00:00.000", 1.0),
+        ] {
+            assert_eq!(
+                Ok(Value::Number(expected)),
+                week_of_year(&[datetime(value)]),
+                "{value}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            week_of_year(&[Value::String(String::from("2024-01-01"))])
+        );
+    }
+
     #[test]
     fn time_is_leap_year() {
         let year_2023 = encode_date(&vec![
@@ -721,35 +1851,91 @@ mod test {
         assert_eq!(Ok(Value::Boolean(true)), is_leap_year(&vec![year_2024]));
     }
 
-    #[allow(dead_code)]
-    // #[test] // dependent on the local timezone
+    #[test]
     fn time_rfc2822() {
         let rfc = Value::String(String::from("Fri, 28 Nov 2014 12:00:00 +0100"));
-        let date = date_from_rfc2822(&vec![rfc.clone()]).unwrap();
+        let date = date_from_rfc2822_utc(std::slice::from_ref(&rfc)).unwrap();
 
-        assert_eq!(Value::Number(16402.5), date);
-        assert_eq!(Ok(rfc), date_to_rfc2822(&vec![date]));
+        assert_eq!(datetime("2014-11-28 11:00:00.000"), date);
+        assert_eq!(
+            Ok(rfc),
+            date_to_rfc2822_tz(&[date.clone(), Value::Number(60.0)])
+        );
+        assert_eq!(
+            Ok(Value::String(String::from(
+                "Fri, 28 Nov 2014 11:00:00 +0000"
+            ))),
+            date_to_rfc2822_tz(&[date, Value::Number(0.0)])
+        );
     }
 
-    #[allow(dead_code)]
-    // #[test] // dependent on the local timezone
+    #[test]
     fn time_rfc3339() {
         let rfc = Value::String(String::from("2014-11-28T12:00:00+01:00"));
-        let date = date_from_rfc3339(&vec![rfc.clone()]).unwrap();
+        let date = date_from_rfc3339_utc(std::slice::from_ref(&rfc)).unwrap();
 
-        assert_eq!(Value::Number(16402.5), date);
-        assert_eq!(Ok(rfc), date_to_rfc3339(&vec![date]));
+        assert_eq!(datetime("2014-11-28 11:00:00.000"), date);
+        assert_eq!(Ok(rfc), date_to_rfc3339_tz(&[date, Value::Number(60.0)]));
 
         let rfc = Value::String(String::from("2014-11-28T01:00:00+01:00"));
-        let date = date_from_rfc3339(&vec![rfc.clone()]).unwrap();
+        let date = date_from_rfc3339_utc(std::slice::from_ref(&rfc)).unwrap();
 
-        assert_eq!(Value::Number(16402.0 + 1. / 24.), date);
-        assert_eq!(Ok(rfc), date_to_rfc3339(&vec![date.clone()]));
+        assert_eq!(Value::Number(16402.0), date);
+        assert_eq!(
+            Ok(rfc),
+            date_to_rfc3339_tz(&[date.clone(), Value::Number(60.0)])
+        );
 
         let rfc = Value::String(String::from("2014-11-28T00:00:00Z"));
-        let date_utc = date_from_rfc3339(&vec![rfc.clone()]).unwrap();
+        let date_utc = date_from_rfc3339_utc(std::slice::from_ref(&rfc)).unwrap();
 
         assert_eq!(date, date_utc);
+        assert_eq!(
+            Ok(Value::String(String::from("2014-11-27T19:30:00-04:30"))),
+            date_to_rfc3339_tz(&[date_utc, Value::Number(-270.0)])
+        );
+    }
+
+    #[test]
+    fn time_convert_tz() {
+        let noon = datetime("2014-11-28 12:00:00.000");
+
+        assert_eq!(
+            Ok(datetime("2014-11-28 11:00:00.000")),
+            convert_tz(&[noon.clone(), Value::Number(60.0), Value::Number(0.0)])
+        );
+        assert_eq!(
+            Ok(datetime("2014-11-29 01:45:00.000")),
+            convert_tz(&[noon.clone(), Value::Number(-60.0), Value::Number(765.0)])
+        );
+        assert_eq!(
+            Ok(noon.clone()),
+            convert_tz(&[noon.clone(), Value::Number(120.0), Value::Number(120.0)])
+        );
+
+        for offset in [841.0, -841.0, f64::NAN] {
+            assert!(matches!(
+                convert_tz(&[noon.clone(), Value::Number(offset), Value::Number(0.0)]),
+                Err(NativeError::CustomError(_))
+            ));
+            assert!(matches!(
+                date_to_rfc3339_tz(&[noon.clone(), Value::Number(offset)]),
+                Err(NativeError::CustomError(_))
+            ));
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            convert_tz(&[
+                noon.clone(),
+                Value::String(String::from("+01:00")),
+                Value::Number(0.0)
+            ])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(3)),
+            convert_tz(&[noon, Value::Number(0.0)])
+        );
     }
 
     #[test]
@@ -764,4 +1950,340 @@ mod test {
         assert_eq!(Ok(Value::Number(12.0)), second(&vec![date.clone()]));
         assert_eq!(Ok(Value::Number(13.0)), millisecond(&vec![date.clone()]));
     }
+
+    #[test]
+    fn time_unix_timestamps() {
+        let datetime = |text: &str| string_to_datetime(&[Value::String(text.to_string())]);
+        let number = |value: NativeResult| match value {
+            Ok(Value::Number(number)) => number,
+            other => panic!("expected a number, got {other:?}"),
+        };
+
+        let instants = [
+            ("1970-01-01 00:00:00", 0.0),
+            ("1969-07-20 20:17:40", -14_182_940.0),
+            ("2000-01-01 00:00:00", 946_684_800.0),
+            ("2023-08-27 12:00:00", 1_693_137_600.0),
+            ("2038-01-19 03:14:08", 2_147_483_648.0),
+        ];
+
+        for (text, seconds) in instants {
+            let date = datetime(text).unwrap();
+
+            assert_eq!(
+                seconds,
+                number(to_unix(std::slice::from_ref(&date))).round(),
+                "{text}"
+            );
+            assert_eq!(
+                seconds * 1000.,
+                number(to_unix_ms(std::slice::from_ref(&date))).round(),
+                "{text}"
+            );
+            assert!((number(from_unix(&[Value::Number(seconds)])) - number(Ok(date))).abs() < 1e-9);
+        }
+
+        // round-trips within a millisecond from 1960 to 2100
+        let mut milliseconds = -315_619_200_000.0;
+        while milliseconds < 4_102_444_800_000.0 {
+            let date = from_unix_ms(&[Value::Number(milliseconds)]).unwrap();
+            assert!((number(to_unix_ms(std::slice::from_ref(&date))) - milliseconds).abs() < 1.0);
+
+            let seconds = number(to_unix(&[date])) * 1000.;
+            assert!((seconds - milliseconds).abs() < 1.0);
+
+            milliseconds += 86_399_937.0 * 17.0;
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            to_unix(&[Value::String(String::from("0"))])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(1)), from_unix(&[]));
+    }
+
+    fn datetime(text: &str) -> Value {
+        let datetime = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.3f").unwrap();
+
+        Value::from(datetime)
+    }
+
+    #[test]
+    fn time_trunc_to() {
+        let unit = |unit: &str| Value::String(unit.to_string());
+        let value = datetime("2024-03-31 23:59:59.999");
+
+        let cases = [
+            ("second", "2024-03-31 23:59:59.000"),
+            ("Minute", "2024-03-31 23:59:00.000"),
+            ("hour", "2024-03-31 23:00:00.000"),
+            ("day", "2024-03-31 00:00:00.000"),
+            ("month", "2024-03-01 00:00:00.000"),
+            ("year", "2024-01-01 00:00:00.000"),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(
+                Ok(datetime(expected)),
+                trunc_to(&[value.clone(), unit(name)]),
+                "{name}"
+            );
+        }
+
+        // months with 30 and 31 days both start on the first
+        for (text, expected) in [
+            ("2023-04-30 12:00:00.000", "2023-04-01 00:00:00.000"),
+            ("2023-05-31 12:00:00.000", "2023-05-01 00:00:00.000"),
+            ("2024-02-29 12:00:00.000", "2024-02-01 00:00:00.000"),
+            ("2023-05-01 00:00:00.000", "2023-05-01 00:00:00.000"),
+        ] {
+            assert_eq!(
+                Ok(datetime(expected)),
+                trunc_to(&[datetime(text), unit("month")]),
+                "{text}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::keyed(
+                "err.datetime.unit",
+                [&"week", &TIME_UNITS]
+            )),
+            trunc_to(&[value.clone(), unit("week")])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            trunc_to(&[value, Value::Number(1.0)])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(2)), trunc_to(&[]));
+    }
+
+    #[test]
+    fn time_round_to_unit() {
+        let unit = |unit: &str| Value::String(unit.to_string());
+
+        let cases = [
+            (
+                "2024-03-31 23:59:59.500",
+                "second",
+                "2024-04-01 00:00:00.000",
+            ),
+            (
+                "2024-03-31 23:59:59.499",
+                "second",
+                "2024-03-31 23:59:59.000",
+            ),
+            ("2024-03-31 12:00:00.000", "day", "2024-04-01 00:00:00.000"),
+            ("2024-03-31 11:59:59.000", "day", "2024-03-31 00:00:00.000"),
+            ("2024-03-10 10:30:00.000", "hour", "2024-03-10 11:00:00.000"),
+            // April has 30 days, its middle is April 16 00:00
+            (
+                "2023-04-15 23:59:00.000",
+                "month",
+                "2023-04-01 00:00:00.000",
+            ),
+            (
+                "2023-04-16 00:00:00.000",
+                "month",
+                "2023-05-01 00:00:00.000",
+            ),
+            // May has 31 days, its middle is May 16 12:00
+            (
+                "2023-05-16 11:59:00.000",
+                "month",
+                "2023-05-01 00:00:00.000",
+            ),
+            (
+                "2023-05-16 12:00:00.000",
+                "month",
+                "2023-06-01 00:00:00.000",
+            ),
+            ("2023-12-31 00:00:00.000", "year", "2024-01-01 00:00:00.000"),
+        ];
+
+        for (text, name, expected) in cases {
+            assert_eq!(
+                Ok(datetime(expected)),
+                round_to_unit(&[datetime(text), unit(name)]),
+                "{text} {name}"
+            );
+        }
+    }
+
+    #[test]
+    fn time_format_duration() {
+        let format = |days: f64, fmt: &str| {
+            format_duration(&[Value::Number(days), Value::String(String::from(fmt))])
+        };
+        let text = |text: &str| Ok(Value::String(String::from(text)));
+
+        assert_eq!(
+            text("12:30:00"),
+            format_duration(&[Value::Number(0.520_833_333_333_333_4)])
+        );
+        assert_eq!(
+            text("36:15:00"),
+            format_duration(&[Value::Number(1.510_416_666_666_666_7)])
+        );
+        assert_eq!(text("-00:30:00"), format(-0.5 / 24.0, "%H:%M:%S"));
+        assert_eq!(text("00:00:00"), format(-1e-12, "%H:%M:%S"));
+        assert_eq!(
+            text("1d 12:15:00.250"),
+            format(1.510_419_560_185_185, "%Dd %H:%M:%S.%f")
+        );
+        assert_eq!(text("240h 00m (100%)"), format(10.0, "%Hh %Mm (100%%)"));
+
+        assert_eq!(
+            Err(NativeError::keyed(
+                "err.datetime.duration_format",
+                [&"Y", &DURATION_SPECIFIERS]
+            )),
+            format(1.0, "%Y")
+        );
+        assert_eq!(
+            Err(NativeError::keyed("err.datetime.range", [])),
+            format_duration(&[Value::Number(f64::INFINITY)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            format_duration(&[Value::String(String::from("12:30:00"))])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            format_duration(&[Value::Number(1.0), Value::Number(1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(1)),
+            format_duration(&[])
+        );
+    }
+
+    #[test]
+    fn time_parse_duration() {
+        let parse = |text: &str| parse_duration(&[Value::String(String::from(text))]);
+
+        assert_eq!(Ok(Value::Number(36.25 / 24.0)), parse("36:15:00"));
+        assert_eq!(Ok(Value::Number(-0.5 / 24.0)), parse("-0:30:00"));
+        assert_eq!(Ok(Value::Number(0.0)), parse("00:00:00"));
+        assert_eq!(
+            Ok(Value::Number(1.25 / SECONDS_PER_DAY)),
+            parse("0:00:01.250")
+        );
+
+        for text in [
+            "",
+            "12:30",
+            "12:60:00",
+            "12:00:60",
+            "1:2:3",
+            "a:00:00",
+            "+1:00:00",
+            "1:00:00:00",
+            "1:00:00.",
+        ] {
+            assert_eq!(
+                Err(NativeError::keyed("err.datetime.duration_parse", [&text])),
+                parse(text),
+                "{text}"
+            );
+        }
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            parse_duration(&[Value::Number(1.0)])
+        );
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(1)),
+            parse_duration(&[])
+        );
+    }
+
+    #[test]
+    fn time_duration_round_trip() {
+        for text in [
+            "00:00:00",
+            "12:30:00",
+            "36:15:00",
+            "-01:00:01",
+            "1000:59:59",
+        ] {
+            let days = parse_duration(&[Value::String(String::from(text))]).unwrap();
+
+            assert_eq!(
+                Ok(Value::String(String::from(text))),
+                format_duration(&[days])
+            );
+        }
+
+        for days in [0.25, 1.510_416_666_666_666_7, -2.75, 10_000.5] {
+            let text = format_duration(&[
+                Value::Number(days),
+                Value::String(String::from("%H:%M:%S.%f")),
+            ])
+            .unwrap();
+
+            assert_eq!(Ok(Value::Number(days)), parse_duration(&[text]));
+        }
+    }
+
+    #[test]
+    fn time_same_moment() {
+        let a = datetime("2024-03-31 12:00:00.900");
+        let b = datetime("2024-03-31 12:00:01.100");
+        let c = datetime("2024-03-31 12:00:02.000");
+
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            same_moment(&[a.clone(), b.clone()])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            same_moment(&[b.clone(), a.clone()])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(false)),
+            same_moment(&[a.clone(), c.clone()])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            same_moment(&[a.clone(), c, Value::Number(1.5)])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(false)),
+            same_moment(&[a.clone(), b, Value::Number(0.1)])
+        );
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            same_moment(&[a.clone(), a.clone(), Value::Number(0.0)])
+        );
+
+        assert_eq!(
+            Err(NativeError::WrongParameterType),
+            same_moment(&[a.clone(), Value::String(String::from("now"))])
+        );
+        assert_eq!(Err(NativeError::WrongParameterCount(2)), same_moment(&[a]));
+    }
+
+    #[test]
+    fn time_now_and_today() {
+        let clock =
+            NaiveDateTime::parse_from_str("2019-07-24 18:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+
+        assert_eq!(Ok(Value::Number(18101.75)), now_at(clock, &[]));
+        assert_eq!(Ok(Value::Number(18101.0)), today_at(clock, &[]));
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(0)),
+            now_at(clock, &[Value::Number(1.0)])
+        );
+
+        let before = Value::from(Local::now().naive_local());
+        let current = now(&[]).unwrap();
+
+        assert!(before <= current);
+        assert!(today(&[]).unwrap() <= current);
+        assert_eq!(
+            Err(NativeError::WrongParameterCount(0)),
+            today(&[Value::Null])
+        );
+    }
 }
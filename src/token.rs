@@ -13,29 +13,87 @@ pub enum Token {
   Greater, GreaterEqual,
   Less, LessEqual,
   // Equality
-  Equal, NotEqual,
+  Equal, NotEqual, ApproxEqual,
   // Keywords
-  And, Or, Xor, Not, Div, Mod,
+  And, Or, Xor, Not, Div, Mod, Is, Implies, Iff, If, Then, Else, In,
   // Literal Values
   Literal(Value),
   Identifier(String)
 }
 
+/// The kind of a [`Token`] without its payload, e.g. to describe the tokens
+/// the compiler expects at a certain position.
+#[rustfmt::skip]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TokenKind {
+  LeftParen, RightParen,
+  LeftBracket, RightBracket,
+  Plus, Minus, Star, Slash,
+  Comma,
+  Greater, GreaterEqual,
+  Less, LessEqual,
+  Equal, NotEqual, ApproxEqual,
+  And, Or, Xor, Not, Div, Mod, Is, Implies, Iff, If, Then, Else, In,
+  Literal,
+  Identifier
+}
+
+impl From<&Token> for TokenKind {
+    #[rustfmt::skip]
+    fn from(token: &Token) -> Self {
+        match token {
+            Token::LeftParen => TokenKind::LeftParen,
+            Token::RightParen => TokenKind::RightParen,
+            Token::LeftBracket => TokenKind::LeftBracket,
+            Token::RightBracket => TokenKind::RightBracket,
+            Token::Plus => TokenKind::Plus,
+            Token::Minus => TokenKind::Minus,
+            Token::Star => TokenKind::Star,
+            Token::Slash => TokenKind::Slash,
+            Token::Comma => TokenKind::Comma,
+            Token::Greater => TokenKind::Greater,
+            Token::GreaterEqual => TokenKind::GreaterEqual,
+            Token::Less => TokenKind::Less,
+            Token::LessEqual => TokenKind::LessEqual,
+            Token::Equal => TokenKind::Equal,
+            Token::NotEqual => TokenKind::NotEqual,
+            Token::ApproxEqual => TokenKind::ApproxEqual,
+            Token::And => TokenKind::And,
+            Token::Or => TokenKind::Or,
+            Token::Xor => TokenKind::Xor,
+            Token::Not => TokenKind::Not,
+            Token::Div => TokenKind::Div,
+            Token::Mod => TokenKind::Mod,
+            Token::Is => TokenKind::Is,
+            Token::Implies => TokenKind::Implies,
+            Token::Iff => TokenKind::Iff,
+            Token::If => TokenKind::If,
+            Token::Then => TokenKind::Then,
+            Token::Else => TokenKind::Else,
+            Token::In => TokenKind::In,
+            Token::Literal(_) => TokenKind::Literal,
+            Token::Identifier(_) => TokenKind::Identifier,
+        }
+    }
+}
+
 /// The precedences used to order the operators evaluated in the
 /// [Pratt-Parser](https://en.wikipedia.org/wiki/Operator-precedence_parser#Pratt_parsing)
 /// when building the [`Expression`](crate::Expression) tree.
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum Precedence {
     None,
+    Iff,        // iff
+    Implies,    // implies
     Or,         // or
     And,        // and
     Xor,        // xor
-    Equality,   // = <>
-    Comparison, // < > <= >=
+    Equality,   // = <> ~= is
+    Comparison, // < > <= >= in
     Term,       // + -
     Factor,     // * / div mod
     Unary,      // not -
-    Call,       // ()
+    Call,       // () []
     Primary,    // Literals
 }
 
@@ -46,12 +104,14 @@ impl From<&Token> for Precedence {
         match token {
             Token::Minus | Token::Plus => Precedence::Term,
             Token::Star | Token::Slash | Token::Div | Token::Mod => Precedence::Factor,
-            Token::Equal | Token::NotEqual => Precedence::Equality,
-            Token::Greater | Token::GreaterEqual | Token::Less | Token::LessEqual => Precedence::Comparison,
+            Token::Equal | Token::NotEqual | Token::ApproxEqual | Token::Is => Precedence::Equality,
+            Token::Greater | Token::GreaterEqual | Token::Less | Token::LessEqual | Token::In => Precedence::Comparison,
             Token::And => Precedence::And,
             Token::Or => Precedence::Or,
             Token::Xor => Precedence::Xor, 
-            Token::LeftParen => Precedence::Call,
+            Token::Implies => Precedence::Implies,
+            Token::Iff => Precedence::Iff,
+            Token::LeftParen | Token::LeftBracket => Precedence::Call,
             _ => Precedence::None,
         }
     }
@@ -61,7 +121,9 @@ impl Precedence {
     /// Returns the next `Precendence` with wrap around to the first.
     pub fn next(self) -> Precedence {
         match self {
-            Precedence::None => Precedence::Or,
+            Precedence::None => Precedence::Iff,
+            Precedence::Iff => Precedence::Implies,
+            Precedence::Implies => Precedence::Or,
             Precedence::Or => Precedence::And,
             Precedence::And => Precedence::Xor,
             Precedence::Xor => Precedence::Equality,
@@ -1,17 +1,36 @@
+use std::collections::HashSet;
+
 use crate::{
-    ast::Expression,
-    environment::{Environment, FunctionResult},
+    ast::{visit_post_order, Expression, NodeSpans},
+    compiler::is_ascending,
+    environment::{get_env_key, Environment, FunctionResult},
     error::{Error, Result},
+    interpreter::DEFAULT_MAX_DEPTH,
     operator::Operator,
+    scanner::Span,
     value::Value,
 };
 
+/// A finding of a lint like [`lint_array_scalar_equality`]. Unlike an [`Error`],
+/// a lint does not prevent the execution of an [`Expression`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// A stable identifier of the lint, e.g. `array_scalar_equality`.
+    pub code: &'static str,
+    /// A human readable description with a suggested fix.
+    pub message: String,
+    /// The source position of the offending node, if [`NodeSpans`] were supplied.
+    pub span: Option<Span>,
+}
+
 /// Validates [`Variable`](Expression::Variable) and [`Call`](Expression::Call) [`Expressions`](Expression)
 /// by walking the AST and returning the first error.
 ///
 /// # Errors
 ///
-/// Returns an [`Error`] on missing variables or functions.
+/// Returns an [`Error`] on missing variables or functions and
+/// [`Error::ParamCountMismatch`] if a call does not match the [`Arity`](crate::function::Arity)
+/// of its function. Variadic functions accept any number of parameters.
 pub fn check_variables_and_functions(
     env: &impl Environment,
     expression: &Expression,
@@ -22,6 +41,10 @@ pub fn check_variables_and_functions(
             left,
             right,
             operator: _,
+        }
+        | Expression::Index {
+            target: left,
+            index: right,
         } => check_variables_and_functions(env, left)
             .and_then(|()| check_variables_and_functions(env, right)),
         Expression::Ternary {
@@ -34,6 +57,10 @@ pub fn check_variables_and_functions(
             .and_then(|()| check_variables_and_functions(env, right)),
         Expression::Array {
             expressions: values,
+        }
+        | Expression::Chain {
+            expressions: values,
+            operators: _,
         } => check_expressions(env, values),
         Expression::Variable { name } => {
             if env.variable_exists(name) {
@@ -48,12 +75,12 @@ pub fn check_variables_and_functions(
             match env.function_exists(name, param_count) {
                 FunctionResult::Exists { pure: _ } => check_expressions(env, params),
                 FunctionResult::NotFound => Err(Error::MissingFunction(name.clone())),
-                FunctionResult::WrongArity { min, max } => Err(Error::ParamCountMismatch(
-                    name.clone(),
-                    param_count,
-                    min,
-                    max,
-                )),
+                FunctionResult::WrongArity { min, max } => Err(Error::ParamCountMismatch {
+                    name: name.clone(),
+                    expected_min: min,
+                    expected_max: max,
+                    found: param_count,
+                }),
             }
         }
         Expression::Literal { value: _ } => Ok(()),
@@ -66,6 +93,265 @@ fn check_expressions(env: &impl Environment, expressions: &[Expression]) -> Resu
         .try_for_each(|expression| check_variables_and_functions(env, expression))
 }
 
+/// Checks if all [`Variable`](Expression::Variable) [`Expressions`](Expression)
+/// are part of an allowlist, the validation counterpart of a
+/// [`ScopedEnvironment`](crate::ScopedEnvironment). Names are compared *case-insensitive*.
+///
+/// # Examples
+/// ```
+/// use std::collections::HashSet;
+/// use slac::{check_variable_scope, compile, Error};
+///
+/// let allowlist = HashSet::from([String::from("price")]);
+///
+/// assert!(check_variable_scope(&compile("Price > 10").unwrap(), &allowlist).is_ok());
+/// assert_eq!(
+///     Err(Error::VariableAccessDenied(String::from("internal_cost"))),
+///     check_variable_scope(&compile("price > internal_cost").unwrap(), &allowlist)
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error::VariableAccessDenied`] for the first variable outside of the allowlist.
+pub fn check_variable_scope(expression: &Expression, allowlist: &HashSet<String>) -> Result<()> {
+    let allowed: HashSet<String> = allowlist.iter().map(|name| get_env_key(name)).collect();
+
+    check_scope(expression, &allowed)
+}
+
+fn check_scope(expression: &Expression, allowed: &HashSet<String>) -> Result<()> {
+    match expression {
+        Expression::Unary { right, operator: _ } => check_scope(right, allowed),
+        Expression::Binary {
+            left,
+            right,
+            operator: _,
+        }
+        | Expression::Index {
+            target: left,
+            index: right,
+        } => check_scope(left, allowed).and_then(|()| check_scope(right, allowed)),
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            operator: _,
+        } => check_scope(left, allowed)
+            .and_then(|()| check_scope(middle, allowed))
+            .and_then(|()| check_scope(right, allowed)),
+        Expression::Array { expressions }
+        | Expression::Chain {
+            expressions,
+            operators: _,
+        }
+        | Expression::Call {
+            name: _,
+            params: expressions,
+        } => expressions
+            .iter()
+            .try_for_each(|expression| check_scope(expression, allowed)),
+        Expression::Variable { name } => {
+            if allowed.contains(&get_env_key(name)) {
+                Ok(())
+            } else {
+                Err(Error::VariableAccessDenied(name.clone()))
+            }
+        }
+        Expression::Literal { value: _ } => Ok(()),
+    }
+}
+
+/// The maximum number of parameters of a [`Call`](Expression::Call) accepted
+/// by [`check_ast_integrity`].
+pub const MAX_CALL_PARAMS: usize = 255;
+
+/// Checks the structure of an [`Expression`] which was not created by the
+/// compiler, e.g. deserialized from untrusted JSON, before it is cached or
+/// executed. Every node must use an [`Operator`] of its kind and a sensible
+/// number of operands.
+///
+/// # Examples
+/// ```
+/// use slac::{check_ast_integrity, compile, Error, Expression, Operator, Value};
+///
+/// assert!(check_ast_integrity(&compile("if a then -b else c < d <= e").unwrap()).is_ok());
+///
+/// let ast = Expression::Binary {
+///     left: Box::new(Expression::Literal { value: Value::Boolean(true) }),
+///     right: Box::new(Expression::Literal { value: Value::Boolean(false) }),
+///     operator: Operator::Not,
+/// };
+///
+/// assert_eq!(Err(Error::InvalidBinaryOperator(Operator::Not)), check_ast_integrity(&ast));
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidUnaryOperator`], [`Error::InvalidBinaryOperator`] or
+/// [`Error::InvalidTernaryOperator`] if a node uses an operator of another kind.
+/// Returns [`Error::MixedComparisonChain`] if the operators of a chain point into
+/// different directions.
+/// Returns [`Error::InvalidNode`] for other malformed nodes, e.g. a chain with
+/// a non-comparison operator, a call with more than [`MAX_CALL_PARAMS`]
+/// parameters or an empty name.
+/// Returns [`Error::RecursionLimitExceeded`] if the nodes are nested deeper than
+/// the [`DEFAULT_MAX_DEPTH`](crate::DEFAULT_MAX_DEPTH).
+pub fn check_ast_integrity(expression: &Expression) -> Result<()> {
+    check_integrity(expression, 0)
+}
+
+fn invalid_node(kind: &'static str, reason: String) -> Error {
+    Error::InvalidNode { kind, reason }
+}
+
+fn check_integrity(expression: &Expression, depth: usize) -> Result<()> {
+    if depth >= DEFAULT_MAX_DEPTH {
+        return Err(Error::RecursionLimitExceeded(DEFAULT_MAX_DEPTH));
+    }
+
+    let depth = depth + 1;
+    let check_all = |expressions: &[Expression]| {
+        expressions
+            .iter()
+            .try_for_each(|expression| check_integrity(expression, depth))
+    };
+
+    match expression {
+        Expression::Unary { right, operator } => match operator {
+            Operator::Minus | Operator::Not | Operator::IsEmpty | Operator::IsNotEmpty => {
+                check_integrity(right, depth)
+            }
+            _ => Err(Error::InvalidUnaryOperator(*operator)),
+        },
+        Expression::Binary {
+            left,
+            right,
+            operator,
+        } => match operator {
+            Operator::Not
+            | Operator::IsEmpty
+            | Operator::IsNotEmpty
+            | Operator::TernaryCondition => Err(Error::InvalidBinaryOperator(*operator)),
+            _ => check_integrity(left, depth).and_then(|()| check_integrity(right, depth)),
+        },
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            operator,
+        } => match operator {
+            Operator::TernaryCondition => check_integrity(left, depth)
+                .and_then(|()| check_integrity(middle, depth))
+                .and_then(|()| check_integrity(right, depth)),
+            _ => Err(Error::InvalidTernaryOperator(*operator)),
+        },
+        Expression::Chain {
+            expressions,
+            operators,
+        } => {
+            if operators.is_empty() || expressions.len() != operators.len() + 1 {
+                return Err(invalid_node(
+                    "chain",
+                    format!(
+                        "{} operators need {} operands but found {}",
+                        operators.len(),
+                        operators.len() + 1,
+                        expressions.len()
+                    ),
+                ));
+            }
+
+            for operator in operators {
+                if !matches!(
+                    operator,
+                    Operator::Greater
+                        | Operator::GreaterEqual
+                        | Operator::Less
+                        | Operator::LessEqual
+                ) {
+                    return Err(invalid_node(
+                        "chain",
+                        format!("\"{operator:?}\" is not a comparison operator"),
+                    ));
+                }
+                if is_ascending(*operator) != is_ascending(operators[0]) {
+                    return Err(Error::MixedComparisonChain(operators[0], *operator));
+                }
+            }
+
+            check_all(expressions)
+        }
+        Expression::Array { expressions } => check_all(expressions),
+        Expression::Variable { name } if name.is_empty() => {
+            Err(invalid_node("variable", String::from("the name is empty")))
+        }
+        Expression::Call { name, .. } if name.is_empty() => {
+            Err(invalid_node("call", String::from("the name is empty")))
+        }
+        Expression::Call { name, params } if params.len() > MAX_CALL_PARAMS => Err(invalid_node(
+            "call",
+            format!(
+                "\"{name}\" has {} parameters, at most {MAX_CALL_PARAMS} are allowed",
+                params.len()
+            ),
+        )),
+        Expression::Call { name: _, params } => check_all(params),
+        Expression::Index { target, index } => {
+            check_integrity(target, depth).and_then(|()| check_integrity(index, depth))
+        }
+        Expression::Literal { value: _ } | Expression::Variable { name: _ } => Ok(()),
+    }
+}
+
+/// Finds all [`Call`](Expression::Call) [`Expressions`](Expression) to deprecated
+/// functions by walking the AST. Returns the name of each call in order of
+/// appearance together with its [`Span`] in the source, the deprecation note is
+/// provided by [`Environment::function_deprecation`].
+///
+/// # Examples
+/// ```
+/// use slac::{compile_with_spans, find_deprecated, Span, StaticEnvironment};
+/// use slac::environment::Environment;
+/// use slac::stdlib::extend_environment;
+///
+/// let mut env = StaticEnvironment::default();
+/// extend_environment(&mut env);
+/// env.add_alias("maximum", "max").unwrap();
+///
+/// let (ast, spans) = compile_with_spans("maximum(1, 2) > max(3, 4)").unwrap();
+/// let deprecated = find_deprecated(&env, &ast, &spans);
+///
+/// assert_eq!(
+///     vec![(String::from("maximum"), Span { start: 0, end: 13, line: 1, column: 1 })],
+///     deprecated
+/// );
+/// assert_eq!(
+///     Some(String::from("use \"max\" instead")),
+///     env.function_deprecation("maximum")
+/// );
+/// ```
+#[must_use]
+pub fn find_deprecated(
+    env: &impl Environment,
+    expression: &Expression,
+    spans: &NodeSpans,
+) -> Vec<(String, Span)> {
+    let mut deprecated = vec![];
+
+    visit_post_order(expression, Some(spans), &mut |node, span| {
+        if let (Expression::Call { name, params: _ }, Some(span)) = (node, span) {
+            if env.function_deprecation(name).is_some() {
+                deprecated.push((name.clone(), span));
+            }
+        }
+    });
+
+    // the nodes are visited in post-order, a call precedes its parameters
+    deprecated.sort_by_key(|(_, span)| span.start);
+    deprecated
+}
+
 /// Checks if the top level [`Expression`] produces a [`Value::Boolean`] result.
 ///
 /// # Examples
@@ -80,13 +366,19 @@ fn check_expressions(env: &impl Environment, expressions: &[Expression]) -> Resu
 ///
 /// assert!(check_boolean_result(&ast).is_ok());
 /// ```
+///
+/// # Remarks
+///
+/// Only the top level [`Expression`] is checked, e.g. a function call passes.
+/// Use [`execute_as`](crate::execute_as) to check the actual result.
+///
 /// # Errors
 ///
 /// Returns an [`Error`] when the top most Expression can't evaluate to a [`Value::Boolean`].
 pub fn check_boolean_result(ast: &Expression) -> Result<()> {
     match ast {
         Expression::Unary { right: _, operator } => match operator {
-            Operator::Not => Ok(()),
+            Operator::Not | Operator::IsEmpty | Operator::IsNotEmpty => Ok(()),
             _ => Err(Error::InvalidUnaryOperator(*operator)),
         },
         Expression::Binary {
@@ -100,9 +392,13 @@ pub fn check_boolean_result(ast: &Expression) -> Result<()> {
             | Operator::LessEqual
             | Operator::Equal
             | Operator::NotEqual
+            | Operator::ApproxEqual
             | Operator::And
             | Operator::Or
-            | Operator::Xor => Ok(()),
+            | Operator::Xor
+            | Operator::Implies
+            | Operator::Iff
+            | Operator::In => Ok(()),
             _ => Err(Error::InvalidBinaryOperator(*operator)),
         },
         Expression::Ternary {
@@ -120,17 +416,134 @@ pub fn check_boolean_result(ast: &Expression) -> Result<()> {
             }
             _ => Err(Error::InvalidTernaryOperator(*operator)),
         },
+        Expression::Chain {
+            expressions: _,
+            operators: _,
+        } => Ok(()),
         Expression::Array { expressions: _ } => Err(Error::LiteralNotBoolean),
         Expression::Literal { value } => match value {
             Value::Boolean(_) => Ok(()),
             _ => Err(Error::LiteralNotBoolean),
         },
-        Expression::Variable { name: _ } | Expression::Call { name: _, params: _ } => {
+        Expression::Variable { name: _ }
+        | Expression::Call { name: _, params: _ }
+        | Expression::Index {
+            target: _,
+            index: _,
+        } => {
             Ok(()) // the type is not known
         }
     }
 }
 
+/// Finds `=` and `<>` comparisons of an Array with a scalar literal, like
+/// `roles = 'admin'`. These never match, as values of different kinds are never
+/// equal, and were most likely meant as a `contains` check.
+///
+/// A side is only known to be an Array if it is an array literal or a variable
+/// which holds an Array in the [`Environment`]. Comparisons of two Arrays or
+/// with variables of unknown type are not reported.
+///
+/// # Examples
+/// ```
+/// use slac::{compile_with_spans, lint_array_scalar_equality, Span, StaticEnvironment, Value};
+///
+/// let mut env = StaticEnvironment::default();
+/// env.add_variable("roles", Value::Array(vec![Value::String(String::from("admin"))]));
+///
+/// let (ast, spans) = compile_with_spans("roles = 'admin'").unwrap();
+/// let lints = lint_array_scalar_equality(&env, &ast, Some(&spans));
+///
+/// assert_eq!(1, lints.len());
+/// assert_eq!("array_scalar_equality", lints[0].code);
+/// assert_eq!(Some(Span { start: 0, end: 15, line: 1, column: 1 }), lints[0].span);
+/// assert!(lints[0].message.contains("contains(roles, 'admin')"));
+/// ```
+#[must_use]
+pub fn lint_array_scalar_equality(
+    env: &impl Environment,
+    expression: &Expression,
+    spans: Option<&NodeSpans>,
+) -> Vec<Lint> {
+    let mut lints = vec![];
+
+    visit_post_order(expression, spans, &mut |node, span| {
+        let Expression::Binary {
+            left,
+            right,
+            operator: operator @ (Operator::Equal | Operator::NotEqual),
+        } = node
+        else {
+            return;
+        };
+
+        let (array, scalar) = match (array_source(env, left), scalar_literal(right)) {
+            (Some(array), Some(scalar)) => (array, scalar),
+            _ => match (array_source(env, right), scalar_literal(left)) {
+                (Some(array), Some(scalar)) => (array, scalar),
+                _ => return,
+            },
+        };
+
+        let (outcome, suggestion) = if *operator == Operator::Equal {
+            (
+                "false",
+                format!("contains({array}, {})", scalar.to_literal()),
+            )
+        } else {
+            (
+                "true",
+                format!("not contains({array}, {})", scalar.to_literal()),
+            )
+        };
+
+        lints.push(Lint {
+            code: "array_scalar_equality",
+            message: format!(
+                "comparing the Array {array} with a {} is always {outcome}, use {suggestion} instead",
+                scalar.type_name()
+            ),
+            span,
+        });
+    });
+
+    lints
+}
+
+/// Returns the source of an [`Expression`] which is statically known to be an Array.
+fn array_source(env: &impl Environment, expression: &Expression) -> Option<String> {
+    match expression {
+        Expression::Array { expressions } => {
+            let literals: Option<Vec<String>> = expressions
+                .iter()
+                .map(|expression| match expression {
+                    Expression::Literal { value } => Some(value.to_literal()),
+                    _ => None,
+                })
+                .collect();
+
+            Some(literals.map_or(String::from("[...]"), |literals| {
+                format!("[{}]", literals.join(", "))
+            }))
+        }
+        Expression::Literal {
+            value: value @ Value::Array(_),
+        } => Some(value.to_literal()),
+        Expression::Variable { name } => match env.variable(name).as_deref() {
+            Some(Value::Array(_)) => Some(name.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn scalar_literal(expression: &Expression) -> Option<&Value> {
+    match expression {
+        Expression::Literal { value } if !matches!(value, Value::Array(_)) => Some(value),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
@@ -143,7 +556,7 @@ mod test {
         value::Value,
     };
 
-    use super::check_variables_and_functions;
+    use super::{check_ast_integrity, check_variables_and_functions, MAX_CALL_PARAMS};
 
     #[test]
     fn valid() {
@@ -250,11 +663,50 @@ mod test {
         let result = check_variables_and_functions(&env, &ast);
 
         assert_eq!(
-            Err(Error::ParamCountMismatch(String::from("max"), 0, 2, 2)),
+            Err(Error::ParamCountMismatch {
+                name: String::from("max"),
+                expected_min: 2,
+                expected_max: 2,
+                found: 0,
+            }),
             result
         );
     }
 
+    #[test]
+    fn function_arity() {
+        let mut env = StaticEnvironment::default();
+        env.add_functions([
+            Function::new(dummy_function, Arity::optional(2, 1), "range(a, b, c = 0)"),
+            Function::new(dummy_function, Arity::Variadic, "sum(...)"),
+            Function::new(dummy_function, Arity::None, "now()"),
+        ]);
+
+        let check = |script: &str| {
+            let ast = crate::compile(script).unwrap();
+            check_variables_and_functions(&env, &ast)
+        };
+        let mismatch = |name: &str, expected_min, expected_max, found| {
+            Err(Error::ParamCountMismatch {
+                name: String::from(name),
+                expected_min,
+                expected_max,
+                found,
+            })
+        };
+
+        assert_eq!(Ok(()), check("range(1, 2)"));
+        assert_eq!(Ok(()), check("range(1, 2, 3)"));
+        assert_eq!(mismatch("range", 2, 3, 1), check("range(1)"));
+        assert_eq!(mismatch("range", 2, 3, 4), check("range(1, 2, 3, 4)"));
+
+        assert_eq!(Ok(()), check("sum()"));
+        assert_eq!(Ok(()), check("sum(1, 2, 3, 4, 5)"));
+
+        assert_eq!(Ok(()), check("now()"));
+        assert_eq!(mismatch("now", 0, 0, 1), check("now(1)"));
+    }
+
     #[test]
     fn err_function_nested_params() {
         let ast = Expression::Call {
@@ -278,4 +730,201 @@ mod test {
             result
         );
     }
+
+    #[test]
+    fn err_index_missing_variable() {
+        let ast = crate::compile("values[position]").unwrap();
+
+        let mut env = StaticEnvironment::default();
+        env.add_variable("values", Value::Array(vec![]));
+
+        assert_eq!(
+            Err(Error::MissingVariable(String::from("position"))),
+            check_variables_and_functions(&env, &ast)
+        );
+    }
+
+    fn boolean(value: bool) -> Box<Expression> {
+        Box::new(Expression::Literal {
+            value: Value::Boolean(value),
+        })
+    }
+
+    fn number(value: f64) -> Expression {
+        Expression::Literal {
+            value: Value::Number(value),
+        }
+    }
+
+    #[test]
+    fn integrity_of_compiled_expressions() {
+        for script in [
+            "-a + b * c div d mod e / f - g",
+            "not a is empty and b is not empty or c xor d implies e iff f",
+            "1 < a <= 3 and 4 > b >= c > 0 and x ~= y and z in [1, 2]",
+            "if a then max(b[1], []) else if c then -d else 'e'",
+        ] {
+            let ast = crate::compile(script).unwrap();
+
+            assert_eq!(Ok(()), check_ast_integrity(&ast), "{script}");
+        }
+    }
+
+    #[test]
+    fn integrity_operator_mismatch() {
+        let unary = Expression::Unary {
+            right: boolean(true),
+            operator: Operator::And,
+        };
+        let binary = Expression::Binary {
+            left: boolean(true),
+            right: boolean(false),
+            operator: Operator::Not,
+        };
+        let ternary = Expression::Ternary {
+            left: boolean(true),
+            middle: boolean(true),
+            right: boolean(false),
+            operator: Operator::Or,
+        };
+        let binary_ternary = Expression::Binary {
+            left: boolean(true),
+            right: boolean(false),
+            operator: Operator::TernaryCondition,
+        };
+
+        assert_eq!(
+            Err(Error::InvalidUnaryOperator(Operator::And)),
+            check_ast_integrity(&unary)
+        );
+        assert_eq!(
+            Err(Error::InvalidBinaryOperator(Operator::Not)),
+            check_ast_integrity(&binary)
+        );
+        assert_eq!(
+            Err(Error::InvalidTernaryOperator(Operator::Or)),
+            check_ast_integrity(&ternary)
+        );
+        assert_eq!(
+            Err(Error::InvalidBinaryOperator(Operator::TernaryCondition)),
+            check_ast_integrity(&binary_ternary)
+        );
+
+        // an invalid node deep inside of a valid tree
+        let nested = Expression::Call {
+            name: String::from("max"),
+            params: vec![
+                number(1.0),
+                Expression::Array {
+                    expressions: vec![Expression::Index {
+                        target: Box::new(number(2.0)),
+                        index: Box::new(binary),
+                    }],
+                },
+            ],
+        };
+
+        assert_eq!(
+            Err(Error::InvalidBinaryOperator(Operator::Not)),
+            check_ast_integrity(&nested)
+        );
+    }
+
+    #[test]
+    fn integrity_chain() {
+        let chain = |expressions: usize, operators: Vec<Operator>| Expression::Chain {
+            expressions: (0..expressions).map(|n| number(n as f64)).collect(),
+            operators,
+        };
+        let invalid = |reason: &str| {
+            Err(Error::InvalidNode {
+                kind: "chain",
+                reason: String::from(reason),
+            })
+        };
+
+        assert_eq!(
+            Ok(()),
+            check_ast_integrity(&chain(3, vec![Operator::Less, Operator::LessEqual]))
+        );
+        assert_eq!(
+            invalid("2 operators need 3 operands but found 2"),
+            check_ast_integrity(&chain(2, vec![Operator::Less, Operator::Less]))
+        );
+        assert_eq!(
+            invalid("0 operators need 1 operands but found 1"),
+            check_ast_integrity(&chain(1, vec![]))
+        );
+        assert_eq!(
+            invalid("\"Plus\" is not a comparison operator"),
+            check_ast_integrity(&chain(3, vec![Operator::Less, Operator::Plus]))
+        );
+        assert_eq!(
+            Err(Error::MixedComparisonChain(
+                Operator::Greater,
+                Operator::LessEqual
+            )),
+            check_ast_integrity(&chain(3, vec![Operator::Greater, Operator::LessEqual]))
+        );
+    }
+
+    #[test]
+    fn integrity_calls_and_names() {
+        let call = |name: &str, params: usize| Expression::Call {
+            name: String::from(name),
+            params: vec![number(1.0); params],
+        };
+
+        assert_eq!(Ok(()), check_ast_integrity(&call("max", MAX_CALL_PARAMS)));
+        assert_eq!(
+            Err(Error::InvalidNode {
+                kind: "call",
+                reason: String::from("\"max\" has 256 parameters, at most 255 are allowed")
+            }),
+            check_ast_integrity(&call("max", MAX_CALL_PARAMS + 1))
+        );
+        assert_eq!(
+            Err(Error::InvalidNode {
+                kind: "call",
+                reason: String::from("the name is empty")
+            }),
+            check_ast_integrity(&call("", 1))
+        );
+        assert_eq!(
+            Err(Error::InvalidNode {
+                kind: "variable",
+                reason: String::from("the name is empty")
+            }),
+            check_ast_integrity(&Expression::Variable {
+                name: String::new()
+            })
+        );
+        assert_eq!(
+            "invalid variable node: the name is empty",
+            Error::InvalidNode {
+                kind: "variable",
+                reason: String::from("the name is empty")
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn integrity_depth() {
+        let nested = |depth: usize| {
+            (1..depth).fold(number(1.0), |right, _| Expression::Unary {
+                right: Box::new(right),
+                operator: Operator::Minus,
+            })
+        };
+
+        assert_eq!(
+            Ok(()),
+            check_ast_integrity(&nested(crate::DEFAULT_MAX_DEPTH))
+        );
+        assert_eq!(
+            Err(Error::RecursionLimitExceeded(crate::DEFAULT_MAX_DEPTH)),
+            check_ast_integrity(&nested(crate::DEFAULT_MAX_DEPTH + 1))
+        );
+    }
 }
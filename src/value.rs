@@ -1,25 +1,36 @@
 #[cfg(feature = "serde")]
-use serde::{de::Visitor, ser::SerializeSeq, Deserialize, Serialize};
+use serde::{
+    de::Visitor,
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Serialize,
+};
 
 use std::{
     cmp::Ordering,
     fmt::Display,
     hash::{Hash, Hasher},
-    ops::{Add, BitXor, Div, Mul, Neg, Not, Rem, Sub},
+    ops::{Add, BitXor, Div, Mul, Neg, Not, Range, Rem, Sub},
 };
 
 use crate::{
+    arithmetic,
     error::{self, Error},
+    stdlib::{approx_equal_f64, DEFAULT_EPSILON},
     Operator,
 };
 
-/// A Wrapper for the four different possible variable types.
+/// A Wrapper for the different possible variable types.
 #[derive(Debug, Clone)]
+#[must_use]
 pub enum Value {
     Boolean(bool),
     String(String),
     Number(f64),
     Array(Vec<Value>),
+    /// The explicit absence of a value. `Null` is empty, only equal to itself
+    /// and ordered below every other value. The `=` operator additionally
+    /// treats it as equal to every empty value, see [`Value::equals`].
+    Null,
 }
 impl Eq for Value {}
 
@@ -61,6 +72,8 @@ impl PartialEq for Value {
             (Self::String(l0), Self::String(r0)) => l0 == r0,
             (Self::Number(l0), Self::Number(r0)) => l0 == r0,
             (Self::Array(l0), Self::Array(r0)) => l0 == r0,
+            (Self::Null, Self::Null) => true,
+            (Self::Null, _) | (_, Self::Null) => false,
 
             // allow equality of boolean and numbers by converting the boolean to 0 or 1
             (Value::Boolean(left), Value::Number(right)) => f64::from(*left) == *right,
@@ -83,6 +96,7 @@ impl Neg for Value {
     fn neg(self) -> Self::Output {
         match self {
             Value::Number(value) => Ok(Value::Number(-value)),
+            Value::Null => Ok(Value::Null),
             _ => Err(Error::InvalidUnaryOperator(Operator::Minus)),
         }
     }
@@ -101,8 +115,11 @@ impl Add for Value {
 
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
             (Value::String(lhs), Value::String(rhs)) => Ok(Value::String(lhs + &rhs)),
-            (Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs + rhs)),
+            (Value::Number(lhs), Value::Number(rhs)) => {
+                Ok(Value::Number(arithmetic::add(lhs, rhs)))
+            }
             (Value::Array(lhs), Value::Array(rhs)) => Ok(Value::Array([lhs, rhs].concat())),
             _ => Err(Error::InvalidBinaryOperator(Operator::Plus)),
         }
@@ -114,7 +131,10 @@ impl Sub for Value {
 
     fn sub(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs - rhs)),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Number(lhs), Value::Number(rhs)) => {
+                Ok(Value::Number(arithmetic::sub(lhs, rhs)))
+            }
             _ => Err(Error::InvalidBinaryOperator(Operator::Minus)),
         }
     }
@@ -125,7 +145,10 @@ impl Mul for Value {
 
     fn mul(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs * rhs)),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Number(lhs), Value::Number(rhs)) => {
+                Ok(Value::Number(arithmetic::mul(lhs, rhs)))
+            }
             _ => Err(Error::InvalidBinaryOperator(Operator::Multiply)),
         }
     }
@@ -136,7 +159,10 @@ impl Div for Value {
 
     fn div(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs / rhs)),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Number(lhs), Value::Number(rhs)) => {
+                Ok(Value::Number(arithmetic::div(lhs, rhs)))
+            }
             _ => Err(Error::InvalidBinaryOperator(Operator::Divide)),
         }
     }
@@ -147,7 +173,10 @@ impl Rem for Value {
 
     fn rem(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs % rhs)),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Number(lhs), Value::Number(rhs)) => {
+                Ok(Value::Number(arithmetic::rem(lhs, rhs)))
+            }
             _ => Err(Error::InvalidBinaryOperator(Operator::Mod)),
         }
     }
@@ -158,19 +187,45 @@ impl BitXor for Value {
 
     fn bitxor(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
             (Value::Boolean(lhs), Value::Boolean(rhs)) => Ok(Value::Boolean(lhs ^ rhs)),
             _ => Err(Error::InvalidBinaryOperator(Operator::Xor)),
         }
     }
 }
 
+/// The keyword of the [`Value::Null`] literal.
+pub(crate) const NULL_LITERAL: &str = "null";
+
+/// Numbers with a magnitude inside of `PLAIN_NOTATION` are displayed in
+/// plain decimal notation.
+const PLAIN_NOTATION: Range<f64> = 1e-6..1e15;
+
+/// Formats a number by the rules of [`format_number_auto`](crate::stdlib::math::format_number_auto).
+fn fmt_number(v: f64, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if v.is_nan() {
+        f.write_str("nan")
+    } else if v.is_infinite() {
+        f.write_str(if v > 0.0 { "inf" } else { "-inf" })
+    } else if v == 0.0 {
+        f.write_str("0")
+    } else if PLAIN_NOTATION.contains(&v.abs()) {
+        write!(f, "{v}")
+    } else {
+        write!(f, "{v:e}")
+    }
+}
+
+/// Displays the content of a `Value`, see [`format_number_auto`](crate::stdlib::math::format_number_auto)
+/// for the notation of numbers.
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Value::Boolean(v) => write!(f, "{v}"),
             Value::String(v) => write!(f, "{v}"),
-            Value::Number(v) => write!(f, "{v}"),
+            Value::Number(v) => fmt_number(*v, f),
             Value::Array(v) => write!(f, "{v:?}"),
+            Value::Null => f.write_str(NULL_LITERAL),
         }
     }
 }
@@ -194,11 +249,92 @@ impl Value {
     /// Returns [`Error::InvalidBinaryOperator`] if any side of the operator is not a Number.
     pub fn div_int(self, rhs: Self) -> error::Result<Self> {
         match (self, rhs) {
-            (Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number((lhs / rhs).trunc())),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Number(lhs), Value::Number(rhs)) => {
+                Ok(Value::Number(arithmetic::div(lhs, rhs).trunc()))
+            }
             _ => Err(Error::InvalidBinaryOperator(Operator::Div)),
         }
     }
 
+    /// Equality of the `=` operator. Equal to `==`, except that
+    /// [`Value::Null`] is also equal to every empty value, so `null = ''` and
+    /// `null = 0` are `true`. Only applies to the values themselves, not to
+    /// the elements of an `Array`.
+    ///
+    /// # Examples
+    /// ```
+    /// use slac::Value;
+    ///
+    /// assert!(Value::Null.equals(&Value::String(String::new())));
+    /// assert!(Value::Number(0.0).equals(&Value::Null));
+    /// assert!(!Value::Null.equals(&Value::Number(1.0)));
+    /// assert_ne!(Value::Null, Value::String(String::new()));
+    /// ```
+    #[must_use]
+    pub fn equals(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, value) | (value, Value::Null) => value.is_empty(),
+            _ => self == other,
+        }
+    }
+
+    /// Membership test of the `in` operator. Checks if a `String` is a substring
+    /// of the haystack or if the haystack `Array` contains an element which
+    /// [`equals`](Value::equals) the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use slac::Value;
+    ///
+    /// let haystack = Value::String(String::from("Hello World"));
+    /// let needle = Value::String(String::from("World"));
+    ///
+    /// assert_eq!(Ok(Value::Boolean(true)), needle.contained_in(haystack));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidBinaryOperator`] if the haystack is neither a `String`
+    /// nor an `Array`, or if a `String` haystack is searched for a non `String` value.
+    pub fn contained_in(self, haystack: Self) -> error::Result<Self> {
+        match (self, haystack) {
+            (needle, Value::Array(values)) => Ok(Value::Boolean(
+                values.iter().any(|value| value.equals(&needle)),
+            )),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::String(needle), Value::String(haystack)) => {
+                Ok(Value::Boolean(haystack.contains(&needle)))
+            }
+            _ => Err(Error::InvalidBinaryOperator(Operator::In)),
+        }
+    }
+
+    /// Approximate equality of the `~=` operator. Checks if two `Number` values
+    /// are equal within the relative tolerance [`DEFAULT_EPSILON`].
+    ///
+    /// # Examples
+    /// ```
+    /// use slac::Value;
+    ///
+    /// let sum = Value::Number(0.1 + 0.2);
+    ///
+    /// assert_eq!(Ok(Value::Boolean(true)), sum.approx_equal(Value::Number(0.3)));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidBinaryOperator`] if any side of the operator is not a Number.
+    pub fn approx_equal(self, rhs: Self) -> error::Result<Self> {
+        match (self, rhs) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Number(lhs), Value::Number(rhs)) => {
+                Ok(Value::Boolean(approx_equal_f64(lhs, rhs, DEFAULT_EPSILON)))
+            }
+            _ => Err(Error::InvalidBinaryOperator(Operator::ApproxEqual)),
+        }
+    }
+
     /// Returns the length of a `String` or `Array` `Value`.
     /// `Boolean` and `Number` have a length of 0.
     #[must_use]
@@ -213,7 +349,10 @@ impl Value {
     /// Checks if the value is equal to the result of [`Value::empty()`].
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self == &Value::empty(self)
+        match self {
+            Value::Null => true,
+            value => value == &Value::empty(value),
+        }
     }
 
     /// Returns an new empty `Value` of the same type as the callee.
@@ -221,13 +360,14 @@ impl Value {
     /// * `Value::String` -> `''`
     /// * `Value::Number` -> `0`
     /// * `Value::Array` -> `[]`
-    #[must_use]
+    /// * `Value::Null` -> `null`
     pub fn empty(&self) -> Self {
         match self {
             Value::Boolean(_) => Value::Boolean(false),
             Value::String(_) => Value::String(String::new()),
             Value::Number(_) => Value::Number(0.0),
             Value::Array(_) => Value::Array(vec![]),
+            Value::Null => Value::Null,
         }
     }
 
@@ -242,18 +382,124 @@ impl Value {
         }
     }
 
+    /// Formats the `Value` as source code, which compiles back into an equal `Value`.
+    /// Strings are quoted and use the raw string form `r'...'` if they contain
+    /// a backslash, so the pattern of a regular expression is kept verbatim.
+    ///
+    /// # Remarks
+    ///
+    /// In contrast to [`Display`], which outputs the plain content of a string.
+    ///
+    /// # Examples
+    /// ```
+    /// use slac::Value;
+    ///
+    /// assert_eq!("'It''s'", Value::String(String::from("It's")).to_literal());
+    /// assert_eq!(r"r'\d+'", Value::String(String::from(r"\d+")).to_literal());
+    /// assert_eq!("[1, true]", Value::Array(vec![Value::Number(1.0), Value::Boolean(true)]).to_literal());
+    /// ```
+    #[must_use]
+    pub fn to_literal(&self) -> String {
+        match self {
+            Value::String(v) => {
+                let prefix = if v.contains('\\') { "r" } else { "" };
+                format!("{prefix}'{}'", v.replace('\'', "''"))
+            }
+            Value::Array(v) => {
+                let values: Vec<String> = v.iter().map(Value::to_literal).collect();
+                format!("[{}]", values.join(", "))
+            }
+            // plain decimal notation reads back without a loss of precision
+            Value::Number(v) => v.to_string(),
+            Value::Boolean(v) => v.to_string(),
+            Value::Null => String::from(NULL_LITERAL),
+        }
+    }
+
+    /// Returns the name of the [`Value`] kind.
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Boolean(_) => "Boolean",
+            Value::String(_) => "String",
+            Value::Number(_) => "Number",
+            Value::Array(_) => "Array",
+            Value::Null => "Null",
+        }
+    }
+
+    /// Checks if two values can be ordered without falling back to the ordinal
+    /// ranking of their kinds. Values of the same kind are always comparable,
+    /// a `String` is comparable to a `Number` if it parses to a number.
+    ///
+    /// # Examples
+    /// ```
+    /// use slac::Value;
+    ///
+    /// assert!(Value::Number(5.0).is_comparable(&Value::String(String::from("4"))));
+    /// assert!(!Value::Number(5.0).is_comparable(&Value::String(String::from("abc"))));
+    /// assert!(!Value::Number(1.0).is_comparable(&Value::Boolean(true)));
+    /// ```
+    #[must_use]
+    pub fn is_comparable(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::String(text), Value::Number(_)) | (Value::Number(_), Value::String(text)) => {
+                text.parse::<f64>().is_ok()
+            }
+            _ => self.ordinal() == other.ordinal(),
+        }
+    }
+
     /// Returns an ordinal value for each [`Value`] kind.
     #[must_use]
     fn ordinal(&self) -> u8 {
         match self {
-            Value::Boolean(_) => 0,
-            Value::String(_) => 1,
-            Value::Number(_) => 2,
-            Value::Array(_) => 3,
+            Value::Null => 0,
+            Value::Boolean(_) => 1,
+            Value::String(_) => 2,
+            Value::Number(_) => 3,
+            Value::Array(_) => 4,
         }
     }
 }
 
+/// The error of the strict `TryFrom<Value>` conversions into Rust types,
+/// when the [`Value`] is of another kind.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("expected a {expected} but got a {found}")]
+pub struct ConversionError {
+    pub expected: &'static str,
+    pub found: &'static str,
+}
+
+macro_rules! impl_try_from_value {
+    ($($type:ty => $kind:ident),*) => {$(
+
+        /// Unwraps a [`Value`] of the matching kind, without coercing other kinds.
+        impl TryFrom<Value> for $type {
+            type Error = ConversionError;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                match value {
+                    Value::$kind(inner) => Ok(inner),
+                    value => Err(ConversionError {
+                        expected: stringify!($kind),
+                        found: value.type_name(),
+                    }),
+                }
+            }
+        }
+
+    )*};
+}
+
+impl_try_from_value!(
+    bool => Boolean,
+    f64 => Number,
+    String => String,
+    Vec<Value> => Array
+);
+
 #[cfg(feature = "serde")]
 impl Serialize for Value {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -263,7 +509,14 @@ impl Serialize for Value {
         match self {
             Value::Boolean(v) => serializer.serialize_bool(*v),
             Value::String(v) => serializer.serialize_str(v),
-            Value::Number(v) => serializer.serialize_f64(*v),
+            Value::Null => serializer.serialize_unit(),
+            Value::Number(v) if v.is_finite() => serializer.serialize_f64(*v),
+            // formats like JSON can not represent infinity and NaN
+            Value::Number(v) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(NON_FINITE_KEY, &v.to_string())?;
+                map.end()
+            }
             Value::Array(v) => {
                 let mut seq = serializer.serialize_seq(Some(v.len()))?;
                 for element in v {
@@ -275,6 +528,10 @@ impl Serialize for Value {
     }
 }
 
+/// The key of a map containing a non-finite number as a string, e.g. `{"number": "inf"}`.
+#[cfg(feature = "serde")]
+const NON_FINITE_KEY: &str = "number";
+
 #[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for Value {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -293,7 +550,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     type Value = Value;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(formatter, "a primitive value or list")
+        write!(formatter, "a primitive value, list or null")
     }
 
     fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
@@ -303,6 +560,20 @@ impl<'de> Visitor<'de> for ValueVisitor {
         Ok(Value::Boolean(v))
     }
 
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Null)
+    }
+
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
@@ -351,13 +622,30 @@ impl<'de> Visitor<'de> for ValueVisitor {
 
         Ok(Value::Array(values))
     }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        use serde::de::Error;
+
+        match map.next_entry::<String, String>()? {
+            Some((key, number)) if key == NON_FINITE_KEY => number
+                .parse::<f64>()
+                .map(Value::Number)
+                .map_err(A::Error::custom),
+            _ => Err(A::Error::custom("expected a non-finite number")),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::cmp::Ordering;
+
     use crate::{Error, Operator};
 
-    use super::Value;
+    use super::{ConversionError, Value};
 
     fn test_div_int(divisor: f64) -> Value {
         let a = Value::Number(10.0);
@@ -418,6 +706,78 @@ mod test {
 
         assert_eq!(true, Value::Array(vec![]).is_empty());
         assert_eq!(false, Value::Array(vec![Value::Boolean(true)]).is_empty());
+
+        assert_eq!(true, Value::Null.is_empty());
+        assert_eq!(Value::Null, Value::Null.empty());
+    }
+
+    #[test]
+    fn null() {
+        let empty = [
+            Value::Null,
+            Value::Boolean(false),
+            Value::String(String::new()),
+            Value::Number(0.0),
+            Value::Array(vec![]),
+        ];
+        let filled = [
+            Value::Boolean(true),
+            Value::String(String::from("a")),
+            Value::Number(-1.0),
+            Value::Array(vec![Value::Null]),
+        ];
+
+        for value in &empty {
+            assert!(Value::Null.equals(value), "{value:?}");
+            assert!(value.equals(&Value::Null), "{value:?}");
+        }
+
+        for value in &filled {
+            assert!(!Value::Null.equals(value), "{value:?}");
+            assert!(!value.equals(&Value::Null), "{value:?}");
+        }
+
+        // `==` and the ordering stay consistent, `null` is only equal to itself
+        for value in empty.iter().chain(&filled) {
+            let ordering = if value == &Value::Null {
+                Ordering::Equal
+            } else {
+                Ordering::Less
+            };
+
+            assert_eq!(ordering, Value::Null.cmp(value), "{value:?}");
+            assert_eq!(ordering.reverse(), value.cmp(&Value::Null), "{value:?}");
+            assert_eq!(
+                ordering == Ordering::Equal,
+                Value::Null == *value,
+                "{value:?}"
+            );
+        }
+
+        let mut values = [
+            Value::Number(0.0),
+            Value::Null,
+            Value::String(String::new()),
+            Value::Null,
+        ];
+        values.sort();
+        assert_eq!(Value::Null, values[0]);
+        assert_eq!(Value::Null, values[1]);
+
+        assert!(!Value::Null.as_bool());
+        assert_eq!(0, Value::Null.len());
+        assert_eq!("null", Value::Null.to_literal());
+        assert_eq!("Null", Value::Null.type_name());
+
+        // operators propagate null instead of failing
+        assert_eq!(Ok(Value::Null), -Value::Null);
+        assert_eq!(Ok(Value::Null), Value::Number(1.0) + Value::Null);
+        assert_eq!(Ok(Value::Null), Value::Null - Value::Number(1.0));
+        assert_eq!(Ok(Value::Null), Value::Null * Value::Null);
+        assert_eq!(Ok(Value::Null), Value::Number(1.0) / Value::Null);
+        assert_eq!(Ok(Value::Null), Value::Null % Value::Number(1.0));
+        assert_eq!(Ok(Value::Null), Value::Null.div_int(Value::Number(1.0)));
+        assert_eq!(Ok(Value::Null), Value::Boolean(true) ^ Value::Null);
     }
 
     #[test]
@@ -484,6 +844,117 @@ mod test {
         assert!(Value::Boolean(true) != Value::Number(2.0));
         assert!(Value::Boolean(false) != Value::Number(2.0));
     }
+
+    #[test]
+    fn try_from_value() {
+        let values = [
+            Value::Boolean(true),
+            Value::String(String::from("1")),
+            Value::Number(1.0),
+            Value::Array(vec![Value::Boolean(true)]),
+            Value::Null,
+        ];
+
+        for value in values {
+            let found = value.type_name();
+            let mismatch = |expected| ConversionError { expected, found };
+
+            match &value {
+                Value::Boolean(v) => assert_eq!(Ok(*v), bool::try_from(value.clone())),
+                _ => assert_eq!(Err(mismatch("Boolean")), bool::try_from(value.clone())),
+            }
+            match &value {
+                Value::String(v) => assert_eq!(Ok(v.clone()), String::try_from(value.clone())),
+                _ => assert_eq!(Err(mismatch("String")), String::try_from(value.clone())),
+            }
+            match &value {
+                Value::Number(v) => assert_eq!(Ok(*v), f64::try_from(value.clone())),
+                _ => assert_eq!(Err(mismatch("Number")), f64::try_from(value.clone())),
+            }
+            match &value {
+                Value::Array(v) => assert_eq!(Ok(v.clone()), Vec::<Value>::try_from(value.clone())),
+                _ => assert_eq!(
+                    Err(mismatch("Array")),
+                    Vec::<Value>::try_from(value.clone())
+                ),
+            }
+        }
+
+        let error = ConversionError {
+            expected: "Boolean",
+            found: "Number",
+        };
+        assert_eq!("expected a Boolean but got a Number", error.to_string());
+    }
+
+    #[test]
+    fn display_number() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-12.5, "-12.5"),
+            (0.1 + 0.2, "0.30000000000000004"),
+            // lower bound of the plain notation
+            (1e-6, "0.000001"),
+            (-1e-6, "-0.000001"),
+            (1.5e-6, "0.0000015"),
+            (9.99e-7, "9.99e-7"),
+            (1e-7, "1e-7"),
+            (-1e-7, "-1e-7"),
+            (1e-21, "1e-21"),
+            (f64::MIN_POSITIVE, "2.2250738585072014e-308"),
+            // upper bound of the plain notation
+            (999_999_999_999_999.0, "999999999999999"),
+            (999_999_999_999_999.9, "999999999999999.9"),
+            (1e15, "1e15"),
+            (-1e15, "-1e15"),
+            (9_007_199_254_740_991.0, "9.007199254740991e15"),
+            (1e21, "1e21"),
+            (1.5e300, "1.5e300"),
+            (f64::MAX, "1.7976931348623157e308"),
+            // non-finite numbers
+            (f64::INFINITY, "inf"),
+            (f64::NEG_INFINITY, "-inf"),
+            (f64::NAN, "nan"),
+        ];
+
+        for (number, expected) in cases {
+            assert_eq!(expected, Value::Number(number).to_string(), "{number:?}");
+        }
+    }
+
+    #[test]
+    fn display_number_round_trip() {
+        use crate::{Scanner, Token};
+
+        // a deterministic xorshift over the bits of the plain notation range
+        let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+        let lower = 1e-6_f64.to_bits();
+        let upper = 1e15_f64.to_bits();
+
+        for _ in 0..20_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+
+            let number = f64::from_bits(lower + state % (upper - lower));
+            let text = Value::Number(number).to_string();
+
+            assert!(!text.contains('e'), "{text}");
+            assert_eq!(
+                Ok(vec![Token::Literal(Value::Number(number))]),
+                Scanner::tokenize(&text),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn literal_number_stays_plain() {
+        assert_eq!("1000000000000000000000", Value::Number(1e21).to_literal());
+        assert_eq!("0.0000001", Value::Number(1e-7).to_literal());
+    }
 }
 
 #[cfg(all(test, feature = "serde"))]
@@ -496,7 +967,11 @@ mod test_serde_json {
 
     #[test]
     fn convert_from_json() {
-        assert!(serde_json::from_value::<Value>(json!(null)).is_err());
+        assert_eq!(Value::Null, serde_json::from_value(json!(null)).unwrap());
+        assert!(matches!(
+            serde_json::from_value(json!([null])).unwrap(),
+            Value::Array(values) if matches!(values[..], [Value::Null])
+        ));
 
         assert_eq!(
             Value::Boolean(true),
@@ -519,6 +994,7 @@ mod test_serde_json {
     #[test]
     fn convert_to_json() {
         assert_eq!(json!(true), json!(Value::Boolean(true)));
+        assert_eq!(json!(null), json!(Value::Null));
         assert_eq!(
             json!(String::from("ab")),
             json!(Value::String(String::from("ab")))
@@ -533,4 +1009,28 @@ mod test_serde_json {
             ]))
         );
     }
+
+    #[test]
+    fn non_finite_numbers() {
+        assert_eq!(
+            json!({"number": "inf"}),
+            json!(Value::Number(f64::INFINITY))
+        );
+        assert_eq!(
+            json!({"number": "-inf"}),
+            json!(Value::Number(f64::NEG_INFINITY))
+        );
+        assert_eq!(json!({"number": "NaN"}), json!(Value::Number(f64::NAN)));
+
+        assert_eq!(
+            Value::Number(f64::NEG_INFINITY),
+            serde_json::from_value(json!({"number": "-inf"})).unwrap()
+        );
+        assert!(
+            matches!(serde_json::from_value(json!({"number": "NaN"})).unwrap(), Value::Number(v) if v.is_nan())
+        );
+
+        assert!(serde_json::from_value::<Value>(json!({"number": "abc"})).is_err());
+        assert!(serde_json::from_value::<Value>(json!({"string": "inf"})).is_err());
+    }
 }
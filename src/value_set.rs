@@ -0,0 +1,178 @@
+//! An index for fast membership checks against large [`Value::Array`] variables.
+
+use std::collections::HashSet;
+
+use crate::value::Value;
+
+/// A set of [`Values`](Value) with the same membership semantics as searching
+/// a [`Value::Array`] with [`Value::equals`], including the equality of numbers
+/// with booleans and numeric strings.
+///
+/// # Example
+/// ```
+/// use slac::{Value, ValueSet};
+///
+/// let set = ValueSet::from_iter([Value::Number(1.0), Value::String(String::from("DE"))]);
+///
+/// assert!(set.contains(&Value::String(String::from("DE"))));
+/// assert!(set.contains(&Value::String(String::from("1"))));
+/// assert!(set.contains(&Value::Boolean(true)));
+/// assert!(!set.contains(&Value::String(String::from("FR"))));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ValueSet {
+    booleans: HashSet<bool>,
+    strings: HashSet<String>,
+    /// The bits of all numbers.
+    numbers: HashSet<u64>,
+    /// The bits of all strings which parse to a number.
+    numeric_strings: HashSet<u64>,
+    /// Arrays are compared element by element and are not indexed.
+    arrays: Vec<Vec<Value>>,
+    /// If a [`Value::Null`] was added, which equals every empty value.
+    null: bool,
+    /// If any empty value was added, including [`Value::Null`].
+    empty: bool,
+}
+
+/// Returns the bits of a number, treating `0.0` and `-0.0` as equal.
+/// `NaN` is never equal to any value and is skipped.
+fn number_key(number: f64) -> Option<u64> {
+    if number.is_nan() {
+        None
+    } else if number == 0.0 {
+        Some(0.0_f64.to_bits())
+    } else {
+        Some(number.to_bits())
+    }
+}
+
+fn numeric_string_key(string: &str) -> Option<u64> {
+    string.parse::<f64>().ok().and_then(number_key)
+}
+
+impl ValueSet {
+    /// Adds a [`Value`] to the set.
+    pub fn insert(&mut self, value: Value) {
+        self.empty |= value.is_empty();
+
+        match value {
+            Value::Boolean(v) => {
+                self.booleans.insert(v);
+            }
+            Value::Number(v) => {
+                if let Some(key) = number_key(v) {
+                    self.numbers.insert(key);
+                }
+            }
+            Value::String(v) => {
+                if let Some(key) = numeric_string_key(&v) {
+                    self.numeric_strings.insert(key);
+                }
+                self.strings.insert(v);
+            }
+            Value::Array(v) => {
+                if !self.arrays.contains(&v) {
+                    self.arrays.push(v);
+                }
+            }
+            Value::Null => self.null = true,
+        }
+    }
+
+    /// Checks if the set contains a [`Value`] which is equal to the needle.
+    #[must_use]
+    pub fn contains(&self, needle: &Value) -> bool {
+        if self.null && needle.is_empty() {
+            return true;
+        }
+
+        match needle {
+            Value::Boolean(v) => {
+                self.booleans.contains(v) || self.contains_number(f64::from(u8::from(*v)))
+            }
+            Value::Number(v) => {
+                self.contains_number(*v)
+                    || number_key(*v).is_some_and(|key| self.numeric_strings.contains(&key))
+                    || (*v == 0.0 && self.booleans.contains(&false))
+                    || (*v == 1.0 && self.booleans.contains(&true))
+            }
+            Value::String(v) => {
+                self.strings.contains(v)
+                    || numeric_string_key(v).is_some_and(|key| self.numbers.contains(&key))
+            }
+            Value::Array(v) => self.arrays.contains(v),
+            Value::Null => self.empty,
+        }
+    }
+
+    fn contains_number(&self, number: f64) -> bool {
+        number_key(number).is_some_and(|key| self.numbers.contains(&key))
+    }
+}
+
+impl FromIterator<Value> for ValueSet {
+    fn from_iter<T: IntoIterator<Item = Value>>(iter: T) -> Self {
+        let mut set = ValueSet::default();
+
+        for value in iter {
+            set.insert(value);
+        }
+
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ValueSet;
+    use crate::Value;
+
+    fn values() -> Vec<Value> {
+        let strings = ["", "DE", "1", "1.0", "-0", "0", "NaN", "abc", "true"];
+        let numbers = [0.0, -0.0, 1.0, 2.5, -3.0, f64::NAN, f64::INFINITY];
+        let scalars: Vec<Value> = strings
+            .iter()
+            .map(|s| Value::String((*s).to_string()))
+            .chain(numbers.iter().map(|n| Value::Number(*n)))
+            .chain([Value::Boolean(true), Value::Boolean(false), Value::Null])
+            .collect();
+
+        let arrays = vec![
+            Value::Array(vec![]),
+            Value::Array(vec![Value::Number(1.0)]),
+            Value::Array(vec![Value::Boolean(true)]),
+            Value::Array(vec![Value::String(String::from("1"))]),
+            Value::Array(vec![Value::Array(vec![Value::Number(2.5)])]),
+            Value::Array(vec![Value::Array(vec![Value::String(String::from("2.5"))])]),
+            Value::Array(vec![Value::Null]),
+            Value::Array(vec![Value::Number(0.0)]),
+        ];
+
+        scalars.into_iter().chain(arrays).collect()
+    }
+
+    #[test]
+    fn same_as_array_search() {
+        let values = values();
+
+        // build sets from every single value and from all values
+        let haystacks: Vec<Vec<Value>> = values
+            .iter()
+            .map(|value| vec![value.clone()])
+            .chain([values.clone(), vec![]])
+            .collect();
+
+        for haystack in haystacks {
+            let set = ValueSet::from_iter(haystack.clone());
+
+            for needle in &values {
+                assert_eq!(
+                    haystack.iter().any(|v| v.equals(needle)),
+                    set.contains(needle),
+                    "{needle:?} in {haystack:?}"
+                );
+            }
+        }
+    }
+}
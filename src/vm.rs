@@ -0,0 +1,954 @@
+//! The [`CompiledProgram`], a flat list of instructions for a stack machine,
+//! and the [`VirtualMachine`] executing it.
+//!
+//! The instructions replace the recursion of the [`TreeWalkingInterpreter`]
+//! with jumps, so a program compiled once can be executed for many rows
+//! without visiting any boxed nodes.
+//!
+//! # Undefined variables
+//!
+//! The few places accepting an undefined operand protect it with a
+//! [`Instruction::Catch`] handler, see the [`interpreter`](crate::interpreter)
+//! for the rules. An error unwinds the stack to the innermost matching handler
+//! or ends the execution. A caught undefined operand evaluates to
+//! [`Value::Null`], which has the same results as an undefined operand for
+//! `=`, `<>`, `is empty`, `and` and `implies`. The parameters of a function
+//! call evaluate to [`Value::Null`] only if the function accepts undefined
+//! variables, which is checked when the handler catches.
+
+use std::sync::Arc;
+
+use crate::{
+    ast::Expression,
+    environment::Environment,
+    interpreter::{Operand, TreeWalkingInterpreter},
+    operator::Operator,
+    stdlib::common::{at, AT, CONTAINS, TRY},
+    value::Value,
+    value_set::ValueSet,
+    Error, Result,
+};
+
+/// The errors caught by an [`Instruction::Catch`] handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Catch {
+    /// An [`Error::UndefinedVariable`], the operand evaluates to `null`.
+    Undefined,
+    /// Every error which is not [fatal](Error::is_fatal), used by `try`.
+    Recoverable,
+    /// An [`Error::UndefinedVariable`] in a parameter of the named function,
+    /// caught only if the function accepts undefined variables. The parameter
+    /// evaluates to `null`.
+    Argument(u32),
+}
+
+impl Catch {
+    fn matches(self, error: &Error) -> bool {
+        match self {
+            Catch::Undefined | Catch::Argument(_) => matches!(error, Error::UndefinedVariable(_)),
+            Catch::Recoverable => !error.is_fatal(),
+        }
+    }
+}
+
+/// A literal or variable operand of an [`Instruction::Compare`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Leaf {
+    Constant(u32),
+    Variable(u32),
+}
+
+/// A single instruction of a [`CompiledProgram`]. Indices refer to the
+/// constants, the names or the instructions of the program.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Instruction {
+    /// Pushes a value of the constant pool.
+    Constant(u32),
+    /// Pushes a [`Value::Boolean`].
+    Boolean(bool),
+    /// Pushes the value of a variable.
+    Variable(u32),
+    /// Discards the top value.
+    Pop,
+    /// Replaces the top value with the result of the operator.
+    Unary(Operator),
+    /// Replaces the two top values with the result of the operator.
+    Binary(Operator),
+    /// Compares a variable and a literal without cloning them and pushes the
+    /// result, see [`TreeWalkingInterpreter::compare_operands`].
+    Compare {
+        operator: Operator,
+        left: Leaf,
+        right: Leaf,
+    },
+    /// Adds the top value to the sum of a `+` chain, a string sum only
+    /// accepts further strings.
+    PlusChain,
+    /// Checks if the top value is in the variable, answered by its
+    /// [`ValueSet`] if it has one.
+    InVariable(u32),
+    /// Pushes the value of the variable, or a placeholder if the variable has
+    /// a [`ValueSet`] which answers the following [`Instruction::Contains`].
+    /// A boolean above the value tells them apart.
+    Haystack(u32),
+    /// Checks if the top value is in the set of the haystack, otherwise calls
+    /// `contains` with the haystack and the top value.
+    Contains(u32),
+    /// Replaces the top values with the result of a function call.
+    Call {
+        name: u32,
+        params: u32,
+    },
+    /// Replaces the top values with an array of them.
+    Array(u32),
+    /// Replaces the two top values with the element of the array.
+    Index,
+    /// Converts the top value to a [`Value::Boolean`].
+    ToBoolean,
+    /// Converts the top value to a [`Value::Boolean`], if the value below is
+    /// `true`. The value below is discarded.
+    ToBooleanIf,
+    /// Compares the two top values of a chained comparison. Jumps to the
+    /// target if the comparison fails, otherwise keeps the right value as the
+    /// left value of the next comparison.
+    Chain {
+        operator: Operator,
+        target: u32,
+    },
+    Jump(u32),
+    /// Pops the top value and jumps if it is `false`.
+    JumpIfFalse(u32),
+    /// Pops the top value and jumps if it is `true`.
+    JumpIfTrue(u32),
+    /// Installs a handler for the errors of the following instructions.
+    Catch {
+        catch: Catch,
+        handler: u32,
+    },
+    /// Removes the innermost handler.
+    EndCatch,
+    /// Fails with an [`Error::InvalidTernaryOperator`].
+    InvalidTernary(Operator),
+}
+
+/// An [`Expression`] compiled into a flat list of instructions for a stack
+/// machine, with a pool of its literals and names. Execute it with
+/// [`execute_compiled`](crate::execute_compiled).
+///
+/// The execution always has the same result as [`execute`](crate::execute)
+/// on the [`Expression`] the program was compiled from, including the short
+/// circuit evaluation and the rules for undefined variables. Whether it
+/// executes faster than the [`Expression`] depends on the expression, the
+/// `compiled` benchmark compares both.
+///
+/// The instructions are executed without recursion, so an expression nested
+/// deeper than the [`DEFAULT_MAX_DEPTH`](crate::DEFAULT_MAX_DEPTH) does not
+/// fail with an [`Error::RecursionLimitExceeded`].
+///
+/// # Example
+/// ```
+/// use slac::{compile, execute_compiled, CompiledProgram, StaticEnvironment, Value};
+///
+/// let program = CompiledProgram::from(compile("price > 10 and price < 100").unwrap());
+/// let mut env = StaticEnvironment::default();
+///
+/// for price in [5.0, 50.0] {
+///     env.add_variable("price", Value::Number(price));
+///     assert_eq!(Ok(Value::Boolean(price == 50.0)), execute_compiled(&env, &program));
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[must_use]
+pub struct CompiledProgram {
+    instructions: Vec<Instruction>,
+    constants: Vec<Value>,
+    names: Vec<String>,
+}
+
+/// Converts an index into the `u32` used by a [`CompiledProgram`].
+fn index(index: usize) -> u32 {
+    u32::try_from(index).expect("a CompiledProgram holds at most u32::MAX instructions")
+}
+
+impl CompiledProgram {
+    /// The number of instructions.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// A program compiled from an [`Expression`] is never empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.instructions.push(instruction);
+        self.instructions.len() - 1
+    }
+
+    /// Points the jump or handler at `at` to the next instruction.
+    fn patch(&mut self, at: usize) {
+        let next = index(self.instructions.len());
+
+        match &mut self.instructions[at] {
+            Instruction::Jump(target)
+            | Instruction::JumpIfFalse(target)
+            | Instruction::JumpIfTrue(target)
+            | Instruction::Chain { target, .. }
+            | Instruction::Catch {
+                handler: target, ..
+            } => *target = next,
+            instruction => unreachable!("{instruction:?} has no target"),
+        }
+    }
+
+    fn name(&mut self, name: &str) -> u32 {
+        let position = self.names.iter().position(|known| known == name);
+
+        index(position.unwrap_or_else(|| {
+            self.names.push(name.to_string());
+            self.names.len() - 1
+        }))
+    }
+
+    /// The names of the variables and functions, an [`Instruction`] refers to
+    /// a name by its position.
+    pub(crate) fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    fn constant(&mut self, value: &Value) -> u32 {
+        self.constants.push(value.clone());
+        index(self.constants.len() - 1)
+    }
+
+    fn leaf(&mut self, expression: &Expression) -> Leaf {
+        match expression {
+            Expression::Literal { value } => Leaf::Constant(self.constant(value)),
+            Expression::Variable { name } => Leaf::Variable(self.name(name)),
+            _ => unreachable!("only literals and variables are leaves"),
+        }
+    }
+
+    fn push(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Unary { right, operator } => self.unary(right, *operator),
+            Expression::Binary {
+                left,
+                right,
+                operator,
+            } => self.binary(left, right, *operator),
+            Expression::Ternary {
+                left,
+                middle,
+                right,
+                operator: Operator::TernaryCondition,
+            } => {
+                self.push(left);
+                let otherwise = self.emit(Instruction::JumpIfFalse(0));
+                self.push(middle);
+                let end = self.emit(Instruction::Jump(0));
+                self.patch(otherwise);
+                self.push(right);
+                self.patch(end);
+            }
+            Expression::Ternary { operator, .. } => {
+                self.emit(Instruction::InvalidTernary(*operator));
+            }
+            Expression::Chain {
+                expressions,
+                operators,
+            } => self.chain(expressions, operators),
+            Expression::Array { expressions } => {
+                expressions
+                    .iter()
+                    .for_each(|expression| self.push(expression));
+                self.emit(Instruction::Array(index(expressions.len())));
+            }
+            Expression::Literal { value } => {
+                let constant = self.constant(value);
+                self.emit(Instruction::Constant(constant));
+            }
+            Expression::Variable { name } => {
+                let name = self.name(name);
+                self.emit(Instruction::Variable(name));
+            }
+            Expression::Call { name, params } => self.call(name, params),
+            Expression::Index { target, index } => {
+                self.push(target);
+                self.push(index);
+                self.emit(Instruction::Index);
+            }
+        }
+    }
+
+    /// Pushes an operand, which evaluates to `null` if it is undefined.
+    fn push_protected(&mut self, expression: &Expression) {
+        if let Some(catch) = self.push_catching(expression) {
+            self.patch(catch);
+        }
+    }
+
+    /// Pushes an operand with an [`Catch::Undefined`] handler, if the operand
+    /// may be undefined. Returns the handler to patch.
+    fn push_catching(&mut self, expression: &Expression) -> Option<usize> {
+        if !may_be_undefined(expression) {
+            self.push(expression);
+            return None;
+        }
+
+        let catch = self.emit(Instruction::Catch {
+            catch: Catch::Undefined,
+            handler: 0,
+        });
+        self.push(expression);
+        self.emit(Instruction::EndCatch);
+        Some(catch)
+    }
+
+    /// Pushes a parameter of a function call, which evaluates to `null` if it
+    /// is undefined and the function accepts undefined variables.
+    fn push_argument(&mut self, expression: &Expression, function: u32) {
+        if !may_be_undefined(expression) {
+            self.push(expression);
+            return;
+        }
+
+        let catch = self.emit(Instruction::Catch {
+            catch: Catch::Argument(function),
+            handler: 0,
+        });
+        self.push(expression);
+        self.emit(Instruction::EndCatch);
+        self.patch(catch);
+    }
+
+    fn unary(&mut self, right: &Expression, operator: Operator) {
+        match operator {
+            Operator::IsEmpty | Operator::IsNotEmpty => self.push_protected(right),
+            _ => self.push(right),
+        }
+
+        self.emit(Instruction::Unary(operator));
+    }
+
+    fn binary(&mut self, left: &Expression, right: &Expression, operator: Operator) {
+        match (operator, left, right) {
+            (
+                Operator::Equal
+                | Operator::NotEqual
+                | Operator::Greater
+                | Operator::GreaterEqual
+                | Operator::Less
+                | Operator::LessEqual,
+                Expression::Variable { .. } | Expression::Literal { .. },
+                Expression::Literal { .. },
+            )
+            | (
+                Operator::Equal
+                | Operator::NotEqual
+                | Operator::Greater
+                | Operator::GreaterEqual
+                | Operator::Less
+                | Operator::LessEqual,
+                Expression::Literal { .. },
+                Expression::Variable { .. },
+            ) => {
+                let left = self.leaf(left);
+                let right = self.leaf(right);
+                self.emit(Instruction::Compare {
+                    operator,
+                    left,
+                    right,
+                });
+            }
+            (Operator::And | Operator::Implies, _, _) => {
+                // `undefined and right` is false and `undefined implies right` is true
+                self.push_protected(left);
+                let short_circuit = self.emit(Instruction::JumpIfFalse(0));
+                self.push(right);
+                self.emit(Instruction::ToBoolean);
+                let end = self.emit(Instruction::Jump(0));
+                self.patch(short_circuit);
+                self.emit(Instruction::Boolean(operator == Operator::Implies));
+                self.patch(end);
+            }
+            (Operator::Or, _, _) => {
+                // `undefined or right` is the result of `right` as is, the
+                // caught `null` tells `ToBooleanIf` to keep it
+                let catch = self.push_catching(left);
+                let short_circuit = self.emit(Instruction::JumpIfTrue(0));
+                self.emit(Instruction::Boolean(true));
+                if let Some(catch) = catch {
+                    self.patch(catch);
+                }
+                self.push(right);
+                self.emit(Instruction::ToBooleanIf);
+                let end = self.emit(Instruction::Jump(0));
+                self.patch(short_circuit);
+                self.emit(Instruction::Boolean(true));
+                self.patch(end);
+            }
+            (Operator::Equal | Operator::NotEqual, _, _) => {
+                self.push_protected(left);
+                self.push_protected(right);
+                self.emit(Instruction::Binary(operator));
+            }
+            (
+                Operator::Plus,
+                Expression::Binary {
+                    operator: Operator::Plus,
+                    ..
+                },
+                _,
+            ) => self.plus_chain(left, right),
+            (Operator::In, _, Expression::Variable { name }) => {
+                self.push(left);
+                let name = self.name(name);
+                self.emit(Instruction::InVariable(name));
+            }
+            _ => {
+                self.push(left);
+                self.push(right);
+                self.emit(Instruction::Binary(operator));
+            }
+        }
+    }
+
+    /// See [`TreeWalkingInterpreter::plus_chain`], the operands are added one
+    /// by one, so an operand of the wrong type stops the evaluation.
+    fn plus_chain(&mut self, left: &Expression, right: &Expression) {
+        let mut operands = vec![right];
+        let mut first = left;
+
+        while let Expression::Binary {
+            left,
+            right,
+            operator: Operator::Plus,
+        } = first
+        {
+            operands.push(right);
+            first = left;
+        }
+
+        self.push(first);
+
+        for operand in operands.into_iter().rev() {
+            self.push(operand);
+            self.emit(Instruction::PlusChain);
+        }
+    }
+
+    fn chain(&mut self, expressions: &[Expression], operators: &[Operator]) {
+        let Some((first, expressions)) = expressions.split_first() else {
+            self.emit(Instruction::Boolean(true));
+            return;
+        };
+
+        self.push(first);
+
+        let mut failed = vec![];
+        for (operator, right) in operators.iter().zip(expressions) {
+            self.push(right);
+            failed.push(self.emit(Instruction::Chain {
+                operator: *operator,
+                target: 0,
+            }));
+        }
+
+        self.emit(Instruction::Pop);
+        self.emit(Instruction::Boolean(true));
+
+        if !failed.is_empty() {
+            let end = self.emit(Instruction::Jump(0));
+            failed.into_iter().for_each(|at| self.patch(at));
+            self.emit(Instruction::Boolean(false));
+            self.patch(end);
+        }
+    }
+
+    fn call(&mut self, name: &str, params: &[Expression]) {
+        if let [first, second] = params {
+            if name.eq_ignore_ascii_case(TRY) {
+                let catch = self.emit(Instruction::Catch {
+                    catch: Catch::Recoverable,
+                    handler: 0,
+                });
+                self.push(first);
+                self.emit(Instruction::EndCatch);
+                let end = self.emit(Instruction::Jump(0));
+                self.patch(catch);
+                self.push(second);
+                self.patch(end);
+                return;
+            }
+
+            if let (true, Expression::Variable { name: haystack }) =
+                (name.eq_ignore_ascii_case(CONTAINS), first)
+            {
+                let haystack = self.name(haystack);
+                self.emit(Instruction::Haystack(haystack));
+                self.push(second);
+                let name = self.name(name);
+                self.emit(Instruction::Contains(name));
+                return;
+            }
+        }
+
+        let name = self.name(name);
+        params
+            .iter()
+            .for_each(|param| self.push_argument(param, name));
+        self.emit(Instruction::Call {
+            name,
+            params: index(params.len()),
+        });
+    }
+}
+
+/// Checks if the evaluation of an [`Expression`] may fail with an
+/// [`Error::UndefinedVariable`]. An operand which is never undefined needs no
+/// [`Instruction::Catch`] handler.
+fn may_be_undefined(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal { .. } => false,
+        Expression::Variable { .. } => true,
+        Expression::Unary {
+            operator: Operator::IsEmpty | Operator::IsNotEmpty,
+            ..
+        }
+        | Expression::Binary {
+            operator: Operator::Equal | Operator::NotEqual,
+            ..
+        } => false,
+        Expression::Binary {
+            left,
+            right,
+            operator,
+        } => match operator {
+            Operator::And | Operator::Or | Operator::Implies => may_be_undefined(right),
+            _ => may_be_undefined(left) || may_be_undefined(right),
+        },
+        Expression::Unary { right, .. } => may_be_undefined(right),
+        Expression::Ternary {
+            left,
+            middle,
+            right,
+            ..
+        } => [left, middle, right]
+            .into_iter()
+            .any(|e| may_be_undefined(e)),
+        Expression::Chain { expressions, .. } | Expression::Array { expressions } => {
+            expressions.iter().any(may_be_undefined)
+        }
+        Expression::Call { name, params } => match params.as_slice() {
+            [_, fallback] if name.eq_ignore_ascii_case(TRY) => may_be_undefined(fallback),
+            params => params.iter().any(may_be_undefined),
+        },
+        Expression::Index { target, index } => may_be_undefined(target) || may_be_undefined(index),
+    }
+}
+
+impl From<&Expression> for CompiledProgram {
+    fn from(expression: &Expression) -> Self {
+        let mut program = CompiledProgram {
+            instructions: Vec::new(),
+            constants: Vec::new(),
+            names: Vec::new(),
+        };
+        program.push(expression);
+        program
+    }
+}
+
+impl From<Expression> for CompiledProgram {
+    fn from(expression: Expression) -> Self {
+        Self::from(&expression)
+    }
+}
+
+/// An active [`Instruction::Catch`] handler with the stack heights to unwind to.
+struct Handler {
+    catch: Catch,
+    target: u32,
+    stack: usize,
+    sets: usize,
+}
+
+/// The row values of the variables during columnar execution, by the position
+/// of their name in the [`CompiledProgram`]. A name without a column, like the
+/// name of a function, is `None`.
+pub(crate) type ColumnSlots<'c> = [Option<&'c [Value]>];
+
+/// Executes a [`CompiledProgram`]. Follows the exact same rules as the
+/// [`TreeWalkingInterpreter`], which it uses for variables, comparisons and
+/// function calls.
+pub(crate) struct VirtualMachine<'a, 'p> {
+    interpreter: TreeWalkingInterpreter<'a>,
+    program: &'p CompiledProgram,
+    /// The columns of a columnar execution, which take precedence over the
+    /// variables of the [`Environment`].
+    columns: &'p ColumnSlots<'p>,
+    row: usize,
+    stack: Vec<Value>,
+    handlers: Vec<Handler>,
+    /// The sets of the pending [`Instruction::Contains`] with a set-backed haystack.
+    sets: Vec<Arc<ValueSet>>,
+}
+
+impl<'a, 'p> VirtualMachine<'a, 'p> {
+    pub fn execute(env: &'a impl Environment, program: &'p CompiledProgram) -> Result<Value> {
+        Self::execute_row(env, program, &[], 0)
+    }
+
+    /// Executes the program for a single row of the [`ColumnSlots`].
+    pub fn execute_row(
+        env: &'a impl Environment,
+        program: &'p CompiledProgram,
+        columns: &'p ColumnSlots<'p>,
+        row: usize,
+    ) -> Result<Value> {
+        let mut machine = VirtualMachine {
+            interpreter: TreeWalkingInterpreter::new(env),
+            program,
+            columns,
+            row,
+            stack: Vec::new(),
+            handlers: Vec::new(),
+            sets: Vec::new(),
+        };
+
+        machine.run()
+    }
+
+    fn run(&mut self) -> Result<Value> {
+        let mut next = 0;
+
+        while let Some(instruction) = self.program.instructions.get(next) {
+            next += 1;
+
+            match self.step(*instruction) {
+                Ok(Some(target)) => next = target as usize,
+                Ok(None) => (),
+                Err(error) => next = self.unwind(error)? as usize,
+            }
+        }
+
+        Ok(self.pop())
+    }
+
+    /// Finds the innermost handler catching the error and restores the stack
+    /// of its operand. Returns the error if no handler catches it.
+    fn unwind(&mut self, error: Error) -> Result<u32> {
+        while let Some(handler) = self.handlers.pop() {
+            if handler.catch.matches(&error) && self.accepts(handler.catch) {
+                self.stack.truncate(handler.stack);
+                self.sets.truncate(handler.sets);
+
+                if matches!(handler.catch, Catch::Undefined | Catch::Argument(_)) {
+                    self.stack.push(Value::Null);
+                }
+
+                return Ok(handler.target);
+            }
+        }
+
+        Err(error)
+    }
+
+    /// Checks if the function of a [`Catch::Argument`] accepts undefined variables.
+    fn accepts(&self, catch: Catch) -> bool {
+        match catch {
+            Catch::Argument(function) => self.interpreter.accepts_undefined(self.name(function)),
+            Catch::Undefined | Catch::Recoverable => true,
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack
+            .pop()
+            .expect("the instructions are balanced, every pop follows a push")
+    }
+
+    fn pop_many(&mut self, count: u32) -> Vec<Value> {
+        self.stack.split_off(self.stack.len() - count as usize)
+    }
+
+    fn name(&self, name: u32) -> &'p str {
+        &self.program.names[name as usize]
+    }
+
+    /// The value of a variable in the current row of its column.
+    fn column(&self, name: u32) -> Option<&'p Value> {
+        let values = (*self.columns.get(name as usize)?)?;
+
+        Some(&values[self.row])
+    }
+
+    /// The [`ValueSet`] of a variable, columns are never backed by a set.
+    fn variable_set(&self, name: u32) -> Option<Arc<ValueSet>> {
+        match self.column(name) {
+            Some(_) => None,
+            None => self.interpreter.variable_set(self.name(name)),
+        }
+    }
+
+    fn variable(&self, name: u32) -> Result<Value> {
+        match self.column(name) {
+            Some(value) => Ok(value.clone()),
+            None => self.interpreter.variable(self.name(name)),
+        }
+    }
+
+    /// Executes a single instruction, returns the target of a jump.
+    fn step(&mut self, instruction: Instruction) -> Result<Option<u32>> {
+        let value = match instruction {
+            Instruction::Constant(constant) => self.program.constants[constant as usize].clone(),
+            Instruction::Boolean(value) => Value::Boolean(value),
+            Instruction::Variable(name) => self.variable(name)?,
+            Instruction::Pop => {
+                self.stack.pop();
+                return Ok(None);
+            }
+            Instruction::Unary(operator) => match (operator, self.pop()) {
+                (Operator::Minus, right) => (-right)?,
+                (Operator::Not, right) => (!right)?,
+                (Operator::IsEmpty, right) => Value::Boolean(right.is_empty()),
+                (Operator::IsNotEmpty, right) => Value::Boolean(!right.is_empty()),
+                _ => return Err(Error::InvalidUnaryOperator(operator)),
+            },
+            Instruction::Binary(operator) => {
+                let right = self.pop();
+                let left = self.pop();
+                self.interpreter.operate(operator, left, right)?
+            }
+            Instruction::Compare {
+                operator,
+                left,
+                right,
+            } => self.compare_leaves(operator, left, right)?,
+            Instruction::PlusChain => {
+                let right = self.pop();
+
+                match (self.pop(), right) {
+                    (Value::String(mut sum), Value::String(right)) => {
+                        sum.push_str(&right);
+                        Value::String(sum)
+                    }
+                    (sum, right) => (sum + right)?,
+                }
+            }
+            Instruction::InVariable(name) => {
+                let left = self.pop();
+
+                match self.variable_set(name) {
+                    Some(set) => Value::Boolean(set.contains(&left)),
+                    None => left.contained_in(self.variable(name)?)?,
+                }
+            }
+            Instruction::Haystack(name) => {
+                if let Some(set) = self.variable_set(name) {
+                    self.sets.push(set);
+                    self.stack.push(Value::Null);
+                    Value::Boolean(true)
+                } else {
+                    let values = self.variable(name)?;
+                    self.stack.push(values);
+                    Value::Boolean(false)
+                }
+            }
+            Instruction::Contains(name) => {
+                let needle = self.pop();
+                let has_set = self.pop();
+                let values = self.pop();
+
+                if has_set.as_bool() {
+                    let set = self
+                        .sets
+                        .pop()
+                        .expect("a set for every set-backed haystack");
+                    Value::Boolean(set.contains(&needle))
+                } else {
+                    self.interpreter
+                        .call_native(self.name(name), &[values, needle])?
+                }
+            }
+            Instruction::Call { name, params } => {
+                let params = self.pop_many(params);
+                self.interpreter.call_native(self.name(name), &params)?
+            }
+            Instruction::Array(len) => Value::Array(self.pop_many(len)),
+            Instruction::Index => {
+                let index = self.pop();
+                let target = self.pop();
+                at(&[target, index]).map_err(|e| Error::NativeFunctionError(AT.to_string(), e))?
+            }
+            Instruction::ToBoolean => Value::Boolean(self.pop().as_bool()),
+            Instruction::ToBooleanIf => {
+                let value = self.pop();
+
+                if self.pop().as_bool() {
+                    Value::Boolean(value.as_bool())
+                } else {
+                    value
+                }
+            }
+            Instruction::Chain { operator, target } => {
+                let right = self.pop();
+                let left = self.pop();
+
+                if !self.interpreter.compare(operator, &left, &right)?.as_bool() {
+                    return Ok(Some(target));
+                }
+
+                right
+            }
+            Instruction::Jump(target) => return Ok(Some(target)),
+            Instruction::JumpIfFalse(target) => {
+                return Ok((!self.pop().as_bool()).then_some(target));
+            }
+            Instruction::JumpIfTrue(target) => {
+                return Ok(self.pop().as_bool().then_some(target));
+            }
+            Instruction::Catch { catch, handler } => {
+                self.handlers.push(Handler {
+                    catch,
+                    target: handler,
+                    stack: self.stack.len(),
+                    sets: self.sets.len(),
+                });
+                return Ok(None);
+            }
+            Instruction::EndCatch => {
+                self.handlers.pop();
+                return Ok(None);
+            }
+            Instruction::InvalidTernary(operator) => {
+                return Err(Error::InvalidTernaryOperator(operator))
+            }
+        };
+
+        self.stack.push(value);
+        Ok(None)
+    }
+
+    fn leaf(&self, leaf: Leaf) -> Result<Operand<'_>> {
+        match leaf {
+            Leaf::Constant(constant) => Ok(Operand::Borrowed(
+                &self.program.constants[constant as usize],
+            )),
+            Leaf::Variable(name) => match self.column(name) {
+                Some(value) => Ok(Operand::Borrowed(value)),
+                None => self.interpreter.lookup(self.name(name)),
+            },
+        }
+    }
+
+    /// See [`TreeWalkingInterpreter::compare_operands`].
+    fn compare_leaves(&self, operator: Operator, left: Leaf, right: Leaf) -> Result<Value> {
+        self.interpreter
+            .compare_operands(operator, self.leaf(left), || self.leaf(right))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        compile,
+        function::{Arity, Function},
+        interpreter::TreeWalkingInterpreter,
+        stdlib::extend_environment,
+        value::Value,
+        vm::{CompiledProgram, VirtualMachine},
+        StaticEnvironment,
+    };
+
+    fn assert_same(env: &StaticEnvironment, source: &str) {
+        let ast = compile(source).unwrap();
+        let program = CompiledProgram::from(&ast);
+
+        assert_eq!(
+            TreeWalkingInterpreter::interprete(env, &ast),
+            VirtualMachine::execute(env, &program),
+            "{source}"
+        );
+    }
+
+    #[test]
+    fn undefined_operands() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+        env.add_variable("flag", Value::Boolean(false));
+
+        for source in [
+            "missing or 5",
+            "flag or 5",
+            "missing or missing",
+            "missing and 5",
+            "missing implies 5",
+            "missing + 1 is empty",
+            "(missing = '') and (missing <> 1)",
+            "max(missing, 1) = 0",
+            "[1, missing] = []",
+            "(missing < 1) = true",
+            "not (missing = missing)",
+        ] {
+            assert_same(&env, source);
+        }
+    }
+
+    #[test]
+    fn nested_handlers() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        for source in [
+            "try(1 + (missing = 'a' + 1), 2)",
+            "try(missing, try(1 / 'a', 3))",
+            "try((missing or 1 + 'a') = 1, 4) + 1",
+            "[try(at([1], 5), 0), missing is empty, 1 < 2 < try(missing, 3)]",
+            "if_then(try(missing, false), 1, 2)",
+            "try(contains(missing, 1), true)",
+        ] {
+            assert_same(&env, source);
+        }
+    }
+
+    #[test]
+    fn value_set_haystack() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+        env.add_set_var("codes", [Value::Number(1.0), Value::Number(2.0)]);
+        env.add_function(Function::impure(
+            |_| Err("no value".into()),
+            Arity::None,
+            "fail(): Number",
+        ));
+
+        for source in [
+            "contains(codes, 2)",
+            "contains(codes, 3)",
+            "2 in codes",
+            "contains(codes, fail())",
+            "try(contains(codes, fail()), 5) + contains(codes, 1)",
+        ] {
+            assert_same(&env, source);
+        }
+    }
+
+    #[test]
+    fn plus_chain() {
+        let env = StaticEnvironment::default();
+
+        for source in [
+            "'a' + 'b' + 'c'",
+            "'a' + 1 + missing",
+            "1 + 2 + 3",
+            "'a' + 'b' + null",
+        ] {
+            assert_same(&env, source);
+        }
+    }
+}
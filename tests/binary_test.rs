@@ -0,0 +1,190 @@
+#[cfg(feature = "binary")]
+mod test {
+    use slac::{
+        compile, from_bytes, optimize, stdlib::extend_environment, to_bytes, Error, Expression,
+        Operator, StaticEnvironment, Value, BINARY_FORMAT_VERSION, DEFAULT_MAX_DEPTH,
+    };
+
+    fn assert_roundtrip(input: &Expression) {
+        let bytes = to_bytes(input);
+
+        assert_eq!(Some(&BINARY_FORMAT_VERSION), bytes.first());
+        assert_eq!(Ok(input.clone()), from_bytes(&bytes));
+    }
+
+    fn test_roundtrip(script: &str) {
+        assert_roundtrip(&compile(script).unwrap());
+    }
+
+    fn literal(value: Value) -> Expression {
+        Expression::Literal { value }
+    }
+
+    #[test]
+    fn roundtrip_expressions() {
+        test_roundtrip("-10");
+        test_roundtrip("not a");
+        test_roundtrip("a is empty and b is not empty");
+        test_roundtrip("1 + 2 * 3 div 4 mod 5 - 6 / 7");
+        test_roundtrip("a = b <> c ~= d");
+        test_roundtrip("a or b xor c implies d iff e");
+        test_roundtrip("1 < a <= 10");
+        test_roundtrip("3 > b >= c > 0");
+        test_roundtrip("'x' in ['x', 'y', [1, 2, []]]");
+        test_roundtrip("max(1, 2, min(3, 4)) + now()");
+        test_roundtrip("values[1][index - 1]");
+        test_roundtrip("'text'[2]");
+        test_roundtrip("if a then b else c");
+        test_roundtrip("if a then if b then 1 else 2 else if c then 3 else if d then 4 else 5");
+        test_roundtrip("(if a then [1, 2] else []) [if b then 1 else 2]");
+    }
+
+    #[test]
+    fn roundtrip_operators() {
+        for operator in Operator::ALL {
+            assert_roundtrip(&Expression::Binary {
+                left: Box::new(literal(Value::Number(1.0))),
+                right: Box::new(literal(Value::Number(2.0))),
+                operator: *operator,
+            });
+        }
+    }
+
+    #[test]
+    fn roundtrip_values() {
+        let values = [
+            Value::Null,
+            Value::Boolean(true),
+            Value::Boolean(false),
+            Value::Number(0.0),
+            Value::Number(-0.5),
+            Value::Number(f64::MAX),
+            Value::Number(f64::MIN_POSITIVE),
+            Value::String(String::new()),
+            Value::String(String::from("Grüße, 世界 🦀")),
+            Value::String("long".repeat(100)),
+            Value::Array(vec![]),
+            Value::Array(vec![
+                Value::Null,
+                Value::Number(42.0),
+                Value::Array(vec![Value::String(String::from("ä")), Value::Boolean(true)]),
+            ]),
+        ];
+
+        for value in values {
+            assert_roundtrip(&literal(value));
+        }
+
+        test_roundtrip("'Größe' + 'ünïcödé' + '🦀'");
+        test_roundtrip("näme + 名前");
+    }
+
+    #[test]
+    fn roundtrip_non_finite_numbers() {
+        for number in [f64::INFINITY, f64::NEG_INFINITY, -0.0] {
+            let bytes = to_bytes(&literal(Value::Number(number)));
+
+            match from_bytes(&bytes) {
+                Ok(Expression::Literal {
+                    value: Value::Number(result),
+                }) => assert_eq!(number.to_bits(), result.to_bits()),
+                result => panic!("unexpected {result:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrip_optimized() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        let mut ast = compile("[1, 2, 'three'] = array(1, 2, 'three') and b").unwrap();
+        optimize(&env, &mut ast).unwrap();
+
+        assert_roundtrip(&ast);
+    }
+
+    #[test]
+    fn compact_encoding() {
+        let ast = compile("price * 1.19 > 100 and contains(tags, 'vip')").unwrap();
+        let bytes = to_bytes(&ast);
+
+        assert_eq!(57, bytes.len());
+    }
+
+    #[test]
+    fn reject_unsupported_version() {
+        let mut bytes = to_bytes(&compile("1 + 2").unwrap());
+        bytes[0] = BINARY_FORMAT_VERSION + 1;
+
+        assert_eq!(
+            Err(Error::UnsupportedVersion(BINARY_FORMAT_VERSION + 1)),
+            from_bytes(&bytes)
+        );
+        assert_eq!(Err(Error::UnsupportedVersion(0)), from_bytes(&[0, 5, 0]));
+    }
+
+    #[test]
+    fn reject_invalid_data() {
+        let bytes = to_bytes(&compile("max(a, 'text') + [1, 2]").unwrap());
+
+        for length in 0..bytes.len() {
+            assert!(
+                matches!(from_bytes(&bytes[..length]), Err(Error::InvalidBinary(_))),
+                "{length}"
+            );
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+
+        let cases: [(&[u8], &str); 7] = [
+            (&trailing, "trailing bytes at byte 41"),
+            (&[1, 9], "unknown node 9 at byte 2"),
+            (&[1, 5, 6], "unknown value 6 at byte 3"),
+            (&[1, 0, 99, 5, 0], "unknown operator 99 at byte 3"),
+            (&[1, 6, 2, 0xff, 0xfe], "invalid UTF-8 at byte 5"),
+            (
+                &[1, 4, 0xff, 0xff, 0xff, 0x0f],
+                "unexpected end of data at byte 6",
+            ),
+            (
+                &[
+                    1, 6, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                ],
+                "length out of range at byte 12",
+            ),
+        ];
+
+        for (bytes, message) in cases {
+            assert_eq!(
+                Err(Error::InvalidBinary(String::from(message))),
+                from_bytes(bytes)
+            );
+        }
+    }
+
+    #[test]
+    fn reject_deep_nesting() {
+        let nested = |depth: usize| {
+            let ast = compile(&format!("{}a", "not ".repeat(depth - 1))).unwrap();
+            from_bytes(&to_bytes(&ast))
+        };
+        let exceeded = Err(Error::RecursionLimitExceeded(DEFAULT_MAX_DEPTH));
+
+        assert!(nested(DEFAULT_MAX_DEPTH).is_ok());
+
+        let mut bytes = vec![BINARY_FORMAT_VERSION];
+        bytes.extend([0, 13].repeat(DEFAULT_MAX_DEPTH));
+        bytes.extend([5, 0]);
+
+        assert_eq!(exceeded, from_bytes(&bytes));
+
+        // nested arrays inside of a literal count as well
+        let mut bytes = vec![BINARY_FORMAT_VERSION, 5];
+        bytes.extend([5, 1].repeat(DEFAULT_MAX_DEPTH));
+        bytes.push(0);
+
+        assert_eq!(exceeded, from_bytes(&bytes));
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use slac::{
+    compile, execute, execute_cancellable,
+    function::{Arity, Function},
+    stdlib::{NativeError, NativeResult},
+    CancellationToken, Error, StaticEnvironment, Value,
+};
+
+fn slow(_params: &[Value]) -> NativeResult {
+    thread::sleep(Duration::from_millis(10));
+    Ok(Value::Number(1.0))
+}
+
+fn wait_for_cancel(_params: &[Value], token: &CancellationToken) -> NativeResult {
+    while !token.is_cancelled() {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    Err(NativeError::from("stopped"))
+}
+
+fn environment() -> StaticEnvironment {
+    let mut env = StaticEnvironment::default();
+    env.add_function(Function::impure(slow, Arity::None, "slow(): Number"));
+    env.add_function(Function::cancellable(
+        wait_for_cancel,
+        Arity::None,
+        "wait_for_cancel(): Number",
+    ));
+
+    env
+}
+
+/// Executes the script on a separate thread, cancels it after a short delay
+/// and returns the result together with the time it took after cancelling.
+fn cancel_script(script: &'static str) -> (slac::Result<Value>, Duration) {
+    let token = CancellationToken::new();
+    let (sender, receiver) = mpsc::channel();
+
+    let worker = {
+        let token = token.clone();
+        thread::spawn(move || {
+            let env = environment();
+            let ast = compile(script).unwrap();
+            sender.send(()).unwrap();
+
+            execute_cancellable(&env, &ast, &token)
+        })
+    };
+
+    receiver.recv().unwrap();
+    thread::sleep(Duration::from_millis(50));
+
+    let cancelled_at = Instant::now();
+    token.cancel();
+    let result = worker.join().unwrap();
+
+    (result, cancelled_at.elapsed())
+}
+
+#[test]
+fn cancel_slow_expression() {
+    let script = "max([slow(), slow(), slow(), slow(), slow(), slow(), slow(), slow(), slow(),
+                       slow(), slow(), slow(), slow(), slow(), slow(), slow(), slow(), slow(),
+                       slow(), slow(), slow(), slow(), slow(), slow(), slow(), slow(), slow(),
+                       slow(), slow(), slow(), slow(), slow(), slow(), slow(), slow(), slow()])";
+
+    let (result, elapsed) = cancel_script(script);
+
+    assert_eq!(Err(Error::Cancelled), result);
+    assert!(elapsed < Duration::from_millis(500), "took {elapsed:?}");
+}
+
+#[test]
+fn cancel_native_function() {
+    let (result, elapsed) = cancel_script("wait_for_cancel() > 0");
+
+    assert_eq!(
+        Err(Error::NativeFunctionError(
+            String::from("wait_for_cancel"),
+            NativeError::from("stopped")
+        )),
+        result
+    );
+    assert!(elapsed < Duration::from_millis(500), "took {elapsed:?}");
+}
+
+#[test]
+fn uncancelled_execution() {
+    let env = environment();
+    let ast = compile("slow() + 1").unwrap();
+    let token = CancellationToken::new();
+
+    assert_eq!(execute(&env, &ast), execute_cancellable(&env, &ast, &token));
+}
+
+#[test]
+fn cancel_inside_try() {
+    let (result, _) = cancel_script("try(wait_for_cancel(), slow())");
+
+    assert_eq!(Err(Error::Cancelled), result);
+}
@@ -0,0 +1,39 @@
+#[cfg(all(
+    feature = "serde",
+    feature = "chrono",
+    feature = "regex",
+    feature = "unicode",
+    feature = "random"
+))]
+mod test {
+    use slac::{stdlib::extend_environment, Catalog, StaticEnvironment};
+
+    const GOLDEN_FILE: &str = "tests/catalog.json";
+
+    /// Compares the catalog of the default stdlib against the golden file.
+    /// Run with `SLAC_UPDATE_CATALOG=1` to update the golden file after an
+    /// intended change.
+    #[test]
+    fn stdlib_catalog() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        let catalog = env.catalog();
+        let json = serde_json::to_string_pretty(&catalog).unwrap() + "\n";
+
+        if std::env::var_os("SLAC_UPDATE_CATALOG").is_some() {
+            std::fs::write(GOLDEN_FILE, &json).unwrap();
+        }
+
+        let golden = std::fs::read_to_string(GOLDEN_FILE).unwrap();
+        let golden: Catalog = serde_json::from_str(&golden).unwrap();
+        let diff = catalog.diff(&golden);
+
+        assert!(
+            diff.is_empty(),
+            "stdlib catalog changed: {}",
+            serde_json::to_string_pretty(&diff).unwrap()
+        );
+        assert!(catalog.functions.iter().all(|f| f.module.is_some()));
+    }
+}
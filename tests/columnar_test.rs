@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use slac::{
+    compile, execute, execute_columnar, execute_columnar_rows, stdlib::extend_environment, Error,
+    StaticEnvironment, Value,
+};
+
+fn columns() -> HashMap<String, Vec<Value>> {
+    HashMap::from([
+        (
+            String::from("number"),
+            vec![
+                Value::Number(1.0),
+                Value::Number(-20.5),
+                Value::Number(0.0),
+                Value::Number(300.0),
+            ],
+        ),
+        (
+            String::from("text"),
+            vec![
+                Value::String(String::from("Hello")),
+                Value::String(String::new()),
+                Value::String(String::from("42")),
+                Value::String(String::from("World")),
+            ],
+        ),
+        (
+            String::from("flag"),
+            vec![
+                Value::Boolean(true),
+                Value::Boolean(false),
+                Value::Boolean(true),
+                Value::Boolean(false),
+            ],
+        ),
+    ])
+}
+
+fn execute_rows(script: &str) -> Vec<slac::Result<Value>> {
+    let ast = compile(script).unwrap();
+    let columns = columns();
+    let rows = columns["number"].len();
+
+    (0..rows)
+        .map(|row| {
+            let mut env = StaticEnvironment::default();
+            extend_environment(&mut env);
+
+            for (name, values) in &columns {
+                env.add_variable(name, values[row].clone());
+            }
+
+            execute(&env, &ast)
+        })
+        .collect()
+}
+
+fn assert_columnar(script: &str) {
+    let ast = compile(script).unwrap();
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    assert_eq!(
+        Ok(execute_rows(script)),
+        execute_columnar_rows(&env, &ast, &columns())
+    );
+    assert_eq!(
+        execute_rows(script)
+            .into_iter()
+            .collect::<slac::Result<Vec<Value>>>(),
+        execute_columnar(&env, &ast, &columns())
+    );
+}
+
+#[test]
+fn columnar_matches_execute() {
+    assert_columnar("number * 2 + 1");
+    assert_columnar("max(number, 10) + abs(number)");
+    assert_columnar("text + '!'");
+    assert_columnar("flag and number > 0 or text = ''");
+    assert_columnar("if_then(flag, text, str(number))");
+    assert_columnar("[number, text, flag]");
+    assert_columnar("length(text) * (3 + 4)");
+    assert_columnar("10 + 20");
+}
+
+#[test]
+fn columnar_row_errors() {
+    assert_columnar("number + text");
+    assert_columnar("-text");
+
+    let ast = compile("-text").unwrap();
+    let env = StaticEnvironment::default();
+    let result = execute_columnar_rows(&env, &ast, &columns()).unwrap();
+
+    assert_eq!(4, result.len());
+    assert!(result.iter().all(Result::is_err));
+}
+
+#[test]
+fn columnar_empty() {
+    let ast = compile("1 + 2").unwrap();
+    let env = StaticEnvironment::default();
+
+    assert_eq!(Ok(vec![]), execute_columnar(&env, &ast, &HashMap::new()));
+    assert_eq!(
+        Err(Error::MissingVariable(String::from("number"))),
+        execute_columnar(&env, &compile("number").unwrap(), &HashMap::new())
+    );
+}
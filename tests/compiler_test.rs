@@ -1,4 +1,10 @@
-use slac::{compile, Expression, Operator, Value};
+use std::collections::BTreeSet;
+
+use slac::{
+    collect_function_calls, collect_variables, compile, compile_strict, compile_with_spans,
+    expected_tokens_at_error, node_at, Error, Expression, Operator, Scanner, Span, Token,
+    TokenKind, Value,
+};
 
 #[test]
 fn single_boolean_true() {
@@ -386,3 +392,376 @@ fn function_call_no_params() {
 
     assert_eq!(result, Ok(expected));
 }
+
+#[test]
+fn comparison_chain_precedence() {
+    let chain = compile("1 + 1 <= x * 2 < 10 = true").unwrap();
+    let expected = compile("(1 + 1 <= x * 2 < 10) = true").unwrap();
+    assert_eq!(expected, chain);
+
+    let grouped = compile("(1 <= x) <= 10").unwrap();
+    assert!(matches!(grouped, Expression::Binary { .. }));
+}
+
+#[test]
+fn implies_precedence() {
+    let chained = compile("a implies b implies c").unwrap();
+    let expected = compile("a implies (b implies c)").unwrap();
+    assert_eq!(expected, chained);
+
+    let grouped = compile("(a implies b) implies c").unwrap();
+    assert_ne!(grouped, chained);
+
+    let logical = compile("a or b implies c and d").unwrap();
+    let expected = compile("(a or b) implies (c and d)").unwrap();
+    assert_eq!(expected, logical);
+
+    let iff = compile("a implies b iff not b implies not a").unwrap();
+    let expected = compile("(a implies b) iff ((not b) implies (not a))").unwrap();
+    assert_eq!(expected, iff);
+
+    let Expression::Binary { operator, .. } = compile("a IMPLIES b").unwrap() else {
+        panic!("expected a binary expression");
+    };
+    assert_eq!(Operator::Implies, operator);
+}
+
+#[test]
+fn token_at_offset() {
+    let source = "max( 'a b' ,x)";
+    let token = |offset| Scanner::token_at(source, offset).map(|(token, span)| (token, span.start));
+
+    let max = Some((Token::Identifier(String::from("max")), 0));
+    let string = Some((Token::Literal(Value::String(String::from("a b"))), 5));
+
+    assert_eq!(max, token(0));
+    assert_eq!(max, token(2));
+    assert_eq!(Some((Token::LeftParen, 3)), token(3)); // starts where max ends
+    assert_eq!(Some((Token::LeftParen, 3)), token(4)); // directly behind a token
+    assert_eq!(string, token(5));
+    assert_eq!(string, token(8));
+    assert_eq!(Some((Token::Comma, 11)), token(11));
+    assert_eq!(Some((Token::RightParen, 13)), token(14));
+    assert_eq!(None, token(15));
+
+    assert_eq!(None, Scanner::token_at("", 0));
+    assert_eq!(None, Scanner::token_at("  { comment }  ", 4));
+    assert_eq!(
+        Some((
+            Token::Plus,
+            Span {
+                start: 2,
+                end: 3,
+                line: 1,
+                column: 3
+            }
+        )),
+        Scanner::token_at("1 + 'unterminated", 2)
+    );
+}
+
+#[test]
+fn error_spans() {
+    let span = |start, end, line, column| {
+        Some(Span {
+            start,
+            end,
+            line,
+            column,
+        })
+    };
+    let error = |source| compile(source).unwrap_err();
+
+    assert_eq!(span(10, 11, 2, 4), error("max(1,\n   $)").span());
+    assert_eq!(span(8, 13, 3, 1), error("1 +\n2 +\n'open").span());
+    assert_eq!(span(9, 10, 2, 4), error("(a or\n b c)").span());
+    assert_eq!(span(8, 12, 1, 9), error("a is {}\tfull").span());
+    assert_eq!(span(0, 1, 1, 1), error(") + 1").span());
+    assert_eq!(span(2, 3, 1, 3), error("1 +").span());
+    assert_eq!(span(0, 1, 1, 1), error("(1 +\n2").span());
+    assert_eq!(None, error("1 2").span());
+
+    assert_eq!(
+        "error at line 2, column 4: invalid Token \"Identifier(\"c\")\"",
+        error("(a or\n b c)").to_string()
+    );
+    assert_eq!(
+        "error at line 1, column 4: unbalanced brackets, expected a closing \"RightParen\"",
+        error("max(1").to_string()
+    );
+}
+
+#[test]
+fn node_at_offset() {
+    let source = "not (a or b) and max(1, -x) is empty";
+    let (ast, spans) = compile_with_spans(source).unwrap();
+
+    assert_eq!(compile(source), Ok(ast.clone()));
+
+    let variable = |name: &str| Expression::Variable {
+        name: String::from(name),
+    };
+    let node = |offset| node_at(&ast, &spans, offset);
+
+    assert!(matches!(
+        node(0),
+        Some(Expression::Unary {
+            operator: Operator::Not,
+            ..
+        })
+    ));
+    assert!(matches!(
+        node(4),
+        Some(Expression::Unary {
+            operator: Operator::Not,
+            ..
+        })
+    ));
+    assert_eq!(Some(&variable("a")), node(5));
+    assert!(matches!(
+        node(7),
+        Some(Expression::Binary {
+            operator: Operator::Or,
+            ..
+        })
+    ));
+    assert!(matches!(
+        node(13),
+        Some(Expression::Binary {
+            operator: Operator::And,
+            ..
+        })
+    ));
+    assert!(matches!(node(17), Some(Expression::Call { .. })));
+    assert_eq!(
+        Some(&Expression::Literal {
+            value: Value::Number(1.0)
+        }),
+        node(21)
+    );
+    assert!(matches!(
+        node(24),
+        Some(Expression::Unary {
+            operator: Operator::Minus,
+            ..
+        })
+    ));
+    assert_eq!(Some(&variable("x")), node(25));
+    assert!(matches!(
+        node(30),
+        Some(Expression::Unary {
+            operator: Operator::IsEmpty,
+            ..
+        })
+    ));
+    assert_eq!(None, node(source.len()));
+}
+
+#[test]
+fn node_at_chain_and_array() {
+    let (ast, spans) = compile_with_spans("[1 < x <= 3, []]").unwrap();
+
+    assert!(matches!(
+        node_at(&ast, &spans, 0),
+        Some(Expression::Array { .. })
+    ));
+    assert!(matches!(
+        node_at(&ast, &spans, 3),
+        Some(Expression::Chain { .. })
+    ));
+    assert_eq!(
+        Some(&Expression::Variable {
+            name: String::from("x")
+        }),
+        node_at(&ast, &spans, 5)
+    );
+    assert_eq!(
+        Some(&Expression::Array {
+            expressions: vec![]
+        }),
+        node_at(&ast, &spans, 14)
+    );
+}
+
+#[test]
+fn expected_tokens() {
+    let operands = vec![
+        TokenKind::LeftParen,
+        TokenKind::LeftBracket,
+        TokenKind::Minus,
+        TokenKind::Not,
+        TokenKind::If,
+        TokenKind::Literal,
+        TokenKind::Identifier,
+    ];
+    let with = |closer: TokenKind| {
+        let mut expected = operands.clone();
+        expected.push(closer);
+        expected.sort();
+        expected
+    };
+
+    assert_eq!(operands, expected_tokens_at_error("1 + "));
+    assert_eq!(operands, expected_tokens_at_error("not"));
+    assert_eq!(operands, expected_tokens_at_error("(1 * "));
+    assert_eq!(operands, expected_tokens_at_error(""));
+    assert_eq!(
+        with(TokenKind::RightParen),
+        expected_tokens_at_error("max(")
+    );
+    assert_eq!(
+        with(TokenKind::RightBracket),
+        expected_tokens_at_error("[1, ")
+    );
+    assert_eq!(
+        with(TokenKind::RightBracket),
+        expected_tokens_at_error("[[1], ")
+    );
+
+    let after_operand = expected_tokens_at_error("1 + 2 $");
+    assert!(after_operand.contains(&TokenKind::Plus));
+    assert!(after_operand.contains(&TokenKind::Is));
+    assert!(after_operand.contains(&TokenKind::In));
+    assert!(!after_operand.contains(&TokenKind::RightParen));
+    assert!(!after_operand.contains(&TokenKind::Literal));
+
+    let after_element = expected_tokens_at_error("max(1");
+    assert!(after_element.contains(&TokenKind::Plus));
+    assert!(after_element.contains(&TokenKind::Comma));
+    assert!(after_element.contains(&TokenKind::RightParen));
+
+    let after_condition = expected_tokens_at_error("if a");
+    assert!(after_condition.contains(&TokenKind::Then));
+    assert!(!after_condition.contains(&TokenKind::Else));
+
+    let after_branch = expected_tokens_at_error("if a then 1");
+    assert!(after_branch.contains(&TokenKind::Else));
+    assert!(after_branch.contains(&TokenKind::Plus));
+
+    let after_group = expected_tokens_at_error("(1");
+    assert!(after_group.contains(&TokenKind::RightParen));
+    assert!(!after_group.contains(&TokenKind::Comma));
+
+    assert_eq!(
+        vec![TokenKind::Not, TokenKind::Identifier],
+        expected_tokens_at_error("x is")
+    );
+    assert_eq!(
+        vec![TokenKind::Identifier],
+        expected_tokens_at_error("x is not")
+    );
+
+    assert!(expected_tokens_at_error("max(1, 2)").is_empty());
+    assert_eq!(
+        expected_tokens_at_error("[1, "),
+        expected_tokens_at_error("[1, ")
+    );
+}
+
+#[test]
+fn collect_names() {
+    let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<BTreeSet<_>>();
+    let calls = |calls: &[(&str, usize)]| {
+        calls
+            .iter()
+            .map(|(name, params)| (name.to_string(), *params))
+            .collect::<BTreeSet<_>>()
+    };
+
+    // nested calls, variables used bare and as parameters
+    let ast = compile("max(a, min(b, abs(a))) > a and if c then [d, e[0]] else f").unwrap();
+    assert_eq!(
+        names(&["a", "b", "c", "d", "e", "f"]),
+        collect_variables(&ast)
+    );
+    assert_eq!(
+        calls(&[("abs", 1), ("max", 2), ("min", 2)]),
+        collect_function_calls(&ast)
+    );
+
+    // every node of a chain and a call without parameters
+    let ast = compile("low < now() <= high").unwrap();
+    assert_eq!(names(&["high", "low"]), collect_variables(&ast));
+    assert_eq!(calls(&[("now", 0)]), collect_function_calls(&ast));
+
+    // the names are kept as written
+    let ast = compile("Max(Amount) = max(amount, 0)").unwrap();
+    assert_eq!(names(&["Amount", "amount"]), collect_variables(&ast));
+    assert_eq!(
+        calls(&[("Max", 1), ("max", 2)]),
+        collect_function_calls(&ast)
+    );
+
+    for script in ["1 + 2 * 3", "'a'", "[1, [true]]", "if true then 1 else 2"] {
+        let ast = compile(script).unwrap();
+        assert!(collect_variables(&ast).is_empty(), "{script}");
+        assert!(collect_function_calls(&ast).is_empty(), "{script}");
+    }
+}
+
+#[test]
+fn to_source() {
+    let cases = [
+        ("1+2*3", "1 + 2 * 3"),
+        ("(1 + 2) * 3", "(1 + 2) * 3"),
+        ("1 - (2 - 3)", "1 - (2 - 3)"),
+        ("(1 - 2) - 3", "1 - 2 - 3"),
+        ("a implies (b implies c)", "a implies b implies c"),
+        ("(a implies b) implies c", "(a implies b) implies c"),
+        ("not (a and b) or not c", "not (a and b) or not c"),
+        ("-(-a)", "--a"),
+        ("(a < b) < c", "(a < b) < c"),
+        ("a<b<=c", "a < b <= c"),
+        ("(a < b) in c", "a < b in c"),
+        ("a = b is not empty", "a = b is not empty"),
+        ("a = (b is empty)", "a = (b is empty)"),
+        ("not (a is empty)", "not (a is empty)"),
+        (
+            "(1 + if a then b else c) * 2",
+            "(1 + (if a then b else c)) * 2",
+        ),
+        (
+            "if (if a then b else c) then d else 1 + 2",
+            "if if a then b else c then d else 1 + 2",
+        ),
+        (
+            "max(if a then 1 else 2, [3,'it''s'])",
+            "max(if a then 1 else 2, [3, 'it''s'])",
+        ),
+        ("(a + b)[0] + values[i - 1]", "(a + b)[0] + values[i - 1]"),
+        (
+            "r'\\d+' DIV 2 MOD 3 XOR true IFF null",
+            "r'\\d+' div 2 mod 3 xor true iff null",
+        ),
+    ];
+
+    for (source, expected) in cases {
+        let ast = compile(source).unwrap();
+
+        assert_eq!(expected, ast.to_source(), "{source}");
+        assert_eq!(Ok(ast), compile(expected), "{source}");
+    }
+}
+
+#[test]
+fn strict_number_literals() {
+    for source in [
+        "customer_id = 9007199254740991",
+        "customer_id = -9007199254740992",
+        "max(1.5, 2e20) > $1_000",
+        "1 +",
+    ] {
+        assert_eq!(compile(source), compile_strict(source), "{source}");
+    }
+
+    let imprecise = Err(Error::ImpreciseNumberLiteral(String::from(
+        "9007199254740993",
+    )));
+
+    assert!(compile("customer_id = 9007199254740993").is_ok());
+    assert_eq!(imprecise, compile_strict("customer_id = 9007199254740993"));
+    assert_eq!(
+        imprecise,
+        compile_strict("if a then [1, f(9007199254740993)] else 0")
+    );
+}
@@ -0,0 +1,258 @@
+//! Runs the language agnostic conformance suite in `tests/conformance`.
+//!
+//! Every case is compiled, printed back to source, validated, executed and
+//! executed again after optimizing. See `tests/conformance/README.md` for the format of the case
+//! files, new cases only need a new line in one of them.
+
+use std::{fs, path::Path};
+
+use serde_json::{Map, Value as Json};
+use slac::{
+    check_variables_and_functions, compile, execute, optimize, stdlib::extend_environment, Error,
+    StaticEnvironment, Value,
+};
+
+const SUITE_DIR: &str = "tests/conformance";
+
+/// Checks if a feature requirement like `chrono` or `!zero_based_strings` is met.
+fn requirement_met(requirement: &str) -> Result<bool, String> {
+    let (name, expected) = match requirement.strip_prefix('!') {
+        Some(name) => (name, false),
+        None => (requirement, true),
+    };
+
+    let enabled = match name {
+        "chrono" => cfg!(feature = "chrono"),
+        "decimal" => cfg!(feature = "decimal"),
+        "hash" => cfg!(feature = "hash"),
+        "random" => cfg!(feature = "random"),
+        "regex" => cfg!(feature = "regex"),
+        "unicode" => cfg!(feature = "unicode"),
+        "zero_based_strings" => cfg!(feature = "zero_based_strings"),
+        _ => return Err(format!("unknown feature \"{name}\"")),
+    };
+
+    Ok(enabled == expected)
+}
+
+fn to_value(json: &Json) -> Result<Value, String> {
+    match json {
+        Json::Bool(value) => Ok(Value::Boolean(*value)),
+        Json::Number(value) => value
+            .as_f64()
+            .map(Value::Number)
+            .ok_or(format!("{value} is not a valid number")),
+        Json::String(value) => Ok(Value::String(value.clone())),
+        Json::Array(values) => values
+            .iter()
+            .map(to_value)
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        // non-finite numbers like `{"number": "inf"}`
+        Json::Object(map) => match map.get("number").and_then(Json::as_str) {
+            Some(number @ ("inf" | "-inf" | "NaN")) => Ok(Value::Number(number.parse().unwrap())),
+            _ => Err(format!("{json} is not a valid value")),
+        },
+        Json::Null => Ok(Value::Null),
+    }
+}
+
+/// Compares two values, unlike [`PartialEq`] `NaN` matches `NaN` and `null`
+/// only matches `null`.
+fn same_value(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Null, value) | (value, Value::Null) => matches!(value, Value::Null),
+        (Value::Number(left), Value::Number(right)) => {
+            left == right || (left.is_nan() && right.is_nan())
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(l, r)| same_value(l, r))
+        }
+        (left, right) => left == right,
+    }
+}
+
+/// The language agnostic category of an [`Error`] raised during execution.
+fn runtime_category(error: &Error) -> String {
+    match error {
+        Error::UndefinedVariable(_) => String::from("undefined_variable"),
+        Error::NativeFunctionError(_, error) => error.key().to_string(),
+        Error::InvalidUnaryOperator(_)
+        | Error::InvalidBinaryOperator(_)
+        | Error::InvalidTernaryOperator(_)
+        | Error::IncomparableTypes(..) => String::from("type"),
+        error => format!("unexpected error: {error}"),
+    }
+}
+
+type Outcome = Result<Value, String>;
+
+fn describe(outcome: &Outcome) -> String {
+    match outcome {
+        Ok(value) => format!("value {value:?}"),
+        Err(category) => format!("error \"{category}\""),
+    }
+}
+
+fn run_case(case: &Map<String, Json>) -> Result<(), String> {
+    let field = |name: &str| case.get(name);
+
+    let Some(source) = field("source").and_then(Json::as_str) else {
+        return Err(String::from("missing \"source\""));
+    };
+
+    let expected: Outcome = match (field("value"), field("error")) {
+        (Some(value), None) => Ok(to_value(value)?),
+        (None, Some(Json::String(category))) => Err(category.clone()),
+        _ => return Err(String::from("expected either a \"value\" or an \"error\"")),
+    };
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    if let Some(variables) = field("variables") {
+        let Json::Object(variables) = variables else {
+            return Err(String::from("\"variables\" is not an object"));
+        };
+
+        for (name, value) in variables {
+            env.add_variable(name, to_value(value)?);
+        }
+    }
+
+    let validate = match field("validate") {
+        None => true,
+        Some(Json::Bool(validate)) => *validate,
+        Some(_) => return Err(String::from("\"validate\" is not a boolean")),
+    };
+
+    if let Ok(ast) = compile(source) {
+        let printed = ast.to_source();
+
+        if compile(&printed) != Ok(ast) {
+            return Err(format!(
+                "prints as \"{printed}\", which compiles differently"
+            ));
+        }
+    }
+
+    let outcomes: Vec<(&str, Outcome)> = match compile(source) {
+        Err(_) => vec![("compile", Err(String::from("syntax")))],
+        Ok(ast) => match check_variables_and_functions(&env, &ast) {
+            Err(_) if validate => vec![("validate", Err(String::from("validation")))],
+            _ => {
+                let mut optimized = ast.clone();
+                let optimized =
+                    optimize(&env, &mut optimized).and_then(|()| execute(&env, &optimized));
+
+                vec![
+                    (
+                        "execute",
+                        execute(&env, &ast).map_err(|e| runtime_category(&e)),
+                    ),
+                    ("optimize", optimized.map_err(|e| runtime_category(&e))),
+                ]
+            }
+        },
+    };
+
+    for (stage, outcome) in outcomes {
+        let matches = match (&expected, &outcome) {
+            (Ok(expected), Ok(value)) => same_value(expected, value),
+            (Err(expected), Err(category)) => expected == category,
+            _ => false,
+        };
+
+        if !matches {
+            return Err(format!(
+                "{source:?}: expected {} but {stage} returned {}",
+                describe(&expected),
+                describe(&outcome)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs all cases of a file, returns the number of executed cases.
+fn run_file(path: &Path, failures: &mut Vec<String>) -> usize {
+    let content = fs::read_to_string(path).unwrap();
+    let mut executed = 0;
+
+    for (index, line) in content.lines().enumerate() {
+        let location = format!("{}:{}", path.display(), index + 1);
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let case = match serde_json::from_str::<Map<String, Json>>(line) {
+            Ok(case) => case,
+            Err(error) => {
+                failures.push(format!("{location}: invalid JSON: {error}"));
+                continue;
+            }
+        };
+
+        // a header with the feature requirements of all cases in the file
+        if let Some(requires) = case.get("requires") {
+            let requirements = match requires {
+                Json::Array(requirements) if executed == 0 => requirements,
+                _ => {
+                    failures.push(format!("{location}: misplaced or invalid \"requires\""));
+                    return executed;
+                }
+            };
+
+            for requirement in requirements {
+                match requirement.as_str().map(requirement_met) {
+                    Some(Ok(true)) => (),
+                    Some(Ok(false)) => return 0,
+                    Some(Err(error)) => {
+                        failures.push(format!("{location}: {error}"));
+                        return 0;
+                    }
+                    None => {
+                        failures.push(format!("{location}: invalid requirement {requirement}"));
+                        return 0;
+                    }
+                }
+            }
+
+            continue;
+        }
+
+        if let Err(error) = run_case(&case) {
+            failures.push(format!("{location}: {error}"));
+        }
+
+        executed += 1;
+    }
+
+    executed
+}
+
+#[test]
+fn conformance() {
+    let mut paths: Vec<_> = fs::read_dir(SUITE_DIR)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "jsonl")
+        })
+        .collect();
+    paths.sort();
+
+    let mut failures = Vec::new();
+    let executed: usize = paths.iter().map(|path| run_file(path, &mut failures)).sum();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {executed} conformance cases failed:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+    assert!(executed > 0, "no conformance cases found in {SUITE_DIR}");
+}
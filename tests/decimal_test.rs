@@ -0,0 +1,140 @@
+#[cfg(feature = "decimal")]
+mod test {
+    use slac::{compile, execute, stdlib::extend_environment, StaticEnvironment, Value};
+
+    fn environment() -> StaticEnvironment {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+        env
+    }
+
+    /// Executes a script with the amounts `a` and `b`.
+    fn execute_amounts(env: &mut StaticEnvironment, script: &str, a: f64, b: f64) -> Value {
+        let ast = compile(script).unwrap();
+
+        env.add_variable("a", Value::Number(a));
+        env.add_variable("b", Value::Number(b));
+
+        execute(env, &ast).unwrap()
+    }
+
+    /// The amount of a number of cents, parsed from its decimal representation.
+    fn amount(cents: i64) -> f64 {
+        let sign = if cents < 0 { "-" } else { "" };
+
+        format!("{sign}{}.{:02}", cents.abs() / 100, cents.abs() % 100)
+            .parse()
+            .unwrap()
+    }
+
+    /// A number differs from the result in f64 mode only by the rounding
+    /// errors of the operands.
+    fn close_to_f64(number: f64, float: f64, lhs: f64, rhs: f64) -> bool {
+        let scale = lhs.abs().max(rhs.abs()).max(float.abs());
+
+        (number - float).abs() <= 4.0 * f64::EPSILON * scale
+    }
+
+    #[test]
+    fn exact_currency_amounts() {
+        let mut env = environment();
+
+        for a in (-1_000..=1_000).step_by(17) {
+            for b in (-1_000..=1_000).step_by(23) {
+                let (lhs, rhs) = (amount(a), amount(b));
+
+                let sum = execute_amounts(&mut env, "a + b", lhs, rhs);
+                assert_eq!(Value::Number(amount(a + b)), sum, "{lhs} + {rhs}");
+
+                let difference = execute_amounts(&mut env, "a - b", lhs, rhs);
+                assert_eq!(Value::Number(amount(a - b)), difference, "{lhs} - {rhs}");
+
+                let equal =
+                    execute_amounts(&mut env, &format!("a + b = {}", amount(a + b)), lhs, rhs);
+                assert_eq!(Value::Boolean(true), equal, "{lhs} + {rhs}");
+            }
+        }
+    }
+
+    #[test]
+    fn differential_to_f64() {
+        let mut env = environment();
+
+        for a in (-1_000..=1_000).step_by(17) {
+            for b in (1..=1_000).step_by(23) {
+                let (lhs, rhs) = (amount(a), amount(b));
+
+                for (script, float) in [
+                    ("a + b", lhs + rhs),
+                    ("a - b", lhs - rhs),
+                    ("a * b", lhs * rhs),
+                    ("a / b", lhs / rhs),
+                ] {
+                    let Value::Number(number) = execute_amounts(&mut env, script, lhs, rhs) else {
+                        panic!("{script} is not a number");
+                    };
+
+                    assert!(
+                        close_to_f64(number, float, lhs, rhs),
+                        "{script} with a = {lhs}, b = {rhs}: {number} != {float}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fall_back_to_f64() {
+        let mut env = environment();
+
+        for (a, b) in [
+            (1e300, 10.0),
+            (f64::MAX, 2.0),
+            (f64::INFINITY, 1.0),
+            (1.0, 0.0),
+            (-1.0, 0.0),
+            (0.0, 0.0),
+            (1e-30, 3.0),
+            (0.1, f64::MIN_POSITIVE),
+        ] {
+            for (script, float) in [
+                ("a + b", a + b),
+                ("a - b", a - b),
+                ("a * b", a * b),
+                ("a / b", a / b),
+                ("a mod b", a % b),
+            ] {
+                // NaN is never equal to itself, so the results are compared by their debug output
+                assert_eq!(
+                    format!("{:?}", Value::Number(float)),
+                    format!("{:?}", execute_amounts(&mut env, script, a, b)),
+                    "{script} with a = {a:e}, b = {b:e}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn aggregate_amounts() {
+        let amounts = Value::Array((1..=10).map(|_| Value::Number(0.1)).collect());
+        let ast = compile("sum(amounts) = 1 and avg(amounts) = 0.1 and product([0.1, 0.1]) = 0.01")
+            .unwrap();
+        let mut env = StaticEnvironment::default();
+
+        extend_environment(&mut env);
+        env.add_variable("amounts", amounts);
+
+        assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let mut env = environment();
+        let value = execute_amounts(&mut env, "[a + b, a * b, a / b]", 0.1, 0.2);
+        let json = serde_json::to_string(&value).unwrap();
+
+        assert_eq!("[0.3,0.02,0.5]", json);
+        assert_eq!(value, serde_json::from_str::<Value>(&json).unwrap());
+    }
+}
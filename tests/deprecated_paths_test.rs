@@ -0,0 +1,19 @@
+// Only imports from deprecated paths, which are kept working until their removal.
+#![allow(deprecated)]
+
+use slac::std::{extend_environment, NativeError};
+use slac::{compile, execute, StaticEnvironment, Value};
+
+#[test]
+fn deprecated_std_module() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let ast = compile("if_then(true, 1, 2)").unwrap();
+    assert_eq!(Ok(Value::Number(1.0)), execute(&env, &ast));
+
+    assert_eq!(
+        NativeError::WrongParameterType,
+        slac::std::math::abs(&[Value::Boolean(true)]).unwrap_err()
+    );
+}
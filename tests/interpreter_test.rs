@@ -1,11 +1,41 @@
+use std::collections::HashSet;
+
 use slac::{
-    check_variables_and_functions, compile, execute,
+    check_variable_scope, check_variables_and_functions, compile, compile_with_spans,
+    environment::Environment,
+    execute_as, execute_compiled, execute_flat, execute_with_options, execute_with_reads,
     function::{Arity, Function},
+    lint_array_scalar_equality,
     optimizer::optimize,
     stdlib::{extend_environment, NativeResult},
-    Expression, Result, StaticEnvironment, Value,
+    CallGuard, CompiledProgram, DefaultCatalog, Error, ExecutionOptions, Expression,
+    FlatExpression, MessageCatalog, Operator, Result, Scanner, ScopedEnvironment,
+    StaticEnvironment, Value, DEFAULT_MAX_DEPTH,
 };
 
+/// Executes the [`Expression`], its [`FlatExpression`] and its [`CompiledProgram`],
+/// so every test also checks that all representations always agree.
+fn execute(env: &impl Environment, ast: &Expression) -> Result<Value> {
+    let flat = FlatExpression::from(ast);
+    assert_eq!(*ast, Expression::from(&flat));
+
+    let result = slac::execute(env, ast);
+
+    // NaN is never equal to itself, so the results are compared by their debug output
+    assert_eq!(
+        format!("{result:?}"),
+        format!("{:?}", execute_flat(env, &flat)),
+        "{ast:?}"
+    );
+    assert_eq!(
+        format!("{result:?}"),
+        format!("{:?}", execute_compiled(env, &CompiledProgram::from(ast))),
+        "{ast:?}"
+    );
+
+    result
+}
+
 fn execute_raw(script: &str) -> Result<Value> {
     let ast = compile(script).unwrap();
     let env = StaticEnvironment::default();
@@ -119,6 +149,13 @@ fn add_number() {
     assert_eq!(Value::Number(100.5), execute_test("99.2 + 1.3"));
 }
 
+#[test]
+fn add_number_literal_notations() {
+    assert_eq!(Value::Number(1001.0), execute_test("1e3 + 1"));
+    assert_eq!(Value::Number(100_050.0), execute_test("1_000.5e2"));
+    assert_eq!(Value::Number(0.75), execute_test("1_5e-2 * 5"));
+}
+
 #[test]
 fn add_string() {
     let expected = Value::String(String::from("Hello World"));
@@ -183,6 +220,39 @@ fn boolean_xor() {
     assert_eq!(Value::Boolean(false), execute_test("false xor false"));
 }
 
+#[test]
+fn boolean_implies_iff() {
+    let table = [
+        (false, false, true, true),
+        (false, true, true, false),
+        (true, false, false, false),
+        (true, true, true, true),
+    ];
+
+    for (left, right, implies, iff) in table {
+        assert_execute(&implies.to_string(), &format!("{left} implies {right}"));
+        assert_execute(&implies.to_string(), &format!("not {left} or {right}"));
+        assert_execute(&iff.to_string(), &format!("{left} iff {right}"));
+    }
+
+    // right associative: `false implies (true implies false)`
+    assert_execute("true", "false implies true implies false");
+    assert_execute("false", "(false implies true) implies false");
+
+    // truthiness like `and` and `or`
+    assert_execute("true", "0 implies ''");
+    assert_execute("false", "'a' implies []");
+    assert_execute("true", "[1] implies 2");
+    assert_execute("true", "'' iff 0");
+    assert_execute("false", "'a' iff []");
+
+    // an undefined premise is empty
+    assert_eq!(
+        Ok(Value::Boolean(true)),
+        execute_raw("does_not_exist implies false")
+    );
+}
+
 #[test]
 fn boolean_not() {
     assert_eq!(Value::Boolean(false), execute_test("not true"));
@@ -259,6 +329,63 @@ fn std_lib_lowercase_uppercase() {
     assert_str("HELLO WORLD 😀", "uppercase('Hello World 😀')");
 }
 
+#[cfg(feature = "unicode")]
+#[test]
+fn std_unicode_normalization() {
+    use slac::stdlib::STRING_OFFSET;
+
+    // 'Café' composed and decomposed
+    let composed = "'Caf\u{e9}'";
+    let decomposed = "'Cafe\u{301}'";
+
+    assert_execute("false", &format!("{composed} = {decomposed}"));
+    assert_execute("false", &format!("same_text({composed}, {decomposed})"));
+    assert_execute(
+        "true",
+        &format!("same_text({composed}, {decomposed}, true)"),
+    );
+    assert_execute(
+        "true",
+        &format!("same_text('CAF\u{c9}', {decomposed}, true)"),
+    );
+    assert_execute("false", &format!("same_text('Cafe', {decomposed}, true)"));
+    assert_execute("true", &format!("normalize({decomposed}) = {composed}"));
+    assert_execute(
+        "true",
+        &format!("normalize({composed}, 'NFD') = {decomposed}"),
+    );
+
+    // find and contains compare the exact codepoints
+    assert_execute("false", &format!("contains({decomposed}, '\u{e9}')"));
+    assert_execute(
+        "true",
+        &format!("contains(normalize({decomposed}), '\u{e9}')"),
+    );
+    assert_execute(
+        "-1",
+        &format!("find({decomposed}, '\u{e9}') - {STRING_OFFSET}"),
+    );
+    assert_execute(
+        "3",
+        &format!("find(normalize({decomposed}), '\u{e9}') - {STRING_OFFSET}"),
+    );
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn std_case_and_accent_folding() {
+    assert_bool(true, "same_text_normalized('Straße', 'STRASSE')");
+    assert_bool(true, "same_text_normalized('Crème brûlée', 'CREME BRULEE')");
+    assert_bool(false, "same_text_normalized('Straße', 'Strase')");
+    assert_bool(true, "fold_text('Café') = 'cafe'");
+    assert_bool(true, "fold_text('Cafe\u{301}') = fold_text('CAF\u{c9}')");
+    assert_bool(true, "fold_text('Ärger über Öl') = 'arger uber ol'");
+    assert_bool(true, "fold_text('order-42') = 'order-42'");
+
+    assert_err("fold_text(42)");
+    assert_err("same_text_normalized('a')");
+}
+
 #[test]
 fn std_str() {
     assert_str("0", "str(0)");
@@ -267,6 +394,26 @@ fn std_str() {
     assert_bool(true, "str(true) = 'true'");
 }
 
+#[test]
+fn std_non_finite_constants() {
+    assert_execute("inf", "1 / 0");
+    assert_execute("-inf", "-1 / 0");
+    assert_execute("true", "inf > pow(10, 308)");
+    assert_execute("true", "-inf < -pow(10, 308)");
+    assert_execute("false", "nan = nan");
+    assert_execute("true", "nan <> nan");
+    assert_execute("true", "is_nan(0 / 0) and is_nan(nan)");
+    assert_execute("true", "is_infinite(inf) and is_infinite(-inf)");
+    assert_execute("false", "is_infinite(nan) or is_nan(inf)");
+    assert_execute("'inf'", "str(inf)");
+    assert_execute("'-inf'", "str(-inf)");
+    assert_execute("'nan'", "str(nan)");
+    assert_execute("inf", "float(str(inf))");
+    assert_execute("-inf", "float('-Infinity')");
+    assert_execute("true", "is_nan(float('NaN')) and is_nan(float(str(nan)))");
+    assert_execute("true", "pi * 2 = tau and round(e * 1000) = 2718");
+}
+
 #[test]
 fn std_lib_full() {
     assert_bool(
@@ -290,11 +437,135 @@ fn std_lib_full() {
             (round(3.4) = round(2.5)) and
             (str(-10) = '-10') and
             (trim('  space   ') = 'space') and
-            (count([1,2,3,4,3,2,1], 2) = 2)
+            (count([1,2,3,4,3,2,1], 2) = 2) and
+            (floor(-1.5) = -2) and
+            (ceil(-1.5) = -1) and
+            (sign(-4) = -1) and (sign(0) = 0) and (sign(4) = 1) and
+            (clamp(15, 0, 10) = 10) and
+            (tan(0) = 0) and
+            (arc_sin(1) = pi / 2) and
+            (arc_cos(1) = 0) and
+            (log2(8) = 3) and
+            (log10(1000) = 3) and
+            (log(81, 9) = 2) and
+            (degrees(pi) = 180) and
+            (radians(180) = pi) and
+            (sin_deg(180) = 0) and
+            (cos_deg(90) = 0) and
+            (tan_deg(45) = 1)
              ",
     );
 }
 
+#[test]
+fn std_boolean_rules() {
+    let rules = "[price > 10, amount < 5, name = 'slac', true]";
+    let variables = [
+        ("price", Value::Number(20.0)),
+        ("amount", Value::Number(10.0)),
+        ("name", Value::String(String::from("slac"))),
+    ];
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    for (name, value) in variables {
+        env.add_variable(name, value);
+    }
+
+    let run = |script: &str| execute(&env, &compile(script).unwrap());
+
+    assert_eq!(Ok(Value::Number(3.0)), run(&format!("count_true({rules})")));
+    assert_eq!(
+        Ok(Value::Array(vec![Value::Number(1.0)])),
+        run(&format!("failed_indices({rules})"))
+    );
+    assert_eq!(Ok(Value::Number(1.0)), run("count_true(price > 10, false)"));
+    assert_eq!(
+        Ok(Value::Array(vec![Value::Number(1.0), Value::Number(2.0)])),
+        run("failed_indices(true, false, price)")
+    );
+
+    // both functions are eager, an erroring member fails the whole call
+    for script in [
+        "count_true([true, false, price - 'a' > 0])",
+        "failed_indices([true, false, price - 'a' > 0])",
+    ] {
+        assert_eq!(
+            Err(Error::InvalidBinaryOperator(Operator::Minus)),
+            run(script),
+            "{script}"
+        );
+    }
+
+    assert_eq!(
+        Err(Error::NativeFunctionError(
+            String::from("count_true"),
+            slac::stdlib::NativeError::WrongParameterType
+        )),
+        run("count_true([true, price])")
+    );
+}
+
+#[test]
+fn boolean_strings() {
+    let run = |env: &StaticEnvironment, script: &str| {
+        let mut ast = compile(script).unwrap();
+        let result = execute(env, &ast);
+
+        optimize(env, &mut ast).unwrap();
+        assert_eq!(result, execute(env, &ast), "{script}");
+
+        result
+    };
+    let text = |text: &str| Ok(Value::String(text.to_string()));
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("flag", Value::Boolean(true));
+
+    assert_eq!(text("true"), run(&env, "str(flag)"));
+    assert_eq!(text("false"), run(&env, "str(not flag)"));
+
+    env.set_boolean_strings("Ja", "Nein");
+
+    assert_eq!(text("Ja"), run(&env, "str(flag)"));
+    assert_eq!(text("Nein"), run(&env, "STR(not flag)"));
+    assert_eq!(text("Nein"), run(&env, "str(1 > 2)"));
+    assert_eq!(text("Ja / 1"), run(&env, "str(flag) + ' / ' + str(1)"));
+    assert_eq!(text("Ja"), run(&env, "try(str(flag), 'x')"));
+
+    // other values and the explicit texts of bool_to_string are not affected
+    assert_eq!(text("[Boolean(true)]"), run(&env, "str([flag])"));
+    assert_eq!(text("true"), run(&env, "bool_to_string(flag)"));
+    assert_eq!(text("Yes"), run(&env, "bool_to_string(flag, 'Yes', 'No')"));
+    assert_eq!(
+        text("No"),
+        run(&env, "bool_to_string(not flag, 'Yes', 'No')")
+    );
+
+    // wrapping environments pass the texts through
+    let scoped =
+        ScopedEnvironment::with_allowed_variables(&env, HashSet::from([String::from("flag")]));
+    assert_eq!(text("Ja"), execute(&scoped, &compile("str(flag)").unwrap()));
+
+    // the Display of a Value can not see the environment
+    assert_eq!("true", Value::Boolean(true).to_string());
+
+    // every environment has its own texts
+    let mut english = StaticEnvironment::default();
+    extend_environment(&mut english);
+    english.set_boolean_strings("Yes", "No");
+    assert_eq!(text("No"), run(&english, "str(false)"));
+    assert_eq!(text("Ja"), run(&env, "str(true)"));
+
+    // a custom function replacing `str` does not receive the texts
+    env.add_function(Function::new(
+        |_| Ok(Value::String(String::from("custom"))),
+        Arity::required(1),
+        "str(value: Any): String",
+    ));
+    assert_eq!(text("custom"), run(&env, "str(flag)"));
+}
+
 #[test]
 #[cfg(feature = "chrono")]
 fn std_time() {
@@ -319,10 +590,128 @@ fn std_time() {
 
     assert_num(6.0, "day_of_week(string_to_date('2023-08-27'))"); // Sunday = 6
     assert_num(4.0, "day_of_week(string_to_date('2023-08-27') + 5)"); // Friday = 4
+    assert_num(7.0, "day_of_week(string_to_date('2023-08-27'), 'iso')");
+    assert_num(1.0, "day_of_week(string_to_date('2023-08-27'), 'delphi')");
+    assert_num(6.0, "day_of_week(string_to_date('2023-08-27'), 'zero')");
+    assert_num(1.0, "iso_week(encode_date(2024, 12, 30))");
+    assert_num(2025.0, "iso_week_year(encode_date(2024, 12, 30))");
+    assert_num(2.0, "quarter(encode_date(2024, 6, 30))");
 
     assert_num(18101.5, "string_to_datetime('2019-07-24 12:00:00')");
     assert_num(18101.0, "date(string_to_datetime('2019-07-24 12:00:00'))");
     assert_num(0.5, "time(string_to_datetime('2019-07-24 12:00:00'))");
+
+    assert_num(
+        1693137600.0,
+        "to_unix(string_to_datetime('2023-08-27 12:00:00'))",
+    );
+    assert_execute(
+        "from_unix_ms(1693137600000)",
+        "string_to_datetime('2023-08-27 12:00:00')",
+    );
+    assert_num(-86400.0, "to_unix(from_unix(-86400))");
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn std_time_date_arithmetic() {
+    assert_num(
+        366.0,
+        "days_between(encode_date(2024, 1, 1), encode_date(2025, 1, 1))",
+    );
+    assert_num(
+        -29.0,
+        "days_between(encode_date(2024, 3, 1), encode_date(2024, 2, 1))",
+    );
+    assert_execute(
+        "add_days(encode_date(2023, 12, 31), 1)",
+        "encode_date(2024, 1, 1)",
+    );
+    assert_execute(
+        "add_years(encode_date(2024, 2, 29), -1)",
+        "encode_date(2023, 2, 28)",
+    );
+    assert_execute(
+        "start_of_month(string_to_datetime('2024-02-10 08:30:00'))",
+        "encode_date(2024, 2, 1)",
+    );
+    assert_execute(
+        "end_of_month(encode_date(2024, 2, 10)) + 1",
+        "start_of_month(encode_date(2024, 3, 10))",
+    );
+    assert_num(9.0, "week_of_year(encode_date(2024, 2, 29))");
+
+    assert_err("add_days('2024-01-01', 1)");
+    assert_err("days_between(encode_date(2024, 1, 1), 'tomorrow')");
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn std_time_fixed_clock() {
+    use chrono::NaiveDate;
+    use slac::stdlib::time::extend_environment_with_clock;
+
+    let clock = NaiveDate::from_ymd_opt(2023, 8, 27)
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+        .unwrap();
+    let mut env = StaticEnvironment::default();
+    extend_environment_with_clock(&mut env, clock);
+
+    let run = |script: &str| execute(&env, &compile(script).unwrap());
+
+    assert_eq!(Ok(Value::Number(19596.5)), run("now()"));
+    assert_eq!(Ok(Value::Number(19596.0)), run("today()"));
+    assert_eq!(Ok(Value::Boolean(true)), run("today() + 0.5 = now()"));
+    assert_eq!(
+        Ok(Value::Boolean(true)),
+        run("encode_time(10, 30, 0) + (1 / 24) <= time(now())")
+    );
+
+    // the local clock is only used without a fixed clock
+    assert_bool(true, "today() <= now() and now() - today() < 1");
+
+    // impure functions are never folded
+    assert_expr("now()", "now()");
+    assert_expr("today() - 1", "today() - 1");
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn std_time_deprecated_alias() {
+    use slac::{find_deprecated, Span};
+
+    assert_execute(
+        "string_to_date_time('2023-08-27 08:30:00')",
+        "string_to_datetime('2023-08-27 08:30:00')",
+    );
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let (ast, spans) =
+        compile_with_spans("1 < string_to_date_time(string_to_date_time('2023-08-27') + 'x')")
+            .unwrap();
+    let span = |start, end| Span {
+        start,
+        end,
+        line: 1,
+        column: start + 1,
+    };
+
+    assert_eq!(
+        vec![
+            (String::from("string_to_date_time"), span(4, 64)),
+            (String::from("string_to_date_time"), span(24, 57)),
+        ],
+        find_deprecated(&env, &ast, &spans)
+    );
+    assert_eq!(
+        Some(String::from("use \"string_to_datetime\" instead")),
+        env.function_deprecation("string_to_date_time")
+    );
+
+    let (ast, spans) = compile_with_spans("string_to_datetime('2023-08-27 08:30:00') > 0").unwrap();
+    assert!(find_deprecated(&env, &ast, &spans).is_empty());
 }
 
 #[allow(dead_code)]
@@ -352,6 +741,52 @@ fn std_time_rfc() {
     );
 }
 
+#[test]
+#[cfg(feature = "chrono")]
+fn std_time_duration() {
+    assert_str("12:30:00", "format_duration(encode_time(12, 30, 0))");
+    assert_str("36:15:00", "format_duration(1 + encode_time(12, 15, 0))");
+    assert_str("-01:30", "format_duration(-1.5 / 24, '%H:%M')");
+    assert_bool(
+        true,
+        "parse_duration('36:15:00') ~= 1 + encode_time(12, 15, 0)",
+    );
+    assert_str("-48:00:00", "format_duration(parse_duration('-48:00:00'))");
+
+    assert_err("format_duration('12:30:00')");
+    assert_err("parse_duration(0.5)");
+    assert_err("parse_duration('12 hours')");
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn std_time_rfc_utc() {
+    assert_num(
+        19596.5,
+        "date_from_rfc3339_utc('2023-08-27T12:00:00+00:00')",
+    );
+    assert_num(
+        19596.5,
+        "date_from_rfc2822_utc('Sun, 27 Aug 2023 14:00:00 +0200')",
+    );
+
+    assert_str(
+        "2023-08-27T12:00:00+02:00",
+        "date_to_rfc3339_tz(date_from_rfc3339_utc('2023-08-27T12:00:00+02:00'), 120)",
+    );
+    assert_str(
+        "Sun, 27 Aug 2023 12:00:00 +0200",
+        "date_to_rfc2822_tz(date_from_rfc2822_utc('Sun, 27 Aug 2023 12:00:00 +0200'), 120)",
+    );
+    assert_execute(
+        "convert_tz(string_to_datetime('2023-08-27 12:00:00'), 120, -300)",
+        "string_to_datetime('2023-08-27 05:00:00')",
+    );
+
+    assert_err("date_to_rfc3339_tz(0, 900)");
+    assert_err("convert_tz(0, 0, -900)");
+}
+
 #[test]
 fn operators_full() {
     assert_bool(
@@ -383,6 +818,14 @@ fn short_circuit_bool() {
     let ast = compile("true or expensive()").unwrap();
     let result = execute(&env, &ast);
     assert_eq!(Ok(Value::Boolean(true)), result);
+
+    let ast = compile("false implies expensive()").unwrap();
+    let result = execute(&env, &ast);
+    assert_eq!(Ok(Value::Boolean(true)), result);
+
+    let ast = compile("true implies false implies expensive()").unwrap();
+    let result = execute(&env, &ast);
+    assert_eq!(Ok(Value::Boolean(true)), result);
 }
 
 #[test]
@@ -392,6 +835,110 @@ fn empty_var_comparison() {
         Ok(Value::Boolean(false)),
         execute_raw("does_not_exist <> ''")
     );
+
+    // an operand containing an undefined variable is undefined as a whole
+    assert_eq!(
+        Ok(Value::Boolean(true)),
+        execute_raw("does_not_exist + 1 = ''")
+    );
+    assert_eq!(Ok(Value::Boolean(true)), execute_raw("-does_not_exist = 0"));
+    assert_eq!(
+        Ok(Value::Boolean(false)),
+        execute_raw("not does_not_exist <> ''")
+    );
+    assert_eq!(
+        Ok(Value::Boolean(false)),
+        execute_raw("'a' = -does_not_exist")
+    );
+    assert_eq!(
+        Err(Error::InvalidBinaryOperator(Operator::Minus)),
+        execute_raw("-(1 - 'a')")
+    );
+}
+
+#[test]
+fn is_empty_keyword() {
+    let values = [
+        ("true", false),
+        ("false", true),
+        ("'text'", false),
+        ("''", true),
+        ("42", false),
+        ("0", true),
+        ("[1]", false),
+        ("[]", true),
+    ];
+
+    for (value, empty) in values {
+        assert_bool(empty, &format!("{value} is empty"));
+        assert_bool(!empty, &format!("{value} is not empty"));
+        assert_bool(empty, &format!("{value} IS EMPTY"));
+        assert_execute(&format!("{value} is empty"), &format!("empty({value})"));
+    }
+
+    assert_eq!(
+        Ok(Value::Boolean(true)),
+        execute_raw("undefined_var is empty")
+    );
+    assert_eq!(
+        Ok(Value::Boolean(false)),
+        execute_raw("undefined_var is not empty")
+    );
+    assert_eq!(
+        execute_raw("undefined_var = ''"),
+        execute_raw("undefined_var is empty")
+    );
+    assert_eq!(
+        execute_raw("undefined_var <> ''"),
+        execute_raw("undefined_var is not empty")
+    );
+    assert_eq!(
+        Ok(Value::Boolean(true)),
+        execute_raw("undefined_var is empty and not (undefined_var is not empty)")
+    );
+
+    // precedence above `and` / `or`, below arithmetics
+    assert_bool(true, "'' is empty and 1 is not empty");
+    assert_bool(true, "1 - 1 is empty");
+    assert_bool(true, "not 1 is empty");
+    assert_bool(true, "1 = 2 is empty");
+    assert_bool(false, "1 = (2 is empty)");
+
+    assert!(compile("some_var is").is_err());
+    assert!(compile("some_var is not").is_err());
+    assert!(compile("some_var is full").is_err());
+}
+
+#[test]
+fn index_matches_at() {
+    for (target, index) in [
+        ("[10, 20, 30]", "0"),
+        ("[10, 20, 30]", "2"),
+        ("[10, 20, 30]", "3"),
+        ("[10, 20, 30]", "-1"),
+        ("[10, 20, 30]", "'a'"),
+        ("'hello'", "0"),
+        ("'hello'", "1"),
+        ("'hello'", "5"),
+        ("'hello'", "6"),
+        ("42", "0"),
+    ] {
+        assert_execute(
+            &format!("{target}[{index}]"),
+            &format!("at({target}, {index})"),
+        );
+    }
+
+    // the access does not depend on the `at` function of the environment
+    assert_eq!(Value::Number(20.0), execute_test("[10, 20, 30][1]"));
+    assert_eq!(
+        Value::Number(4.0),
+        execute_test("[[1, 2], [3, 4]][1][[0, 1][1]]")
+    );
+    assert_eq!(
+        Err(Error::UndefinedVariable(String::from("values"))),
+        execute_raw("values[0]")
+    );
 }
 
 #[test]
@@ -418,57 +965,460 @@ fn compare_mixed_boolean_number() {
 }
 
 #[test]
-fn optional_params() {
-    assert_bool(true, "replace('Hello', 'o', 'p') = 'Hellp'");
-    assert_bool(true, "replace('Hello', 'o') = 'Hell'");
-    assert_bool(true, "pow(10) = 100");
-    assert_bool(true, "pow(10, 3) = 1000");
-}
+fn safe_integers() {
+    assert_bool(true, "is_safe_integer(9007199254740991)");
+    assert_bool(true, "is_safe_integer(-9007199254740991)");
+    assert_bool(false, "is_safe_integer(9007199254740992)");
+    assert_bool(false, "is_safe_integer(9007199254740991.5)");
 
-#[test]
-#[cfg(feature = "regex")]
-fn regex_is_match() {
-    assert_bool(true, "re_is_match('ABCDE', 'BC')");
-    assert_bool(false, "re_is_match('ABCDE', 'EF')");
-}
+    assert_num(9007199254740991.0, "int('9007199254740991')");
+    assert_err("int('9007199254740993')");
+    assert_err("int(9007199254740993)");
 
-#[test]
-#[cfg(feature = "regex")]
-fn regex_find() {
-    assert_execute("re_find('ABCDE', 'BC')", "['BC']");
-    assert_execute(
-        "re_find('an employer has an employee in employment', 'employ(er|ee|ment|ing|able)')",
-        "['employer', 'employee', 'employment']",
-    );
-    assert_execute(
-        r"re_find('john.smith@example.com','([a-z0-9_\.\-]+)@([\da-z\.\-]+)\.([a-z\.]{2,5})')",
-        "['john.smith@example.com']",
+    assert!(Scanner::tokenize_strict("customer_id = 9007199254740991").is_ok());
+    assert_eq!(
+        Err(Error::ImpreciseNumberLiteral(String::from(
+            "9007199254740993"
+        ))),
+        Scanner::tokenize_strict("customer_id = 9007199254740993")
     );
-
-    assert_execute(r"re_find('12354', '\D')", "[]");
-    assert_execute(r"re_find('ABCDE', '\D*')", "['ABCDE']");
-    assert_execute(r"re_find('ABCDE', '\D')", "['A','B','C','D','E']");
 }
 
 #[test]
-#[cfg(feature = "regex")]
-fn regex_capture() {
-    assert_execute(
-        r"re_capture('john.smith@example.com', '(.*)@(.*)\.(.*)')",
-        r"['john.smith@example.com', 'john.smith', 'example', 'com']",
-    );
+fn strict_type_comparisons() {
+    let values = [
+        ("true", "Boolean"),
+        ("'abc'", "String"),
+        ("42", "Number"),
+        ("[1]", "Array"),
+    ];
+    let operators = [
+        ("<", Operator::Less),
+        ("<=", Operator::LessEqual),
+        (">", Operator::Greater),
+        (">=", Operator::GreaterEqual),
+    ];
 
-    assert_execute(
-        r"re_capture('john.smith@example', '(.*)@(.*)\.?(.*)?')",
-        r"['john.smith@example', 'john.smith', 'example', '']",
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    let strict = ExecutionOptions {
+        strict_type_comparisons: true,
+        ..Default::default()
+    };
+
+    for (left, left_type) in values {
+        for (right, right_type) in values {
+            for (symbol, operator) in operators {
+                let ast = compile(&format!("{left} {symbol} {right}")).unwrap();
+                let permissive = execute(&env, &ast);
+
+                assert!(permissive.is_ok());
+
+                if left_type == right_type {
+                    assert_eq!(permissive, execute_with_options(&env, &ast, strict.clone()));
+                } else {
+                    assert_eq!(
+                        Err(Error::IncomparableTypes(operator, left_type, right_type)),
+                        execute_with_options(&env, &ast, strict.clone())
+                    );
+                }
+            }
+
+            for symbol in ["=", "<>"] {
+                let ast = compile(&format!("{left} {symbol} {right}")).unwrap();
+                assert_eq!(
+                    execute(&env, &ast),
+                    execute_with_options(&env, &ast, strict.clone())
+                );
+            }
+
+            let ast = compile(&format!("between({left}, {right}, {right})")).unwrap();
+            let result = execute_with_options(&env, &ast, strict.clone());
+            assert!(execute(&env, &ast).is_ok());
+            assert_eq!(left_type == right_type, result.is_ok());
+
+            let ast = compile(&format!("compare({left}, {right})")).unwrap();
+            assert_eq!(
+                execute(&env, &ast),
+                execute_with_options(&env, &ast, strict.clone())
+            );
+        }
+    }
+
+    // numeric strings are comparable to numbers
+    let ast = compile("'10' > 5 and between(7, '5', '10')").unwrap();
+    assert_eq!(
+        Ok(Value::Boolean(true)),
+        execute_with_options(&env, &ast, strict.clone())
     );
 
-    assert_execute(
-        r"re_capture('11 aa 22 bb', '(\d{2})\W(\D{2})')",
-        r"['11 aa', '11', 'aa']",
+    // a custom function replacing `between` is not checked
+    env.add_function(Function::new(
+        |_| Ok(Value::Boolean(true)),
+        Arity::required(3),
+        "between(value: Any, lower: Any, upper: Any): Boolean",
+    ));
+    let ast = compile("between(1, 'abc', [])").unwrap();
+    assert_eq!(
+        Ok(Value::Boolean(true)),
+        execute_with_options(&env, &ast, strict.clone())
     );
+}
 
-    assert_execute(r"re_capture('111', '(\D)(\D)')", r"['', '', '']");
+#[test]
+fn reject_non_finite_numbers() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("zero", Value::Number(0.0));
+    let checked = ExecutionOptions {
+        reject_non_finite_numbers: true,
+        ..Default::default()
+    };
+
+    let cases = [
+        ("1 / 0", Error::NonFiniteNumber(Operator::Divide)),
+        ("0 / zero", Error::NonFiniteNumber(Operator::Divide)),
+        ("1 div 0", Error::NonFiniteNumber(Operator::Div)),
+        ("1 mod 0", Error::NonFiniteNumber(Operator::Mod)),
+        ("1e308 * 10", Error::NonFiniteNumber(Operator::Multiply)),
+        ("-1e308 - 1e308", Error::NonFiniteNumber(Operator::Minus)),
+        ("1e308 + 1e308 + 1", Error::NonFiniteNumber(Operator::Plus)),
+        ("inf + 1", Error::NonFiniteNumber(Operator::Plus)),
+        ("pow(10, 400)", Error::NonFiniteResult(String::from("pow"))),
+        ("ln(-1)", Error::NonFiniteResult(String::from("ln"))),
+        ("ln(0)", Error::NonFiniteResult(String::from("ln"))),
+        ("sqrt(-1)", Error::NonFiniteResult(String::from("sqrt"))),
+        ("log(0, 10)", Error::NonFiniteResult(String::from("log"))),
+        ("log10(-1)", Error::NonFiniteResult(String::from("log10"))),
+        ("round(1 / zero)", Error::NonFiniteNumber(Operator::Divide)),
+    ];
+
+    for (script, error) in cases {
+        let ast = compile(script).unwrap();
+
+        let value = execute(&env, &ast).unwrap();
+        assert!(
+            matches!(value, Value::Number(n) if !n.is_finite()),
+            "{script}"
+        );
+        assert_eq!(
+            Err(error),
+            execute_with_options(&env, &ast, checked.clone()),
+            "{script}"
+        );
+    }
+
+    // finite results, non-finite literals and caught errors are not affected
+    for (script, expected) in [
+        ("10 / 4", Value::Number(2.5)),
+        ("7 mod 3", Value::Number(1.0)),
+        ("pow(2, 10)", Value::Number(1024.0)),
+        ("inf > 5", Value::Boolean(true)),
+        ("is_nan(nan)", Value::Boolean(true)),
+        ("try(1 / zero, -1)", Value::Number(-1.0)),
+    ] {
+        let ast = compile(script).unwrap();
+
+        assert_eq!(
+            Ok(expected),
+            execute_with_options(&env, &ast, checked.clone()),
+            "{script}"
+        );
+    }
+}
+
+#[test]
+fn approximate_equality() {
+    assert_bool(true, "0.1 + 0.2 ~= 0.3");
+    assert_bool(true, "1e10 ~= 1e10 + 1");
+    assert_bool(false, "1 ~= 2");
+    assert_bool(
+        cfg!(feature = "decimal"),
+        "0.1 + 0.2 ~= 0.3 and 0.1 + 0.2 = 0.3",
+    );
+    assert_bool(true, "inf ~= inf");
+    assert_bool(false, "nan ~= nan");
+    assert_bool(true, "approx_equal(0.1 + 0.2, 0.3)");
+    assert_bool(true, "approx_equal(100, 101, 0.01)");
+    assert_bool(false, "approx_equal(100, 102, 0.01)");
+
+    assert_value(Value::Null, "null ~= 1");
+    assert_err("'0.3' ~= 0.3");
+    assert_err("approx_equal('0.3', 0.3)");
+}
+
+#[test]
+fn windowed_arrays() {
+    let sales = "[10, 20, 30, 80, 90, 40]";
+
+    // any 3-month moving average above 60
+    assert_bool(true, &format!("max(moving_average({sales}, 3)) > 60"));
+    assert_bool(false, &format!("max(moving_average({sales}, 3)) > 70"));
+    assert_bool(
+        true,
+        &format!("any(moving_average({sales}, 6) = 45, diff({sales}) = [10, 10, 50, 10, -50])"),
+    );
+    assert_value(
+        Value::Array(vec![Value::Number(45.0)]),
+        &format!("moving_average({sales}, 6)"),
+    );
+    assert_num(270.0, &format!("max(cumulative_sum({sales}))"));
+
+    assert_value(
+        Value::Array(vec![Value::Number(5.0)]),
+        "moving_average([5], 1)",
+    );
+    assert_value(
+        Value::Array(vec![Value::Number(5.0)]),
+        "cumulative_sum([5])",
+    );
+    assert_value(Value::Array(vec![]), "diff([5])");
+    assert_value(Value::Array(vec![]), "cumulative_sum([])");
+    assert_value(Value::Array(vec![]), "diff([])");
+
+    assert_err("moving_average([], 1)");
+    assert_err("moving_average([1, 2], 3)");
+    assert_err("diff([1, 'a'])");
+}
+
+#[test]
+fn percentiles() {
+    let latencies = "[120, 80, 95, 310, 101, 99, 87, 450, 92, 105]";
+
+    assert_bool(true, &format!("percentile({latencies}, 95) > 300"));
+    assert_bool(false, &format!("percentile({latencies}, 50) > 100"));
+    assert_num(100.0, &format!("quantile({latencies}, 0.5)"));
+    assert_num(99.0, &format!("quantile({latencies}, 0.5, 'lower')"));
+    assert_num(101.0, &format!("quantile({latencies}, 0.5, 'higher')"));
+    assert_num(450.0, &format!("percentile({latencies}, 100, 'nearest')"));
+    assert_num(42.0, "percentile([42], 95)");
+
+    assert_err("percentile([], 50)");
+    assert_err("percentile([1, 2], 101)");
+    assert_err("quantile([1, 2], -0.5)");
+    assert_err("quantile([1, 'a'], 0.5)");
+}
+
+#[test]
+fn aggregations() {
+    assert_bool(true, "avg([1, 2, 3, 4]) = 2.5");
+    assert_bool(true, "avg(1, 2, 3) = 2");
+    assert_bool(true, "median([7, 1, 3]) = 3");
+    assert_bool(true, "median([4, 1, 3, 2]) = 2.5");
+    assert_bool(true, "sum([1, 2, 3]) = 6 and sum(1, 2, 3) = 6");
+    assert_bool(true, "product([2, 3, 4]) = 24");
+    assert_bool(true, "sum([]) = 0 and product([]) = 1");
+    assert_num(12.5, "sum(1.5, 2, 9)");
+
+    assert_err("avg([])");
+    assert_err("median([])");
+    assert_err("sum([1, '2'])");
+    assert_err("product(2, true)");
+    assert_err("sum([1, 2], 3)");
+}
+
+#[test]
+fn join_values() {
+    assert_bool(true, "join(['a', 1, 2.5, true]) = 'a,1,2.5,true'");
+    assert_bool(true, "join([1, 'b', false], ' | ') = '1 | b | false'");
+    assert_bool(true, "join([]) = '' and join([], ';') = ''");
+    assert_bool(true, "join(['x']) = 'x'");
+    assert_bool(true, "join(split('a;b;;c', ';'), ';') = 'a;b;;c'");
+    assert_bool(
+        true,
+        "split(join(['a', 'b', 'c'], '-'), '-') = ['a', 'b', 'c']",
+    );
+
+    assert_err("join('abc')");
+    assert_err("join([1, 2], 3)");
+    assert_err("join([1], ',', ';')");
+}
+
+#[test]
+fn padding() {
+    assert_bool(true, "pad_left('42', 5, '0') = '00042'");
+    assert_bool(true, "pad_right('ab', 4) = 'ab  '");
+    assert_bool(true, "center('ab', 5, '*') = '*ab**'");
+    assert_bool(true, "pad_left('overflow', 3) = 'overflow'");
+    assert_bool(true, "pad_right('äö', 4, 'ß') = 'äößß'");
+    assert_bool(true, "pad_left('€', 3, '€') = '€€€'");
+
+    assert_err("pad_left('42', 5, '00')");
+    assert_err("pad_right('42', 5, '')");
+    assert_err("center(42, 5)");
+    assert_err("pad_left('42')");
+}
+
+#[test]
+fn starts_ends_with() {
+    assert_bool(true, "starts_with('DE89370400440532013000', 'DE')");
+    assert_bool(false, "starts_with('AT611904300234573201', 'DE')");
+    assert_bool(true, "ends_with('invoice.pdf', '.pdf')");
+    assert_bool(false, "ends_with('invoice.pdf', '.PDF')");
+    assert_bool(true, "starts_with('äbc', 'ä') and ends_with('äbc', '')");
+    assert_bool(
+        true,
+        "starts_with([1, 2, 3], 1) and ends_with([1, 2, 3], 3)",
+    );
+    assert_bool(false, "starts_with([1, 2, 3], 3) or ends_with([], 1)");
+    assert_bool(true, "ends_with(['a', ['b']], ['b'])");
+
+    assert_err("starts_with(12, '1')");
+    assert_err("ends_with('12', 2)");
+    assert_err("starts_with('12')");
+}
+
+#[test]
+fn fuzzy_matching() {
+    assert_num(3.0, "levenshtein('kitten', 'sitting')");
+    assert_num(0.0, "levenshtein('', '')");
+    assert_num(4.0, "levenshtein('', 'text')");
+    assert_bool(true, "similarity('kitten', 'sitting') > 0.5");
+    assert_bool(true, "similarity('', '') = 1 and similarity('abc', '') = 0");
+    assert_bool(
+        true,
+        "similarity('Müller', 'Mueller') > similarity('Müller', 'Meier')",
+    );
+
+    assert_err("levenshtein('a', 1)");
+    assert_err("similarity(['a'], 'a')");
+    assert_err("similarity('a')");
+}
+
+#[test]
+fn encodings() {
+    assert_execute("base64_encode('Hello World')", "'SGVsbG8gV29ybGQ='");
+    assert_execute("base64_decode('SGVsbG8gV29ybGQ=')", "'Hello World'");
+    assert_bool(true, "base64_decode(base64_encode('Grüße')) = 'Grüße'");
+    assert_execute("url_encode('name=Jöhn Doe')", "'name%3DJ%C3%B6hn%20Doe'");
+    assert_bool(true, "url_decode(url_encode('a/b?c&d')) = 'a/b?c&d'");
+
+    assert_err("base64_decode('!!!')");
+    assert_err("base64_decode('/w==')");
+    assert_err("url_decode('%zz')");
+    assert_err("url_encode(1)");
+}
+
+#[test]
+fn currency_amounts() {
+    // sums of f64 amounts must be rounded before comparing them, unless the
+    // `decimal` feature calculates them exactly
+    assert_bool(cfg!(feature = "decimal"), "0.1 + 0.2 = 0.3");
+    assert_bool(true, "round_to(0.1 + 0.2, 2) = 0.3");
+    assert_bool(true, "round_to(19.99 * 3, 2) = 59.97");
+    assert_bool(
+        true,
+        "round_to(sum(0.1, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1), 2) = 1",
+    );
+    assert_bool(true, "round_to(1.005, 2) = 1.01");
+}
+
+#[test]
+fn optional_params() {
+    assert_bool(true, "replace('Hello', 'o', 'p') = 'Hellp'");
+    assert_bool(true, "replace('Hello', 'o') = 'Hell'");
+    assert_bool(true, "pow(10) = 100");
+    assert_bool(true, "pow(10, 3) = 1000");
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn regex_is_match() {
+    assert_bool(true, "re_is_match('ABCDE', 'BC')");
+    assert_bool(false, "re_is_match('ABCDE', 'EF')");
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn regex_find() {
+    assert_execute("re_find('ABCDE', 'BC')", "['BC']");
+    assert_execute(
+        "re_find('an employer has an employee in employment', 'employ(er|ee|ment|ing|able)')",
+        "['employer', 'employee', 'employment']",
+    );
+    assert_execute(
+        r"re_find('john.smith@example.com','([a-z0-9_\.\-]+)@([\da-z\.\-]+)\.([a-z\.]{2,5})')",
+        "['john.smith@example.com']",
+    );
+
+    assert_execute(r"re_find('12354', '\D')", "[]");
+    assert_execute(r"re_find('ABCDE', '\D*')", "['ABCDE']");
+    assert_execute(r"re_find('ABCDE', '\D')", "['A','B','C','D','E']");
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn regex_capture() {
+    assert_execute(
+        r"re_capture('john.smith@example.com', '(.*)@(.*)\.(.*)')",
+        r"['john.smith@example.com', 'john.smith', 'example', 'com']",
+    );
+
+    assert_execute(
+        r"re_capture('john.smith@example', '(.*)@(.*)\.?(.*)?')",
+        r"['john.smith@example', 'john.smith', 'example', '']",
+    );
+
+    assert_execute(
+        r"re_capture('11 aa 22 bb', '(\d{2})\W(\D{2})')",
+        r"['11 aa', '11', 'aa']",
+    );
+
+    assert_execute(r"re_capture('111', '(\D)(\D)')", r"['', '', '']");
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn regex_capture_named() {
+    assert_execute(
+        r"re_capture_named('john.smith@example.com', '(?<user>.*)@(?<domain>.*)')",
+        r"[['user', 'john.smith'], ['domain', 'example.com']]",
+    );
+
+    assert_execute(
+        r"at(at(re_capture_named('Order 4711', 'Order (?<id>\d+)'), 0), 1)",
+        r"'4711'",
+    );
+
+    assert_execute(r"re_capture_named('abc', '(?<digit>\d)')", r"[]");
+    assert_execute(r"re_capture_named('123', '(\d)(\d)')", r"[]");
+    assert_err(r"re_capture_named('abc', '(?<name>')");
+    assert_err(r"re_capture_named('abc', 1)");
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn regex_split() {
+    assert_bool(true, r"re_split('a1b22c', '\d+') = ['a', 'b', 'c']");
+    assert_bool(true, r"re_split('a, b;c', '[,;]\s*') = ['a', 'b', 'c']");
+    assert_bool(true, r"re_split('a1b2c3d', '\d', 2) = ['a', 'b', 'c3d']");
+    assert_bool(true, r"re_split('1a', '\d') = ['', 'a']");
+    assert_bool(true, r"re_split('abc', 'x') = ['abc']");
+    assert_bool(true, r"length(re_split('abc', '')) = 5");
+
+    assert_err(r"re_split('abc', '(')");
+    assert_err(r"re_split('abc', 1)");
+    assert_err(r"re_split('abc')");
+}
+
+#[test]
+#[cfg(feature = "hash")]
+fn hash_functions() {
+    assert_num(891_568_578.0, "crc32('abc')");
+    assert_num(0.0, "crc32('')");
+    assert_execute(
+        "sha256('abc')",
+        "'ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad'",
+    );
+    assert_bool(
+        true,
+        "sha256('') = 'e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855'",
+    );
+    assert_bool(true, "sha256('a' + 'bc') = sha256('abc')");
+
+    assert_err("crc32(123)");
+    assert_err("sha256(null)");
+    assert_err("sha256()");
 }
 
 #[test]
@@ -580,6 +1530,156 @@ fn ternary_if() {
     assert_execute("if_then(false, 1)", "0");
 }
 
+#[test]
+fn ternary_if_two_params() {
+    assert_execute("if_then(false, true)", "false");
+    assert_execute("if_then(false, 'Hello')", "''");
+    assert_execute("if_then(false, 42)", "0");
+    assert_execute("if_then(false, [1, 2])", "[]");
+    assert_execute("if_then(false, 1 < 2)", "false");
+    assert_execute("if_then(false, not true)", "false");
+    assert_execute("if_then(false, -5)", "0");
+    assert_execute("if_then(false, 5 mod 3)", "0");
+    assert_execute("if_then(false, if_then(true, 'a', 'b'))", "''");
+    assert_execute("if_then(false, 'a' + 'b')", "''");
+    assert_execute("if_then(true, 'a' + 'b')", "'ab'");
+}
+
+#[test]
+fn ternary_if_two_params_short_circuit() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_function(Function::impure(expensive_func, Arity::None, "expensive()"));
+    env.add_variable("guard", Value::Boolean(false));
+
+    let cases = [
+        ("if_then(guard, [expensive()])", Value::Array(vec![])),
+        ("if_then(guard, not expensive())", Value::Boolean(false)),
+        ("if_then(guard, expensive() = 1)", Value::Boolean(false)),
+        ("if_then(false, [expensive()])", Value::Array(vec![])),
+    ];
+
+    for (script, expected) in cases {
+        let mut ast = compile(script).unwrap();
+        optimize(&env, &mut ast).unwrap();
+
+        assert_eq!(Ok(expected), execute(&env, &ast));
+    }
+}
+
+#[test]
+fn ternary_if_two_params_counted() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn counted(params: &[Value]) -> NativeResult {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        Ok(params[0].clone())
+    }
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_function(Function::impure(
+        counted,
+        Arity::required(1),
+        "counted(value: Any): Any",
+    ));
+
+    // the second parameter is lazy if the type of its result is known statically
+    let cases = [
+        ("if_then(guard, [counted(value)])", true),
+        ("if_then(guard, counted(value) = 1)", true),
+        ("if_then(guard, not counted(value))", true),
+        ("if_then(guard, counted(value) is empty)", true),
+        ("if_then(guard, counted(value))", false),
+        ("if_then(guard, -counted(value))", false),
+        ("if_then(guard, counted(value) * 2)", false),
+        ("if_then(guard, counted(value) + 1)", false),
+    ];
+
+    for guard in [true, false] {
+        for value in [Value::Number(42.0), Value::Null] {
+            env.add_variable("guard", Value::Boolean(guard));
+            env.add_variable("value", value.clone());
+
+            for (script, lazy) in cases {
+                let mut ast = compile(script).unwrap();
+                let eager = slac::execute(&env, &ast);
+                optimize(&env, &mut ast).unwrap();
+
+                CALLS.store(0, Ordering::Relaxed);
+                let result = slac::execute(&env, &ast);
+                let expected = usize::from(guard || !lazy);
+
+                assert_eq!(eager, result, "{script} with {value:?}");
+                assert_eq!(expected, CALLS.load(Ordering::Relaxed), "{script}");
+            }
+        }
+    }
+}
+
+/// The lazy `if_then` gives the same result as the eager one for every type of
+/// the condition and the second parameter.
+#[test]
+fn ternary_if_two_params_matrix() {
+    let values = [
+        Value::Boolean(true),
+        Value::Boolean(false),
+        Value::Number(42.0),
+        Value::String(String::from("text")),
+        Value::Array(vec![Value::Number(1.0)]),
+        Value::Null,
+    ];
+    let scripts = [
+        "if_then(guard, value)",
+        "if_then(guard, -value)",
+        "if_then(guard, value * 2)",
+        "if_then(guard, value + value)",
+        "if_then(guard, value xor true)",
+        "if_then(guard, value in [value])",
+        "if_then(guard, [value][0])",
+        "if_then(guard, [value])",
+        "if_then(guard, value = 1)",
+        "if_then(guard, not value)",
+        "if_then(guard, value is empty)",
+        "if_then(guard, 'text')",
+        "if_then(guard, if guard then value else value)",
+    ];
+
+    for guard in &values {
+        for value in &values {
+            let mut env = StaticEnvironment::default();
+            extend_environment(&mut env);
+            env.add_variable("guard", guard.clone());
+            env.add_variable("value", value.clone());
+
+            for script in scripts {
+                let eager = execute(&env, &compile(script).unwrap());
+                let mut ast = compile(script).unwrap();
+                optimize(&env, &mut ast).unwrap();
+
+                assert_eq!(
+                    eager,
+                    execute(&env, &ast),
+                    "{script} with guard = {guard:?}, value = {value:?}"
+                );
+            }
+        }
+    }
+
+    // a constant condition which is not a Boolean fails while optimizing
+    let wrong_type = Err(Error::NativeFunctionError(
+        String::from("if_then"),
+        slac::stdlib::NativeError::WrongParameterType,
+    ));
+
+    for script in ["if_then('a', 1)", "if_then(1, [2])", "if_then([], 1 < 2)"] {
+        assert_eq!(wrong_type, execute_with_stdlib(script, false), "{script}");
+        assert_eq!(wrong_type, execute_with_stdlib(script, true), "{script}");
+    }
+}
+
 #[test]
 fn optimize_fold() {
     assert_execute("1+1", "2");
@@ -600,10 +1700,17 @@ fn optimize_fold() {
     assert_expr("10", "max(min(30, 10), 5)");
     assert_expr("true", "all([true, true])");
     assert_expr("max(some_var, 5)", "max(some_var, min(10, 5))");
-    assert_expr("random()", "random()");
-    assert_expr("choice(1,2,3)", "choice(1,2,3)");
-    assert_expr("choice(1,2,3)", "choice(1,1+1,3)");
+    #[cfg(feature = "random")]
+    {
+        assert_expr("random()", "random()");
+        assert_expr("choice(1,2,3)", "choice(1,2,3)");
+        assert_expr("choice(1,2,3)", "choice(1,1+1,3)");
+    }
+    #[cfg(feature = "chrono")]
     assert_expr("true", "is_leap_year(string_to_date('2024-01-01'))");
+    assert_expr("false", "1 < 2 implies 2 > 3");
+    assert_expr("true", "1 = 1 iff 'a' + 'b' = 'ab'");
+    assert_expr("some_var implies true", "some_var implies 1 < 2");
 
     assert_value(Value::Array(vec![Value::Boolean(true)]), "[true]");
     assert_value(
@@ -611,6 +1718,7 @@ fn optimize_fold() {
         "Min(20, if_then(all([true or false, true]) and true, 10, 30))",
     );
 
+    #[cfg(feature = "regex")]
     assert_value(
         Value::String("john.smith@example.com".to_string()),
         "at(re_capture('john.smith@example.com', '(.*)@.*\\.*'), 0)",
@@ -623,6 +1731,7 @@ fn optimize_fold() {
     );
 }
 
+#[cfg(feature = "random")]
 #[test]
 fn random() {
     for _ in 0..100 {
@@ -633,6 +1742,51 @@ fn random() {
     }
 }
 
+#[test]
+fn seeded_random() {
+    assert_bool(true, "random_seeded(42) = random_seeded(42)");
+    assert_bool(false, "random_seeded(42) = random_seeded(43)");
+    #[cfg(not(feature = "decimal"))]
+    assert_bool(true, "random_seeded(42, 10) = random_seeded(42) * 10");
+    // the range of `random` is applied in f64, not as an exact multiplication
+    #[cfg(feature = "decimal")]
+    assert_bool(true, "random_seeded(42, 10) ~= random_seeded(42) * 10");
+    assert_bool(
+        true,
+        "choice_seeded(1, [1, 2, 3]) = choice_seeded(1, 1, 2, 3)",
+    );
+    assert_bool(true, "choice_seeded(1, 'a', 'b') in ['a', 'b']");
+    assert_expr("0.8833108082136426", "random_seeded(0)");
+
+    assert_err("random_seeded('42')");
+    assert_err("choice_seeded(1)");
+}
+
+#[cfg(feature = "random")]
+#[test]
+fn random_with_seeded_environment() {
+    let seeded = |seed| {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+        env.set_random_seed(seed);
+        env
+    };
+    // every call advances the generator, so the representations are not compared
+    let ast = compile("[random(), random(100), choice(['a', 'b', 'c'])]").unwrap();
+
+    let first = slac::execute(&seeded(0), &ast);
+    assert_eq!(first, slac::execute(&seeded(0), &ast));
+    assert_ne!(first, slac::execute(&seeded(1), &ast));
+
+    let env = seeded(0);
+    let ast = compile("random()").unwrap();
+    assert_eq!(
+        Ok(Value::Number(0.8833108082136426)),
+        slac::execute(&env, &ast)
+    );
+    assert_ne!(slac::execute(&env, &ast), slac::execute(&env, &ast));
+}
+
 #[test]
 fn has_comments() {
     assert_execute("3.14", "3 + .14 // eh, close enough");
@@ -682,6 +1836,33 @@ fn has_comments() {
 
     assert_err("// todo add expression");
     assert_err("{todo add expression}");
+
+    // inside call arguments
+    assert_execute("3", "max {a} ( {b} 1 {c} , // d\n 3 {e} ) {f}");
+    assert_execute("3", "max(1, // first\n {second} // third\n 3)");
+    assert_execute("''", "lowercase(\n// nothing\n'')");
+
+    // inside array literals
+    assert_execute(
+        "[1, 2, 3]",
+        "[ {a} 1, // b\n 2, {c} {d} // e\n // f\n 3 {g} ]",
+    );
+    assert_execute("[]", "[ {empty} ]");
+    assert_execute("[]", "[ // empty\n]");
+
+    // between a unary operator and its operand
+    assert_execute("-4", "- {minus} 4");
+    assert_execute("-4", "- // minus\n 4");
+    assert_execute("false", "not {negated} // twice\n true");
+
+    // immediately before EOF without trailing newline
+    assert_execute("4", "4//");
+    assert_execute("4", "4{}");
+    assert_execute("4", "4 {a}{b} {c {d}}// e");
+
+    // a line comment inside a block comment has no meaning, braces still count
+    assert_execute("4", "{ // } 4");
+    assert_execute("4", "{ // { } } 4");
 }
 
 #[test]
@@ -733,3 +1914,989 @@ fn null_or_bool() {
     let result = execute(&env, &ast);
     assert_eq!(Ok(Value::Boolean(false)), result);
 }
+
+#[test]
+fn try_fallback() {
+    assert_execute("0", "try(float('abc'), 0)");
+    assert_execute("0", "try(int(9007199254740992 * 2), 0)");
+    assert_execute("'fallback'", "try('a' - 1, 'fallback')");
+    assert_execute("'fallback'", "try(true + 1, 'fallback')");
+    assert_execute("42.5", "try(float('42.5'), 0)");
+    assert_execute("1", "try(1, 'a' - 1)");
+    assert_execute("3", "try(try(float('x'), 'a' - 1), 3)");
+    assert_execute("2", "try(try(float('x'), 2), 3)");
+    assert_bool(true, "try(float('500'), 0) > 100");
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let ast = compile("try(float(raw_amount), 0) + try(undefined_var * 2, 5)").unwrap();
+    assert_eq!(Ok(Value::Number(5.0)), execute(&env, &ast));
+
+    let ast = compile("try(float('x'), 'a' - 1)").unwrap();
+    assert_eq!(
+        Err(Error::InvalidBinaryOperator(Operator::Minus)),
+        execute(&env, &ast)
+    );
+
+    let ast = compile("try(float(raw_amount), missing)").unwrap();
+    assert_eq!(
+        Err(Error::MissingVariable(String::from("raw_amount"))),
+        check_variables_and_functions(&env, &ast)
+    );
+
+    env.add_variable("raw_amount", Value::String(String::from("12")));
+    assert_eq!(
+        Err(Error::MissingVariable(String::from("missing"))),
+        check_variables_and_functions(&env, &ast)
+    );
+}
+
+#[test]
+fn coalesce_undefined() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("discount", Value::Number(5.0));
+    env.add_variable("name", Value::String(String::new()));
+
+    let cases = [
+        ("coalesce(missing, 0)", Ok(Value::Number(0.0))),
+        ("coalesce(discount, 0)", Ok(Value::Number(5.0))),
+        (
+            "coalesce(missing, other, 'default')",
+            Ok(Value::String(String::from("default"))),
+        ),
+        ("coalesce(missing, name, other)", Ok(Value::Null)),
+        (
+            "coalesce(missing * 2, discount + 1)",
+            Ok(Value::Number(6.0)),
+        ),
+        (
+            "coalesce(if missing then 1 else 2, 3)",
+            Ok(Value::Number(3.0)),
+        ),
+        ("coalesce(max(missing, 1), 7) + 1", Ok(Value::Number(8.0))),
+        ("10 * coalesce(missing, discount)", Ok(Value::Number(50.0))),
+        // only the parameters of `coalesce` accept undefined variables
+        (
+            "max(missing, 1)",
+            Err(Error::UndefinedVariable(String::from("missing"))),
+        ),
+        (
+            "coalesce(1 - 'a', 0)",
+            Err(Error::InvalidBinaryOperator(Operator::Minus)),
+        ),
+        (
+            "coalesce()",
+            Err(Error::NativeFunctionError(
+                String::from("coalesce"),
+                slac::stdlib::NativeError::WrongParameterCount(1),
+            )),
+        ),
+    ];
+
+    for (script, expected) in cases {
+        let ast = compile(script).unwrap();
+        assert_eq!(expected, execute(&env, &ast), "{script}");
+    }
+}
+
+#[test]
+fn plus_chain() {
+    assert_eq!(
+        Ok(Value::String(String::from("abcd"))),
+        execute_raw("'a' + 'b' + 'c' + 'd'")
+    );
+    assert_eq!(
+        Ok(Value::String(String::from("ab-cd"))),
+        execute_raw("'a' + 'b' + ('-' + 'c') + 'd'")
+    );
+    assert_eq!(Ok(Value::Number(10.0)), execute_raw("1 + 2 + 3 + 4"));
+    assert_eq!(Ok(Value::Number(2.0)), execute_raw("1 + 2 - 3 + 2"));
+    assert_eq!(
+        Ok(Value::Array(vec![Value::Number(1.0), Value::Number(2.0)])),
+        execute_raw("[] + [1] + [2]")
+    );
+
+    let plus = Err(Error::InvalidBinaryOperator(Operator::Plus));
+    assert_eq!(plus, execute_raw("'a' + 'b' + 1 + 'c'"));
+    assert_eq!(plus, execute_raw("1 + 2 + 'c' + 3"));
+    assert_eq!(plus, execute_raw("'a' + 1 + undefined_var"));
+    assert_eq!(plus, execute_raw("'a' + 'b' + [1]"));
+    assert_eq!(
+        Err(Error::UndefinedVariable(String::from("undefined_var"))),
+        execute_raw("'a' + undefined_var + 1")
+    );
+    assert_eq!(
+        Err(Error::UndefinedVariable(String::from("undefined_var"))),
+        execute_raw("undefined_var + 'a' + 1")
+    );
+
+    let mut env = StaticEnvironment::default();
+    env.add_function(Function::new(expensive_func, Arity::None, "expensive()"));
+
+    let ast = compile("'a' + 1 + expensive() + 'b'").unwrap();
+    assert_eq!(plus, execute(&env, &ast));
+}
+
+#[test]
+fn plus_chain_with_null() {
+    let parts = [
+        ("s", "'x'", Value::String(String::from("x"))),
+        ("n", "null", Value::Null),
+        ("d", "1", Value::Number(1.0)),
+        ("a", "[1]", Value::Array(vec![Value::Number(1.0)])),
+    ];
+
+    let mut env = StaticEnvironment::default();
+    for (name, _, value) in &parts {
+        env.add_variable(name, value.clone());
+    }
+
+    for first in &parts {
+        for second in &parts {
+            for third in &parts {
+                // the chain must agree with the pairwise `Add` of the values
+                let expected =
+                    (first.2.clone() + second.2.clone()).and_then(|sum| sum + third.2.clone());
+
+                for script in [
+                    format!("{} + {} + {}", first.0, second.0, third.0),
+                    format!("{} + {} + {}", first.1, second.1, third.1),
+                ] {
+                    let mut ast = compile(&script).unwrap();
+                    assert_eq!(expected, execute(&env, &ast), "{script}");
+
+                    match optimize(&env, &mut ast) {
+                        Ok(()) => assert_eq!(expected, execute(&env, &ast), "{script} optimized"),
+                        Err(error) => assert_eq!(expected, Err(error), "{script} optimized"),
+                    }
+                }
+            }
+        }
+    }
+
+    assert_eq!(Ok(Value::Null), execute_raw("'a' + null + 'b'"));
+    assert_eq!(Ok(Value::Null), execute_raw("'a' + 'b' + null + 1"));
+}
+
+#[test]
+fn bool_conformance() {
+    // input, expected `bool()` result (`None` for an error), expected `truthy()` result
+    let cases = [
+        ("'true'", Some(true), true),
+        ("'TRUE'", Some(true), true),
+        ("'false'", Some(false), true),
+        ("'FALSE'", Some(false), true),
+        ("'1'", Some(true), true),
+        ("'0'", Some(false), true),
+        ("''", None, false),
+        ("'yes'", None, true),
+        ("' true'", None, true),
+        ("1", Some(true), true),
+        ("0", Some(false), false),
+        ("true", Some(true), true),
+        ("false", Some(false), false),
+    ];
+
+    for (input, expected_bool, expected_truthy) in cases {
+        let result = execute_with_stdlib(&format!("bool({input})"), false);
+
+        match expected_bool {
+            Some(expected) => assert_eq!(Ok(Value::Boolean(expected)), result, "bool({input})"),
+            None => assert!(result.is_err(), "bool({input})"),
+        }
+
+        assert_eq!(
+            Ok(Value::Boolean(expected_truthy)),
+            execute_with_stdlib(&format!("truthy({input})"), false),
+            "truthy({input})"
+        );
+    }
+}
+
+#[test]
+fn variable_reads() {
+    let mut env = StaticEnvironment::from_declarations(&[
+        "flag: Boolean = true",
+        "x: Number = 1",
+        "y: Number = 2",
+    ])
+    .unwrap();
+    extend_environment(&mut env);
+
+    let reads = |script: &str| {
+        let mut ast = compile(script).unwrap();
+        optimize(&env, &mut ast).unwrap();
+
+        let (_, reads) = execute_with_reads(&env, &ast);
+        let mut reads: Vec<String> = reads.into_iter().collect();
+        reads.sort();
+        reads
+    };
+
+    assert!(reads("false and x").is_empty());
+    assert_eq!(vec!["y"], reads("false or y or x"));
+    assert_eq!(vec!["missing"], reads("missing = ''"));
+    assert_eq!(vec!["flag", "x"], reads("if_then(flag, x, y)"));
+    assert_eq!(vec!["flag", "y"], reads("if_then(not flag, x, y)"));
+    assert_eq!(vec!["flag", "x", "y"], reads("[flag, x, y, x]"));
+    assert_eq!(vec!["x", "y", "zero"], reads("try(float(x) / zero, y)"));
+}
+
+#[test]
+fn literal_comparison_parity() {
+    let values = [
+        Value::Number(0.0),
+        Value::Number(1.5),
+        Value::Number(-3.0),
+        Value::String(String::new()),
+        Value::String(String::from("a")),
+        Value::String(String::from("10")),
+        Value::Boolean(true),
+        Value::Boolean(false),
+        Value::Array(vec![]),
+        Value::Array(vec![Value::Number(1.0)]),
+    ];
+    let operators = [
+        Operator::Equal,
+        Operator::NotEqual,
+        Operator::Greater,
+        Operator::GreaterEqual,
+        Operator::Less,
+        Operator::LessEqual,
+    ];
+    let variable = |name: &str| Expression::Variable {
+        name: String::from(name),
+    };
+    let binary = |left, right, operator| Expression::Binary {
+        left: Box::new(left),
+        right: Box::new(right),
+        operator,
+    };
+
+    for strict_type_comparisons in [false, true] {
+        let options = ExecutionOptions {
+            strict_type_comparisons,
+            ..Default::default()
+        };
+
+        for variable_value in values.iter().map(Some).chain([None]) {
+            for literal_value in &values {
+                let mut env = StaticEnvironment::default();
+                env.add_variable("literal", literal_value.clone());
+                if let Some(value) = variable_value {
+                    env.add_variable("var", value.clone());
+                }
+
+                let literal = Expression::Literal {
+                    value: literal_value.clone(),
+                };
+
+                for operator in operators {
+                    // the fast path compares a variable and a literal, the
+                    // reference compares two variables holding the same values
+                    let cases = [
+                        (
+                            binary(variable("var"), literal.clone(), operator),
+                            binary(variable("var"), variable("literal"), operator),
+                        ),
+                        (
+                            binary(literal.clone(), variable("var"), operator),
+                            binary(variable("literal"), variable("var"), operator),
+                        ),
+                    ];
+
+                    for (fast, reference) in cases {
+                        assert_eq!(
+                            execute_with_options(&env, &reference, options.clone()),
+                            execute_with_options(&env, &fast, options.clone()),
+                            "{variable_value:?} {operator:?} {literal_value:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn raw_strings() {
+    let cases = [
+        (
+            r"re_find('john.smith@example.com','([a-z0-9_\.\-]+)@([\da-z\.\-]+)\.([a-z\.]{2,5})')",
+            r"re_find(r'john.smith@example.com',r'([a-z0-9_\.\-]+)@([\da-z\.\-]+)\.([a-z\.]{2,5})')",
+        ),
+        (r"re_find('12354', '\D')", r"re_find('12354', r'\D')"),
+        (
+            r"re_capture('11 aa 22 bb', '(\d{2})\W(\D{2})')",
+            r"re_capture(r'11 aa 22 bb', r'(\d{2})\W(\D{2})')",
+        ),
+        (
+            r"re_replace('john.smith@example.com', '(.*)@(.*)\.(.*)', '$1@test.$3')",
+            r"re_replace('john.smith@example.com', r'(.*)@(.*)\.(.*)', r'$1@test.$3')",
+        ),
+        (r"'It''s \'", r"r'It''s \'"),
+    ];
+
+    for (plain, raw) in cases {
+        assert_eq!(compile(plain), compile(raw), "{raw}");
+    }
+
+    for value in [
+        Value::String(String::from(r"(\d{2})\W'")),
+        Value::String(String::from("It's")),
+        Value::Array(vec![
+            Value::Number(1.5),
+            Value::Boolean(false),
+            Value::String(String::from(r"\D")),
+        ]),
+    ] {
+        let ast = compile(&value.to_literal()).unwrap();
+        assert_eq!(Ok(value), execute(&StaticEnvironment::default(), &ast));
+    }
+}
+
+#[test]
+fn set_variable_contains() {
+    let values = vec![
+        Value::Number(3.0),
+        Value::String(String::from("DE")),
+        Value::String(String::from("42")),
+        Value::Boolean(false),
+        Value::Array(vec![Value::Number(1.0), Value::String(String::from("a"))]),
+        Value::Array(vec![Value::Array(vec![Value::Boolean(true)])]),
+        Value::Number(3.0),
+    ];
+    let needles = [
+        "3",
+        "'3'",
+        "'DE'",
+        "'de'",
+        "42",
+        "0",
+        "false",
+        "true",
+        "[1, 'a']",
+        "['1', 'a']",
+        "[[true]]",
+        "[[1]]",
+        "[]",
+    ];
+
+    let mut set_env = StaticEnvironment::default();
+    extend_environment(&mut set_env);
+    set_env.add_set_var("haystack", values.clone());
+
+    let mut array_env = StaticEnvironment::default();
+    extend_environment(&mut array_env);
+    array_env.add_variable("haystack", Value::Array(values));
+
+    for needle in needles {
+        let ast = compile(&format!("contains(haystack, {needle})")).unwrap();
+        assert_eq!(
+            execute(&array_env, &ast),
+            execute(&set_env, &ast),
+            "{needle}"
+        );
+
+        let membership = compile(&format!("{needle} in haystack")).unwrap();
+        assert_eq!(
+            execute(&set_env, &ast),
+            execute(&set_env, &membership),
+            "{needle}"
+        );
+    }
+
+    let ast = compile("haystack").unwrap();
+    let Ok(Value::Array(sorted)) = execute(&set_env, &ast) else {
+        panic!("set variable is not an array");
+    };
+    assert_eq!(6, sorted.len());
+    assert!(sorted.windows(2).all(|pair| pair[0] < pair[1]));
+
+    set_env.add_variable("haystack", Value::Array(vec![]));
+    let ast = compile("contains(haystack, 3)").unwrap();
+    assert_eq!(Ok(Value::Boolean(false)), execute(&set_env, &ast));
+}
+
+#[test]
+fn comparison_chain() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn counted(params: &[Value]) -> NativeResult {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        Ok(params[0].clone())
+    }
+
+    let mut env = StaticEnvironment::default();
+    env.add_function(Function::impure(
+        counted,
+        Arity::required(1),
+        "counted(value: Any): Any",
+    ));
+
+    let cases = [
+        (true, "1 <= 5 <= 10", 0),
+        (false, "1 <= 11 <= 10", 0),
+        (false, "1 <= 0 <= 10", 0),
+        (true, "10 > counted(5) >= 5", 1),
+        (false, "10 > counted(15) >= 5", 1),
+        (true, "1 < 2 < 3 < counted(4)", 1),
+        (false, "5 < 1 < counted(3)", 0), // short-circuits like `and`
+        (true, "counted(1) < counted(2) < counted(3)", 3),
+    ];
+
+    for (expected, script, calls) in cases {
+        CALLS.store(0, Ordering::Relaxed);
+        let ast = compile(script).unwrap();
+
+        assert_eq!(
+            Ok(Value::Boolean(expected)),
+            slac::execute(&env, &ast),
+            "{script}"
+        );
+        assert_eq!(calls, CALLS.load(Ordering::Relaxed), "{script}");
+
+        // the flat representation evaluates every operand just as often
+        CALLS.store(0, Ordering::Relaxed);
+        let flat = FlatExpression::from(&ast);
+
+        assert_eq!(Ok(Value::Boolean(expected)), execute_flat(&env, &flat));
+        assert_eq!(calls, CALLS.load(Ordering::Relaxed), "{script}");
+    }
+
+    assert_eq!(
+        Err(Error::MixedComparisonChain(
+            Operator::Less,
+            Operator::Greater
+        )),
+        compile("1 < x > 0")
+    );
+
+    let mut ast = compile("1 < 2 <= 2").unwrap();
+    optimize(&env, &mut ast).unwrap();
+    assert_eq!(
+        Expression::Literal {
+            value: Value::Boolean(true)
+        },
+        ast
+    );
+}
+
+#[test]
+fn keyed_error_conformance() {
+    let cases = [
+        ("not_a_function(1)", "err.function.not_found"),
+        ("chr()", "err.param.count"),
+        ("chr('a')", "err.param.type"),
+        ("at([1], 5)", "err.index.bounds"),
+        ("at([1], -1)", "err.index.negative"),
+        ("bool('yes')", "err.bool.parse"),
+        ("float('abc')", "err.number.parse"),
+        ("int(9007199254740992 * 2)", "err.number.safe_integer"),
+        ("chr(200)", "err.string.chr_range"),
+        ("ord('ä')", "err.string.ord_range"),
+        ("ord('ab')", "err.string.ord_length"),
+        #[cfg(feature = "regex")]
+        ("re_find('a', '(')", "err.regex.invalid"),
+        #[cfg(feature = "chrono")]
+        ("string_to_date('2023-13-01')", "err.datetime.parse"),
+        #[cfg(feature = "chrono")]
+        ("string_to_week_date('2023-08-27')", "err.datetime.parse"),
+        #[cfg(feature = "chrono")]
+        ("string_to_time('25:00:00')", "err.datetime.parse"),
+        #[cfg(feature = "chrono")]
+        ("string_to_datetime('2023-08-27')", "err.datetime.parse"),
+        #[cfg(feature = "chrono")]
+        ("date_from_rfc2822('2023-08-27')", "err.datetime.parse"),
+        #[cfg(feature = "chrono")]
+        ("date_from_rfc3339('2023-08-27')", "err.datetime.parse"),
+        #[cfg(feature = "chrono")]
+        ("parse_date_any('8/27/23')", "err.datetime.parse_any"),
+        #[cfg(feature = "chrono")]
+        ("inc_month(100000000000)", "err.datetime.range"),
+        #[cfg(feature = "chrono")]
+        ("encode_date(2023, 13, 1)", "err.datetime.encode_date"),
+        #[cfg(feature = "chrono")]
+        ("encode_time(25, 0, 0)", "err.datetime.encode_time"),
+        ("moving_average([1, 'a'], 1)", "err.math.not_number"),
+        ("moving_average([1, 2], 3)", "err.math.window"),
+        ("avg([])", "err.math.empty"),
+        ("base64_decode('!!!')", "err.string.base64"),
+        ("url_decode('%E')", "err.string.url_encoding"),
+        ("url_decode('%FF')", "err.string.utf8"),
+        ("quantile([], 0.5)", "err.math.quantile_empty"),
+        ("quantile([1], 2)", "err.math.quantile_range"),
+        ("percentile([1], 200)", "err.math.percentile_range"),
+        ("quantile([1], 0.5, 'cubic')", "err.math.interpolation"),
+        ("clamp(5, 10, 0)", "err.math.clamp_range"),
+        ("parse_money('12a4 EUR')", "err.money.parse"),
+        #[cfg(feature = "unicode")]
+        ("normalize('abc', 'NFKC')", "err.string.normalize_form"),
+    ];
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    for (script, key) in cases {
+        let ast = compile(script).unwrap();
+
+        let Err(error) = execute(&env, &ast) else {
+            panic!("{script} did not fail");
+        };
+        let Error::NativeFunctionError(_, native) = &error else {
+            panic!("{script} failed with {error:?}");
+        };
+
+        assert_eq!(key, native.key(), "{script}");
+        assert!(
+            DefaultCatalog.template(key).is_some(),
+            "{key} has no default message"
+        );
+        assert_eq!(error.to_string(), error.render_with(&DefaultCatalog));
+    }
+}
+
+#[test]
+fn scoped_variable_access() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("price", Value::Number(10.0));
+    env.add_variable("internal_cost", Value::Number(7.0));
+    env.add_set_var("regions", vec![Value::String(String::from("DE"))]);
+
+    let allowlist = HashSet::from([String::from("Price"), String::from("discount")]);
+    let scoped = ScopedEnvironment::with_allowed_variables(&env, allowlist.clone());
+
+    let allowed = [
+        ("price > 5", true),
+        ("max(price, 20) = 20", true),
+        // allowed but undefined variables keep the rules for missing variables
+        ("discount = ''", true),
+        ("discount = 0 or price = 10", true),
+    ];
+
+    for (script, expected) in allowed {
+        let ast = compile(script).unwrap();
+
+        assert_eq!(
+            Ok(Value::Boolean(expected)),
+            execute(&scoped, &ast),
+            "{script}"
+        );
+        assert_eq!(Ok(()), check_variable_scope(&ast, &allowlist), "{script}");
+    }
+
+    let denied = [
+        ("internal_cost > 5", "internal_cost"),
+        ("price > INTERNAL_COST", "INTERNAL_COST"),
+        ("unknown = ''", "unknown"),
+        ("unknown <> ''", "unknown"),
+        ("'' = unknown", "unknown"),
+        ("unknown and true", "unknown"),
+        ("unknown or true", "unknown"),
+        ("1 < unknown < 3", "unknown"),
+        ("try(internal_cost, 0) = 0", "internal_cost"),
+        ("contains(regions, 'DE')", "regions"),
+        ("str(internal_cost) = '7'", "internal_cost"),
+    ];
+
+    for (script, name) in denied {
+        let ast = compile(script).unwrap();
+        let denied = || Error::VariableAccessDenied(name.to_string());
+
+        assert_eq!(Err(denied()), execute(&scoped, &ast), "{script}");
+        assert_eq!(
+            Err(denied()),
+            check_variable_scope(&ast, &allowlist),
+            "{script}"
+        );
+    }
+
+    // without a scope the missing variable is compared as empty
+    let ast = compile("unknown = ''").unwrap();
+    assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+
+    let ast = compile("price > internal_cost").unwrap();
+    assert_eq!(
+        Err(Error::MissingVariable(String::from("internal_cost"))),
+        check_variables_and_functions(&scoped, &ast)
+    );
+}
+
+#[test]
+fn environment_from_declarations() {
+    let env = StaticEnvironment::from_declarations(&[
+        "amount: Number = 12.5",
+        "name: String = 'x'",
+        "tags: Array<String> = ['a', 'b']",
+        "lookup(key: String, fallback: String = ''): String => 'stub'",
+        "fail(...): Any => 1 - 'a'",
+    ])
+    .unwrap();
+
+    let run = |script: &str| {
+        let ast = compile(script).unwrap();
+        check_variables_and_functions(&env, &ast)?;
+        execute(&env, &ast)
+    };
+
+    assert_eq!(Ok(Value::Number(12.5)), run("amount"));
+    assert_eq!(Ok(Value::String(String::from("xab"))), run("name + 'ab'"));
+    assert_eq!(
+        Ok(Value::Array(vec![
+            Value::String(String::from("a")),
+            Value::String(String::from("b"))
+        ])),
+        run("tags")
+    );
+    assert_eq!(Ok(Value::String(String::from("stub"))), run("lookup(name)"));
+    assert_eq!(
+        Ok(Value::String(String::from("stub"))),
+        run("lookup(name, 'y')")
+    );
+    assert!(matches!(
+        run("lookup()"),
+        Err(Error::ParamCountMismatch { .. })
+    ));
+    assert!(matches!(
+        run("fail(1, 2)"),
+        Err(Error::NativeFunctionError(name, _)) if name == "fail"
+    ));
+}
+
+#[test]
+fn environment_from_invalid_declarations() {
+    let reason = |declaration: &str| match StaticEnvironment::from_declarations(&[declaration]) {
+        Err(Error::InvalidDeclaration(_, reason)) => reason,
+        Err(error) => panic!("{declaration}: unexpected error {error:?}"),
+        Ok(_) => panic!("{declaration}: unexpectedly accepted"),
+    };
+
+    assert_eq!("unknown type \"Integer\"", reason("count: Integer = 1"));
+    assert_eq!("type Any requires a default value", reason("value: Any"));
+    assert_eq!(
+        "expected a Number but got a String literal",
+        reason("count: Number = '1'")
+    );
+    assert!(reason("count: Number = 1 +").starts_with("invalid literal \"1 +\""));
+    assert_eq!(
+        "required parameter \"b\" after an optional parameter",
+        reason("f(a: Number = 1, b: Number): Number => 1")
+    );
+    assert_eq!(
+        "'...' must be the only parameter",
+        reason("f(a: Number, ...) => 1")
+    );
+    assert_eq!("parameter \"a\" has no type", reason("f(a) => 1"));
+    assert_eq!("missing function body after '=>'", reason("f(): Number"));
+    assert!(reason("f(): Number => 1 +").starts_with("invalid function body"));
+}
+
+#[test]
+fn execute_as_rust_types() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let run = |script: &str| compile(script).unwrap();
+
+    assert_eq!(Ok(true), execute_as::<bool>(&env, &run("1 < 2")));
+    assert_eq!(Ok(3.0), execute_as::<f64>(&env, &run("1 + 2")));
+    assert_eq!(
+        Ok(String::from("ab")),
+        execute_as::<String>(&env, &run("'a' + 'b'"))
+    );
+    assert_eq!(
+        Ok(vec![Value::Number(1.0)]),
+        execute_as::<Vec<Value>>(&env, &run("[1]"))
+    );
+
+    // NaN is still a Number
+    assert!(execute_as::<f64>(&env, &run("nan")).unwrap().is_nan());
+    assert_eq!(Ok(f64::INFINITY), execute_as::<f64>(&env, &run("inf")));
+
+    // no coercion: no truthiness and no parsing of strings
+    let mismatch = |expected, found| Error::ResultTypeMismatch { expected, found };
+    assert_eq!(
+        Err(mismatch("Boolean", "Number")),
+        execute_as::<bool>(&env, &run("1"))
+    );
+    assert_eq!(
+        Err(mismatch("Boolean", "String")),
+        execute_as::<bool>(&env, &run("'true'"))
+    );
+    assert_eq!(
+        Err(mismatch("Number", "String")),
+        execute_as::<f64>(&env, &run("'1'"))
+    );
+    assert_eq!(
+        Err(mismatch("Number", "Boolean")),
+        execute_as::<f64>(&env, &run("true"))
+    );
+    assert_eq!(
+        Err(mismatch("String", "Array")),
+        execute_as::<String>(&env, &run("['a']"))
+    );
+    assert_eq!(
+        Err(mismatch("Array", "String")),
+        execute_as::<Vec<Value>>(&env, &run("'a'"))
+    );
+
+    // runtime errors take precedence
+    assert!(matches!(
+        execute_as::<bool>(&env, &run("1 - 'a'")),
+        Err(error) if !matches!(error, Error::ResultTypeMismatch { .. })
+    ));
+    assert_eq!(
+        "expected a Boolean result but got a Number",
+        execute_as::<bool>(&env, &run("1")).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn array_scalar_equality_lint() {
+    let env = StaticEnvironment::from_declarations(&[
+        "roles: Array<String> = ['admin', 'user']",
+        "other_roles: Array = []",
+        "role: String = 'admin'",
+        "amount: Number = 1",
+    ])
+    .unwrap();
+
+    let lint = |script: &str| {
+        let (ast, spans) = compile_with_spans(script).unwrap();
+        lint_array_scalar_equality(&env, &ast, Some(&spans))
+    };
+    let messages = |script: &str| -> Vec<String> {
+        lint(script).into_iter().map(|lint| lint.message).collect()
+    };
+
+    // positive
+    assert_eq!(
+        vec!["comparing the Array roles with a String is always false, use contains(roles, 'admin') instead"],
+        messages("roles = 'admin'")
+    );
+    assert_eq!(
+        vec!["comparing the Array roles with a Number is always true, use not contains(roles, 1) instead"],
+        messages("1 <> roles")
+    );
+    assert_eq!(
+        vec!["comparing the Array [1, 2] with a Number is always false, use contains([1, 2], 1) instead"],
+        messages("[1, 2] = 1")
+    );
+    assert_eq!(
+        vec!["comparing the Array [...] with a Boolean is always false, use contains([...], true) instead"],
+        messages("[amount, 2] = true")
+    );
+    assert_eq!(1, lint("ROLES = 'admin' and amount = 1").len());
+    assert_eq!(
+        2,
+        lint("roles = 'a' or if_then(true, roles <> 'b', false)").len()
+    );
+
+    let lints = lint("amount > 0 and roles = 'admin'");
+    assert_eq!("array_scalar_equality", lints[0].code);
+    assert_eq!(Some(15), lints[0].span.map(|span| span.start));
+    assert_eq!(Some(30), lints[0].span.map(|span| span.end));
+
+    // negative
+    assert!(lint("roles = other_roles").is_empty());
+    assert!(lint("roles = ['admin']").is_empty());
+    assert!(lint("roles = []").is_empty());
+    assert!(lint("unknown = 'admin'").is_empty());
+    assert!(lint("role = 'admin'").is_empty());
+    assert!(lint("roles = role").is_empty());
+    assert!(lint("contains(roles, 'admin')").is_empty());
+    assert!(lint("roles > 'admin'").is_empty());
+    assert!(lint("roles + ['x'] = 'admin'").is_empty()); // the type of `+` is not known
+
+    // without spans
+    let ast = compile("roles = 'admin'").unwrap();
+    assert_eq!(None, lint_array_scalar_equality(&env, &ast, None)[0].span);
+}
+
+#[test]
+fn call_guard() {
+    use std::sync::{Arc, Mutex};
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("flag", Value::Boolean(false));
+    env.add_variable("name", Value::String(String::from("A")));
+    env.add_set_var("regions", vec![Value::String(String::from("DE"))]);
+
+    // records every guarded call and denies `lowercase`
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = calls.clone();
+    let restrictive = ExecutionOptions {
+        call_guard: Some(CallGuard::new(move |name, params| {
+            recorded.lock().unwrap().push(format!("{name}{params:?}"));
+
+            match name {
+                "lowercase" => Err(String::from("interns may not call lowercase")),
+                _ => Ok(()),
+            }
+        })),
+        ..Default::default()
+    };
+    let permissive = ExecutionOptions {
+        call_guard: Some(CallGuard::new(|_, _| Ok(()))),
+        ..Default::default()
+    };
+    let denied = |name: &str| {
+        Err(Error::FunctionCallDenied {
+            name: name.to_string(),
+            reason: String::from("interns may not call lowercase"),
+        })
+    };
+
+    let cases = [
+        "lowercase(name) = 'a'",
+        "if_then(not flag, lowercase(name), 'b') = 'a'",
+        "try(lowercase(name), 'fallback') = 'a'",
+        "max(1, length(lowercase(name))) = 1",
+        "[lowercase(name)] = ['a']",
+        "not flag and lowercase(name) = 'a'",
+        "unknown <> lowercase(name)",
+        "LowerCase(name) = 'a'",
+    ];
+
+    for script in cases {
+        let mut ast = compile(script).unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(
+                Ok(Value::Boolean(true)),
+                execute_with_options(&env, &ast, permissive.clone()),
+                "{script}"
+            );
+            let name = if script.starts_with("LowerCase") {
+                "LowerCase"
+            } else {
+                "lowercase"
+            };
+            assert_eq!(
+                denied(name),
+                execute_with_options(&env, &ast, restrictive.clone()),
+                "{script}"
+            );
+
+            optimize(&env, &mut ast).unwrap();
+        }
+    }
+
+    // skipped calls never reach the guard
+    let short_circuits = [
+        "not (flag and lowercase(name) = 'a')",
+        "not flag or lowercase(name) = 'a'",
+        "flag implies lowercase(name) = 'a'",
+        "if_then(flag, lowercase(name), 'b') = 'b'",
+        "not if_then(flag, lowercase(name) = 'a')",
+        "try(upper, lowercase(name)) <> 'a' or true",
+        "not (1 > 2 > length(lowercase(name)))",
+    ];
+
+    for script in short_circuits {
+        let mut ast = compile(script).unwrap();
+        optimize(&env, &mut ast).unwrap();
+        calls.lock().unwrap().clear();
+
+        let result = execute_with_options(&env, &ast, restrictive.clone());
+
+        if script.starts_with("try") {
+            // the fallback is evaluated, but the call is still denied
+            assert_eq!(denied("lowercase"), result, "{script}");
+        } else {
+            assert_eq!(Ok(Value::Boolean(true)), result, "{script}");
+            assert!(
+                !calls
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .any(|c| c.starts_with("lowercase")),
+                "{script}"
+            );
+        }
+    }
+
+    // set-backed variables are passed to the guard like any other array
+    let ast = compile("contains(regions, 'DE')").unwrap();
+    calls.lock().unwrap().clear();
+    assert_eq!(
+        Ok(Value::Boolean(true)),
+        execute_with_options(&env, &ast, restrictive.clone())
+    );
+    assert_eq!(
+        vec![String::from(
+            "contains[Array([String(\"DE\")]), String(\"DE\")]"
+        )],
+        *calls.lock().unwrap()
+    );
+}
+
+#[test]
+fn max_depth() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let options = |max_depth| ExecutionOptions {
+        max_depth,
+        ..Default::default()
+    };
+    let ast = compile("-(-(-1))").unwrap();
+
+    assert_eq!(
+        Ok(Value::Number(-1.0)),
+        execute_with_options(&env, &ast, options(4))
+    );
+    assert_eq!(
+        Err(Error::RecursionLimitExceeded(3)),
+        execute_with_options(&env, &ast, options(3))
+    );
+
+    // the limit is not caught by `try`
+    let ast = compile("try(-(-(-1)), 0)").unwrap();
+    assert_eq!(
+        Err(Error::RecursionLimitExceeded(4)),
+        execute_with_options(&env, &ast, options(4))
+    );
+}
+
+#[test]
+fn deep_expression_fails_gracefully() {
+    let env = StaticEnvironment::default();
+    let negated = |depth: usize| {
+        (0..depth).fold(
+            Expression::Literal {
+                value: Value::Number(1.0),
+            },
+            |right, _| Expression::Unary {
+                right: Box::new(right),
+                operator: Operator::Minus,
+            },
+        )
+    };
+
+    let ast = negated(DEFAULT_MAX_DEPTH - 1);
+    assert_eq!(Ok(Value::Number(-1.0)), execute(&env, &ast));
+
+    let ast = negated(DEFAULT_MAX_DEPTH);
+    assert_eq!(
+        Err(Error::RecursionLimitExceeded(DEFAULT_MAX_DEPTH)),
+        slac::execute(&env, &ast)
+    );
+    assert_eq!(
+        Err(Error::RecursionLimitExceeded(DEFAULT_MAX_DEPTH)),
+        execute_flat(&env, &FlatExpression::from(&ast))
+    );
+
+    // would overflow the stack without the limit
+    let ast = negated(20_000);
+    assert_eq!(
+        Err(Error::RecursionLimitExceeded(DEFAULT_MAX_DEPTH)),
+        slac::execute(&env, &ast)
+    );
+}
@@ -0,0 +1,77 @@
+// The core of SLAC must work with every combination of features, run with
+// `cargo test --no-default-features` to check a build without any optional
+// dependency.
+use slac::{
+    check_variables_and_functions, compile, execute, optimize, stdlib::extend_environment, Error,
+    StaticEnvironment, Value,
+};
+
+#[test]
+fn compile_validate_optimize_execute() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("price", Value::Number(12.0));
+
+    let mut ast = compile("max(price, 10) * 2 > 20 and contains(lowercase('ABC'), 'b')").unwrap();
+    check_variables_and_functions(&env, &ast).unwrap();
+    optimize(&env, &mut ast).unwrap();
+
+    assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+}
+
+#[test]
+fn optional_functions_follow_features() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let cases = [
+        ("date_to_string", cfg!(feature = "chrono")),
+        ("now", cfg!(feature = "chrono")),
+        ("re_is_match", cfg!(feature = "regex")),
+        ("normalize", cfg!(feature = "unicode")),
+        ("same_text_normalized", cfg!(feature = "unicode")),
+        ("random", cfg!(feature = "random")),
+        ("choice", cfg!(feature = "random")),
+        ("sha256", cfg!(feature = "hash")),
+    ];
+
+    for (name, expected) in cases {
+        let exists = env.list_functions().iter().any(|func| func.name == name);
+
+        assert_eq!(expected, exists, "{name}");
+    }
+}
+
+#[test]
+fn errors_do_not_depend_on_features() {
+    let errors = [
+        (
+            Error::InvalidDebugBundle(String::from("missing field `ast`")),
+            "invalid debug bundle: missing field `ast`",
+        ),
+        (
+            Error::ReplayMismatch(Value::Number(1.0), Value::Number(2.0)),
+            "replay returned \"2\" but \"1\" was recorded",
+        ),
+        (
+            Error::UnsupportedSchemaVersion(2),
+            "unsupported schema version 2, the latest version is 1",
+        ),
+        (
+            Error::InvalidSerializedExpression(String::from("missing field `ast` at `ast`")),
+            "invalid serialized expression: missing field `ast` at `ast`",
+        ),
+        (
+            Error::UnsupportedVersion(2),
+            "unsupported binary format version 2",
+        ),
+        (
+            Error::InvalidBinary(String::from("trailing bytes at byte 3")),
+            "invalid binary expression: trailing bytes at byte 3",
+        ),
+    ];
+
+    for (error, message) in errors {
+        assert_eq!(message, error.to_string());
+    }
+}
@@ -0,0 +1,424 @@
+#[cfg(feature = "serde")]
+mod test {
+    use std::collections::BTreeMap;
+
+    use slac::{compile, optimize, stdlib::extend_environment, Expression, StaticEnvironment};
+
+    const GOLDEN_FILE: &str = "tests/optimizer_corpus.json";
+
+    const CORPUS: &[&str] = &[
+        "1 + 2 * 3",
+        "-(4 - 6) * 2",
+        "not (1 > 2)",
+        "price * (1 + 19 / 100)",
+        "(1 + 2) * price + (3 * 4) * price",
+        "[1 + 1, 'a' + 'b', [2 * 3, price]]",
+        "[1, 2, 3] + [4]",
+        "1 < 2 <= 2 < 3",
+        "1 < price < 2 + 3",
+        "max(1, 2) + min(3, 4)",
+        "max(price, 1 + 2)",
+        "random(1 + 1) > 0",
+        "lowercase('ABC') = 'abc' and price > 0",
+        "if_then(true, 1, 2)",
+        "if_then(false, 1, 2 + 3)",
+        "if_then(price > 1, 1 + 1, 2 * 2)",
+        "if_then(price > 1, 'a' + 'b')",
+        "if_then(price > 1, max(1, 2))",
+        "if_then(price > 1, price)",
+        "if_then(true, if_then(false, 1, 2), 3)",
+        "if_then(if_then(price, true, false), 1, 2)",
+        "if_then(1 > 2, price, if_then(price, 1 + 2))",
+        "if_then(true, 1, 1 - 'a')",
+        "if_then(max(1, 2) > 1, 'yes', 'no')",
+        "if_then(price, if_then(price, if_then(true, 1 + 1)))",
+        "try(1 - 'a', 5)",
+        "try(1 + 2, 5)",
+        "try(price, 1 + 2)",
+        "arr or [] ~= [1, 2]",
+        "n implies 0 / ''",
+        "missing and [1, 2] div 'a'",
+        "try(try(1 - 'a', 1 - 'b'), 3 * 3)",
+        "try(if_then(true, 1 - 'a', 2), 7)",
+        "try(price + (1 - 'a'), 2)",
+        "price and true",
+        "true xor price",
+        "contains('abc', 'b') and contains(price, 1)",
+        "between(2, 1, 3) or price",
+        "1 - 'a'",
+        "price + (1 - 'a')",
+        "1 < 3 > 2",
+        "price = '' or [] = []",
+        "if_then(true, 1) + if_then(false, 1)",
+        "if true then 1 + 1 else price",
+        "if price then 1 + 1 else 2 * 2",
+        "if 1 > 2 then price else if true then 'a' + 'b' else price",
+        "[10, 20, 30][1 + 1]",
+        "price[1 + 1]",
+        "[price, 2][0]",
+        "[10, 20][5]",
+        "'b' in 'a' + 'b'",
+        "2 * 2 in [1, 4, price]",
+        "price in [1, 2]",
+        "1 in 2",
+    ];
+
+    fn optimized(script: &str) -> String {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        let mut ast = match compile(script) {
+            Ok(ast) => ast,
+            Err(error) => return format!("compile error: {error}"),
+        };
+
+        match optimize(&env, &mut ast) {
+            Ok(()) => serde_json::to_string(&ast).unwrap(),
+            Err(error) => format!("error: {error}"),
+        }
+    }
+
+    /// Compares the optimized ASTs of the corpus against the golden file.
+    /// Run with `SLAC_UPDATE_OPTIMIZER=1` to update the golden file after an
+    /// intended change.
+    #[test]
+    fn optimizer_corpus() {
+        let results: BTreeMap<&str, String> = CORPUS
+            .iter()
+            .map(|script| (*script, optimized(script)))
+            .collect();
+
+        if std::env::var_os("SLAC_UPDATE_OPTIMIZER").is_some() {
+            let json = serde_json::to_string_pretty(&results).unwrap() + "\n";
+            std::fs::write(GOLDEN_FILE, json).unwrap();
+        }
+
+        let golden = std::fs::read_to_string(GOLDEN_FILE).unwrap();
+        let golden: BTreeMap<String, String> = serde_json::from_str(&golden).unwrap();
+
+        for (script, result) in &results {
+            assert_eq!(golden.get(*script), Some(result), "{script}");
+        }
+        assert_eq!(golden.len(), results.len());
+
+        // the optimized AST is still a valid AST
+        for result in results.values().filter(|result| result.starts_with('{')) {
+            assert!(
+                serde_json::from_str::<Expression>(result).is_ok(),
+                "{result}"
+            );
+        }
+    }
+}
+
+/// Builds random but deterministic scripts to check properties of the optimizer.
+struct ScriptGenerator {
+    state: u64,
+}
+
+impl ScriptGenerator {
+    fn next(&mut self, bound: usize) -> usize {
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        usize::try_from(self.state % bound as u64).unwrap()
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.next(items.len())]
+    }
+
+    fn script(&mut self, depth: usize) -> String {
+        const ATOMS: &[&str] = &[
+            "1", "2.5", "0", "-3", "'a'", "''", "true", "false", "[]", "[1, 2]", "price", "flag",
+            "name",
+        ];
+        const BINARY: &[&str] = &[
+            "+", "-", "*", "/", "div", "mod", "=", "<>", "<", ">=", "and", "or", "xor", "implies",
+            "in",
+        ];
+        const CALLS: &[&str] = &["max", "min", "lowercase", "contains", "random", "float"];
+
+        if depth == 0 {
+            return String::from(self.pick(ATOMS));
+        }
+
+        let depth = depth - 1;
+
+        match self.next(9) {
+            0 => String::from(self.pick(ATOMS)),
+            1 => format!("not {}", self.script(depth)),
+            2 => format!("-({})", self.script(depth)),
+            3 | 4 => format!(
+                "({} {} {})",
+                self.script(depth),
+                self.pick(BINARY),
+                self.script(depth)
+            ),
+            5 => format!(
+                "({} < {} <= {})",
+                self.script(depth),
+                self.script(depth),
+                self.script(depth)
+            ),
+            6 => match self.next(2) {
+                0 => format!("if_then({}, {})", self.script(depth), self.script(depth)),
+                _ => format!(
+                    "if_then({}, {}, {})",
+                    self.script(depth),
+                    self.script(depth),
+                    self.script(depth)
+                ),
+            },
+            7 => format!("try({}, {})", self.script(depth), self.script(depth)),
+            _ => format!(
+                "{}({}, {})",
+                self.pick(CALLS),
+                self.script(depth),
+                self.script(depth)
+            ),
+        }
+    }
+}
+
+/// Environments with random but deterministic values for the variables
+/// `price`, `flag` and `name` of the generated scripts, any of them may stay
+/// undefined.
+fn seeded_environments() -> Vec<slac::StaticEnvironment> {
+    use slac::{stdlib::extend_environment, StaticEnvironment, Value};
+
+    let values = [
+        Value::Number(12.5),
+        Value::Number(0.0),
+        Value::Boolean(true),
+        Value::Boolean(false),
+        Value::String(String::from("a")),
+        Value::String(String::new()),
+        Value::Null,
+        Value::Array(vec![Value::Number(1.0)]),
+    ];
+
+    let mut seed = ScriptGenerator {
+        state: 0xd1b5_4a32_d192_ed03,
+    };
+
+    (0..12)
+        .map(|_| {
+            let mut env = StaticEnvironment::default();
+            extend_environment(&mut env);
+
+            for name in ["price", "flag", "name"] {
+                let index = seed.next(values.len() + 1);
+
+                if let Some(value) = values.get(index) {
+                    env.add_variable(name, value.clone());
+                }
+            }
+
+            env
+        })
+        .collect()
+}
+
+/// Printing a compiled AST back to source must compile to the same AST, the
+/// generated scripts put every operation into parentheses.
+#[test]
+fn to_source_reads_back() {
+    use slac::compile;
+
+    let mut generator = ScriptGenerator {
+        state: 0x5851_f42d_4c95_7f2d,
+    };
+
+    for _ in 0..2000 {
+        let script = generator.script(5);
+        let ast = compile(&script).unwrap();
+        let source = ast.to_source();
+
+        assert_eq!(Ok(ast), compile(&source), "{script} printed as {source}");
+    }
+}
+
+/// Optimizing an optimized tree again must not change it.
+#[test]
+fn optimize_is_idempotent() {
+    use slac::{compile, optimize, stdlib::extend_environment, StaticEnvironment};
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    let mut generator = ScriptGenerator {
+        state: 0x2545_f491_4f6c_dd1d,
+    };
+    let mut optimized = 0;
+
+    for _ in 0..2000 {
+        let script = generator.script(4);
+        let mut ast = compile(&script).unwrap();
+
+        if optimize(&env, &mut ast).is_err() {
+            continue; // a constant error is the same on every run
+        }
+
+        // NaN literals are never equal, so the trees are compared by their debug output
+        let once = format!("{ast:?}");
+        optimize(&env, &mut ast).unwrap();
+        assert_eq!(once, format!("{ast:?}"), "{script}");
+
+        optimized += 1;
+    }
+
+    assert!(
+        optimized > 500,
+        "only {optimized} scripts could be optimized"
+    );
+}
+
+/// Executing the flat representation and the compiled program of an AST must
+/// give the same result as the boxed `Expression`, before and after optimizing it.
+#[test]
+fn flat_expression_matches_expression() {
+    use slac::{
+        compile, execute, execute_compiled, execute_flat, optimize, stdlib::extend_environment,
+        CompiledProgram, FlatExpression, StaticEnvironment, Value,
+    };
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("price", Value::Number(12.5));
+    env.add_variable("flag", Value::Boolean(true));
+    // `name` stays undefined
+
+    let mut generator = ScriptGenerator {
+        state: 0x9e37_79b9_7f4a_7c15,
+    };
+
+    for _ in 0..2000 {
+        let script = generator.script(5);
+
+        if script.contains("random") {
+            continue; // two runs never agree
+        }
+
+        let mut ast = compile(&script).unwrap();
+
+        for _ in 0..2 {
+            let flat = FlatExpression::from(&ast);
+            let result = format!("{:?}", execute(&env, &ast));
+
+            assert_eq!(
+                result,
+                format!("{:?}", execute_flat(&env, &flat)),
+                "{script}"
+            );
+            assert_eq!(
+                result,
+                format!("{:?}", execute_compiled(&env, &CompiledProgram::from(&ast))),
+                "{script}"
+            );
+
+            if optimize(&env, &mut ast).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Normalizing an AST must not change its result in any of a set of seeded
+/// environments, and normalizing it again must not change it.
+#[test]
+fn normalize_keeps_results() {
+    use slac::{compile, execute, optimizer::normalize};
+
+    let environments = seeded_environments();
+
+    let mut generator = ScriptGenerator {
+        state: 0x6a09_e667_f3bc_c909,
+    };
+    let mut rewritten = 0;
+
+    for _ in 0..2000 {
+        let script = generator.script(5);
+
+        if script.contains("random") {
+            continue; // two runs never agree
+        }
+
+        let ast = compile(&script).unwrap();
+        let mut normalized = ast.clone();
+        normalize(&mut normalized);
+
+        // NaN results are never equal, so they are compared by their debug output
+        for env in &environments {
+            assert_eq!(
+                format!("{:?}", execute(env, &ast)),
+                format!("{:?}", execute(env, &normalized)),
+                "{script} normalized to {}",
+                normalized.to_source()
+            );
+        }
+
+        let once = format!("{normalized:?}");
+        normalize(&mut normalized);
+        assert_eq!(once, format!("{normalized:?}"), "{script}");
+
+        if normalized != ast {
+            rewritten += 1;
+        }
+    }
+
+    assert!(rewritten > 200, "only {rewritten} scripts were rewritten");
+}
+
+/// Simplifying identities must not change the result of an AST in any of a set
+/// of seeded environments.
+#[test]
+fn simplify_identities_keeps_results() {
+    use slac::{compile, execute, optimizer::simplify_identities, Value};
+
+    let environments = seeded_environments();
+
+    let mut generator = ScriptGenerator {
+        state: 0xbb67_ae85_84ca_a73b,
+    };
+    let mut rewritten = 0;
+
+    for _ in 0..4000 {
+        let script = generator.script(5);
+
+        if script.contains("random") {
+            continue; // two runs never agree
+        }
+
+        let ast = compile(&script).unwrap();
+        let mut simplified = ast.clone();
+        simplify_identities(&mut simplified);
+
+        for env in &environments {
+            let expected = execute(env, &ast);
+            let result = execute(env, &simplified);
+
+            // the sign of a zero may change, NaN results are compared by their debug output
+            let same = match (&expected, &result) {
+                (Ok(Value::Number(expected)), Ok(Value::Number(result))) => {
+                    expected == result || (expected.is_nan() && result.is_nan())
+                }
+                _ => format!("{expected:?}") == format!("{result:?}"),
+            };
+
+            assert!(
+                same,
+                "{script} simplified to {}: {expected:?} <> {result:?}",
+                simplified.to_source()
+            );
+        }
+
+        if simplified != ast {
+            rewritten += 1;
+        }
+    }
+
+    assert!(rewritten > 50, "only {rewritten} scripts were simplified");
+}
@@ -0,0 +1,97 @@
+use slac::{
+    compile, execute,
+    stdlib::{extend_environment, max_output_len, set_max_output_len, NativeError, STRING_OFFSET},
+    Error, StaticEnvironment, Value,
+};
+
+const LIMIT: usize = 10;
+
+/// The limit can only be set once per process, all tests share the same limit.
+fn execute_limited(script: &str) -> slac::Result<Value> {
+    let _ = set_max_output_len(LIMIT);
+    assert_eq!(LIMIT, max_output_len());
+
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+
+    execute(&env, &compile(script)?)
+}
+
+/// The string index of the zero-based `position`, depending on the
+/// `zero_based_strings` feature.
+fn index(position: usize) -> f64 {
+    position as f64 + STRING_OFFSET
+}
+
+#[test]
+fn refuse_oversized_outputs() {
+    let cases = [
+        (format!("insert('abcdef', 'ghijk', {})", index(0)), 11),
+        (
+            String::from("insert([1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 11, 0)"),
+            11,
+        ),
+        (String::from("replace('aaaa', 'a', 'bbb')"), 12),
+        (String::from("replace('abc', '', '--')"), 11),
+        (format!("copy('abcdefghijkl', {}, 20)", index(0)), 12),
+        (
+            String::from("copy([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], 0, 11)"),
+            11,
+        ),
+        (String::from("split('a,b,c,d,e,f,g,h,i,j,k', ',')"), 11),
+        (String::from("split_csv('a;b;c;d;e;f;g;h;i;j;k')"), 11),
+        (String::from("split_csv('a;\"b\";c;d;e;f;g;h;i;j;k')"), 11),
+    ];
+
+    for (script, requested) in cases {
+        let Err(Error::NativeFunctionError(_, error)) = execute_limited(&script) else {
+            panic!("{script} did not fail");
+        };
+
+        assert_eq!(
+            NativeError::OutputTooLarge {
+                limit: LIMIT,
+                requested
+            },
+            error,
+            "{script}"
+        );
+    }
+}
+
+#[test]
+fn accept_outputs_within_limit() {
+    let cases = [
+        (
+            format!("insert('abcde', 'fghij', {})", index(0)),
+            "'fghijabcde'",
+        ),
+        (
+            String::from("replace('aaaaaaaaaaaa', 'aa', 'b')"),
+            "'bbbbbb'",
+        ),
+        (
+            format!("copy('abcdefghijkl', {}, 10)", index(2)),
+            "'cdefghijkl'",
+        ),
+        (String::from("split('a,b,c', ',')"), "['a', 'b', 'c']"),
+        (String::from("split_csv('a;\"b;c\"')"), "['a', 'b;c']"),
+        // quoted separators do not count towards the limit
+        (
+            String::from("split_csv('a;\"b;c;d;e;f;g;h;i;j;k\"')"),
+            "['a', 'b;c;d;e;f;g;h;i;j;k']",
+        ),
+        (
+            String::from("tokenize('a \"b c d e f g h i j k\"')"),
+            "['a', 'b c d e f g h i j k']",
+        ),
+    ];
+
+    for (script, expected) in cases {
+        assert_eq!(
+            execute_limited(expected),
+            execute_limited(&script),
+            "{script}"
+        );
+    }
+}
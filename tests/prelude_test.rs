@@ -0,0 +1,31 @@
+// Only imports from the prelude, to make sure it covers the common use cases.
+use slac::prelude::*;
+
+fn prepare(script: &str) -> Result<(StaticEnvironment, Expression)> {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("price", Value::Number(12.0));
+
+    let mut ast = compile(script)?;
+    check_variables_and_functions(&env, &ast)?;
+    check_boolean_result(&ast)?;
+    optimize(&env, &mut ast)?;
+
+    Ok((env, ast))
+}
+
+#[test]
+fn prelude_only() {
+    let (env, ast) = prepare("max(price, 10) > 11").unwrap();
+    assert_eq!(Ok(Value::Boolean(true)), execute(&env, &ast));
+    assert!(env.variable_exists("price"));
+
+    assert!(matches!(
+        prepare("unknown > 1"),
+        Err(Error::MissingVariable(_))
+    ));
+
+    // the two parameter form of `Result` is still available
+    let parsed: Result<f64, std::num::ParseFloatError> = "1.5".parse();
+    assert_eq!(Ok(1.5), parsed);
+}
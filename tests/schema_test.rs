@@ -0,0 +1,142 @@
+#[cfg(feature = "serde")]
+mod test {
+    use slac::{
+        compile, deserialize_expression, serialize_expression, Error, Expression, Operator,
+        SerializedExpression, Value, SCHEMA_VERSION,
+    };
+
+    const V0: &str = include_str!("schema/v0.json");
+    const V1: &str = include_str!("schema/v1.json");
+
+    /// The expression stored in the fixtures, it contains every kind of
+    /// `Expression` and `Value`.
+    fn fixture() -> Expression {
+        let script = "if not (a is empty) then 1 < b <= 3 \
+                      else max(c[1], [1, 'Grüße 🦀', true]) + -2.5 ~= 3";
+
+        Expression::Binary {
+            left: Box::new(compile(script).unwrap()),
+            right: Box::new(Expression::Literal {
+                value: Value::Array(vec![
+                    Value::Null,
+                    Value::Boolean(false),
+                    Value::Number(f64::INFINITY),
+                    Value::String(String::from("x")),
+                ]),
+            }),
+            operator: Operator::And,
+        }
+    }
+
+    fn invalid(message: &str) -> Result<Expression, Error> {
+        Err(Error::InvalidSerializedExpression(String::from(message)))
+    }
+
+    #[test]
+    fn load_fixtures() {
+        assert_eq!(Ok(fixture()), deserialize_expression(V1));
+        assert_eq!(Ok(fixture()), deserialize_expression(V0));
+    }
+
+    #[test]
+    fn save_current_version() {
+        let json = serialize_expression(&fixture()).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(V1).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&json).unwrap()
+        );
+        assert_eq!(
+            Ok(SerializedExpression {
+                version: SCHEMA_VERSION,
+                ast: fixture()
+            }),
+            serde_json::from_str(&json).map_err(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        for script in ["a", "-1", "[]", "f(x)[0] or not y", "if a then b else c"] {
+            let ast = compile(script).unwrap();
+            let json = serialize_expression(&ast).unwrap();
+
+            assert_eq!(Ok(ast), deserialize_expression(&json), "{script}");
+        }
+    }
+
+    #[test]
+    fn reject_unknown_versions() {
+        let ast = r#"{"type": "variable", "name": "a"}"#;
+
+        for version in [0, 2, 100] {
+            assert_eq!(
+                Err(Error::UnsupportedSchemaVersion(version)),
+                deserialize_expression(&format!(r#"{{"version": {version}, "ast": {ast}}}"#))
+            );
+        }
+
+        assert_eq!(
+            Err(Error::UnsupportedSchemaVersion(u32::MAX)),
+            deserialize_expression(&format!(r#"{{"version": 99999999999, "ast": {ast}}}"#))
+        );
+        assert_eq!(
+            "unsupported schema version 2, the latest version is 1",
+            Error::UnsupportedSchemaVersion(2).to_string()
+        );
+    }
+
+    #[test]
+    fn name_the_invalid_node() {
+        assert_eq!(
+            invalid(
+                "unknown variant `plus`, expected one of `unary`, `binary`, `ternary`, \
+                 `chain`, `array`, `literal`, `variable`, `call`, `index` at `ast.left`"
+            ),
+            deserialize_expression(
+                r#"{"version": 1, "ast": {
+                    "type": "binary",
+                    "left": {"type": "plus"},
+                    "right": {"type": "variable", "name": "a"},
+                    "operator": "+"
+                }}"#
+            )
+        );
+        assert_eq!(
+            invalid("missing field `name` at `ast.params[1]`"),
+            deserialize_expression(
+                r#"{"type": "call", "name": "max", "params": [
+                    {"type": "literal", "value": 1},
+                    {"type": "variable"}
+                ]}"#
+            )
+        );
+        assert!(matches!(
+            deserialize_expression(
+                r#"{"version": 1, "ast": {
+                    "type": "unary",
+                    "right": {"type": "literal", "value": true},
+                    "operator": "power"
+                }}"#
+            ),
+            Err(Error::InvalidSerializedExpression(message))
+                if message.starts_with("unknown variant `power`") && message.ends_with("at `ast`")
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_envelopes() {
+        assert_eq!(
+            invalid("missing field `ast` at `ast`"),
+            deserialize_expression(r#"{"version": 1}"#)
+        );
+        assert_eq!(
+            invalid("expected an unsigned integer at `version`"),
+            deserialize_expression(r#"{"version": "1", "ast": null}"#)
+        );
+        assert_eq!(
+            invalid("expected value at line 1 column 1"),
+            deserialize_expression("version: 1")
+        );
+    }
+}
@@ -3,10 +3,11 @@ mod test {
 
     use minify::json::minify;
     use slac::{
-        check_variables_and_functions, compile,
+        check_variables_and_functions, compile, execute,
         function::{Arity, Function},
-        stdlib::NativeResult,
-        Expression, Operator, StaticEnvironment,
+        replay,
+        stdlib::{extend_environment, NativeResult},
+        DebugBundle, Error, Expression, FlatExpression, Operator, StaticEnvironment, Value,
     };
 
     fn test_serialize(script: &str, expected: &str) {
@@ -70,7 +71,7 @@ mod test {
             "type": "literal",
             "value": 2.0
           },
-          "operator": "plus"
+          "operator": "+"
         }"#;
 
         test_json("1+ 2", expected);
@@ -99,13 +100,107 @@ mod test {
             "type": "literal",
             "value": 5.0
           },
-          "operator": "greater"
+          "operator": ">"
         }
         "#;
 
         test_json("max(10, 20) > 5", expected);
     }
 
+    #[test]
+    fn serialize_is_empty() {
+        let expected = r#"
+        {
+          "type": "binary",
+          "left": {
+            "type": "unary",
+            "right": {
+              "type": "variable",
+              "name": "some_var"
+            },
+            "operator": "is empty"
+          },
+          "right": {
+            "type": "unary",
+            "right": {
+              "type": "literal",
+              "value": "text"
+            },
+            "operator": "is not empty"
+          },
+          "operator": "or"
+        }
+        "#;
+
+        test_json("some_var is empty or 'text' is not empty", expected);
+    }
+
+    #[test]
+    fn serialize_if_then_else() {
+        let expected = r#"
+        {
+          "type": "ternary",
+          "left": {
+            "type": "variable",
+            "name": "some_var"
+          },
+          "middle": {
+            "type": "literal",
+            "value": 1.0
+          },
+          "right": {
+            "type": "ternary",
+            "left": {
+              "type": "literal",
+              "value": false
+            },
+            "middle": {
+              "type": "literal",
+              "value": 2.0
+            },
+            "right": {
+              "type": "literal",
+              "value": 3.0
+            },
+            "operator": "if"
+          },
+          "operator": "if"
+        }
+        "#;
+
+        test_json("if some_var then 1 else if false then 2 else 3", expected);
+    }
+
+    #[test]
+    fn serialize_index() {
+        let expected = r#"
+        {
+          "type": "index",
+          "target": {
+            "type": "variable",
+            "name": "some_var"
+          },
+          "index": {
+            "type": "call",
+            "name": "max",
+            "params": [
+              {
+                "type": "literal",
+                "value": 1.0
+              },
+              {
+                "type": "literal",
+                "value": 2.0
+              }
+            ]
+          }
+        }
+        "#;
+
+        test_json("some_var[max(1, 2)]", expected);
+        test_roundtrip("[[1, 2], [3]][0][some_var]");
+    }
+
     #[test]
     fn full_syntax() {
         let script = r#"true and not false and // this comment should not appear in the ast
@@ -156,13 +251,13 @@ mod test {
                           "type": "literal",
                           "value": 20.0
                         },
-                        "operator": "plus"
+                        "operator": "+"
                       },
                       "right": {
                         "type": "literal",
                         "value": 30.0
                       },
-                      "operator": "minus"
+                      "operator": "-"
                     },
                     "right": {
                       "type": "binary",
@@ -176,15 +271,15 @@ mod test {
                           "type": "literal",
                           "value": 5.0
                         },
-                        "operator": "multiply"
+                        "operator": "*"
                       },
                       "right": {
                         "type": "literal",
                         "value": 25.0
                       },
-                      "operator": "divide"
+                      "operator": "/"
                     },
-                    "operator": "less"
+                    "operator": "<"
                   },
                   "operator": "and"
                 },
@@ -214,7 +309,7 @@ mod test {
                     },
                     "operator": "mod"
                   },
-                  "operator": "lessEqual"
+                  "operator": "<="
                 },
                 "operator": "and"
               },
@@ -247,7 +342,7 @@ mod test {
                   "type": "variable",
                   "name": "some_var"
                 },
-                "operator": "greater"
+                "operator": ">"
               },
               "operator": "or"
             },
@@ -261,7 +356,7 @@ mod test {
                 "type": "literal",
                 "value": 8.0
               },
-              "operator": "greaterEqual"
+              "operator": ">="
             },
             "operator": "or"
           },
@@ -277,7 +372,7 @@ mod test {
                 "type": "literal",
                 "value": 10.0
               },
-              "operator": "notEqual"
+              "operator": "<>"
             },
             "right": {
               "type": "binary",
@@ -291,13 +386,13 @@ mod test {
                   "type": "literal",
                   "value": "Pen"
                 },
-                "operator": "plus"
+                "operator": "+"
               },
               "right": {
                 "type": "literal",
                 "value": "ApplePen"
               },
-              "operator": "equal"
+              "operator": "="
             },
             "operator": "and"
           },
@@ -346,7 +441,7 @@ mod test {
             "type": "literal",
             "value": 0
           },
-          "operator": "minus"
+          "operator": "-"
         }"#;
 
         let expected = Expression::Binary {
@@ -363,4 +458,221 @@ mod test {
 
         assert_eq!(expected, ast);
     }
+
+    #[test]
+    fn null_literal() {
+        let expected = r#"
+        {
+          "type": "binary",
+          "left": {
+            "type": "variable",
+            "name": "some_var"
+          },
+          "right": {
+            "type": "literal",
+            "value": null
+          },
+          "operator": "="
+        }
+        "#;
+
+        test_json("some_var = null", expected);
+
+        let ast = serde_json::from_str::<Expression>(&minify(expected)).unwrap();
+        assert!(matches!(
+            ast,
+            Expression::Binary { right, .. }
+                if matches!(*right, Expression::Literal { value: slac::Value::Null })
+        ));
+    }
+
+    #[test]
+    fn non_finite_literals() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+
+        let mut ast = compile("[1 / 0, -1 / 0, 0 / 0]").unwrap();
+        slac::optimize(&env, &mut ast).unwrap();
+
+        let json = serde_json::to_string(&ast).unwrap();
+        assert!(json.contains(r#"{"number":"inf"}"#), "{json}");
+        assert!(json.contains(r#"{"number":"-inf"}"#), "{json}");
+        assert!(json.contains(r#"{"number":"NaN"}"#), "{json}");
+
+        let output = serde_json::from_str::<Expression>(&json).unwrap();
+        let Value::Array(values) = execute(&env, &output).unwrap() else {
+            panic!("expected an array");
+        };
+
+        assert_eq!(Value::Number(f64::INFINITY), values[0]);
+        assert_eq!(Value::Number(f64::NEG_INFINITY), values[1]);
+        assert!(matches!(values[2], Value::Number(v) if v.is_nan()));
+    }
+
+    #[test]
+    fn flat_expression_json() {
+        let ast = compile("if_then(price > 1, [1, 'a'], max(1 < 2 <= 3, not flag))").unwrap();
+        let flat = FlatExpression::from(&ast);
+
+        // a FlatExpression is serialized just like the Expression it was built from
+        let json = serde_json::to_string(&flat).unwrap();
+        assert_eq!(serde_json::to_string(&ast).unwrap(), json);
+        assert_eq!(flat, serde_json::from_str::<FlatExpression>(&json).unwrap());
+    }
+
+    #[test]
+    fn operator_names() {
+        let expected = [
+            "+",
+            "-",
+            "*",
+            "/",
+            ">",
+            ">=",
+            "<",
+            "<=",
+            "=",
+            "<>",
+            "and",
+            "or",
+            "xor",
+            "not",
+            "div",
+            "mod",
+            "is empty",
+            "is not empty",
+            "if",
+            "implies",
+            "iff",
+            "in",
+            "~=",
+        ];
+
+        assert_eq!(expected.len(), Operator::ALL.len());
+
+        for (operator, name) in Operator::ALL.iter().zip(expected) {
+            let json = format!("\"{name}\"");
+
+            assert_eq!(name, operator.to_string());
+            assert_eq!(json, serde_json::to_string(operator).unwrap());
+            assert_eq!(*operator, serde_json::from_str::<Operator>(&json).unwrap());
+        }
+    }
+
+    #[test]
+    fn legacy_operator_names() {
+        let legacy = [
+            "plus",
+            "minus",
+            "multiply",
+            "divide",
+            "greater",
+            "greaterEqual",
+            "less",
+            "lessEqual",
+            "equal",
+            "notEqual",
+            "and",
+            "or",
+            "xor",
+            "not",
+            "div",
+            "mod",
+            "isEmpty",
+            "isNotEmpty",
+            "ternaryCondition",
+        ];
+
+        for (operator, name) in Operator::ALL.iter().zip(legacy) {
+            let json = format!("\"{name}\"");
+            assert_eq!(*operator, serde_json::from_str::<Operator>(&json).unwrap());
+        }
+
+        assert!(serde_json::from_str::<Operator>("\"Plus\"").is_err());
+    }
+
+    fn capture(script: &str, env: &StaticEnvironment) -> DebugBundle {
+        let ast = compile(script).unwrap();
+
+        DebugBundle {
+            source: Some(script.to_string()),
+            result: execute(env, &ast).ok(),
+            variables: env.snapshot_variables(),
+            ast,
+        }
+    }
+
+    #[test]
+    fn debug_bundle_replay() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+        env.add_variable("price", Value::Number(19.99));
+        env.add_variable("name", Value::String(String::from("widget")));
+        env.add_variable("tags", Value::Array(vec![Value::Boolean(true)]));
+
+        let failing = capture("price + name", &env);
+        let succeeding = capture("max(price, 20) > 10 and tags = [true]", &env);
+
+        for bundle in [failing, succeeding] {
+            let json = bundle.save_json().unwrap();
+            let loaded = DebugBundle::load_json(&json).unwrap();
+            // the `nan` constant is never equal to itself, compare the saved form instead
+            assert_eq!(json, loaded.save_json().unwrap());
+
+            let mut replay_env = StaticEnvironment::default();
+            extend_environment(&mut replay_env);
+
+            assert_eq!(execute(&env, &bundle.ast), replay(&loaded, &mut replay_env));
+        }
+    }
+
+    #[test]
+    fn debug_bundle_failing_expression() {
+        let mut env = StaticEnvironment::default();
+        extend_environment(&mut env);
+        env.add_variable("price", Value::Number(19.99));
+        env.add_variable("name", Value::String(String::from("widget")));
+
+        let bundle = capture("price > 10 and int(name) > 0", &env);
+        let failure = execute(&env, &bundle.ast);
+
+        assert!(
+            matches!(&failure, Err(Error::NativeFunctionError(name, _)) if name == "int"),
+            "{failure:?}"
+        );
+        assert_eq!(None, bundle.result);
+
+        let json = bundle.save_json().unwrap();
+        let loaded = DebugBundle::load_json(&json).unwrap();
+
+        assert_eq!(json, loaded.save_json().unwrap());
+        assert_eq!(bundle.ast, loaded.ast);
+        assert_eq!(None, loaded.result);
+
+        let mut replay_env = StaticEnvironment::default();
+        extend_environment(&mut replay_env);
+
+        assert_eq!(failure, replay(&loaded, &mut replay_env));
+    }
+
+    #[test]
+    fn debug_bundle_mismatch() {
+        let mut env = StaticEnvironment::default();
+        env.add_variable("price", Value::Number(10.0));
+
+        let mut bundle = capture("price * 2", &env);
+        bundle.result = Some(Value::Number(21.0));
+
+        assert_eq!(
+            Err(Error::ReplayMismatch(
+                Value::Number(21.0),
+                Value::Number(20.0)
+            )),
+            replay(&bundle, &mut StaticEnvironment::default())
+        );
+        assert!(matches!(
+            DebugBundle::load_json("{}"),
+            Err(Error::InvalidDebugBundle(_))
+        ));
+    }
 }
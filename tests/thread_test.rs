@@ -0,0 +1,60 @@
+use std::thread;
+
+use slac::{
+    compile, execute, execute_compiled, stdlib::extend_environment, CompiledProgram, Expression,
+    OverlayEnvironment, ScopedEnvironment, StaticEnvironment, Value,
+};
+
+/// Fails to compile if one of the types can not be shared between threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<StaticEnvironment>();
+    assert_send_sync::<ScopedEnvironment<StaticEnvironment>>();
+    assert_send_sync::<OverlayEnvironment<StaticEnvironment>>();
+    assert_send_sync::<Expression>();
+    assert_send_sync::<CompiledProgram>();
+    assert_send_sync::<Value>();
+};
+
+#[test]
+fn execute_from_many_threads() {
+    let mut env = StaticEnvironment::default();
+    extend_environment(&mut env);
+    env.add_variable("limit", Value::Number(100.0));
+    env.add_set_var(
+        "codes",
+        (0..1_000).map(|code| Value::Number(f64::from(code))),
+    );
+
+    let ast = compile("contains(codes, code) and max(code * 2, 0) > limit").unwrap();
+    let program = CompiledProgram::from(&ast);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..8_u32)
+            .map(|worker| {
+                let (env, ast, program) = (&env, &ast, &program);
+
+                scope.spawn(move || {
+                    (0..500)
+                        .map(|row| {
+                            let code = f64::from(worker * 500 + row);
+                            let row =
+                                OverlayEnvironment::new(env).with_var("code", Value::Number(code));
+
+                            let result = execute(&row, ast).unwrap();
+                            assert_eq!(Ok(result.clone()), execute_compiled(&row, program));
+                            assert_eq!(Value::Boolean(code > 50.0 && code < 1_000.0), result);
+
+                            result
+                        })
+                        .filter(|result| *result == Value::Boolean(true))
+                        .count()
+                })
+            })
+            .collect();
+
+        let matches: usize = workers.into_iter().map(|w| w.join().unwrap()).sum();
+        assert_eq!(949, matches);
+    });
+}